@@ -201,6 +201,32 @@ impl ActivityIndicator {
                 })),
             });
         }
+        // Show the progress of files that are being loaded in large-file mode.
+        if let Some((project_path, load)) = self.project.read(cx).large_file_loads(cx).next() {
+            let percent_loaded = (load.bytes_loaded * 100)
+                .checked_div(load.total_bytes)
+                .unwrap_or(100)
+                .min(100);
+            return Some(Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_animation(
+                            "arrow-circle",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        )
+                        .into_any_element(),
+                ),
+                message: format!(
+                    "Loading {} ({}%)",
+                    project_path.path.to_string_lossy(),
+                    percent_loaded
+                ),
+                on_click: None,
+            });
+        }
+
         // Show any language server has pending activity.
         let mut pending_work = self.pending_language_server_work(cx);
         if let Some(PendingWork {
@@ -35,7 +35,10 @@ use language_model::{
 pub use patch::*;
 pub use prompts::PromptBuilder;
 use prompts::PromptLoadingParams;
-use semantic_index::{CloudEmbeddingProvider, SemanticDb};
+use semantic_index::{
+    CloudEmbeddingProvider, EmbeddingProvider, EmbeddingProviderSetting, LocalEmbeddingModel,
+    LocalEmbeddingProvider, SemanticDb, SemanticIndexSettings,
+};
 use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsStore};
 use slash_command::search_command::SearchSlashCommandFeatureFlag;
@@ -198,6 +201,7 @@ pub fn init(
     cx.set_global(Assistant::default());
     AssistantSettings::register(cx);
     SlashCommandSettings::register(cx);
+    semantic_index::init(cx);
 
     // TODO: remove this when 0.148.0 is released.
     if AssistantSettings::get_global(cx).using_outdated_settings_version {
@@ -212,29 +216,53 @@ pub fn init(
     cx.spawn(|mut cx| {
         let client = client.clone();
         async move {
-            let is_search_slash_command_enabled = cx
-                .update(|cx| cx.wait_for_flag::<SearchSlashCommandFeatureFlag>())?
-                .await;
-            let is_project_slash_command_enabled = cx
-                .update(|cx| cx.wait_for_flag::<ProjectSlashCommandFeatureFlag>())?
-                .await;
-
-            if !is_search_slash_command_enabled && !is_project_slash_command_enabled {
-                return Ok(());
-            }
-
-            let embedding_provider = CloudEmbeddingProvider::new(client.clone());
-            let semantic_index = SemanticDb::new(
-                paths::embeddings_dir().join("semantic-index-db.0.mdb"),
-                Arc::new(embedding_provider),
-                &mut cx,
-            )
-            .await?;
+            let embedding_provider_setting =
+                cx.update(|cx| SemanticIndexSettings::get_global(cx).embedding_provider)?;
+
+            // A local embedding model needs no account, so it doesn't wait on feature flags.
+            let (db_path, embedding_provider): (_, Arc<dyn EmbeddingProvider>) =
+                match embedding_provider_setting {
+                    EmbeddingProviderSetting::ZedDotDev => {
+                        let is_search_slash_command_enabled = cx
+                            .update(|cx| cx.wait_for_flag::<SearchSlashCommandFeatureFlag>())?
+                            .await;
+                        let is_project_slash_command_enabled = cx
+                            .update(|cx| cx.wait_for_flag::<ProjectSlashCommandFeatureFlag>())?
+                            .await;
+
+                        if !is_search_slash_command_enabled && !is_project_slash_command_enabled {
+                            return Ok(());
+                        }
+
+                        (
+                            paths::embeddings_dir().join("semantic-index-db.0.mdb"),
+                            Arc::new(CloudEmbeddingProvider::new(client.clone())),
+                        )
+                    }
+                    EmbeddingProviderSetting::Local => {
+                        let model = LocalEmbeddingModel::default();
+                        let provider = LocalEmbeddingProvider::new(
+                            model,
+                            client.http_client(),
+                            cx.background_executor().clone(),
+                        )
+                        .await?;
+                        // Embeddings from different models can't be compared, so
+                        // each model gets its own database.
+                        (
+                            paths::embeddings_dir()
+                                .join(format!("semantic-index-db.{}.0.mdb", model.id())),
+                            Arc::new(provider),
+                        )
+                    }
+                };
+
+            let semantic_index = SemanticDb::new(db_path, embedding_provider, &mut cx).await?;
 
             cx.update(|cx| cx.set_global(semantic_index))
         }
     })
-    .detach();
+    .detach_and_log_err(cx);
 
     context_store::init(&client.clone().into());
     prompt_library::init(cx);
@@ -387,6 +415,12 @@ fn register_slash_commands(prompt_builder: Option<Arc<PromptBuilder>>, cx: &mut
     cx.observe_global::<SettingsStore>(update_slash_commands_from_settings)
         .detach();
 
+    update_search_slash_command(cx);
+    cx.observe_global::<SemanticDb>(update_search_slash_command)
+        .detach();
+    cx.observe_global::<SettingsStore>(update_search_slash_command)
+        .detach();
+
     cx.observe_flag::<search_command::SearchSlashCommandFeatureFlag, _>({
         let slash_command_registry = slash_command_registry.clone();
         move |is_enabled, _cx| {
@@ -417,6 +451,20 @@ fn update_slash_commands_from_settings(cx: &mut AppContext) {
     }
 }
 
+/// Offers `/search` with a local embedding model once its index has loaded,
+/// without waiting for the feature flag.
+fn update_search_slash_command(cx: &mut AppContext) {
+    let slash_command_registry = SlashCommandRegistry::global(cx);
+    let is_local =
+        SemanticIndexSettings::get_global(cx).embedding_provider == EmbeddingProviderSetting::Local;
+
+    if is_local && cx.has_global::<SemanticDb>() {
+        slash_command_registry.register_command(search_command::SearchSlashCommand, true);
+    } else if !cx.has_flag::<SearchSlashCommandFeatureFlag>() {
+        slash_command_registry.unregister_command(search_command::SearchSlashCommand);
+    }
+}
+
 pub fn humanize_token_count(count: usize) -> String {
     match count {
         0..=999 => count.to_string(),
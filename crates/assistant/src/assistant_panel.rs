@@ -22,7 +22,7 @@ use crate::{
 };
 use anyhow::Result;
 use assistant_slash_command::{SlashCommand, SlashCommandOutputSection};
-use assistant_tool::ToolWorkingSet;
+use assistant_tool::{ActionLog, ToolWorkingSet};
 use client::{proto, zed_urls, Client, Status};
 use collections::{hash_map, BTreeSet, HashMap, HashSet};
 use editor::{
@@ -2063,7 +2063,8 @@ impl ContextEditor {
 
                 for tool_use in pending_tool_uses {
                     if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
+                        let action_log = cx.new_model(|_| ActionLog::new());
+                        let task = tool.run(tool_use.input, self.workspace.clone(), action_log, cx);
 
                         self.context.update(cx, |context, cx| {
                             context.insert_tool_output(tool_use.id.clone(), task, cx);
@@ -404,7 +404,7 @@ pub struct LanguageModelSelection {
 }
 
 fn providers_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
-    schemars::schema::SchemaObject {
+    let built_in_providers = schemars::schema::SchemaObject {
         enum_values: Some(vec![
             "anthropic".into(),
             "google".into(),
@@ -414,6 +414,21 @@ fn providers_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema:
             "copilot_chat".into(),
         ]),
         ..Default::default()
+    };
+    // OpenAI compatible servers are named in the `language_models` settings.
+    let openai_compatible_providers = schemars::schema::SchemaObject {
+        instance_type: Some(schemars::schema::InstanceType::String.into()),
+        ..Default::default()
+    };
+    schemars::schema::SchemaObject {
+        subschemas: Some(Box::new(schemars::schema::SubschemaValidation {
+            any_of: Some(vec![
+                built_in_providers.into(),
+                openai_compatible_providers.into(),
+            ]),
+            ..Default::default()
+        })),
+        ..Default::default()
     }
     .into()
 }
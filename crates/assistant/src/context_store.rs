@@ -892,6 +892,13 @@ impl ContextStore {
                     tool_working_set.remove(&tool_ids);
                 }
             }
+            context_server::manager::Event::SamplingRequested { request_id, .. } => {
+                // Contexts have no way to ask the user to approve sampling, so decline it.
+                context_server_manager.update(cx, |manager, _| {
+                    manager.take_sampling_request(*request_id);
+                });
+            }
+            context_server::manager::Event::ResourceUpdated { .. } => {}
         }
     }
 }
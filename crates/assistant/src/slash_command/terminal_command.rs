@@ -8,6 +8,7 @@ use assistant_slash_command::{
 };
 use gpui::{AppContext, Task, View, WeakView};
 use language::{BufferSnapshot, CodeLabel, LspAdapterDelegate};
+use terminal::shell_integration::CommandStatus;
 use terminal_view::{terminal_panel::TerminalPanel, TerminalView};
 use ui::prelude::*;
 use workspace::{dock::Panel, Workspace};
@@ -19,6 +20,7 @@ use super::create_label_for_command;
 pub(crate) struct TerminalSlashCommand;
 
 const LINE_COUNT_ARG: &str = "--line-count";
+const LAST_COMMAND_ARG: &str = "--last-command";
 
 impl SlashCommand for TerminalSlashCommand {
     fn name(&self) -> String {
@@ -26,7 +28,7 @@ impl SlashCommand for TerminalSlashCommand {
     }
 
     fn label(&self, cx: &AppContext) -> CodeLabel {
-        create_label_for_command("terminal", &[LINE_COUNT_ARG], cx)
+        create_label_for_command("terminal", &[LINE_COUNT_ARG, LAST_COMMAND_ARG], cx)
     }
 
     fn description(&self) -> String {
@@ -51,12 +53,21 @@ impl SlashCommand for TerminalSlashCommand {
 
     fn complete_argument(
         self: Arc<Self>,
-        _arguments: &[String],
+        arguments: &[String],
         _cancel: Arc<AtomicBool>,
         _workspace: Option<WeakView<Workspace>>,
         _cx: &mut WindowContext,
     ) -> Task<Result<Vec<ArgumentCompletion>>> {
-        Task::ready(Ok(Vec::new()))
+        let query = arguments.last().map(String::as_str).unwrap_or_default();
+        if arguments.len() > 1 || !LAST_COMMAND_ARG.starts_with(query) {
+            return Task::ready(Ok(Vec::new()));
+        }
+        Task::ready(Ok(vec![ArgumentCompletion {
+            label: LAST_COMMAND_ARG.into(),
+            new_text: LAST_COMMAND_ARG.to_string(),
+            after_completion: assistant_slash_command::AfterCompletion::Run,
+            replace_previous_arguments: false,
+        }]))
     }
 
     fn run(
@@ -76,20 +87,38 @@ impl SlashCommand for TerminalSlashCommand {
             return Task::ready(Err(anyhow::anyhow!("no active terminal")));
         };
 
-        let line_count = arguments
-            .get(0)
-            .and_then(|s| s.parse::<usize>().ok())
-            .unwrap_or(DEFAULT_CONTEXT_LINES);
-
-        let lines = active_terminal
-            .read(cx)
-            .model()
-            .read(cx)
-            .last_n_non_empty_lines(line_count);
-
+        let terminal = active_terminal.read(cx).model().clone();
         let mut text = String::new();
-        text.push_str("Terminal output:\n");
-        text.push_str(&lines.join("\n"));
+        if arguments
+            .iter()
+            .any(|argument| argument == LAST_COMMAND_ARG)
+        {
+            let Some(output) = terminal.update(cx, |terminal, _| terminal.last_command_output())
+            else {
+                return Task::ready(Err(anyhow::anyhow!(
+                    "the terminal's shell did not report any commands"
+                )));
+            };
+            let command = terminal
+                .read(cx)
+                .shell_commands()
+                .filter(|command| command.status != CommandStatus::Prompt)
+                .last()
+                .and_then(|command| command.command.clone());
+            match command {
+                Some(command) => text.push_str(&format!("Output of `{command}`:\n")),
+                None => text.push_str("Terminal output:\n"),
+            }
+            text.push_str(&output);
+        } else {
+            let line_count = arguments
+                .get(0)
+                .and_then(|s| s.parse::<usize>().ok())
+                .unwrap_or(DEFAULT_CONTEXT_LINES);
+            let lines = terminal.read(cx).last_n_non_empty_lines(line_count);
+            text.push_str("Terminal output:\n");
+            text.push_str(&lines.join("\n"));
+        }
         let range = 0..text.len();
 
         Task::ready(Ok(SlashCommandOutput {
@@ -1,20 +1,22 @@
 use std::sync::Arc;
 
-use assistant_tool::ToolWorkingSet;
+use assistant_tool::{ActionLog, ToolPermission, ToolSettings, ToolWorkingSet};
 use collections::HashMap;
 use gpui::{
     list, AnyElement, AppContext, Empty, ListAlignment, ListState, Model, StyleRefinement,
     Subscription, TextStyleRefinement, View, WeakView,
 };
 use language::LanguageRegistry;
-use language_model::Role;
+use language_model::{LanguageModelRegistry, LanguageModelToolUseId, Role};
 use markdown::{Markdown, MarkdownStyle};
 use settings::Settings as _;
 use theme::ThemeSettings;
-use ui::prelude::*;
+use ui::{prelude::*, Tooltip};
 use workspace::Workspace;
 
-use crate::thread::{MessageId, Thread, ThreadError, ThreadEvent};
+use crate::thread::{
+    MessageId, PendingToolUse, Thread, ThreadError, ThreadEvent, ToolUse, ToolUseStatus,
+};
 
 pub struct ActiveThread {
     workspace: WeakView<Workspace>,
@@ -174,24 +176,153 @@ impl ActiveThread {
                     .collect::<Vec<_>>();
 
                 for tool_use in pending_tool_uses {
-                    if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
+                    let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+                        let reason = format!("There is no tool named {}.", tool_use.name);
+                        self.thread.update(cx, |thread, cx| {
+                            thread.reject_tool_use(tool_use.id, reason, cx);
+                        });
+                        continue;
+                    };
 
+                    match ToolSettings::get_global(cx).permission(&tool) {
+                        ToolPermission::Always => self.run_tool(tool_use, cx),
+                        ToolPermission::Ask => self.thread.update(cx, |thread, cx| {
+                            thread.request_tool_confirmation(&tool_use.id, cx);
+                        }),
+                        ToolPermission::Never => {
+                            let reason =
+                                format!("The user does not allow the {} tool.", tool_use.name);
+                            self.thread.update(cx, |thread, cx| {
+                                thread.reject_tool_use(tool_use.id, reason, cx);
+                            });
+                        }
+                    }
+                }
+            }
+            ThreadEvent::ToolFinished { .. } => {
+                if self.thread.read(cx).all_tools_finished() {
+                    if let Some(model) = LanguageModelRegistry::read_global(cx).active_model() {
                         self.thread.update(cx, |thread, cx| {
-                            thread.insert_tool_output(
-                                tool_use.assistant_message_id,
-                                tool_use.id.clone(),
-                                task,
-                                cx,
-                            );
+                            thread.send_tool_results_to_model(model, cx);
                         });
                     }
                 }
             }
-            ThreadEvent::ToolFinished { .. } => {}
         }
     }
 
+    fn run_tool(&mut self, tool_use: PendingToolUse, cx: &mut ViewContext<Self>) {
+        let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+            return;
+        };
+        let action_log = self.thread.update(cx, |thread, cx| {
+            thread.action_log_for_turn(tool_use.assistant_message_id, cx)
+        });
+        let task = tool.run(tool_use.input, self.workspace.clone(), action_log, cx);
+        self.thread.update(cx, |thread, cx| {
+            thread.insert_tool_output(tool_use.assistant_message_id, tool_use.id, task, cx);
+        });
+    }
+
+    /// Reverts the changes that tools proposed in the turn started by the user message.
+    fn undo_turn_changes(&mut self, user_message_id: MessageId, cx: &mut ViewContext<Self>) {
+        let Some(action_log) = self.thread.read(cx).action_log(user_message_id).cloned() else {
+            return;
+        };
+        ActionLog::undo_all(&action_log, cx);
+        cx.notify();
+    }
+
+    fn allow_tool_use(&mut self, tool_use_id: &LanguageModelToolUseId, cx: &mut ViewContext<Self>) {
+        let tool_use = self
+            .thread
+            .read(cx)
+            .pending_tool_uses()
+            .into_iter()
+            .find(|tool_use| tool_use.id == *tool_use_id)
+            .cloned();
+        if let Some(tool_use) = tool_use {
+            self.run_tool(tool_use, cx);
+        }
+    }
+
+    fn deny_tool_use(&mut self, tool_use_id: &LanguageModelToolUseId, cx: &mut ViewContext<Self>) {
+        self.thread.update(cx, |thread, cx| {
+            thread.reject_tool_use(
+                tool_use_id.clone(),
+                "The user denied this use of the tool.".into(),
+                cx,
+            );
+        });
+    }
+
+    fn render_tool_use(&self, tool_use: ToolUse, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let status = match &tool_use.status {
+            ToolUseStatus::Pending => Label::new("Pending").color(Color::Muted),
+            ToolUseStatus::NeedsConfirmation => {
+                Label::new("Waiting for permission").color(Color::Warning)
+            }
+            ToolUseStatus::Running => Label::new("Running…").color(Color::Muted),
+            ToolUseStatus::Finished => Label::new("Done").color(Color::Success),
+            ToolUseStatus::Error(err) => Label::new(err.clone()).color(Color::Error),
+        };
+
+        v_flex()
+            .gap_1()
+            .p_1p5()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .justify_between()
+                    .gap_2()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Icon::new(IconName::PocketKnife).size(IconSize::Small))
+                            .child(Label::new(tool_use.name.clone()).size(LabelSize::Small)),
+                    )
+                    .child(status.size(LabelSize::Small).single_line()),
+            )
+            .child(
+                Label::new(tool_use.input.to_string())
+                    .size(LabelSize::XSmall)
+                    .color(Color::Muted)
+                    .single_line(),
+            )
+            .when(
+                matches!(tool_use.status, ToolUseStatus::NeedsConfirmation),
+                |this| {
+                    let tool_use_id = tool_use.id.clone();
+                    this.child(
+                        h_flex()
+                            .gap_1()
+                            .justify_end()
+                            .child(
+                                Button::new(
+                                    SharedString::from(format!("deny-tool-use-{}", tool_use.id)),
+                                    "Deny",
+                                )
+                                .on_click(cx.listener({
+                                    let tool_use_id = tool_use_id.clone();
+                                    move |this, _, cx| this.deny_tool_use(&tool_use_id, cx)
+                                })),
+                            )
+                            .child(
+                                Button::new(
+                                    SharedString::from(format!("allow-tool-use-{}", tool_use.id)),
+                                    "Allow",
+                                )
+                                .style(ButtonStyle::Filled)
+                                .on_click(cx.listener(
+                                    move |this, _, cx| this.allow_tool_use(&tool_use_id, cx),
+                                )),
+                            ),
+                    )
+                },
+            )
+    }
+
     fn render_message(&self, ix: usize, cx: &mut ViewContext<Self>) -> AnyElement {
         let message_id = self.messages[ix];
         let Some(message) = self.thread.read(cx).message(message_id) else {
@@ -202,6 +333,26 @@ impl ActiveThread {
             return Empty.into_any();
         };
 
+        let context = self
+            .thread
+            .read(cx)
+            .context_for_message(message_id)
+            .to_vec();
+
+        // The user messages that only carry tool results have no text to show.
+        if message.role == Role::User && message.text.is_empty() && context.is_empty() {
+            return Empty.into_any();
+        }
+
+        let tool_uses = self.thread.read(cx).tool_uses_for_message(message_id);
+        let has_proposed_changes = self
+            .thread
+            .read(cx)
+            .action_log(message_id)
+            .map_or(false, |action_log| {
+                action_log.read(cx).has_proposed_changes()
+            });
+
         let (role_icon, role_name) = match message.role {
             Role::User => (IconName::Person, "You"),
             Role::Assistant => (IconName::ZedAssistant, "Assistant"),
@@ -227,9 +378,50 @@ impl ActiveThread {
                                     .gap_2()
                                     .child(Icon::new(role_icon).size(IconSize::Small))
                                     .child(Label::new(role_name).size(LabelSize::Small)),
-                            ),
+                            )
+                            .when(has_proposed_changes, |this| {
+                                this.child(
+                                    Button::new(("undo-turn-changes", ix), "Undo Changes")
+                                        .icon(IconName::Undo)
+                                        .icon_size(IconSize::Small)
+                                        .icon_position(IconPosition::Start)
+                                        .label_size(LabelSize::Small)
+                                        .tooltip(|cx| {
+                                            Tooltip::text(
+                                                "Revert the files the assistant changed in this turn",
+                                                cx,
+                                            )
+                                        })
+                                        .on_click(cx.listener(move |this, _, cx| {
+                                            this.undo_turn_changes(message_id, cx)
+                                        })),
+                                )
+                            }),
                     )
-                    .child(v_flex().p_1p5().text_ui(cx).child(markdown.clone())),
+                    .child(v_flex().p_1p5().text_ui(cx).child(markdown.clone()))
+                    .when(!context.is_empty(), |parent| {
+                        parent.child(h_flex().flex_wrap().gap_1().px_1p5().pb_1p5().children(
+                            context.into_iter().map(|context| {
+                                h_flex()
+                                    .gap_1()
+                                    .px_1()
+                                    .rounded_md()
+                                    .border_1()
+                                    .border_color(cx.theme().colors().border_variant)
+                                    .child(
+                                        Icon::new(IconName::Server)
+                                            .size(IconSize::XSmall)
+                                            .color(Color::Muted),
+                                    )
+                                    .child(Label::new(context.name).size(LabelSize::Small))
+                            }),
+                        ))
+                    })
+                    .children(
+                        tool_uses
+                            .into_iter()
+                            .map(|tool_use| self.render_tool_use(tool_use, cx)),
+                    ),
             )
             .into_any()
     }
@@ -240,3 +432,217 @@ impl Render for ActiveThread {
         list(self.list_state.clone()).flex_1()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::Result;
+    use assistant_tool::Tool;
+    use gpui::{Task, TestAppContext, VisualTestContext};
+    use language_model::{
+        LanguageModel, LanguageModelCompletionEvent, LanguageModelToolResult, LanguageModelToolUse,
+        MessageContent, StopReason,
+    };
+    use project::{FakeFs, Project};
+    use settings::SettingsStore;
+
+    use super::*;
+    use crate::thread::RequestKind;
+
+    /// A tool that counts how many times it ran.
+    struct CountingTool(Arc<AtomicUsize>);
+
+    impl Tool for CountingTool {
+        fn name(&self) -> String {
+            "count".into()
+        }
+
+        fn description(&self) -> String {
+            "Counts its uses.".into()
+        }
+
+        fn run(
+            self: Arc<Self>,
+            _input: serde_json::Value,
+            _workspace: WeakView<Workspace>,
+            _action_log: Model<ActionLog>,
+            _cx: &mut WindowContext,
+        ) -> Task<Result<String>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Task::ready(Ok("Counted.".into()))
+        }
+    }
+
+    #[gpui::test]
+    async fn test_tool_permissions(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            assistant_tool::init(cx);
+            LanguageModelRegistry::test(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let runs = Arc::new(AtomicUsize::new(0));
+        let tools = Arc::new(ToolWorkingSet::default());
+        tools.insert(Arc::new(CountingTool(runs.clone())));
+        let thread = cx.new_model(|cx| Thread::new(tools.clone(), cx));
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        let active_thread = cx.new_view(|cx| {
+            ActiveThread::new(
+                thread.clone(),
+                workspace.downgrade(),
+                language_registry,
+                tools,
+                cx,
+            )
+        });
+        let model = cx.update(|cx| {
+            LanguageModelRegistry::read_global(cx)
+                .active_model()
+                .unwrap()
+        });
+
+        // The tool asks for permission by default, so it doesn't run until the user allows it.
+        let tool_use_id = request_tool_use(&thread, &model, "tool-1", cx);
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+        assert!(matches!(
+            last_tool_use_status(&thread, cx),
+            ToolUseStatus::NeedsConfirmation
+        ));
+
+        active_thread.update(cx, |active_thread, cx| {
+            active_thread.allow_tool_use(&tool_use_id, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            last_tool_use_status(&thread, cx),
+            ToolUseStatus::Finished
+        ));
+        assert_eq!(
+            last_tool_results(&model),
+            vec![LanguageModelToolResult {
+                tool_use_id: "tool-1".into(),
+                content: "Counted.".into(),
+                is_error: false,
+            }]
+        );
+        model.as_fake().end_last_completion_stream();
+
+        // A denied tool doesn't run, and the model is told so.
+        let tool_use_id = request_tool_use(&thread, &model, "tool-2", cx);
+        assert!(matches!(
+            last_tool_use_status(&thread, cx),
+            ToolUseStatus::NeedsConfirmation
+        ));
+        active_thread.update(cx, |active_thread, cx| {
+            active_thread.deny_tool_use(&tool_use_id, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            last_tool_use_status(&thread, cx),
+            ToolUseStatus::Error(error) if error == "The user denied this use of the tool."
+        ));
+        assert_eq!(
+            last_tool_results(&model),
+            vec![LanguageModelToolResult {
+                tool_use_id: "tool-2".into(),
+                content: "The user denied this use of the tool.".into(),
+                is_error: true,
+            }]
+        );
+        model.as_fake().end_last_completion_stream();
+
+        // Tools the user always allows run right away.
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<ToolSettings>(cx, |settings| {
+                    settings
+                        .permissions
+                        .insert("count".into(), ToolPermission::Always);
+                });
+            });
+        });
+        request_tool_use(&thread, &model, "tool-3", cx);
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert!(matches!(
+            last_tool_use_status(&thread, cx),
+            ToolUseStatus::Finished
+        ));
+    }
+
+    /// Sends a user message, to which the model answers by asking to use the tool.
+    fn request_tool_use(
+        thread: &Model<Thread>,
+        model: &Arc<dyn LanguageModel>,
+        id: &str,
+        cx: &mut VisualTestContext,
+    ) -> LanguageModelToolUseId {
+        // Let the completions that ended start the summary first.
+        cx.run_until_parked();
+        thread.update(cx, |thread, cx| {
+            thread.insert_user_message("Count", Vec::new(), cx);
+            let request = thread.to_completion_request(RequestKind::Chat, cx);
+            thread.stream_completion(request, model.clone(), cx);
+        });
+        cx.run_until_parked();
+
+        let tool_use_id = LanguageModelToolUseId::from(id);
+        let fake_model = model.as_fake();
+        fake_model.send_last_completion_event(LanguageModelCompletionEvent::StartMessage {
+            message_id: format!("message-{id}"),
+        });
+        fake_model.send_last_completion_event(LanguageModelCompletionEvent::ToolUse(
+            LanguageModelToolUse {
+                id: tool_use_id.clone(),
+                name: "count".into(),
+                input: serde_json::json!({}),
+            },
+        ));
+        fake_model
+            .send_last_completion_event(LanguageModelCompletionEvent::Stop(StopReason::ToolUse));
+        fake_model.end_last_completion_stream();
+        cx.run_until_parked();
+        tool_use_id
+    }
+
+    fn last_tool_use_status(thread: &Model<Thread>, cx: &mut VisualTestContext) -> ToolUseStatus {
+        thread.read_with(cx, |thread, _| {
+            let message = thread
+                .messages()
+                .filter(|message| message.role == Role::Assistant)
+                .last()
+                .unwrap();
+            thread
+                .tool_uses_for_message(message.id)
+                .pop()
+                .unwrap()
+                .status
+        })
+    }
+
+    /// Returns the tool results sent in the last request to the model.
+    fn last_tool_results(model: &Arc<dyn LanguageModel>) -> Vec<LanguageModelToolResult> {
+        let request = model.as_fake().pending_completions().pop().unwrap();
+        request
+            .messages
+            .last()
+            .unwrap()
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                MessageContent::ToolResult(result) => Some(result.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
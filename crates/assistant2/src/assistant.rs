@@ -1,7 +1,9 @@
 mod active_thread;
 mod assistant_panel;
+mod context;
 mod context_picker;
 mod message_editor;
+mod sampling;
 mod thread;
 mod thread_history;
 mod thread_store;
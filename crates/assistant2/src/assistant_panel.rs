@@ -1,12 +1,14 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use assistant_tool::ToolWorkingSet;
 use client::zed_urls;
+use context_server::manager::ContextServerManager;
+use context_server::types;
 use gpui::{
     prelude::*, px, svg, Action, AnyElement, AppContext, AsyncWindowContext, EventEmitter,
-    FocusHandle, FocusableView, FontWeight, Model, Pixels, Task, View, ViewContext, WeakView,
-    WindowContext,
+    FocusHandle, FocusableView, FontWeight, Model, Pixels, Subscription, Task, View, ViewContext,
+    WeakView, WindowContext,
 };
 use language::LanguageRegistry;
 use language_model::LanguageModelRegistry;
@@ -18,6 +20,7 @@ use workspace::Workspace;
 
 use crate::active_thread::ActiveThread;
 use crate::message_editor::MessageEditor;
+use crate::sampling;
 use crate::thread::{ThreadError, ThreadId};
 use crate::thread_history::{PastThread, ThreadHistory};
 use crate::thread_store::ThreadStore;
@@ -43,12 +46,14 @@ pub struct AssistantPanel {
     workspace: WeakView<Workspace>,
     language_registry: Arc<LanguageRegistry>,
     thread_store: Model<ThreadStore>,
+    context_server_manager: Model<ContextServerManager>,
     thread: View<ActiveThread>,
     message_editor: View<MessageEditor>,
     tools: Arc<ToolWorkingSet>,
     local_timezone: UtcOffset,
     active_view: ActiveView,
     history: View<ThreadHistory>,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl AssistantPanel {
@@ -81,12 +86,29 @@ impl AssistantPanel {
         let language_registry = workspace.project().read(cx).languages().clone();
         let workspace = workspace.weak_handle();
         let weak_self = cx.view().downgrade();
+        let context_server_manager = thread_store.read(cx).context_server_manager();
+        thread_store.update(cx, |thread_store, cx| {
+            thread_store.set_approves_sampling(true, cx)
+        });
+        let subscriptions = vec![
+            cx.subscribe(&context_server_manager, |_, _, event, cx| match event {
+                context_server::manager::Event::SamplingRequested { .. }
+                | context_server::manager::Event::ServerStopped { .. } => cx.notify(),
+                _ => {}
+            }),
+            cx.on_release(|this, _, cx| {
+                this.thread_store.update(cx, |thread_store, cx| {
+                    thread_store.set_approves_sampling(false, cx)
+                });
+            }),
+        ];
 
         Self {
             active_view: ActiveView::Thread,
             workspace: workspace.clone(),
             language_registry: language_registry.clone(),
             thread_store: thread_store.clone(),
+            context_server_manager,
             thread: cx.new_view(|cx| {
                 ActiveThread::new(
                     thread.clone(),
@@ -96,13 +118,15 @@ impl AssistantPanel {
                     cx,
                 )
             }),
-            message_editor: cx.new_view(|cx| MessageEditor::new(thread.clone(), cx)),
+            message_editor: cx
+                .new_view(|cx| MessageEditor::new(thread.clone(), thread_store.downgrade(), cx)),
             tools,
             local_timezone: UtcOffset::from_whole_seconds(
                 chrono::Local::now().offset().local_minus_utc(),
             )
             .unwrap(),
             history: cx.new_view(|cx| ThreadHistory::new(weak_self, thread_store, cx)),
+            _subscriptions: subscriptions,
         }
     }
 
@@ -125,7 +149,8 @@ impl AssistantPanel {
                 cx,
             )
         });
-        self.message_editor = cx.new_view(|cx| MessageEditor::new(thread, cx));
+        let thread_store = self.thread_store.downgrade();
+        self.message_editor = cx.new_view(|cx| MessageEditor::new(thread, thread_store, cx));
         self.message_editor.focus_handle(cx).focus(cx);
     }
 
@@ -147,7 +172,8 @@ impl AssistantPanel {
                 cx,
             )
         });
-        self.message_editor = cx.new_view(|cx| MessageEditor::new(thread, cx));
+        let thread_store = self.thread_store.downgrade();
+        self.message_editor = cx.new_view(|cx| MessageEditor::new(thread, thread_store, cx));
         self.message_editor.focus_handle(cx).focus(cx);
     }
 
@@ -155,6 +181,29 @@ impl AssistantPanel {
         self.thread_store
             .update(cx, |this, cx| this.delete_thread(thread_id, cx));
     }
+
+    fn approve_sampling_request(&mut self, request_id: usize, cx: &mut ViewContext<Self>) {
+        let Some(request) = self
+            .context_server_manager
+            .update(cx, |manager, _| manager.take_sampling_request(request_id))
+        else {
+            return;
+        };
+        cx.notify();
+
+        let Some(model) = LanguageModelRegistry::read_global(cx).active_model() else {
+            request.respond(Err(anyhow!("no language model is selected")));
+            return;
+        };
+        cx.spawn(|_, cx| async move { sampling::sample_message(request, model, &cx).await })
+            .detach();
+    }
+
+    fn decline_sampling_request(&mut self, request_id: usize, cx: &mut ViewContext<Self>) {
+        self.context_server_manager
+            .update(cx, |manager, _| manager.take_sampling_request(request_id));
+        cx.notify();
+    }
 }
 
 impl FocusableView for AssistantPanel {
@@ -431,6 +480,77 @@ impl AssistantPanel {
             })
     }
 
+    fn render_sampling_request(&self, cx: &mut ViewContext<Self>) -> Option<AnyElement> {
+        let request = self
+            .context_server_manager
+            .read(cx)
+            .sampling_requests()
+            .first()?;
+        let request_id = request.id;
+        let server_id = request.server_id.clone();
+        let prompt = request
+            .params
+            .messages
+            .iter()
+            .rev()
+            .find_map(|message| match &message.content {
+                types::MessageContent::Text { text, .. } => Some(text.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let model_name = LanguageModelRegistry::read_global(cx)
+            .active_model()
+            .map_or_else(|| "the selected model".into(), |model| model.name().0);
+
+        Some(
+            v_flex()
+                .gap_1()
+                .p_2()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    h_flex()
+                        .gap_1p5()
+                        .child(Icon::new(IconName::Server).size(IconSize::Small))
+                        .child(
+                            Label::new(format!(
+                                "{server_id} wants to generate a message with {model_name}"
+                            ))
+                            .size(LabelSize::Small),
+                        ),
+                )
+                .child(
+                    div()
+                        .id("sampling-prompt")
+                        .max_h_24()
+                        .overflow_y_scroll()
+                        .child(
+                            Label::new(prompt)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+                .child(
+                    h_flex()
+                        .justify_end()
+                        .gap_1()
+                        .child(
+                            Button::new("decline-sampling", "Deny").on_click(cx.listener(
+                                move |this, _, cx| this.decline_sampling_request(request_id, cx),
+                            )),
+                        )
+                        .child(
+                            Button::new("approve-sampling", "Allow")
+                                .style(ButtonStyle::Filled)
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    this.approve_sampling_request(request_id, cx)
+                                })),
+                        ),
+                )
+                .into_any(),
+        )
+    }
+
     fn render_last_error(&self, cx: &mut ViewContext<Self>) -> Option<AnyElement> {
         let last_error = self.thread.read(cx).last_error()?;
 
@@ -611,6 +731,7 @@ impl Render for AssistantPanel {
             .map(|parent| match self.active_view {
                 ActiveView::Thread => parent
                     .child(self.render_active_thread_or_empty_state(cx))
+                    .children(self.render_sampling_request(cx))
                     .child(
                         h_flex()
                             .border_t_1()
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use gpui::SharedString;
+use url::Url;
+
+/// Some context attached to a message in a thread.
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub name: SharedString,
+    pub kind: ContextKind,
+    pub text: SharedString,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextKind {
+    /// A resource provided by a context server, kept up to date when the
+    /// server reports changes to it.
+    ContextServerResource { server_id: Arc<str>, uri: Url },
+}
+
+impl ContextKind {
+    pub fn is_context_server_resource(&self, server_id: &str, uri: &Url) -> bool {
+        match self {
+            ContextKind::ContextServerResource {
+                server_id: resource_server_id,
+                uri: resource_uri,
+            } => resource_server_id.as_ref() == server_id && resource_uri == uri,
+        }
+    }
+}
+
+/// Formats the context attached to a message for the model.
+pub fn format_context(context: &[Context]) -> String {
+    let mut text = String::from("The user has attached the following context:\n");
+    for context in context {
+        match &context.kind {
+            ContextKind::ContextServerResource { uri, .. } => {
+                text.push_str(&format!(
+                    "\n<resource name=\"{}\" uri=\"{}\">\n{}\n</resource>\n",
+                    context.name, uri, context.text
+                ));
+            }
+        }
+    }
+    text
+}
@@ -4,6 +4,7 @@ use gpui::{DismissEvent, SharedString, Task, WeakView};
 use picker::{Picker, PickerDelegate, PickerEditorPosition};
 use ui::{prelude::*, ListItem, ListItemSpacing, PopoverMenu, PopoverTrigger, Tooltip};
 
+use crate::context::ContextKind;
 use crate::message_editor::MessageEditor;
 
 #[derive(IntoElement)]
@@ -17,6 +18,7 @@ struct ContextPickerEntry {
     name: SharedString,
     description: SharedString,
     icon: IconName,
+    kind: Option<ContextKind>,
 }
 
 pub(crate) struct ContextPickerDelegate {
@@ -89,8 +91,11 @@ impl PickerDelegate for ContextPickerDelegate {
     fn confirm(&mut self, _secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
         if let Some(entry) = self.filtered_entries.get(self.selected_ix) {
             self.message_editor
-                .update(cx, |_message_editor, _cx| {
-                    println!("Insert context from {}", entry.name);
+                .update(cx, |message_editor, cx| match &entry.kind {
+                    Some(kind) => {
+                        message_editor.attach_context(entry.name.clone(), kind.clone(), cx)
+                    }
+                    None => println!("Insert context from {}", entry.name),
                 })
                 .ok();
             cx.emit(DismissEvent);
@@ -151,24 +156,51 @@ impl PickerDelegate for ContextPickerDelegate {
 
 impl<T: PopoverTrigger> RenderOnce for ContextPicker<T> {
     fn render(self, cx: &mut WindowContext) -> impl IntoElement {
-        let entries = vec![
+        let mut entries = vec![
             ContextPickerEntry {
                 name: "directory".into(),
                 description: "Insert any directory".into(),
                 icon: IconName::Folder,
+                kind: None,
             },
             ContextPickerEntry {
                 name: "file".into(),
                 description: "Insert any file".into(),
                 icon: IconName::File,
+                kind: None,
             },
             ContextPickerEntry {
                 name: "web".into(),
                 description: "Fetch content from URL".into(),
                 icon: IconName::Globe,
+                kind: None,
             },
         ];
 
+        let thread_store = self
+            .message_editor
+            .upgrade()
+            .and_then(|message_editor| message_editor.read(cx).thread_store.upgrade());
+        if let Some(thread_store) = thread_store {
+            entries.extend(thread_store.read(cx).context_server_resources().map(
+                |(server_id, resource)| {
+                    ContextPickerEntry {
+                        name: resource.name.clone().into(),
+                        description: resource
+                            .description
+                            .clone()
+                            .unwrap_or_else(|| format!("{} from {server_id}", resource.uri))
+                            .into(),
+                        icon: IconName::Server,
+                        kind: Some(ContextKind::ContextServerResource {
+                            server_id: server_id.clone(),
+                            uri: resource.uri.clone(),
+                        }),
+                    }
+                },
+            ));
+        }
+
         let delegate = ContextPickerDelegate {
             all_entries: entries.clone(),
             message_editor: self.message_editor.clone(),
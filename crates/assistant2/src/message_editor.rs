@@ -1,29 +1,39 @@
 use editor::{Editor, EditorElement, EditorStyle};
-use gpui::{AppContext, FocusableView, Model, TextStyle, View};
-use language_model::{LanguageModelRegistry, LanguageModelRequestTool};
+use gpui::{AppContext, FocusableView, Model, TextStyle, View, WeakModel};
+use language_model::LanguageModelRegistry;
 use picker::Picker;
 use settings::Settings;
 use theme::ThemeSettings;
 use ui::{
     prelude::*, ButtonLike, CheckboxWithLabel, ElevationIndex, IconButtonShape, KeyBinding,
-    PopoverMenuHandle,
+    PopoverMenuHandle, Tooltip,
 };
 
+use crate::context::{Context, ContextKind};
 use crate::context_picker::{ContextPicker, ContextPickerDelegate};
 use crate::thread::{RequestKind, Thread};
+use crate::thread_store::ThreadStore;
 use crate::Chat;
 
 pub struct MessageEditor {
     thread: Model<Thread>,
+    pub(crate) thread_store: WeakModel<ThreadStore>,
     editor: View<Editor>,
+    context: Vec<Context>,
     pub(crate) context_picker_handle: PopoverMenuHandle<Picker<ContextPickerDelegate>>,
     use_tools: bool,
 }
 
 impl MessageEditor {
-    pub fn new(thread: Model<Thread>, cx: &mut ViewContext<Self>) -> Self {
+    pub fn new(
+        thread: Model<Thread>,
+        thread_store: WeakModel<ThreadStore>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
         Self {
             thread,
+            thread_store,
+            context: Vec::new(),
             editor: cx.new_view(|cx| {
                 let mut editor = Editor::auto_height(80, cx);
                 editor.set_placeholder_text("Ask anything…", cx);
@@ -35,6 +45,42 @@ impl MessageEditor {
         }
     }
 
+    /// Loads the context and attaches it to the next message.
+    pub(crate) fn attach_context(
+        &mut self,
+        name: SharedString,
+        kind: ContextKind,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.context.iter().any(|context| context.kind == kind) {
+            return;
+        }
+        let Some(thread_store) = self.thread_store.upgrade() else {
+            return;
+        };
+        let text = thread_store.update(cx, |thread_store, cx| match &kind {
+            ContextKind::ContextServerResource { server_id, uri } => {
+                thread_store.read_context_server_resource(server_id.clone(), uri.clone(), cx)
+            }
+        });
+
+        cx.spawn(|this, mut cx| async move {
+            let text = text.await?;
+            this.update(&mut cx, |this, cx| {
+                this.context.push(Context { name, kind, text });
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn remove_context(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if ix < self.context.len() {
+            self.context.remove(ix);
+            cx.notify();
+        }
+    }
+
     fn chat(&mut self, _: &Chat, cx: &mut ViewContext<Self>) {
         self.send_to_model(RequestKind::Chat, cx);
     }
@@ -62,21 +108,14 @@ impl MessageEditor {
             text
         });
 
+        let context = std::mem::take(&mut self.context);
+
         self.thread.update(cx, |thread, cx| {
-            thread.insert_user_message(user_message, cx);
+            thread.insert_user_message(user_message, context, cx);
             let mut request = thread.to_completion_request(request_kind, cx);
 
             if self.use_tools {
-                request.tools = thread
-                    .tools()
-                    .tools(cx)
-                    .into_iter()
-                    .map(|tool| LanguageModelRequestTool {
-                        name: tool.name(),
-                        description: tool.description(),
-                        input_schema: tool.input_schema(),
-                    })
-                    .collect();
+                request.tools = thread.request_tools(cx);
             }
 
             thread.stream_completion(request, model, cx)
@@ -106,12 +145,33 @@ impl Render for MessageEditor {
             .p_2()
             .bg(cx.theme().colors().editor_background)
             .child(
-                h_flex().gap_2().child(ContextPicker::new(
-                    cx.view().downgrade(),
-                    IconButton::new("add-context", IconName::Plus)
-                        .shape(IconButtonShape::Square)
-                        .icon_size(IconSize::Small),
-                )),
+                h_flex()
+                    .gap_2()
+                    .flex_wrap()
+                    .child(ContextPicker::new(
+                        cx.view().downgrade(),
+                        IconButton::new("add-context", IconName::Plus)
+                            .shape(IconButtonShape::Square)
+                            .icon_size(IconSize::Small),
+                    ))
+                    .children(self.context.iter().enumerate().map(|(ix, context)| {
+                        h_flex()
+                            .gap_1()
+                            .pl_1p5()
+                            .rounded_md()
+                            .border_1()
+                            .border_color(cx.theme().colors().border_variant)
+                            .child(Label::new(context.name.clone()).size(LabelSize::Small))
+                            .child(
+                                IconButton::new(("remove-context", ix), IconName::Close)
+                                    .shape(IconButtonShape::Square)
+                                    .icon_size(IconSize::XSmall)
+                                    .tooltip(|cx| Tooltip::text("Remove Context", cx))
+                                    .on_click(cx.listener(move |this, _event, cx| {
+                                        this.remove_context(ix, cx);
+                                    })),
+                            )
+                    })),
             )
             .child({
                 let settings = ThemeSettings::get_global(cx);
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use context_server::manager::SamplingRequest;
+use context_server::types;
+use futures::StreamExt as _;
+use gpui::AsyncAppContext;
+use language_model::{
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelRequest, LanguageModelRequestMessage,
+    MessageContent, Role, StopReason,
+};
+
+/// Turns a context server's request to sample a message into a request to the user's model.
+///
+/// The stop sequences are left out, as they are matched in [`sample_message`],
+/// which tells the server when one of them ended the message.
+pub fn completion_request(params: &types::CreateMessageRequest) -> LanguageModelRequest {
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = params
+        .system_prompt
+        .as_ref()
+        .filter(|system_prompt| !system_prompt.is_empty())
+    {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![MessageContent::Text(system_prompt.clone())],
+            cache: false,
+        });
+    }
+
+    for message in &params.messages {
+        let text = match &message.content {
+            types::MessageContent::Text { text, .. } => text.clone(),
+            types::MessageContent::Image { mime_type, .. } => format!("[{mime_type} image]"),
+            types::MessageContent::Resource { resource, .. } => {
+                format!("[resource {}]", resource.uri)
+            }
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                types::Role::User => Role::User,
+                types::Role::Assistant => Role::Assistant,
+            },
+            content: vec![MessageContent::Text(text)],
+            cache: false,
+        });
+    }
+
+    LanguageModelRequest {
+        messages,
+        tools: Vec::new(),
+        stop: Vec::new(),
+        temperature: params.temperature.map(|temperature| temperature as f32),
+    }
+}
+
+/// Samples a message from the model, and sends it back to the context server that asked for it.
+pub async fn sample_message(
+    request: SamplingRequest,
+    model: Arc<dyn LanguageModel>,
+    cx: &AsyncAppContext,
+) {
+    let completion_request = completion_request(&request.params);
+    let max_tokens = request.params.max_tokens as usize;
+    let stop_sequences = request
+        .params
+        .stop_sequences
+        .iter()
+        .flatten()
+        .filter(|stop_sequence| !stop_sequence.is_empty())
+        .collect::<Vec<_>>();
+    let result = async {
+        let mut events = model.stream_completion(completion_request, cx).await?;
+        let mut text = String::new();
+        let mut stop_reason = "endTurn";
+        while let Some(event) = events.next().await {
+            let chunk = match event? {
+                LanguageModelCompletionEvent::Text(chunk) => chunk,
+                LanguageModelCompletionEvent::Stop(StopReason::MaxTokens) => {
+                    stop_reason = "maxTokens";
+                    continue;
+                }
+                _ => continue,
+            };
+            let previous_len = text.len();
+            text.push_str(&chunk);
+
+            if let Some(stop_sequence_start) = stop_sequences
+                .iter()
+                .filter_map(|stop_sequence| text.find(stop_sequence.as_str()))
+                .min()
+            {
+                text.truncate(stop_sequence_start);
+                stop_reason = "stopSequence";
+                break;
+            }
+
+            // Tokens are at least a byte long, so shorter texts are within the limit.
+            if text.len() > max_tokens && count_tokens(&model, &text, cx).await? > max_tokens {
+                text.truncate(previous_len);
+                stop_reason = "maxTokens";
+                break;
+            }
+        }
+
+        Result::<_>::Ok(types::CreateMessageResult {
+            role: types::Role::Assistant,
+            content: types::MessageContent::Text {
+                text,
+                annotations: None,
+            },
+            model: model.id().0.to_string(),
+            stop_reason: Some(stop_reason.into()),
+        })
+    }
+    .await;
+
+    request.respond(result);
+}
+
+async fn count_tokens(
+    model: &Arc<dyn LanguageModel>,
+    text: &str,
+    cx: &AsyncAppContext,
+) -> Result<usize> {
+    let request = LanguageModelRequest {
+        messages: vec![LanguageModelRequestMessage {
+            role: Role::Assistant,
+            content: vec![MessageContent::Text(text.to_string())],
+            cache: false,
+        }],
+        tools: Vec::new(),
+        stop: Vec::new(),
+        temperature: None,
+    };
+    cx.update(|cx| model.count_tokens(request, cx))?.await
+}
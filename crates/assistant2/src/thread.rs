@@ -1,22 +1,26 @@
 use std::sync::Arc;
 
-use anyhow::Result;
-use assistant_tool::ToolWorkingSet;
+use anyhow::{anyhow, Result};
+use assistant_tool::{ActionLog, ToolPermission, ToolSettings, ToolWorkingSet};
 use chrono::{DateTime, Utc};
 use collections::HashMap;
 use futures::future::Shared;
 use futures::{FutureExt as _, StreamExt as _};
-use gpui::{AppContext, EventEmitter, ModelContext, SharedString, Task};
+use gpui::{AppContext, EventEmitter, Model, ModelContext, SharedString, Task};
 use language_model::{
     LanguageModel, LanguageModelCompletionEvent, LanguageModelRegistry, LanguageModelRequest,
-    LanguageModelRequestMessage, LanguageModelToolResult, LanguageModelToolUse,
-    LanguageModelToolUseId, MessageContent, Role, StopReason,
+    LanguageModelRequestMessage, LanguageModelRequestTool, LanguageModelToolResult,
+    LanguageModelToolUse, LanguageModelToolUseId, MessageContent, Role, StopReason,
 };
 use language_models::provider::cloud::{MaxMonthlySpendReachedError, PaymentRequiredError};
 use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+use url::Url;
 use util::{post_inc, TryFutureExt as _};
 use uuid::Uuid;
 
+use crate::context::{format_context, Context};
+
 #[derive(Debug, Clone, Copy)]
 pub enum RequestKind {
     Chat,
@@ -68,6 +72,9 @@ pub struct Thread {
     tool_uses_by_message: HashMap<MessageId, Vec<LanguageModelToolUse>>,
     tool_results_by_message: HashMap<MessageId, Vec<LanguageModelToolResult>>,
     pending_tool_uses_by_id: HashMap<LanguageModelToolUseId, PendingToolUse>,
+    /// The changes proposed by tools, by the user message that started the turn.
+    action_logs_by_message: HashMap<MessageId, Model<ActionLog>>,
+    context_by_message: HashMap<MessageId, Vec<Context>>,
 }
 
 impl Thread {
@@ -85,6 +92,8 @@ impl Thread {
             tool_uses_by_message: HashMap::default(),
             tool_results_by_message: HashMap::default(),
             pending_tool_uses_by_id: HashMap::default(),
+            action_logs_by_message: HashMap::default(),
+            context_by_message: HashMap::default(),
         }
     }
 
@@ -121,6 +130,38 @@ impl Thread {
         self.messages.iter()
     }
 
+    /// Returns the context the user attached to the message.
+    pub fn context_for_message(&self, id: MessageId) -> &[Context] {
+        self.context_by_message
+            .get(&id)
+            .map_or(&[], |context| context.as_slice())
+    }
+
+    /// Returns whether a message has the context server's resource attached.
+    pub fn has_context_server_resource(&self, server_id: &str, uri: &Url) -> bool {
+        self.context_by_message
+            .values()
+            .flatten()
+            .any(|context| context.kind.is_context_server_resource(server_id, uri))
+    }
+
+    /// Replaces the text of the context server's resource wherever it is attached,
+    /// so that the next request sends its latest contents.
+    pub fn update_context_server_resource(
+        &mut self,
+        server_id: &str,
+        uri: &Url,
+        text: SharedString,
+        cx: &mut ModelContext<Self>,
+    ) {
+        for context in self.context_by_message.values_mut().flatten() {
+            if context.kind.is_context_server_resource(server_id, uri) {
+                context.text = text.clone();
+            }
+        }
+        cx.notify();
+    }
+
     pub fn tools(&self) -> &Arc<ToolWorkingSet> {
         &self.tools
     }
@@ -129,7 +170,107 @@ impl Thread {
         self.pending_tool_uses_by_id.values().collect()
     }
 
-    pub fn insert_user_message(&mut self, text: impl Into<String>, cx: &mut ModelContext<Self>) {
+    /// Returns whether every tool the model asked for has produced its output.
+    pub fn all_tools_finished(&self) -> bool {
+        self.pending_tool_uses_by_id.is_empty()
+    }
+
+    /// Returns the tools the model asked for in the message, with their progress.
+    pub fn tool_uses_for_message(&self, id: MessageId) -> Vec<ToolUse> {
+        let Some(tool_uses) = self.tool_uses_by_message.get(&id) else {
+            return Vec::new();
+        };
+        let tool_results = self.tool_results_by_message.get(&MessageId(id.0 + 1));
+
+        tool_uses
+            .iter()
+            .map(|tool_use| {
+                let status = if let Some(pending_tool_use) =
+                    self.pending_tool_uses_by_id.get(&tool_use.id)
+                {
+                    match &pending_tool_use.status {
+                        PendingToolUseStatus::Idle => ToolUseStatus::Pending,
+                        PendingToolUseStatus::NeedsConfirmation => ToolUseStatus::NeedsConfirmation,
+                        PendingToolUseStatus::Running { .. } => ToolUseStatus::Running,
+                    }
+                } else {
+                    match tool_results.and_then(|results| {
+                        results
+                            .iter()
+                            .find(|result| result.tool_use_id == tool_use.id.to_string())
+                    }) {
+                        Some(result) if result.is_error => {
+                            ToolUseStatus::Error(result.content.clone())
+                        }
+                        Some(_) => ToolUseStatus::Finished,
+                        None => ToolUseStatus::Pending,
+                    }
+                };
+
+                ToolUse {
+                    id: tool_use.id.clone(),
+                    name: tool_use.name.clone().into(),
+                    input: tool_use.input.clone(),
+                    status,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the log of the changes proposed by tools in the turn started by the user message.
+    pub fn action_log(&self, user_message_id: MessageId) -> Option<&Model<ActionLog>> {
+        self.action_logs_by_message.get(&user_message_id)
+    }
+
+    /// Returns the log where the tools requested by the Assistant message record their changes,
+    /// shared by the whole turn.
+    pub fn action_log_for_turn(
+        &mut self,
+        assistant_message_id: MessageId,
+        cx: &mut ModelContext<Self>,
+    ) -> Model<ActionLog> {
+        // Tool results are sent in user messages without text, which don't start a turn.
+        let turn_message_id = self
+            .messages
+            .iter()
+            .rfind(|message| {
+                message.id < assistant_message_id
+                    && message.role == Role::User
+                    && !message.text.is_empty()
+            })
+            .map_or(assistant_message_id, |message| message.id);
+
+        self.action_logs_by_message
+            .entry(turn_message_id)
+            .or_insert_with(|| cx.new_model(|_| ActionLog::new()))
+            .clone()
+    }
+
+    /// Returns the tools to offer to the model, leaving out the ones the user never allows.
+    pub fn request_tools(&self, cx: &AppContext) -> Vec<LanguageModelRequestTool> {
+        let settings = ToolSettings::get_global(cx);
+        self.tools
+            .tools(cx)
+            .into_iter()
+            .filter(|tool| settings.permission(tool) != ToolPermission::Never)
+            .map(|tool| LanguageModelRequestTool {
+                name: tool.name(),
+                description: tool.description(),
+                input_schema: tool.input_schema(),
+            })
+            .collect()
+    }
+
+    pub fn insert_user_message(
+        &mut self,
+        text: impl Into<String>,
+        context: Vec<Context>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !context.is_empty() {
+            self.context_by_message
+                .insert(self.next_message_id, context);
+        }
         self.insert_message(Role::User, text, cx)
     }
 
@@ -176,6 +317,12 @@ impl Thread {
                 }
             }
 
+            if let Some(context) = self.context_by_message.get(&message.id) {
+                request_message
+                    .content
+                    .push(MessageContent::Text(format_context(context)));
+            }
+
             if !message.text.is_empty() {
                 request_message
                     .content
@@ -369,6 +516,52 @@ impl Thread {
         });
     }
 
+    /// Holds the tool use until the user allows or denies it.
+    pub fn request_tool_confirmation(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(tool_use_id) {
+            tool_use.status = PendingToolUseStatus::NeedsConfirmation;
+            cx.notify();
+        }
+    }
+
+    /// Answers the tool use with an error instead of running the tool.
+    pub fn reject_tool_use(
+        &mut self,
+        tool_use_id: LanguageModelToolUseId,
+        reason: String,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(assistant_message_id) = self
+            .pending_tool_uses_by_id
+            .get(&tool_use_id)
+            .map(|tool_use| tool_use.assistant_message_id)
+        else {
+            return;
+        };
+        self.insert_tool_output(
+            assistant_message_id,
+            tool_use_id,
+            Task::ready(Err(anyhow!(reason))),
+            cx,
+        );
+    }
+
+    /// Sends the outputs of the tools back to the model, so that it can continue its turn.
+    pub fn send_tool_results_to_model(
+        &mut self,
+        model: Arc<dyn LanguageModel>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.insert_user_message(String::new(), Vec::new(), cx);
+        let mut request = self.to_completion_request(RequestKind::Chat, cx);
+        request.tools = self.request_tools(cx);
+        self.stream_completion(request, model, cx);
+    }
+
     pub fn insert_tool_output(
         &mut self,
         assistant_message_id: MessageId,
@@ -399,8 +592,6 @@ impl Thread {
                                     content: output,
                                     is_error: false,
                                 });
-
-                                cx.emit(ThreadEvent::ToolFinished { tool_use_id });
                             }
                             Err(err) => {
                                 tool_results.push(LanguageModelToolResult {
@@ -408,14 +599,12 @@ impl Thread {
                                     content: err.to_string(),
                                     is_error: true,
                                 });
-
-                                if let Some(tool_use) =
-                                    thread.pending_tool_uses_by_id.get_mut(&tool_use_id)
-                                {
-                                    tool_use.status = PendingToolUseStatus::Error(err.to_string());
-                                }
                             }
                         }
+
+                        thread.pending_tool_uses_by_id.remove(&tool_use_id);
+                        cx.emit(ThreadEvent::ToolFinished { tool_use_id });
+                        cx.notify();
                     })
                     .ok();
             }
@@ -470,8 +659,11 @@ pub struct PendingToolUse {
 #[derive(Debug, Clone)]
 pub enum PendingToolUseStatus {
     Idle,
-    Running { _task: Shared<Task<()>> },
-    Error(#[allow(unused)] String),
+    /// The user has to allow the tool use before it runs.
+    NeedsConfirmation,
+    Running {
+        _task: Shared<Task<()>>,
+    },
 }
 
 impl PendingToolUseStatus {
@@ -479,3 +671,21 @@ impl PendingToolUseStatus {
         matches!(self, PendingToolUseStatus::Idle)
     }
 }
+
+/// A tool use of an Assistant message, as shown in the thread.
+#[derive(Debug, Clone)]
+pub struct ToolUse {
+    pub id: LanguageModelToolUseId,
+    pub name: SharedString,
+    pub input: serde_json::Value,
+    pub status: ToolUseStatus,
+}
+
+#[derive(Debug, Clone)]
+pub enum ToolUseStatus {
+    Pending,
+    NeedsConfirmation,
+    Running,
+    Finished,
+    Error(String),
+}
@@ -1,13 +1,15 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use assistant_tool::{ToolId, ToolWorkingSet};
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use context_server::manager::ContextServerManager;
+use context_server::types::{Resource, ResourceContentsType};
 use context_server::{ContextServerFactoryRegistry, ContextServerTool};
-use gpui::{prelude::*, AppContext, Model, ModelContext, Task};
+use gpui::{prelude::*, AppContext, Model, ModelContext, SharedString, Task};
 use project::Project;
 use unindent::Unindent;
+use url::Url;
 use util::ResultExt as _;
 
 use crate::thread::{Thread, ThreadId};
@@ -18,6 +20,10 @@ pub struct ThreadStore {
     tools: Arc<ToolWorkingSet>,
     context_server_manager: Model<ContextServerManager>,
     context_server_tool_ids: HashMap<Arc<str>, Vec<ToolId>>,
+    context_server_resources: HashMap<Arc<str>, Vec<Resource>>,
+    subscribed_resources: HashSet<(Arc<str>, Url)>,
+    /// Whether a panel asks the user to approve the context servers' sampling requests.
+    approves_sampling: bool,
     threads: Vec<Model<Thread>>,
 }
 
@@ -40,6 +46,9 @@ impl ThreadStore {
                     tools,
                     context_server_manager,
                     context_server_tool_ids: HashMap::default(),
+                    context_server_resources: HashMap::default(),
+                    subscribed_resources: HashSet::default(),
+                    approves_sampling: false,
                     threads: Vec::new(),
                 };
                 this.mock_recent_threads(cx);
@@ -84,6 +93,80 @@ impl ThreadStore {
         self.threads.retain(|thread| thread.read(cx).id() != id);
     }
 
+    pub fn context_server_manager(&self) -> Model<ContextServerManager> {
+        self.context_server_manager.clone()
+    }
+
+    /// Sets whether a panel asks the user to approve sampling requests,
+    /// declining the pending ones when there is none left.
+    pub fn set_approves_sampling(&mut self, approves_sampling: bool, cx: &mut ModelContext<Self>) {
+        self.approves_sampling = approves_sampling;
+        if !approves_sampling {
+            self.context_server_manager.update(cx, |manager, _| {
+                let request_ids = manager
+                    .sampling_requests()
+                    .iter()
+                    .map(|request| request.id)
+                    .collect::<Vec<_>>();
+                for request_id in request_ids {
+                    manager.take_sampling_request(request_id);
+                }
+            });
+        }
+    }
+
+    /// Returns the resources offered by the running context servers.
+    pub fn context_server_resources(&self) -> impl Iterator<Item = (&Arc<str>, &Resource)> {
+        self.context_server_resources
+            .iter()
+            .flat_map(|(server_id, resources)| {
+                resources.iter().map(move |resource| (server_id, resource))
+            })
+    }
+
+    /// Reads the text of a context server's resource, subscribing to its changes
+    /// the first time it's read.
+    pub fn read_context_server_resource(
+        &mut self,
+        server_id: Arc<str>,
+        uri: Url,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<SharedString>> {
+        let Some(protocol) = self
+            .context_server_manager
+            .read(cx)
+            .get_server(&server_id)
+            .and_then(|server| server.client())
+        else {
+            return Task::ready(Err(anyhow!("context server {server_id} is not running")));
+        };
+        let subscribe = protocol.can_subscribe_to_resources()
+            && self
+                .subscribed_resources
+                .insert((server_id.clone(), uri.clone()));
+
+        cx.spawn(|_, _cx| async move {
+            if subscribe {
+                protocol.subscribe_to_resource(uri.clone()).await.log_err();
+            }
+
+            let response = protocol.read_resource(uri).await?;
+            let text = response
+                .contents
+                .into_iter()
+                .map(|contents| match contents {
+                    ResourceContentsType::Text(contents) => contents.text,
+                    ResourceContentsType::Blob(contents) => format!(
+                        "[binary contents of {}]",
+                        contents.mime_type.as_deref().unwrap_or("unknown type")
+                    ),
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            Ok(text.into())
+        })
+    }
+
     fn register_context_server_handlers(&self, cx: &mut ModelContext<Self>) {
         cx.subscribe(
             &self.context_server_manager.clone(),
@@ -132,7 +215,20 @@ impl ThreadStore {
                                         .collect::<Vec<_>>();
 
                                     this.update(&mut cx, |this, _cx| {
-                                        this.context_server_tool_ids.insert(server_id, tool_ids);
+                                        this.context_server_tool_ids
+                                            .insert(server_id.clone(), tool_ids);
+                                    })
+                                    .log_err();
+                                }
+                            }
+
+                            if protocol
+                                .capable(context_server::protocol::ServerCapability::Resources)
+                            {
+                                if let Some(response) = protocol.list_resources().await.log_err() {
+                                    this.update(&mut cx, |this, _cx| {
+                                        this.context_server_resources
+                                            .insert(server_id, response.resources);
                                     })
                                     .log_err();
                                 }
@@ -146,6 +242,46 @@ impl ThreadStore {
                 if let Some(tool_ids) = self.context_server_tool_ids.remove(server_id) {
                     tool_working_set.remove(&tool_ids);
                 }
+                self.context_server_resources.remove(server_id);
+                self.subscribed_resources
+                    .retain(|(resource_server_id, _)| resource_server_id != server_id);
+            }
+            context_server::manager::Event::ResourceUpdated { server_id, uri } => {
+                let threads = self
+                    .threads
+                    .iter()
+                    .filter(|thread| thread.read(cx).has_context_server_resource(server_id, uri))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if threads.is_empty() {
+                    return;
+                }
+
+                let text = self.read_context_server_resource(server_id.clone(), uri.clone(), cx);
+                let (server_id, uri) = (server_id.clone(), uri.clone());
+                cx.spawn(|_, mut cx| async move {
+                    let text = text.await?;
+                    for thread in threads {
+                        thread.update(&mut cx, |thread, cx| {
+                            thread.update_context_server_resource(
+                                &server_id,
+                                &uri,
+                                text.clone(),
+                                cx,
+                            );
+                        })?;
+                    }
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
+            }
+            // Sampling requests are approved by the user in the panel, and declined without one.
+            context_server::manager::Event::SamplingRequested { request_id, .. } => {
+                if !self.approves_sampling {
+                    context_server_manager.update(cx, |manager, _| {
+                        manager.take_sampling_request(*request_id);
+                    });
+                }
             }
         }
     }
@@ -159,9 +295,9 @@ impl ThreadStore {
         self.threads.push(cx.new_model(|cx| {
             let mut thread = Thread::new(self.tools.clone(), cx);
             thread.set_summary("Introduction to quantum computing", cx);
-            thread.insert_user_message("Hello! Can you help me understand quantum computing?", cx);
+            thread.insert_user_message("Hello! Can you help me understand quantum computing?", Vec::new(), cx);
             thread.insert_message(Role::Assistant, "Of course! I'd be happy to help you understand quantum computing. Quantum computing is a fascinating field that uses the principles of quantum mechanics to process information. Unlike classical computers that use bits (0s and 1s), quantum computers use quantum bits or 'qubits'. These qubits can exist in multiple states simultaneously, a property called superposition. This allows quantum computers to perform certain calculations much faster than classical computers. What specific aspect of quantum computing would you like to know more about?", cx);
-            thread.insert_user_message("That's interesting! Can you explain how quantum entanglement is used in quantum computing?", cx);
+            thread.insert_user_message("That's interesting! Can you explain how quantum entanglement is used in quantum computing?", Vec::new(), cx);
             thread.insert_message(Role::Assistant, "Certainly! Quantum entanglement is a key principle used in quantum computing. When two qubits become entangled, the state of one qubit is directly related to the state of the other, regardless of the distance between them. This property is used in quantum computing to create complex quantum states and to perform operations on multiple qubits simultaneously. Entanglement allows quantum computers to process information in ways that classical computers cannot, potentially solving certain problems much more efficiently. For example, it's crucial in quantum error correction and in algorithms like quantum teleportation, which is important for quantum communication.", cx);
             thread
         }));
@@ -169,7 +305,7 @@ impl ThreadStore {
         self.threads.push(cx.new_model(|cx| {
             let mut thread = Thread::new(self.tools.clone(), cx);
             thread.set_summary("Rust web development and async programming", cx);
-            thread.insert_user_message("Can you show me an example of Rust code for a simple web server?", cx);
+            thread.insert_user_message("Can you show me an example of Rust code for a simple web server?", Vec::new(), cx);
             thread.insert_message(Role::Assistant, "Certainly! Here's an example of a simple web server in Rust using the `actix-web` framework:
 
         ```rust
@@ -206,7 +342,7 @@ impl ThreadStore {
         ```
 
         Then you can run the server with `cargo run` and access it at `http://localhost:8080`.".unindent(), cx);
-            thread.insert_user_message("That's great! Can you explain more about async functions in Rust?", cx);
+            thread.insert_user_message("That's great! Can you explain more about async functions in Rust?", Vec::new(), cx);
             thread.insert_message(Role::Assistant, "Certainly! Async functions are a key feature in Rust for writing efficient, non-blocking code, especially for I/O-bound operations. Here's an overview:
 
         1. **Syntax**: Async functions are declared using the `async` keyword:
@@ -0,0 +1,43 @@
+use editor::ProposedChangesEditor;
+use gpui::{AppContext, Model, View, WindowContext};
+
+/// Records the changes that tools proposed during a turn of a conversation,
+/// so that the whole turn can be undone, even after some of them were applied.
+#[derive(Default)]
+pub struct ActionLog {
+    /// The editors where the proposed changes are reviewed. They are kept alive after
+    /// their tabs are closed, as they know which changes were applied to the files.
+    review_editors: Vec<View<ProposedChangesEditor>>,
+}
+
+impl ActionLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an editor where the user reviews changes a tool proposed.
+    pub fn push_review_editor(&mut self, editor: View<ProposedChangesEditor>) {
+        self.review_editors.push(editor);
+    }
+
+    /// Returns whether the tools proposed any changes.
+    pub fn has_proposed_changes(&self) -> bool {
+        !self.review_editors.is_empty()
+    }
+
+    /// Returns whether some of the proposed changes were applied to the files.
+    pub fn has_applied_changes(&self, cx: &AppContext) -> bool {
+        self.review_editors
+            .iter()
+            .any(|editor| editor.read(cx).has_applied_changes(cx))
+    }
+
+    /// Undoes the changes that were applied and discards the pending ones,
+    /// most recent first.
+    pub fn undo_all(action_log: &Model<Self>, cx: &mut WindowContext) {
+        let review_editors = action_log.read(cx).review_editors.clone();
+        for editor in review_editors.into_iter().rev() {
+            editor.update(cx, |editor, cx| editor.undo_all_changes(cx));
+        }
+    }
+}
@@ -1,17 +1,23 @@
+mod action_log;
 mod tool_registry;
+mod tool_settings;
 mod tool_working_set;
 
 use std::sync::Arc;
 
 use anyhow::Result;
-use gpui::{AppContext, Task, WeakView, WindowContext};
+use gpui::{AppContext, Model, Task, WeakView, WindowContext};
+use settings::Settings as _;
 use workspace::Workspace;
 
+pub use crate::action_log::*;
 pub use crate::tool_registry::*;
+pub use crate::tool_settings::*;
 pub use crate::tool_working_set::*;
 
 pub fn init(cx: &mut AppContext) {
     ToolRegistry::default_global(cx);
+    ToolSettings::register(cx);
 }
 
 /// A tool that can be used by a language model.
@@ -27,11 +33,18 @@ pub trait Tool: 'static + Send + Sync {
         serde_json::Value::Object(serde_json::Map::default())
     }
 
-    /// Runs the tool with the provided input.
+    /// Returns whether the tool can be used without asking the user, unless the settings say otherwise.
+    /// Tools that change the project or run commands should ask first.
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Ask
+    }
+
+    /// Runs the tool with the provided input, recording the changes it proposes in the action log.
     fn run(
         self: Arc<Self>,
         input: serde_json::Value,
         workspace: WeakView<Workspace>,
+        action_log: Model<ActionLog>,
         cx: &mut WindowContext,
     ) -> Task<Result<String>>;
 }
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use collections::HashMap;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+use crate::Tool;
+
+/// Whether the assistant may use a tool.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPermission {
+    /// Run the tool as soon as the model asks for it.
+    Always,
+    /// Ask the user to confirm every use of the tool.
+    Ask,
+    /// Never offer the tool to the model.
+    Never,
+}
+
+/// Settings for the tools the assistant can use.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, JsonSchema)]
+pub struct ToolSettings {
+    /// Permissions of the tools, by tool name.
+    /// Tools that are not listed use their own default permission.
+    #[serde(default)]
+    pub permissions: HashMap<String, ToolPermission>,
+}
+
+impl ToolSettings {
+    /// Returns the permission the user gave to the tool.
+    pub fn permission(&self, tool: &Arc<dyn Tool>) -> ToolPermission {
+        self.permissions
+            .get(&tool.name())
+            .copied()
+            .unwrap_or_else(|| tool.default_permission())
+    }
+}
+
+impl Settings for ToolSettings {
+    const KEY: Option<&'static str> = Some("assistant_tools");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
@@ -1,13 +1,34 @@
+mod edit_files_tool;
+mod find_symbols_tool;
+mod list_directory_tool;
 mod now_tool;
+mod read_file_tool;
+mod run_command_tool;
+mod search_project_tool;
+
+#[cfg(test)]
+mod assistant_tools_tests;
 
 use assistant_tool::ToolRegistry;
 use gpui::AppContext;
 
+use crate::edit_files_tool::EditFilesTool;
+use crate::find_symbols_tool::FindSymbolsTool;
+use crate::list_directory_tool::ListDirectoryTool;
 use crate::now_tool::NowTool;
+use crate::read_file_tool::ReadFileTool;
+use crate::run_command_tool::RunCommandTool;
+use crate::search_project_tool::SearchProjectTool;
 
 pub fn init(cx: &mut AppContext) {
     assistant_tool::init(cx);
 
     let registry = ToolRegistry::global(cx);
     registry.register_tool(NowTool);
+    registry.register_tool(ListDirectoryTool);
+    registry.register_tool(ReadFileTool);
+    registry.register_tool(SearchProjectTool);
+    registry.register_tool(FindSymbolsTool);
+    registry.register_tool(EditFilesTool);
+    registry.register_tool(RunCommandTool);
 }
@@ -0,0 +1,537 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use assistant_tool::{ActionLog, Tool};
+use editor::ProposedChangesEditor;
+use futures::StreamExt as _;
+use gpui::{SemanticVersion, TestAppContext, View, VisualTestContext};
+use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher};
+use project::{FakeFs, Project};
+use serde_json::json;
+use settings::SettingsStore;
+use terminal_view::terminal_panel::TerminalPanel;
+use workspace::Workspace;
+
+use crate::edit_files_tool::EditFilesTool;
+use crate::find_symbols_tool::FindSymbolsTool;
+use crate::list_directory_tool::ListDirectoryTool;
+use crate::read_file_tool::ReadFileTool;
+use crate::run_command_tool::RunCommandTool;
+use crate::search_project_tool::SearchProjectTool;
+
+#[gpui::test]
+async fn test_list_directory(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "README.md": "",
+            "src": {
+                "main.rs": "",
+                "lib.rs": "",
+            },
+            "empty": {},
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/root".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+    assert_eq!(
+        run_tool(ListDirectoryTool, json!({}), &workspace, cx)
+            .await
+            .unwrap(),
+        "root/\n"
+    );
+    assert_eq!(
+        run_tool(ListDirectoryTool, json!({ "path": "root" }), &workspace, cx)
+            .await
+            .unwrap(),
+        "root/README.md\nroot/empty/\nroot/src/\n"
+    );
+    assert_eq!(
+        run_tool(
+            ListDirectoryTool,
+            json!({ "path": "root/src/" }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap(),
+        "root/src/lib.rs\nroot/src/main.rs\n"
+    );
+    assert_eq!(
+        run_tool(
+            ListDirectoryTool,
+            json!({ "path": "root/empty" }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap(),
+        "root/empty is empty."
+    );
+    assert_eq!(
+        run_tool(
+            ListDirectoryTool,
+            json!({ "path": "root/README.md" }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap_err()
+        .to_string(),
+        "root/README.md is not a directory"
+    );
+    assert_eq!(
+        run_tool(
+            ListDirectoryTool,
+            json!({ "path": "other" }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap_err()
+        .to_string(),
+        "other is not in the project"
+    );
+}
+
+#[gpui::test]
+async fn test_read_file(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "a.txt": "one\ntwo\nthree\nfour\nfive\n",
+            ".env": "TOKEN=secret\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/root".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+    assert_eq!(
+        run_tool(
+            ReadFileTool,
+            json!({ "path": "root/a.txt" }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap(),
+        "Lines 1-5 of 5 in root/a.txt:\none\ntwo\nthree\nfour\nfive\n"
+    );
+    assert_eq!(
+        run_tool(
+            ReadFileTool,
+            json!({ "path": "root/a.txt", "start_line": 2, "end_line": 3 }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap(),
+        "Lines 2-3 of 5 in root/a.txt:\ntwo\nthree\n"
+    );
+    assert_eq!(
+        run_tool(
+            ReadFileTool,
+            json!({ "path": "root/a.txt", "start_line": 4, "end_line": 100 }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap(),
+        "Lines 4-5 of 5 in root/a.txt:\nfour\nfive\n"
+    );
+    assert_eq!(
+        run_tool(
+            ReadFileTool,
+            json!({ "path": "root/a.txt", "start_line": 7 }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap(),
+        "root/a.txt has 5 lines, there is nothing to read at line 7."
+    );
+    assert_eq!(
+        run_tool(
+            ReadFileTool,
+            json!({ "path": "other/a.txt" }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap_err()
+        .to_string(),
+        "other/a.txt is not in the project"
+    );
+    assert_eq!(
+        run_tool(ReadFileTool, json!({ "path": "root/.env" }), &workspace, cx)
+            .await
+            .unwrap_err()
+            .to_string(),
+        "root/.env is private, so it can't be read"
+    );
+}
+
+#[gpui::test]
+async fn test_search_project(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "README.md": "Call Helper.\n",
+            ".env": "HELPER_TOKEN=secret\n",
+            "src": {
+                "main.rs": "fn main() {\n    helper();\n    helper();\n}\n",
+                "lib.rs": "pub fn helper() {}\n",
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/root".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+    // Several matches on a line are reported once, and private files are skipped.
+    assert_eq!(
+        sorted_lines(
+            run_tool(
+                SearchProjectTool,
+                json!({ "query": "helper" }),
+                &workspace,
+                cx
+            )
+            .await
+            .unwrap()
+        ),
+        [
+            "root/README.md:1: Call Helper.",
+            "root/src/lib.rs:1: pub fn helper() {}",
+            "root/src/main.rs:2: helper();",
+            "root/src/main.rs:3: helper();",
+        ]
+    );
+    assert_eq!(
+        sorted_lines(
+            run_tool(
+                SearchProjectTool,
+                json!({ "query": "Helper", "case_sensitive": true }),
+                &workspace,
+                cx
+            )
+            .await
+            .unwrap()
+        ),
+        ["root/README.md:1: Call Helper."]
+    );
+    assert_eq!(
+        sorted_lines(
+            run_tool(
+                SearchProjectTool,
+                json!({ "query": "fn \\w+", "regex": true, "include": ["*.rs"] }),
+                &workspace,
+                cx
+            )
+            .await
+            .unwrap()
+        ),
+        [
+            "root/src/lib.rs:1: pub fn helper() {}",
+            "root/src/main.rs:1: fn main() {",
+        ]
+    );
+    assert_eq!(
+        run_tool(
+            SearchProjectTool,
+            json!({ "query": "missing" }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap(),
+        "No matches found."
+    );
+}
+
+#[gpui::test]
+async fn test_find_symbols(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/root", json!({ "src": { "lib.rs": "" } }))
+        .await;
+    let project = Project::test(fs, ["/root".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(Arc::new(Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        None,
+    )));
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+    let _buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/root/src/lib.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_symbols = [
+        symbol("helper", "/root/src/lib.rs", 2),
+        symbol("Helpers", "/root/src/lib.rs", 9),
+        symbol("main", "/root/src/lib.rs", 14),
+    ];
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::WorkspaceSymbolRequest, _, _>(
+        move |params: lsp::WorkspaceSymbolParams, _| {
+            let symbols = fake_symbols
+                .iter()
+                .filter(|symbol| {
+                    symbol
+                        .name
+                        .to_lowercase()
+                        .contains(&params.query.to_lowercase())
+                })
+                .cloned()
+                .collect();
+            async move { Ok(Some(lsp::WorkspaceSymbolResponse::Flat(symbols))) }
+        },
+    );
+
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+    assert_eq!(
+        run_tool(FindSymbolsTool, json!({ "query": "help" }), &workspace, cx)
+            .await
+            .unwrap(),
+        "root/src/lib.rs:3: helper\nroot/src/lib.rs:10: Helpers\n"
+    );
+    assert_eq!(
+        run_tool(
+            FindSymbolsTool,
+            json!({ "query": "missing" }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap(),
+        "No symbols match missing."
+    );
+}
+
+#[gpui::test]
+async fn test_edit_files(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    let main_rs = "fn main() {\n    println!(\"hello\");\n}\n";
+    fs.insert_tree(
+        "/root",
+        json!({
+            "src": {
+                "main.rs": main_rs,
+                "lib.rs": "pub fn one() {}\npub fn two() {}\n",
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+    let action_log = cx.new_model(|_| ActionLog::new());
+    let output = cx
+        .update(|cx| {
+            Arc::new(EditFilesTool).run(
+                json!({
+                    "title": "Say goodbye",
+                    "edits": [
+                        {
+                            "path": "root/src/main.rs",
+                            "old_text": "\"hello\"",
+                            "new_text": "\"goodbye\"",
+                        },
+                        {
+                            "path": "root/src/main.rs",
+                            "old_text": "fn main()",
+                            "new_text": "pub fn main()",
+                        },
+                    ],
+                }),
+                workspace.downgrade(),
+                action_log.clone(),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        output,
+        "Proposed 2 edits to 1 files. They are open for the user to review, and the files only change once the user accepts them."
+    );
+
+    // The edits are proposed in a review editor, and the files don't change until they are applied.
+    assert!(action_log.read_with(cx, |action_log, _| action_log.has_proposed_changes()));
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/root/src/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let editor = workspace
+        .read_with(cx, |workspace, cx| {
+            workspace.active_item_as::<ProposedChangesEditor>(cx)
+        })
+        .unwrap();
+    let branch = editor
+        .read_with(cx, |editor, _| editor.branch_buffer_for_base(&buffer))
+        .unwrap();
+    assert_eq!(
+        branch.read_with(cx, |branch, _| branch.text()),
+        "pub fn main() {\n    println!(\"goodbye\");\n}\n"
+    );
+    assert_eq!(buffer.read_with(cx, |buffer, _| buffer.text()), main_rs);
+    assert_eq!(
+        fs.load(Path::new("/root/src/main.rs")).await.unwrap(),
+        main_rs
+    );
+
+    for (edit, error) in [
+        (
+            json!({ "path": "root/src/lib.rs", "old_text": "three", "new_text": "" }),
+            "the old text of an edit was not found in root/src/lib.rs",
+        ),
+        (
+            json!({ "path": "root/src/lib.rs", "old_text": "pub fn", "new_text": "fn" }),
+            "the old text of an edit appears more than once in root/src/lib.rs, include more lines to make it unique",
+        ),
+        (
+            json!({ "path": "root/src/lib.rs", "old_text": "", "new_text": "fn" }),
+            "the old text of an edit to root/src/lib.rs is empty",
+        ),
+        (
+            json!({ "path": "other/lib.rs", "old_text": "one", "new_text": "two" }),
+            "other/lib.rs is not in the project",
+        ),
+    ] {
+        assert_eq!(
+            run_tool(
+                EditFilesTool,
+                json!({ "title": "Fail", "edits": [edit] }),
+                &workspace,
+                cx
+            )
+            .await
+            .unwrap_err()
+            .to_string(),
+            error
+        );
+    }
+    assert_eq!(
+        run_tool(
+            EditFilesTool,
+            json!({
+                "title": "Overlap",
+                "edits": [
+                    { "path": "root/src/lib.rs", "old_text": "one() {}\npub", "new_text": "" },
+                    { "path": "root/src/lib.rs", "old_text": "pub fn two", "new_text": "" },
+                ],
+            }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap_err()
+        .to_string(),
+        "some edits to the same file overlap"
+    );
+}
+
+#[gpui::test]
+async fn test_run_command_errors(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/root", json!({ "src": {} })).await;
+    let project = Project::test(fs, ["/root".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+    assert_eq!(
+        run_tool(RunCommandTool, json!({ "command": "ls" }), &workspace, cx)
+            .await
+            .unwrap_err()
+            .to_string(),
+        "there is no terminal panel to run the command in"
+    );
+
+    workspace.update(cx, |workspace, cx| {
+        let terminal_panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
+        workspace.add_panel(terminal_panel, cx);
+    });
+    assert_eq!(
+        run_tool(
+            RunCommandTool,
+            json!({ "command": "ls", "cwd": "other" }),
+            &workspace,
+            cx
+        )
+        .await
+        .unwrap_err()
+        .to_string(),
+        "other is not a directory of the project"
+    );
+}
+
+fn init_test(cx: &mut TestAppContext) {
+    cx.update(|cx| {
+        let settings_store = SettingsStore::test(cx);
+        cx.set_global(settings_store);
+        theme::init(theme::LoadThemes::JustBase, cx);
+        release_channel::init(SemanticVersion::default(), cx);
+        language::init(cx);
+        Project::init_settings(cx);
+        workspace::init_settings(cx);
+        editor::init(cx);
+        terminal::init(cx);
+    });
+}
+
+async fn run_tool(
+    tool: impl Tool,
+    input: serde_json::Value,
+    workspace: &View<Workspace>,
+    cx: &mut VisualTestContext,
+) -> Result<String> {
+    let action_log = cx.new_model(|_| ActionLog::new());
+    cx.update(|cx| Arc::new(tool).run(input, workspace.downgrade(), action_log, cx))
+        .await
+}
+
+/// Sorts the lines of a tool's output, as the search results arrive in any order.
+fn sorted_lines(output: String) -> Vec<String> {
+    let mut lines = output.lines().map(str::to_string).collect::<Vec<_>>();
+    lines.sort();
+    lines
+}
+
+fn symbol(name: &str, path: &str, line: u32) -> lsp::SymbolInformation {
+    #[allow(deprecated)]
+    lsp::SymbolInformation {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        deprecated: None,
+        container_name: None,
+        location: lsp::Location::new(
+            lsp::Url::from_file_path(path).unwrap(),
+            lsp::Range::new(lsp::Position::new(line, 0), lsp::Position::new(line, 0)),
+        ),
+    }
+}
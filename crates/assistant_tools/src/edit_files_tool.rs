@@ -0,0 +1,184 @@
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use assistant_tool::{ActionLog, Tool, ToolPermission};
+use editor::{ProposedChangeLocation, ProposedChangesEditor};
+use gpui::{Model, Task, WeakView, WindowContext};
+use language::{Buffer, Point};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+/// The lines shown around every edit in the review editor.
+const CONTEXT_LINES: u32 = 3;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EditFilesToolInput {
+    /// A short title describing the changes, shown to the user when reviewing them.
+    title: String,
+    /// The edits to make.
+    edits: Vec<FileEdit>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FileEdit {
+    /// The path of the file, starting with the name of one of the project's root directories.
+    path: String,
+    /// The text to replace. It must appear exactly once in the file,
+    /// so include enough surrounding lines to make it unique.
+    old_text: String,
+    /// The text to replace it with.
+    new_text: String,
+}
+
+pub struct EditFilesTool;
+
+impl Tool for EditFilesTool {
+    fn name(&self) -> String {
+        "edit_files".into()
+    }
+
+    fn description(&self) -> String {
+        "Proposes edits to existing files of the project, each replacing a piece of text with another. The edits are opened as a diff that the user reviews before they are applied, so they are not visible in the files right away.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(EditFilesToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Always
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        action_log: Model<ActionLog>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: EditFilesToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(project) = workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+
+        let mut open_buffers = Vec::new();
+        for edit in input.edits {
+            if edit.old_text.is_empty() {
+                return Task::ready(Err(anyhow!(
+                    "the old text of an edit to {} is empty",
+                    edit.path
+                )));
+            }
+            let Some(project_path) = project
+                .read(cx)
+                .find_project_path(Path::new(&edit.path), cx)
+            else {
+                return Task::ready(Err(anyhow!("{} is not in the project", edit.path)));
+            };
+            let open_buffer =
+                project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+            open_buffers.push((edit, open_buffer));
+        }
+
+        cx.spawn(|mut cx| async move {
+            let mut edits_by_buffer: Vec<(Model<Buffer>, Vec<(Range<usize>, String)>)> =
+                Vec::new();
+            let mut edit_count = 0;
+            for (edit, open_buffer) in open_buffers {
+                let buffer = open_buffer.await?;
+                let text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+                let mut matches = text.match_indices(&edit.old_text);
+                let Some((start, _)) = matches.next() else {
+                    bail!("the old text of an edit was not found in {}", edit.path);
+                };
+                if matches.next().is_some() {
+                    bail!(
+                        "the old text of an edit appears more than once in {}, include more lines to make it unique",
+                        edit.path
+                    );
+                }
+
+                let range = start..start + edit.old_text.len();
+                match edits_by_buffer
+                    .iter_mut()
+                    .find(|(existing_buffer, _)| *existing_buffer == buffer)
+                {
+                    Some((_, edits)) => edits.push((range, edit.new_text)),
+                    None => edits_by_buffer.push((buffer, vec![(range, edit.new_text)])),
+                }
+                edit_count += 1;
+            }
+
+            let mut locations = Vec::new();
+            for (buffer, edits) in &mut edits_by_buffer {
+                edits.sort_by_key(|(range, _)| range.start);
+                if edits
+                    .windows(2)
+                    .any(|pair| pair[0].0.end > pair[1].0.start)
+                {
+                    bail!("some edits to the same file overlap");
+                }
+
+                let ranges = buffer.read_with(&cx, |buffer, _| {
+                    let snapshot = buffer.snapshot();
+                    let max_row = snapshot.max_point().row;
+                    let mut ranges: Vec<Range<Point>> = Vec::new();
+                    for (range, _) in edits.iter() {
+                        let start_row = snapshot
+                            .offset_to_point(range.start)
+                            .row
+                            .saturating_sub(CONTEXT_LINES);
+                        let end_row =
+                            (snapshot.offset_to_point(range.end).row + CONTEXT_LINES).min(max_row);
+                        let context_range = Point::new(start_row, 0)
+                            ..Point::new(end_row, snapshot.line_len(end_row));
+                        match ranges.last_mut() {
+                            Some(last) if last.end.row + 1 >= start_row => {
+                                last.end = context_range.end
+                            }
+                            _ => ranges.push(context_range),
+                        }
+                    }
+                    ranges
+                })?;
+                locations.push(ProposedChangeLocation {
+                    buffer: buffer.clone(),
+                    ranges,
+                });
+            }
+
+            let file_count = edits_by_buffer.len();
+            let editor = cx.new_view(|cx| {
+                let editor =
+                    ProposedChangesEditor::new(input.title, locations, Some(project.clone()), cx);
+                for (buffer, edits) in edits_by_buffer {
+                    if let Some(branch) = editor.branch_buffer_for_base(&buffer) {
+                        branch.update(cx, |branch, cx| branch.edit(edits, None, cx));
+                    }
+                }
+                editor.recalculate_all_buffer_diffs();
+                editor
+            })?;
+            action_log.update(&mut cx, |action_log, _| {
+                action_log.push_review_editor(editor.clone())
+            })?;
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.add_item_to_active_pane(Box::new(editor), None, false, cx)
+            })?;
+
+            Ok(format!(
+                "Proposed {edit_count} edits to {file_count} files. They are open for the user to review, and the files only change once the user accepts them."
+            ))
+        })
+    }
+}
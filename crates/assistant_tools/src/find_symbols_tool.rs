@@ -0,0 +1,93 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{ActionLog, Tool, ToolPermission};
+use gpui::{Model, Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+/// The most symbols returned, so that broad queries don't fill the context window.
+const MAX_SYMBOLS: usize = 50;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindSymbolsToolInput {
+    /// The name of the symbol, or a part of it.
+    query: String,
+}
+
+pub struct FindSymbolsTool;
+
+impl Tool for FindSymbolsTool {
+    fn name(&self) -> String {
+        "find_symbols".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Looks up functions, types and other symbols by name in the project's language servers. Returns their signatures with the paths and line numbers where they are defined, at most {MAX_SYMBOLS} of them.")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(FindSymbolsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Always
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        _action_log: Model<ActionLog>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: FindSymbolsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let project = workspace.read(cx).project().clone();
+        let symbols = project.update(cx, |project, cx| project.symbols(&input.query, cx));
+
+        cx.spawn(|cx| async move {
+            let symbols = symbols.await?;
+            if symbols.is_empty() {
+                return Ok(format!("No symbols match {}.", input.query));
+            }
+
+            project.read_with(&cx, |project, cx| {
+                let mut output = String::new();
+                for symbol in symbols.iter().take(MAX_SYMBOLS) {
+                    let Some(worktree) = project.worktree_for_id(symbol.path.worktree_id, cx)
+                    else {
+                        continue;
+                    };
+                    let path = Path::new(worktree.read(cx).root_name()).join(&symbol.path.path);
+                    writeln!(
+                        output,
+                        "{}:{}: {}",
+                        path.display(),
+                        symbol.range.start.0.row + 1,
+                        symbol.label.text
+                    )
+                    .ok();
+                }
+                if symbols.len() > MAX_SYMBOLS {
+                    writeln!(
+                        output,
+                        "There are {} more symbols, narrow the query to see them.",
+                        symbols.len() - MAX_SYMBOLS
+                    )
+                    .ok();
+                }
+                output
+            })
+        })
+    }
+}
@@ -0,0 +1,89 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{ActionLog, Tool, ToolPermission};
+use gpui::{Model, Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListDirectoryToolInput {
+    /// The path of the directory to list, starting with the name of one of the project's root directories.
+    /// Leave it empty to list the root directories of the project.
+    #[serde(default)]
+    path: String,
+}
+
+pub struct ListDirectoryTool;
+
+impl Tool for ListDirectoryTool {
+    fn name(&self) -> String {
+        "list_directory".into()
+    }
+
+    fn description(&self) -> String {
+        "Lists the files and directories in a directory of the project. Directories end with a slash.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ListDirectoryToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Always
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        _action_log: Model<ActionLog>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ListDirectoryToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let project = workspace.read(cx).project().read(cx);
+
+        let mut output = String::new();
+        let path = input.path.trim_matches('/');
+        if path.is_empty() {
+            for worktree in project.visible_worktrees(cx) {
+                writeln!(output, "{}/", worktree.read(cx).root_name()).ok();
+            }
+            return Task::ready(Ok(output));
+        }
+
+        let Some(project_path) = project.find_project_path(Path::new(path), cx) else {
+            return Task::ready(Err(anyhow!("{path} is not in the project")));
+        };
+        let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("{path} is not in the project")));
+        };
+        let worktree = worktree.read(cx);
+        match worktree.entry_for_path(&project_path.path) {
+            Some(entry) if entry.is_dir() => {}
+            Some(_) => return Task::ready(Err(anyhow!("{path} is not a directory"))),
+            None => return Task::ready(Err(anyhow!("{path} does not exist"))),
+        }
+
+        let root_name = Path::new(worktree.root_name());
+        for entry in worktree.child_entries(&project_path.path) {
+            let suffix = if entry.is_dir() { "/" } else { "" };
+            writeln!(output, "{}{suffix}", root_name.join(&entry.path).display()).ok();
+        }
+        if output.is_empty() {
+            output = format!("{path} is empty.");
+        }
+
+        Task::ready(Ok(output))
+    }
+}
@@ -1,9 +1,9 @@
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use assistant_tool::Tool;
+use assistant_tool::{ActionLog, Tool, ToolPermission};
 use chrono::{Local, Utc};
-use gpui::{Task, WeakView, WindowContext};
+use gpui::{Model, Task, WeakView, WindowContext};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -38,10 +38,15 @@ impl Tool for NowTool {
         serde_json::to_value(&schema).unwrap()
     }
 
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Always
+    }
+
     fn run(
         self: Arc<Self>,
         input: serde_json::Value,
         _workspace: WeakView<workspace::Workspace>,
+        _action_log: Model<ActionLog>,
         _cx: &mut WindowContext,
     ) -> Task<Result<String>> {
         let input: FileToolInput = match serde_json::from_value(input) {
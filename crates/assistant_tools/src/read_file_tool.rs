@@ -0,0 +1,111 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{ActionLog, Tool, ToolPermission};
+use gpui::{Model, Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use workspace::Workspace;
+
+/// The most lines returned at once, so that large files don't fill the context window.
+const MAX_LINES: u32 = 1000;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadFileToolInput {
+    /// The path of the file, starting with the name of one of the project's root directories.
+    path: String,
+    /// The first line to read, starting at 1. Defaults to the beginning of the file.
+    #[serde(default)]
+    start_line: Option<u32>,
+    /// The last line to read, inclusive. Defaults to the end of the file.
+    #[serde(default)]
+    end_line: Option<u32>,
+}
+
+pub struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> String {
+        "read_file".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Reads the contents of a file in the project, or a range of its lines. At most {MAX_LINES} lines are returned at once. Private files, such as `.env` files and keys, can't be read.")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ReadFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Always
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        _action_log: Model<ActionLog>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ReadFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let project = workspace.read(cx).project().clone();
+        let Some(project_path) = project
+            .read(cx)
+            .find_project_path(Path::new(&input.path), cx)
+        else {
+            return Task::ready(Err(anyhow!("{} is not in the project", input.path)));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            // Private files are never sent to the model, whatever the tool's permission.
+            let (is_private, text) = buffer.read_with(&cx, |buffer, _| {
+                (
+                    buffer.file().map_or(false, |file| file.is_private()),
+                    buffer.text(),
+                )
+            })?;
+            if is_private {
+                return Err(anyhow!("{} is private, so it can't be read", input.path));
+            }
+
+            let line_count = text.lines().count() as u32;
+            let start_line = input.start_line.unwrap_or(1).max(1);
+            let end_line = input
+                .end_line
+                .unwrap_or(line_count)
+                .min(line_count)
+                .min(start_line.saturating_add(MAX_LINES - 1));
+            if start_line > end_line {
+                return Ok(format!(
+                    "{} has {line_count} lines, there is nothing to read at line {start_line}.",
+                    input.path
+                ));
+            }
+
+            let mut output = format!(
+                "Lines {start_line}-{end_line} of {line_count} in {}:\n",
+                input.path
+            );
+            for line in text
+                .lines()
+                .skip(start_line as usize - 1)
+                .take((end_line - start_line + 1) as usize)
+            {
+                writeln!(output, "{line}").ok();
+            }
+            Ok(output)
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tool::{ActionLog, Tool};
+use futures::future::{self, Either};
+use gpui::{Model, Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use task::{HideStrategy, RevealStrategy, TaskContext, TaskTemplate};
+use terminal::TaskStatus;
+use terminal_view::terminal_panel::TerminalPanel;
+use workspace::Workspace;
+
+/// How long the command may run when the input doesn't say.
+const DEFAULT_TIMEOUT_SECONDS: u64 = 120;
+/// The most output lines returned, counting from the end of the output.
+const MAX_OUTPUT_LINES: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunCommandToolInput {
+    /// The command line to run in a shell.
+    command: String,
+    /// The directory to run it in, starting with the name of one of the project's root directories.
+    /// Defaults to the first root directory of the project.
+    #[serde(default)]
+    cwd: Option<String>,
+    /// How many seconds to wait for the command to finish. Defaults to 120.
+    #[serde(default)]
+    timeout_seconds: Option<u64>,
+}
+
+pub struct RunCommandTool;
+
+impl Tool for RunCommandTool {
+    fn name(&self) -> String {
+        "run_command".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Runs a shell command in a new terminal of the project and waits for it to finish. Returns whether it succeeded and the last {MAX_OUTPUT_LINES} lines of its output. Commands that keep running, like servers or watchers, are left running after the timeout.")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(RunCommandToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        _action_log: Model<ActionLog>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: RunCommandToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let Some(terminal_panel) = workspace.read(cx).panel::<TerminalPanel>(cx) else {
+            return Task::ready(Err(anyhow!(
+                "there is no terminal panel to run the command in"
+            )));
+        };
+
+        let project = workspace.read(cx).project().read(cx);
+        let cwd: Option<PathBuf> = match &input.cwd {
+            Some(cwd) => project
+                .find_project_path(Path::new(cwd), cx)
+                .and_then(|project_path| project.absolute_path(&project_path, cx)),
+            None => project
+                .visible_worktrees(cx)
+                .next()
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf()),
+        };
+        let Some(cwd) = cwd else {
+            return Task::ready(Err(anyhow!(
+                "{} is not a directory of the project",
+                input.cwd.unwrap_or_default()
+            )));
+        };
+
+        let template = TaskTemplate {
+            label: format!("Assistant: {}", input.command),
+            command: input.command.clone(),
+            use_new_terminal: true,
+            allow_concurrent_runs: true,
+            reveal: RevealStrategy::NoFocus,
+            hide: HideStrategy::Never,
+            ..TaskTemplate::default()
+        };
+        let task_context = TaskContext {
+            cwd: Some(cwd),
+            ..TaskContext::default()
+        };
+        let Some(spawn_in_terminal) = template
+            .resolve_task("assistant", &task_context)
+            .and_then(|task| task.resolved)
+        else {
+            return Task::ready(Err(anyhow!("cannot run {}", input.command)));
+        };
+        let spawn_task = terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.spawn_task(&spawn_in_terminal, cx)
+        });
+
+        let timeout = Duration::from_secs(input.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS));
+        cx.spawn(|cx| async move {
+            let terminal = spawn_task
+                .await
+                .with_context(|| format!("running {}", input.command))?;
+            let completed =
+                terminal.read_with(&cx, |terminal, cx| terminal.wait_for_completed_task(cx))?;
+            let timer = cx.background_executor().timer(timeout);
+            let timed_out = matches!(future::select(completed, timer).await, Either::Right(_));
+
+            terminal.read_with(&cx, |terminal, _| {
+                let status = match terminal.task().map(|task| &task.status) {
+                    Some(TaskStatus::Completed { success: true }) => "The command succeeded.",
+                    Some(TaskStatus::Completed { success: false }) => "The command failed.",
+                    _ if timed_out => "The command is still running after the timeout.",
+                    _ => "The command stopped.",
+                };
+                let mut lines = terminal.last_n_non_empty_lines(MAX_OUTPUT_LINES);
+                lines.reverse();
+                if lines.is_empty() {
+                    format!("{status} It printed nothing.")
+                } else {
+                    format!("{status} Its output:\n{}", lines.join("\n"))
+                }
+            })
+        })
+    }
+}
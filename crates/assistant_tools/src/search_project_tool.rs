@@ -0,0 +1,155 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::{ActionLog, Tool, ToolPermission};
+use gpui::{Model, Task, WeakView, WindowContext};
+use language::{Point, ToPoint as _};
+use project::search::{SearchQuery, SearchResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+use workspace::Workspace;
+
+/// The most matches returned, so that broad queries don't fill the context window.
+const MAX_MATCHES: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchProjectToolInput {
+    /// The text to search for.
+    query: String,
+    /// Whether the query is a regular expression.
+    #[serde(default)]
+    regex: bool,
+    /// Whether the search distinguishes upper and lower case.
+    #[serde(default)]
+    case_sensitive: bool,
+    /// Globs of the paths to search in, e.g. `src/**/*.rs`. Defaults to all files.
+    #[serde(default)]
+    include: Vec<String>,
+}
+
+pub struct SearchProjectTool;
+
+impl Tool for SearchProjectTool {
+    fn name(&self) -> String {
+        "search_project".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Searches the files of the project for text or a regular expression, skipping ignored and private files. Returns the matching lines with their paths and line numbers, at most {MAX_MATCHES} of them.")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SearchProjectToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn default_permission(&self) -> ToolPermission {
+        ToolPermission::Always
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<Workspace>,
+        _action_log: Model<ActionLog>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: SearchProjectToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let Some(workspace) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow!("workspace was dropped")));
+        };
+        let query = PathMatcher::new(&input.include)
+            .map_err(|err| anyhow!(err))
+            .and_then(|files_to_include| {
+                if input.regex {
+                    SearchQuery::regex(
+                        &input.query,
+                        false,
+                        input.case_sensitive,
+                        false,
+                        files_to_include,
+                        PathMatcher::default(),
+                        None,
+                    )
+                } else {
+                    SearchQuery::text(
+                        &input.query,
+                        false,
+                        input.case_sensitive,
+                        false,
+                        files_to_include,
+                        PathMatcher::default(),
+                        None,
+                    )
+                }
+            });
+        let query = match query {
+            Ok(query) => query,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let results = workspace
+            .read(cx)
+            .project()
+            .update(cx, |project, cx| project.search(query, cx));
+
+        cx.spawn(|cx| async move {
+            let mut output = String::new();
+            let mut match_count = 0;
+            let mut truncated = false;
+            while let Ok(result) = results.recv().await {
+                match result {
+                    SearchResult::Buffer { buffer, ranges } => {
+                        buffer.read_with(&cx, |buffer, cx| {
+                            // Private files are never sent to the model, whatever the
+                            // tool's permission.
+                            let Some(path) = buffer
+                                .file()
+                                .filter(|file| !file.is_private())
+                                .map(|file| file.full_path(cx))
+                            else {
+                                return;
+                            };
+                            let snapshot = buffer.snapshot();
+                            let mut last_row = None;
+                            for range in ranges {
+                                let row = range.start.to_point(&snapshot).row;
+                                if last_row == Some(row) {
+                                    continue;
+                                }
+                                last_row = Some(row);
+                                if match_count == MAX_MATCHES {
+                                    truncated = true;
+                                    return;
+                                }
+                                let line = snapshot
+                                    .text_for_range(
+                                        Point::new(row, 0)..Point::new(row, snapshot.line_len(row)),
+                                    )
+                                    .collect::<String>();
+                                writeln!(output, "{}:{}: {}", path.display(), row + 1, line.trim())
+                                    .ok();
+                                match_count += 1;
+                            }
+                        })?;
+                    }
+                    SearchResult::LimitReached => truncated = true,
+                }
+                if truncated {
+                    break;
+                }
+            }
+
+            if output.is_empty() {
+                output = "No matches found.".into();
+            } else if truncated {
+                output.push_str("There are more matches, narrow the search to see them.\n");
+            }
+            Ok(output)
+        })
+    }
+}
@@ -133,6 +133,8 @@ pub struct CommandPaletteDelegate {
     commands: Vec<Command>,
     matches: Vec<StringMatch>,
     selected_ix: usize,
+    /// Commands from this index onwards were produced by the [`CommandPaletteInterceptor`].
+    first_intercepted_command: usize,
     telemetry: Arc<Telemetry>,
     previous_focus_handle: FocusHandle,
     updating_matches: Option<(
@@ -170,12 +172,14 @@ impl CommandPaletteDelegate {
         telemetry: Arc<Telemetry>,
         previous_focus_handle: FocusHandle,
     ) -> Self {
+        let first_intercepted_command = commands.len();
         Self {
             command_palette,
             all_commands: commands.clone(),
             matches: vec![],
             commands,
             selected_ix: 0,
+            first_intercepted_command,
             telemetry,
             previous_focus_handle,
             updating_matches: None,
@@ -191,27 +195,29 @@ impl CommandPaletteDelegate {
     ) {
         self.updating_matches.take();
 
-        let mut intercept_result = CommandPaletteInterceptor::try_global(cx)
-            .and_then(|interceptor| interceptor.intercept(&query, cx));
+        let mut intercept_results = CommandPaletteInterceptor::try_global(cx)
+            .map(|interceptor| interceptor.intercept(&query, cx))
+            .unwrap_or_default();
 
         if parse_zed_link(&query, cx).is_some() {
-            intercept_result = Some(CommandInterceptResult {
+            intercept_results = vec![CommandInterceptResult {
                 action: OpenZedUrl { url: query.clone() }.boxed_clone(),
                 string: query.clone(),
                 positions: vec![],
-            })
+            }];
         }
 
-        if let Some(CommandInterceptResult {
-            action,
-            string,
-            positions,
-        }) = intercept_result
-        {
-            if let Some(idx) = matches
-                .iter()
-                .position(|m| commands[m.candidate_id].action.type_id() == action.type_id())
-            {
+        self.first_intercepted_command = commands.len();
+        for (ix, intercept_result) in intercept_results.into_iter().enumerate() {
+            let CommandInterceptResult {
+                action,
+                string,
+                positions,
+            } = intercept_result;
+            if let Some(idx) = matches.iter().position(|m| {
+                m.candidate_id < self.first_intercepted_command
+                    && commands[m.candidate_id].action.type_id() == action.type_id()
+            }) {
                 matches.remove(idx);
             }
             commands.push(Command {
@@ -219,7 +225,7 @@ impl CommandPaletteDelegate {
                 action,
             });
             matches.insert(
-                0,
+                ix,
                 StringMatch {
                     candidate_id: commands.len() - 1,
                     string,
@@ -357,6 +363,18 @@ impl PickerDelegate for CommandPaletteDelegate {
         }
     }
 
+    fn confirm_completion(
+        &mut self,
+        _query: String,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<String> {
+        let candidate_id = self.matches.get(self.selected_ix)?.candidate_id;
+        if candidate_id < self.first_intercepted_command {
+            return None;
+        }
+        Some(self.commands.get(candidate_id)?.name.clone())
+    }
+
     fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
         self.command_palette
             .update(cx, |_, cx| cx.emit(DismissEvent))
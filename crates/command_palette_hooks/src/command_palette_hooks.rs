@@ -95,7 +95,7 @@ pub struct CommandInterceptResult {
 /// An interceptor for the command palette.
 #[derive(Default)]
 pub struct CommandPaletteInterceptor(
-    Option<Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>>,
+    Option<Box<dyn Fn(&str, &AppContext) -> Vec<CommandInterceptResult>>>,
 );
 
 #[derive(Default)]
@@ -119,8 +119,13 @@ impl CommandPaletteInterceptor {
     }
 
     /// Intercepts the given query from the command palette.
-    pub fn intercept(&self, query: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
-        let handler = self.0.as_ref()?;
+    ///
+    /// The results are shown above the other matches, in order. Confirming a completion
+    /// on one of them replaces the query with its string.
+    pub fn intercept(&self, query: &str, cx: &AppContext) -> Vec<CommandInterceptResult> {
+        let Some(handler) = self.0.as_ref() else {
+            return Vec::new();
+        };
 
         (handler)(query, cx)
     }
@@ -133,10 +138,7 @@ impl CommandPaletteInterceptor {
     /// Sets the global interceptor.
     ///
     /// This will override the previous interceptor, if it exists.
-    pub fn set(
-        &mut self,
-        handler: Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>,
-    ) {
+    pub fn set(&mut self, handler: Box<dyn Fn(&str, &AppContext) -> Vec<CommandInterceptResult>>) {
         self.0 = Some(handler);
     }
 }
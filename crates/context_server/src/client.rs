@@ -1,16 +1,14 @@
 use anyhow::{anyhow, Context, Result};
 use collections::HashMap;
-use futures::{channel::oneshot, io::BufWriter, select, AsyncRead, AsyncWrite, FutureExt};
+use context_server_settings::ServerEndpoint;
+use futures::{channel::oneshot, select, FutureExt, StreamExt};
 use gpui::{AsyncAppContext, BackgroundExecutor, Task};
+use http_client::HttpClient;
 use parking_lot::Mutex;
 use postage::barrier;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{value::RawValue, Value};
-use smol::{
-    channel,
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
-    process::Child,
-};
+use smol::channel;
 use std::{
     fmt,
     path::PathBuf,
@@ -22,6 +20,8 @@ use std::{
 };
 use util::TryFutureExt;
 
+use crate::transport::{HttpTransport, StdioTransport, Transport};
+
 const JSON_RPC_VERSION: &str = "2.0";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
 
@@ -34,6 +34,7 @@ pub const INTERNAL_ERROR: i32 = -32603;
 
 type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncAppContext)>;
+type RequestHandler = Box<dyn Send + FnMut(Value, AsyncAppContext) -> Task<Result<Value>>>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -48,6 +49,7 @@ pub struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
@@ -55,7 +57,8 @@ pub struct Client {
     #[allow(dead_code)]
     output_done_rx: Mutex<Option<barrier::Receiver>>,
     executor: BackgroundExecutor,
-    server: Arc<Mutex<Option<Child>>>,
+    #[allow(dead_code)]
+    transport: Arc<dyn Transport>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -79,6 +82,16 @@ struct Request<'a, T> {
     params: T,
 }
 
+#[derive(Deserialize)]
+struct AnyRequest<'a> {
+    #[allow(dead_code)]
+    jsonrpc: &'a str,
+    id: RequestId,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct AnyResponse<'a> {
     jsonrpc: &'a str,
@@ -89,6 +102,16 @@ struct AnyResponse<'a> {
     result: Option<&'a RawValue>,
 }
 
+#[derive(Serialize)]
+struct OutgoingResponse {
+    jsonrpc: &'static str,
+    id: RequestId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
 #[derive(Deserialize)]
 #[allow(dead_code)]
 struct Response<T> {
@@ -125,6 +148,8 @@ struct AnyNotification<'a> {
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Error {
+    #[serde(default)]
+    code: i32,
     message: String,
 }
 
@@ -136,12 +161,11 @@ pub struct ModelContextServerBinary {
 }
 
 impl Client {
-    /// Creates a new Client instance for a context server.
+    /// Creates a new Client instance for a context server that runs as a child process.
     ///
-    /// This function initializes a new Client by spawning a child process for the context server,
-    /// setting up communication channels, and initializing handlers for input/output operations.
+    /// This function spawns the context server and talks to it over its stdin and stdout.
     /// It takes a server ID, binary information, and an async app context as input.
-    pub fn new(
+    pub fn stdio(
         server_id: ContextServerId,
         binary: ModelContextServerBinary,
         cx: AsyncAppContext,
@@ -152,49 +176,83 @@ impl Client {
             &binary.args
         );
 
-        let mut command = util::command::new_smol_command(&binary.executable);
-        command
-            .args(&binary.args)
-            .envs(binary.env.unwrap_or_default())
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
-            .kill_on_drop(true);
-
-        let mut server = command.spawn().with_context(|| {
-            format!(
-                "failed to spawn command. (path={:?}, args={:?})",
-                binary.executable, &binary.args
-            )
-        })?;
+        let server_name = binary
+            .executable
+            .file_name()
+            .map(|name| name.to_string_lossy().into())
+            .unwrap_or_else(|| "".into());
+        let transport = StdioTransport::new(binary, &cx)?;
+        Self::new(server_id, server_name, Arc::new(transport), cx)
+    }
+
+    /// Creates a new Client instance for a remote context server reached over HTTP.
+    pub fn http(
+        server_id: ContextServerId,
+        endpoint: &ServerEndpoint,
+        http_client: Arc<dyn HttpClient>,
+        cx: AsyncAppContext,
+    ) -> Result<Self> {
+        log::info!(
+            "connecting to context server (url={:?}, transport={:?})",
+            endpoint.url,
+            endpoint.transport
+        );
 
-        let stdin = server.stdin.take().unwrap();
-        let stdout = server.stdout.take().unwrap();
-        let stderr = server.stderr.take().unwrap();
+        let transport =
+            HttpTransport::new(endpoint, http_client, cx.background_executor().clone())?;
+        let server_name = transport.url().host_str().unwrap_or_default().into();
+        Self::new(server_id, server_name, Arc::new(transport), cx)
+    }
 
+    /// Creates a new Client instance that exchanges messages with a context server
+    /// over the given transport.
+    ///
+    /// This function sets up communication channels and initializes handlers for
+    /// input/output operations.
+    pub fn new(
+        server_id: ContextServerId,
+        server_name: Arc<str>,
+        transport: Arc<dyn Transport>,
+        cx: AsyncAppContext,
+    ) -> Result<Self> {
         let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
         let (output_done_tx, output_done_rx) = barrier::channel();
 
         let notification_handlers =
             Arc::new(Mutex::new(HashMap::<_, NotificationHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
         let response_handlers =
             Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
 
         let stdout_input_task = cx.spawn({
             let notification_handlers = notification_handlers.clone();
+            let request_handlers = request_handlers.clone();
             let response_handlers = response_handlers.clone();
+            let transport = transport.clone();
+            let outbound_tx = outbound_tx.clone();
             move |cx| {
-                Self::handle_input(stdout, notification_handlers, response_handlers, cx).log_err()
+                Self::handle_input(
+                    transport,
+                    notification_handlers,
+                    request_handlers,
+                    response_handlers,
+                    outbound_tx,
+                    cx,
+                )
+                .log_err()
             }
         });
-        let stderr_input_task = cx.spawn(|_| Self::handle_stderr(stderr).log_err());
+        let stderr_input_task = cx.spawn({
+            let transport = transport.clone();
+            move |_| Self::handle_stderr(transport).log_err()
+        });
         let input_task = cx.spawn(|_| async move {
             let (stdout, stderr) = futures::join!(stdout_input_task, stderr_input_task);
             stdout.or(stderr)
         });
         let output_task = cx.background_executor().spawn({
             Self::handle_output(
-                stdin,
+                transport.clone(),
                 outbound_rx,
                 output_done_tx,
                 response_handlers.clone(),
@@ -202,54 +260,83 @@ impl Client {
             .log_err()
         });
 
-        let mut context_server = Self {
+        Ok(Self {
             server_id,
             notification_handlers,
+            request_handlers,
             response_handlers,
-            name: "".into(),
+            name: server_name,
             next_id: Default::default(),
             outbound_tx,
             executor: cx.background_executor().clone(),
             io_tasks: Mutex::new(Some((input_task, output_task))),
             output_done_rx: Mutex::new(Some(output_done_rx)),
-            server: Arc::new(Mutex::new(Some(server))),
-        };
-
-        if let Some(name) = binary.executable.file_name() {
-            context_server.name = name.to_string_lossy().into();
-        }
-
-        Ok(context_server)
+            transport,
+        })
     }
 
-    /// Handles input from the server's stdout.
+    /// Handles input from the server.
     ///
-    /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
-    async fn handle_input<Stdout>(
-        stdout: Stdout,
+    /// This function continuously receives messages from the transport,
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches them
+    /// to the appropriate handlers. It processes requests (which are answered by
+    /// registered handlers), responses (which are matched to pending requests) and
+    /// notifications (which trigger registered handlers).
+    async fn handle_input(
+        transport: Arc<dyn Transport>,
         notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+        request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: AsyncAppContext,
-    ) -> anyhow::Result<()>
-    where
-        Stdout: AsyncRead + Unpin + Send + 'static,
-    {
-        let mut stdout = BufReader::new(stdout);
-        let mut buffer = String::new();
-
-        loop {
-            buffer.clear();
-            if stdout.read_line(&mut buffer).await? == 0 {
-                return Ok(());
-            }
+    ) -> anyhow::Result<()> {
+        let mut receiver = transport.receive();
 
-            let content = buffer.trim();
+        while let Some(message) = receiver.next().await {
+            let content = message.trim();
 
             if !content.is_empty() {
-                if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
+                if let Ok(request) = serde_json::from_str::<AnyRequest>(content) {
+                    let response = request_handlers
+                        .lock()
+                        .get_mut(request.method.as_str())
+                        .map(|handler| handler(request.params.unwrap_or(Value::Null), cx.clone()));
+                    let (id, method) = (request.id, request.method);
+                    let outbound_tx = outbound_tx.clone();
+                    cx.spawn(|_| {
+                        async move {
+                            let (result, error) = match response {
+                                Some(response) => match response.await {
+                                    Ok(result) => (Some(result), None),
+                                    Err(error) => (
+                                        None,
+                                        Some(Error {
+                                            code: INTERNAL_ERROR,
+                                            message: format!("{error:#}"),
+                                        }),
+                                    ),
+                                },
+                                None => (
+                                    None,
+                                    Some(Error {
+                                        code: METHOD_NOT_FOUND,
+                                        message: format!("method not found: {method}"),
+                                    }),
+                                ),
+                            };
+                            let response = serde_json::to_string(&OutgoingResponse {
+                                jsonrpc: JSON_RPC_VERSION,
+                                id,
+                                result,
+                                error,
+                            })?;
+                            outbound_tx.send(response).await?;
+                            anyhow::Ok(())
+                        }
+                        .log_err()
+                    })
+                    .detach();
+                } else if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
                     if let Some(handlers) = response_handlers.lock().as_mut() {
                         if let Some(handler) = handlers.remove(&response.id) {
                             handler(Ok(content.to_string()));
@@ -267,40 +354,32 @@ impl Client {
 
             smol::future::yield_now().await;
         }
+
+        Ok(())
     }
 
-    /// Handles the stderr output from the context server.
-    /// Continuously reads and logs any error messages from the server.
-    async fn handle_stderr<Stderr>(stderr: Stderr) -> anyhow::Result<()>
-    where
-        Stderr: AsyncRead + Unpin + Send + 'static,
-    {
-        let mut stderr = BufReader::new(stderr);
-        let mut buffer = String::new();
+    /// Handles the diagnostic output of the context server, such as its stderr.
+    /// Continuously receives and logs any error messages from the server.
+    async fn handle_stderr(transport: Arc<dyn Transport>) -> anyhow::Result<()> {
+        let mut receiver = transport.receive_err();
 
-        loop {
-            buffer.clear();
-            if stderr.read_line(&mut buffer).await? == 0 {
-                return Ok(());
-            }
-            log::warn!("context server stderr: {}", buffer.trim());
+        while let Some(line) = receiver.next().await {
+            log::warn!("context server stderr: {}", line.trim());
             smol::future::yield_now().await;
         }
+
+        Ok(())
     }
 
-    /// Handles the output to the context server's stdin.
+    /// Handles the output to the context server.
     /// This function continuously receives messages from the outbound channel,
-    /// writes them to the server's stdin, and manages the lifecycle of response handlers.
-    async fn handle_output<Stdin>(
-        stdin: Stdin,
+    /// sends them over the transport, and manages the lifecycle of response handlers.
+    async fn handle_output(
+        transport: Arc<dyn Transport>,
         outbound_rx: channel::Receiver<String>,
         output_done_tx: barrier::Sender,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
-    ) -> anyhow::Result<()>
-    where
-        Stdin: AsyncWrite + Unpin + Send + 'static,
-    {
-        let mut stdin = BufWriter::new(stdin);
+    ) -> anyhow::Result<()> {
         let _clear_response_handlers = util::defer({
             let response_handlers = response_handlers.clone();
             move || {
@@ -310,9 +389,7 @@ impl Client {
         while let Ok(message) = outbound_rx.recv().await {
             log::trace!("outgoing message: {}", message);
 
-            stdin.write_all(message.as_bytes()).await?;
-            stdin.write_all(b"\n").await?;
-            stdin.flush().await?;
+            transport.send(message).await?;
         }
         drop(output_done_tx);
         Ok(())
@@ -352,7 +429,7 @@ impl Client {
         let send = self
             .outbound_tx
             .try_send(request)
-            .context("failed to write to context server's stdin");
+            .context("failed to send message to context server");
 
         let executor = self.executor.clone();
         let started = Instant::now();
@@ -407,6 +484,14 @@ impl Client {
             .insert(method, Box::new(f));
     }
 
+    /// Registers the handler answering the requests the server sends with the given method.
+    pub fn on_request<F>(&self, method: &'static str, f: F)
+    where
+        F: 'static + Send + FnMut(Value, AsyncAppContext) -> Task<Result<Value>>,
+    {
+        self.request_handlers.lock().insert(method, Box::new(f));
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -416,14 +501,6 @@ impl Client {
     }
 }
 
-impl Drop for Client {
-    fn drop(&mut self) {
-        if let Some(mut server) = self.server.lock().take() {
-            let _ = server.kill();
-        }
-    }
-}
-
 impl fmt::Display for ContextServerId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.0.fmt(f)
@@ -438,3 +515,74 @@ impl fmt::Debug for Client {
             .finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use futures::Stream;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use std::pin::Pin;
+
+    struct FakeTransport {
+        incoming_rx: channel::Receiver<String>,
+        outgoing_tx: channel::Sender<String>,
+    }
+
+    #[async_trait]
+    impl Transport for FakeTransport {
+        async fn send(&self, message: String) -> Result<()> {
+            self.outgoing_tx.send(message).await?;
+            Ok(())
+        }
+
+        fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+            Box::pin(self.incoming_rx.clone())
+        }
+
+        fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+            Box::pin(futures::stream::empty())
+        }
+    }
+
+    #[gpui::test]
+    async fn test_answer_server_requests(cx: &mut TestAppContext) {
+        let (incoming_tx, incoming_rx) = channel::unbounded();
+        let (outgoing_tx, outgoing_rx) = channel::unbounded();
+        let transport = Arc::new(FakeTransport {
+            incoming_rx,
+            outgoing_tx,
+        });
+        let client = Client::new(
+            ContextServerId("test".into()),
+            "test".into(),
+            transport,
+            cx.to_async(),
+        )
+        .unwrap();
+        client.on_request("roots/list", |_, _| {
+            Task::ready(Ok(json!({ "roots": [{ "uri": "file:///project" }] })))
+        });
+
+        incoming_tx
+            .try_send(r#"{"jsonrpc":"2.0","id":5,"method":"roots/list"}"#.into())
+            .unwrap();
+        let response: Value = serde_json::from_str(&outgoing_rx.recv().await.unwrap()).unwrap();
+        assert_eq!(
+            response,
+            json!({
+                "jsonrpc": "2.0",
+                "id": 5,
+                "result": { "roots": [{ "uri": "file:///project" }] },
+            })
+        );
+
+        incoming_tx
+            .try_send(r#"{"jsonrpc":"2.0","id":"a","method":"unknown"}"#.into())
+            .unwrap();
+        let response: Value = serde_json::from_str(&outgoing_rx.recv().await.unwrap()).unwrap();
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
@@ -4,10 +4,14 @@ mod extension_context_server;
 pub mod manager;
 pub mod protocol;
 mod registry;
+pub mod transport;
 pub mod types;
 
 use command_palette_hooks::CommandPaletteFilter;
-pub use context_server_settings::{ContextServerSettings, ServerCommand, ServerConfig};
+pub use context_server_settings::{
+    ContextServerSettings, HttpTransportKind, ServerAuth, ServerCommand, ServerConfig,
+    ServerEndpoint,
+};
 use gpui::{actions, AppContext};
 
 pub use crate::context_server_tool::ContextServerTool;
@@ -52,6 +52,7 @@ impl Tool for ContextServerTool {
         self: std::sync::Arc<Self>,
         input: serde_json::Value,
         _workspace: gpui::WeakView<workspace::Workspace>,
+        _action_log: gpui::Model<assistant_tool::ActionLog>,
         cx: &mut ui::WindowContext,
     ) -> gpui::Task<gpui::Result<String>> {
         if let Some(server) = self.server_manager.read(cx).get_server(&self.server_id) {
@@ -17,15 +17,19 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use collections::HashMap;
 use command_palette_hooks::CommandPaletteFilter;
-use gpui::{AsyncAppContext, EventEmitter, Model, ModelContext, Subscription, Task, WeakModel};
+use futures::channel::oneshot;
+use gpui::{
+    AppContext, AsyncAppContext, EventEmitter, Model, ModelContext, Subscription, Task, WeakModel,
+};
 use log;
 use parking_lot::RwLock;
 use project::Project;
 use settings::{Settings, SettingsStore};
-use util::ResultExt as _;
+use url::Url;
+use util::{post_inc, ResultExt as _};
 
 use crate::{ContextServerSettings, ServerConfig};
 
@@ -61,20 +65,30 @@ impl ContextServer {
         self.client.read().clone()
     }
 
-    pub async fn start(self: Arc<Self>, cx: &AsyncAppContext) -> Result<()> {
+    pub async fn start(
+        self: Arc<Self>,
+        manager: WeakModel<ContextServerManager>,
+        cx: &AsyncAppContext,
+    ) -> Result<()> {
         log::info!("starting context server {}", self.id);
-        let Some(command) = &self.config.command else {
-            bail!("no command specified for server {}", self.id);
+        let server_id = client::ContextServerId(self.id.clone());
+        let client = if let Some(endpoint) = &self.config.endpoint {
+            let http_client = cx.update(|cx| cx.http_client())?;
+            Client::http(server_id, endpoint, http_client, cx.clone())?
+        } else if let Some(command) = &self.config.command {
+            Client::stdio(
+                server_id,
+                client::ModelContextServerBinary {
+                    executable: Path::new(&command.path).to_path_buf(),
+                    args: command.args.clone(),
+                    env: command.env.clone(),
+                },
+                cx.clone(),
+            )?
+        } else {
+            bail!("no command or endpoint specified for server {}", self.id);
         };
-        let client = Client::new(
-            client::ContextServerId(self.id.clone()),
-            client::ModelContextServerBinary {
-                executable: Path::new(&command.path).to_path_buf(),
-                args: command.args.clone(),
-                env: command.env.clone(),
-            },
-            cx.clone(),
-        )?;
+        self.register_handlers(&client, manager);
 
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
@@ -93,6 +107,61 @@ impl ContextServer {
         Ok(())
     }
 
+    /// Answers the requests the server makes to Zed, and forwards its notifications
+    /// about resources to the manager's subscribers.
+    fn register_handlers(&self, client: &Client, manager: WeakModel<ContextServerManager>) {
+        client.on_request(types::RequestType::ListRoots.as_str(), {
+            let manager = manager.clone();
+            move |_, cx| {
+                let manager = manager.clone();
+                cx.spawn(|cx| async move {
+                    let roots = manager.read_with(&cx, |manager, cx| manager.roots(cx))?;
+                    Ok(serde_json::to_value(types::ListRootsResponse {
+                        roots,
+                        meta: None,
+                    })?)
+                })
+            }
+        });
+
+        client.on_request(types::RequestType::CreateMessage.as_str(), {
+            let manager = manager.clone();
+            let server_id = self.id.clone();
+            move |params, cx| {
+                let manager = manager.clone();
+                let server_id = server_id.clone();
+                cx.spawn(|mut cx| async move {
+                    let params = serde_json::from_value::<types::CreateMessageRequest>(params)?;
+                    let response = manager.update(&mut cx, |manager, cx| {
+                        manager.request_sampling(server_id, params, cx)
+                    })?;
+                    let result = response
+                        .await
+                        .map_err(|_| anyhow!("the user declined to sample a message"))??;
+                    Ok(serde_json::to_value(result)?)
+                })
+            }
+        });
+
+        client.on_notification(types::NotificationType::ResourcesUpdated.as_str(), {
+            let server_id = self.id.clone();
+            move |params, mut cx| {
+                if let Some(params) =
+                    serde_json::from_value::<types::ResourcesUpdatedParams>(params).log_err()
+                {
+                    manager
+                        .update(&mut cx, |_, cx| {
+                            cx.emit(Event::ResourceUpdated {
+                                server_id: server_id.clone(),
+                                uri: params.uri,
+                            })
+                        })
+                        .ok();
+                }
+            }
+        });
+    }
+
     pub fn stop(&self) -> Result<()> {
         let mut client = self.client.write();
         if let Some(protocol) = client.take() {
@@ -102,8 +171,28 @@ impl ContextServer {
     }
 }
 
+/// A request from a context server to sample a message from the user's language model,
+/// pending until the user approves or declines it.
+pub struct SamplingRequest {
+    pub id: usize,
+    pub server_id: Arc<str>,
+    pub params: types::CreateMessageRequest,
+    response_tx: oneshot::Sender<Result<types::CreateMessageResult>>,
+}
+
+impl SamplingRequest {
+    /// Sends the sampled message, or the reason sampling failed, back to the server.
+    ///
+    /// Dropping the request without responding declines it.
+    pub fn respond(self, result: Result<types::CreateMessageResult>) {
+        self.response_tx.send(result).ok();
+    }
+}
+
 pub struct ContextServerManager {
     servers: HashMap<Arc<str>, Arc<ContextServer>>,
+    sampling_requests: Vec<SamplingRequest>,
+    next_sampling_request_id: usize,
     project: Model<Project>,
     registry: Model<ContextServerFactoryRegistry>,
     update_servers_task: Option<Task<Result<()>>>,
@@ -112,8 +201,20 @@ pub struct ContextServerManager {
 }
 
 pub enum Event {
-    ServerStarted { server_id: Arc<str> },
-    ServerStopped { server_id: Arc<str> },
+    ServerStarted {
+        server_id: Arc<str>,
+    },
+    ServerStopped {
+        server_id: Arc<str>,
+    },
+    ResourceUpdated {
+        server_id: Arc<str>,
+        uri: Url,
+    },
+    SamplingRequested {
+        server_id: Arc<str>,
+        request_id: usize,
+    },
 }
 
 impl EventEmitter<Event> for ContextServerManager {}
@@ -132,11 +233,19 @@ impl ContextServerManager {
                 cx.observe_global::<SettingsStore>(|this, cx| {
                     this.available_context_servers_changed(cx);
                 }),
+                cx.subscribe(&project, |this, _project, event, _cx| match event {
+                    project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                        this.roots_changed();
+                    }
+                    _ => {}
+                }),
             ],
             project,
             registry,
             needs_server_update: false,
             servers: HashMap::default(),
+            sampling_requests: Vec::new(),
+            next_sampling_request_id: 0,
             update_servers_task: None,
         };
         this.available_context_servers_changed(cx);
@@ -180,6 +289,75 @@ impl ContextServerManager {
             .cloned()
     }
 
+    /// Returns the sampling requests waiting for the user's approval.
+    pub fn sampling_requests(&self) -> &[SamplingRequest] {
+        &self.sampling_requests
+    }
+
+    /// Removes the sampling request, to respond to it.
+    pub fn take_sampling_request(&mut self, request_id: usize) -> Option<SamplingRequest> {
+        let ix = self
+            .sampling_requests
+            .iter()
+            .position(|request| request.id == request_id)?;
+        Some(self.sampling_requests.remove(ix))
+    }
+
+    fn request_sampling(
+        &mut self,
+        server_id: Arc<str>,
+        params: types::CreateMessageRequest,
+        cx: &mut ModelContext<Self>,
+    ) -> oneshot::Receiver<Result<types::CreateMessageResult>> {
+        let (response_tx, response_rx) = oneshot::channel();
+        let request_id = post_inc(&mut self.next_sampling_request_id);
+        self.sampling_requests.push(SamplingRequest {
+            id: request_id,
+            server_id: server_id.clone(),
+            params,
+            response_tx,
+        });
+        cx.emit(Event::SamplingRequested {
+            server_id,
+            request_id,
+        });
+        response_rx
+    }
+
+    /// Returns the project's worktrees, which context servers may operate on.
+    fn roots(&self, cx: &AppContext) -> Vec<types::Root> {
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                Some(types::Root {
+                    uri: Url::from_file_path(worktree.abs_path()).ok()?,
+                    name: Some(worktree.root_name().to_string()),
+                })
+            })
+            .collect()
+    }
+
+    fn roots_changed(&self) {
+        for server in self.servers() {
+            if let Some(protocol) = server.client() {
+                protocol
+                    .notify(
+                        types::NotificationType::RootsListChanged.as_str(),
+                        serde_json::json!({}),
+                    )
+                    .log_err();
+            }
+        }
+    }
+
+    fn server_stopped(&mut self, server_id: Arc<str>, cx: &mut ModelContext<Self>) {
+        self.sampling_requests
+            .retain(|request| request.server_id != server_id);
+        cx.emit(Event::ServerStopped { server_id });
+    }
+
     pub fn restart_server(
         &mut self,
         id: &Arc<str>,
@@ -191,12 +369,10 @@ impl ContextServerManager {
                 server.stop()?;
                 let config = server.config();
                 let new_server = Arc::new(ContextServer::new(id.clone(), config));
-                new_server.clone().start(&cx).await?;
+                new_server.clone().start(this.clone(), &cx).await?;
                 this.update(&mut cx, |this, cx| {
                     this.servers.insert(id.clone(), new_server);
-                    cx.emit(Event::ServerStopped {
-                        server_id: id.clone(),
-                    });
+                    this.server_stopped(id.clone(), cx);
                     cx.emit(Event::ServerStarted {
                         server_id: id.clone(),
                     });
@@ -234,7 +410,7 @@ impl ContextServerManager {
             registry.read_with(&cx, |registry, _| registry.context_server_factories())?
         {
             let config = desired_servers.entry(id).or_default();
-            if config.command.is_none() {
+            if config.command.is_none() && config.endpoint.is_none() {
                 if let Some(extension_command) = factory(project.clone(), &cx).await.log_err() {
                     config.command = Some(extension_command);
                 }
@@ -270,13 +446,11 @@ impl ContextServerManager {
 
         for (id, server) in servers_to_stop {
             server.stop().log_err();
-            this.update(&mut cx, |_, cx| {
-                cx.emit(Event::ServerStopped { server_id: id })
-            })?;
+            this.update(&mut cx, |this, cx| this.server_stopped(id, cx))?;
         }
 
         for (id, server) in servers_to_start {
-            if server.start(&cx).await.log_err().is_some() {
+            if server.start(this.clone(), &cx).await.log_err().is_some() {
                 this.update(&mut cx, |_, cx| {
                     cx.emit(Event::ServerStarted { server_id: id })
                 })?;
@@ -0,0 +1,26 @@
+mod http_transport;
+mod sse;
+mod stdio_transport;
+
+use std::pin::Pin;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::Stream;
+
+pub use http_transport::*;
+pub use stdio_transport::*;
+
+/// The channel a [`Client`](crate::client::Client) exchanges JSON-RPC messages
+/// with a context server over.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Sends a serialized JSON-RPC message to the server.
+    async fn send(&self, message: String) -> Result<()>;
+
+    /// Returns the serialized JSON-RPC messages received from the server.
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>>;
+
+    /// Returns the diagnostic output of the server, such as a child process's stderr.
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>>;
+}
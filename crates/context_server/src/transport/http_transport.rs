@@ -0,0 +1,650 @@
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use async_trait::async_trait;
+use base64::Engine as _;
+use context_server_settings::{HttpTransportKind, ServerAuth, ServerEndpoint};
+use futures::{io::BufReader, AsyncBufReadExt as _, AsyncReadExt as _, Stream, StreamExt as _};
+use gpui::{BackgroundExecutor, Task};
+use http_client::{http, AsyncBody, HttpClient, Method, Request, Response, StatusCode, Url};
+use parking_lot::Mutex;
+use postage::{prelude::Stream as _, watch};
+use serde_json::{json, Value};
+use smol::channel;
+use util::ResultExt as _;
+
+use crate::client::INTERNAL_ERROR;
+use crate::transport::sse::SseParser;
+use crate::transport::Transport;
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+
+/// Talks to a remote context server over HTTP, using either the streamable
+/// HTTP transport or the older HTTP with SSE transport.
+pub struct HttpTransport {
+    state: Arc<HttpTransportState>,
+    message_rx: channel::Receiver<String>,
+    _event_stream_task: Task<()>,
+}
+
+struct HttpTransportState {
+    http_client: Arc<dyn HttpClient>,
+    url: Url,
+    kind: HttpTransportKind,
+    headers: Vec<(String, String)>,
+    session_id: Mutex<Option<String>>,
+    session_started_tx: channel::Sender<()>,
+    /// Where to post messages to. Servers using the SSE transport announce it
+    /// in their event stream, so it's unknown until connected.
+    post_url_tx: Mutex<watch::Sender<Option<Url>>>,
+    post_url_rx: Mutex<watch::Receiver<Option<Url>>>,
+    last_event_id: Mutex<Option<String>>,
+    reconnect_delay: Mutex<Duration>,
+    message_tx: channel::Sender<String>,
+    executor: BackgroundExecutor,
+}
+
+impl HttpTransport {
+    pub fn new(
+        endpoint: &ServerEndpoint,
+        http_client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Result<Self> {
+        let url = Url::parse(&endpoint.url)
+            .with_context(|| format!("invalid context server URL {:?}", endpoint.url))?;
+        let mut headers = endpoint
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        if let Some(auth) = &endpoint.auth {
+            headers.push(("Authorization".into(), authorization_header(auth)));
+        }
+
+        let (message_tx, message_rx) = channel::unbounded();
+        let (session_started_tx, session_started_rx) = channel::unbounded();
+        let (post_url_tx, post_url_rx) = watch::channel_with(match endpoint.transport {
+            HttpTransportKind::StreamableHttp => Some(url.clone()),
+            HttpTransportKind::Sse => None,
+        });
+        let state = Arc::new(HttpTransportState {
+            http_client,
+            url,
+            kind: endpoint.transport,
+            headers,
+            session_id: Mutex::new(None),
+            session_started_tx,
+            post_url_tx: Mutex::new(post_url_tx),
+            post_url_rx: Mutex::new(post_url_rx),
+            last_event_id: Mutex::new(None),
+            reconnect_delay: Mutex::new(RECONNECT_DELAY),
+            message_tx,
+            executor: executor.clone(),
+        });
+
+        let event_stream_task = executor.spawn({
+            let state = state.clone();
+            async move {
+                match state.kind {
+                    // Streamable HTTP servers can only send messages on their own
+                    // once they've started a session with us.
+                    HttpTransportKind::StreamableHttp => {
+                        while session_started_rx.recv().await.is_ok() {
+                            state.listen().await;
+                        }
+                    }
+                    HttpTransportKind::Sse => state.listen().await,
+                }
+            }
+        });
+
+        Ok(Self {
+            state,
+            message_rx,
+            _event_stream_task: event_stream_task,
+        })
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.state.url
+    }
+}
+
+impl HttpTransportState {
+    fn request_builder(&self, method: Method, url: &Url) -> http::request::Builder {
+        let mut request = Request::builder().method(method).uri(url.as_str());
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(session_id) = self.session_id.lock().as_deref() {
+            request = request.header(SESSION_ID_HEADER, session_id);
+        }
+        request
+    }
+
+    async fn post_url(&self) -> Result<Url> {
+        let mut post_url_rx = self.post_url_rx.lock().clone();
+        loop {
+            if let Some(url) = post_url_rx.borrow().clone() {
+                return Ok(url);
+            }
+            post_url_rx
+                .recv()
+                .await
+                .ok_or_else(|| anyhow!("context server transport was closed"))?;
+        }
+    }
+
+    async fn post(&self, message: String) -> Result<()> {
+        let url = self.post_url().await?;
+        let request = self
+            .request_builder(Method::POST, &url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream")
+            .body(AsyncBody::from(message))?;
+        let response = self.http_client.send(request).await?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            let previous_session_id = self.session_id.lock().replace(session_id.to_string());
+            if previous_session_id.as_deref() != Some(session_id) {
+                self.session_started_tx.try_send(()).ok();
+            }
+        }
+
+        let response = self.check_status(response).await?;
+        let content_type = response
+            .headers()
+            .get("Content-Type")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        let mut body = response.into_body();
+        if content_type.starts_with("text/event-stream") {
+            self.read_events(body).await?;
+        } else if content_type.starts_with("application/json") {
+            let mut text = String::new();
+            body.read_to_string(&mut text).await?;
+            self.receive_json(text)?;
+        }
+
+        Ok(())
+    }
+
+    async fn check_status(&self, response: Response<AsyncBody>) -> Result<Response<AsyncBody>> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        if status == StatusCode::NOT_FOUND && self.session_id.lock().take().is_some() {
+            bail!("the context server session expired, restart the server to start a new one");
+        }
+
+        let mut body = String::new();
+        response.into_body().read_to_string(&mut body).await.ok();
+        bail!("context server responded with {status}: {body}")
+    }
+
+    /// Keeps an event stream open to receive the messages the server sends on
+    /// its own, reconnecting whenever it drops.
+    async fn listen(&self) {
+        let mut attempts = 0;
+        loop {
+            match self.open_event_stream().await {
+                Ok(Some(body)) => {
+                    attempts = 0;
+                    if let Err(error) = self.read_events(body).await {
+                        log::warn!(
+                            "context server event stream at {} broke: {error:#}",
+                            self.url
+                        );
+                    }
+                }
+                Ok(None) => return,
+                Err(error) => log::warn!(
+                    "failed to connect to context server event stream at {}: {error:#}",
+                    self.url
+                ),
+            }
+
+            // The endpoint is only valid for the connection that announced it.
+            if self.kind == HttpTransportKind::Sse {
+                *self.post_url_tx.lock().borrow_mut() = None;
+            }
+
+            attempts += 1;
+            if attempts > MAX_RECONNECT_ATTEMPTS {
+                log::error!(
+                    "giving up on context server event stream at {} after {MAX_RECONNECT_ATTEMPTS} attempts",
+                    self.url
+                );
+                return;
+            }
+            let delay = self
+                .reconnect_delay
+                .lock()
+                .saturating_mul(2u32.pow(attempts - 1));
+            self.executor.timer(delay.min(MAX_RECONNECT_DELAY)).await;
+        }
+    }
+
+    /// Opens the server's event stream, or returns `None` when the server
+    /// doesn't offer one.
+    async fn open_event_stream(&self) -> Result<Option<AsyncBody>> {
+        let mut request = self
+            .request_builder(Method::GET, &self.url)
+            .header("Accept", "text/event-stream");
+        if let Some(last_event_id) = self.last_event_id.lock().as_deref() {
+            request = request.header(LAST_EVENT_ID_HEADER, last_event_id);
+        }
+        let response = self
+            .http_client
+            .send(request.body(AsyncBody::empty())?)
+            .await?;
+
+        if self.kind == HttpTransportKind::StreamableHttp
+            && response.status() == StatusCode::METHOD_NOT_ALLOWED
+        {
+            return Ok(None);
+        }
+        let response = self.check_status(response).await?;
+        Ok(Some(response.into_body()))
+    }
+
+    async fn read_events(&self, body: AsyncBody) -> Result<()> {
+        let mut parser = SseParser::new();
+        let mut lines = BufReader::new(body).lines();
+        while let Some(line) = lines.next().await {
+            let Some(event) = parser.push_line(&line?) else {
+                continue;
+            };
+            if let Some(retry) = parser.retry() {
+                *self.reconnect_delay.lock() = retry;
+            }
+            if let Some(id) = event.id {
+                *self.last_event_id.lock() = Some(id);
+            }
+
+            match event.event.as_str() {
+                "endpoint" => {
+                    let url = self
+                        .url
+                        .join(&event.data)
+                        .with_context(|| format!("invalid endpoint {:?}", event.data))?;
+                    *self.post_url_tx.lock().borrow_mut() = Some(url);
+                }
+                "message" => self.receive_json(event.data)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn receive_json(&self, json: String) -> Result<()> {
+        if let Ok(Value::Array(messages)) = serde_json::from_str(&json) {
+            for message in messages {
+                self.message_tx.try_send(message.to_string())?;
+            }
+        } else {
+            self.message_tx.try_send(json)?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let Some(request_id) = request_id(&message) else {
+            // Notifications are sent in order, so that the server sees the
+            // `initialized` notification before any other request.
+            if let Err(error) = self.state.post(message).await {
+                log::error!(
+                    "failed to send notification to context server at {}: {error:#}",
+                    self.state.url
+                );
+            }
+            return Ok(());
+        };
+
+        // Requests take as long as the server needs to handle them, so don't
+        // hold up the messages sent after them.
+        let state = self.state.clone();
+        self.state
+            .executor
+            .spawn(async move {
+                if let Err(error) = state.post(message).await {
+                    state
+                        .message_tx
+                        .try_send(error_response(request_id, &error))
+                        .log_err();
+                }
+            })
+            .detach();
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.message_rx.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(futures::stream::empty())
+    }
+}
+
+impl Drop for HttpTransport {
+    fn drop(&mut self) {
+        // Let the server know we're done with the session, so it can release it.
+        if self.state.session_id.lock().is_none() {
+            return;
+        }
+        let Some(request) = self
+            .state
+            .request_builder(Method::DELETE, &self.state.url)
+            .body(AsyncBody::empty())
+            .log_err()
+        else {
+            return;
+        };
+        let response = self.state.http_client.send(request);
+        self.state
+            .executor
+            .spawn(async move {
+                response.await.log_err();
+            })
+            .detach();
+    }
+}
+
+fn authorization_header(auth: &ServerAuth) -> String {
+    match auth {
+        ServerAuth::Bearer { token } => format!("Bearer {token}"),
+        ServerAuth::Basic { username, password } => format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"))
+        ),
+    }
+}
+
+fn request_id(message: &str) -> Option<Value> {
+    let mut message = serde_json::from_str::<Value>(message).ok()?;
+    message.get("method")?;
+    message.get_mut("id").map(Value::take)
+}
+
+/// Builds the response for a request that couldn't be delivered, so that it
+/// fails right away instead of timing out.
+fn error_response(request_id: Value, error: &anyhow::Error) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": request_id,
+        "error": {
+            "code": INTERNAL_ERROR,
+            "message": format!("{error:#}"),
+        },
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use collections::HashMap;
+    use futures::{StreamExt as _, TryStreamExt as _};
+    use gpui::TestAppContext;
+    use http_client::FakeHttpClient;
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    struct RecordedRequest {
+        method: Method,
+        uri: String,
+        session_id: Option<String>,
+        body: String,
+    }
+
+    fn header(request: &Request<AsyncBody>, name: &str) -> Option<String> {
+        request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(ToString::to_string)
+    }
+
+    async fn record(
+        requests: &Mutex<Vec<RecordedRequest>>,
+        request: Request<AsyncBody>,
+    ) -> RecordedRequest {
+        let method = request.method().clone();
+        let uri = request.uri().to_string();
+        let session_id = header(&request, SESSION_ID_HEADER);
+        let mut body = String::new();
+        request.into_body().read_to_string(&mut body).await.unwrap();
+        let request = RecordedRequest {
+            method,
+            uri,
+            session_id,
+            body,
+        };
+        requests.lock().push(request.clone());
+        request
+    }
+
+    fn endpoint(url: &str, transport: HttpTransportKind) -> ServerEndpoint {
+        ServerEndpoint {
+            url: url.into(),
+            transport,
+            headers: HashMap::default(),
+            auth: None,
+        }
+    }
+
+    #[gpui::test]
+    async fn test_streamable_http_transport(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    assert_eq!(
+                        header(&request, "Authorization").as_deref(),
+                        Some("Bearer secret")
+                    );
+                    assert_eq!(header(&request, "X-Team").as_deref(), Some("editor"));
+
+                    let request = record(&requests, request).await;
+                    let response = Response::builder();
+                    let response = match request.method {
+                        Method::GET => response.status(405).body(AsyncBody::empty()),
+                        Method::DELETE => response.status(200).body(AsyncBody::empty()),
+                        _ if request.body.contains("\"initialize\"") => response
+                            .header("Content-Type", "application/json")
+                            .header(SESSION_ID_HEADER, "session-1")
+                            .body(r#"{"jsonrpc":"2.0","id":0,"result":{}}"#.into()),
+                        _ if request.body.contains("\"tools/list\"") => response
+                            .header("Content-Type", "text/event-stream")
+                            .body(
+                                concat!(
+                                    "data: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/message\"}\n",
+                                    "\n",
+                                    "id: 1\n",
+                                    "data: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"tools\":[]}}\n",
+                                    "\n",
+                                )
+                                .into(),
+                            ),
+                        _ => response.status(202).body(AsyncBody::empty()),
+                    };
+                    Ok(response.unwrap())
+                }
+            }
+        });
+
+        let mut endpoint = endpoint(
+            "https://mcp.example.com/mcp",
+            HttpTransportKind::StreamableHttp,
+        );
+        endpoint.headers.insert("X-Team".into(), "editor".into());
+        endpoint.auth = Some(ServerAuth::Bearer {
+            token: "secret".into(),
+        });
+        let transport = HttpTransport::new(&endpoint, http_client, cx.executor()).unwrap();
+        let mut messages = transport.receive();
+
+        transport
+            .send(r#"{"jsonrpc":"2.0","id":0,"method":"initialize"}"#.into())
+            .await
+            .unwrap();
+        assert_eq!(
+            messages.next().await.unwrap(),
+            r#"{"jsonrpc":"2.0","id":0,"result":{}}"#
+        );
+
+        transport
+            .send(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#.into())
+            .await
+            .unwrap();
+        transport
+            .send(r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#.into())
+            .await
+            .unwrap();
+        assert_eq!(
+            messages.next().await.unwrap(),
+            r#"{"jsonrpc":"2.0","method":"notifications/message"}"#
+        );
+        assert_eq!(
+            messages.next().await.unwrap(),
+            r#"{"jsonrpc":"2.0","id":1,"result":{"tools":[]}}"#
+        );
+
+        drop(transport);
+        cx.run_until_parked();
+
+        let requests = requests.lock().clone();
+        let methods = requests
+            .iter()
+            .map(|request| request.method.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(methods.len(), 5);
+        assert_eq!(methods[0], Method::POST);
+        assert_eq!(methods[4], Method::DELETE);
+        assert!(methods.contains(&Method::GET));
+        assert!(requests
+            .iter()
+            .all(|request| request.uri == "https://mcp.example.com/mcp"));
+        assert_eq!(requests[0].session_id, None);
+        assert!(requests[1..]
+            .iter()
+            .all(|request| request.session_id.as_deref() == Some("session-1")));
+    }
+
+    #[gpui::test]
+    async fn test_sse_transport(cx: &mut TestAppContext) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let (events_tx, events_rx) = channel::unbounded::<Vec<u8>>();
+        events_tx
+            .try_send(b"event: endpoint\ndata: /messages?session_id=abc\n\n".to_vec())
+            .unwrap();
+        let events_rx = Arc::new(Mutex::new(Some(events_rx)));
+
+        let http_client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                let events_rx = events_rx.clone();
+                let events_tx = events_tx.clone();
+                async move {
+                    let request = record(&requests, request).await;
+                    if request.method == Method::GET {
+                        let body = events_rx
+                            .lock()
+                            .take()
+                            .unwrap()
+                            .map(Ok::<_, std::io::Error>)
+                            .into_async_read();
+                        return Ok(Response::builder()
+                            .header("Content-Type", "text/event-stream")
+                            .body(AsyncBody::from_reader(body))
+                            .unwrap());
+                    }
+
+                    let reply = request.body.replace("\"method\":\"ping\"", "\"result\":{}");
+                    events_tx
+                        .try_send(format!("event: message\ndata: {reply}\n\n").into_bytes())
+                        .unwrap();
+                    Ok(Response::builder()
+                        .status(202)
+                        .body(AsyncBody::empty())
+                        .unwrap())
+                }
+            }
+        });
+
+        let transport = HttpTransport::new(
+            &endpoint("https://mcp.example.com/sse", HttpTransportKind::Sse),
+            http_client,
+            cx.executor(),
+        )
+        .unwrap();
+        let mut messages = transport.receive();
+
+        transport
+            .send(r#"{"jsonrpc":"2.0","id":7,"method":"ping"}"#.into())
+            .await
+            .unwrap();
+        assert_eq!(
+            messages.next().await.unwrap(),
+            r#"{"jsonrpc":"2.0","id":7,"result":{}}"#
+        );
+
+        let requests = requests.lock().clone();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, Method::GET);
+        assert_eq!(requests[0].uri, "https://mcp.example.com/sse");
+        assert_eq!(requests[1].method, Method::POST);
+        assert_eq!(
+            requests[1].uri,
+            "https://mcp.example.com/messages?session_id=abc"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_failed_request_responds_with_error(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|_| async {
+            Ok(Response::builder()
+                .status(401)
+                .body("missing credentials".into())
+                .unwrap())
+        });
+        let transport = HttpTransport::new(
+            &endpoint(
+                "https://mcp.example.com/mcp",
+                HttpTransportKind::StreamableHttp,
+            ),
+            http_client,
+            cx.executor(),
+        )
+        .unwrap();
+        let mut messages = transport.receive();
+
+        transport
+            .send(r#"{"jsonrpc":"2.0","id":3,"method":"tools/call"}"#.into())
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&messages.next().await.unwrap()).unwrap();
+        assert_eq!(response["id"], 3);
+        assert_eq!(response["error"]["code"], INTERNAL_ERROR);
+        assert_eq!(
+            response["error"]["message"],
+            "context server responded with 401 Unauthorized: missing credentials"
+        );
+    }
+}
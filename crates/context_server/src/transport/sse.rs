@@ -0,0 +1,137 @@
+//! A parser for server-sent events, as specified in
+//! <https://html.spec.whatwg.org/multipage/server-sent-events.html#parsing-an-event-stream>.
+
+use std::time::Duration;
+
+/// An event of a server-sent event stream.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The type of the event, `message` when the server doesn't name it.
+    pub event: String,
+    /// The data lines of the event, joined with newlines.
+    pub data: String,
+    /// The ID of the event, to resume the stream after it when reconnecting.
+    pub id: Option<String>,
+}
+
+/// Turns the lines of an event stream into events.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns how long the server asked to wait before reconnecting, if it did.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Feeds a line of the stream, without its line terminator, and returns the
+    /// event that the line completes, if any.
+    pub fn push_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(milliseconds) = value.parse() {
+                    self.retry = Some(Duration::from_millis(milliseconds));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+
+        Some(SseEvent {
+            event: event
+                .filter(|event| !event.is_empty())
+                .unwrap_or_else(|| "message".to_string()),
+            data: std::mem::take(&mut self.data).join("\n"),
+            id: self.id.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(stream: &str) -> Vec<SseEvent> {
+        let mut parser = SseParser::new();
+        stream
+            .lines()
+            .filter_map(|line| parser.push_line(line))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let events = parse(
+            ": keep-alive\n\
+             event: endpoint\n\
+             data: /messages?session_id=1\n\
+             \n\
+             id: 7\n\
+             data: {\"jsonrpc\":\"2.0\",\n\
+             data:\"method\":\"ping\"}\n\
+             \n\
+             data\n\
+             \n\
+             event: ignored\n\
+             \n",
+        );
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: "endpoint".into(),
+                    data: "/messages?session_id=1".into(),
+                    id: None,
+                },
+                SseEvent {
+                    event: "message".into(),
+                    data: "{\"jsonrpc\":\"2.0\",\n\"method\":\"ping\"}".into(),
+                    id: Some("7".into()),
+                },
+                SseEvent {
+                    event: "message".into(),
+                    data: "".into(),
+                    id: Some("7".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_retry() {
+        let mut parser = SseParser::new();
+        assert_eq!(parser.push_line("retry: 2500"), None);
+        assert_eq!(parser.push_line("retry: soon"), None);
+        assert_eq!(parser.retry(), Some(Duration::from_millis(2500)));
+    }
+}
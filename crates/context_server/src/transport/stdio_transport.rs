@@ -0,0 +1,109 @@
+use std::pin::Pin;
+
+use anyhow::{Context as _, Result};
+use async_trait::async_trait;
+use futures::{io::BufWriter, AsyncRead, Stream};
+use gpui::{AsyncAppContext, Task};
+use smol::{
+    channel,
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    lock::Mutex,
+    process::{Child, ChildStdin},
+};
+use util::ResultExt as _;
+
+use crate::client::ModelContextServerBinary;
+use crate::transport::Transport;
+
+/// Talks to a context server running as a child process, exchanging one
+/// message per line over its stdin and stdout.
+pub struct StdioTransport {
+    stdin: Mutex<BufWriter<ChildStdin>>,
+    stdout_rx: channel::Receiver<String>,
+    stderr_rx: channel::Receiver<String>,
+    _server: Child,
+    _io_tasks: [Task<()>; 2],
+}
+
+impl StdioTransport {
+    pub fn new(binary: ModelContextServerBinary, cx: &AsyncAppContext) -> Result<Self> {
+        let mut command = util::command::new_smol_command(&binary.executable);
+        command
+            .args(&binary.args)
+            .envs(binary.env.unwrap_or_default())
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut server = command.spawn().with_context(|| {
+            format!(
+                "failed to spawn command. (path={:?}, args={:?})",
+                binary.executable, &binary.args
+            )
+        })?;
+
+        let stdin = server.stdin.take().unwrap();
+        let stdout = server.stdout.take().unwrap();
+        let stderr = server.stderr.take().unwrap();
+
+        let (stdout_tx, stdout_rx) = channel::unbounded();
+        let (stderr_tx, stderr_rx) = channel::unbounded();
+        let executor = cx.background_executor();
+        let io_tasks = [
+            executor.spawn(async move {
+                Self::read_lines(stdout, stdout_tx).await.log_err();
+            }),
+            executor.spawn(async move {
+                Self::read_lines(stderr, stderr_tx).await.log_err();
+            }),
+        ];
+
+        Ok(Self {
+            stdin: Mutex::new(BufWriter::new(stdin)),
+            stdout_rx,
+            stderr_rx,
+            _server: server,
+            _io_tasks: io_tasks,
+        })
+    }
+
+    async fn read_lines(
+        output: impl AsyncRead + Unpin,
+        lines_tx: channel::Sender<String>,
+    ) -> Result<()> {
+        let mut output = BufReader::new(output);
+        let mut buffer = String::new();
+
+        loop {
+            buffer.clear();
+            if output.read_line(&mut buffer).await? == 0 {
+                return Ok(());
+            }
+
+            let line = buffer.trim();
+            if !line.is_empty() && lines_tx.send(line.to_string()).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    async fn send(&self, message: String) -> Result<()> {
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(message.as_bytes()).await?;
+        stdin.write_all(b"\n").await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.stdout_rx.clone())
+    }
+
+    fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+        Box::pin(self.stderr_rx.clone())
+    }
+}
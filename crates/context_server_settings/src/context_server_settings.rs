@@ -18,6 +18,9 @@ pub struct ServerConfig {
     ///
     /// This will override the command set by an extension.
     pub command: Option<ServerCommand>,
+    /// The address of a remote context server to connect to over HTTP,
+    /// instead of running a command.
+    pub endpoint: Option<ServerEndpoint>,
     /// The settings for this context server.
     ///
     /// Consult the documentation for the context server to see what settings
@@ -40,6 +43,44 @@ pub struct ServerCommand {
     pub env: Option<HashMap<String, String>>,
 }
 
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+pub struct ServerEndpoint {
+    /// The URL of the server's MCP endpoint, or of its event stream for
+    /// servers using the `sse` transport.
+    pub url: String,
+    /// The HTTP transport the server speaks.
+    ///
+    /// Default: streamable_http
+    #[serde(default)]
+    pub transport: HttpTransportKind,
+    /// Headers to send with every request to the server.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// The credentials to authenticate with.
+    pub auth: Option<ServerAuth>,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpTransportKind {
+    /// Post each message to the endpoint, reading responses from the reply
+    /// body or from the event stream the server replies with.
+    #[default]
+    StreamableHttp,
+    /// Listen to the event stream at the URL for messages, posting messages
+    /// to the endpoint announced in the stream.
+    Sse,
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerAuth {
+    /// Send the token in an `Authorization: Bearer` header.
+    Bearer { token: String },
+    /// Send the username and password in an `Authorization: Basic` header.
+    Basic { username: String, password: String },
+}
+
 #[derive(Deserialize, Serialize, Default, Clone, PartialEq, Eq, JsonSchema, Debug)]
 pub struct ContextServerSettings {
     /// Settings for context servers used in the Assistant.
@@ -35,9 +35,11 @@ mod mouse_context_menu;
 pub mod movement;
 mod persistence;
 mod proposed_changes_editor;
+mod rainbow_brackets;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod snippet_variables;
 pub mod tasks;
 
 #[cfg(test)]
@@ -137,7 +139,8 @@ use selections_collection::{
 use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsLocation, SettingsStore};
 use smallvec::SmallVec;
-use snippet::Snippet;
+use snippet::{Snippet, Transform};
+use snippet_variables::SnippetVariables;
 use std::{
     any::TypeId,
     borrow::Cow,
@@ -879,6 +882,15 @@ struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
     active_index: usize,
     choices: Vec<Option<Vec<String>>>,
+    mirrors: Vec<SnippetMirror>,
+}
+
+/// A range of a snippet that holds the transformed text of one of its tabstops,
+/// updated as the tabstop is edited.
+struct SnippetMirror {
+    source: Range<Anchor>,
+    range: Range<Anchor>,
+    transform: Transform,
 }
 
 #[doc(hidden)]
@@ -1764,13 +1776,20 @@ pub struct ClipboardSelection {
 }
 
 #[derive(Debug)]
-pub(crate) struct NavigationData {
+pub struct NavigationData {
     cursor_anchor: Anchor,
     cursor_position: Point,
     scroll_anchor: ScrollAnchor,
     scroll_top_row: u32,
 }
 
+impl NavigationData {
+    /// The position of the cursor when the entry was recorded.
+    pub fn cursor_position(&self) -> Point {
+        self.cursor_position
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GotoDefinitionKind {
     Symbol,
@@ -2400,6 +2419,10 @@ impl Editor {
         self.mode
     }
 
+    pub fn project(&self) -> Option<&Model<Project>> {
+        self.project.as_ref()
+    }
+
     pub fn collaboration_hub(&self) -> Option<&dyn CollaborationHub> {
         self.collaboration_hub.as_deref()
     }
@@ -4594,7 +4617,9 @@ impl Editor {
         let text;
 
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            let variables = SnippetVariables::new(self, &buffer_handle, cx);
+            snippet =
+                Some(Snippet::parse_with_variables(&completion.new_text, &variables).log_err()?);
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -5756,7 +5781,7 @@ impl Editor {
             choices: Option<Vec<String>>,
         }
 
-        let tabstops = self.buffer.update(cx, |buffer, cx| {
+        let (tabstops, mirrors) = self.buffer.update(cx, |buffer, cx| {
             let snippet_text: Arc<str> = snippet.text.clone().into();
             buffer.edit(
                 insertion_ranges
@@ -5769,7 +5794,7 @@ impl Editor {
 
             let snapshot = &*buffer.read(cx);
             let snippet = &snippet;
-            snippet
+            let tabstops = snippet
                 .tabstops
                 .iter()
                 .map(|tabstop| {
@@ -5802,7 +5827,36 @@ impl Editor {
                         choices: tabstop.choices.clone(),
                     }
                 })
-                .collect::<Vec<_>>()
+                .collect::<Vec<_>>();
+
+            // Each insertion gets its own mirrors, which follow the first range of the
+            // mirrored tabstop within the same insertion.
+            let mut mirrors = Vec::new();
+            let mut delta = 0_isize;
+            for insertion_range in insertion_ranges {
+                let insertion_start = insertion_range.start as isize + delta;
+                delta += snippet.text.len() as isize - insertion_range.len() as isize;
+                let offset =
+                    |position: isize| ((insertion_start + position) as usize).min(snapshot.len());
+                for mirror in &snippet.mirrors {
+                    let Some(source) = snippet
+                        .tabstops
+                        .get(mirror.tabstop)
+                        .and_then(|tabstop| tabstop.ranges.first())
+                    else {
+                        continue;
+                    };
+                    mirrors.push(SnippetMirror {
+                        source: snapshot.anchor_before(offset(source.start))
+                            ..snapshot.anchor_after(offset(source.end)),
+                        range: snapshot.anchor_after(offset(mirror.range.start))
+                            ..snapshot.anchor_before(offset(mirror.range.end)),
+                        transform: mirror.transform.clone(),
+                    });
+                }
+            }
+
+            (tabstops, mirrors)
         });
         if let Some(tabstop) = tabstops.first() {
             self.change_selections(Some(Autoscroll::fit()), cx, |s| {
@@ -5832,6 +5886,7 @@ impl Editor {
                     active_index: 0,
                     ranges,
                     choices,
+                    mirrors,
                 });
             }
 
@@ -5924,6 +5979,53 @@ impl Editor {
         false
     }
 
+    /// Re-applies the transforms of the active snippet's mirrors to the current text
+    /// of their tabstops, as part of the transaction that edited them.
+    fn update_snippet_mirrors(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(snippet) = self.snippet_stack.last() else {
+            return;
+        };
+        if snippet.mirrors.is_empty() {
+            return;
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut edits = Vec::new();
+        for (ix, mirror) in snippet.mirrors.iter().enumerate() {
+            let source = mirror.source.to_offset(&snapshot);
+            let source_text = snapshot.text_for_range(source).collect::<String>();
+            let start = mirror.range.start.to_offset(&snapshot);
+            let end = mirror.range.end.to_offset(&snapshot).max(start);
+            let new_text = mirror.transform.apply(&source_text);
+            if !snapshot.contains_str_at(start, &new_text) || end - start != new_text.len() {
+                edits.push((ix, snapshot.anchor_before(start), start..end, new_text));
+            }
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        edits.sort_unstable_by_key(|(_, _, range, _)| range.start);
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit(
+                edits
+                    .iter()
+                    .map(|(_, _, range, new_text)| (range.clone(), new_text.clone())),
+                None,
+                cx,
+            );
+        });
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        if let Some(snippet) = self.snippet_stack.last_mut() {
+            for (ix, start, _, new_text) in edits {
+                let start = start.to_offset(&snapshot);
+                snippet.mirrors[ix].range =
+                    snapshot.anchor_after(start)..snapshot.anchor_before(start + new_text.len());
+            }
+        }
+    }
+
     pub fn clear(&mut self, cx: &mut ViewContext<Self>) {
         self.transact(cx, |this, cx| {
             this.select_all(&SelectAll, cx);
@@ -10953,6 +11055,7 @@ impl Editor {
         now: Instant,
         cx: &mut ViewContext<Self>,
     ) -> Option<TransactionId> {
+        self.update_snippet_mirrors(cx);
         if let Some(transaction_id) = self
             .buffer
             .update(cx, |buffer, cx| buffer.end_transaction_at(now, cx))
@@ -12601,6 +12704,7 @@ impl Editor {
                 self.active_indent_guides_state.dirty = true;
                 self.refresh_active_diagnostics(cx);
                 self.refresh_code_actions(cx);
+                self.refresh_rainbow_brackets(cx);
                 if self.has_active_inline_completion(cx) {
                     self.update_visible_inline_completion(cx);
                 }
@@ -12664,6 +12768,7 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                self.refresh_rainbow_brackets(cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
@@ -12677,11 +12782,13 @@ impl Editor {
             }
             multi_buffer::Event::Reparsed(buffer_id) => {
                 self.tasks_update_task = Some(self.refresh_runnables(cx));
+                self.refresh_rainbow_brackets(cx);
 
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                self.refresh_rainbow_brackets(cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -12712,6 +12819,7 @@ impl Editor {
     fn settings_changed(&mut self, cx: &mut ViewContext<Self>) {
         self.tasks_update_task = Some(self.refresh_runnables(cx));
         self.refresh_inline_completion(true, false, cx);
+        self.refresh_rainbow_brackets(cx);
         self.refresh_inlay_hints(
             InlayHintRefreshReason::SettingsChange(inlay_hint_settings(
                 self.selections.newest_anchor().head(),
@@ -6809,6 +6809,95 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_snippet_mirrors(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let (text, insertion_ranges) = marked_text_ranges(
+        indoc! {"
+            let ˇ;
+            let ˇ;
+        "},
+        false,
+    );
+
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+
+    editor.update(cx, |editor, cx| {
+        let snippet = Snippet::parse("${1:name}: ${1/(.*)/${1:/pascalcase}/} = $2").unwrap();
+
+        editor
+            .insert_snippet(&insertion_ranges, snippet, cx)
+            .unwrap();
+
+        fn assert(editor: &mut Editor, cx: &mut ViewContext<Editor>, marked_text: &str) {
+            let (expected_text, selection_ranges) = marked_text_ranges(marked_text, false);
+            assert_eq!(editor.text(cx), expected_text);
+            assert_eq!(editor.selections.ranges::<usize>(cx), selection_ranges);
+        }
+
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let «name»: Name = ;
+                let «name»: Name = ;
+            "},
+        );
+
+        // Mirrors follow the text of their tabstop as it's edited.
+        editor.handle_input("user", cx);
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let userˇ: User = ;
+                let userˇ: User = ;
+            "},
+        );
+        editor.handle_input("_id", cx);
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let user_idˇ: UserId = ;
+                let user_idˇ: UserId = ;
+            "},
+        );
+
+        // Undoing the edit restores the tabstop and its mirrors together.
+        editor.undo(&Undo, cx);
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                let name: Name = ;
+                let name: Name = ;
+            "}
+        );
+        editor.redo(&Redo, cx);
+        assert(
+            editor,
+            cx,
+            indoc! {"
+                let user_idˇ: UserId = ;
+                let user_idˇ: UserId = ;
+            "},
+        );
+
+        // Once the snippet is finished, its mirrors are no longer updated.
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        editor.handle_input("0", cx);
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                let user_id: UserId = 0;
+                let user_id: UserId = 0;
+            "}
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -15,6 +15,7 @@ use crate::{
     hunk_status,
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
+    rainbow_brackets,
     scroll::scroll_amount::ScrollAmount,
     BlockId, ChunkReplacement, CodeActionsMenu, CursorShape, CustomBlockId, DisplayPoint,
     DisplayRow, DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode, EditorSettings,
@@ -44,7 +45,7 @@ use language::{
         IndentGuideBackgroundColoring, IndentGuideColoring, IndentGuideSettings,
         ShowWhitespaceSetting,
     },
-    ChunkRendererContext,
+    BufferId, ChunkRendererContext,
 };
 use lsp::DiagnosticSeverity;
 use multi_buffer::{Anchor, ExcerptId, ExpandExcerptDirection, MultiBufferPoint, MultiBufferRow};
@@ -67,7 +68,7 @@ use std::{
 use sum_tree::Bias;
 use theme::{ActiveTheme, Appearance, PlayerColor};
 use ui::prelude::*;
-use ui::{h_flex, ButtonLike, ButtonStyle, ContextMenu, Tooltip};
+use ui::{h_flex, ButtonLike, ButtonStyle, ContextMenu, IconButtonShape, Tooltip};
 use unicode_segmentation::UnicodeSegmentation;
 use util::RangeExt;
 use util::ResultExt;
@@ -1601,6 +1602,41 @@ impl EditorElement {
         )
     }
 
+    fn layout_scope_guide(
+        &self,
+        content_origin: gpui::Point<Pixels>,
+        text_origin: gpui::Point<Pixels>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        line_height: Pixels,
+        newest_selection_head: Option<DisplayPoint>,
+        snapshot: &EditorSnapshot,
+        cx: &mut WindowContext,
+    ) -> Option<ScopeGuideLayout> {
+        let head = newest_selection_head?.to_offset(&snapshot.display_snapshot, Bias::Left);
+        let scope = rainbow_brackets::active_scope(&snapshot.buffer_snapshot, head, cx)?;
+
+        let open = scope.open.start.to_display_point(snapshot);
+        let close = scope.close.start.to_display_point(snapshot);
+        if close.row().0 <= open.row().0 + 1 {
+            return None;
+        }
+
+        let column = open.column().min(close.column());
+        let start_x =
+            content_origin.x + self.column_pixels(column as usize, cx) - scroll_pixel_position.x;
+        if start_x < text_origin.x {
+            return None;
+        }
+        let start_y =
+            content_origin.y + (open.row().as_f32() + 1.) * line_height - scroll_pixel_position.y;
+
+        Some(ScopeGuideLayout {
+            origin: point(start_x, start_y),
+            length: (close.row().as_f32() - open.row().as_f32() - 1.) * line_height,
+            color: scope.color,
+        })
+    }
+
     fn calculate_indent_guide_bounds(
         row_range: Range<MultiBufferRow>,
         line_height: Pixels,
@@ -2052,6 +2088,34 @@ impl EditorElement {
         line_elements
     }
 
+    /// Renders the buttons that apply or discard all the changes of a branch buffer at once.
+    fn render_branch_buffer_controls(
+        &self,
+        buffer_id: BufferId,
+        cx: &mut WindowContext,
+    ) -> impl IntoElement {
+        h_flex()
+            .gap_1()
+            .child(
+                IconButton::new("discard-file-changes", IconName::Undo)
+                    .shape(IconButtonShape::Square)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Discard File Changes", cx))
+                    .on_click(cx.listener_for(&self.editor, move |editor, _, cx| {
+                        editor.revert_diff_hunks_in_buffer(buffer_id, cx);
+                    })),
+            )
+            .child(
+                IconButton::new("apply-file-changes", IconName::Check)
+                    .shape(IconButtonShape::Square)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Apply File Changes", cx))
+                    .on_click(cx.listener_for(&self.editor, move |editor, _, cx| {
+                        editor.apply_diff_hunks_in_buffer(buffer_id, cx);
+                    })),
+            )
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn render_block(
         &self,
@@ -2209,6 +2273,13 @@ impl EditorElement {
                         let parent_path = path.as_ref().and_then(|path| {
                             Some(path.parent()?.to_string_lossy().to_string() + "/")
                         });
+                        let is_branch_buffer = self
+                            .editor
+                            .read(cx)
+                            .buffer
+                            .read(cx)
+                            .buffer(buffer.remote_id())
+                            .map_or(false, |buffer| buffer.read(cx).base_buffer().is_some());
 
                         result = result.child(
                             div()
@@ -2247,7 +2318,17 @@ impl EditorElement {
                                                     }),
                                             ),
                                         )
-                                        .child(Icon::new(IconName::ArrowUpRight))
+                                        .child(
+                                            h_flex()
+                                                .gap_2()
+                                                .when(is_branch_buffer, |this| {
+                                                    this.child(self.render_branch_buffer_controls(
+                                                        buffer.remote_id(),
+                                                        cx,
+                                                    ))
+                                                })
+                                                .child(Icon::new(IconName::ArrowUpRight)),
+                                        )
                                         .cursor_pointer()
                                         .tooltip(|cx| {
                                             Tooltip::for_action("Jump to File", &OpenExcerpts, cx)
@@ -3186,6 +3267,20 @@ impl EditorElement {
         }
     }
 
+    fn paint_scope_guide(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        let Some(scope_guide) = &layout.scope_guide else {
+            return;
+        };
+
+        cx.paint_quad(fill(
+            Bounds {
+                origin: scope_guide.origin,
+                size: size(px(1.), scope_guide.length),
+            },
+            scope_guide.color,
+        ));
+    }
+
     fn paint_line_numbers(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
         let line_height = layout.position_map.line_height;
         let scroll_position = layout.position_map.snapshot.scroll_position();
@@ -5305,6 +5400,16 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let scope_guide = self.layout_scope_guide(
+                        content_origin,
+                        text_hitbox.origin,
+                        scroll_pixel_position,
+                        line_height,
+                        newest_selection_head,
+                        &snapshot,
+                        cx,
+                    );
+
                     let crease_trailers = cx.with_element_namespace("crease_trailers", |cx| {
                         self.prepaint_crease_trailers(
                             crease_trailers,
@@ -5632,6 +5737,7 @@ impl Element for EditorElement {
                         visible_display_row_range: start_row..end_row,
                         wrap_guides,
                         indent_guides,
+                        scope_guide,
                         hitbox,
                         text_hitbox,
                         gutter_hitbox,
@@ -5721,6 +5827,7 @@ impl Element for EditorElement {
                     self.paint_mouse_listeners(layout, hovered_hunk, cx);
                     self.paint_background(layout, cx);
                     self.paint_indent_guides(layout, cx);
+                    self.paint_scope_guide(layout, cx);
 
                     if layout.gutter_hitbox.size.width > Pixels::ZERO {
                         self.paint_blamed_display_rows(layout, cx);
@@ -5777,6 +5884,7 @@ pub struct EditorLayout {
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
+    scope_guide: Option<ScopeGuideLayout>,
     visible_display_row_range: Range<DisplayRow>,
     active_rows: BTreeMap<DisplayRow, bool>,
     highlighted_rows: BTreeMap<DisplayRow, Hsla>,
@@ -6034,6 +6142,13 @@ pub struct IndentGuideLayout {
     settings: IndentGuideSettings,
 }
 
+#[derive(Debug)]
+struct ScopeGuideLayout {
+    origin: gpui::Point<Pixels>,
+    length: Pixels,
+    color: Hsla,
+}
+
 pub struct CursorLayout {
     origin: gpui::Point<Pixels>,
     block_width: Pixels,
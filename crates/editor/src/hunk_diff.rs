@@ -21,10 +21,10 @@ use util::RangeExt;
 use workspace::Item;
 
 use crate::{
-    editor_settings::CurrentLineHighlight, hunk_status, hunks_for_selections, ApplyAllDiffHunks,
-    ApplyDiffHunk, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId, DiffRowHighlight,
-    DisplayRow, DisplaySnapshot, Editor, EditorElement, ExpandAllHunkDiffs, GoToHunk, GoToPrevHunk,
-    RevertFile, RevertSelectedHunks, ToDisplayPoint, ToggleHunkDiff,
+    editor_settings::CurrentLineHighlight, hunk_status, hunks_for_ranges, hunks_for_selections,
+    ApplyAllDiffHunks, ApplyDiffHunk, BlockPlacement, BlockProperties, BlockStyle, CustomBlockId,
+    DiffRowHighlight, DisplayRow, DisplaySnapshot, Editor, EditorElement, ExpandAllHunkDiffs,
+    GoToHunk, GoToPrevHunk, RevertFile, RevertSelectedHunks, ToDisplayPoint, ToggleHunkDiff,
 };
 
 #[derive(Debug, Clone)]
@@ -492,6 +492,47 @@ impl Editor {
         }
     }
 
+    /// Applies all the changes of a branch buffer to its base buffer.
+    pub(crate) fn apply_diff_hunks_in_buffer(
+        &mut self,
+        buffer_id: BufferId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(branch_buffer) = self.buffer.read(cx).buffer(buffer_id) else {
+            return;
+        };
+        branch_buffer.update(cx, |branch_buffer, cx| {
+            branch_buffer.merge_into_base(Vec::new(), cx);
+        });
+
+        if let Some(project) = self.project.clone() {
+            self.save(true, project, cx).detach_and_log_err(cx);
+        }
+    }
+
+    /// Discards all the changes of a buffer, restoring the text of its diff base.
+    pub(crate) fn revert_diff_hunks_in_buffer(
+        &mut self,
+        buffer_id: BufferId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let mut revert_changes = HashMap::default();
+        let snapshot = self.snapshot(cx);
+        for hunk in hunks_for_ranges(
+            Some(Point::zero()..snapshot.buffer_snapshot.max_point()).into_iter(),
+            &snapshot,
+        ) {
+            if hunk.buffer_id == buffer_id {
+                self.prepare_revert_change(&mut revert_changes, &hunk, cx);
+            }
+        }
+        if !revert_changes.is_empty() {
+            self.transact(cx, |editor, cx| {
+                editor.revert(revert_changes, cx);
+            });
+        }
+    }
+
     pub(crate) fn apply_selected_diff_hunks(
         &mut self,
         _: &ApplyDiffHunk,
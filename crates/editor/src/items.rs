@@ -537,6 +537,22 @@ impl Item for Editor {
     type Event = EditorEvent;
 
     fn navigate(&mut self, data: Box<dyn std::any::Any>, cx: &mut ViewContext<Self>) -> bool {
+        // A bare point comes from a navigation history restored from a previous session.
+        let data = match data.downcast::<Point>() {
+            Ok(point) => {
+                let point = self.buffer.read(cx).read(cx).clip_point(*point, Bias::Left);
+                if self.selections.newest::<Point>(cx).head() == point {
+                    return false;
+                }
+                let nav_history = self.nav_history.take();
+                self.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+                self.nav_history = nav_history;
+                return true;
+            }
+            Err(data) => data,
+        };
         if let Ok(data) = data.downcast::<NavigationData>() {
             let newest_selection = self.selections.newest::<Point>(cx);
             let buffer = self.buffer.read(cx).read(cx);
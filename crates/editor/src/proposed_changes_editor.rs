@@ -1,4 +1,4 @@
-use crate::{ApplyAllDiffHunks, Editor, EditorEvent, SemanticsProvider};
+use crate::{ApplyAllDiffHunks, Editor, EditorEvent, RevertFile, SemanticsProvider};
 use collections::HashSet;
 use futures::{channel::mpsc, future::join_all};
 use gpui::{AppContext, EventEmitter, FocusableView, Model, Render, Subscription, Task, View};
@@ -8,7 +8,7 @@ use project::{buffer_store::BufferChangeSet, Project};
 use smol::stream::StreamExt;
 use std::{any::TypeId, ops::Range, rc::Rc, time::Duration};
 use text::ToOffset;
-use ui::{prelude::*, ButtonLike, KeyBinding};
+use ui::{prelude::*, ButtonLike, KeyBinding, Tooltip};
 use workspace::{
     searchable::SearchableItemHandle, Item, ItemHandle as _, ToolbarItemEvent, ToolbarItemLocation,
     ToolbarItemView, Workspace,
@@ -155,23 +155,8 @@ impl ProposedChangesEditor {
         locations: Vec<ProposedChangeLocation<T>>,
         cx: &mut ViewContext<Self>,
     ) {
-        // Undo all branch changes
         for entry in &self.buffer_entries {
-            let base_version = entry.base.read(cx).version();
-            entry.branch.update(cx, |buffer, cx| {
-                let undo_counts = buffer
-                    .operations()
-                    .iter()
-                    .filter_map(|(timestamp, _)| {
-                        if !base_version.observed(*timestamp) {
-                            Some((*timestamp, u32::MAX))
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-                buffer.undo_operations(undo_counts, cx);
-            });
+            entry.discard_branch_changes(cx);
         }
 
         self.multibuffer.update(cx, |multibuffer, cx| {
@@ -229,6 +214,34 @@ impl ProposedChangesEditor {
         });
     }
 
+    /// Returns whether some of the proposed changes were applied to the base buffers.
+    pub fn has_applied_changes(&self, cx: &AppContext) -> bool {
+        self.buffer_entries
+            .iter()
+            .any(|entry| entry.branch.read(cx).has_merged_into_base())
+    }
+
+    /// Undoes the proposed changes that were applied to the base buffers, and discards
+    /// the ones that weren't, leaving the files as they were before the changes were proposed.
+    pub fn undo_all_changes(&mut self, cx: &mut ViewContext<Self>) {
+        let had_applied_changes = self.has_applied_changes(cx);
+        for entry in &self.buffer_entries {
+            entry
+                .branch
+                .update(cx, |branch, cx| branch.undo_merges_into_base(cx));
+            entry.discard_branch_changes(cx);
+        }
+
+        if had_applied_changes {
+            self.editor.update(cx, |editor, cx| {
+                if let Some(project) = editor.project.clone() {
+                    editor.save(true, project, cx).detach_and_log_err(cx);
+                }
+            });
+        }
+        cx.notify();
+    }
+
     pub fn recalculate_all_buffer_diffs(&self) {
         for (ix, entry) in self.buffer_entries.iter().enumerate().rev() {
             self.recalculate_diffs_tx
@@ -268,6 +281,27 @@ impl ProposedChangesEditor {
     }
 }
 
+impl BufferEntry {
+    /// Undoes the changes of the branch buffer that its base buffer hasn't seen.
+    fn discard_branch_changes(&self, cx: &mut AppContext) {
+        let base_version = self.base.read(cx).version();
+        self.branch.update(cx, |buffer, cx| {
+            let undo_counts = buffer
+                .operations()
+                .iter()
+                .filter_map(|(timestamp, _)| {
+                    if !base_version.observed(*timestamp) {
+                        Some((*timestamp, u32::MAX))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            buffer.undo_operations(undo_counts, cx);
+        });
+    }
+}
+
 impl Render for ProposedChangesEditor {
     fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
         div()
@@ -373,20 +407,51 @@ impl ProposedChangesEditorToolbar {
 
 impl Render for ProposedChangesEditorToolbar {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
-        let button_like = ButtonLike::new("apply-changes").child(Label::new("Apply All"));
-
-        match &self.current_editor {
-            Some(editor) => {
-                let focus_handle = editor.focus_handle(cx);
-                let keybinding = KeyBinding::for_action_in(&ApplyAllDiffHunks, &focus_handle, cx)
-                    .map(|binding| binding.into_any_element());
+        let undo_button = ButtonLike::new("undo-changes").child(Label::new("Undo Applied"));
+        let discard_button = ButtonLike::new("discard-changes").child(Label::new("Discard All"));
+        let apply_button = ButtonLike::new("apply-changes").child(Label::new("Apply All"));
+
+        let Some(editor) = &self.current_editor else {
+            return h_flex()
+                .gap_1()
+                .child(undo_button.disabled(true))
+                .child(discard_button.disabled(true))
+                .child(apply_button.disabled(true));
+        };
 
-                button_like.children(keybinding).on_click({
-                    move |_event, cx| focus_handle.dispatch_action(&ApplyAllDiffHunks, cx)
-                })
-            }
-            None => button_like.disabled(true),
-        }
+        let focus_handle = editor.focus_handle(cx);
+        let has_applied_changes = editor.read(cx).has_applied_changes(cx);
+        let discard_keybinding = KeyBinding::for_action_in(&RevertFile, &focus_handle, cx)
+            .map(|binding| binding.into_any_element());
+        let apply_keybinding = KeyBinding::for_action_in(&ApplyAllDiffHunks, &focus_handle, cx)
+            .map(|binding| binding.into_any_element());
+
+        h_flex()
+            .gap_1()
+            .child(
+                undo_button
+                    .disabled(!has_applied_changes)
+                    .tooltip(|cx| Tooltip::text("Undo the changes already applied", cx))
+                    .on_click({
+                        let editor = editor.downgrade();
+                        move |_event, cx| {
+                            editor
+                                .update(cx, |editor, cx| editor.undo_all_changes(cx))
+                                .ok();
+                        }
+                    }),
+            )
+            .child(discard_button.children(discard_keybinding).on_click({
+                let focus_handle = focus_handle.clone();
+                move |_event, cx| focus_handle.dispatch_action(&RevertFile, cx)
+            }))
+            .child(
+                apply_button
+                    .children(apply_keybinding)
+                    .on_click(move |_event, cx| {
+                        focus_handle.dispatch_action(&ApplyAllDiffHunks, cx)
+                    }),
+            )
     }
 }
 
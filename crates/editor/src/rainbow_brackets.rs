@@ -0,0 +1,378 @@
+use std::{any::TypeId, cmp::Reverse, ops::Range};
+
+use gpui::{AppContext, HighlightStyle, Hsla, ViewContext};
+use language::{language_settings::language_settings, BufferSnapshot};
+use multi_buffer::MultiBufferSnapshot;
+use text::Bias;
+use theme::ActiveTheme as _;
+
+use crate::{DisplayPoint, DisplayRow, Editor};
+
+/// The maximum number of colors that bracket pairs cycle through. Palettes with
+/// fewer accent colors cycle through all of them.
+const MAX_RAINBOW_BRACKET_COLORS: usize = 8;
+
+pub(crate) struct RainbowBracketHighlight<const COLOR_INDEX: usize>;
+
+/// The colors that bracket pairs cycle through, taken from the theme's accents.
+fn rainbow_palette(cx: &AppContext) -> &[Hsla] {
+    let accents = &cx.theme().accents().0;
+    &accents[..accents.len().min(MAX_RAINBOW_BRACKET_COLORS)]
+}
+
+fn rainbow_bracket_highlight_key(color_index: usize) -> TypeId {
+    match color_index {
+        0 => TypeId::of::<RainbowBracketHighlight<0>>(),
+        1 => TypeId::of::<RainbowBracketHighlight<1>>(),
+        2 => TypeId::of::<RainbowBracketHighlight<2>>(),
+        3 => TypeId::of::<RainbowBracketHighlight<3>>(),
+        4 => TypeId::of::<RainbowBracketHighlight<4>>(),
+        5 => TypeId::of::<RainbowBracketHighlight<5>>(),
+        6 => TypeId::of::<RainbowBracketHighlight<6>>(),
+        _ => TypeId::of::<RainbowBracketHighlight<7>>(),
+    }
+}
+
+/// The innermost multi-line bracket pair containing the newest cursor, as
+/// multibuffer offsets.
+pub(crate) struct ActiveScope {
+    pub open: Range<usize>,
+    pub close: Range<usize>,
+    pub color: Hsla,
+}
+
+impl Editor {
+    /// Colors the bracket pairs in the visible rows by their nesting depth.
+    pub(crate) fn refresh_rainbow_brackets(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(visible_line_count) = self.visible_line_count() else {
+            return;
+        };
+        if !self.rainbow_brackets_enabled(cx) && !self.has_rainbow_bracket_highlights(cx) {
+            return;
+        }
+
+        let display_snapshot = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let scroll_top = self
+            .scroll_manager
+            .anchor()
+            .scroll_position(&display_snapshot)
+            .y;
+        let visible_start = DisplayPoint::new(DisplayRow(scroll_top.floor() as u32), 0);
+        let visible_end = display_snapshot.clip_point(
+            DisplayPoint::new(
+                DisplayRow((scroll_top + visible_line_count).ceil() as u32 + 1),
+                0,
+            ),
+            Bias::Left,
+        );
+        let visible_range = visible_start.to_offset(&display_snapshot, Bias::Left)
+            ..visible_end.to_offset(&display_snapshot, Bias::Right);
+        let multi_buffer = &display_snapshot.buffer_snapshot;
+
+        let palette = rainbow_palette(cx);
+        let color_count = palette.len();
+        let mut ranges_by_color = vec![Vec::new(); color_count];
+        if color_count > 0 {
+            for excerpt in multi_buffer.excerpts_for_range(visible_range.clone()) {
+                let buffer = excerpt.buffer();
+                let settings =
+                    language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx);
+                if !settings.rainbow_brackets.enabled {
+                    continue;
+                }
+
+                let buffer_range = excerpt.map_range_to_buffer(visible_range.clone());
+                for (depth, open, close) in bracket_pairs_with_depth(buffer, buffer_range) {
+                    for range in [open, close] {
+                        if excerpt.contains_buffer_range(range.clone()) {
+                            ranges_by_color[depth % color_count]
+                                .push(excerpt.map_range_from_buffer(range));
+                        }
+                    }
+                }
+            }
+        }
+
+        let highlights = ranges_by_color
+            .into_iter()
+            .zip(palette.iter())
+            .map(|(mut ranges, color)| {
+                ranges.sort_unstable_by_key(|range| range.start);
+                let ranges = ranges
+                    .into_iter()
+                    .map(|range| {
+                        multi_buffer.anchor_after(range.start)
+                            ..multi_buffer.anchor_before(range.end)
+                    })
+                    .collect::<Vec<_>>();
+                let style = HighlightStyle {
+                    color: Some(*color),
+                    ..Default::default()
+                };
+                (ranges, style)
+            })
+            .collect::<Vec<_>>();
+
+        self.display_map.update(cx, |display_map, _| {
+            let mut highlights = highlights.into_iter();
+            for color_index in 0..MAX_RAINBOW_BRACKET_COLORS {
+                let key = rainbow_bracket_highlight_key(color_index);
+                match highlights.next() {
+                    Some((ranges, style)) if !ranges.is_empty() => {
+                        display_map.highlight_text(key, ranges, style)
+                    }
+                    _ => {
+                        display_map.clear_highlights(key);
+                    }
+                }
+            }
+        });
+        cx.notify();
+    }
+
+    /// Whether rainbow brackets are enabled for any of the editor's buffers.
+    fn rainbow_brackets_enabled(&self, cx: &AppContext) -> bool {
+        self.buffer.read(cx).all_buffers().iter().any(|buffer| {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx)
+                .rainbow_brackets
+                .enabled
+        })
+    }
+
+    fn has_rainbow_bracket_highlights(&self, cx: &AppContext) -> bool {
+        let display_map = self.display_map.read(cx);
+        (0..MAX_RAINBOW_BRACKET_COLORS).any(|color_index| {
+            display_map
+                .text_highlights(rainbow_bracket_highlight_key(color_index))
+                .is_some()
+        })
+    }
+}
+
+/// Returns the bracket pair to draw a scope guide for around the given cursor
+/// position, if scope guides are enabled.
+pub(crate) fn active_scope(
+    snapshot: &MultiBufferSnapshot,
+    head: usize,
+    cx: &AppContext,
+) -> Option<ActiveScope> {
+    let settings = snapshot.settings_at(head, cx).rainbow_brackets;
+    if !settings.scope_guide {
+        return None;
+    }
+
+    let is_multiline_pair = |open: Range<usize>, close: Range<usize>| {
+        snapshot.offset_to_point(open.start).row + 1 < snapshot.offset_to_point(close.end).row
+    };
+    let (open, close) = snapshot
+        .enclosing_bracket_ranges(head..head)?
+        .filter(|(open, close)| {
+            is_colored_pair(
+                snapshot.text_for_range(open.clone()).collect(),
+                snapshot.text_for_range(close.clone()).collect(),
+            ) && is_multiline_pair(open.clone(), close.clone())
+        })
+        .min_by_key(|(open, close)| close.end - open.start)?;
+    let depth = snapshot
+        .enclosing_bracket_ranges(open.clone())?
+        .filter(|(outer_open, outer_close)| {
+            outer_open.start < open.start
+                && is_colored_pair(
+                    snapshot.text_for_range(outer_open.clone()).collect(),
+                    snapshot.text_for_range(outer_close.clone()).collect(),
+                )
+        })
+        .count();
+
+    let palette = rainbow_palette(cx);
+    let color = if settings.enabled && !palette.is_empty() {
+        palette[depth % palette.len()]
+    } else {
+        cx.theme().colors().editor_indent_guide_active
+    };
+
+    Some(ActiveScope { open, close, color })
+}
+
+/// Whether a bracket pair should be colored. Pairs whose opening and closing text are
+/// the same, such as quotes, don't nest and are left alone.
+fn is_colored_pair(open: String, close: String) -> bool {
+    !open.is_empty() && open != close
+}
+
+/// Returns the bracket pairs overlapping the given range along with their nesting
+/// depth, ordered by the position of their opening bracket.
+fn bracket_pairs_with_depth(
+    buffer: &BufferSnapshot,
+    range: Range<usize>,
+) -> Vec<(usize, Range<usize>, Range<usize>)> {
+    let mut pairs = buffer
+        .bracket_ranges(range)
+        .filter(|(open, close)| {
+            is_colored_pair(
+                buffer.text_for_range(open.clone()).collect(),
+                buffer.text_for_range(close.clone()).collect(),
+            )
+        })
+        .collect::<Vec<_>>();
+    pairs.sort_unstable_by_key(|(open, close)| (open.start, Reverse(close.end)));
+    pairs.dedup();
+
+    // Pairs that enclose the visible range are included, so every pair's ancestors
+    // precede it and the depth is exact.
+    let mut enclosing_ends = Vec::<usize>::new();
+    pairs
+        .into_iter()
+        .map(|(open, close)| {
+            while enclosing_ends
+                .last()
+                .map_or(false, |&end| end <= open.start)
+            {
+                enclosing_ends.pop();
+            }
+            let depth = enclosing_ends.len();
+            enclosing_ends.push(close.end);
+            (depth, open, close)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::{init_test, update_test_language_settings},
+        test::editor_lsp_test_context::EditorLspTestContext,
+    };
+    use indoc::indoc;
+    use language::{
+        language_settings::RainbowBracketSettings, Language, LanguageConfig, LanguageMatcher, Point,
+    };
+
+    #[gpui::test]
+    async fn test_rainbow_brackets(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.rainbow_brackets = Some(RainbowBracketSettings {
+                enabled: true,
+                scope_guide: false,
+            });
+        });
+
+        let mut cx = EditorLspTestContext::new(rust_lang(), Default::default(), cx).await;
+        cx.update_editor(|editor, cx| editor.set_visible_line_count(20., cx));
+
+        cx.set_state(indoc! {r#"
+            fn test(a: &str) {
+                call("Test", (1, 2));
+            }ˇ
+        "#});
+        cx.run_until_parked();
+        cx.update_editor(|editor, cx| editor.refresh_rainbow_brackets(cx));
+
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<0>>(indoc! {r#"
+            fn test«(»a: &str«)» «{»
+                call("Test", (1, 2));
+            «}»
+        "#});
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<1>>(indoc! {r#"
+            fn test(a: &str) {
+                call«(»"Test", (1, 2)«)»;
+            }
+        "#});
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<2>>(indoc! {r#"
+            fn test(a: &str) {
+                call("Test", «(»1, 2«)»);
+            }
+        "#});
+        cx.assert_editor_text_highlights::<RainbowBracketHighlight<3>>(indoc! {r#"
+            fn test(a: &str) {
+                call("Test", (1, 2));
+            }
+        "#});
+    }
+
+    #[gpui::test]
+    async fn test_scope_guide(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.rainbow_brackets = Some(RainbowBracketSettings {
+                enabled: true,
+                scope_guide: true,
+            });
+        });
+
+        let mut cx = EditorLspTestContext::new(rust_lang(), Default::default(), cx).await;
+        cx.set_state(indoc! {r#"
+            fn test() {
+                if true {
+                    call(1, 2);ˇ
+                }
+            }
+        "#});
+        cx.run_until_parked();
+
+        // The guide is drawn for the innermost pair spanning multiple lines, in the
+        // color of its depth.
+        let (open, close, color) = cx.update_editor(|editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let head = editor.selections.newest::<usize>(cx).head();
+            let scope = active_scope(&snapshot, head, cx).unwrap();
+            (
+                snapshot.offset_to_point(scope.open.start),
+                snapshot.offset_to_point(scope.close.start),
+                scope.color,
+            )
+        });
+        assert_eq!(open, Point::new(1, 12));
+        assert_eq!(close, Point::new(3, 4));
+        assert_eq!(color, cx.update_editor(|_, cx| cx.theme().accents().0[1]));
+
+        // Pairs on adjacent lines are too short to need a guide.
+        cx.set_state(indoc! {r#"
+            fn test() {
+                if true { callˇ(1, 2); }
+            }
+        "#});
+        cx.run_until_parked();
+        let open = cx.update_editor(|editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let head = editor.selections.newest::<usize>(cx).head();
+            active_scope(&snapshot, head, cx)
+                .map(|scope| snapshot.offset_to_point(scope.open.start))
+        });
+        assert_eq!(open, Some(Point::new(0, 10)));
+
+        update_test_language_settings(&mut cx, |settings| {
+            settings.defaults.rainbow_brackets = Some(RainbowBracketSettings {
+                enabled: true,
+                scope_guide: false,
+            });
+        });
+        let scope = cx.update_editor(|editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let head = editor.selections.newest::<usize>(cx).head();
+            active_scope(&snapshot, head, cx).is_some()
+        });
+        assert!(!scope);
+    }
+
+    fn rust_lang() -> Language {
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_brackets_query(indoc! {r#"
+            ("{" @open "}" @close)
+            ("(" @open ")" @close)
+            ("\"" @open "\"" @close)
+            "#})
+        .unwrap()
+    }
+}
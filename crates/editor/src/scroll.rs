@@ -361,12 +361,16 @@ impl Editor {
 
     pub(crate) fn set_visible_line_count(&mut self, lines: f32, cx: &mut ViewContext<Self>) {
         let opened_first_time = self.scroll_manager.visible_line_count.is_none();
+        let line_count_changed = self.scroll_manager.visible_line_count != Some(lines);
         self.scroll_manager.visible_line_count = Some(lines);
-        if opened_first_time {
+        if opened_first_time || line_count_changed {
             cx.spawn(|editor, mut cx| async move {
                 editor
                     .update(&mut cx, |editor, cx| {
-                        editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx)
+                        if opened_first_time {
+                            editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx)
+                        }
+                        editor.refresh_rainbow_brackets(cx);
                     })
                     .ok()
             })
@@ -430,6 +434,7 @@ impl Editor {
         );
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+        self.refresh_rainbow_brackets(cx);
     }
 
     pub fn scroll_position(&self, cx: &mut ViewContext<Self>) -> gpui::Point<f32> {
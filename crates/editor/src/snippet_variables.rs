@@ -0,0 +1,166 @@
+use crate::Editor;
+use chrono::Local;
+use gpui::{Model, ViewContext};
+use language::{Buffer, Point};
+use multi_buffer::MultiBufferRow;
+use rand::Rng as _;
+use std::path::PathBuf;
+
+/// The values of the variables that snippets can reference, such as `$TM_FILENAME`,
+/// resolved against the newest selection when the snippet is inserted.
+pub(crate) struct SnippetVariables {
+    selected_text: String,
+    current_line: String,
+    current_word: String,
+    line_index: u32,
+    file_path: Option<PathBuf>,
+    relative_file_path: Option<PathBuf>,
+    workspace_name: Option<String>,
+    workspace_folder: Option<PathBuf>,
+    clipboard: Option<String>,
+    line_comment: Option<String>,
+    block_comment: Option<(String, String)>,
+}
+
+impl SnippetVariables {
+    pub(crate) fn new(
+        editor: &Editor,
+        buffer: &Model<Buffer>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Self {
+        let selection = editor.selections.newest::<Point>(cx);
+        let snapshot = editor.buffer.read(cx).snapshot(cx);
+        let row = selection.head().row;
+        let selected_text = snapshot
+            .text_for_range(selection.range())
+            .collect::<String>();
+        let current_line = snapshot
+            .text_for_range(
+                Point::new(row, 0)..Point::new(row, snapshot.line_len(MultiBufferRow(row))),
+            )
+            .collect::<String>();
+        let (word_range, _) = snapshot.surrounding_word(selection.head(), false);
+        let current_word = snapshot.text_for_range(word_range).collect::<String>();
+
+        let scope = snapshot.language_scope_at(selection.head());
+        let line_comment = scope.as_ref().and_then(|scope| {
+            scope
+                .line_comment_prefixes()
+                .first()
+                .map(|prefix| prefix.trim_end().to_string())
+        });
+        let block_comment = scope.as_ref().and_then(|scope| {
+            scope
+                .block_comment_delimiters()
+                .map(|(start, end)| (start.trim().to_string(), end.trim().to_string()))
+        });
+
+        let file = buffer.read(cx).file().cloned();
+        let file_path = file.as_ref().map(|file| match file.as_local() {
+            Some(local) => local.abs_path(cx),
+            None => file.full_path(cx),
+        });
+        let relative_file_path = file.as_ref().map(|file| file.path().to_path_buf());
+        let worktree = editor.project.as_ref().and_then(|project| {
+            let project = project.read(cx);
+            match &file {
+                Some(file) => project.worktree_for_id(file.worktree_id(cx), cx),
+                None => project.visible_worktrees(cx).next(),
+            }
+        });
+        let (workspace_name, workspace_folder) = match worktree {
+            Some(worktree) => {
+                let worktree = worktree.read(cx);
+                (
+                    Some(worktree.root_name().to_string()),
+                    Some(worktree.abs_path().to_path_buf()),
+                )
+            }
+            None => (None, None),
+        };
+
+        Self {
+            selected_text,
+            current_line,
+            current_word,
+            line_index: row,
+            file_path,
+            relative_file_path,
+            workspace_name,
+            workspace_folder,
+            clipboard: cx.read_from_clipboard().and_then(|item| item.text()),
+            line_comment,
+            block_comment,
+        }
+    }
+}
+
+impl snippet::VariableResolver for SnippetVariables {
+    fn resolve(&self, name: &str) -> Option<String> {
+        let path_string = |path: &Option<PathBuf>| {
+            path.as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        let now = Local::now();
+        let value = match name {
+            "TM_SELECTED_TEXT" => self.selected_text.clone(),
+            "TM_CURRENT_LINE" => self.current_line.clone(),
+            "TM_CURRENT_WORD" => self.current_word.clone(),
+            "TM_LINE_INDEX" => self.line_index.to_string(),
+            "TM_LINE_NUMBER" => (self.line_index + 1).to_string(),
+            "TM_FILENAME" => self
+                .file_path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "TM_FILENAME_BASE" => self
+                .file_path
+                .as_ref()
+                .and_then(|path| path.file_stem())
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "TM_DIRECTORY" => self
+                .file_path
+                .as_ref()
+                .and_then(|path| path.parent())
+                .map(|parent| parent.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            "TM_FILEPATH" => path_string(&self.file_path),
+            "RELATIVE_FILEPATH" => path_string(&self.relative_file_path),
+            "WORKSPACE_NAME" => self.workspace_name.clone().unwrap_or_default(),
+            "WORKSPACE_FOLDER" => path_string(&self.workspace_folder),
+            "CLIPBOARD" => self.clipboard.clone().unwrap_or_default(),
+            "CURRENT_YEAR" => now.format("%Y").to_string(),
+            "CURRENT_YEAR_SHORT" => now.format("%y").to_string(),
+            "CURRENT_MONTH" => now.format("%m").to_string(),
+            "CURRENT_MONTH_NAME" => now.format("%B").to_string(),
+            "CURRENT_MONTH_NAME_SHORT" => now.format("%b").to_string(),
+            "CURRENT_DATE" => now.format("%d").to_string(),
+            "CURRENT_DAY_NAME" => now.format("%A").to_string(),
+            "CURRENT_DAY_NAME_SHORT" => now.format("%a").to_string(),
+            "CURRENT_HOUR" => now.format("%H").to_string(),
+            "CURRENT_MINUTE" => now.format("%M").to_string(),
+            "CURRENT_SECOND" => now.format("%S").to_string(),
+            "CURRENT_SECONDS_UNIX" => now.timestamp().to_string(),
+            "CURRENT_TIMEZONE_OFFSET" => now.format("%:z").to_string(),
+            "RANDOM" => format!("{:06}", rand::thread_rng().gen_range(0..1_000_000)),
+            "RANDOM_HEX" => format!("{:06x}", rand::thread_rng().gen_range(0..0x1000000)),
+            "UUID" => uuid::Uuid::new_v4().to_string(),
+            "LINE_COMMENT" => self.line_comment.clone().unwrap_or_default(),
+            "BLOCK_COMMENT_START" => self
+                .block_comment
+                .as_ref()
+                .map(|(start, _)| start.clone())
+                .unwrap_or_default(),
+            "BLOCK_COMMENT_END" => self
+                .block_comment
+                .as_ref()
+                .map(|(_, end)| end.clone())
+                .unwrap_or_default(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
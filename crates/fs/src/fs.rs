@@ -31,6 +31,7 @@ use serde::{Deserialize, Serialize};
 use smol::io::AsyncWriteExt;
 use std::{
     io::{self, Write},
+    ops::Range,
     path::{Component, Path, PathBuf},
     pin::Pin,
     sync::Arc,
@@ -106,6 +107,9 @@ pub trait Fs: Send + Sync {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Loads the bytes in the given range of a file. The result is shorter than the
+    /// range if the file ends before the range does.
+    async fn load_bytes_range(&self, path: &Path, range: Range<u64>) -> Result<Vec<u8>>;
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
     async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
@@ -512,6 +516,22 @@ impl Fs for RealFs {
         Ok(bytes)
     }
 
+    async fn load_bytes_range(&self, path: &Path, range: Range<u64>) -> Result<Vec<u8>> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let path = path.to_path_buf();
+        let bytes = smol::unblock(move || -> io::Result<Vec<u8>> {
+            let mut file = std::fs::File::open(path)?;
+            file.seek(SeekFrom::Start(range.start))?;
+            let mut bytes = Vec::new();
+            file.take(range.end.saturating_sub(range.start))
+                .read_to_end(&mut bytes)?;
+            Ok(bytes)
+        })
+        .await?;
+        Ok(bytes)
+    }
+
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
         smol::unblock(move || {
             let mut tmp_file = if cfg!(any(target_os = "linux", target_os = "freebsd")) {
@@ -1793,6 +1813,14 @@ impl Fs for FakeFs {
         self.load_internal(path).await
     }
 
+    async fn load_bytes_range(&self, path: &Path, range: Range<u64>) -> Result<Vec<u8>> {
+        let bytes = self.load_internal(path).await?;
+        let len = bytes.len() as u64;
+        let start = range.start.min(len);
+        let end = range.end.clamp(start, len);
+        Ok(bytes[start as usize..end as usize].to_vec())
+    }
+
     async fn atomic_write(&self, path: PathBuf, data: String) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path.as_path());
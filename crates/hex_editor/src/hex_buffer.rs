@@ -0,0 +1,886 @@
+use std::{
+    collections::VecDeque,
+    ffi::OsStr,
+    future::Future,
+    io,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::{HashMap, HashSet};
+use fs::{Fs, RemoveOptions};
+use futures::{stream, AsyncRead, StreamExt as _, TryStreamExt as _};
+use gpui::{AppContext, Context as _, EventEmitter, Model, ModelContext, Subscription, Task};
+use project::{File, Project, ProjectEntryId, ProjectPath, Worktree};
+use util::ResultExt as _;
+
+use crate::{
+    piece_table::{PieceTable, Source},
+    search,
+};
+
+/// The size of the pages in which the original file is read.
+const PAGE_SIZE: u64 = 64 * 1024;
+
+/// The number of pages of the original file kept in memory, 16 MiB in total.
+const MAX_CACHED_PAGES: usize = 256;
+
+/// The size of the chunks in which the file is read when searching or saving.
+const CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Extensions of files that are opened in the hex editor rather than as text.
+/// Images are left to the image viewer.
+const BINARY_EXTENSIONS: &[&str] = &[
+    "7z", "a", "avi", "bin", "bz2", "class", "dat", "db", "dll", "dmg", "dylib", "elf", "exe",
+    "flac", "gz", "iso", "jar", "lib", "mkv", "mov", "mp3", "mp4", "o", "obj", "ogg", "otf", "pdb",
+    "pyc", "rar", "rlib", "so", "sqlite", "sqlite3", "tgz", "ttf", "wasm", "wav", "woff", "woff2",
+    "xz", "zip", "zst",
+];
+
+pub fn is_binary_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .map_or(false, |extension| {
+            BINARY_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        })
+}
+
+pub enum HexBufferEvent {
+    Edited,
+    PagesLoaded,
+    Saved,
+    Reloaded,
+    FileHandleChanged,
+}
+
+/// A local file being edited as bytes.
+///
+/// Only the pages that have been displayed recently are kept in memory, and edits
+/// are recorded in a [`PieceTable`] on top of the file, so files larger than the
+/// available memory can be viewed and edited.
+pub struct HexBuffer {
+    fs: Arc<dyn Fs>,
+    file: Arc<File>,
+    abs_path: PathBuf,
+    table: PieceTable,
+    saved_version: usize,
+    pages: HashMap<u64, Arc<[u8]>>,
+    page_order: VecDeque<u64>,
+    loading_pages: HashSet<u64>,
+    /// Incremented whenever the file on disk is replaced, so that pages loaded
+    /// from its previous contents are discarded.
+    generation: usize,
+    saving: bool,
+    /// Whether the file changed on disk while there were unsaved edits.
+    has_conflict: bool,
+    _worktree_subscription: Subscription,
+}
+
+impl EventEmitter<HexBufferEvent> for HexBuffer {}
+
+impl HexBuffer {
+    pub fn open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Task<Result<Model<Self>>> {
+        let project = project.read(cx);
+        let Some(worktree) = project.worktree_for_id(path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no worktree for {path:?}")));
+        };
+        if !worktree.read(cx).is_local() {
+            return Task::ready(Err(anyhow!("the hex editor only supports local files")));
+        }
+        let Some(entry) = worktree.read(cx).entry_for_path(&path.path).cloned() else {
+            return Task::ready(Err(anyhow!("no entry for {path:?}")));
+        };
+        if entry.is_dir() {
+            return Task::ready(Err(anyhow!("{path:?} is a directory")));
+        }
+        let abs_path = match worktree.read(cx).absolutize(&path.path) {
+            Ok(abs_path) => abs_path,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let fs = project.fs().clone();
+        let file = File::for_entry(entry, worktree);
+        cx.spawn(|mut cx| async move {
+            let metadata = fs
+                .metadata(&abs_path)
+                .await?
+                .with_context(|| format!("{abs_path:?} does not exist"))?;
+            cx.new_model(|cx| {
+                let worktree_subscription = cx.subscribe(&file.worktree, Self::on_worktree_event);
+                Self {
+                    fs,
+                    file,
+                    abs_path,
+                    table: PieceTable::new(metadata.len),
+                    saved_version: 0,
+                    pages: HashMap::default(),
+                    page_order: VecDeque::new(),
+                    loading_pages: HashSet::default(),
+                    generation: 0,
+                    saving: false,
+                    has_conflict: false,
+                    _worktree_subscription: worktree_subscription,
+                }
+            })
+        })
+    }
+
+    /// Reloads the file when it changes on disk, unless it has unsaved edits, in
+    /// which case the change is reported as a conflict.
+    fn on_worktree_event(
+        &mut self,
+        worktree: Model<Worktree>,
+        event: &worktree::Event,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let worktree::Event::UpdatedEntries(changes) = event else {
+            return;
+        };
+        if self.saving
+            || !changes
+                .iter()
+                .any(|(path, _, _)| path.as_ref() == self.file.path.as_ref())
+        {
+            return;
+        }
+        let Some(entry) = worktree.read(cx).entry_for_path(&self.file.path).cloned() else {
+            return;
+        };
+        if entry.mtime == self.file.disk_state.mtime() {
+            return;
+        }
+
+        self.file = File::for_entry(entry, worktree);
+        cx.emit(HexBufferEvent::FileHandleChanged);
+        if self.is_dirty() {
+            self.has_conflict = true;
+            cx.notify();
+        } else {
+            self.reload(cx).detach_and_log_err(cx);
+        }
+    }
+
+    pub fn file(&self) -> &Arc<File> {
+        &self.file
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn len(&self) -> u64 {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.table.version() != self.saved_version
+    }
+
+    pub fn is_saving(&self) -> bool {
+        self.saving
+    }
+
+    pub fn has_conflict(&self) -> bool {
+        self.has_conflict
+    }
+
+    /// Returns the bytes in the given range, with `None` for bytes whose page hasn't
+    /// been loaded yet. Missing pages are loaded in the background, and
+    /// [`HexBufferEvent::PagesLoaded`] is emitted once they're available.
+    pub fn read(&mut self, range: Range<u64>, cx: &mut ModelContext<Self>) -> Vec<Option<u8>> {
+        let mut bytes = Vec::with_capacity((range.end - range.start) as usize);
+        for (source, range) in self.table.chunks(range) {
+            match source {
+                Source::Added => {
+                    bytes.extend(self.table.added_bytes(range).iter().copied().map(Some))
+                }
+                Source::Original => {
+                    let mut offset = range.start;
+                    while offset < range.end {
+                        let page_index = offset / PAGE_SIZE;
+                        let page_start = page_index * PAGE_SIZE;
+                        let end = range.end.min(page_start + PAGE_SIZE);
+                        match self.page(page_index, cx) {
+                            Some(page) => {
+                                // Pages are shorter than expected if the file was
+                                // truncated on disk since it was opened.
+                                let page_range = ((offset - page_start) as usize).min(page.len())
+                                    ..((end - page_start) as usize).min(page.len());
+                                bytes.extend(page[page_range.clone()].iter().copied().map(Some));
+                                let missing = (end - offset) as usize - page_range.len();
+                                bytes.extend(std::iter::repeat(None).take(missing));
+                            }
+                            None => {
+                                bytes.extend(std::iter::repeat(None).take((end - offset) as usize))
+                            }
+                        }
+                        offset = end;
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Replaces the given range with the given bytes. When `amend` is true, the edit
+    /// is undone together with the previous one.
+    pub fn edit(
+        &mut self,
+        range: Range<u64>,
+        bytes: &[u8],
+        amend: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if self.saving {
+            return;
+        }
+        if amend {
+            self.table.amend(range, bytes);
+        } else {
+            self.table.replace(range, bytes);
+        }
+        cx.emit(HexBufferEvent::Edited);
+        cx.notify();
+    }
+
+    pub fn undo(&mut self, cx: &mut ModelContext<Self>) -> bool {
+        if self.saving || !self.table.undo() {
+            return false;
+        }
+        cx.emit(HexBufferEvent::Edited);
+        cx.notify();
+        true
+    }
+
+    pub fn redo(&mut self, cx: &mut ModelContext<Self>) -> bool {
+        if self.saving || !self.table.redo() {
+            return false;
+        }
+        cx.emit(HexBufferEvent::Edited);
+        cx.notify();
+        true
+    }
+
+    pub fn snapshot(&self) -> HexBufferSnapshot {
+        HexBufferSnapshot {
+            fs: self.fs.clone(),
+            abs_path: self.abs_path.clone(),
+            table: Arc::new(self.table.clone()),
+        }
+    }
+
+    /// Writes the contents to the file, streaming them in chunks.
+    ///
+    /// The undo history is cleared once saved, since edits are recorded relative to
+    /// the file's previous contents.
+    pub fn save(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let path = self.file.path.clone();
+        self.save_to(self.file.worktree.clone(), path, cx)
+    }
+
+    /// Writes the contents to a different file, which is edited from then on.
+    pub fn save_as(
+        &mut self,
+        project: &Model<Project>,
+        path: ProjectPath,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = project.read(cx).worktree_for_id(path.worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no worktree for {path:?}")));
+        };
+        if !worktree.read(cx).is_local() {
+            return Task::ready(Err(anyhow!("the hex editor only supports local files")));
+        }
+        self.save_to(worktree, path.path, cx)
+    }
+
+    fn save_to(
+        &mut self,
+        worktree: Model<Worktree>,
+        path: Arc<Path>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        if self.saving {
+            return Task::ready(Err(anyhow!("{:?} is already being saved", self.abs_path)));
+        }
+        let abs_path = match worktree.read(cx).absolutize(&path) {
+            Ok(abs_path) => abs_path,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        self.saving = true;
+        cx.notify();
+
+        let snapshot = self.snapshot();
+        let fs = self.fs.clone();
+        cx.spawn(|this, mut cx| async move {
+            let result = write_snapshot(&fs, &abs_path, snapshot).await;
+            let file = match &result {
+                Ok(_) => {
+                    let refresh = worktree
+                        .update(&mut cx, |worktree, cx| {
+                            worktree
+                                .as_local()
+                                .map(|worktree| worktree.refresh_file(path, cx))
+                        })
+                        .ok()
+                        .flatten();
+                    match refresh {
+                        Some(refresh) => refresh.await.log_err(),
+                        None => None,
+                    }
+                }
+                Err(_) => None,
+            };
+            this.update(&mut cx, |this, cx| {
+                this.saving = false;
+                if let Ok(len) = &result {
+                    if let Some(file) = file {
+                        if this.file.worktree != file.worktree || this.file.path != file.path {
+                            this.abs_path = abs_path;
+                            this._worktree_subscription =
+                                cx.subscribe(&file.worktree, Self::on_worktree_event);
+                        }
+                        this.file = file;
+                        cx.emit(HexBufferEvent::FileHandleChanged);
+                    }
+                    this.reset(*len);
+                    cx.emit(HexBufferEvent::Saved);
+                }
+                cx.notify();
+            })?;
+            result.map(|_| ())
+        })
+    }
+
+    /// Discards all edits and reloads the file from disk.
+    pub fn reload(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        cx.spawn(|this, mut cx| async move {
+            let metadata = fs
+                .metadata(&abs_path)
+                .await?
+                .with_context(|| format!("{abs_path:?} does not exist"))?;
+            this.update(&mut cx, |this, cx| {
+                this.reset(metadata.len);
+                cx.emit(HexBufferEvent::Reloaded);
+                cx.notify();
+            })
+        })
+    }
+
+    /// Searches for the given query starting at `offset`, wrapping around at the
+    /// end (or the start when searching backwards) of the file.
+    pub fn search(
+        &self,
+        query: Vec<u8>,
+        offset: u64,
+        backwards: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<Range<u64>>>> {
+        let snapshot = self.snapshot();
+        cx.background_executor().spawn(async move {
+            let len = snapshot.len();
+            let offset = offset.min(len);
+            let found = if backwards {
+                match snapshot.find_backwards(&query, 0..offset).await? {
+                    Some(start) => Some(start),
+                    None => snapshot.find_backwards(&query, offset..len).await?,
+                }
+            } else {
+                match snapshot.find(&query, offset..len).await? {
+                    Some(start) => Some(start),
+                    None => snapshot.find(&query, 0..offset).await?,
+                }
+            };
+            Ok(found.map(|start| start..start + query.len() as u64))
+        })
+    }
+
+    fn reset(&mut self, len: u64) {
+        self.has_conflict = false;
+        self.table = PieceTable::new(len);
+        self.saved_version = self.table.version();
+        self.pages.clear();
+        self.page_order.clear();
+        self.loading_pages.clear();
+        self.generation += 1;
+    }
+
+    fn page(&mut self, page_index: u64, cx: &mut ModelContext<Self>) -> Option<Arc<[u8]>> {
+        if let Some(page) = self.pages.get(&page_index) {
+            if let Some(position) = self.page_order.iter().position(|ix| *ix == page_index) {
+                self.page_order.remove(position);
+            }
+            self.page_order.push_back(page_index);
+            return Some(page.clone());
+        }
+
+        if self.loading_pages.insert(page_index) {
+            let fs = self.fs.clone();
+            let abs_path = self.abs_path.clone();
+            let generation = self.generation;
+            cx.spawn(|this, mut cx| async move {
+                let page_start = page_index * PAGE_SIZE;
+                let page = fs
+                    .load_bytes_range(&abs_path, page_start..page_start + PAGE_SIZE)
+                    .await
+                    .with_context(|| format!("reading {abs_path:?} at offset {page_start}"))
+                    .log_err();
+                this.update(&mut cx, |this, cx| {
+                    if this.generation != generation {
+                        return;
+                    }
+                    this.loading_pages.remove(&page_index);
+                    if let Some(page) = page {
+                        this.insert_page(page_index, page.into());
+                        cx.emit(HexBufferEvent::PagesLoaded);
+                        cx.notify();
+                    }
+                })
+                .ok();
+            })
+            .detach();
+        }
+        None
+    }
+
+    fn insert_page(&mut self, page_index: u64, page: Arc<[u8]>) {
+        self.pages.insert(page_index, page);
+        self.page_order.push_back(page_index);
+        while self.page_order.len() > MAX_CACHED_PAGES {
+            if let Some(evicted) = self.page_order.pop_front() {
+                self.pages.remove(&evicted);
+            }
+        }
+    }
+}
+
+/// Writes the snapshot to the given path.
+///
+/// As the snapshot reads unedited bytes from its own file, it can't overwrite that
+/// file directly. Its contents are written to a temporary file next to it first,
+/// which is then copied over the original in place rather than renamed over it, so
+/// that the original keeps its permissions, ownership and extended attributes. The
+/// temporary file is only removed once the copy succeeded, as the original may be
+/// left truncated otherwise.
+async fn write_snapshot(
+    fs: &Arc<dyn Fs>,
+    abs_path: &Path,
+    snapshot: HexBufferSnapshot,
+) -> Result<u64> {
+    let len = snapshot.len();
+    if snapshot.abs_path != abs_path {
+        let mut reader = chunked_reader(len, move |range| {
+            let snapshot = snapshot.clone();
+            async move { snapshot.read(range).await }
+        });
+        fs.create_file_with(abs_path, std::pin::Pin::new(&mut reader))
+            .await
+            .with_context(|| format!("writing {abs_path:?}"))?;
+        return Ok(len);
+    }
+
+    let file_name = abs_path
+        .file_name()
+        .with_context(|| format!("{abs_path:?} has no file name"))?;
+    let temp_path = abs_path.with_file_name(format!(".{}.zed-save", file_name.to_string_lossy()));
+
+    let mut reader = chunked_reader(len, move |range| {
+        let snapshot = snapshot.clone();
+        async move { snapshot.read(range).await }
+    });
+    let mut result = fs
+        .create_file_with(&temp_path, std::pin::Pin::new(&mut reader))
+        .await
+        .with_context(|| format!("writing {temp_path:?}"));
+    let mut keep_temp_file = false;
+    if result.is_ok() {
+        let mut reader = chunked_reader(len, {
+            let fs = fs.clone();
+            let temp_path = temp_path.clone();
+            move |range| {
+                let fs = fs.clone();
+                let temp_path = temp_path.clone();
+                async move { fs.load_bytes_range(&temp_path, range).await }
+            }
+        });
+        result = fs
+            .create_file_with(abs_path, std::pin::Pin::new(&mut reader))
+            .await
+            .with_context(|| {
+                format!("replacing {abs_path:?}, the saved contents are kept in {temp_path:?}")
+            });
+        keep_temp_file = result.is_err();
+    }
+
+    if !keep_temp_file {
+        fs.remove_file(
+            &temp_path,
+            RemoveOptions {
+                ignore_if_not_exists: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .log_err();
+    }
+    result.map(|_| len)
+}
+
+/// Returns a reader over `len` bytes, read in chunks by `read_chunk`.
+fn chunked_reader<F, Fut>(len: u64, read_chunk: F) -> impl AsyncRead + Send + Unpin
+where
+    F: Fn(Range<u64>) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<u8>>> + Send + 'static,
+{
+    stream::iter((0..len).step_by(CHUNK_SIZE as usize))
+        .then(move |start| {
+            let chunk = read_chunk(start..(start + CHUNK_SIZE).min(len));
+            async move {
+                chunk
+                    .await
+                    .map_err(|error| io::Error::new(io::ErrorKind::Other, error))
+            }
+        })
+        .boxed()
+        .into_async_read()
+}
+
+/// An immutable copy of a [`HexBuffer`]'s contents that reads the original file
+/// directly rather than through the page cache.
+#[derive(Clone)]
+pub struct HexBufferSnapshot {
+    fs: Arc<dyn Fs>,
+    abs_path: PathBuf,
+    table: Arc<PieceTable>,
+}
+
+impl HexBufferSnapshot {
+    pub fn len(&self) -> u64 {
+        self.table.len()
+    }
+
+    pub async fn read(&self, range: Range<u64>) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity((range.end - range.start) as usize);
+        for (source, range) in self.table.chunks(range) {
+            match source {
+                Source::Added => bytes.extend_from_slice(self.table.added_bytes(range)),
+                Source::Original => {
+                    let expected_len = range.end - range.start;
+                    let chunk = self.fs.load_bytes_range(&self.abs_path, range).await?;
+                    if chunk.len() as u64 != expected_len {
+                        return Err(anyhow!("{:?} was truncated on disk", self.abs_path));
+                    }
+                    bytes.extend_from_slice(&chunk);
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Returns the offset of the first occurrence of `needle` within the range.
+    async fn find(&self, needle: &[u8], range: Range<u64>) -> Result<Option<u64>> {
+        let overlap = needle.len().saturating_sub(1) as u64;
+        let mut start = range.start;
+        while start < range.end {
+            let end = (start + CHUNK_SIZE + overlap).min(range.end);
+            let chunk = self.read(start..end).await?;
+            if let Some(position) = search::find(&chunk, needle) {
+                return Ok(Some(start + position as u64));
+            }
+            if end == range.end {
+                break;
+            }
+            start += CHUNK_SIZE;
+        }
+        Ok(None)
+    }
+
+    /// Returns the offset of the last occurrence of `needle` starting within the range.
+    async fn find_backwards(&self, needle: &[u8], range: Range<u64>) -> Result<Option<u64>> {
+        let overlap = needle.len().saturating_sub(1) as u64;
+        let len = self.len();
+        let mut end = range.end;
+        while end > range.start {
+            let start = end.saturating_sub(CHUNK_SIZE).max(range.start);
+            let chunk = self.read(start..(end + overlap).min(len)).await?;
+            // The chunk extends past `end` by less than the needle's length, so every
+            // match found in it starts before `end`.
+            if let Some(position) = search::rfind(&chunk, needle) {
+                return Ok(Some(start + position as u64));
+            }
+            end = start;
+        }
+        Ok(None)
+    }
+}
+
+impl project::ProjectItem for HexBuffer {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Model<Self>>>> {
+        let worktree = project.read(cx).worktree_for_id(path.worktree_id, cx)?;
+        let worktree = worktree.read(cx);
+        if !worktree.is_local() {
+            return None;
+        }
+        if !is_binary_path(&path.path) {
+            return None;
+        }
+        if let Some(entry) = worktree.entry_for_path(&path.path) {
+            if !entry.is_file() || entry.is_fifo {
+                return None;
+            }
+        }
+        Some(Self::open(project, path, cx))
+    }
+
+    fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+        self.file.entry_id
+    }
+
+    fn project_path(&self, cx: &AppContext) -> Option<ProjectPath> {
+        Some(ProjectPath {
+            worktree_id: self.file.worktree.read(cx).id(),
+            path: self.file.path.clone(),
+        })
+    }
+
+    fn is_dirty(&self) -> bool {
+        HexBuffer::is_dirty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_edit_undo_and_save(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({})).await;
+        fs.insert_file("/dir/data.bin", vec![0, 1, 2, 3]).await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let buffer = open_buffer(&project, "data.bin", cx).await;
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit(1..2, &[0xff], false, cx);
+            buffer.edit(4..4, &[4, 5], false, cx);
+            assert!(buffer.is_dirty());
+        });
+        assert_eq!(contents(&buffer, cx).await, [0, 0xff, 2, 3, 4, 5]);
+
+        buffer.update(cx, |buffer, cx| assert!(buffer.undo(cx)));
+        assert_eq!(contents(&buffer, cx).await, [0, 0xff, 2, 3]);
+        buffer.update(cx, |buffer, cx| assert!(buffer.redo(cx)));
+        assert_eq!(contents(&buffer, cx).await, [0, 0xff, 2, 3, 4, 5]);
+
+        buffer
+            .update(cx, |buffer, cx| buffer.save(cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.load_bytes("/dir/data.bin".as_ref()).await.unwrap(),
+            [0, 0xff, 2, 3, 4, 5]
+        );
+        assert!(!fs.is_file("/dir/.data.bin.zed-save".as_ref()).await);
+        buffer.update(cx, |buffer, cx| {
+            assert!(!buffer.is_dirty());
+            // Edits made before saving can't be undone, as they were relative to the
+            // file's previous contents.
+            assert!(!buffer.undo(cx));
+        });
+        assert_eq!(contents(&buffer, cx).await, [0, 0xff, 2, 3, 4, 5]);
+    }
+
+    #[gpui::test]
+    async fn test_save_as(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({})).await;
+        fs.insert_file("/dir/data.bin", vec![0, 1, 2, 3]).await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let buffer = open_buffer(&project, "data.bin", cx).await;
+
+        buffer.update(cx, |buffer, cx| buffer.edit(0..1, &[9], false, cx));
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        buffer
+            .update(cx, |buffer, cx| {
+                buffer.save_as(
+                    &project,
+                    ProjectPath {
+                        worktree_id,
+                        path: Path::new("copy.bin").into(),
+                    },
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            fs.load_bytes("/dir/data.bin".as_ref()).await.unwrap(),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(
+            fs.load_bytes("/dir/copy.bin".as_ref()).await.unwrap(),
+            [9, 1, 2, 3]
+        );
+        buffer.read_with(cx, |buffer, _| {
+            assert!(!buffer.is_dirty());
+            assert_eq!(buffer.file().path.as_ref(), Path::new("copy.bin"));
+            assert_eq!(buffer.abs_path(), Path::new("/dir/copy.bin"));
+        });
+        assert_eq!(contents(&buffer, cx).await, [9, 1, 2, 3]);
+    }
+
+    #[gpui::test]
+    async fn test_reload_on_file_change(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({})).await;
+        fs.insert_file("/dir/data.bin", vec![0, 1, 2, 3]).await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let buffer = open_buffer(&project, "data.bin", cx).await;
+
+        // Without unsaved edits, the new contents are loaded.
+        fs.insert_file("/dir/data.bin", vec![7, 8]).await;
+        cx.executor().run_until_parked();
+        assert_eq!(contents(&buffer, cx).await, [7, 8]);
+        buffer.read_with(cx, |buffer, _| assert!(!buffer.has_conflict()));
+
+        // Saving doesn't cause the file to be reloaded.
+        buffer.update(cx, |buffer, cx| buffer.edit(0..1, &[9], false, cx));
+        buffer
+            .update(cx, |buffer, cx| buffer.save(cx))
+            .await
+            .unwrap();
+        cx.executor().run_until_parked();
+        buffer.read_with(cx, |buffer, _| {
+            assert!(!buffer.is_dirty());
+            assert!(!buffer.has_conflict());
+        });
+        assert_eq!(contents(&buffer, cx).await, [9, 8]);
+
+        // With unsaved edits, the change is reported as a conflict instead.
+        buffer.update(cx, |buffer, cx| buffer.edit(0..1, &[1], false, cx));
+        fs.insert_file("/dir/data.bin", vec![4, 5, 6]).await;
+        cx.executor().run_until_parked();
+        buffer.read_with(cx, |buffer, _| {
+            assert!(buffer.is_dirty());
+            assert!(buffer.has_conflict());
+        });
+
+        buffer
+            .update(cx, |buffer, cx| buffer.reload(cx))
+            .await
+            .unwrap();
+        buffer.read_with(cx, |buffer, _| {
+            assert!(!buffer.is_dirty());
+            assert!(!buffer.has_conflict());
+        });
+        assert_eq!(contents(&buffer, cx).await, [4, 5, 6]);
+    }
+
+    #[cfg(unix)]
+    #[gpui::test]
+    async fn test_save_keeps_permissions(cx: &mut TestAppContext) {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        init_test(cx);
+        cx.executor().allow_parking();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, [0, 1, 2, 3]).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o751)).unwrap();
+
+        let project = Project::test(Arc::new(fs::RealFs::default()), [dir.path()], cx).await;
+        let buffer = open_buffer(&project, "data.bin", cx).await;
+        buffer.update(cx, |buffer, cx| buffer.edit(2..4, &[], false, cx));
+        buffer
+            .update(cx, |buffer, cx| buffer.save(cx))
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), [0, 1]);
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o751
+        );
+    }
+
+    #[gpui::test]
+    async fn test_failed_save_keeps_temp_file(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let dir = tempfile::tempdir().unwrap();
+        // A directory can't be replaced by copying the saved contents over it.
+        let path = dir.path().join("data.bin");
+        std::fs::create_dir(&path).unwrap();
+
+        let fs: Arc<dyn Fs> = Arc::new(fs::RealFs::default());
+        let mut table = PieceTable::new(0);
+        table.replace(0..0, &[1, 2, 3]);
+        let snapshot = HexBufferSnapshot {
+            fs: fs.clone(),
+            abs_path: path.clone(),
+            table: Arc::new(table),
+        };
+        let error = write_snapshot(&fs, &path, snapshot).await.unwrap_err();
+
+        let temp_path = dir.path().join(".data.bin.zed-save");
+        assert!(format!("{error:?}").contains(&format!("{temp_path:?}")));
+        assert_eq!(std::fs::read(&temp_path).unwrap(), [1, 2, 3]);
+    }
+
+    async fn open_buffer(
+        project: &Model<Project>,
+        path: &str,
+        cx: &mut TestAppContext,
+    ) -> Model<HexBuffer> {
+        let project_path = project.read_with(cx, |project, cx| {
+            let worktree = project.worktrees(cx).next().unwrap();
+            ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: Path::new(path).into(),
+            }
+        });
+        cx.update(|cx| HexBuffer::open(project, &project_path, cx))
+            .await
+            .unwrap()
+    }
+
+    async fn contents(buffer: &Model<HexBuffer>, cx: &mut TestAppContext) -> Vec<u8> {
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        snapshot.read(0..snapshot.len()).await.unwrap()
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            release_channel::init(gpui::SemanticVersion::default(), cx);
+            language::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+}
@@ -0,0 +1,1055 @@
+mod hex_buffer;
+mod piece_table;
+mod search;
+
+use std::{cell::Cell, ops::Range, path::PathBuf, rc::Rc};
+
+use anyhow::{anyhow, Context as _};
+use editor::{Editor, EditorEvent};
+use file_icons::FileIcons;
+use gpui::{
+    actions, canvas, div, px, AnyElement, AppContext, Div, EventEmitter, FocusHandle,
+    FocusableView, InteractiveElement, IntoElement, KeyDownEvent, Model, MouseButton,
+    ParentElement, Pixels, Render, ScrollWheelEvent, Styled, Task, View, ViewContext,
+    VisualContext, WeakView, WindowContext,
+};
+use persistence::HEX_EDITOR;
+use project::{Project, ProjectPath};
+use settings::Settings;
+use theme::{Theme, ThemeSettings};
+use ui::prelude::*;
+use util::paths::PathExt;
+use workspace::{
+    item::{BreadcrumbText, Item, ItemEvent, ProjectItem, SerializableItem, TabContentParams},
+    ItemId, ItemSettings, ToolbarItemLocation, Workspace, WorkspaceId,
+};
+
+pub use crate::hex_buffer::{is_binary_path, HexBuffer, HexBufferEvent, HexBufferSnapshot};
+pub use crate::search::SearchMode;
+
+actions!(
+    hex_editor,
+    [
+        OpenInHexEditor,
+        MoveLeft,
+        MoveRight,
+        MoveUp,
+        MoveDown,
+        PageUp,
+        PageDown,
+        MoveToBeginningOfLine,
+        MoveToEndOfLine,
+        MoveToBeginning,
+        MoveToEnd,
+        Backspace,
+        Delete,
+        Undo,
+        Redo,
+        ToggleInsertMode,
+        SwitchColumn,
+        Find,
+        FindNext,
+        FindPrevious,
+        ToggleSearchMode,
+        DismissSearch,
+    ]
+);
+
+const HEX_EDITOR_KIND: &str = "HexEditor";
+
+/// The number of bytes shown on each row.
+const BYTES_PER_ROW: u64 = 16;
+
+pub fn init(cx: &mut AppContext) {
+    workspace::register_project_item::<HexEditor>(cx);
+    workspace::register_serializable_item::<HexEditor>(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &OpenInHexEditor, cx| {
+            let Some(project_path) = workspace
+                .active_item(cx)
+                .and_then(|item| item.project_path(cx))
+            else {
+                return;
+            };
+            let project = workspace.project().clone();
+            let open = HexBuffer::open(&project, &project_path, cx);
+            cx.spawn(|workspace, mut cx| async move {
+                let buffer = open.await?;
+                workspace.update(&mut cx, |workspace, cx| {
+                    let hex_editor = cx.new_view(|cx| HexEditor::new(buffer, project, cx));
+                    workspace.add_item_to_active_pane(Box::new(hex_editor), None, true, cx);
+                })
+            })
+            .detach_and_log_err(cx);
+        });
+    })
+    .detach();
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    Hex,
+    Ascii,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EditMode {
+    Overwrite,
+    Insert,
+}
+
+struct SearchBar {
+    query_editor: View<Editor>,
+    mode: SearchMode,
+    active_match: Option<Range<u64>>,
+    error: Option<SharedString>,
+    search_task: Task<()>,
+}
+
+/// Shows a file as rows of offsets, hex byte values and their ASCII characters.
+pub struct HexEditor {
+    buffer: Model<HexBuffer>,
+    project: Model<Project>,
+    focus_handle: FocusHandle,
+    cursor: u64,
+    /// Whether the high nibble of the byte at the cursor has been typed and the low
+    /// nibble is expected next.
+    pending_low_nibble: bool,
+    column: Column,
+    mode: EditMode,
+    scroll_top: u64,
+    scroll_remainder: Pixels,
+    /// The number of rows that fit in the view as of the last frame.
+    visible_rows: Rc<Cell<u64>>,
+    search: Option<SearchBar>,
+}
+
+pub enum HexEditorEvent {
+    TitleChanged,
+    Edited,
+}
+
+impl EventEmitter<HexEditorEvent> for HexEditor {}
+
+impl HexEditor {
+    pub fn new(
+        buffer: Model<HexBuffer>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        cx.subscribe(&buffer, Self::on_buffer_event).detach();
+        Self {
+            buffer,
+            project,
+            focus_handle: cx.focus_handle(),
+            cursor: 0,
+            pending_low_nibble: false,
+            column: Column::Hex,
+            mode: EditMode::Overwrite,
+            scroll_top: 0,
+            scroll_remainder: px(0.),
+            visible_rows: Rc::new(Cell::new(32)),
+            search: None,
+        }
+    }
+
+    fn on_buffer_event(
+        &mut self,
+        buffer: Model<HexBuffer>,
+        event: &HexBufferEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            HexBufferEvent::Edited => {
+                self.cursor = self.cursor.min(buffer.read(cx).len());
+                cx.emit(HexEditorEvent::Edited);
+            }
+            HexBufferEvent::Saved | HexBufferEvent::Reloaded => {
+                self.cursor = self.cursor.min(buffer.read(cx).len());
+                self.pending_low_nibble = false;
+                cx.emit(HexEditorEvent::TitleChanged);
+            }
+            HexBufferEvent::FileHandleChanged => cx.emit(HexEditorEvent::TitleChanged),
+            HexBufferEvent::PagesLoaded => {}
+        }
+        cx.notify();
+    }
+
+    fn row_count(&self, cx: &AppContext) -> u64 {
+        // The last row always has room for the position after the final byte, where
+        // bytes are appended.
+        self.buffer.read(cx).len() / BYTES_PER_ROW + 1
+    }
+
+    fn move_cursor_to(&mut self, offset: u64, cx: &mut ViewContext<Self>) {
+        self.cursor = offset.min(self.buffer.read(cx).len());
+        self.pending_low_nibble = false;
+        self.autoscroll(cx);
+        cx.notify();
+    }
+
+    fn autoscroll(&mut self, cx: &mut ViewContext<Self>) {
+        let cursor_row = self.cursor / BYTES_PER_ROW;
+        let visible_rows = self.visible_rows.get().max(1);
+        if cursor_row < self.scroll_top {
+            self.scroll_top = cursor_row;
+        } else if cursor_row >= self.scroll_top + visible_rows {
+            self.scroll_top = cursor_row + 1 - visible_rows;
+        }
+        self.clamp_scroll_top(cx);
+    }
+
+    fn clamp_scroll_top(&mut self, cx: &AppContext) {
+        let max_scroll_top = self
+            .row_count(cx)
+            .saturating_sub(self.visible_rows.get().max(1));
+        self.scroll_top = self.scroll_top.min(max_scroll_top);
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(1), cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(self.cursor + 1, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, cx: &mut ViewContext<Self>) {
+        if self.cursor >= BYTES_PER_ROW {
+            self.move_cursor_to(self.cursor - BYTES_PER_ROW, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, cx: &mut ViewContext<Self>) {
+        if self.cursor / BYTES_PER_ROW + 1 < self.row_count(cx) {
+            self.move_cursor_to(self.cursor + BYTES_PER_ROW, cx);
+        }
+    }
+
+    fn page_up(&mut self, _: &PageUp, cx: &mut ViewContext<Self>) {
+        let page = self.visible_rows.get().max(1) * BYTES_PER_ROW;
+        self.scroll_top = self.scroll_top.saturating_sub(self.visible_rows.get());
+        self.move_cursor_to(self.cursor.saturating_sub(page), cx);
+    }
+
+    fn page_down(&mut self, _: &PageDown, cx: &mut ViewContext<Self>) {
+        let page = self.visible_rows.get().max(1) * BYTES_PER_ROW;
+        self.scroll_top += self.visible_rows.get();
+        self.move_cursor_to(self.cursor.saturating_add(page), cx);
+    }
+
+    fn move_to_beginning_of_line(&mut self, _: &MoveToBeginningOfLine, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(self.cursor - self.cursor % BYTES_PER_ROW, cx);
+    }
+
+    fn move_to_end_of_line(&mut self, _: &MoveToEndOfLine, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(
+            self.cursor - self.cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1,
+            cx,
+        );
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(0, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, cx: &mut ViewContext<Self>) {
+        self.move_cursor_to(u64::MAX, cx);
+    }
+
+    fn backspace(&mut self, _: &Backspace, cx: &mut ViewContext<Self>) {
+        if self.cursor == 0 {
+            return;
+        }
+        let cursor = self.cursor;
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.edit(cursor - 1..cursor, &[], false, cx)
+        });
+        self.move_cursor_to(cursor - 1, cx);
+    }
+
+    fn delete(&mut self, _: &Delete, cx: &mut ViewContext<Self>) {
+        let cursor = self.cursor;
+        if cursor < self.buffer.read(cx).len() {
+            self.buffer.update(cx, |buffer, cx| {
+                buffer.edit(cursor..cursor + 1, &[], false, cx)
+            });
+            self.move_cursor_to(cursor, cx);
+        }
+    }
+
+    fn undo(&mut self, _: &Undo, cx: &mut ViewContext<Self>) {
+        self.pending_low_nibble = false;
+        self.buffer.update(cx, |buffer, cx| buffer.undo(cx));
+    }
+
+    fn redo(&mut self, _: &Redo, cx: &mut ViewContext<Self>) {
+        self.pending_low_nibble = false;
+        self.buffer.update(cx, |buffer, cx| buffer.redo(cx));
+    }
+
+    fn toggle_insert_mode(&mut self, _: &ToggleInsertMode, cx: &mut ViewContext<Self>) {
+        self.mode = match self.mode {
+            EditMode::Overwrite => EditMode::Insert,
+            EditMode::Insert => EditMode::Overwrite,
+        };
+        cx.notify();
+    }
+
+    fn switch_column(&mut self, _: &SwitchColumn, cx: &mut ViewContext<Self>) {
+        self.column = match self.column {
+            Column::Hex => Column::Ascii,
+            Column::Ascii => Column::Hex,
+        };
+        self.pending_low_nibble = false;
+        cx.notify();
+    }
+
+    fn handle_key_down(&mut self, event: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        if !self.focus_handle.is_focused(cx) {
+            return;
+        }
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.control
+            || keystroke.modifiers.platform
+            || keystroke.modifiers.function
+        {
+            return;
+        }
+        let Some(text) = keystroke.key_char.as_deref() else {
+            return;
+        };
+
+        match self.column {
+            Column::Hex => {
+                let mut chars = text.chars();
+                let (Some(digit), None) = (chars.next().and_then(|c| c.to_digit(16)), chars.next())
+                else {
+                    return;
+                };
+                self.type_nibble(digit as u8, cx);
+            }
+            Column::Ascii => {
+                if text.chars().any(|c| c.is_control()) {
+                    return;
+                }
+                self.type_bytes(text.as_bytes(), cx);
+            }
+        }
+        cx.stop_propagation();
+    }
+
+    fn type_nibble(&mut self, digit: u8, cx: &mut ViewContext<Self>) {
+        let cursor = self.cursor;
+        let len = self.buffer.read(cx).len();
+        let current = if cursor < len {
+            match self
+                .buffer
+                .update(cx, |buffer, cx| buffer.read(cursor..cursor + 1, cx))[0]
+            {
+                Some(byte) => Some(byte),
+                // The byte's page hasn't been loaded yet.
+                None => return,
+            }
+        } else {
+            None
+        };
+
+        if self.pending_low_nibble {
+            let byte = (current.unwrap_or(0) & 0xf0) | digit;
+            self.buffer.update(cx, |buffer, cx| {
+                buffer.edit(cursor..cursor + 1, &[byte], true, cx)
+            });
+            self.move_cursor_to(cursor + 1, cx);
+        } else {
+            let (range, byte) = match (self.mode, current) {
+                (EditMode::Overwrite, Some(current)) => {
+                    (cursor..cursor + 1, (digit << 4) | (current & 0x0f))
+                }
+                _ => (cursor..cursor, digit << 4),
+            };
+            self.buffer
+                .update(cx, |buffer, cx| buffer.edit(range, &[byte], false, cx));
+            self.pending_low_nibble = true;
+            self.autoscroll(cx);
+        }
+    }
+
+    fn type_bytes(&mut self, bytes: &[u8], cx: &mut ViewContext<Self>) {
+        let cursor = self.cursor;
+        let len = self.buffer.read(cx).len();
+        let range = match self.mode {
+            EditMode::Overwrite => cursor..(cursor + bytes.len() as u64).min(len),
+            EditMode::Insert => cursor..cursor,
+        };
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(range, bytes, false, cx));
+        self.move_cursor_to(cursor + bytes.len() as u64, cx);
+    }
+
+    fn handle_scroll_wheel(&mut self, event: &ScrollWheelEvent, cx: &mut ViewContext<Self>) {
+        let row_height = row_height(cx);
+        self.scroll_remainder += event.delta.pixel_delta(row_height).y;
+        let rows = (self.scroll_remainder / row_height).trunc();
+        if rows != 0. {
+            self.scroll_remainder -= row_height * rows;
+            // Scrolling down produces negative deltas.
+            if rows < 0. {
+                self.scroll_top = self.scroll_top.saturating_add(-rows as u64);
+            } else {
+                self.scroll_top = self.scroll_top.saturating_sub(rows as u64);
+            }
+            self.clamp_scroll_top(cx);
+            cx.notify();
+        }
+        cx.stop_propagation();
+    }
+
+    fn find(&mut self, _: &Find, cx: &mut ViewContext<Self>) {
+        let search = self.search.get_or_insert_with(|| {
+            let query_editor = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Search text or hex bytes…", cx);
+                editor
+            });
+            cx.subscribe(&query_editor, |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    if let Some(search) = this.search.as_mut() {
+                        search.active_match = None;
+                        search.error = None;
+                        cx.notify();
+                    }
+                }
+            })
+            .detach();
+            SearchBar {
+                query_editor,
+                mode: SearchMode::default(),
+                active_match: None,
+                error: None,
+                search_task: Task::ready(()),
+            }
+        });
+        let query_editor = search.query_editor.clone();
+        query_editor.update(cx, |editor, cx| {
+            editor.select_all(&editor::actions::SelectAll, cx)
+        });
+        cx.focus_view(&query_editor);
+        cx.notify();
+    }
+
+    fn find_next(&mut self, _: &FindNext, cx: &mut ViewContext<Self>) {
+        self.search(false, cx);
+    }
+
+    fn find_previous(&mut self, _: &FindPrevious, cx: &mut ViewContext<Self>) {
+        self.search(true, cx);
+    }
+
+    fn toggle_search_mode(&mut self, _: &ToggleSearchMode, cx: &mut ViewContext<Self>) {
+        if let Some(search) = self.search.as_mut() {
+            search.mode = match search.mode {
+                SearchMode::Text => SearchMode::Hex,
+                SearchMode::Hex => SearchMode::Text,
+            };
+            search.active_match = None;
+            search.error = None;
+            cx.notify();
+        }
+    }
+
+    fn dismiss_search(&mut self, _: &DismissSearch, cx: &mut ViewContext<Self>) {
+        if self.search.take().is_some() {
+            cx.focus(&self.focus_handle);
+            cx.notify();
+        }
+    }
+
+    fn search(&mut self, backwards: bool, cx: &mut ViewContext<Self>) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        let query = search.query_editor.read(cx).text(cx);
+        let query = match search::parse_query(&query, search.mode) {
+            Ok(query) if !query.is_empty() => query,
+            Ok(_) => return,
+            Err(error) => {
+                search.error = Some(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+
+        // Searching forward from the current match would find it again.
+        let offset = match &search.active_match {
+            Some(active_match) if !backwards && active_match.start == self.cursor => {
+                self.cursor + 1
+            }
+            _ => self.cursor,
+        };
+        let search_task = self
+            .buffer
+            .update(cx, |buffer, cx| buffer.search(query, offset, backwards, cx));
+        search.search_task = cx.spawn(|this, mut cx| async move {
+            let result = search_task.await;
+            this.update(&mut cx, |this, cx| {
+                let Some(search) = this.search.as_mut() else {
+                    return;
+                };
+                match result {
+                    Ok(Some(found)) => {
+                        search.active_match = Some(found.clone());
+                        search.error = None;
+                        this.move_cursor_to(found.start, cx);
+                    }
+                    Ok(None) => {
+                        search.active_match = None;
+                        search.error = Some("No matches".into());
+                    }
+                    Err(error) => {
+                        search.active_match = None;
+                        search.error = Some(error.to_string().into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn render_search_bar(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
+        let search = self.search.as_ref()?;
+        Some(
+            h_flex()
+                .key_context("HexEditorSearch")
+                .gap_2()
+                .px_2()
+                .py_1()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(div().flex_1().child(search.query_editor.clone()))
+                .child(Button::new("search-mode", search.mode.label()).on_click(
+                    cx.listener(|this, _, cx| this.toggle_search_mode(&ToggleSearchMode, cx)),
+                ))
+                .child(
+                    IconButton::new("find-previous", IconName::ChevronLeft)
+                        .on_click(cx.listener(|this, _, cx| this.find_previous(&FindPrevious, cx))),
+                )
+                .child(
+                    IconButton::new("find-next", IconName::ChevronRight)
+                        .on_click(cx.listener(|this, _, cx| this.find_next(&FindNext, cx))),
+                )
+                .children(
+                    search
+                        .error
+                        .clone()
+                        .map(|error| Label::new(error).color(Color::Error)),
+                ),
+        )
+    }
+
+    fn render_rows(&mut self, cx: &mut ViewContext<Self>) -> Vec<Div> {
+        let len = self.buffer.read(cx).len();
+        let offset_digits = offset_digits(len);
+        // Render one row more than fits, so that partially visible rows are filled in.
+        let rows = self.scroll_top
+            ..(self.scroll_top + self.visible_rows.get() + 1).min(self.row_count(cx));
+        let byte_range = rows.start * BYTES_PER_ROW..(rows.end * BYTES_PER_ROW).min(len);
+        let bytes = self
+            .buffer
+            .update(cx, |buffer, cx| buffer.read(byte_range.clone(), cx));
+
+        let colors = cx.theme().colors();
+        let cursor_background = cx.theme().players().local().selection;
+        let match_background = colors.search_match_background;
+        let focused = self.focus_handle.is_focused(cx);
+        let active_match = self
+            .search
+            .as_ref()
+            .and_then(|search| search.active_match.clone());
+
+        rows.map(|row| {
+            let row_start = row * BYTES_PER_ROW;
+            let mut hex_cells = Vec::with_capacity(BYTES_PER_ROW as usize);
+            let mut ascii_cells = Vec::with_capacity(BYTES_PER_ROW as usize);
+            for offset in row_start..row_start + BYTES_PER_ROW {
+                let byte = bytes
+                    .get((offset - byte_range.start) as usize)
+                    .copied()
+                    .filter(|_| offset < len);
+                let (hex_text, ascii_text) = match byte {
+                    Some(Some(byte)) => (format!("{byte:02X}"), ascii_char(byte).to_string()),
+                    Some(None) => ("··".to_string(), "·".to_string()),
+                    None => ("  ".to_string(), " ".to_string()),
+                };
+
+                let is_cursor = offset == self.cursor;
+                let is_match = active_match
+                    .as_ref()
+                    .map_or(false, |active_match| active_match.contains(&offset));
+                let background = |column| {
+                    if is_cursor && (focused && self.column == column) {
+                        Some(cursor_background)
+                    } else if is_cursor {
+                        Some(cursor_background.opacity(0.4))
+                    } else if is_match {
+                        Some(match_background)
+                    } else {
+                        None
+                    }
+                };
+                let text_color = match byte {
+                    Some(Some(_)) => colors.text,
+                    _ => colors.text_muted,
+                };
+
+                hex_cells.push(
+                    div()
+                        .when(offset % BYTES_PER_ROW == BYTES_PER_ROW / 2, |cell| {
+                            cell.ml_2()
+                        })
+                        .px(px(2.))
+                        .when_some(background(Column::Hex), |cell, background| {
+                            cell.bg(background)
+                        })
+                        .text_color(text_color)
+                        .child(hex_text)
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |this, _, cx| this.click(offset, Column::Hex, cx)),
+                        ),
+                );
+                ascii_cells.push(
+                    div()
+                        .when_some(background(Column::Ascii), |cell, background| {
+                            cell.bg(background)
+                        })
+                        .text_color(text_color)
+                        .child(ascii_text)
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |this, _, cx| this.click(offset, Column::Ascii, cx)),
+                        ),
+                );
+            }
+
+            h_flex()
+                .h(row_height(cx))
+                .gap_4()
+                .child(div().text_color(colors.text_muted).child(format!(
+                    "{:0width$X}",
+                    row_start,
+                    width = offset_digits
+                )))
+                .child(h_flex().children(hex_cells))
+                .child(h_flex().children(ascii_cells))
+        })
+        .collect()
+    }
+
+    fn click(&mut self, offset: u64, column: Column, cx: &mut ViewContext<Self>) {
+        self.column = column;
+        cx.focus(&self.focus_handle);
+        self.move_cursor_to(offset, cx);
+    }
+
+    fn render_status(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let buffer = self.buffer.read(cx);
+        let mode = match self.mode {
+            EditMode::Overwrite => "Overwrite",
+            EditMode::Insert => "Insert",
+        };
+        let mut status = format!(
+            "{mode} · Offset {:#X} ({}) · {} bytes",
+            self.cursor,
+            self.cursor,
+            buffer.len()
+        );
+        if buffer.is_saving() {
+            status.push_str(" · Saving…");
+        }
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(status)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
+
+fn row_height(cx: &WindowContext) -> Pixels {
+    let settings = ThemeSettings::get_global(cx);
+    settings.buffer_font_size(cx) * settings.line_height()
+}
+
+/// The number of hex digits needed to show every offset in a file of the given length.
+fn offset_digits(len: u64) -> usize {
+    let digits = (u64::BITS - len.leading_zeros()).div_ceil(4) as usize;
+    digits.max(8)
+}
+
+fn ascii_char(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+impl Item for HexEditor {
+    type Event = HexEditorEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            HexEditorEvent::TitleChanged => {
+                f(ItemEvent::UpdateTab);
+                f(ItemEvent::UpdateBreadcrumbs);
+            }
+            HexEditorEvent::Edited => {
+                f(ItemEvent::Edit);
+                f(ItemEvent::UpdateTab);
+            }
+        }
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.buffer.entity_id(), self.buffer.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        let file_path = self
+            .buffer
+            .read(cx)
+            .abs_path()
+            .compact()
+            .to_string_lossy()
+            .to_string();
+        Some(file_path.into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, cx: &WindowContext) -> AnyElement {
+        let path = &self.buffer.read(cx).file().path;
+        let title = path
+            .file_name()
+            .unwrap_or_else(|| path.as_os_str())
+            .to_string_lossy()
+            .to_string();
+        Label::new(title)
+            .single_line()
+            .color(params.text_color())
+            .italic(params.preview)
+            .into_any_element()
+    }
+
+    fn tab_icon(&self, cx: &WindowContext) -> Option<Icon> {
+        let path = &self.buffer.read(cx).file().path;
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn breadcrumb_location(&self, _: &AppContext) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, _theme: &Theme, cx: &AppContext) -> Option<Vec<BreadcrumbText>> {
+        let file = self.buffer.read(cx).file();
+        let text = if self.project.read(cx).visible_worktrees(cx).count() <= 1 {
+            file.path.to_string_lossy().to_string()
+        } else {
+            PathBuf::from(file.worktree.read(cx).root_name())
+                .join(&file.path)
+                .to_string_lossy()
+                .to_string()
+        };
+        Some(vec![BreadcrumbText {
+            text,
+            highlights: None,
+            font: None,
+        }])
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>>
+    where
+        Self: Sized,
+    {
+        Some(cx.new_view(|cx| Self::new(self.buffer.clone(), self.project.clone(), cx)))
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _format: bool,
+        _project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.pending_low_nibble = false;
+        self.buffer.update(cx, |buffer, cx| buffer.save(cx))
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.buffer.read(cx).has_conflict()
+    }
+
+    fn save_as(
+        &mut self,
+        project: Model<Project>,
+        path: ProjectPath,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.pending_low_nibble = false;
+        self.buffer
+            .update(cx, |buffer, cx| buffer.save_as(&project, path, cx))
+    }
+
+    fn reload(
+        &mut self,
+        _project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.buffer.update(cx, |buffer, cx| buffer.reload(cx))
+    }
+}
+
+impl SerializableItem for HexEditor {
+    fn serialized_item_kind() -> &'static str {
+        HEX_EDITOR_KIND
+    }
+
+    fn deserialize(
+        project: Model<Project>,
+        _workspace: WeakView<Workspace>,
+        workspace_id: WorkspaceId,
+        item_id: ItemId,
+        cx: &mut WindowContext,
+    ) -> Task<gpui::Result<View<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let path = HEX_EDITOR
+                .get_path(item_id, workspace_id)?
+                .ok_or_else(|| anyhow!("No path found"))?;
+
+            let (worktree, relative_path) = project
+                .update(&mut cx, |project, cx| {
+                    project.find_or_create_worktree(path.clone(), false, cx)
+                })?
+                .await
+                .context("Path not found")?;
+            let worktree_id = worktree.update(&mut cx, |worktree, _cx| worktree.id())?;
+
+            let project_path = ProjectPath {
+                worktree_id,
+                path: relative_path.into(),
+            };
+
+            let buffer = cx
+                .update(|cx| HexBuffer::open(&project, &project_path, cx))?
+                .await?;
+
+            cx.update(|cx| Ok(cx.new_view(|cx| HexEditor::new(buffer, project, cx))))?
+        })
+    }
+
+    fn cleanup(
+        workspace_id: WorkspaceId,
+        alive_items: Vec<ItemId>,
+        cx: &mut WindowContext,
+    ) -> Task<gpui::Result<()>> {
+        cx.spawn(|_| HEX_EDITOR.delete_unloaded_items(workspace_id, alive_items))
+    }
+
+    fn serialize(
+        &mut self,
+        workspace: &mut Workspace,
+        item_id: ItemId,
+        _closing: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Task<gpui::Result<()>>> {
+        let workspace_id = workspace.database_id()?;
+        let path = self.buffer.read(cx).abs_path().to_path_buf();
+
+        Some(
+            cx.background_executor()
+                .spawn({ async move { HEX_EDITOR.save_path(item_id, workspace_id, path).await } }),
+        )
+    }
+
+    fn should_serialize(&self, event: &Self::Event) -> bool {
+        // The path changes when the file is saved elsewhere.
+        matches!(event, HexEditorEvent::TitleChanged)
+    }
+}
+
+impl FocusableView for HexEditor {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let rows = self.render_rows(cx);
+        let row_height = row_height(cx);
+        let visible_rows = self.visible_rows.clone();
+
+        v_flex()
+            .key_context("HexEditor")
+            .track_focus(&self.focus_handle(cx))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::move_left))
+            .on_action(cx.listener(Self::move_right))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::page_up))
+            .on_action(cx.listener(Self::page_down))
+            .on_action(cx.listener(Self::move_to_beginning_of_line))
+            .on_action(cx.listener(Self::move_to_end_of_line))
+            .on_action(cx.listener(Self::move_to_beginning))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_action(cx.listener(Self::backspace))
+            .on_action(cx.listener(Self::delete))
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .on_action(cx.listener(Self::toggle_insert_mode))
+            .on_action(cx.listener(Self::switch_column))
+            .on_action(cx.listener(Self::find))
+            .on_action(cx.listener(Self::find_next))
+            .on_action(cx.listener(Self::find_previous))
+            .on_action(cx.listener(Self::toggle_search_mode))
+            .on_action(cx.listener(Self::dismiss_search))
+            .on_key_down(cx.listener(Self::handle_key_down))
+            .children(self.render_search_bar(cx))
+            .child(
+                div()
+                    .relative()
+                    .flex_1()
+                    .overflow_hidden()
+                    .px_2()
+                    .on_scroll_wheel(cx.listener(Self::handle_scroll_wheel))
+                    .child(
+                        canvas(
+                            move |bounds, _| {
+                                let rows = (bounds.size.height / row_height).floor() as u64;
+                                visible_rows.set(rows.max(1));
+                            },
+                            |_, _, _| {},
+                        )
+                        .absolute()
+                        .size_full(),
+                    )
+                    .child(v_flex().font_buffer(cx).text_buffer(cx).children(rows)),
+            )
+            .child(self.render_status(cx))
+    }
+}
+
+impl ProjectItem for HexEditor {
+    type Item = HexBuffer;
+
+    fn for_project_item(
+        project: Model<Project>,
+        item: Model<Self::Item>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, cx)
+    }
+}
+
+mod persistence {
+    use anyhow::Result;
+    use std::path::PathBuf;
+
+    use db::{define_connection, query, sqlez::statement::Statement, sqlez_macros::sql};
+    use workspace::{ItemId, WorkspaceDb, WorkspaceId};
+
+    define_connection! {
+        pub static ref HEX_EDITOR: HexEditorDb<WorkspaceDb> =
+            &[sql!(
+                CREATE TABLE hex_editors (
+                    workspace_id INTEGER,
+                    item_id INTEGER UNIQUE,
+
+                    path BLOB,
+
+                    PRIMARY KEY(workspace_id, item_id),
+                    FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                    ON DELETE CASCADE
+                ) STRICT;
+            )];
+    }
+
+    impl HexEditorDb {
+        query! {
+            pub async fn save_path(
+                item_id: ItemId,
+                workspace_id: WorkspaceId,
+                path: PathBuf
+            ) -> Result<()> {
+                INSERT OR REPLACE INTO hex_editors(item_id, workspace_id, path)
+                VALUES (?, ?, ?)
+            }
+        }
+
+        query! {
+            pub fn get_path(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<PathBuf>> {
+                SELECT path
+                FROM hex_editors
+                WHERE item_id = ? AND workspace_id = ?
+            }
+        }
+
+        pub async fn delete_unloaded_items(
+            &self,
+            workspace: WorkspaceId,
+            alive_items: Vec<ItemId>,
+        ) -> Result<()> {
+            let placeholders = alive_items
+                .iter()
+                .map(|_| "?")
+                .collect::<Vec<&str>>()
+                .join(", ");
+
+            let query = format!("DELETE FROM hex_editors WHERE workspace_id = ? AND item_id NOT IN ({placeholders})");
+
+            self.write(move |conn| {
+                let mut statement = Statement::prepare(conn, query)?;
+                let mut next_index = statement.bind(&workspace, 1)?;
+                for id in alive_items {
+                    next_index = statement.bind(&id, next_index)?;
+                }
+                statement.exec()
+            })
+            .await
+        }
+    }
+}
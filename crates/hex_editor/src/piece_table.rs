@@ -0,0 +1,300 @@
+use std::ops::Range;
+
+/// Where the bytes of a [`Piece`] are stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// The file on disk, as it was when it was opened or last saved.
+    Original,
+    /// The table's append-only buffer of inserted bytes.
+    Added,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Piece {
+    source: Source,
+    offset: u64,
+    len: u64,
+}
+
+#[derive(Clone, Debug)]
+struct HistoryEntry {
+    pieces: Vec<Piece>,
+    version: usize,
+}
+
+/// The contents of a file as a sequence of pieces, each referring either to a
+/// range of the original file or to bytes added while editing.
+///
+/// The original file is never read by the table itself, so files of any size can
+/// be edited while only the pages being displayed are kept in memory.
+#[derive(Clone, Debug)]
+pub struct PieceTable {
+    pieces: Vec<Piece>,
+    added: Vec<u8>,
+    version: usize,
+    next_version: usize,
+    undo_stack: Vec<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+}
+
+impl PieceTable {
+    pub fn new(original_len: u64) -> Self {
+        let pieces = if original_len > 0 {
+            vec![Piece {
+                source: Source::Original,
+                offset: 0,
+                len: original_len,
+            }]
+        } else {
+            Vec::new()
+        };
+        Self {
+            pieces,
+            added: Vec::new(),
+            version: 0,
+            next_version: 1,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.pieces.iter().map(|piece| piece.len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    /// Identifies the current contents. Undoing back to an earlier state restores
+    /// its version, so comparing versions tells whether the contents changed.
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// Replaces the given range with the given bytes as a new undoable edit.
+    /// Overwriting, inserting and deleting are all replacements.
+    pub fn replace(&mut self, range: Range<u64>, bytes: &[u8]) {
+        self.undo_stack.push(HistoryEntry {
+            pieces: self.pieces.clone(),
+            version: self.version,
+        });
+        self.redo_stack.clear();
+        self.splice(range, bytes);
+    }
+
+    /// Replaces the given range with the given bytes as part of the previous edit,
+    /// so that both are undone together.
+    pub fn amend(&mut self, range: Range<u64>, bytes: &[u8]) {
+        if self.undo_stack.is_empty() {
+            self.replace(range, bytes);
+        } else {
+            self.redo_stack.clear();
+            self.splice(range, bytes);
+        }
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(entry) = self.undo_stack.pop() else {
+            return false;
+        };
+        let current = self.restore(entry);
+        self.redo_stack.push(current);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(entry) = self.redo_stack.pop() else {
+            return false;
+        };
+        let current = self.restore(entry);
+        self.undo_stack.push(current);
+        true
+    }
+
+    /// Returns the bytes that were added while editing within the given range of
+    /// the added buffer, as returned by [`PieceTable::chunks`].
+    pub fn added_bytes(&self, range: Range<u64>) -> &[u8] {
+        &self.added[range.start as usize..range.end as usize]
+    }
+
+    /// Returns where the bytes in the given range are stored, in order, as ranges
+    /// within their source.
+    pub fn chunks(&self, range: Range<u64>) -> Vec<(Source, Range<u64>)> {
+        let mut chunks = Vec::new();
+        let mut piece_start = 0;
+        for piece in &self.pieces {
+            let piece_end = piece_start + piece.len;
+            if piece_start >= range.end {
+                break;
+            }
+            if piece_end > range.start {
+                let start = range.start.max(piece_start) - piece_start + piece.offset;
+                let end = range.end.min(piece_end) - piece_start + piece.offset;
+                chunks.push((piece.source, start..end));
+            }
+            piece_start = piece_end;
+        }
+        chunks
+    }
+
+    fn restore(&mut self, entry: HistoryEntry) -> HistoryEntry {
+        let current = HistoryEntry {
+            pieces: std::mem::replace(&mut self.pieces, entry.pieces),
+            version: self.version,
+        };
+        self.version = entry.version;
+        current
+    }
+
+    fn splice(&mut self, range: Range<u64>, bytes: &[u8]) {
+        let len = self.len();
+        let start = range.start.min(len);
+        let end = range.end.clamp(start, len);
+
+        let mut pieces = Vec::with_capacity(self.pieces.len() + 2);
+        let mut inserted = false;
+        let mut piece_start = 0;
+        for piece in self.pieces.drain(..) {
+            let piece_end = piece_start + piece.len;
+            if piece_start < start {
+                pieces.push(Piece {
+                    len: piece_end.min(start) - piece_start,
+                    ..piece
+                });
+            }
+            if piece_end > end {
+                if !inserted {
+                    push_added(&mut pieces, &mut self.added, bytes);
+                    inserted = true;
+                }
+                let suffix_start = piece_start.max(end);
+                pieces.push(Piece {
+                    source: piece.source,
+                    offset: piece.offset + suffix_start - piece_start,
+                    len: piece_end - suffix_start,
+                });
+            }
+            piece_start = piece_end;
+        }
+        if !inserted {
+            push_added(&mut pieces, &mut self.added, bytes);
+        }
+
+        pieces.dedup_by(|piece, previous| {
+            let is_contiguous =
+                piece.source == previous.source && previous.offset + previous.len == piece.offset;
+            if is_contiguous {
+                previous.len += piece.len;
+            }
+            is_contiguous
+        });
+        self.pieces = pieces;
+        self.version = self.next_version;
+        self.next_version += 1;
+    }
+}
+
+fn push_added(pieces: &mut Vec<Piece>, added: &mut Vec<u8>, bytes: &[u8]) {
+    if bytes.is_empty() {
+        return;
+    }
+    pieces.push(Piece {
+        source: Source::Added,
+        offset: added.len() as u64,
+        len: bytes.len() as u64,
+    });
+    added.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(table: &PieceTable, original: &[u8]) -> Vec<u8> {
+        table
+            .chunks(0..table.len())
+            .into_iter()
+            .flat_map(|(source, range)| match source {
+                Source::Original => original[range.start as usize..range.end as usize].to_vec(),
+                Source::Added => table.added_bytes(range).to_vec(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_edits() {
+        let original = b"0123456789";
+        let mut table = PieceTable::new(original.len() as u64);
+        assert_eq!(contents(&table, original), b"0123456789");
+
+        // Overwrite
+        table.replace(2..4, b"ab");
+        assert_eq!(contents(&table, original), b"01ab456789");
+
+        // Insert
+        table.replace(5..5, b"XYZ");
+        assert_eq!(contents(&table, original), b"01ab4XYZ56789");
+
+        // Delete across pieces
+        table.replace(3..7, b"");
+        assert_eq!(contents(&table, original), b"01aZ56789");
+
+        // Append at the end
+        table.replace(9..9, b"!");
+        assert_eq!(contents(&table, original), b"01aZ56789!");
+        assert_eq!(table.len(), 10);
+
+        assert_eq!(
+            table.chunks(1..4),
+            vec![
+                (Source::Original, 1..2),
+                (Source::Added, 0..1),
+                (Source::Added, 4..5),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let original = b"abcdef";
+        let mut table = PieceTable::new(original.len() as u64);
+        let initial_version = table.version();
+
+        table.replace(0..1, b"x");
+        table.amend(1..2, b"y");
+        table.replace(6..6, b"z");
+        assert_eq!(contents(&table, original), b"xycdefz");
+        let edited_version = table.version();
+
+        assert!(table.undo());
+        assert_eq!(contents(&table, original), b"xycdef");
+        assert!(table.undo());
+        assert_eq!(contents(&table, original), b"abcdef");
+        assert_eq!(table.version(), initial_version);
+        assert!(!table.undo());
+
+        assert!(table.redo());
+        assert!(table.redo());
+        assert_eq!(contents(&table, original), b"xycdefz");
+        assert_eq!(table.version(), edited_version);
+        assert!(!table.redo());
+
+        table.undo();
+        table.replace(0..0, b"_");
+        assert!(!table.redo());
+        assert_eq!(contents(&table, original), b"_xycdef");
+    }
+
+    #[test]
+    fn test_empty_file() {
+        let mut table = PieceTable::new(0);
+        assert!(table.is_empty());
+        assert_eq!(table.chunks(0..10), vec![]);
+
+        table.replace(0..0, b"abc");
+        table.replace(3..3, b"def");
+        assert_eq!(table.chunks(0..6), vec![(Source::Added, 0..6)]);
+        assert_eq!(contents(&table, b""), b"abcdef");
+    }
+}
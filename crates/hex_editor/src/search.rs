@@ -0,0 +1,105 @@
+use anyhow::{anyhow, Result};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// The query is matched against the UTF-8 encoding of the text.
+    #[default]
+    Text,
+    /// The query is a sequence of hex byte values, such as `DE AD be ef` or `0xcafe`.
+    Hex,
+}
+
+impl SearchMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchMode::Text => "Text",
+            SearchMode::Hex => "Hex",
+        }
+    }
+}
+
+/// Returns the bytes to search for, or an error if the query isn't valid for the mode.
+pub fn parse_query(query: &str, mode: SearchMode) -> Result<Vec<u8>> {
+    match mode {
+        SearchMode::Text => Ok(query.as_bytes().to_vec()),
+        SearchMode::Hex => parse_hex_pattern(query),
+    }
+}
+
+fn parse_hex_pattern(query: &str) -> Result<Vec<u8>> {
+    let digits = query
+        .split_whitespace()
+        .map(|group| {
+            group
+                .strip_prefix("0x")
+                .or_else(|| group.strip_prefix("0X"))
+                .unwrap_or(group)
+        })
+        .collect::<String>();
+    if let Some(invalid) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(anyhow!("invalid hex digit {invalid:?}"));
+    }
+    if digits.len() % 2 != 0 {
+        return Err(anyhow!("hex pattern has an odd number of digits"));
+    }
+
+    Ok(digits
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| (hex_digit_value(pair[0]) << 4) | hex_digit_value(pair[1]))
+        .collect())
+}
+
+fn hex_digit_value(digit: u8) -> u8 {
+    (digit as char).to_digit(16).unwrap_or(0) as u8
+}
+
+/// Returns the offset of the first occurrence of `needle` in `haystack`.
+pub fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return None;
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Returns the offset of the last occurrence of `needle` in `haystack`.
+pub fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return None;
+    }
+    haystack
+        .windows(needle.len())
+        .rposition(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        assert_eq!(
+            parse_query("DE AD be ef", SearchMode::Hex).unwrap(),
+            vec![0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(
+            parse_query("0xcafe 0X01", SearchMode::Hex).unwrap(),
+            vec![0xca, 0xfe, 0x01]
+        );
+        assert!(parse_query("abc", SearchMode::Hex).is_err());
+        assert!(parse_query("zz", SearchMode::Hex).is_err());
+        assert_eq!(parse_query("zz", SearchMode::Text).unwrap(), b"zz");
+    }
+
+    #[test]
+    fn test_find() {
+        let haystack = b"abcabc";
+        assert_eq!(find(haystack, b"bc"), Some(1));
+        assert_eq!(rfind(haystack, b"bc"), Some(4));
+        assert_eq!(find(haystack, b"x"), None);
+        assert_eq!(find(haystack, b""), None);
+        assert_eq!(find(b"a", b"abc"), None);
+    }
+}
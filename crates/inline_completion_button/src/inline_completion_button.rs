@@ -193,6 +193,28 @@ impl Render for InlineCompletionButton {
                         ),
                 );
             }
+
+            InlineCompletionProvider::LanguageModel => {
+                let this = cx.view().clone();
+                div().child(
+                    PopoverMenu::new("language-model-completions")
+                        .menu(move |cx| {
+                            Some(
+                                this.update(cx, |this, cx| {
+                                    this.build_language_model_context_menu(cx)
+                                }),
+                            )
+                        })
+                        .anchor(AnchorCorner::BottomRight)
+                        .trigger(
+                            IconButton::new(
+                                "language-model-completions-icon",
+                                IconName::ZedAssistant,
+                            )
+                            .tooltip(|cx| Tooltip::text("Language Model Completions", cx)),
+                        ),
+                )
+            }
         }
     }
 }
@@ -325,6 +347,10 @@ impl InlineCompletionButton {
         })
     }
 
+    fn build_language_model_context_menu(&self, cx: &mut ViewContext<Self>) -> View<ContextMenu> {
+        ContextMenu::build(cx, |menu, cx| self.build_language_settings_menu(menu, cx))
+    }
+
     pub fn update_enabled(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
         let editor = editor.read(cx);
         let snapshot = editor.buffer().read(cx).snapshot(cx);
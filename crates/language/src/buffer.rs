@@ -23,7 +23,7 @@ use clock::Lamport;
 pub use clock::ReplicaId;
 use collections::HashMap;
 use fs::MTime;
-use futures::channel::oneshot;
+use futures::{channel::oneshot, future::Either};
 use gpui::{
     AnyElement, AppContext, Context as _, EventEmitter, HighlightStyle, Model, ModelContext,
     Pixels, Task, TaskLabel, WindowContext,
@@ -123,6 +123,8 @@ pub struct Buffer {
     completion_triggers_timestamp: clock::Lamport,
     deferred_ops: OperationQueue<Operation>,
     capability: Capability,
+    /// Whether this buffer was opened in large-file mode, which disables parsing.
+    large_file: bool,
     has_conflict: bool,
     /// Memoize calls to has_changes_since(saved_version).
     /// The contents of a cell are (self.version, has_changes) at the time of a last call.
@@ -139,6 +141,9 @@ pub enum ParseStatus {
 struct BufferBranchState {
     base_buffer: Model<Buffer>,
     merged_operations: Vec<Lamport>,
+    /// Every operation that merged changes of this branch into the base buffer,
+    /// kept after the base buffer acknowledged them so that the merges can be undone.
+    merge_history: Vec<Lamport>,
 }
 
 /// An immutable, cheaply cloneable representation of a fixed
@@ -428,6 +433,18 @@ pub trait LocalFile: File {
     /// Loads the file's contents from disk.
     fn load_bytes(&self, cx: &AppContext) -> Task<Result<Vec<u8>>>;
 
+    /// Loads the file contents from disk into a rope with normalized line endings,
+    /// returning the line ending the file used.
+    fn load_rope(&self, cx: &AppContext) -> Task<Result<(Rope, LineEnding)>> {
+        let load = self.load(cx);
+        cx.background_executor().spawn(async move {
+            let mut text = load.await?;
+            let line_ending = LineEnding::detect(&text);
+            LineEnding::normalize(&mut text);
+            Ok((Rope::from(text.as_str()), line_ending))
+        })
+    }
+
     /// Returns true if the file should not be shared with collaborators.
     fn is_private(&self, _: &AppContext) -> bool {
         false
@@ -766,6 +783,7 @@ impl Buffer {
             branch_state: None,
             file,
             capability,
+            large_file: false,
             syntax_map,
             parsing_in_background: false,
             non_text_state_update_count: 0,
@@ -812,6 +830,7 @@ impl Buffer {
                 branch_state: Some(BufferBranchState {
                     base_buffer: this.clone(),
                     merged_operations: Default::default(),
+                    merge_history: Default::default(),
                 }),
                 language: self.language.clone(),
                 has_conflict: self.has_conflict,
@@ -878,14 +897,51 @@ impl Buffer {
 
         if let Some(operation) = operation {
             if let Some(BufferBranchState {
-                merged_operations, ..
+                merged_operations,
+                merge_history,
+                ..
             }) = &mut self.branch_state
             {
                 merged_operations.push(operation);
+                merge_history.push(operation);
             }
         }
     }
 
+    /// Returns whether any changes of this branch buffer were merged into its base buffer.
+    pub fn has_merged_into_base(&self) -> bool {
+        self.branch_state
+            .as_ref()
+            .map_or(false, |state| !state.merge_history.is_empty())
+    }
+
+    /// Undoes, in the base buffer, every change this buffer merged into it.
+    ///
+    /// The changes are kept in this buffer, so they show up as unmerged again.
+    /// This buffer must be a branch buffer to call this method.
+    pub fn undo_merges_into_base(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(BufferBranchState {
+            base_buffer,
+            merge_history,
+            ..
+        }) = &mut self.branch_state
+        else {
+            debug_panic!("not a branch buffer");
+            return;
+        };
+        if merge_history.is_empty() {
+            return;
+        }
+
+        let counts = merge_history
+            .drain(..)
+            .map(|timestamp| (timestamp, u32::MAX))
+            .collect();
+        base_buffer.update(cx, |base_buffer, cx| {
+            base_buffer.undo_operations(counts, cx);
+        });
+    }
+
     fn on_base_buffer_event(
         &mut self,
         _: Model<Buffer>,
@@ -971,6 +1027,17 @@ impl Buffer {
         cx.emit(BufferEvent::CapabilityChanged)
     }
 
+    /// Marks this buffer as a large file. Large files are never parsed, and
+    /// the project does not start language servers or compute git diffs for them.
+    pub fn set_large_file(&mut self, large_file: bool) {
+        self.large_file = large_file;
+    }
+
+    /// Whether this buffer was opened in large-file mode.
+    pub fn is_large_file(&self) -> bool {
+        self.large_file
+    }
+
     /// This method is called to signal that the buffer has been saved.
     pub fn did_save(
         &mut self,
@@ -998,18 +1065,34 @@ impl Buffer {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
         self.reload_task = Some(cx.spawn(|this, mut cx| async move {
-            let Some((new_mtime, new_text)) = this.update(&mut cx, |this, cx| {
+            let Some((new_mtime, load)) = this.update(&mut cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.disk_state().mtime(), file.load(cx)))
+                // Large files are read in chunks, as when they were opened.
+                let load = if this.large_file {
+                    Either::Left(file.load_rope(cx))
+                } else {
+                    Either::Right(file.load(cx))
+                };
+                Some((file.disk_state().mtime(), load))
             })?
             else {
                 return Ok(());
             };
 
-            let new_text = new_text.await?;
-            let diff = this
-                .update(&mut cx, |this, cx| this.diff(new_text.clone(), cx))?
-                .await;
+            let diff = match load {
+                Either::Left(load) => {
+                    let (new_text, line_ending) = load.await?;
+                    this.update(&mut cx, |this, cx| {
+                        this.diff_large_file(new_text, line_ending, cx)
+                    })?
+                    .await
+                }
+                Either::Right(load) => {
+                    let new_text = load.await?;
+                    this.update(&mut cx, |this, cx| this.diff(new_text, cx))?
+                        .await
+                }
+            };
             this.update(&mut cx, |this, cx| {
                 if this.version() == diff.base_version {
                     this.finalize_last_transaction();
@@ -1154,7 +1237,7 @@ impl Buffer {
     /// for the same buffer, we only initiate a new parse if we are not already
     /// parsing in the background.
     pub fn reparse(&mut self, cx: &mut ModelContext<Self>) {
-        if self.parsing_in_background {
+        if self.parsing_in_background || self.large_file {
             return;
         }
         let language = if let Some(language) = self.language.clone() {
@@ -1501,6 +1584,51 @@ impl Buffer {
         }
     }
 
+    /// Spawns a background task that computes a `Diff` between the buffer's text and
+    /// the given rope, as a single edit between their common prefix and suffix. This
+    /// avoids a full text diff, which is too slow for large files.
+    pub fn diff_large_file(
+        &self,
+        new_text: Rope,
+        line_ending: LineEnding,
+        cx: &AppContext,
+    ) -> Task<Diff> {
+        let old_text = self.as_rope().clone();
+        let base_version = self.version();
+        cx.background_executor().spawn(async move {
+            let prefix_len = old_text
+                .chars()
+                .zip(new_text.chars())
+                .take_while(|(old, new)| old == new)
+                .map(|(char, _)| char.len_utf8())
+                .sum::<usize>();
+            let max_suffix_len = (old_text.len() - prefix_len).min(new_text.len() - prefix_len);
+            let mut suffix_len = 0;
+            for (old, new) in old_text
+                .reversed_chars_at(old_text.len())
+                .zip(new_text.reversed_chars_at(new_text.len()))
+            {
+                if old != new || suffix_len + old.len_utf8() > max_suffix_len {
+                    break;
+                }
+                suffix_len += old.len_utf8();
+            }
+
+            let old_range = prefix_len..old_text.len() - suffix_len;
+            let new_range = prefix_len..new_text.len() - suffix_len;
+            let mut edits = Vec::new();
+            if !old_range.is_empty() || !new_range.is_empty() {
+                let new_text = new_text.chunks_in_range(new_range).collect::<String>();
+                edits.push((old_range, Arc::from(new_text)));
+            }
+            Diff {
+                base_version,
+                line_ending,
+                edits,
+            }
+        })
+    }
+
     /// Spawns a background task that asynchronously computes a `Diff` between the buffer's text
     /// and the given new text.
     pub fn diff(&self, mut new_text: String, cx: &AppContext) -> Task<Diff> {
@@ -2895,6 +3023,54 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the ranges within the given range that are covered by syntax overrides
+    /// with one of the given names, such as `comment` or `string`. Overlapping and
+    /// adjacent ranges are merged.
+    pub fn override_ranges_in_range<T: ToOffset>(
+        &self,
+        range: Range<T>,
+        names: &[&str],
+    ) -> Vec<Range<usize>> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut captures = self.syntax.captures(range.clone(), &self.text, |grammar| {
+            grammar.override_config.as_ref().map(|config| &config.query)
+        });
+        let configs = captures
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.override_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+
+        let mut ranges = Vec::<Range<usize>>::new();
+        while let Some(capture) = captures.peek() {
+            let matches_name = configs[capture.grammar_index]
+                .values
+                .get(&capture.index)
+                .map_or(false, |entry| names.contains(&entry.name.as_str()));
+            if matches_name {
+                let node_range = capture.node.byte_range();
+                let node_range = node_range.start.max(range.start)..node_range.end.min(range.end);
+                if !node_range.is_empty() {
+                    ranges.push(node_range);
+                }
+            }
+            captures.advance();
+        }
+
+        ranges.sort_unstable_by_key(|range| (range.start, Reverse(range.end)));
+        let mut merged = Vec::<Range<usize>>::with_capacity(ranges.len());
+        for range in ranges {
+            if let Some(last) = merged.last_mut() {
+                if range.start <= last.end {
+                    last.end = last.end.max(range.end);
+                    continue;
+                }
+            }
+            merged.push(range);
+        }
+        merged
+    }
+
     /// Returns a tuple of the range and character kind of the word
     /// surrounding the given position.
     pub fn surrounding_word<T: ToOffset>(&self, start: T) -> (Range<usize>, Option<CharKind>) {
@@ -2337,6 +2337,62 @@ fn test_language_scope_at_with_rust(cx: &mut AppContext) {
     });
 }
 
+#[gpui::test]
+fn test_override_ranges_in_range(cx: &mut AppContext) {
+    init_settings(cx, |_| {});
+
+    cx.new_model(|cx| {
+        let language = Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_override_query(
+            r#"
+                (string_literal) @string
+                (block_comment) @comment.inclusive
+            "#,
+        )
+        .unwrap();
+
+        let text = r#"
+            /* one
+               two */
+            fn a() -> &'static str { "three" }
+        "#
+        .unindent();
+
+        let buffer = Buffer::local(text.clone(), cx).with_language(Arc::new(language), cx);
+        let snapshot = buffer.snapshot();
+
+        let ranges = snapshot.override_ranges_in_range(0..text.len(), &["comment", "string"]);
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            &["/* one\n   two */", "\"three\""]
+        );
+
+        let ranges = snapshot.override_ranges_in_range(0..text.len(), &["string"]);
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            &["\"three\""]
+        );
+
+        let start = text.find("three").unwrap();
+        let ranges = snapshot.override_ranges_in_range(start..start + 2, &["string"]);
+        assert_eq!(ranges, &[start..start + 2]);
+
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_language_scope_at_with_combined_injections(cx: &mut AppContext) {
     init_settings(cx, |_| {});
@@ -2706,6 +2762,43 @@ fn test_undo_after_merge_into_base(cx: &mut TestAppContext) {
     branch.read_with(cx, |branch, _| assert_eq!(branch.text(), "ABCdefgHIjk"));
 }
 
+#[gpui::test]
+fn test_undo_merges_into_base(cx: &mut TestAppContext) {
+    cx.update(|cx| init_settings(cx, |_| {}));
+
+    let base = cx.new_model(|cx| Buffer::local("abcdefghijk", cx));
+    let branch = base.update(cx, |buffer, cx| buffer.branch(cx));
+
+    // Make 3 edits, merge two of them into the base in separate steps.
+    branch.update(cx, |branch, cx| {
+        branch.edit([(0..3, "ABC"), (7..9, "HI"), (11..11, "LMN")], None, cx);
+        branch.merge_into_base(vec![0..3], cx);
+        branch.merge_into_base(vec![11..11], cx);
+    });
+    base.read_with(cx, |base, _| assert_eq!(base.text(), "ABCdefghijkLMN"));
+
+    // The user edits the base buffer in between.
+    base.update(cx, |base, cx| {
+        base.edit([(5..6, "F")], None, cx);
+    });
+    branch.read_with(cx, |branch, _| {
+        assert!(branch.has_merged_into_base());
+        assert_eq!(branch.text(), "ABCdeFgHIjkLMN");
+    });
+
+    // Undoing the merges keeps the user's edit, and the branch's changes.
+    branch.update(cx, |branch, cx| branch.undo_merges_into_base(cx));
+    base.read_with(cx, |base, _| assert_eq!(base.text(), "abcdeFghijk"));
+    branch.read_with(cx, |branch, _| {
+        assert!(!branch.has_merged_into_base());
+        assert_eq!(branch.text(), "ABCdeFgHIjkLMN");
+    });
+
+    // The changes can be merged again.
+    branch.update(cx, |branch, cx| branch.merge_into_base(Vec::new(), cx));
+    base.read_with(cx, |base, _| assert_eq!(base.text(), "ABCdeFgHIjkLMN"));
+}
+
 #[gpui::test(iterations = 100)]
 fn test_random_collaboration(cx: &mut AppContext, mut rng: StdRng) {
     let min_peers = env::var("MIN_PEERS")
@@ -88,6 +88,8 @@ pub struct LanguageSettings {
     pub wrap_guides: Vec<usize>,
     /// Indent guide related settings.
     pub indent_guides: IndentGuideSettings,
+    /// Rainbow bracket related settings.
+    pub rainbow_brackets: RainbowBracketSettings,
     /// Whether or not to perform a buffer format before saving.
     pub format_on_save: FormatOnSave,
     /// Whether or not to remove any trailing whitespace from lines of a buffer
@@ -197,6 +199,9 @@ pub enum InlineCompletionProvider {
     #[default]
     Copilot,
     Supermaven,
+    /// A language model from one of the assistant's providers, such as Ollama
+    /// or an OpenAI compatible server.
+    LanguageModel,
 }
 
 /// The settings for inline completions, such as [GitHub Copilot](https://github.com/features/copilot)
@@ -207,6 +212,46 @@ pub struct InlineCompletionSettings {
     pub provider: InlineCompletionProvider,
     /// A list of globs representing files that inline completions should be disabled for.
     pub disabled_globs: Vec<GlobMatcher>,
+    /// The settings for inline completions supplied by a language model.
+    pub language_model: LanguageModelCompletionSettings,
+}
+
+/// The settings for inline completions supplied by a language model.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct LanguageModelCompletionSettings {
+    /// The provider of the model, such as `ollama` or the name of an OpenAI
+    /// compatible server. The assistant's model is used when this or `model`
+    /// is unset.
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// The model to complete with.
+    #[serde(default)]
+    pub model: Option<String>,
+    /// How to prompt the model.
+    ///
+    /// Default: chat
+    #[serde(default)]
+    pub prompt_format: LanguageModelCompletionPromptFormat,
+    /// The prompt to send when `prompt_format` is `fill_in_the_middle`, in
+    /// which `{prefix}` and `{suffix}` are replaced with the text before and
+    /// after the cursor.
+    ///
+    /// Default: "<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>"
+    #[serde(default)]
+    pub fim_template: Option<String>,
+}
+
+/// How to prompt a language model for inline completions.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageModelCompletionPromptFormat {
+    /// Ask the model to write the code at the cursor, showing it the current
+    /// file and other open files.
+    #[default]
+    Chat,
+    /// Send the model a fill-in-the-middle prompt made of the text around the
+    /// cursor, for code models trained on one.
+    FillInTheMiddle,
 }
 
 /// The settings for all languages.
@@ -271,6 +316,9 @@ pub struct LanguageSettingsContent {
     /// Indent guide related settings.
     #[serde(default)]
     pub indent_guides: Option<IndentGuideSettings>,
+    /// Rainbow bracket related settings.
+    #[serde(default)]
+    pub rainbow_brackets: Option<RainbowBracketSettings>,
     /// Whether or not to perform a buffer format before saving.
     ///
     /// Default: on
@@ -389,6 +437,10 @@ pub struct InlineCompletionSettingsContent {
     /// A list of globs representing files that inline completions should be disabled for.
     #[serde(default)]
     pub disabled_globs: Option<Vec<String>>,
+    /// The settings for inline completions supplied by a language model, when
+    /// `features.inline_completion_provider` is `language_model`.
+    #[serde(default)]
+    pub language_model: Option<LanguageModelCompletionSettings>,
 }
 
 /// The settings for enabling/disabling features.
@@ -755,6 +807,23 @@ pub enum IndentGuideBackgroundColoring {
     IndentAware,
 }
 
+/// The settings for rainbow brackets.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RainbowBracketSettings {
+    /// Whether to color bracket pairs by their nesting depth, using the
+    /// theme's accent colors.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub enabled: bool,
+    /// Whether to draw a guide linking the innermost bracket pair that
+    /// contains the cursor, when the pair spans multiple lines.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub scope_guide: bool,
+}
+
 /// The settings for inlay hints.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct InlayHintSettings {
@@ -986,6 +1055,10 @@ impl settings::Settings for AllLanguageSettings {
             .as_ref()
             .and_then(|c| c.disabled_globs.as_ref())
             .ok_or_else(Self::missing_default)?;
+        let mut language_model_completions = default_value
+            .inline_completions
+            .as_ref()
+            .and_then(|c| c.language_model.as_ref());
 
         let mut file_types: HashMap<Arc<str>, GlobSet> = HashMap::default();
 
@@ -1017,6 +1090,13 @@ impl settings::Settings for AllLanguageSettings {
             {
                 completion_globs = globs;
             }
+            if let Some(language_model) = user_settings
+                .inline_completions
+                .as_ref()
+                .and_then(|f| f.language_model.as_ref())
+            {
+                language_model_completions = Some(language_model);
+            }
 
             // A user's global settings override the default global settings and
             // all default language-specific settings.
@@ -1068,6 +1148,7 @@ impl settings::Settings for AllLanguageSettings {
                     .iter()
                     .filter_map(|g| Some(globset::Glob::new(g).ok()?.compile_matcher()))
                     .collect(),
+                language_model: language_model_completions.cloned().unwrap_or_default(),
             },
             defaults,
             languages,
@@ -1144,6 +1225,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
     merge(&mut settings.show_wrap_guides, src.show_wrap_guides);
     merge(&mut settings.wrap_guides, src.wrap_guides.clone());
     merge(&mut settings.indent_guides, src.indent_guides);
+    merge(&mut settings.rainbow_brackets, src.rainbow_brackets);
     merge(
         &mut settings.code_actions_on_format,
         src.code_actions_on_format.clone(),
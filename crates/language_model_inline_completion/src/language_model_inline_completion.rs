@@ -0,0 +1,525 @@
+mod prompt;
+
+use anyhow::Result;
+use client::telemetry::Telemetry;
+use futures::StreamExt as _;
+use gpui::{AppContext, EntityId, Model, ModelContext, Task, WeakModel};
+use inline_completion::{CompletionProposal, Direction, InlayProposal, InlineCompletionProvider};
+use language::{
+    language_settings::all_language_settings, Anchor, Buffer, BufferSnapshot, ToOffset,
+};
+use language_model::{LanguageModel, LanguageModelProviderId, LanguageModelRegistry};
+use project::Project;
+use std::{
+    collections::{hash_map::DefaultHasher, VecDeque},
+    hash::{Hash, Hasher},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+use text::Bias;
+
+use crate::prompt::{clean_completion, completion_request, CursorContext, RelatedFile};
+
+/// Language models are slower than dedicated completion services, so wait
+/// longer for typing to pause before asking for a completion.
+pub const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+const MAX_PREFIX_BYTES: usize = 6000;
+const MAX_SUFFIX_BYTES: usize = 2000;
+const MAX_RELATED_FILES: usize = 3;
+const MAX_RELATED_FILE_BYTES: usize = 2000;
+const MAX_CACHED_COMPLETIONS: usize = 32;
+
+/// Supplies inline completions from a language model of one of the
+/// assistant's providers, such as Ollama or an OpenAI compatible server.
+pub struct LanguageModelCompletionProvider {
+    project: Option<WeakModel<Project>>,
+    buffer_id: Option<EntityId>,
+    completion: Option<Completion>,
+    cache: VecDeque<(u64, String)>,
+    file_extension: Option<String>,
+    pending_refresh: Task<Result<()>>,
+    telemetry: Option<Arc<Telemetry>>,
+}
+
+struct Completion {
+    /// Where the cursor was when the completion was requested.
+    position: Anchor,
+    text: String,
+}
+
+impl LanguageModelCompletionProvider {
+    pub fn new(project: Option<WeakModel<Project>>) -> Self {
+        Self {
+            project,
+            buffer_id: None,
+            completion: None,
+            cache: VecDeque::new(),
+            file_extension: None,
+            pending_refresh: Task::ready(Ok(())),
+            telemetry: None,
+        }
+    }
+
+    pub fn with_telemetry(mut self, telemetry: Arc<Telemetry>) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    /// Returns the model configured for inline completions, falling back to
+    /// the assistant's model.
+    pub fn model(cx: &AppContext) -> Option<Arc<dyn LanguageModel>> {
+        let settings = &all_language_settings(None, cx)
+            .inline_completions
+            .language_model;
+        let registry = LanguageModelRegistry::read_global(cx);
+        match (&settings.provider, &settings.model) {
+            (Some(provider), Some(model)) => registry
+                .provider(&LanguageModelProviderId::from(provider.clone()))?
+                .provided_models(cx)
+                .into_iter()
+                .find(|candidate| candidate.id().0.as_ref() == model.as_str()),
+            _ => registry.active_model(),
+        }
+    }
+
+    fn cursor_context(
+        &self,
+        buffer: &Model<Buffer>,
+        snapshot: &BufferSnapshot,
+        offset: usize,
+        cx: &AppContext,
+    ) -> CursorContext {
+        let prefix_start =
+            snapshot.clip_offset(offset.saturating_sub(MAX_PREFIX_BYTES), Bias::Right);
+        let suffix_end = snapshot.clip_offset(offset + MAX_SUFFIX_BYTES, Bias::Left);
+        let language = snapshot.language().cloned();
+
+        let mut related_files = Vec::new();
+        if let Some(project) = self.project.as_ref().and_then(|project| project.upgrade()) {
+            for other_buffer in project.read(cx).opened_buffers(cx) {
+                if related_files.len() == MAX_RELATED_FILES {
+                    break;
+                }
+                if other_buffer.entity_id() == buffer.entity_id() {
+                    continue;
+                }
+
+                let other_snapshot = other_buffer.read(cx).snapshot();
+                let Some(file) = other_snapshot.file() else {
+                    continue;
+                };
+                if file.is_private()
+                    || other_snapshot.language().map(|language| language.name())
+                        != language.as_ref().map(|language| language.name())
+                {
+                    continue;
+                }
+
+                let end = other_snapshot.clip_offset(MAX_RELATED_FILE_BYTES, Bias::Left);
+                related_files.push(RelatedFile {
+                    path: file.path().to_string_lossy().into_owned(),
+                    text: other_snapshot.text_for_range(0..end).collect(),
+                });
+            }
+        }
+
+        CursorContext {
+            path: snapshot
+                .file()
+                .map(|file| file.path().to_string_lossy().into_owned()),
+            language: language.map(|language| language.name().0.to_string()),
+            prefix: snapshot.text_for_range(prefix_start..offset).collect(),
+            suffix: snapshot.text_for_range(offset..suffix_end).collect(),
+            related_files,
+        }
+    }
+
+    fn cached_completion(&self, key: u64) -> Option<&str> {
+        self.cache
+            .iter()
+            .find(|(cached_key, _)| *cached_key == key)
+            .map(|(_, text)| text.as_str())
+    }
+
+    fn cache_completion(&mut self, key: u64, text: String) {
+        self.cache.retain(|(cached_key, _)| *cached_key != key);
+        if self.cache.len() == MAX_CACHED_COMPLETIONS {
+            self.cache.pop_front();
+        }
+        self.cache.push_back((key, text));
+    }
+
+    fn set_completion(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        text: String,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.buffer_id = Some(buffer.entity_id());
+        self.file_extension = buffer.read(cx).file().and_then(|file| {
+            Some(
+                Path::new(file.file_name(cx))
+                    .extension()?
+                    .to_str()?
+                    .to_string(),
+            )
+        });
+        self.completion = Some(Completion { position, text });
+        cx.notify();
+    }
+
+    fn report_completion_event(&self, accepted: bool) {
+        if self.completion.is_some() {
+            if let Some(telemetry) = self.telemetry.as_ref() {
+                telemetry.report_inline_completion_event(
+                    Self::name().to_string(),
+                    accepted,
+                    self.file_extension.clone(),
+                );
+            }
+        }
+    }
+}
+
+fn cache_key(model: &dyn LanguageModel, context: &CursorContext) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    model.provider_id().0.hash(&mut hasher);
+    model.id().0.hash(&mut hasher);
+    context.prefix.hash(&mut hasher);
+    context.suffix.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl InlineCompletionProvider for LanguageModelCompletionProvider {
+    fn name() -> &'static str {
+        "language_model"
+    }
+
+    fn is_enabled(&self, buffer: &Model<Buffer>, cursor_position: Anchor, cx: &AppContext) -> bool {
+        let Some(model) = Self::model(cx) else {
+            return false;
+        };
+        let is_authenticated = LanguageModelRegistry::read_global(cx)
+            .provider(&model.provider_id())
+            .map_or(false, |provider| provider.is_authenticated(cx));
+        if !is_authenticated {
+            return false;
+        }
+
+        let buffer = buffer.read(cx);
+        let file = buffer.file();
+        if file.map_or(false, |file| file.is_private()) {
+            return false;
+        }
+        let language = buffer.language_at(cursor_position);
+        let settings = all_language_settings(file, cx);
+        settings.inline_completions_enabled(language.as_ref(), file.map(|f| f.path().as_ref()), cx)
+    }
+
+    fn refresh(
+        &mut self,
+        buffer: Model<Buffer>,
+        cursor_position: Anchor,
+        debounce: bool,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(model) = Self::model(cx) else {
+            return;
+        };
+        let settings = all_language_settings(None, cx)
+            .inline_completions
+            .language_model
+            .clone();
+
+        let snapshot = buffer.read(cx).snapshot();
+        let offset = cursor_position.to_offset(&snapshot);
+        let context = self.cursor_context(&buffer, &snapshot, offset, cx);
+        let key = cache_key(model.as_ref(), &context);
+        if let Some(text) = self.cached_completion(key) {
+            let text = text.to_string();
+            self.pending_refresh = Task::ready(Ok(()));
+            self.set_completion(&buffer, cursor_position, text, cx);
+            return;
+        }
+
+        let request = completion_request(
+            &context,
+            settings.prompt_format,
+            settings.fim_template.as_deref(),
+        );
+
+        // Replacing the pending task drops the previous request.
+        self.pending_refresh = cx.spawn(|this, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(DEBOUNCE_TIMEOUT).await;
+            }
+
+            let mut chunks = model.stream_completion_text(request, &cx).await?.stream;
+            let mut response = String::new();
+            let mut completion = String::new();
+            while let Some(chunk) = chunks.next().await {
+                response.push_str(&chunk?);
+                let Some(text) = clean_completion(&response, &context, settings.prompt_format)
+                else {
+                    continue;
+                };
+                if text == completion {
+                    continue;
+                }
+
+                completion = text;
+                this.update(&mut cx, |this, cx| {
+                    this.set_completion(&buffer, cursor_position, completion.clone(), cx);
+                })?;
+                if response.lines().count() > prompt::MAX_COMPLETION_LINES {
+                    break;
+                }
+            }
+
+            // An empty reply may be a transient failure of the server, so ask again next time.
+            if !completion.is_empty() {
+                this.update(&mut cx, |this, _| this.cache_completion(key, completion))?;
+            }
+            Ok(())
+        });
+    }
+
+    fn cycle(
+        &mut self,
+        _buffer: Model<Buffer>,
+        _cursor_position: Anchor,
+        _direction: Direction,
+        _cx: &mut ModelContext<Self>,
+    ) {
+    }
+
+    fn accept(&mut self, _cx: &mut ModelContext<Self>) {
+        self.report_completion_event(true);
+        self.pending_refresh = Task::ready(Ok(()));
+        self.completion = None;
+    }
+
+    fn discard(
+        &mut self,
+        should_report_inline_completion_event: bool,
+        _cx: &mut ModelContext<Self>,
+    ) {
+        if should_report_inline_completion_event {
+            self.report_completion_event(false);
+        }
+        self.pending_refresh = Task::ready(Ok(()));
+        self.completion = None;
+    }
+
+    fn active_completion_text<'a>(
+        &'a self,
+        buffer: &Model<Buffer>,
+        cursor_position: Anchor,
+        cx: &'a AppContext,
+    ) -> Option<CompletionProposal> {
+        if self.buffer_id != Some(buffer.entity_id()) {
+            return None;
+        }
+
+        let completion = self.completion.as_ref()?;
+        let buffer = buffer.read(cx);
+        if !completion.position.is_valid(buffer) {
+            return None;
+        }
+
+        // Keep showing the completion while what was typed since it was
+        // requested matches its beginning.
+        let start = completion.position.to_offset(buffer);
+        let end = cursor_position.to_offset(buffer);
+        if end < start {
+            return None;
+        }
+        let typed = buffer.text_for_range(start..end).collect::<String>();
+        let text = completion.text.strip_prefix(typed.as_str())?;
+        if text.trim().is_empty() {
+            return None;
+        }
+
+        Some(CompletionProposal {
+            inlays: vec![InlayProposal::Suggestion(
+                cursor_position.bias_right(buffer),
+                text.into(),
+            )],
+            text: text.into(),
+            delete_range: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language::Point;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_debounce_and_cancellation(cx: &mut TestAppContext) {
+        init_test(cx);
+        let model = fake_model(cx);
+        let (provider, buffer) = build_provider("fn main() {\n    let x = \n}", cx);
+        let position = anchor_at(&buffer, Point::new(1, 12), cx);
+
+        // No request is made until typing pauses.
+        refresh(&provider, &buffer, position, true, cx);
+        cx.run_until_parked();
+        assert_eq!(model.as_fake().completion_count(), 0);
+
+        // Refreshing again drops the pending request.
+        cx.executor().advance_clock(DEBOUNCE_TIMEOUT / 2);
+        refresh(&provider, &buffer, position, true, cx);
+        cx.executor().advance_clock(DEBOUNCE_TIMEOUT / 2);
+        cx.run_until_parked();
+        assert_eq!(model.as_fake().completion_count(), 0);
+        cx.executor().advance_clock(DEBOUNCE_TIMEOUT / 2);
+        cx.run_until_parked();
+        assert_eq!(model.as_fake().completion_count(), 1);
+        model.as_fake().end_last_completion_stream();
+
+        // Discarding the completion cancels the pending request.
+        refresh(&provider, &buffer, position, true, cx);
+        provider.update(cx, |provider, cx| provider.discard(false, cx));
+        cx.executor().advance_clock(DEBOUNCE_TIMEOUT);
+        cx.run_until_parked();
+        assert_eq!(model.as_fake().completion_count(), 0);
+    }
+
+    #[gpui::test]
+    async fn test_completion_cache(cx: &mut TestAppContext) {
+        init_test(cx);
+        let model = fake_model(cx);
+        let (provider, buffer) = build_provider("fn main() {\n    let x = \n}", cx);
+        let position = anchor_at(&buffer, Point::new(1, 12), cx);
+
+        refresh(&provider, &buffer, position, false, cx);
+        cx.run_until_parked();
+        model
+            .as_fake()
+            .stream_last_completion_response("42;".into());
+        model.as_fake().end_last_completion_stream();
+        cx.run_until_parked();
+        assert_eq!(
+            completion_text(&provider, &buffer, position, cx),
+            Some("42;".into())
+        );
+
+        // Asking again at the same position reuses the completion.
+        provider.update(cx, |provider, cx| provider.discard(false, cx));
+        assert_eq!(completion_text(&provider, &buffer, position, cx), None);
+        refresh(&provider, &buffer, position, false, cx);
+        cx.run_until_parked();
+        assert_eq!(model.as_fake().completion_count(), 0);
+        assert_eq!(
+            completion_text(&provider, &buffer, position, cx),
+            Some("42;".into())
+        );
+
+        // Empty completions aren't cached.
+        let position = anchor_at(&buffer, Point::new(0, 11), cx);
+        refresh(&provider, &buffer, position, false, cx);
+        cx.run_until_parked();
+        model.as_fake().end_last_completion_stream();
+        cx.run_until_parked();
+        assert_eq!(completion_text(&provider, &buffer, position, cx), None);
+        refresh(&provider, &buffer, position, false, cx);
+        cx.run_until_parked();
+        assert_eq!(model.as_fake().completion_count(), 1);
+    }
+
+    #[gpui::test]
+    async fn test_completion_after_typing(cx: &mut TestAppContext) {
+        init_test(cx);
+        let model = fake_model(cx);
+        let (provider, buffer) = build_provider("fn main() {\n    let x = \n}", cx);
+        let position = anchor_at(&buffer, Point::new(1, 12), cx);
+
+        refresh(&provider, &buffer, position, false, cx);
+        cx.run_until_parked();
+        model
+            .as_fake()
+            .stream_last_completion_response("42;".into());
+        model.as_fake().end_last_completion_stream();
+        cx.run_until_parked();
+
+        // Typing the beginning of the completion keeps showing the rest of it.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 12)..Point::new(1, 12), "4")], None, cx)
+        });
+        let cursor = anchor_at(&buffer, Point::new(1, 13), cx);
+        assert_eq!(
+            completion_text(&provider, &buffer, cursor, cx),
+            Some("2;".into())
+        );
+
+        // Typing something else hides it.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 13)..Point::new(1, 13), "5")], None, cx)
+        });
+        let cursor = anchor_at(&buffer, Point::new(1, 14), cx);
+        assert_eq!(completion_text(&provider, &buffer, cursor, cx), None);
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::language_settings::init(cx);
+            LanguageModelRegistry::test(cx);
+        });
+    }
+
+    fn build_provider(
+        text: &str,
+        cx: &mut TestAppContext,
+    ) -> (Model<LanguageModelCompletionProvider>, Model<Buffer>) {
+        let provider = cx.new_model(|_| LanguageModelCompletionProvider::new(None));
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx));
+        (provider, buffer)
+    }
+
+    fn fake_model(cx: &mut TestAppContext) -> Arc<dyn LanguageModel> {
+        cx.update(|cx| {
+            LanguageModelRegistry::read_global(cx)
+                .active_model()
+                .unwrap()
+        })
+    }
+
+    fn anchor_at(buffer: &Model<Buffer>, point: Point, cx: &mut TestAppContext) -> Anchor {
+        buffer.read_with(cx, |buffer, _| buffer.anchor_before(point))
+    }
+
+    fn refresh(
+        provider: &Model<LanguageModelCompletionProvider>,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        debounce: bool,
+        cx: &mut TestAppContext,
+    ) {
+        provider.update(cx, |provider, cx| {
+            provider.refresh(buffer.clone(), position, debounce, cx)
+        });
+    }
+
+    fn completion_text(
+        provider: &Model<LanguageModelCompletionProvider>,
+        buffer: &Model<Buffer>,
+        cursor: Anchor,
+        cx: &mut TestAppContext,
+    ) -> Option<String> {
+        cx.update(|cx| {
+            provider
+                .read(cx)
+                .active_completion_text(buffer, cursor, cx)
+                .map(|proposal| proposal.text.to_string())
+        })
+    }
+}
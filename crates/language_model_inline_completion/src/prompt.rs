@@ -0,0 +1,226 @@
+use language::language_settings::LanguageModelCompletionPromptFormat;
+use language_model::{LanguageModelRequest, LanguageModelRequestMessage, Role};
+use std::fmt::Write as _;
+
+pub const DEFAULT_FIM_TEMPLATE: &str = "<|fim_prefix|>{prefix}<|fim_suffix|>{suffix}<|fim_middle|>";
+
+/// Completions longer than this are cut off, which also stops the request.
+pub const MAX_COMPLETION_LINES: usize = 12;
+
+const CURSOR_MARKER: &str = "<|CURSOR|>";
+
+const CHAT_INSTRUCTIONS: &str = "\
+You are a code completion engine. Reply with only the text to insert at <|CURSOR|> in the \
+current file, without explanations or code fences. Continue the code where the cursor is and \
+stop at the end of the current statement or block. Reply with nothing if there is nothing to \
+insert.";
+
+/// The text a completion is requested for.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CursorContext {
+    pub path: Option<String>,
+    pub language: Option<String>,
+    /// The text before the cursor.
+    pub prefix: String,
+    /// The text after the cursor.
+    pub suffix: String,
+    /// Excerpts of other open files, shown to the model in chat prompts.
+    pub related_files: Vec<RelatedFile>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelatedFile {
+    pub path: String,
+    pub text: String,
+}
+
+pub fn completion_request(
+    context: &CursorContext,
+    format: LanguageModelCompletionPromptFormat,
+    fim_template: Option<&str>,
+) -> LanguageModelRequest {
+    let messages = match format {
+        LanguageModelCompletionPromptFormat::Chat => vec![
+            LanguageModelRequestMessage {
+                role: Role::System,
+                content: vec![CHAT_INSTRUCTIONS.into()],
+                cache: false,
+            },
+            LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![chat_prompt(context).into()],
+                cache: false,
+            },
+        ],
+        LanguageModelCompletionPromptFormat::FillInTheMiddle => {
+            let prompt = fim_template
+                .unwrap_or(DEFAULT_FIM_TEMPLATE)
+                .replace("{prefix}", &context.prefix)
+                .replace("{suffix}", &context.suffix);
+            vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![prompt.into()],
+                cache: false,
+            }]
+        }
+    };
+
+    LanguageModelRequest {
+        messages,
+        tools: Vec::new(),
+        stop: Vec::new(),
+        temperature: Some(0.2),
+    }
+}
+
+fn chat_prompt(context: &CursorContext) -> String {
+    let mut prompt = String::new();
+    for file in &context.related_files {
+        writeln!(prompt, "Excerpt of {}:", file.path).unwrap();
+        writeln!(prompt, "```\n{}\n```\n", file.text.trim_end()).unwrap();
+    }
+
+    let path = context.path.as_deref().unwrap_or("untitled");
+    match &context.language {
+        Some(language) => writeln!(prompt, "Current file ({language}), {path}:").unwrap(),
+        None => writeln!(prompt, "Current file, {path}:").unwrap(),
+    }
+    writeln!(
+        prompt,
+        "```\n{}{CURSOR_MARKER}{}\n```",
+        context.prefix, context.suffix
+    )
+    .unwrap();
+    prompt
+}
+
+/// Extracts the text to insert from what the model replied so far.
+///
+/// Returns `None` while a chat reply may still be opening a code fence.
+pub fn clean_completion(
+    text: &str,
+    context: &CursorContext,
+    format: LanguageModelCompletionPromptFormat,
+) -> Option<String> {
+    let mut text = text;
+    if format == LanguageModelCompletionPromptFormat::Chat {
+        // Models often wrap their reply in a code fence despite being asked not to.
+        if text.starts_with('`') {
+            let fence_end = text.find('\n')?;
+            text = &text[fence_end + 1..];
+        }
+        if let Some(fence_start) = text.find("```") {
+            text = &text[..fence_start];
+        }
+        text = text.trim_start_matches(CURSOR_MARKER);
+        if let Some(marker) = text.find(CURSOR_MARKER) {
+            text = &text[..marker];
+        }
+
+        // Some models repeat the current line before continuing it.
+        let line_prefix = context.prefix.rsplit('\n').next().unwrap_or_default();
+        if !line_prefix.trim().is_empty() {
+            if let Some(rest) = text.strip_prefix(line_prefix) {
+                text = rest;
+            }
+        }
+    }
+
+    let mut completion = text
+        .split_inclusive('\n')
+        .take(MAX_COMPLETION_LINES)
+        .collect::<String>();
+
+    // Don't suggest text that already follows the cursor.
+    let suffix_line = context.suffix.lines().next().unwrap_or_default().trim();
+    if !suffix_line.is_empty() {
+        let trimmed_len = completion.trim_end().len();
+        if completion[..trimmed_len].ends_with(suffix_line) {
+            completion.truncate(trimmed_len - suffix_line.len());
+        }
+    }
+
+    completion.truncate(completion.trim_end().len());
+    Some(completion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn context(prefix: &str, suffix: &str) -> CursorContext {
+        CursorContext {
+            path: Some("src/main.rs".into()),
+            language: Some("Rust".into()),
+            prefix: prefix.into(),
+            suffix: suffix.into(),
+            related_files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fill_in_the_middle_prompt() {
+        let request = completion_request(
+            &context("fn main() {\n    ", "\n}"),
+            LanguageModelCompletionPromptFormat::FillInTheMiddle,
+            Some("<PRE> {prefix} <SUF>{suffix} <MID>"),
+        );
+        assert_eq!(request.messages.len(), 1);
+        assert_eq!(
+            request.messages[0].string_contents(),
+            "<PRE> fn main() {\n     <SUF>\n} <MID>"
+        );
+    }
+
+    #[test]
+    fn test_chat_prompt() {
+        let mut context = context("fn main() {\n    ", "\n}");
+        context.related_files.push(RelatedFile {
+            path: "src/lib.rs".into(),
+            text: "pub fn run() {}\n".into(),
+        });
+        let request = completion_request(&context, LanguageModelCompletionPromptFormat::Chat, None);
+        assert_eq!(request.messages[0].role, Role::System);
+        assert_eq!(
+            request.messages[1].string_contents(),
+            indoc! {"
+                Excerpt of src/lib.rs:
+                ```
+                pub fn run() {}
+                ```
+
+                Current file (Rust), src/main.rs:
+                ```
+                fn main() {
+                    <|CURSOR|>
+                }
+                ```
+            "}
+        );
+    }
+
+    #[test]
+    fn test_clean_chat_completion() {
+        let chat = LanguageModelCompletionPromptFormat::Chat;
+        let context = context("fn main() {\n    let x = ", ";\n}");
+
+        assert_eq!(clean_completion("``", &context, chat), None);
+        assert_eq!(
+            clean_completion("```rust\nrun();\n```\nThis runs it.", &context, chat),
+            Some("run()".into())
+        );
+        assert_eq!(
+            clean_completion("    let x = 42;", &context, chat),
+            Some("42".into())
+        );
+        assert_eq!(
+            clean_completion(
+                "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\nm\nn",
+                &context,
+                LanguageModelCompletionPromptFormat::FillInTheMiddle
+            ),
+            Some("a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl".into())
+        );
+    }
+}
@@ -113,15 +113,18 @@ impl PickerDelegate for LanguageModelPickerDelegate {
                     if query.is_empty() {
                         displayed_models
                     } else {
+                        let query = query.to_lowercase();
                         displayed_models
                             .into_iter()
                             .filter(|model_info| {
-                                model_info
-                                    .model
-                                    .name()
-                                    .0
-                                    .to_lowercase()
-                                    .contains(&query.to_lowercase())
+                                // Match the provider too, to tell apart servers offering the same model.
+                                model_info.model.name().0.to_lowercase().contains(&query)
+                                    || model_info
+                                        .model
+                                        .provider_name()
+                                        .0
+                                        .to_lowercase()
+                                        .contains(&query)
                             })
                             .collect()
                     }
@@ -1,8 +1,11 @@
 use std::sync::Arc;
 
+use ::settings::{Settings, SettingsStore};
 use client::{Client, UserStore};
+use collections::BTreeSet;
 use fs::Fs;
 use gpui::{AppContext, Model, ModelContext};
+use http_client::HttpClient;
 use language_model::{LanguageModelProviderId, LanguageModelRegistry, ZED_CLOUD_PROVIDER_ID};
 
 mod logging;
@@ -15,6 +18,7 @@ use crate::provider::copilot_chat::CopilotChatLanguageModelProvider;
 use crate::provider::google::GoogleLanguageModelProvider;
 use crate::provider::ollama::OllamaLanguageModelProvider;
 use crate::provider::open_ai::OpenAiLanguageModelProvider;
+use crate::provider::open_ai_compatible::OpenAiCompatibleLanguageModelProvider;
 pub use crate::settings::*;
 pub use logging::report_assistant_event;
 
@@ -59,6 +63,26 @@ fn register_language_model_providers(
     );
     registry.register_provider(CopilotChatLanguageModelProvider::new(cx), cx);
 
+    let mut openai_compatible_providers = BTreeSet::default();
+    update_openai_compatible_providers(
+        registry,
+        &mut openai_compatible_providers,
+        client.http_client(),
+        cx,
+    );
+    cx.observe_global::<SettingsStore>({
+        let http_client = client.http_client();
+        move |registry, cx| {
+            update_openai_compatible_providers(
+                registry,
+                &mut openai_compatible_providers,
+                http_client.clone(),
+                cx,
+            );
+        }
+    })
+    .detach();
+
     cx.observe_flag::<feature_flags::LanguageModels, _>(move |enabled, cx| {
         let user_store = user_store.clone();
         let client = client.clone();
@@ -78,3 +102,42 @@ fn register_language_model_providers(
     })
     .detach();
 }
+
+/// Registers a provider for every OpenAI compatible server in the settings,
+/// and unregisters the ones that were removed.
+fn update_openai_compatible_providers(
+    registry: &mut LanguageModelRegistry,
+    registered: &mut BTreeSet<Arc<str>>,
+    http_client: Arc<dyn HttpClient>,
+    cx: &mut ModelContext<LanguageModelRegistry>,
+) {
+    let configured = AllLanguageModelSettings::get_global(cx)
+        .openai_compatible
+        .iter()
+        .filter(|(_, settings)| !settings.api_url.is_empty())
+        .map(|(name, _)| name.clone())
+        .collect::<BTreeSet<_>>();
+
+    for name in registered.difference(&configured) {
+        registry.unregister_provider(LanguageModelProviderId::from(name.to_string()), cx);
+    }
+    registered.retain(|name| configured.contains(name));
+
+    for name in configured {
+        if registered.contains(&name) {
+            continue;
+        }
+
+        let id = LanguageModelProviderId::from(name.to_string());
+        if registry.provider(&id).is_some() {
+            log::error!("an OpenAI compatible server can't be named {name:?}, as another provider uses that name");
+            continue;
+        }
+
+        registry.register_provider(
+            OpenAiCompatibleLanguageModelProvider::new(name.clone(), http_client.clone(), cx),
+            cx,
+        );
+        registered.insert(name);
+    }
+}
@@ -0,0 +1,897 @@
+use anyhow::{anyhow, Result};
+use collections::BTreeMap;
+use editor::{Editor, EditorElement, EditorStyle};
+use futures::{future::BoxFuture, FutureExt, StreamExt};
+use gpui::{
+    AnyView, AppContext, AsyncAppContext, FontStyle, ModelContext, Subscription, Task, TextStyle,
+    View, WhiteSpace,
+};
+use http_client::HttpClient;
+use language_model::{
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelId, LanguageModelName,
+    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelProviderState, LanguageModelRequest, RateLimiter,
+};
+use open_ai::{
+    list_models, stream_completion_with_headers, FunctionDefinition, ListedModel,
+    ResponseStreamEvent, ToolChoice, ToolDefinition,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use std::sync::Arc;
+use theme::ThemeSettings;
+use ui::{prelude::*, Icon, IconName, Tooltip};
+use util::ResultExt;
+
+use crate::provider::open_ai::count_open_ai_tokens;
+use crate::AllLanguageModelSettings;
+
+/// The context length of models that neither the server nor the settings
+/// report one for.
+const DEFAULT_MAX_TOKENS: usize = 8192;
+
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct OpenAiCompatibleSettings {
+    pub api_url: String,
+    pub api_key_env_var: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    pub available_models: Vec<AvailableModel>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AvailableModel {
+    /// The model's ID in the API, as listed by the server's `/models` endpoint.
+    pub name: String,
+    /// The model's name in Zed's UI, such as in the model selector dropdown menu in the assistant panel.
+    pub display_name: Option<String>,
+    /// The model's context length, overriding the one reported by the server.
+    pub max_tokens: Option<usize>,
+    /// The maximum number of tokens the model generates in a single response.
+    pub max_output_tokens: Option<u32>,
+    /// Whether the model can be asked to use tools.
+    ///
+    /// Default: whether the server lists `tools` among the model's supported parameters
+    pub supports_tools: Option<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Model {
+    name: String,
+    display_name: Option<String>,
+    max_tokens: usize,
+    max_output_tokens: Option<u32>,
+    supports_tools: bool,
+}
+
+impl Model {
+    fn discovered(model: &ListedModel) -> Self {
+        Self {
+            name: model.id.clone(),
+            display_name: None,
+            max_tokens: model.context_length.unwrap_or(DEFAULT_MAX_TOKENS),
+            max_output_tokens: None,
+            supports_tools: model
+                .supported_parameters
+                .iter()
+                .any(|parameter| parameter == "tools"),
+        }
+    }
+
+    fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// A provider for a server implementing the OpenAI API, such as vLLM,
+/// LM Studio, llama.cpp or OpenRouter. One is registered for every entry in
+/// the `openai_compatible` language model settings.
+pub struct OpenAiCompatibleLanguageModelProvider {
+    name: Arc<str>,
+    http_client: Arc<dyn HttpClient>,
+    state: gpui::Model<State>,
+}
+
+pub struct State {
+    name: Arc<str>,
+    http_client: Arc<dyn HttpClient>,
+    api_key: Option<String>,
+    api_key_from_env: bool,
+    available_models: Vec<ListedModel>,
+    fetch_models_task: Option<Task<Result<()>>>,
+    _subscription: Subscription,
+}
+
+fn instance_settings(name: &str, cx: &AppContext) -> OpenAiCompatibleSettings {
+    AllLanguageModelSettings::get_global(cx)
+        .openai_compatible
+        .get(name)
+        .cloned()
+        .unwrap_or_default()
+}
+
+impl State {
+    fn is_authenticated(&self) -> bool {
+        // Local servers often don't need an API key, so reaching the server is
+        // as good as being authenticated.
+        self.api_key.is_some() || !self.available_models.is_empty()
+    }
+
+    fn fetch_models(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let settings = instance_settings(&self.name, cx);
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+
+        cx.spawn(|this, mut cx| async move {
+            let mut models = list_models(
+                http_client.as_ref(),
+                &settings.api_url,
+                api_key.as_deref(),
+                &settings.headers,
+            )
+            .await?;
+            models.sort_by(|a, b| a.id.cmp(&b.id));
+
+            this.update(&mut cx, |this, cx| {
+                this.available_models = models;
+                cx.notify();
+            })
+        })
+    }
+
+    fn restart_fetch_models_task(&mut self, cx: &mut ModelContext<Self>) {
+        let task = self.fetch_models(cx);
+        self.fetch_models_task.replace(task);
+    }
+
+    fn reset_api_key(&self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let settings = instance_settings(&self.name, cx);
+        let delete_credentials = cx.delete_credentials(&settings.api_url);
+        cx.spawn(|this, mut cx| async move {
+            delete_credentials.await.log_err();
+            this.update(&mut cx, |this, cx| {
+                this.api_key = None;
+                this.api_key_from_env = false;
+                this.available_models.clear();
+                cx.notify();
+            })
+        })
+    }
+
+    fn set_api_key(&mut self, api_key: String, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let settings = instance_settings(&self.name, cx);
+        let write_credentials =
+            cx.write_credentials(&settings.api_url, "Bearer", api_key.as_bytes());
+
+        cx.spawn(|this, mut cx| async move {
+            write_credentials.await?;
+            this.update(&mut cx, |this, cx| {
+                this.api_key = Some(api_key);
+                this.restart_fetch_models_task(cx);
+                cx.notify();
+            })
+        })
+    }
+
+    fn authenticate(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        if self.is_authenticated() {
+            return Task::ready(Ok(()));
+        }
+
+        let settings = instance_settings(&self.name, cx);
+        cx.spawn(|this, mut cx| async move {
+            let api_key_from_env = settings
+                .api_key_env_var
+                .as_ref()
+                .and_then(|var| std::env::var(var).ok());
+            let (api_key, from_env) = if let Some(api_key) = api_key_from_env {
+                (Some(api_key), true)
+            } else {
+                let credentials = cx
+                    .update(|cx| cx.read_credentials(&settings.api_url))?
+                    .await?;
+                let api_key = credentials
+                    .map(|(_, api_key)| String::from_utf8(api_key))
+                    .transpose()?;
+                (api_key, false)
+            };
+
+            this.update(&mut cx, |this, cx| {
+                this.api_key = api_key;
+                this.api_key_from_env = from_env;
+                this.fetch_models(cx)
+            })?
+            .await
+        })
+    }
+}
+
+impl OpenAiCompatibleLanguageModelProvider {
+    pub fn new(name: Arc<str>, http_client: Arc<dyn HttpClient>, cx: &mut AppContext) -> Self {
+        let state = cx.new_model(|cx| {
+            let subscription = cx.observe_global::<SettingsStore>({
+                let mut settings = instance_settings(&name, cx);
+                move |this: &mut State, cx| {
+                    let new_settings = instance_settings(&this.name, cx);
+                    if settings != new_settings {
+                        // The API key is stored per URL, so look it up again.
+                        if settings.api_url != new_settings.api_url
+                            || settings.api_key_env_var != new_settings.api_key_env_var
+                        {
+                            this.api_key = None;
+                            this.api_key_from_env = false;
+                        }
+                        settings = new_settings;
+                        this.available_models.clear();
+                        let task = this.authenticate(cx);
+                        this.fetch_models_task.replace(task);
+                        cx.notify();
+                    }
+                }
+            });
+
+            State {
+                name: name.clone(),
+                http_client: http_client.clone(),
+                api_key: None,
+                api_key_from_env: false,
+                available_models: Vec::new(),
+                fetch_models_task: None,
+                _subscription: subscription,
+            }
+        });
+        state.update(cx, |state, cx| {
+            let task = state.authenticate(cx);
+            state.fetch_models_task.replace(task);
+        });
+
+        Self {
+            name,
+            http_client,
+            state,
+        }
+    }
+
+    /// Returns the models the server lists, with the overrides and additions from the settings.
+    fn models(&self, cx: &AppContext) -> Vec<Model> {
+        let mut models = BTreeMap::default();
+
+        // Add models discovered from the server
+        for model in &self.state.read(cx).available_models {
+            models.insert(model.id.clone(), Model::discovered(model));
+        }
+
+        // Add or override models from settings
+        for model in instance_settings(&self.name, cx).available_models {
+            let entry = models.entry(model.name.clone()).or_insert_with(|| Model {
+                name: model.name.clone(),
+                display_name: None,
+                max_tokens: DEFAULT_MAX_TOKENS,
+                max_output_tokens: None,
+                supports_tools: false,
+            });
+            if let Some(display_name) = model.display_name {
+                entry.display_name = Some(display_name);
+            }
+            if let Some(max_tokens) = model.max_tokens {
+                entry.max_tokens = max_tokens;
+            }
+            if let Some(max_output_tokens) = model.max_output_tokens {
+                entry.max_output_tokens = Some(max_output_tokens);
+            }
+            if let Some(supports_tools) = model.supports_tools {
+                entry.supports_tools = supports_tools;
+            }
+        }
+
+        models.into_values().collect()
+    }
+}
+
+impl LanguageModelProviderState for OpenAiCompatibleLanguageModelProvider {
+    type ObservableEntity = State;
+
+    fn observable_entity(&self) -> Option<gpui::Model<Self::ObservableEntity>> {
+        Some(self.state.clone())
+    }
+}
+
+impl LanguageModelProvider for OpenAiCompatibleLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        LanguageModelProviderId(self.name.to_string().into())
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName(self.name.to_string().into())
+    }
+
+    fn icon(&self) -> IconName {
+        IconName::Server
+    }
+
+    fn provided_models(&self, cx: &AppContext) -> Vec<Arc<dyn LanguageModel>> {
+        self.models(cx)
+            .into_iter()
+            .map(|model| {
+                Arc::new(OpenAiCompatibleLanguageModel {
+                    id: LanguageModelId::from(model.name.clone()),
+                    model,
+                    provider_name: self.name.clone(),
+                    state: self.state.clone(),
+                    http_client: self.http_client.clone(),
+                    request_limiter: RateLimiter::new(4),
+                }) as Arc<dyn LanguageModel>
+            })
+            .collect()
+    }
+
+    fn is_authenticated(&self, cx: &AppContext) -> bool {
+        self.state.read(cx).is_authenticated()
+    }
+
+    fn authenticate(&self, cx: &mut AppContext) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.authenticate(cx))
+    }
+
+    fn configuration_view(&self, cx: &mut WindowContext) -> AnyView {
+        cx.new_view(|cx| ConfigurationView::new(self.state.clone(), cx))
+            .into()
+    }
+
+    fn reset_credentials(&self, cx: &mut AppContext) -> Task<Result<()>> {
+        self.state.update(cx, |state, cx| state.reset_api_key(cx))
+    }
+}
+
+pub struct OpenAiCompatibleLanguageModel {
+    id: LanguageModelId,
+    model: Model,
+    provider_name: Arc<str>,
+    state: gpui::Model<State>,
+    http_client: Arc<dyn HttpClient>,
+    request_limiter: RateLimiter,
+}
+
+impl OpenAiCompatibleLanguageModel {
+    fn stream_completion(
+        &self,
+        request: open_ai::Request,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<futures::stream::BoxStream<'static, Result<ResponseStreamEvent>>>>
+    {
+        let http_client = self.http_client.clone();
+        let Ok((api_key, settings)) = cx.read_model(&self.state, |state, cx| {
+            (state.api_key.clone(), instance_settings(&state.name, cx))
+        }) else {
+            return futures::future::ready(Err(anyhow!("App state dropped"))).boxed();
+        };
+
+        let future = self.request_limiter.stream(async move {
+            let request = stream_completion_with_headers(
+                http_client.as_ref(),
+                &settings.api_url,
+                api_key.as_deref(),
+                &settings.headers,
+                request,
+            );
+            let response = request.await?;
+            Ok(response)
+        });
+
+        async move { Ok(future.await?.boxed()) }.boxed()
+    }
+
+    fn to_open_ai_request(&self, request: LanguageModelRequest) -> open_ai::Request {
+        let mut request = request.into_open_ai(self.model.name.clone(), self.max_output_tokens());
+        // Unlike OpenAI's o1 models, every model served this way can stream.
+        request.stream = true;
+        request
+    }
+}
+
+impl LanguageModel for OpenAiCompatibleLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.id.clone()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(self.model.display_name().to_string())
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        LanguageModelProviderId(self.provider_name.to_string().into())
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName(self.provider_name.to_string().into())
+    }
+
+    fn telemetry_id(&self) -> String {
+        format!("openai_compatible/{}", self.model.name)
+    }
+
+    fn max_token_count(&self) -> usize {
+        self.model.max_tokens
+    }
+
+    fn max_output_tokens(&self) -> Option<u32> {
+        self.model.max_output_tokens
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        let model = open_ai::Model::Custom {
+            name: self.model.name.clone(),
+            display_name: self.model.display_name.clone(),
+            max_tokens: self.model.max_tokens,
+            max_output_tokens: self.model.max_output_tokens,
+            max_completion_tokens: None,
+        };
+        count_open_ai_tokens(request, model, cx)
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<
+        'static,
+        Result<futures::stream::BoxStream<'static, Result<LanguageModelCompletionEvent>>>,
+    > {
+        let request = self.to_open_ai_request(request);
+        let completions = self.stream_completion(request, cx);
+        async move {
+            Ok(open_ai::extract_text_from_events(completions.await?)
+                .map(|result| result.map(LanguageModelCompletionEvent::Text))
+                .boxed())
+        }
+        .boxed()
+    }
+
+    fn use_any_tool(
+        &self,
+        request: LanguageModelRequest,
+        tool_name: String,
+        tool_description: String,
+        schema: serde_json::Value,
+        cx: &AsyncAppContext,
+    ) -> BoxFuture<'static, Result<futures::stream::BoxStream<'static, Result<String>>>> {
+        if !self.model.supports_tools {
+            return futures::future::ready(Err(anyhow!(
+                "{} does not support tool use",
+                self.model.display_name()
+            )))
+            .boxed();
+        }
+
+        let mut request = self.to_open_ai_request(request);
+        request.tool_choice = Some(ToolChoice::Other(ToolDefinition::Function {
+            function: FunctionDefinition {
+                name: tool_name.clone(),
+                description: None,
+                parameters: None,
+            },
+        }));
+        request.tools = vec![ToolDefinition::Function {
+            function: FunctionDefinition {
+                name: tool_name.clone(),
+                description: Some(tool_description),
+                parameters: Some(schema),
+            },
+        }];
+
+        let response = self.stream_completion(request, cx);
+        self.request_limiter
+            .run(async move {
+                let response = response.await?;
+                Ok(
+                    open_ai::extract_tool_args_from_events(tool_name, Box::pin(response))
+                        .await?
+                        .boxed(),
+                )
+            })
+            .boxed()
+    }
+}
+
+struct ConfigurationView {
+    api_key_editor: View<Editor>,
+    state: gpui::Model<State>,
+    load_credentials_task: Option<Task<()>>,
+}
+
+impl ConfigurationView {
+    fn new(state: gpui::Model<State>, cx: &mut ViewContext<Self>) -> Self {
+        let api_key_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("sk-000000000000000000000000000000000000000000000000", cx);
+            editor
+        });
+
+        cx.observe(&state, |_, _, cx| {
+            cx.notify();
+        })
+        .detach();
+
+        let load_credentials_task = Some(cx.spawn({
+            let state = state.clone();
+            |this, mut cx| async move {
+                if let Some(task) = state
+                    .update(&mut cx, |state, cx| state.authenticate(cx))
+                    .log_err()
+                {
+                    // We don't log an error, because an unreachable server is also an error.
+                    let _ = task.await;
+                }
+
+                this.update(&mut cx, |this, cx| {
+                    this.load_credentials_task = None;
+                    cx.notify();
+                })
+                .log_err();
+            }
+        }));
+
+        Self {
+            api_key_editor,
+            state,
+            load_credentials_task,
+        }
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let api_key = self.api_key_editor.read(cx).text(cx);
+        if api_key.is_empty() {
+            return;
+        }
+
+        let state = self.state.clone();
+        cx.spawn(|_, mut cx| async move {
+            state
+                .update(&mut cx, |state, cx| state.set_api_key(api_key, cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+
+    fn reset_api_key(&mut self, cx: &mut ViewContext<Self>) {
+        self.api_key_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+
+        let state = self.state.clone();
+        cx.spawn(|_, mut cx| async move {
+            state
+                .update(&mut cx, |state, cx| state.reset_api_key(cx))?
+                .await
+        })
+        .detach_and_log_err(cx);
+
+        cx.notify();
+    }
+
+    fn retry(&mut self, cx: &mut ViewContext<Self>) {
+        self.state
+            .update(cx, |state, cx| state.restart_fetch_models_task(cx));
+    }
+
+    fn render_api_key_editor(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.ui_font.family.clone(),
+            font_features: settings.ui_font.features.clone(),
+            font_fallbacks: settings.ui_font.fallbacks.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: settings.ui_font.weight,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+            white_space: WhiteSpace::Normal,
+            truncate: None,
+        };
+        EditorElement::new(
+            &self.api_key_editor,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl Render for ConfigurationView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if self.load_credentials_task.is_some() {
+            return div().child(Label::new("Loading credentials...")).into_any();
+        }
+
+        let state = self.state.read(cx);
+        let settings = instance_settings(&state.name, cx);
+        let model_count = state.available_models.len();
+        let has_api_key = state.api_key.is_some();
+        let env_var = state
+            .api_key_from_env
+            .then(|| settings.api_key_env_var.clone())
+            .flatten();
+
+        let models_status = if model_count > 0 {
+            h_flex()
+                .gap_1()
+                .child(Icon::new(IconName::Check).color(Color::Success))
+                .child(Label::new(format!(
+                    "{model_count} models available at {}.",
+                    settings.api_url
+                )))
+        } else {
+            h_flex()
+                .w_full()
+                .justify_between()
+                .child(Label::new(format!(
+                    "No models found at {}.",
+                    settings.api_url
+                )))
+                .child(
+                    Button::new("retry", "Retry")
+                        .icon(Some(IconName::RotateCw))
+                        .icon_size(IconSize::Small)
+                        .icon_position(IconPosition::Start)
+                        .on_click(cx.listener(|this, _, cx| this.retry(cx))),
+                )
+        };
+
+        let api_key_section = if has_api_key {
+            h_flex()
+                .w_full()
+                .justify_between()
+                .child(Label::new(match &env_var {
+                    Some(env_var) => format!("API key set in {env_var} environment variable."),
+                    None => "API key configured.".to_string(),
+                }))
+                .child(
+                    Button::new("reset-key", "Reset key")
+                        .icon(Some(IconName::Trash))
+                        .icon_size(IconSize::Small)
+                        .icon_position(IconPosition::Start)
+                        .disabled(env_var.is_some())
+                        .when_some(env_var, |this, env_var| {
+                            this.tooltip(move |cx| {
+                                Tooltip::text(
+                                    format!(
+                                        "To reset your API key, unset the {env_var} environment variable."
+                                    ),
+                                    cx,
+                                )
+                            })
+                        })
+                        .on_click(cx.listener(|this, _, cx| this.reset_api_key(cx))),
+                )
+                .into_any()
+        } else {
+            v_flex()
+                .on_action(cx.listener(Self::save_api_key))
+                .child(Label::new(
+                    "If the server requires an API key, paste it below and hit enter.",
+                ))
+                .child(
+                    h_flex()
+                        .w_full()
+                        .my_2()
+                        .px_2()
+                        .py_1()
+                        .bg(cx.theme().colors().editor_background)
+                        .rounded_md()
+                        .child(self.render_api_key_editor(cx)),
+                )
+                .into_any()
+        };
+
+        v_flex()
+            .size_full()
+            .gap_2()
+            .child(models_status)
+            .child(api_key_section)
+            .into_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use collections::BTreeSet;
+    use gpui::TestAppContext;
+    use http_client::{AsyncBody, FakeHttpClient, Response};
+    use language_model::LanguageModelRegistry;
+    use serde_json::json;
+
+    use super::*;
+    use crate::update_openai_compatible_providers;
+
+    #[gpui::test]
+    async fn test_model_discovery(cx: &mut TestAppContext) {
+        init_test(cx);
+        set_openai_compatible_settings(
+            json!({ "Local": { "api_url": "http://localhost:1234/v1" } }),
+            cx,
+        );
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(request.uri().to_string(), "http://localhost:1234/v1/models");
+            let models = json!({
+                "data": [
+                    { "id": "qwen", "context_length": 32768 },
+                    { "id": "llama", "max_model_len": 16384, "supported_parameters": ["temperature", "tools"] },
+                    { "id": "phi" },
+                ]
+            });
+            Ok(Response::builder()
+                .status(200)
+                .body(AsyncBody::from(models.to_string()))
+                .unwrap())
+        });
+        let provider = cx.update(|cx| {
+            OpenAiCompatibleLanguageModelProvider::new("Local".into(), http_client, cx)
+        });
+        cx.run_until_parked();
+
+        // Only the models the server lists with tool support can use tools.
+        cx.update(|cx| {
+            assert!(provider.is_authenticated(cx));
+            assert_eq!(
+                provider.models(cx),
+                vec![
+                    model("llama", None, 16384, None, true),
+                    model("phi", None, DEFAULT_MAX_TOKENS, None, false),
+                    model("qwen", None, 32768, None, false),
+                ]
+            );
+        });
+
+        // The settings override the discovered models, and add the ones the server doesn't list.
+        set_openai_compatible_settings(
+            json!({
+                "Local": {
+                    "api_url": "http://localhost:1234/v1",
+                    "available_models": [
+                        {
+                            "name": "qwen",
+                            "display_name": "Qwen",
+                            "max_tokens": 8000,
+                            "max_output_tokens": 2048,
+                            "supports_tools": true
+                        },
+                        { "name": "llama", "supports_tools": false },
+                        { "name": "extra" },
+                    ]
+                }
+            }),
+            cx,
+        );
+        cx.run_until_parked();
+        cx.update(|cx| {
+            assert_eq!(
+                provider.models(cx),
+                vec![
+                    model("extra", None, DEFAULT_MAX_TOKENS, None, false),
+                    model("llama", None, 16384, None, false),
+                    model("phi", None, DEFAULT_MAX_TOKENS, None, false),
+                    model("qwen", Some("Qwen"), 8000, Some(2048), true),
+                ]
+            );
+            let max_token_counts = provider
+                .provided_models(cx)
+                .iter()
+                .map(|model| (model.name().0.to_string(), model.max_token_count()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                max_token_counts,
+                [
+                    ("extra".to_string(), DEFAULT_MAX_TOKENS),
+                    ("llama".to_string(), 16384),
+                    ("phi".to_string(), DEFAULT_MAX_TOKENS),
+                    ("Qwen".to_string(), 8000),
+                ]
+            );
+        });
+    }
+
+    #[gpui::test]
+    fn test_update_openai_compatible_providers(cx: &mut TestAppContext) {
+        init_test(cx);
+        let http_client: Arc<dyn HttpClient> = FakeHttpClient::with_404_response();
+        let registry = cx.new_model(|_| LanguageModelRegistry::default());
+        let mut registered = BTreeSet::default();
+        let mut update_providers = |openai_compatible: serde_json::Value,
+                                    cx: &mut TestAppContext| {
+            set_openai_compatible_settings(openai_compatible, cx);
+            registry.update(cx, |registry, cx| {
+                update_openai_compatible_providers(
+                    registry,
+                    &mut registered,
+                    http_client.clone(),
+                    cx,
+                );
+                registry
+                    .providers()
+                    .iter()
+                    .map(|provider| provider.id().0.to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // Servers without a URL are skipped.
+        assert_eq!(
+            update_providers(
+                json!({
+                    "Local": { "api_url": "http://localhost:1234/v1" },
+                    "Remote": { "api_url": "https://llm.example.com/v1" },
+                    "Unfinished": {},
+                }),
+                cx
+            ),
+            ["Local", "Remote"]
+        );
+        assert_eq!(
+            update_providers(
+                json!({
+                    "Lab": { "api_url": "http://localhost:1234/v1" },
+                    "Remote": { "api_url": "https://llm.example.com/v1" },
+                }),
+                cx
+            ),
+            ["Lab", "Remote"]
+        );
+        assert_eq!(
+            update_providers(
+                json!({ "Remote": { "api_url": "https://llm.example.com/v1" } }),
+                cx
+            ),
+            ["Remote"]
+        );
+        assert!(update_providers(json!({}), cx).is_empty());
+        assert!(registered.is_empty());
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            AllLanguageModelSettings::register(cx);
+        });
+    }
+
+    fn set_openai_compatible_settings(
+        openai_compatible: serde_json::Value,
+        cx: &mut TestAppContext,
+    ) {
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                let settings =
+                    json!({ "language_models": { "openai_compatible": openai_compatible } });
+                store.set_user_settings(&settings.to_string(), cx).unwrap();
+            });
+        });
+    }
+
+    fn model(
+        name: &str,
+        display_name: Option<&str>,
+        max_tokens: usize,
+        max_output_tokens: Option<u32>,
+        supports_tools: bool,
+    ) -> Model {
+        Model {
+            name: name.to_string(),
+            display_name: display_name.map(str::to_string),
+            max_tokens,
+            max_output_tokens,
+            supports_tools,
+        }
+    }
+}
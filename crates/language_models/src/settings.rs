@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use collections::BTreeMap;
 use gpui::AppContext;
 use language_model::LanguageModelCacheConfiguration;
 use project::Fs;
@@ -16,6 +17,7 @@ use crate::provider::{
     google::GoogleSettings,
     ollama::OllamaSettings,
     open_ai::OpenAiSettings,
+    open_ai_compatible::OpenAiCompatibleSettings,
 };
 
 /// Initializes the language model settings.
@@ -59,6 +61,7 @@ pub struct AllLanguageModelSettings {
     pub zed_dot_dev: ZedDotDevSettings,
     pub google: GoogleSettings,
     pub copilot_chat: CopilotChatSettings,
+    pub openai_compatible: BTreeMap<Arc<str>, OpenAiCompatibleSettings>,
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -70,6 +73,8 @@ pub struct AllLanguageModelSettingsContent {
     pub zed_dot_dev: Option<ZedDotDevSettingsContent>,
     pub google: Option<GoogleSettingsContent>,
     pub copilot_chat: Option<CopilotChatSettingsContent>,
+    /// Servers implementing the OpenAI API, keyed by the name they are shown with.
+    pub openai_compatible: Option<BTreeMap<String, OpenAiCompatibleSettingsContent>>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -228,6 +233,18 @@ pub struct ZedDotDevSettingsContent {
 #[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct CopilotChatSettingsContent {}
 
+#[derive(Default, Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct OpenAiCompatibleSettingsContent {
+    /// The URL of the API, including the version, such as `http://localhost:1234/v1`.
+    pub api_url: Option<String>,
+    /// The environment variable to read the API key from, instead of the keychain.
+    pub api_key_env_var: Option<String>,
+    /// Headers to send with every request.
+    pub headers: Option<BTreeMap<String, String>>,
+    /// Models to add, or overrides for models discovered from the server.
+    pub available_models: Option<Vec<provider::open_ai_compatible::AvailableModel>>,
+}
+
 impl settings::Settings for AllLanguageModelSettings {
     const KEY: Option<&'static str> = Some("language_models");
 
@@ -312,6 +329,24 @@ impl settings::Settings for AllLanguageModelSettings {
                     .as_ref()
                     .and_then(|s| s.available_models.clone()),
             );
+
+            // OpenAI compatible
+            for (name, content) in value.openai_compatible.iter().flatten() {
+                let instance = settings
+                    .openai_compatible
+                    .entry(name.as_str().into())
+                    .or_default();
+
+                merge(&mut instance.api_url, content.api_url.clone());
+                if let Some(api_key_env_var) = content.api_key_env_var.clone() {
+                    instance.api_key_env_var = Some(api_key_env_var);
+                }
+                merge(&mut instance.headers, content.headers.clone());
+                merge(
+                    &mut instance.available_models,
+                    content.available_models.clone(),
+                );
+            }
         }
 
         Ok(settings)
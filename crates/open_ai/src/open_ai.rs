@@ -10,6 +10,7 @@ use http_client::{AsyncBody, HttpClient, Method, Request as HttpRequest};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
+    collections::BTreeMap,
     convert::TryFrom,
     future::{self, Future},
     pin::Pin,
@@ -398,12 +399,29 @@ pub async fn stream_completion(
         return Ok(stream::once(future::ready(response_stream_event)).boxed());
     }
 
+    stream_completion_with_headers(client, api_url, Some(api_key), &BTreeMap::new(), request).await
+}
+
+/// Streams a completion from a server implementing the OpenAI API, which
+/// may not require an API key and may expect additional headers.
+pub async fn stream_completion_with_headers(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    headers: &BTreeMap<String, String>,
+    request: Request,
+) -> Result<BoxStream<'static, Result<ResponseStreamEvent>>> {
     let uri = format!("{api_url}/chat/completions");
-    let request_builder = HttpRequest::builder()
+    let mut request_builder = HttpRequest::builder()
         .method(Method::POST)
         .uri(uri)
-        .header("Content-Type", "application/json")
-        .header("Authorization", format!("Bearer {}", api_key));
+        .header("Content-Type", "application/json");
+    if let Some(api_key) = api_key {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", api_key));
+    }
+    for (name, value) in headers {
+        request_builder = request_builder.header(name.as_str(), value.as_str());
+    }
 
     let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request)?))?;
     let mut response = client.send(request).await?;
@@ -460,6 +478,58 @@ pub async fn stream_completion(
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct ListModelsResponse {
+    data: Vec<ListedModel>,
+}
+
+/// A model returned by the `/models` endpoint.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ListedModel {
+    pub id: String,
+    /// The model's context length, for servers that report it, such as
+    /// OpenRouter (`context_length`) and vLLM (`max_model_len`).
+    #[serde(default, alias = "max_model_len")]
+    pub context_length: Option<usize>,
+    /// The request parameters the model accepts, for servers that report them,
+    /// such as OpenRouter, which lists `tools` for the models that can use tools.
+    #[serde(default)]
+    pub supported_parameters: Vec<String>,
+}
+
+pub async fn list_models(
+    client: &dyn HttpClient,
+    api_url: &str,
+    api_key: Option<&str>,
+    headers: &BTreeMap<String, String>,
+) -> Result<Vec<ListedModel>> {
+    let uri = format!("{api_url}/models");
+    let mut request_builder = HttpRequest::builder().method(Method::GET).uri(uri);
+    if let Some(api_key) = api_key {
+        request_builder = request_builder.header("Authorization", format!("Bearer {}", api_key));
+    }
+    for (name, value) in headers {
+        request_builder = request_builder.header(name.as_str(), value.as_str());
+    }
+
+    let request = request_builder.body(AsyncBody::default())?;
+    let mut response = client.send(request).await?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+    if response.status().is_success() {
+        let response: ListModelsResponse =
+            serde_json::from_str(&body).context("Unable to parse the list of models")?;
+        Ok(response.data)
+    } else {
+        Err(anyhow!(
+            "Failed to list models: {} {}",
+            response.status(),
+            body,
+        ))
+    }
+}
+
 #[derive(Copy, Clone, Serialize, Deserialize)]
 pub enum OpenAiEmbeddingModel {
     #[serde(rename = "text-embedding-3-small")]
@@ -258,6 +258,12 @@ pub fn embeddings_dir() -> &'static PathBuf {
     })
 }
 
+/// Returns the path to the directory where local embedding models are downloaded.
+pub fn embedding_models_dir() -> &'static PathBuf {
+    static EMBEDDING_MODELS_DIR: OnceLock<PathBuf> = OnceLock::new();
+    EMBEDDING_MODELS_DIR.get_or_init(|| embeddings_dir().join("models"))
+}
+
 /// Returns the path to the languages directory.
 ///
 /// This is where language servers are downloaded to for languages built-in to Zed.
@@ -266,6 +272,14 @@ pub fn languages_dir() -> &'static PathBuf {
     LANGUAGES_DIR.get_or_init(|| support_dir().join("languages"))
 }
 
+/// Returns the path to the dictionaries directory.
+///
+/// This is where Hunspell dictionaries for spell checking can be installed.
+pub fn dictionaries_dir() -> &'static PathBuf {
+    static DICTIONARIES_DIR: OnceLock<PathBuf> = OnceLock::new();
+    DICTIONARIES_DIR.get_or_init(|| config_dir().join("dictionaries"))
+}
+
 /// Returns the path to the Copilot directory.
 pub fn copilot_dir() -> &'static PathBuf {
     static COPILOT_DIR: OnceLock<PathBuf> = OnceLock::new();
@@ -284,6 +298,14 @@ pub fn default_prettier_dir() -> &'static PathBuf {
     DEFAULT_PRETTIER_DIR.get_or_init(|| support_dir().join("prettier"))
 }
 
+/// Returns the path to the shell integration directory.
+///
+/// This is where the scripts that report prompts and commands to Zed's terminal are written to.
+pub fn shell_integration_dir() -> &'static PathBuf {
+    static SHELL_INTEGRATION_DIR: OnceLock<PathBuf> = OnceLock::new();
+    SHELL_INTEGRATION_DIR.get_or_init(|| support_dir().join("shell_integration"))
+}
+
 /// Returns the path to the remote server binaries directory.
 pub fn remote_servers_dir() -> &'static PathBuf {
     static REMOTE_SERVERS_DIR: OnceLock<PathBuf> = OnceLock::new();
@@ -305,6 +327,11 @@ pub fn local_tasks_file_relative_path() -> &'static Path {
     Path::new(".zed/tasks.json")
 }
 
+/// Returns the relative path to a `dictionary.txt` file within a project.
+pub fn local_dictionary_file_relative_path() -> &'static Path {
+    Path::new(".zed/dictionary.txt")
+}
+
 /// Returns the relative path to a `.vscode/tasks.json` file within a project.
 pub fn local_vscode_tasks_file_relative_path() -> &'static Path {
     Path::new(".vscode/tasks.json")
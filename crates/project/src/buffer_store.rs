@@ -22,12 +22,15 @@ use language::{
     },
     Buffer, BufferEvent, Capability, DiskState, File as _, Language, Operation,
 };
+use postage::prelude::Stream as _;
 use rpc::{proto, AnyProtoClient, ErrorExt as _, TypedEnvelope};
 use smol::channel::Receiver;
 use std::{io, ops::Range, path::Path, str::FromStr as _, sync::Arc, time::Instant};
 use text::{BufferId, LineEnding, Rope};
 use util::{debug_panic, maybe, ResultExt as _, TryFutureExt};
-use worktree::{File, PathChange, ProjectEntryId, UpdatedGitRepositoriesSet, Worktree, WorktreeId};
+use worktree::{
+    File, FileTooLarge, PathChange, ProjectEntryId, UpdatedGitRepositoriesSet, Worktree, WorktreeId,
+};
 
 /// A set of open buffers.
 pub struct BufferStore {
@@ -41,6 +44,14 @@ pub struct BufferStore {
     opened_buffers: HashMap<BufferId, OpenBuffer>,
     downstream_client: Option<(AnyProtoClient, u64)>,
     shared_buffers: HashMap<proto::PeerId, HashMap<BufferId, SharedBuffer>>,
+    large_file_loads: HashMap<ProjectPath, LargeFileLoad>,
+}
+
+/// The progress of a file that is being loaded in large-file mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LargeFileLoad {
+    pub bytes_loaded: u64,
+    pub total_bytes: u64,
 }
 
 #[derive(Hash, Eq, PartialEq, Clone)]
@@ -96,6 +107,7 @@ pub enum BufferStoreEvent {
         buffer: Model<Buffer>,
         old_file: Option<Arc<dyn language::File>>,
     },
+    LargeFileLoadsChanged,
 }
 
 #[derive(Default, Debug)]
@@ -396,6 +408,9 @@ impl LocalBufferStore {
         let Some(file) = buffer.read(cx).file() else {
             return Task::ready(Err(anyhow!("buffer has no file")));
         };
+        if buffer.read(cx).is_large_file() {
+            return Task::ready(Ok(None));
+        }
         let worktree_id = file.worktree_id(cx);
         let path = file.path().clone();
         let Some(worktree) = self
@@ -531,7 +546,7 @@ impl LocalBufferStore {
                 {
                     let buffer = buffer.upgrade()?.read(cx);
                     let file = File::from_dyn(buffer.file())?;
-                    if file.worktree != worktree_handle {
+                    if file.worktree != worktree_handle || buffer.is_large_file() {
                         return None;
                     }
                     changed_repos
@@ -817,7 +832,19 @@ impl LocalBufferStore {
                         Capability::ReadWrite,
                     )
                 }),
-                Err(e) => Err(e),
+                Err(error) => match error.downcast_ref::<FileTooLarge>() {
+                    Some(&FileTooLarge { size }) => {
+                        Self::open_large_buffer(
+                            this.clone(),
+                            worktree.clone(),
+                            path.clone(),
+                            size,
+                            cx.clone(),
+                        )
+                        .await
+                    }
+                    None => Err(error),
+                },
             }?;
             this.update(&mut cx, |this, cx| {
                 this.add_buffer(buffer.clone(), cx)?;
@@ -845,6 +872,67 @@ impl LocalBufferStore {
         })
     }
 
+    /// Streams a file that exceeds the large-file threshold into a read-only buffer,
+    /// publishing the number of bytes loaded so far in [`BufferStore::large_file_loads`].
+    async fn open_large_buffer(
+        this: WeakModel<BufferStore>,
+        worktree: Model<Worktree>,
+        path: Arc<Path>,
+        total_bytes: u64,
+        mut cx: AsyncAppContext,
+    ) -> Result<Model<Buffer>> {
+        let project_path = ProjectPath {
+            worktree_id: worktree.read_with(&cx, |worktree, _| worktree.id())?,
+            path: path.clone(),
+        };
+        let (progress_tx, mut progress_rx) = postage::watch::channel_with(0);
+        let load_file = worktree.update(&mut cx, |worktree, cx| {
+            worktree.load_large_file(path.as_ref(), progress_tx, cx)
+        })?;
+
+        this.update(&mut cx, |this, cx| {
+            this.large_file_loads.insert(
+                project_path.clone(),
+                LargeFileLoad {
+                    bytes_loaded: 0,
+                    total_bytes,
+                },
+            );
+            cx.emit(BufferStoreEvent::LargeFileLoadsChanged);
+        })?;
+        let _report_progress = cx.spawn({
+            let this = this.clone();
+            let project_path = project_path.clone();
+            |mut cx| async move {
+                while let Some(bytes_loaded) = progress_rx.recv().await {
+                    this.update(&mut cx, |this, cx| {
+                        if let Some(load) = this.large_file_loads.get_mut(&project_path) {
+                            load.bytes_loaded = bytes_loaded;
+                            cx.emit(BufferStoreEvent::LargeFileLoadsChanged);
+                        }
+                    })
+                    .ok();
+                }
+            }
+        });
+
+        let loaded = load_file.await;
+        this.update(&mut cx, |this, cx| {
+            this.large_file_loads.remove(&project_path);
+            cx.emit(BufferStoreEvent::LargeFileLoadsChanged);
+        })?;
+        let loaded = loaded?;
+
+        cx.new_model(|cx| {
+            let buffer_id = BufferId::from(cx.entity_id().as_non_zero_u64());
+            let text_buffer =
+                text::Buffer::new_normalized(0, buffer_id, loaded.line_ending, loaded.text);
+            let mut buffer = Buffer::build(text_buffer, Some(loaded.file), Capability::ReadOnly);
+            buffer.set_large_file(true);
+            buffer
+        })
+    }
+
     fn create_buffer(&self, cx: &mut ModelContext<BufferStore>) -> Task<Result<Model<Buffer>>> {
         cx.spawn(|buffer_store, mut cx| async move {
             let buffer = cx.new_model(|cx| {
@@ -914,6 +1002,7 @@ impl BufferStore {
             downstream_client: None,
             opened_buffers: Default::default(),
             shared_buffers: Default::default(),
+            large_file_loads: Default::default(),
             loading_buffers: Default::default(),
             loading_change_sets: Default::default(),
             worktree_store,
@@ -940,6 +1029,7 @@ impl BufferStore {
             loading_buffers: Default::default(),
             loading_change_sets: Default::default(),
             shared_buffers: Default::default(),
+            large_file_loads: Default::default(),
             worktree_store,
         }
     }
@@ -1146,6 +1236,9 @@ impl BufferStore {
         let Some(file) = File::from_dyn(buffer.file()) else {
             return Task::ready(Err(anyhow!("buffer has no file")));
         };
+        if buffer.is_large_file() {
+            return Task::ready(Ok(None));
+        }
 
         match file.worktree.clone().read(cx) {
             Worktree::Local(worktree) => {
@@ -1329,6 +1422,11 @@ impl BufferStore {
         })
     }
 
+    /// Files that are currently being streamed from disk in large-file mode.
+    pub fn large_file_loads(&self) -> impl '_ + Iterator<Item = (&ProjectPath, &LargeFileLoad)> {
+        self.large_file_loads.iter()
+    }
+
     pub fn get_by_path(&self, path: &ProjectPath, cx: &AppContext) -> Option<Model<Buffer>> {
         self.buffers().find_map(|buffer| {
             let file = File::from_dyn(buffer.read(cx).file())?;
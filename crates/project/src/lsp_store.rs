@@ -713,6 +713,8 @@ pub struct LspStore {
     _maintain_workspace_config: (Task<Result<()>>, watch::Sender<()>),
     _maintain_buffer_languages: Task<()>,
     next_diagnostic_group_id: usize,
+    /// Problems found in task output and reported as diagnostics, by their source name.
+    task_diagnostic_sources: HashMap<String, TaskDiagnostics>,
     diagnostic_summaries:
         HashMap<WorktreeId, HashMap<Arc<Path>, HashMap<LanguageServerId, DiagnosticSummary>>>,
     diagnostics: HashMap<
@@ -759,6 +761,13 @@ pub enum LspStoreEvent {
     },
 }
 
+/// Problems from a task problem matcher source that were reported as diagnostics.
+struct TaskDiagnostics {
+    server_id: LanguageServerId,
+    /// The problems reported last, by the path they were reported for.
+    problems: HashMap<PathBuf, Vec<task::Problem>>,
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct LanguageServerStatus {
     pub name: String,
@@ -922,6 +931,7 @@ impl LspStore {
             nonce: StdRng::from_entropy().gen(),
             buffer_snapshots: Default::default(),
             next_diagnostic_group_id: Default::default(),
+            task_diagnostic_sources: Default::default(),
             diagnostic_summaries: Default::default(),
             diagnostics: Default::default(),
             active_entry: None,
@@ -981,6 +991,7 @@ impl LspStore {
             nonce: StdRng::from_entropy().gen(),
             buffer_snapshots: Default::default(),
             next_diagnostic_group_id: Default::default(),
+            task_diagnostic_sources: Default::default(),
             diagnostic_summaries: Default::default(),
             diagnostics: Default::default(),
             active_entry: None,
@@ -1018,7 +1029,7 @@ impl LspStore {
 
                 self.register_buffer_with_language_servers(buffer, cx);
             }
-            BufferStoreEvent::BufferDropped(_) => {}
+            BufferStoreEvent::BufferDropped(_) | BufferStoreEvent::LargeFileLoadsChanged => {}
         }
     }
 
@@ -3331,6 +3342,15 @@ impl LspStore {
         buffer_handle: &Model<Buffer>,
         cx: &mut ModelContext<Self>,
     ) {
+        if buffer_handle.read(cx).is_large_file() {
+            if buffer_handle.read(cx).language().is_none() {
+                buffer_handle.update(cx, |buffer, cx| {
+                    buffer.set_language(Some(language::PLAIN_TEXT.clone()), cx)
+                });
+            }
+            return;
+        }
+
         let available_language = self.detect_language_for_buffer(buffer_handle, cx);
 
         let buffer = buffer_handle.read(cx);
@@ -6618,6 +6638,94 @@ impl LspStore {
         Ok(())
     }
 
+    /// Replaces the diagnostics reported under the given task problem matcher source
+    /// with the problems found in the task output.
+    pub fn update_task_diagnostics(
+        &mut self,
+        source: &str,
+        problems: Vec<task::Problem>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let languages = self.languages.clone();
+        let task_diagnostics = self
+            .task_diagnostic_sources
+            .entry(source.to_string())
+            .or_insert_with(|| TaskDiagnostics {
+                server_id: languages.next_language_server_id(),
+                problems: HashMap::default(),
+            });
+        let server_id = task_diagnostics.server_id;
+        let old_problems = mem::take(&mut task_diagnostics.problems);
+
+        let mut problems_by_path = HashMap::<PathBuf, Vec<_>>::default();
+        for problem in problems {
+            problems_by_path
+                .entry(problem.path.clone())
+                .or_default()
+                .push(problem);
+        }
+
+        // Only the paths whose problems changed since the last update are reported again,
+        // and the ones whose problems are gone are cleared.
+        for abs_path in old_problems.keys() {
+            if !problems_by_path.contains_key(abs_path) {
+                self.update_diagnostic_entries(server_id, abs_path.clone(), None, Vec::new(), cx)?;
+            }
+        }
+        for (abs_path, problems) in &problems_by_path {
+            if old_problems.get(abs_path) == Some(problems) {
+                continue;
+            }
+            let diagnostics = problems
+                .iter()
+                .map(|problem| self.task_problem_diagnostic(problem))
+                .collect();
+            self.update_diagnostic_entries(server_id, abs_path.clone(), None, diagnostics, cx)?;
+        }
+
+        if let Some(task_diagnostics) = self.task_diagnostic_sources.get_mut(source) {
+            task_diagnostics.problems = problems_by_path;
+        }
+        Ok(())
+    }
+
+    fn task_problem_diagnostic(
+        &mut self,
+        problem: &task::Problem,
+    ) -> DiagnosticEntry<Unclipped<PointUtf16>> {
+        let start_row = problem.line.saturating_sub(1);
+        let start_column = problem.column.map_or(0, |column| column.saturating_sub(1));
+        let end_row = problem
+            .end_line
+            .map_or(start_row, |line| line.saturating_sub(1));
+        let end_column = match (problem.column, problem.end_column) {
+            (_, Some(end_column)) => end_column.saturating_sub(1),
+            (Some(_), None) => start_column + 1,
+            // Without a column, the whole line is highlighted.
+            (None, None) => u32::MAX,
+        };
+        DiagnosticEntry {
+            range: Unclipped(PointUtf16::new(start_row, start_column))
+                ..Unclipped(PointUtf16::new(end_row, end_column)),
+            diagnostic: Diagnostic {
+                source: Some(problem.source.clone()),
+                code: problem.code.clone(),
+                severity: match problem.severity {
+                    task::ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                    task::ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                    task::ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                    task::ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+                },
+                message: problem.message.clone(),
+                group_id: post_inc(&mut self.next_diagnostic_group_id),
+                is_primary: true,
+                is_disk_based: false,
+                is_unnecessary: false,
+                data: None,
+            },
+        }
+    }
+
     fn insert_newly_running_language_server(
         &mut self,
         language: LanguageName,
@@ -92,7 +92,8 @@ pub use language::Location;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
 pub use task_inventory::{
-    BasicContextProvider, ContextProviderWithTasks, Inventory, TaskSourceKind,
+    BasicContextProvider, CombinedTaskStatus, ContextProviderWithTasks, Inventory, TaskRunStatus,
+    TaskSourceKind,
 };
 pub use worktree::{
     Entry, EntryKind, File, LocalWorktree, PathChange, ProjectEntryId, RepositoryEntry,
@@ -100,7 +101,7 @@ pub use worktree::{
     FS_WATCH_LATENCY,
 };
 
-pub use buffer_store::ProjectTransaction;
+pub use buffer_store::{LargeFileLoad, ProjectTransaction};
 pub use lsp_store::{
     DiagnosticSummary, LanguageServerLogType, LanguageServerProgress, LanguageServerPromptRequest,
     LanguageServerStatus, LanguageServerToQuery, LspStore, LspStoreEvent,
@@ -1263,6 +1264,14 @@ impl Project {
         self.environment.read(cx).environment_errors()
     }
 
+    /// Files that are currently being loaded in large-file mode, with their progress.
+    pub fn large_file_loads<'a>(
+        &'a self,
+        cx: &'a AppContext,
+    ) -> impl Iterator<Item = (&'a ProjectPath, &'a LargeFileLoad)> {
+        self.buffer_store.read(cx).large_file_loads()
+    }
+
     pub fn remove_environment_error(
         &mut self,
         cx: &mut ModelContext<Self>,
@@ -2055,6 +2064,7 @@ impl Project {
                 self.register_buffer(buffer, cx).log_err();
             }
             BufferStoreEvent::BufferChangedFilePath { .. } => {}
+            BufferStoreEvent::LargeFileLoadsChanged => cx.notify(),
             BufferStoreEvent::BufferDropped(buffer_id) => {
                 if let Some(ref ssh_client) = self.ssh_client {
                     ssh_client
@@ -17,7 +17,7 @@ use serde_json::json;
 use std::os;
 
 use std::{mem, num::NonZeroU32, ops::Range, task::Poll};
-use task::{ResolvedTask, TaskContext};
+use task::{DetectedTaskSource, ResolvedTask, TaskContext, TaskVariables, VariableName};
 use unindent::Unindent as _;
 use util::{assert_set_eq, paths::PathMatcher, test::temp_tree, TryFutureExt as _};
 
@@ -376,6 +376,113 @@ async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext)
     );
 }
 
+#[gpui::test]
+async fn test_detected_tasks(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    TaskStore::init(None);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            "Makefile": "all: build\nbuild:\n\tcc main.c\n",
+            "web": {
+                "package.json": r#"{ "scripts": { "dev": "vite" } }"#,
+            },
+            "docs": {
+                "package.json": r#"{ "scripts": { "dev": "vitepress dev" } }"#,
+            },
+            "node_modules": {
+                "dep": {
+                    "package.json": r#"{ "scripts": { "hidden": "true" } }"#,
+                },
+            },
+            ".gitignore": "node_modules\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let task_context = TaskContext {
+        task_variables: TaskVariables::from_iter([(
+            VariableName::WorktreeRoot,
+            "/the-root".to_string(),
+        )]),
+        ..TaskContext::default()
+    };
+    let detected_tasks = |cx: &mut gpui::TestAppContext| {
+        cx.update(|cx| get_all_tasks(&project, Some(worktree_id), &task_context, cx))
+            .into_iter()
+            .map(|(source_kind, task)| {
+                let TaskSourceKind::Detected { source, .. } = source_kind else {
+                    panic!("unexpected task source {source_kind:?}");
+                };
+                (source, task.resolved_label, task.resolved.unwrap().cwd)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        detected_tasks(cx),
+        vec![
+            (
+                DetectedTaskSource::PackageJson,
+                "npm run dev (docs)".to_string(),
+                Some(PathBuf::from("/the-root/docs"))
+            ),
+            (
+                DetectedTaskSource::PackageJson,
+                "npm run dev (web)".to_string(),
+                Some(PathBuf::from("/the-root/web"))
+            ),
+            (DetectedTaskSource::Makefile, "make all".to_string(), None),
+            (DetectedTaskSource::Makefile, "make build".to_string(), None),
+        ]
+    );
+
+    // A lock file changes the package manager, and the tasks follow the files' changes.
+    fs.remove_file("/the-root/docs/package.json".as_ref(), Default::default())
+        .await
+        .unwrap();
+    fs.insert_file("/the-root/web/yarn.lock", Vec::new()).await;
+    fs.save(
+        "/the-root/Makefile".as_ref(),
+        &"test:\n\tcargo test\n".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        detected_tasks(cx),
+        vec![
+            (
+                DetectedTaskSource::PackageJson,
+                "yarn run dev (web)".to_string(),
+                Some(PathBuf::from("/the-root/web"))
+            ),
+            (DetectedTaskSource::Makefile, "make test".to_string(), None),
+        ]
+    );
+
+    fs.remove_file("/the-root/Makefile".as_ref(), Default::default())
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(
+        detected_tasks(cx),
+        vec![(
+            DetectedTaskSource::PackageJson,
+            "yarn run dev (web)".to_string(),
+            Some(PathBuf::from("/the-root/web"))
+        )]
+    );
+}
+
 #[gpui::test]
 async fn test_managing_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1418,6 +1525,92 @@ async fn test_restarting_server_with_diagnostics_running(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_opening_large_files(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<WorktreeSettings>(cx, |settings| {
+                settings.large_file_threshold = Some(16);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "small.rs": "fn a() {}",
+            "large.rs": "fn a() {}\r\nfn b() {}\r\nfn c() {}\r\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp("Rust", FakeLspAdapter::default());
+
+    let large_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/large.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    large_buffer.update(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert!(buffer.read_only());
+        assert_eq!(buffer.text(), "fn a() {}\nfn b() {}\nfn c() {}\n");
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+        assert_eq!(
+            buffer.language().map(|language| language.name()),
+            Some("Plain Text".into())
+        );
+    });
+    project.read_with(cx, |project, cx| {
+        assert_eq!(project.large_file_loads(cx).count(), 0);
+    });
+    assert!(fake_servers.try_next().is_err());
+
+    // External changes to large files are reloaded in chunks, and applied as a
+    // single edit between the unchanged prefix and suffix.
+    fs.save(
+        "/dir/large.rs".as_ref(),
+        &"fn a() {}\nfn x() {}\nfn c() {}\nfn d() {}\n".into(),
+        LineEnding::Windows,
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    large_buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            buffer.text(),
+            "fn a() {}\nfn x() {}\nfn c() {}\nfn d() {}\n"
+        );
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+        assert!(!buffer.is_dirty());
+        assert!(!buffer.has_conflict());
+    });
+
+    // Files below the threshold still get a language and a language server.
+    let small_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/small.rs", cx)
+        })
+        .await
+        .unwrap();
+    fake_servers.next().await.unwrap();
+    small_buffer.update(cx, |buffer, _| {
+        assert!(!buffer.is_large_file());
+        assert!(!buffer.read_only());
+        assert_eq!(
+            buffer.language().map(|language| language.name()),
+            Some("Rust".into())
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_restarting_server_with_diagnostics_published(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2121,6 +2314,93 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_task_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "one\ntwo", "b.rs": "three" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let lsp_store = project.read_with(cx, |project, _| project.lsp_store());
+    let updated_paths = Arc::new(Mutex::new(Vec::new()));
+    let _subscription = cx.update(|cx| {
+        let updated_paths = updated_paths.clone();
+        cx.subscribe(&lsp_store, move |_, event, _| {
+            if let LspStoreEvent::DiagnosticsUpdated { path, .. } = event {
+                updated_paths.lock().push(path.path.to_path_buf());
+            }
+        })
+    });
+    let problem = |path: &str, line: u32, message: &str| task::Problem {
+        source: "rustc".to_string(),
+        path: PathBuf::from(path),
+        line,
+        column: None,
+        end_line: None,
+        end_column: None,
+        severity: task::ProblemSeverity::Error,
+        code: None,
+        message: message.to_string(),
+    };
+    let update = |problems: Vec<task::Problem>, cx: &mut gpui::TestAppContext| {
+        lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store
+                .update_task_diagnostics("rustc", problems, cx)
+                .unwrap()
+        });
+        let mut updated_paths = mem::take(&mut *updated_paths.lock());
+        updated_paths.sort();
+        updated_paths
+    };
+
+    assert_eq!(
+        update(
+            vec![problem("/dir/a.rs", 1, "a1"), problem("/dir/b.rs", 1, "b1")],
+            cx
+        ),
+        vec![PathBuf::from("a.rs"), PathBuf::from("b.rs")]
+    );
+
+    // Only the paths with new problems are updated.
+    assert_eq!(
+        update(
+            vec![
+                problem("/dir/a.rs", 1, "a1"),
+                problem("/dir/b.rs", 1, "b1"),
+                problem("/dir/a.rs", 2, "a2"),
+            ],
+            cx
+        ),
+        vec![PathBuf::from("a.rs")]
+    );
+    project.read_with(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 3,
+                warning_count: 0,
+            }
+        );
+    });
+
+    // Paths without problems anymore are cleared.
+    assert_eq!(
+        update(vec![problem("/dir/b.rs", 1, "b1")], cx),
+        vec![PathBuf::from("a.rs")]
+    );
+    project.read_with(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 0,
+            }
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -8,14 +8,15 @@ use std::{
     sync::Arc,
 };
 
-use anyhow::{Context, Result};
-use collections::{HashMap, HashSet, VecDeque};
+use anyhow::{bail, Context, Result};
+use collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use gpui::{AppContext, Context as _, Model, Task};
 use itertools::Itertools;
 use language::{ContextProvider, File, Language, LanguageToolchainStore, Location};
 use settings::{parse_json_with_comments, SettingsLocation};
 use task::{
-    ResolvedTask, TaskContext, TaskId, TaskTemplate, TaskTemplates, TaskVariables, VariableName,
+    DetectedTaskSource, ResolvedTask, SpawnInTerminal, TaskContext, TaskId, TaskTemplate,
+    TaskTemplates, TaskVariables, VariableName,
 };
 use text::{Point, ToPoint};
 use util::{post_inc, NumericPrefixWithSuffix, ResultExt as _};
@@ -28,6 +29,10 @@ use crate::worktree_store::WorktreeStore;
 pub struct Inventory {
     last_scheduled_tasks: VecDeque<(TaskSourceKind, ResolvedTask)>,
     templates_from_settings: ParsedTemplates,
+    /// Tasks detected in the worktree files, by the path of the file they are declared in.
+    detected_templates:
+        HashMap<WorktreeId, BTreeMap<Arc<Path>, (DetectedTaskSource, Vec<TaskTemplate>)>>,
+    task_statuses: HashMap<TaskId, TaskRunStatus>,
 }
 
 #[derive(Debug, Default)]
@@ -54,6 +59,44 @@ pub enum TaskSourceKind {
     },
     /// Languages-specific tasks coming from extensions.
     Language { name: Arc<str> },
+    /// Tasks that the worktree files declare already, like `package.json` scripts or `Makefile` targets.
+    Detected {
+        id: WorktreeId,
+        path_in_worktree: PathBuf,
+        source: DetectedTaskSource,
+    },
+}
+
+/// State of a spawned task, as far as the tasks depending on it are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskRunStatus {
+    /// Waiting for the tasks it depends on to get ready.
+    Waiting,
+    /// Running, but not ready yet.
+    Running,
+    /// Still running, but its output matched its ready pattern.
+    Ready,
+    /// Finished successfully.
+    Succeeded,
+    /// Finished with an error, or was not started because a task it depends on failed.
+    Failed,
+}
+
+impl TaskRunStatus {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Self::Ready | Self::Succeeded)
+    }
+}
+
+/// Status of a task together with all the tasks it depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CombinedTaskStatus {
+    /// Status of the task itself, or [`TaskRunStatus::Failed`] if any of its dependencies failed.
+    pub status: TaskRunStatus,
+    /// How many of the tasks are ready, the task itself included.
+    pub ready: usize,
+    /// How many tasks there are, the task itself included.
+    pub total: usize,
 }
 
 impl TaskSourceKind {
@@ -71,6 +114,11 @@ impl TaskSourceKind {
                 format!("{id_base}_{id}_{}", directory_in_worktree.display())
             }
             TaskSourceKind::Language { name } => format!("language_{name}"),
+            TaskSourceKind::Detected {
+                id,
+                path_in_worktree,
+                ..
+            } => format!("detected_{id}_{}", path_in_worktree.display()),
         }
     }
 }
@@ -99,6 +147,7 @@ impl Inventory {
             .flat_map(|task| Some((task_source_kind.clone()?, task)));
 
         self.templates_from_settings(worktree)
+            .chain(self.detected_templates(worktree))
             .chain(language_tasks)
             .collect()
     }
@@ -172,14 +221,14 @@ impl Inventory {
             .flat_map(|task| Some((task_source_kind.clone()?, task)));
         let new_resolved_tasks = self
             .templates_from_settings(worktree)
+            .chain(self.detected_templates(worktree))
             .chain(language_tasks)
             .filter_map(|(kind, task)| {
                 let id_base = kind.to_id_base();
-                Some((
-                    kind,
-                    task.resolve_task(&id_base, task_context)?,
-                    not_used_score,
-                ))
+                let mut resolved_task = task.resolve_task(&id_base, task_context)?;
+                self.resolve_dependencies(&mut resolved_task, worktree, task_context)
+                    .log_err()?;
+                Some((kind, resolved_task, not_used_score))
             })
             .filter(|(_, resolved_task, _)| {
                 match task_labels_to_ids.entry(resolved_task.resolved_label.clone()) {
@@ -200,6 +249,115 @@ impl Inventory {
         (previously_spawned_tasks, new_resolved_tasks)
     }
 
+    /// Resolves the tasks that the given one depends on with the same [`TaskContext`], looking them up by their labels.
+    /// Fails if any of them cannot be found or resolved, or if the tasks depend on each other.
+    pub fn resolve_dependencies(
+        &self,
+        resolved_task: &mut ResolvedTask,
+        worktree: Option<WorktreeId>,
+        task_context: &TaskContext,
+    ) -> Result<()> {
+        let template = resolved_task.original_task().clone();
+        let Some(spawn_in_terminal) = resolved_task.resolved.as_mut() else {
+            return Ok(());
+        };
+        if template.depends_on.is_empty() {
+            return Ok(());
+        }
+        let templates = self
+            .templates_from_settings(worktree)
+            .chain(self.detected_templates(worktree))
+            .collect::<Vec<_>>();
+        spawn_in_terminal.dependencies = resolve_dependencies(
+            &templates,
+            &template,
+            task_context,
+            &mut vec![template.label.clone()],
+        )?;
+        Ok(())
+    }
+
+    /// Records the current state of a spawned task.
+    pub fn set_task_status(&mut self, id: TaskId, status: TaskRunStatus) {
+        self.task_statuses.insert(id, status);
+    }
+
+    /// Forgets the state of a spawned task, e.g. when the terminal it ran in is closed.
+    pub fn forget_task_status(&mut self, id: &TaskId) {
+        self.task_statuses.remove(id);
+    }
+
+    /// Runs an update of the stored templates, forgetting the states of the tasks spawned
+    /// from the templates it removes.
+    fn update_templates<R>(&mut self, update: impl FnOnce(&mut Self) -> R) -> R {
+        if self.task_statuses.is_empty() {
+            return update(self);
+        }
+        let old_id_prefixes = self.stored_task_id_prefixes();
+        let result = update(self);
+        let new_id_prefixes = self.stored_task_id_prefixes();
+        let removed_id_prefixes = old_id_prefixes
+            .difference(&new_id_prefixes)
+            .collect::<Vec<_>>();
+        if !removed_id_prefixes.is_empty() {
+            self.task_statuses.retain(|id, _| {
+                !removed_id_prefixes
+                    .iter()
+                    .any(|prefix| id.0.starts_with(prefix.as_str()))
+            });
+        }
+        result
+    }
+
+    /// The [`TaskTemplate::task_id_prefix`] of every template from the settings or detected in
+    /// the worktrees.
+    fn stored_task_id_prefixes(&self) -> HashSet<String> {
+        let worktrees = self
+            .templates_from_settings
+            .worktree
+            .keys()
+            .chain(self.detected_templates.keys())
+            .copied()
+            .collect::<HashSet<_>>();
+        self.templates_from_settings(None)
+            .chain(worktrees.into_iter().flat_map(|worktree| {
+                self.templates_from_settings(Some(worktree))
+                    .chain(self.detected_templates(Some(worktree)))
+            }))
+            .filter_map(|(kind, template)| template.task_id_prefix(&kind.to_id_base()))
+            .collect()
+    }
+
+    /// Returns the state of the task and the tasks it depends on, if it was spawned.
+    pub fn combined_task_status(&self, task: &ResolvedTask) -> Option<CombinedTaskStatus> {
+        let mut status = *self.task_statuses.get(&task.id)?;
+        let mut ready = status.is_ready() as usize;
+        let mut total = 1;
+        let mut seen = HashSet::default();
+        let mut dependencies = task
+            .resolved
+            .iter()
+            .flat_map(|spawn_in_terminal| &spawn_in_terminal.dependencies)
+            .collect::<Vec<_>>();
+        while let Some(dependency) = dependencies.pop() {
+            if !seen.insert(&dependency.id) {
+                continue;
+            }
+            dependencies.extend(&dependency.dependencies);
+            total += 1;
+            match self.task_statuses.get(&dependency.id) {
+                Some(TaskRunStatus::Failed) => status = TaskRunStatus::Failed,
+                Some(dependency_status) if dependency_status.is_ready() => ready += 1,
+                _ => {}
+            }
+        }
+        Some(CombinedTaskStatus {
+            status,
+            ready,
+            total,
+        })
+    }
+
     /// Returns the last scheduled task by task_id if provided.
     /// Otherwise, returns the last scheduled task.
     pub fn last_scheduled_task(
@@ -276,6 +434,65 @@ impl Inventory {
             }))
     }
 
+    fn detected_templates(
+        &self,
+        worktree: Option<WorktreeId>,
+    ) -> impl '_ + Iterator<Item = (TaskSourceKind, TaskTemplate)> {
+        worktree.into_iter().flat_map(|worktree| {
+            self.detected_templates
+                .get(&worktree)
+                .into_iter()
+                .flatten()
+                .flat_map(move |(path, (source, templates))| {
+                    templates.iter().map(move |template| {
+                        (
+                            TaskSourceKind::Detected {
+                                id: worktree,
+                                path_in_worktree: path.to_path_buf(),
+                                source: *source,
+                            },
+                            template.clone(),
+                        )
+                    })
+                })
+        })
+    }
+
+    /// Replaces the tasks detected in the worktree file given, removes them if no templates are given.
+    pub(crate) fn update_detected_tasks(
+        &mut self,
+        worktree_id: WorktreeId,
+        path: Arc<Path>,
+        source: DetectedTaskSource,
+        templates: Option<TaskTemplates>,
+    ) {
+        self.update_templates(|inventory| {
+            match templates.filter(|templates| !templates.0.is_empty()) {
+                Some(templates) => {
+                    inventory
+                        .detected_templates
+                        .entry(worktree_id)
+                        .or_default()
+                        .insert(path, (source, templates.0));
+                }
+                None => {
+                    if let Some(worktree_templates) =
+                        inventory.detected_templates.get_mut(&worktree_id)
+                    {
+                        worktree_templates.remove(&path);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Forgets the tasks detected in a worktree that was removed from the project.
+    pub(crate) fn remove_worktree(&mut self, worktree_id: WorktreeId) {
+        self.update_templates(|inventory| {
+            inventory.detected_templates.remove(&worktree_id);
+        })
+    }
+
     /// Updates in-memory task metadata from the JSON string given.
     /// Will fail if the JSON is not a valid array of objects, but will continue if any object will not parse into a [`TaskTemplate`].
     ///
@@ -292,30 +509,70 @@ impl Inventory {
             serde_json::from_value::<TaskTemplate>(raw_template).log_err()
         });
 
-        let parsed_templates = &mut self.templates_from_settings;
-        match location {
-            Some(location) => {
-                let new_templates = new_templates.collect::<Vec<_>>();
-                if new_templates.is_empty() {
-                    if let Some(worktree_tasks) =
-                        parsed_templates.worktree.get_mut(&location.worktree_id)
-                    {
-                        worktree_tasks.remove(location.path);
+        self.update_templates(|inventory| {
+            let parsed_templates = &mut inventory.templates_from_settings;
+            match location {
+                Some(location) => {
+                    let new_templates = new_templates.collect::<Vec<_>>();
+                    if new_templates.is_empty() {
+                        if let Some(worktree_tasks) =
+                            parsed_templates.worktree.get_mut(&location.worktree_id)
+                        {
+                            worktree_tasks.remove(location.path);
+                        }
+                    } else {
+                        parsed_templates
+                            .worktree
+                            .entry(location.worktree_id)
+                            .or_default()
+                            .insert(Arc::from(location.path), new_templates);
                     }
-                } else {
-                    parsed_templates
-                        .worktree
-                        .entry(location.worktree_id)
-                        .or_default()
-                        .insert(Arc::from(location.path), new_templates);
                 }
+                None => parsed_templates.global = new_templates.collect(),
             }
-            None => parsed_templates.global = new_templates.collect(),
-        }
+        });
         Ok(())
     }
 }
 
+fn resolve_dependencies(
+    templates: &[(TaskSourceKind, TaskTemplate)],
+    template: &TaskTemplate,
+    task_context: &TaskContext,
+    labels_stack: &mut Vec<String>,
+) -> Result<Vec<SpawnInTerminal>> {
+    template
+        .depends_on
+        .iter()
+        .map(|label| {
+            if labels_stack.contains(label) {
+                bail!(
+                    "Task dependency cycle: {} -> {label}",
+                    labels_stack.join(" -> ")
+                );
+            }
+            let (kind, dependency) = templates
+                .iter()
+                .find(|(_, template)| &template.label == label)
+                .with_context(|| {
+                    format!(
+                        "Task '{}' depends on an unknown task '{label}'",
+                        template.label
+                    )
+                })?;
+            let mut spawn_in_terminal = dependency
+                .resolve_task(&kind.to_id_base(), task_context)
+                .and_then(|resolved_task| resolved_task.resolved)
+                .with_context(|| format!("Failed to resolve task '{label}'"))?;
+            labels_stack.push(label.clone());
+            spawn_in_terminal.dependencies =
+                resolve_dependencies(templates, dependency, task_context, labels_stack)?;
+            labels_stack.pop();
+            Ok(spawn_in_terminal)
+        })
+        .collect()
+}
+
 fn task_lru_comparator(
     (kind_a, task_a, lru_score_a): &(TaskSourceKind, ResolvedTask, u32),
     (kind_b, task_b, lru_score_b): &(TaskSourceKind, ResolvedTask, u32),
@@ -345,6 +602,7 @@ fn task_source_kind_preference(kind: &TaskSourceKind) -> u32 {
         TaskSourceKind::UserInput => 2,
         TaskSourceKind::Worktree { .. } => 3,
         TaskSourceKind::AbsPath { .. } => 4,
+        TaskSourceKind::Detected { .. } => 5,
     }
 }
 
@@ -814,6 +1072,129 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_task_dependencies(cx: &mut TestAppContext) {
+        init_test(cx);
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    None,
+                    Some(
+                        &json!([
+                            {
+                                "label": "build",
+                                "command": "cargo build",
+                                "depends_on": ["generate", "server"],
+                            },
+                            { "label": "generate", "command": "make", "depends_on": ["server"] },
+                            { "label": "server", "command": "serve", "ready_pattern": "^Listening" },
+                            { "label": "all", "command": "", "depends_on": ["build"] },
+                            { "label": "cycle_1", "command": "echo", "depends_on": ["cycle_2"] },
+                            { "label": "cycle_2", "command": "echo", "depends_on": ["cycle_1"] },
+                            { "label": "unknown", "command": "echo", "depends_on": ["missing"] },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+        });
+        assert_eq!(
+            resolved_task_names(&inventory, None, cx).await,
+            vec!["all", "build", "generate", "server"],
+            "Tasks with unresolvable dependencies should not be listed"
+        );
+
+        let (_, tasks) = inventory.update(cx, |inventory, cx| {
+            inventory.used_and_current_resolved_tasks(None, None, &TaskContext::default(), cx)
+        });
+        let task = |label: &str| {
+            tasks
+                .iter()
+                .map(|(_, task)| task)
+                .find(|task| task.original_task().label == label)
+                .unwrap()
+                .clone()
+        };
+        let dependency_labels = |spawn_in_terminal: &SpawnInTerminal| {
+            spawn_in_terminal
+                .dependencies
+                .iter()
+                .map(|dependency| dependency.label.clone())
+                .collect::<Vec<_>>()
+        };
+        let build = task("build");
+        let build_spawn = build.resolved.as_ref().unwrap();
+        assert_eq!(dependency_labels(build_spawn), vec!["generate", "server"]);
+        assert_eq!(
+            dependency_labels(&build_spawn.dependencies[0]),
+            vec!["server"]
+        );
+        assert_eq!(
+            build_spawn.dependencies[1].ready_pattern.as_deref(),
+            Some("^Listening")
+        );
+
+        let all = task("all");
+        let server = task("server");
+        inventory.update(cx, |inventory, _| {
+            assert_eq!(inventory.combined_task_status(&all), None);
+            inventory.set_task_status(all.id.clone(), TaskRunStatus::Waiting);
+            inventory.set_task_status(server.id.clone(), TaskRunStatus::Ready);
+            assert_eq!(
+                inventory.combined_task_status(&all),
+                Some(CombinedTaskStatus {
+                    status: TaskRunStatus::Waiting,
+                    ready: 1,
+                    total: 4,
+                }),
+                "Dependencies shared by several tasks should be counted once"
+            );
+            inventory.set_task_status(task("generate").id, TaskRunStatus::Failed);
+            assert_eq!(
+                inventory
+                    .combined_task_status(&all)
+                    .map(|status| status.status),
+                Some(TaskRunStatus::Failed)
+            );
+
+            inventory.forget_task_status(&task("generate").id);
+            assert_eq!(
+                inventory.combined_task_status(&all),
+                Some(CombinedTaskStatus {
+                    status: TaskRunStatus::Waiting,
+                    ready: 1,
+                    total: 4,
+                })
+            );
+        });
+
+        // The states of tasks whose templates are removed are forgotten.
+        inventory.update(cx, |inventory, _| {
+            inventory
+                .update_file_based_tasks(
+                    None,
+                    Some(
+                        &json!([
+                            { "label": "all", "command": "", "depends_on": ["build"] },
+                            { "label": "build", "command": "cargo build" },
+                        ])
+                        .to_string(),
+                    ),
+                )
+                .unwrap();
+            assert_eq!(
+                inventory.combined_task_status(&all),
+                Some(CombinedTaskStatus {
+                    status: TaskRunStatus::Waiting,
+                    ready: 0,
+                    total: 4,
+                })
+            );
+            assert_eq!(inventory.task_statuses.len(), 1);
+        });
+    }
+
     fn init_test(_cx: &mut TestAppContext) {
         if std::env::var("RUST_LOG").is_ok() {
             env_logger::try_init().ok();
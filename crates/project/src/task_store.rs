@@ -1,23 +1,29 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::Context as _;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use fs::Fs;
 use futures::StreamExt as _;
 use gpui::{AppContext, AsyncAppContext, EventEmitter, Model, ModelContext, Task, WeakModel};
+use itertools::Itertools as _;
 use language::{
     proto::{deserialize_anchor, serialize_anchor},
     ContextProvider as _, LanguageToolchainStore, Location,
 };
 use rpc::{proto, AnyProtoClient, TypedEnvelope};
 use settings::{watch_config_file, SettingsLocation};
-use task::{TaskContext, TaskVariables, VariableName};
+use task::{DetectedTaskSource, TaskContext, TaskVariables, VariableName};
 use text::BufferId;
 use util::ResultExt;
+use worktree::{PathChange, UpdatedEntriesSet, Worktree};
 
 use crate::{
-    buffer_store::BufferStore, worktree_store::WorktreeStore, BasicContextProvider, Inventory,
-    ProjectEnvironment,
+    buffer_store::BufferStore,
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
+    BasicContextProvider, Inventory, ProjectEnvironment,
 };
 
 #[expect(clippy::large_enum_variant)]
@@ -37,6 +43,7 @@ pub struct StoreState {
 
 enum StoreMode {
     Local {
+        fs: Arc<dyn Fs>,
         downstream_client: Option<(AnyProtoClient, u64)>,
         environment: Model<ProjectEnvironment>,
     },
@@ -161,8 +168,11 @@ impl TaskStore {
         environment: Model<ProjectEnvironment>,
         cx: &mut ModelContext<'_, Self>,
     ) -> Self {
+        cx.subscribe(&worktree_store, Self::on_worktree_store_event)
+            .detach();
         Self::Functional(StoreState {
             mode: StoreMode::Local {
+                fs: fs.clone(),
                 downstream_client: None,
                 environment,
             },
@@ -284,6 +294,137 @@ impl TaskStore {
         })
     }
 
+    fn on_worktree_store_event(
+        &mut self,
+        _: Model<WorktreeStore>,
+        event: &WorktreeStoreEvent,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            WorktreeStoreEvent::WorktreeAdded(worktree) => {
+                cx.subscribe(worktree, |this, worktree, event, cx| {
+                    if let worktree::Event::UpdatedEntries(changes) = event {
+                        this.update_detected_tasks(&worktree, changes, cx);
+                    }
+                })
+                .detach();
+            }
+            WorktreeStoreEvent::WorktreeRemoved(_, worktree_id) => {
+                if let Some(inventory) = self.task_inventory() {
+                    inventory.update(cx, |inventory, _| inventory.remove_worktree(*worktree_id));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Detects the tasks again in the files that declare them and changed,
+    /// and in the ones next to the files added or removed, e.g. a lock file that changes the package manager.
+    fn update_detected_tasks(
+        &self,
+        worktree: &Model<Worktree>,
+        changes: &UpdatedEntriesSet,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let TaskStore::Functional(StoreState {
+            mode: StoreMode::Local { fs, .. },
+            ..
+        }) = self
+        else {
+            return;
+        };
+        let snapshot = worktree.read(cx).snapshot();
+        let changed_paths = changes
+            .iter()
+            .map(|(path, _, _)| path)
+            .collect::<HashSet<_>>();
+        let sources = changes
+            .iter()
+            .flat_map(|(path, _, change)| {
+                let neighbor_sources = match change {
+                    PathChange::Added | PathChange::Removed => {
+                        // `src/bin/tool.rs` is a binary of the package in the directory above `src`.
+                        path.ancestors().skip(1).take(3).collect::<Vec<_>>()
+                    }
+                    _ => Vec::new(),
+                }
+                .into_iter()
+                .flat_map(|directory| {
+                    DetectedTaskSource::file_paths()
+                        .map(move |file_path| Arc::<Path>::from(directory.join(file_path)))
+                });
+                Some(path.clone())
+                    .into_iter()
+                    .chain(neighbor_sources)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|path| DetectedTaskSource::for_path(path).is_some())
+            .unique()
+            .filter_map(|path| {
+                let entry = snapshot.entry_for_path(&path);
+                if entry.map_or(false, |entry| entry.is_ignored || !entry.is_file()) {
+                    return None;
+                }
+                let exists = entry.is_some();
+                // Neighbors of the changed paths are only checked if they exist.
+                if !exists && !changed_paths.contains(&path) {
+                    return None;
+                }
+                let abs_path = snapshot.absolutize(&path).log_err()?;
+                Some((path, abs_path, exists))
+            })
+            .collect::<Vec<_>>();
+        if sources.is_empty() {
+            return;
+        }
+
+        let fs = fs.clone();
+        let worktree = worktree.clone();
+        cx.spawn(move |task_store, mut cx| async move {
+            for (path, abs_path, exists) in sources {
+                let contents = if exists {
+                    fs.load(&abs_path).await.log_err()
+                } else {
+                    None
+                };
+                let Ok(()) = task_store.update(&mut cx, |task_store, cx| {
+                    let Some((source, directory)) = DetectedTaskSource::for_path(&path) else {
+                        return;
+                    };
+                    let Some(inventory) = task_store.task_inventory() else {
+                        return;
+                    };
+                    let snapshot = worktree.read(cx).snapshot();
+                    let templates = contents.and_then(|contents| {
+                        source
+                            .task_templates(&contents, directory, &|relative_directory| {
+                                snapshot
+                                    .child_entries(&directory.join(relative_directory))
+                                    .filter(|entry| entry.is_file())
+                                    .filter_map(|entry| {
+                                        Some(entry.path.file_name()?.to_string_lossy().to_string())
+                                    })
+                                    .collect()
+                            })
+                            .with_context(|| format!("detecting tasks in {abs_path:?}"))
+                            .log_err()
+                    });
+                    inventory.update(cx, |inventory, _| {
+                        inventory.update_detected_tasks(
+                            snapshot.id(),
+                            path.clone(),
+                            source,
+                            templates,
+                        )
+                    });
+                }) else {
+                    break;
+                };
+            }
+        })
+        .detach();
+    }
+
     fn subscribe_to_global_task_file_changes(
         fs: Arc<dyn Fs>,
         cx: &mut ModelContext<'_, Self>,
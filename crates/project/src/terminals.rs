@@ -1,20 +1,25 @@
 use crate::Project;
-use anyhow::{Context as _, Result};
+use anyhow::{anyhow, Context as _, Result};
 use collections::HashMap;
 use gpui::{AnyWindowHandle, AppContext, Context, Entity, Model, ModelContext, Task, WeakModel};
 use itertools::Itertools;
 use language::LanguageName;
 use settings::{Settings, SettingsLocation};
-use smol::channel::bounded;
+use smol::{channel::bounded, io::AsyncWriteExt as _};
 use std::{
     borrow::Cow,
+    cell::Cell,
     env::{self},
-    iter,
+    io, iter,
     path::{Path, PathBuf},
+    process::{Output, Stdio},
+    rc::Rc,
     sync::Arc,
+    time::Duration,
 };
 use task::{Shell, SpawnInTerminal};
 use terminal::{
+    shell_integration,
     terminal_settings::{self, TerminalSettings, VenvSettings},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
 };
@@ -23,6 +28,9 @@ use util::ResultExt;
 // #[cfg(target_os = "macos")]
 // use std::os::unix::ffi::OsStrExt;
 
+/// How long to wait for more problems in a task's output before publishing them.
+const TASK_PROBLEMS_DEBOUNCE: Duration = Duration::from_millis(200);
+
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
 }
@@ -70,6 +78,92 @@ impl Project {
         }
     }
 
+    /// Runs a command with the user's shell in the first project directory, writing
+    /// `input` to its stdin. The command gets the environment that a terminal opened in
+    /// that directory would have, so that direnv and toolchain managers apply.
+    pub fn run_shell_command(
+        &mut self,
+        command: String,
+        input: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Output>> {
+        if !self.is_local() {
+            return Task::ready(Err(anyhow!(
+                "shell commands can only be run in local projects"
+            )));
+        }
+
+        let cwd = self.first_project_directory(cx);
+        let worktree_id = cwd
+            .as_ref()
+            .and_then(|cwd| self.find_worktree(cwd, cx))
+            .map(|(worktree, _)| worktree.read(cx).id());
+        let settings_location = worktree_id.map(|worktree_id| SettingsLocation {
+            worktree_id,
+            path: Path::new(""),
+        });
+        let settings = TerminalSettings::get(settings_location, cx);
+        let shell = match &settings.shell {
+            Shell::System => None,
+            Shell::Program(program) | Shell::WithArguments { program, .. } => Some(program.clone()),
+        };
+        let settings_env = settings.env.clone();
+        let environment = self.environment.update(cx, |environment, cx| {
+            environment.get_environment(worktree_id, cwd.clone().map(Arc::from), cx)
+        });
+
+        cx.background_executor().spawn(async move {
+            let mut env = environment.await.unwrap_or_default();
+            env.extend(settings_env);
+            let (shell, flag) = if cfg!(windows) {
+                (shell.unwrap_or_else(|| "cmd".to_string()), "/C")
+            } else {
+                let shell = shell
+                    .or_else(|| env.get("SHELL").cloned())
+                    .unwrap_or_else(|| "sh".to_string());
+                (shell, "-c")
+            };
+
+            let mut process = util::command::new_smol_command(&shell);
+            process
+                .arg(flag)
+                .arg(&command)
+                .envs(&env)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            // A worktree's directory may no longer exist, or only exist in a test's
+            // fake file system.
+            if let Some(cwd) = cwd.filter(|cwd| cwd.is_dir()) {
+                process.current_dir(cwd);
+            }
+            let mut child = process
+                .spawn()
+                .with_context(|| format!("failed to run {command:?} with {shell}"))?;
+
+            // Write the input while reading the output, as the command may not read all of
+            // its input before its output fills the pipe.
+            let stdin = child.stdin.take();
+            let write_input = async move {
+                if let Some((mut stdin, input)) = stdin.zip(input) {
+                    // Filters like `head` exit without reading all of their input.
+                    if let Err(error) = stdin.write_all(input.as_bytes()).await {
+                        if error.kind() != io::ErrorKind::BrokenPipe {
+                            return Err(error.into());
+                        }
+                    }
+                }
+                anyhow::Ok(())
+            };
+            let (write_result, output) = futures::join!(write_input, child.output());
+            let output = output?;
+            if output.status.success() {
+                write_result?;
+            }
+            Ok(output)
+        })
+    }
+
     fn ssh_details(&self, cx: &AppContext) -> Option<(String, SshCommand)> {
         if let Some(ssh_client) = &self.ssh_client {
             let ssh_client = ssh_client.read(cx);
@@ -179,6 +273,13 @@ impl Project {
                                 },
                             )
                         }
+                        None if settings.shell_integration => {
+                            let shell = settings.shell.clone();
+                            let shell = shell_integration::inject(shell.clone(), &mut env)
+                                .log_err()
+                                .unwrap_or(shell);
+                            (None, shell)
+                        }
                         None => (None, settings.shell.clone()),
                     }
                 }
@@ -193,6 +294,9 @@ impl Project {
                         show_summary: spawn_task.show_summary,
                         show_command: spawn_task.show_command,
                         completion_rx,
+                        ready_pattern: spawn_task.ready_pattern,
+                        ready: false,
+                        problem_matchers: spawn_task.problem_matchers,
                     });
 
                     env.extend(spawn_task.env);
@@ -278,6 +382,34 @@ impl Project {
                     })
                     .detach();
 
+                    if terminal_handle.read(cx).task_problems().is_some() {
+                        // Clear the problems left from the previous run of the task.
+                        this.publish_task_problems(&terminal_handle, cx);
+                        let publish_scheduled = Rc::new(Cell::new(false));
+                        cx.subscribe(&terminal_handle, move |_, terminal, event, cx| {
+                            if let terminal::Event::TaskProblemsChanged = event {
+                                // Tasks can print many problems in a row, publish them in batches.
+                                if publish_scheduled.replace(true) {
+                                    return;
+                                }
+                                let publish_scheduled = publish_scheduled.clone();
+                                let terminal = terminal.downgrade();
+                                cx.spawn(|project, mut cx| async move {
+                                    cx.background_executor().timer(TASK_PROBLEMS_DEBOUNCE).await;
+                                    publish_scheduled.set(false);
+                                    let terminal = terminal.upgrade()?;
+                                    project
+                                        .update(&mut cx, |project, cx| {
+                                            project.publish_task_problems(&terminal, cx)
+                                        })
+                                        .ok()
+                                })
+                                .detach();
+                            }
+                        })
+                        .detach();
+                    }
+
                     if let Some(activate_command) = python_venv_activate_command {
                         this.activate_python_virtual_environment(
                             activate_command,
@@ -293,6 +425,32 @@ impl Project {
         })
     }
 
+    /// Reports the problems found in the task output as diagnostics, one set per matcher source.
+    fn publish_task_problems(&mut self, terminal: &Model<Terminal>, cx: &mut ModelContext<Self>) {
+        let Some(task_problems) = terminal.read(cx).task_problems() else {
+            return;
+        };
+        let problems_by_source = task_problems
+            .sources()
+            .map(|source| {
+                let problems = task_problems
+                    .problems()
+                    .iter()
+                    .filter(|problem| problem.source == source)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                (source.to_string(), problems)
+            })
+            .collect::<Vec<_>>();
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            for (source, problems) in problems_by_source {
+                lsp_store
+                    .update_task_diagnostics(&source, problems, cx)
+                    .log_err();
+            }
+        });
+    }
+
     fn python_venv_directory(
         &self,
         abs_path: Arc<Path>,
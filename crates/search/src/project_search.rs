@@ -1,7 +1,7 @@
 use crate::{
     buffer_search::Deploy, BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery,
     ReplaceAll, ReplaceNext, SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive,
-    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleSemantic, ToggleWholeWord,
 };
 use collections::{HashMap, HashSet};
 use editor::{
@@ -18,13 +18,14 @@ use gpui::{
     ModelContext, ParentElement, Point, Render, SharedString, Styled, Subscription, Task,
     TextStyle, UpdateGlobal, View, ViewContext, VisualContext, WeakModel, WeakView, WindowContext,
 };
-use language::Buffer;
+use language::{Bias, Buffer};
 use menu::Confirm;
 use project::{
     search::{SearchInputKind, SearchQuery},
     search_history::SearchHistoryCursor,
     Project, ProjectPath,
 };
+use semantic_index::{PathFilter, ProjectIndex, SemanticDb, Status};
 use settings::Settings;
 use std::{
     any::{Any, TypeId},
@@ -37,7 +38,7 @@ use ui::{
     h_flex, prelude::*, utils::SearchInputWidth, v_flex, Icon, IconButton, IconButtonShape,
     IconName, KeyBinding, Label, LabelCommon, LabelSize, Selectable, Tooltip,
 };
-use util::paths::PathMatcher;
+use util::{paths::PathMatcher, ResultExt as _};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle},
     searchable::{Direction, SearchableItem, SearchableItemHandle},
@@ -50,6 +51,9 @@ actions!(
     [SearchInNew, ToggleFocus, NextField, ToggleFilters]
 );
 
+/// How many chunks of the project's semantic index a search by meaning shows.
+const SEMANTIC_SEARCH_LIMIT: usize = 64;
+
 #[derive(Default)]
 struct ActiveSettings(HashMap<WeakModel<Project>, ProjectSearchSettings>);
 
@@ -76,6 +80,11 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleSemantic, cx| {
+            if cx.has_global::<SemanticDb>() {
+                search_bar.toggle_search_option(SearchOptions::SEMANTIC, cx);
+            }
+        });
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
@@ -128,6 +137,13 @@ pub struct ProjectSearch {
     pending_search: Option<Task<Option<()>>>,
     match_ranges: Vec<Range<Anchor>>,
     active_query: Option<SearchQuery>,
+    /// Whether the active query was searched for by meaning, in the project's
+    /// semantic index.
+    active_query_is_semantic: bool,
+    /// The semantic index the active query was searched for in, if the
+    /// project has one.
+    semantic_index: Option<Model<ProjectIndex>>,
+    _semantic_index_subscription: Option<Subscription>,
     last_search_query_text: Option<String>,
     search_id: usize,
     no_results: Option<bool>,
@@ -184,6 +200,9 @@ impl ProjectSearch {
             pending_search: Default::default(),
             match_ranges: Default::default(),
             active_query: None,
+            active_query_is_semantic: false,
+            semantic_index: None,
+            _semantic_index_subscription: None,
             last_search_query_text: None,
             search_id: 0,
             no_results: None,
@@ -195,23 +214,43 @@ impl ProjectSearch {
     }
 
     fn clone(&self, cx: &mut ModelContext<Self>) -> Model<Self> {
-        cx.new_model(|cx| Self {
-            project: self.project.clone(),
-            excerpts: self
-                .excerpts
-                .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
-            pending_search: Default::default(),
-            match_ranges: self.match_ranges.clone(),
-            active_query: self.active_query.clone(),
-            last_search_query_text: self.last_search_query_text.clone(),
-            search_id: self.search_id,
-            no_results: self.no_results,
-            limit_reached: self.limit_reached,
-            search_history_cursor: self.search_history_cursor.clone(),
-            search_included_history_cursor: self.search_included_history_cursor.clone(),
-            search_excluded_history_cursor: self.search_excluded_history_cursor.clone(),
+        cx.new_model(|cx| {
+            let mut this = Self {
+                project: self.project.clone(),
+                excerpts: self
+                    .excerpts
+                    .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
+                pending_search: Default::default(),
+                match_ranges: self.match_ranges.clone(),
+                active_query: self.active_query.clone(),
+                active_query_is_semantic: self.active_query_is_semantic,
+                semantic_index: None,
+                _semantic_index_subscription: None,
+                last_search_query_text: self.last_search_query_text.clone(),
+                search_id: self.search_id,
+                no_results: self.no_results,
+                limit_reached: self.limit_reached,
+                search_history_cursor: self.search_history_cursor.clone(),
+                search_included_history_cursor: self.search_included_history_cursor.clone(),
+                search_excluded_history_cursor: self.search_excluded_history_cursor.clone(),
+            };
+            this.set_semantic_index(self.semantic_index.clone(), cx);
+            this
         })
     }
+
+    /// Watches the index a search by meaning was made in, to show whether it
+    /// is still loading or indexing files.
+    fn set_semantic_index(
+        &mut self,
+        semantic_index: Option<Model<ProjectIndex>>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self._semantic_index_subscription = semantic_index
+            .as_ref()
+            .map(|semantic_index| cx.subscribe(semantic_index, |_, _, _: &Status, cx| cx.notify()));
+        self.semantic_index = semantic_index;
+    }
     fn cursor(&self, kind: SearchInputKind) -> &SearchHistoryCursor {
         match kind {
             SearchInputKind::Query => &self.search_history_cursor,
@@ -227,8 +266,8 @@ impl ProjectSearch {
         }
     }
 
-    fn search(&mut self, query: SearchQuery, cx: &mut ModelContext<Self>) {
-        let search = self.project.update(cx, |project, cx| {
+    fn add_to_history(&mut self, query: &SearchQuery, cx: &mut ModelContext<Self>) {
+        self.project.update(cx, |project, _| {
             project
                 .search_history_mut(SearchInputKind::Query)
                 .add(&mut self.search_history_cursor, query.as_str().to_string());
@@ -244,11 +283,19 @@ impl ProjectSearch {
                     .search_history_mut(SearchInputKind::Exclude)
                     .add(&mut self.search_excluded_history_cursor, excluded);
             }
-            project.search(query.clone(), cx)
         });
+    }
+
+    fn search(&mut self, query: SearchQuery, cx: &mut ModelContext<Self>) {
+        self.add_to_history(&query, cx);
+        let search = self
+            .project
+            .update(cx, |project, cx| project.search(query.clone(), cx));
         self.last_search_query_text = Some(query.as_str().to_string());
         self.search_id += 1;
         self.active_query = Some(query);
+        self.active_query_is_semantic = false;
+        self.set_semantic_index(None, cx);
         self.match_ranges.clear();
         self.pending_search = Some(cx.spawn(|this, mut cx| async move {
             let mut matches = search.ready_chunks(1024);
@@ -309,6 +356,124 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
+
+    /// Searches the project's semantic index for the chunks of code that are
+    /// closest in meaning to the query, ranking those that also contain its
+    /// words higher. Files are shown in the order of their best chunk.
+    fn semantic_search(&mut self, query: SearchQuery, cx: &mut ModelContext<Self>) {
+        self.add_to_history(&query, cx);
+        let project = self.project.clone();
+        let project_index = if cx.has_global::<SemanticDb>() {
+            cx.update_global(|semantic_db: &mut SemanticDb, cx| {
+                semantic_db.project_index(project.clone(), cx)
+            })
+        } else {
+            None
+        };
+        let path_filter = PathFilter {
+            files_to_include: query.as_inner().files_to_include().clone(),
+            files_to_exclude: query.as_inner().files_to_exclude().clone(),
+        };
+        let search = project_index.as_ref().map(|project_index| {
+            project_index.read(cx).hybrid_search(
+                query.as_str().to_string(),
+                path_filter,
+                SEMANTIC_SEARCH_LIMIT,
+                cx,
+            )
+        });
+        self.last_search_query_text = Some(query.as_str().to_string());
+        self.search_id += 1;
+        self.active_query = Some(query);
+        self.active_query_is_semantic = true;
+        self.set_semantic_index(project_index, cx);
+        self.match_ranges.clear();
+        self.pending_search = Some(cx.spawn(|this, mut cx| async move {
+            let results = match search {
+                Some(search) => search.await.log_err().unwrap_or_default(),
+                None => Vec::new(),
+            };
+            let this = this.upgrade()?;
+            this.update(&mut cx, |this, cx| {
+                this.match_ranges.clear();
+                this.excerpts.update(cx, |this, cx| this.clear(cx));
+                this.no_results = Some(true);
+                this.limit_reached = false;
+            })
+            .ok()?;
+
+            let mut ranges_by_path = Vec::<(ProjectPath, Vec<Range<usize>>)>::new();
+            for result in results {
+                let project_path = ProjectPath {
+                    worktree_id: result
+                        .worktree
+                        .read_with(&cx, |worktree, _| worktree.id())
+                        .ok()?,
+                    path: result.path,
+                };
+                match ranges_by_path
+                    .iter_mut()
+                    .find(|(path, _)| *path == project_path)
+                {
+                    Some((_, ranges)) => ranges.push(result.range),
+                    None => ranges_by_path.push((project_path, vec![result.range])),
+                }
+            }
+
+            let mut buffers_with_ranges = Vec::with_capacity(ranges_by_path.len());
+            for (project_path, mut ranges) in ranges_by_path {
+                let Some(buffer) = project
+                    .update(&mut cx, |project, cx| project.open_buffer(project_path, cx))
+                    .ok()?
+                    .await
+                    .log_err()
+                else {
+                    continue;
+                };
+                ranges.sort_unstable_by_key(|range| range.start);
+                let ranges = buffer
+                    .read_with(&cx, |buffer, _| {
+                        // The buffer may have changed since it was indexed.
+                        ranges
+                            .into_iter()
+                            .map(|range| {
+                                let start =
+                                    buffer.clip_offset(range.start.min(buffer.len()), Bias::Left);
+                                let end =
+                                    buffer.clip_offset(range.end.min(buffer.len()), Bias::Right);
+                                buffer.anchor_after(start)..buffer.anchor_before(end)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .ok()?;
+                buffers_with_ranges.push((buffer, ranges));
+            }
+
+            let match_ranges = this
+                .update(&mut cx, |this, cx| {
+                    this.excerpts.update(cx, |excerpts, cx| {
+                        excerpts.push_multiple_excerpts_with_context_lines(
+                            buffers_with_ranges,
+                            editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                            cx,
+                        )
+                    })
+                })
+                .ok()?
+                .await;
+
+            this.update(&mut cx, |this, cx| {
+                this.no_results = Some(match_ranges.is_empty());
+                this.match_ranges = match_ranges;
+                this.pending_search.take();
+                cx.notify();
+            })
+            .ok()?;
+
+            None
+        }));
+        cx.notify();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -334,7 +499,37 @@ impl Render for ProjectSearchView {
             let has_no_results = model.no_results.unwrap_or(false);
             let is_search_underway = model.pending_search.is_some();
 
-            let heading_text = if is_search_underway {
+            // Searches by meaning find nothing until the project's index is ready.
+            let semantic_index_message = if model.active_query_is_semantic {
+                match model
+                    .semantic_index
+                    .as_ref()
+                    .map(|index| index.read(cx).status())
+                {
+                    None => Some((
+                        "No Index",
+                        "This project hasn't been indexed, so it can't be searched by meaning"
+                            .to_string(),
+                    )),
+                    Some(Status::Loading) => Some((
+                        "Loading Index…",
+                        "The project will be searched once its index has loaded".to_string(),
+                    )),
+                    Some(Status::Scanning { remaining_count }) => Some((
+                        "Indexing…",
+                        format!(
+                            "Results may be missing until {remaining_count} more files are indexed"
+                        ),
+                    )),
+                    Some(Status::Idle) => None,
+                }
+            } else {
+                None
+            };
+
+            let heading_text = if let Some((heading_text, _)) = &semantic_index_message {
+                *heading_text
+            } else if is_search_underway {
                 "Searching…"
             } else if has_no_results {
                 "No Results"
@@ -346,19 +541,26 @@ impl Render for ProjectSearchView {
                 .justify_center()
                 .child(Label::new(heading_text).size(LabelSize::Large));
 
-            let page_content: Option<AnyElement> = if let Some(no_results) = model.no_results {
-                if model.pending_search.is_none() && no_results {
+            let page_content: Option<AnyElement> =
+                if let Some((_, message)) = semantic_index_message {
                     Some(
-                        Label::new("No results found in this project for the provided query")
+                        Label::new(message)
                             .size(LabelSize::Small)
                             .into_any_element(),
                     )
+                } else if let Some(no_results) = model.no_results {
+                    if model.pending_search.is_none() && no_results {
+                        Some(
+                            Label::new("No results found in this project for the provided query")
+                                .size(LabelSize::Small)
+                                .into_any_element(),
+                        )
+                    } else {
+                        None
+                    }
                 } else {
-                    None
-                }
-            } else {
-                Some(self.landing_text_minor(cx).into_any_element())
-            };
+                    Some(self.landing_text_minor(cx).into_any_element())
+                };
 
             let page_content = page_content.map(|text| div().child(text));
 
@@ -575,7 +777,17 @@ impl ProjectSearchView {
         }
     }
 
+    /// Whether `option` affects the search. Searches by meaning don't match
+    /// the query's text, so they can't match whole words or case.
+    fn is_option_applicable(&self, option: SearchOptions) -> bool {
+        !self.search_options.contains(SearchOptions::SEMANTIC)
+            || !option.intersects(SearchOptions::CASE_SENSITIVE | SearchOptions::WHOLE_WORD)
+    }
+
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
+        if !self.is_option_applicable(option) {
+            return;
+        }
         self.search_options.toggle(option);
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
@@ -590,7 +802,9 @@ impl ProjectSearchView {
     }
 
     fn replace_next(&mut self, _: &ReplaceNext, cx: &mut ViewContext<Self>) {
-        if self.model.read(cx).match_ranges.is_empty() {
+        let model = self.model.read(cx);
+        // Results found by meaning are whole chunks of code, not text to replace.
+        if model.match_ranges.is_empty() || model.active_query_is_semantic {
             return;
         }
         let Some(active_index) = self.active_match_index else {
@@ -613,7 +827,7 @@ impl ProjectSearchView {
         self.replacement_editor.read(cx).text(cx)
     }
     fn replace_all(&mut self, _: &ReplaceAll, cx: &mut ViewContext<Self>) {
-        if self.active_match_index.is_none() {
+        if self.active_match_index.is_none() || self.model.read(cx).active_query_is_semantic {
             return;
         }
 
@@ -667,6 +881,7 @@ impl ProjectSearchView {
                 query_text = active_query.as_str().to_string();
                 replacement_text = active_query.replacement().map(ToOwned::to_owned);
                 options = SearchOptions::from_query(active_query);
+                options.set(SearchOptions::SEMANTIC, model.active_query_is_semantic);
             }
         }
         subscriptions.push(cx.observe(&model, |this, _, cx| this.model_changed(cx)));
@@ -823,22 +1038,32 @@ impl ProjectSearchView {
             .active_item(cx)
             .and_then(|item| item.downcast::<ProjectSearchView>())
         {
-            let new_query = search_view.update(cx, |search_view, cx| {
+            let (new_query, is_semantic) = search_view.update(cx, |search_view, cx| {
                 let new_query = search_view.build_search_query(cx);
+                let is_semantic = search_view.search_options.contains(SearchOptions::SEMANTIC);
                 if new_query.is_some() {
-                    if let Some(old_query) = search_view.model.read(cx).active_query.clone() {
+                    let model = search_view.model.read(cx);
+                    if let Some(old_query) = model.active_query.clone() {
+                        let old_query_is_semantic = model.active_query_is_semantic;
                         search_view.query_editor.update(cx, |editor, cx| {
                             editor.set_text(old_query.as_str(), cx);
                         });
                         search_view.search_options = SearchOptions::from_query(&old_query);
+                        search_view
+                            .search_options
+                            .set(SearchOptions::SEMANTIC, old_query_is_semantic);
                     }
                 }
-                new_query
+                (new_query, is_semantic)
             });
             if let Some(new_query) = new_query {
                 let model = cx.new_model(|cx| {
                     let mut model = ProjectSearch::new(workspace.project().clone(), cx);
-                    model.search(new_query, cx);
+                    if is_semantic {
+                        model.semantic_search(new_query, cx);
+                    } else {
+                        model.search(new_query, cx);
+                    }
                     model
                 });
                 let weak_workspace = cx.view().downgrade();
@@ -915,7 +1140,14 @@ impl ProjectSearchView {
 
     fn search(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(query) = self.build_search_query(cx) {
-            self.model.update(cx, |model, cx| model.search(query, cx));
+            let is_semantic = self.search_options.contains(SearchOptions::SEMANTIC);
+            self.model.update(cx, |model, cx| {
+                if is_semantic {
+                    model.semantic_search(query, cx)
+                } else {
+                    model.search(query, cx)
+                }
+            });
         }
     }
 
@@ -967,11 +1199,18 @@ impl ProjectSearchView {
                 }
             };
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
+        // Searches by meaning use the query's text as it is.
+        let is_regex = self.search_options.contains(SearchOptions::REGEX)
+            && !self.search_options.contains(SearchOptions::SEMANTIC);
+        let whole_word = self.search_options.contains(SearchOptions::WHOLE_WORD)
+            && self.is_option_applicable(SearchOptions::WHOLE_WORD);
+        let case_sensitive = self.search_options.contains(SearchOptions::CASE_SENSITIVE)
+            && self.is_option_applicable(SearchOptions::CASE_SENSITIVE);
+        let query = if is_regex {
             match SearchQuery::regex(
                 text,
-                self.search_options.contains(SearchOptions::WHOLE_WORD),
-                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                whole_word,
+                case_sensitive,
                 self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
                 included_files,
                 excluded_files,
@@ -997,8 +1236,8 @@ impl ProjectSearchView {
         } else {
             match SearchQuery::text(
                 text,
-                self.search_options.contains(SearchOptions::WHOLE_WORD),
-                self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+                whole_word,
+                case_sensitive,
                 self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
                 included_files,
                 excluded_files,
@@ -1228,6 +1467,20 @@ impl ProjectSearchView {
                     ))
                     .on_click(|_event, cx| cx.dispatch_action(ToggleWholeWord.boxed_clone())),
             )
+            .when(cx.has_global::<SemanticDb>(), |this| {
+                this.child(
+                    Button::new("search-by-meaning", "Search by meaning")
+                        .icon(IconName::Sparkle)
+                        .icon_position(IconPosition::Start)
+                        .icon_size(IconSize::Small)
+                        .key_binding(KeyBinding::for_action_in(
+                            &ToggleSemantic,
+                            &focus_handle,
+                            cx,
+                        ))
+                        .on_click(|_event, cx| cx.dispatch_action(ToggleSemantic.boxed_clone())),
+                )
+            })
     }
 
     fn border_color_for(&self, panel: InputPanel, cx: &WindowContext) -> Hsla {
@@ -1612,27 +1865,44 @@ impl Render for ProjectSearchBar {
             .child(
                 h_flex()
                     .gap_0p5()
-                    .child(SearchOptions::CASE_SENSITIVE.as_button(
-                        self.is_option_enabled(SearchOptions::CASE_SENSITIVE, cx),
-                        focus_handle.clone(),
-                        cx.listener(|this, _, cx| {
-                            this.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
-                        }),
-                    ))
-                    .child(SearchOptions::WHOLE_WORD.as_button(
-                        self.is_option_enabled(SearchOptions::WHOLE_WORD, cx),
-                        focus_handle.clone(),
-                        cx.listener(|this, _, cx| {
-                            this.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
-                        }),
-                    ))
+                    .child(
+                        SearchOptions::CASE_SENSITIVE
+                            .as_button(
+                                self.is_option_enabled(SearchOptions::CASE_SENSITIVE, cx),
+                                focus_handle.clone(),
+                                cx.listener(|this, _, cx| {
+                                    this.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
+                                }),
+                            )
+                            .disabled(!search.is_option_applicable(SearchOptions::CASE_SENSITIVE)),
+                    )
+                    .child(
+                        SearchOptions::WHOLE_WORD
+                            .as_button(
+                                self.is_option_enabled(SearchOptions::WHOLE_WORD, cx),
+                                focus_handle.clone(),
+                                cx.listener(|this, _, cx| {
+                                    this.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
+                                }),
+                            )
+                            .disabled(!search.is_option_applicable(SearchOptions::WHOLE_WORD)),
+                    )
                     .child(SearchOptions::REGEX.as_button(
                         self.is_option_enabled(SearchOptions::REGEX, cx),
                         focus_handle.clone(),
                         cx.listener(|this, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
-                    )),
+                    ))
+                    .when(cx.has_global::<SemanticDb>(), |this| {
+                        this.child(SearchOptions::SEMANTIC.as_button(
+                            self.is_option_enabled(SearchOptions::SEMANTIC, cx),
+                            focus_handle.clone(),
+                            cx.listener(|this, _, cx| {
+                                this.toggle_search_option(SearchOptions::SEMANTIC, cx);
+                            }),
+                        ))
+                    }),
             );
 
         let mode_column = h_flex()
@@ -3686,6 +3956,75 @@ pub mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_semantic_search_without_index(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        // Matching whole words and case can't be toggled when searching by meaning,
+        // and don't apply to the query.
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.search_options =
+                        SearchOptions::SEMANTIC | SearchOptions::WHOLE_WORD;
+                    search_view.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
+                    search_view.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
+                    assert_eq!(
+                        search_view.search_options,
+                        SearchOptions::SEMANTIC | SearchOptions::WHOLE_WORD
+                    );
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("ONE", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        // Without a semantic index, the search reports that instead of finding nothing.
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    let model = search_view.model.read(cx);
+                    let query = model.active_query.as_ref().unwrap();
+                    assert!(!query.whole_word());
+                    assert!(!query.case_sensitive());
+                    assert!(model.active_query_is_semantic);
+                    assert!(model.semantic_index.is_none());
+                    assert!(model.pending_search.is_none());
+                    assert_eq!(model.no_results, Some(true));
+                });
+            })
+            .unwrap();
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings = SettingsStore::test(cx);
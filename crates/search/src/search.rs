@@ -1,7 +1,7 @@
 use bitflags::bitflags;
 pub use buffer_search::BufferSearchBar;
 use editor::SearchSettings;
-use gpui::{actions, Action, AppContext, FocusHandle, IntoElement};
+use gpui::{actions, Action, AppContext, FocusHandle};
 use project::search::SearchQuery;
 pub use project_search::ProjectSearchView;
 use ui::{prelude::*, Tooltip};
@@ -27,6 +27,7 @@ actions!(
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
         ToggleRegex,
+        ToggleSemantic,
         ToggleReplace,
         ToggleSelection,
         SelectNextMatch,
@@ -47,6 +48,7 @@ bitflags! {
         const CASE_SENSITIVE = 0b010;
         const INCLUDE_IGNORED = 0b100;
         const REGEX = 0b1000;
+        const SEMANTIC = 0b10000;
     }
 }
 
@@ -57,6 +59,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => "Match Case Sensitively",
             SearchOptions::INCLUDE_IGNORED => "Also search files ignored by configuration",
             SearchOptions::REGEX => "Use Regular Expressions",
+            SearchOptions::SEMANTIC => "Search by Meaning",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -67,6 +70,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => ui::IconName::CaseSensitive,
             SearchOptions::INCLUDE_IGNORED => ui::IconName::Sliders,
             SearchOptions::REGEX => ui::IconName::Regex,
+            SearchOptions::SEMANTIC => ui::IconName::Sparkle,
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -77,6 +81,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
             SearchOptions::REGEX => Box::new(ToggleRegex),
+            SearchOptions::SEMANTIC => Box::new(ToggleSemantic),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -108,7 +113,7 @@ impl SearchOptions {
         active: bool,
         focus_handle: FocusHandle,
         action: impl Fn(&gpui::ClickEvent, &mut WindowContext) + 'static,
-    ) -> impl IntoElement {
+    ) -> IconButton {
         IconButton::new(self.label(), self.icon())
             .on_click(action)
             .style(ButtonStyle::Subtle)
@@ -1,8 +1,10 @@
 mod cloud;
+mod local;
 mod ollama;
 mod open_ai;
 
 pub use cloud::*;
+pub use local::*;
 pub use ollama::*;
 pub use open_ai::*;
 use sha2::{Digest, Sha256};
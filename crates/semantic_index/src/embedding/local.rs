@@ -0,0 +1,384 @@
+use crate::{Embedding, EmbeddingProvider, TextToEmbed};
+use anyhow::{anyhow, Context as _, Result};
+use candle_core::{Device, IndexOp, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use futures::{future::BoxFuture, AsyncReadExt as _, AsyncWriteExt as _, FutureExt};
+use gpui::BackgroundExecutor;
+use http_client::{AsyncBody, HttpClient};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+
+/// Longer texts are truncated, as the models don't support more tokens than this.
+const MAX_TOKENS: usize = 512;
+
+/// A sentence embedding model that runs in-process on the CPU.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LocalEmbeddingModel {
+    #[default]
+    BgeSmallEnV15,
+}
+
+impl LocalEmbeddingModel {
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::BgeSmallEnV15 => "bge-small-en-v1.5",
+        }
+    }
+
+    /// The URL the model's files are downloaded from, at a fixed commit of its
+    /// repository so that they match [`Self::files`].
+    fn url(&self) -> &'static str {
+        match self {
+            Self::BgeSmallEnV15 => "https://huggingface.co/BAAI/bge-small-en-v1.5/resolve/5c38ec7c405ec4b44b94cc5a9bb96e735b38267a",
+        }
+    }
+
+    /// The files that make up the model, as named in its repository.
+    fn files(&self) -> &'static [ModelFile] {
+        match self {
+            Self::BgeSmallEnV15 => &[
+                ModelFile {
+                    name: "config.json",
+                    sha256: "0000000000000000000000000000000000000000000000000000000000000000",
+                },
+                ModelFile {
+                    name: "tokenizer.json",
+                    sha256: "0000000000000000000000000000000000000000000000000000000000000000",
+                },
+                ModelFile {
+                    name: "model.safetensors",
+                    sha256: "0000000000000000000000000000000000000000000000000000000000000000",
+                },
+            ],
+        }
+    }
+
+    /// The directory the model's files are downloaded to.
+    ///
+    /// Placing the files there manually lets the model be used without ever
+    /// going online.
+    pub fn dir(&self) -> PathBuf {
+        paths::embedding_models_dir().join(self.id())
+    }
+}
+
+struct ModelFile {
+    name: &'static str,
+    /// The SHA-256 digest of the file, in lowercase hex.
+    sha256: &'static str,
+}
+
+pub struct LocalEmbeddingProvider {
+    model: Arc<BertModel>,
+    tokenizer: Arc<Tokenizer>,
+    executor: BackgroundExecutor,
+}
+
+impl LocalEmbeddingProvider {
+    /// Loads the model, downloading it first if it isn't on disk yet.
+    pub async fn new(
+        model: LocalEmbeddingModel,
+        client: Arc<dyn HttpClient>,
+        executor: BackgroundExecutor,
+    ) -> Result<Self> {
+        let dir = model.dir();
+        download_model_files(model.url(), model.files(), &dir, client.as_ref())
+            .await
+            .with_context(|| format!("downloading embedding model {}", model.id()))?;
+
+        let (bert, tokenizer) = executor
+            .spawn(async move { load_model(&dir) })
+            .await
+            .with_context(|| format!("loading embedding model {}", model.id()))?;
+
+        Ok(Self {
+            model: Arc::new(bert),
+            tokenizer: Arc::new(tokenizer),
+            executor,
+        })
+    }
+}
+
+/// Downloads the files that aren't in `dir` yet, checking their digests.
+async fn download_model_files(
+    url: &str,
+    files: &[ModelFile],
+    dir: &Path,
+    client: &dyn HttpClient,
+) -> Result<()> {
+    smol::fs::create_dir_all(dir).await?;
+    for file in files {
+        let path = dir.join(file.name);
+        if smol::fs::metadata(&path).await.is_ok() {
+            continue;
+        }
+
+        // Download to a temporary file first, so that an interrupted download
+        // isn't mistaken for a complete one the next time.
+        let temp_path = dir.join(format!("{}.download", file.name));
+        let file_url = format!("{url}/{}", file.name);
+        if let Err(error) = download_file(&file_url, file.sha256, &temp_path, client).await {
+            smol::fs::remove_file(&temp_path).await.ok();
+            return Err(error);
+        }
+        smol::fs::rename(&temp_path, &path).await?;
+    }
+    Ok(())
+}
+
+async fn download_file(
+    url: &str,
+    sha256: &str,
+    path: &Path,
+    client: &dyn HttpClient,
+) -> Result<()> {
+    log::info!("downloading {url}");
+    let mut response = client.get(url, AsyncBody::default(), true).await?;
+    if !response.status().is_success() {
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+        return Err(anyhow!(
+            "failed to download {url}: {} {body}",
+            response.status()
+        ));
+    }
+
+    let mut file = smol::fs::File::create(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let len = response.body_mut().read(&mut buffer).await?;
+        if len == 0 {
+            break;
+        }
+        hasher.update(&buffer[..len]);
+        file.write_all(&buffer[..len]).await?;
+    }
+    file.sync_all().await?;
+
+    let digest = format!("{:x}", hasher.finalize());
+    if digest != sha256 {
+        return Err(anyhow!(
+            "{url} has SHA-256 digest {digest}, expected {sha256}"
+        ));
+    }
+    Ok(())
+}
+
+fn load_model(dir: &Path) -> Result<(BertModel, Tokenizer)> {
+    let config: Config = serde_json::from_slice(&std::fs::read(dir.join("config.json"))?)?;
+
+    let mut tokenizer = Tokenizer::from_file(dir.join("tokenizer.json")).map_err(|e| anyhow!(e))?;
+    tokenizer.with_padding(Some(PaddingParams {
+        strategy: PaddingStrategy::BatchLongest,
+        ..Default::default()
+    }));
+    tokenizer
+        .with_truncation(Some(TruncationParams {
+            max_length: MAX_TOKENS,
+            ..Default::default()
+        }))
+        .map_err(|e| anyhow!(e))?;
+
+    // Safety: the weights are only mapped while nothing else writes to them,
+    // as they are renamed into place once their download completes.
+    let weights = [dir.join("model.safetensors")];
+    let vb = unsafe { VarBuilder::from_mmaped_safetensors(&weights, DTYPE, &Device::Cpu)? };
+    let model = BertModel::load(vb, &config)?;
+    Ok((model, tokenizer))
+}
+
+fn embed_batch(
+    model: &BertModel,
+    tokenizer: &Tokenizer,
+    texts: Vec<String>,
+) -> Result<Vec<Embedding>> {
+    if texts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // The whole batch goes through the model at once, padded to its longest text.
+    let encodings = tokenizer
+        .encode_batch(texts, true)
+        .map_err(|e| anyhow!(e))?;
+    let device = &Device::Cpu;
+    let mut token_ids = Vec::with_capacity(encodings.len());
+    let mut attention_masks = Vec::with_capacity(encodings.len());
+    for encoding in &encodings {
+        token_ids.push(Tensor::new(encoding.get_ids(), device)?);
+        attention_masks.push(Tensor::new(encoding.get_attention_mask(), device)?);
+    }
+    let token_ids = Tensor::stack(&token_ids, 0)?;
+    let attention_mask = Tensor::stack(&attention_masks, 0)?;
+    let token_type_ids = token_ids.zeros_like()?;
+
+    let output = model.forward(&token_ids, &token_type_ids, Some(&attention_mask))?;
+    // BGE models use the hidden state of the leading [CLS] token as the embedding.
+    let cls = output.i((.., 0))?.to_vec2::<f32>()?;
+    Ok(cls.into_iter().map(Embedding::new).collect())
+}
+
+impl EmbeddingProvider for LocalEmbeddingProvider {
+    fn embed<'a>(&'a self, texts: &'a [TextToEmbed<'a>]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        let model = self.model.clone();
+        let tokenizer = self.tokenizer.clone();
+        let texts = texts
+            .iter()
+            .map(|to_embed| to_embed.text.to_string())
+            .collect::<Vec<_>>();
+        self.executor
+            .spawn(async move { embed_batch(&model, &tokenizer, texts) })
+            .boxed()
+    }
+
+    fn batch_size(&self) -> usize {
+        16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_client::{FakeHttpClient, Response};
+    use parking_lot::Mutex;
+
+    const URL: &str = "https://models.example.com/model";
+
+    #[test]
+    fn test_pinned_digests() {
+        for model in [LocalEmbeddingModel::BgeSmallEnV15] {
+            for file in model.files() {
+                assert!(
+                    file.sha256.len() == 64
+                        && file
+                            .sha256
+                            .chars()
+                            .all(|c| matches!(c, '0'..='9' | 'a'..='f')),
+                    "{} of {} has a malformed digest",
+                    file.name,
+                    model.id()
+                );
+                assert!(
+                    file.sha256.chars().any(|c| c != '0'),
+                    "{} of {} is pinned to a placeholder digest",
+                    file.name,
+                    model.id()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_download_model_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("config.json"), "{}").unwrap();
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = FakeHttpClient::create({
+            let requests = requests.clone();
+            move |request| {
+                let requests = requests.clone();
+                async move {
+                    let url = request.uri().to_string();
+                    requests.lock().push(url.clone());
+                    let response = Response::builder();
+                    Ok(match url.strip_prefix(&format!("{URL}/")) {
+                        Some("tokenizer.json") => response.status(200).body("tokens".into())?,
+                        Some("model.safetensors") => response.status(200).body("weights".into())?,
+                        _ => response.status(404).body("not found".into())?,
+                    })
+                }
+            }
+        });
+
+        // Files already on disk aren't downloaded again.
+        let files = [
+            model_file("config.json", "the config"),
+            model_file("tokenizer.json", "tokens"),
+            model_file("model.safetensors", "weights"),
+        ];
+        smol::block_on(download_model_files(
+            URL,
+            &files,
+            dir.path(),
+            client.as_ref(),
+        ))
+        .unwrap();
+        assert_eq!(
+            *requests.lock(),
+            [
+                format!("{URL}/tokenizer.json"),
+                format!("{URL}/model.safetensors")
+            ]
+        );
+        assert_eq!(
+            dir_contents(dir.path()),
+            [
+                ("config.json".to_string(), "{}".to_string()),
+                ("model.safetensors".to_string(), "weights".to_string()),
+                ("tokenizer.json".to_string(), "tokens".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_downloads_leave_no_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = FakeHttpClient::create(|request| async move {
+            let response = Response::builder();
+            Ok(match request.uri().path() {
+                "/model/tokenizer.json" => response.status(200).body("tampered tokens".into())?,
+                _ => response.status(404).body("not found".into())?,
+            })
+        });
+
+        let files = [model_file("tokenizer.json", "tokens")];
+        let error = smol::block_on(download_model_files(
+            URL,
+            &files,
+            dir.path(),
+            client.as_ref(),
+        ))
+        .unwrap_err();
+        assert!(error.to_string().contains("SHA-256"), "{error}");
+        assert_eq!(dir_contents(dir.path()), []);
+
+        let files = [model_file("model.safetensors", "weights")];
+        let error = smol::block_on(download_model_files(
+            URL,
+            &files,
+            dir.path(),
+            client.as_ref(),
+        ))
+        .unwrap_err();
+        assert!(error.to_string().contains("404"), "{error}");
+        assert_eq!(dir_contents(dir.path()), []);
+    }
+
+    fn model_file(name: &'static str, contents: &str) -> ModelFile {
+        ModelFile {
+            name,
+            sha256: format!("{:x}", Sha256::digest(contents)).leak(),
+        }
+    }
+
+    fn dir_contents(dir: &Path) -> Vec<(String, String)> {
+        let mut contents = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let path = entry.unwrap().path();
+                (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    std::fs::read_to_string(&path).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        contents.sort();
+        contents
+    }
+}
@@ -0,0 +1,248 @@
+use collections::{HashMap, HashSet};
+use std::path::Path;
+use util::paths::PathMatcher;
+
+/// How much embedding similarity counts towards a chunk's score, relative to
+/// how well its words match the query.
+const SEMANTIC_WEIGHT: f32 = 0.6;
+
+/// BM25's term frequency saturation.
+const K1: f32 = 1.2;
+
+/// BM25's document length normalization.
+const B: f32 = 0.75;
+
+/// Restricts a search to the files whose paths match.
+#[derive(Clone, Debug, Default)]
+pub struct PathFilter {
+    pub files_to_include: PathMatcher,
+    pub files_to_exclude: PathMatcher,
+}
+
+impl PathFilter {
+    pub fn is_empty(&self) -> bool {
+        self.files_to_include.sources().is_empty() && self.files_to_exclude.sources().is_empty()
+    }
+
+    /// Whether the file at `path` in the worktree named `root_name` should be
+    /// searched. Globs may be written relative to the worktree or start with
+    /// its name, as in text search.
+    pub fn matches(&self, root_name: &str, path: &Path) -> bool {
+        self.matches_path(path) || self.matches_path(&Path::new(root_name).join(path))
+    }
+
+    fn matches_path(&self, path: &Path) -> bool {
+        let mut path = path.to_path_buf();
+        loop {
+            if self.files_to_exclude.is_match(&path) {
+                return false;
+            } else if self.files_to_include.sources().is_empty()
+                || self.files_to_include.is_match(&path)
+            {
+                return true;
+            } else if !path.pop() {
+                return false;
+            }
+        }
+    }
+}
+
+/// Scores chunks by both their embedding similarity to the query and how well
+/// their words match it, given each chunk's text and similarity.
+///
+/// Keyword scores use BM25, with term statistics taken from the chunks being
+/// ranked. Both kinds of scores are normalized to `0..=1` before they are
+/// combined, so that neither dominates.
+pub fn hybrid_scores(query: &str, chunks: &[(&str, f32)]) -> Vec<f32> {
+    let texts = chunks.iter().map(|(text, _)| *text).collect::<Vec<_>>();
+    let keyword_scores = keyword_scores(query, &texts);
+    let max_keyword_score = keyword_scores.iter().copied().fold(0., f32::max);
+
+    let min_similarity = chunks
+        .iter()
+        .map(|(_, similarity)| *similarity)
+        .fold(f32::INFINITY, f32::min);
+    let max_similarity = chunks
+        .iter()
+        .map(|(_, similarity)| *similarity)
+        .fold(f32::NEG_INFINITY, f32::max);
+    let similarity_range = max_similarity - min_similarity;
+
+    chunks
+        .iter()
+        .zip(keyword_scores)
+        .map(|((_, similarity), keyword_score)| {
+            let similarity = if similarity_range > f32::EPSILON {
+                (similarity - min_similarity) / similarity_range
+            } else {
+                1.
+            };
+            let keyword_score = if max_keyword_score > 0. {
+                keyword_score / max_keyword_score
+            } else {
+                0.
+            };
+            SEMANTIC_WEIGHT * similarity + (1. - SEMANTIC_WEIGHT) * keyword_score
+        })
+        .collect()
+}
+
+/// Scores each document by how well its words match the query's, using BM25.
+pub fn keyword_scores(query: &str, documents: &[&str]) -> Vec<f32> {
+    let query_terms = tokenize(query).collect::<HashSet<_>>();
+    if query_terms.is_empty() || documents.is_empty() {
+        return vec![0.; documents.len()];
+    }
+
+    let mut lengths = Vec::with_capacity(documents.len());
+    let mut term_counts = Vec::with_capacity(documents.len());
+    for document in documents {
+        let mut length = 0;
+        let mut counts = HashMap::<String, usize>::default();
+        for term in tokenize(document) {
+            length += 1;
+            if query_terms.contains(&term) {
+                *counts.entry(term).or_default() += 1;
+            }
+        }
+        lengths.push(length as f32);
+        term_counts.push(counts);
+    }
+
+    let document_count = documents.len() as f32;
+    let average_length = (lengths.iter().sum::<f32>() / document_count).max(1.);
+    let inverse_document_frequencies = query_terms
+        .iter()
+        .map(|term| {
+            let frequency = term_counts
+                .iter()
+                .filter(|counts| counts.contains_key(term))
+                .count() as f32;
+            let idf = (1. + (document_count - frequency + 0.5) / (frequency + 0.5)).ln();
+            (term, idf)
+        })
+        .collect::<HashMap<_, _>>();
+
+    term_counts
+        .iter()
+        .zip(lengths)
+        .map(|(counts, length)| {
+            counts
+                .iter()
+                .map(|(term, count)| {
+                    let count = *count as f32;
+                    inverse_document_frequencies[term] * count * (K1 + 1.)
+                        / (count + K1 * (1. - B + B * length / average_length))
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Splits text into lowercase words. Identifiers are also split into their
+/// parts, so that `fooBar` and `foo_bar` both match a query for `foo bar`.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| {
+            let parts = identifier_parts(word);
+            let whole = (parts.len() > 1).then(|| word.to_lowercase());
+            parts
+                .into_iter()
+                .map(|part| part.to_lowercase())
+                .chain(whole)
+        })
+}
+
+fn identifier_parts(word: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    for part in word.split('_').filter(|part| !part.is_empty()) {
+        let mut start = 0;
+        let mut previous: Option<char> = None;
+        for (ix, c) in part.char_indices() {
+            if let Some(previous) = previous {
+                if c.is_uppercase() && (previous.is_lowercase() || previous.is_numeric()) {
+                    parts.push(&part[start..ix]);
+                    start = ix;
+                }
+            }
+            previous = Some(c);
+        }
+        parts.push(&part[start..]);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("fn parseHttpRequest(raw_input: &str)").collect::<Vec<_>>(),
+            [
+                "fn",
+                "parse",
+                "http",
+                "request",
+                "parsehttprequest",
+                "raw",
+                "input",
+                "raw_input",
+                "str"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keyword_scores() {
+        let scores = keyword_scores(
+            "parse request",
+            &[
+                "fn parse_request(input: &str) -> Request { parse(input) }",
+                "fn render(view: &View) {}",
+                "// Requests are parsed lazily.",
+            ],
+        );
+        assert!(scores[0] > scores[2]);
+        assert_eq!(scores[1], 0.);
+    }
+
+    #[test]
+    fn test_hybrid_scores() {
+        // Keyword matches lift a chunk above one that is only slightly more similar.
+        let scores = hybrid_scores(
+            "open_buffer",
+            &[
+                ("fn load_file(path: &Path) {}", 0.81),
+                ("fn open_buffer(path: &Path) {}", 0.80),
+                ("struct Theme {}", 0.2),
+            ],
+        );
+        assert!(scores[1] > scores[0]);
+        assert!(scores[0] > scores[2]);
+
+        // Without any keyword matches, the order follows similarity.
+        let scores = hybrid_scores("xyz", &[("a", 0.5), ("b", 0.9)]);
+        assert!(scores[1] > scores[0]);
+    }
+
+    #[test]
+    fn test_path_filter() {
+        let filter = PathFilter {
+            files_to_include: PathMatcher::new(&["src/**".into()]).unwrap(),
+            files_to_exclude: PathMatcher::new(&["**/*.lock".into()]).unwrap(),
+        };
+        assert!(filter.matches("zed", Path::new("src/main.rs")));
+        assert!(!filter.matches("zed", Path::new("docs/main.md")));
+        assert!(!filter.matches("zed", Path::new("src/Cargo.lock")));
+
+        let filter = PathFilter {
+            files_to_include: PathMatcher::new(&["zed/docs/**".into()]).unwrap(),
+            files_to_exclude: PathMatcher::default(),
+        };
+        assert!(filter.matches("zed", Path::new("docs/main.md")));
+        assert!(!filter.matches("other", Path::new("docs/main.md")));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
     embedding::{EmbeddingProvider, TextToEmbed},
+    hybrid_search::{hybrid_scores, PathFilter},
     summary_index::FileSummary,
     worktree_index::{WorktreeIndex, WorktreeIndexHandle},
 };
@@ -25,6 +26,10 @@ use std::{
 };
 use util::ResultExt;
 
+/// How many of the chunks most similar to the query a hybrid search re-ranks
+/// for each result it returns.
+const HYBRID_SEARCH_CANDIDATES_PER_RESULT: usize = 10;
+
 #[derive(Debug)]
 pub struct SearchResult {
     pub worktree: Model<Worktree>,
@@ -233,12 +238,107 @@ impl ProjectIndex {
         queries: Vec<String>,
         limit: usize,
         cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        self.search_matching_paths(queries, limit, None, cx)
+    }
+
+    /// Searches for chunks that are similar to the query or contain its words,
+    /// in the files matching `path_filter`.
+    ///
+    /// The chunks most similar to the query are re-ranked by combining their
+    /// similarity with how well their words match the query. Only the files of
+    /// those candidates are read, rather than every indexed file.
+    pub fn hybrid_search(
+        &self,
+        query: String,
+        path_filter: PathFilter,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let path_filter = (!path_filter.is_empty()).then(|| Arc::new(path_filter));
+        let chunks = self.search_matching_paths(
+            vec![query.clone()],
+            limit.saturating_mul(HYBRID_SEARCH_CANDIDATES_PER_RESULT),
+            path_filter,
+            cx,
+        );
+        let fs = self.fs.clone();
+        cx.spawn(|cx| async move {
+            let mut chunks = chunks.await?;
+
+            let mut abs_paths = HashMap::<(EntityId, Arc<Path>), PathBuf>::default();
+            for chunk in &chunks {
+                let key = (chunk.worktree.entity_id(), chunk.path.clone());
+                if !abs_paths.contains_key(&key) {
+                    let abs_path = chunk
+                        .worktree
+                        .read_with(&cx, |worktree, _| worktree.abs_path().join(&chunk.path))?;
+                    abs_paths.insert(key, abs_path);
+                }
+            }
+            let file_contents =
+                futures::future::join_all(abs_paths.into_iter().map(|(key, abs_path)| {
+                    let fs = fs.clone();
+                    async move {
+                        let content = fs.load(&abs_path).await.log_err().unwrap_or_default();
+                        (key, content)
+                    }
+                }))
+                .await
+                .into_iter()
+                .collect::<HashMap<_, _>>();
+
+            let chunk_locations = chunks
+                .iter()
+                .map(|chunk| {
+                    (
+                        (chunk.worktree.entity_id(), chunk.path.clone()),
+                        chunk.range.clone(),
+                        chunk.score,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let scores = cx
+                .background_executor()
+                .spawn(async move {
+                    let chunks = chunk_locations
+                        .iter()
+                        .map(|(key, range, similarity)| {
+                            // The file may have changed since it was indexed, leaving
+                            // the chunk to be ranked by its similarity alone.
+                            let text = file_contents
+                                .get(key)
+                                .and_then(|content| content.get(range.clone()))
+                                .unwrap_or_default();
+                            (text, *similarity)
+                        })
+                        .collect::<Vec<_>>();
+                    hybrid_scores(&query, &chunks)
+                })
+                .await;
+            for (chunk, score) in chunks.iter_mut().zip(scores) {
+                chunk.score = score;
+            }
+            chunks
+                .sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+            chunks.truncate(limit);
+            Ok(chunks)
+        })
+    }
+
+    fn search_matching_paths(
+        &self,
+        queries: Vec<String>,
+        limit: usize,
+        path_filter: Option<Arc<PathFilter>>,
+        cx: &AppContext,
     ) -> Task<Result<Vec<SearchResult>>> {
         let (chunks_tx, chunks_rx) = channel::bounded(1024);
         let mut worktree_scan_tasks = Vec::new();
         for worktree_index in self.worktree_indices.values() {
             let worktree_index = worktree_index.clone();
             let chunks_tx = chunks_tx.clone();
+            let path_filter = path_filter.clone();
             worktree_scan_tasks.push(cx.spawn(|cx| async move {
                 let index = match worktree_index {
                     WorktreeIndexHandle::Loading { index } => {
@@ -249,7 +349,9 @@ impl ProjectIndex {
 
                 index
                     .read_with(&cx, |index, cx| {
-                        let worktree_id = index.worktree().read(cx).id();
+                        let worktree = index.worktree().read(cx);
+                        let worktree_id = worktree.id();
+                        let root_name = worktree.root_name().to_string();
                         let db_connection = index.db_connection().clone();
                         let db = *index.embedding_index().db();
                         cx.background_executor().spawn(async move {
@@ -259,6 +361,11 @@ impl ProjectIndex {
                             let db_entries = db.iter(&txn).context("failed to iterate database")?;
                             for db_entry in db_entries {
                                 let (_key, db_embedded_file) = db_entry?;
+                                if let Some(path_filter) = path_filter.as_ref() {
+                                    if !path_filter.matches(&root_name, &db_embedded_file.path) {
+                                        continue;
+                                    }
+                                }
                                 for chunk in db_embedded_file.chunks {
                                     chunks_tx
                                         .send((worktree_id, db_embedded_file.path.clone(), chunk))
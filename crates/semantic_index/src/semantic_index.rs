@@ -1,9 +1,11 @@
 mod chunking;
 mod embedding;
 mod embedding_index;
+mod hybrid_search;
 mod indexing;
 mod project_index;
 mod project_index_debug_view;
+mod semantic_index_settings;
 mod summary_backlog;
 mod summary_index;
 mod worktree_index;
@@ -14,6 +16,7 @@ use fs::Fs;
 use gpui::{AppContext, AsyncAppContext, BorrowAppContext, Context, Global, Model, WeakModel};
 use language::LineEnding;
 use project::{Project, Worktree};
+use settings::Settings as _;
 use std::{
     cmp::Ordering,
     path::{Path, PathBuf},
@@ -24,10 +27,16 @@ use util::ResultExt as _;
 use workspace::Workspace;
 
 pub use embedding::*;
+pub use hybrid_search::PathFilter;
 pub use project_index::{LoadedSearchResult, ProjectIndex, SearchResult, Status};
 pub use project_index_debug_view::ProjectIndexDebugView;
+pub use semantic_index_settings::{EmbeddingProviderSetting, SemanticIndexSettings};
 pub use summary_index::FileSummary;
 
+pub fn init(cx: &mut AppContext) {
+    SemanticIndexSettings::register(cx);
+}
+
 pub struct SemanticDb {
     embedding_provider: Arc<dyn EmbeddingProvider>,
     db_connection: Option<heed::Env>,
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+/// Settings for indexing projects for search by meaning.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SemanticIndexSettings {
+    /// Where text is turned into embeddings.
+    ///
+    /// Default: zed.dev
+    pub embedding_provider: EmbeddingProviderSetting,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingProviderSetting {
+    /// Embed text with Zed's hosted embedding service.
+    #[default]
+    #[serde(rename = "zed.dev")]
+    ZedDotDev,
+    /// Embed text on this machine's CPU, with a small model that is
+    /// downloaded once.
+    Local,
+}
+
+impl Settings for SemanticIndexSettings {
+    const KEY: Option<&'static str> = Some("semantic_index");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -1,11 +1,21 @@
+mod transform;
+
 use anyhow::{anyhow, Context, Result};
 use smallvec::SmallVec;
-use std::{collections::BTreeMap, ops::Range};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
+
+pub use crate::transform::Transform;
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Snippet {
     pub text: String,
     pub tabstops: Vec<TabStop>,
+    /// Ranges of the text that mirror a tabstop through a transform, such as
+    /// `${1/(.*)/${1:/upcase}/}`.
+    pub mirrors: Vec<Mirror>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -14,17 +24,78 @@ pub struct TabStop {
     pub choices: Option<Vec<String>>,
 }
 
+/// A range of a snippet's text that holds the transformed text of a tabstop, and
+/// that should be updated as the tabstop is edited.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mirror {
+    /// The index of the mirrored tabstop in [`Snippet::tabstops`].
+    pub tabstop: usize,
+    pub range: Range<isize>,
+    pub transform: Transform,
+}
+
+/// Provides the values of variables such as `$TM_FILENAME` when a snippet is parsed.
+pub trait VariableResolver {
+    /// Returns the value of the variable with the given name, or `None` if the
+    /// variable is unknown. Unknown variables are inserted as a placeholder
+    /// containing their name.
+    fn resolve(&self, name: &str) -> Option<String>;
+}
+
+impl<F: Fn(&str) -> Option<String>> VariableResolver for F {
+    fn resolve(&self, name: &str) -> Option<String> {
+        self(name)
+    }
+}
+
 impl Snippet {
+    /// Parses a snippet in which every variable is treated as unknown.
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
-            .context("failed to parse snippet")?;
+        Self::parse_with_variables(source, &|_: &str| None)
+    }
+
+    pub fn parse_with_variables(source: &str, variables: &dyn VariableResolver) -> Result<Self> {
+        let (nodes, _) = parse_nodes(source, false).context("failed to parse snippet")?;
+
+        let mut renderer = Renderer {
+            root: &nodes,
+            variables,
+            variable_values: HashMap::default(),
+            text: String::with_capacity(source.len()),
+            tabstops: BTreeMap::new(),
+            mirrors: Vec::new(),
+            next_variable_index: max_tabstop_index(&nodes) + 1,
+            variable_indices: HashMap::default(),
+        };
+        renderer.render(&nodes);
+        let Renderer {
+            text,
+            mut tabstops,
+            mirrors,
+            ..
+        } = renderer;
 
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
+        let indices = tabstops.keys().copied().collect::<Vec<_>>();
         let mut tabstops = tabstops.into_values().collect::<Vec<_>>();
 
+        let mirrors = mirrors
+            .into_iter()
+            .filter_map(|(index, range, transform)| {
+                let tabstop = if index == 0 {
+                    final_tabstop.as_ref().map(|_| tabstops.len())?
+                } else {
+                    indices.binary_search(&index).ok()?
+                };
+                Some(Mirror {
+                    tabstop,
+                    range,
+                    transform,
+                })
+            })
+            .collect();
+
         if let Some(final_tabstop) = final_tabstop {
             tabstops.push(final_tabstop);
         } else {
@@ -38,21 +109,232 @@ impl Snippet {
             }
         }
 
-        Ok(Snippet { text, tabstops })
+        Ok(Snippet {
+            text,
+            tabstops,
+            mirrors,
+        })
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Tabstop {
+        index: usize,
+        placeholder: Option<Vec<Node>>,
+        choices: Option<Vec<String>>,
+        transform: Option<Transform>,
+    },
+    Variable {
+        name: String,
+        default: Option<Vec<Node>>,
+        transform: Option<Transform>,
+    },
+}
+
+fn max_tabstop_index(nodes: &[Node]) -> usize {
+    nodes
+        .iter()
+        .map(|node| match node {
+            Node::Text(_) => 0,
+            Node::Tabstop {
+                index, placeholder, ..
+            } => (*index).max(placeholder.as_deref().map_or(0, max_tabstop_index)),
+            Node::Variable { default, .. } => default.as_deref().map_or(0, max_tabstop_index),
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+struct Renderer<'a> {
+    root: &'a [Node],
+    variables: &'a dyn VariableResolver,
+    /// Variables are resolved once, so that values such as `$UUID` are the same
+    /// everywhere they appear.
+    variable_values: HashMap<&'a str, Option<String>>,
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    mirrors: Vec<(usize, Range<isize>, Transform)>,
+    /// Unknown variables become tabstops numbered after the snippet's own.
+    next_variable_index: usize,
+    variable_indices: HashMap<&'a str, usize>,
+}
+
+impl<'a> Renderer<'a> {
+    fn render(&mut self, nodes: &'a [Node]) {
+        for node in nodes {
+            let start = self.text.len() as isize;
+            match node {
+                Node::Text(text) => self.text.push_str(text),
+                Node::Tabstop {
+                    index,
+                    transform: Some(transform),
+                    ..
+                } => {
+                    let value = self.tabstop_text(*index, &mut Vec::new());
+                    self.text.push_str(&transform.apply(&value));
+                    let end = self.text.len() as isize;
+                    self.mirrors.push((*index, start..end, transform.clone()));
+                }
+                Node::Tabstop {
+                    index,
+                    placeholder,
+                    choices,
+                    transform: None,
+                } => {
+                    match placeholder {
+                        Some(placeholder) => self.render(placeholder),
+                        None => {
+                            let text = self.tabstop_text(*index, &mut Vec::new());
+                            self.text.push_str(&text);
+                        }
+                    }
+                    self.push_tabstop_range(*index, start, choices);
+                }
+                Node::Variable {
+                    name,
+                    default,
+                    transform,
+                } => match self.variable_value(name) {
+                    Some(value) if !value.is_empty() => {
+                        let value = match transform {
+                            Some(transform) => transform.apply(&value),
+                            None => value,
+                        };
+                        self.text.push_str(&value);
+                    }
+                    _ if default.is_some() => self.render(default.as_deref().unwrap_or_default()),
+                    Some(_) => {}
+                    None => {
+                        let next_index = &mut self.next_variable_index;
+                        let index = *self.variable_indices.entry(name).or_insert_with(|| {
+                            *next_index += 1;
+                            *next_index - 1
+                        });
+                        self.text.push_str(name);
+                        self.push_tabstop_range(index, start, &None);
+                    }
+                },
+            }
+        }
+    }
+
+    fn push_tabstop_range(&mut self, index: usize, start: isize, choices: &Option<Vec<String>>) {
+        let end = self.text.len() as isize;
+        let tabstop = self.tabstops.entry(index).or_default();
+        tabstop.ranges.push(start..end);
+        if tabstop.choices.is_none() {
+            tabstop.choices = choices.clone();
+        }
+    }
+
+    fn variable_value(&mut self, name: &'a str) -> Option<String> {
+        let variables = self.variables;
+        self.variable_values
+            .entry(name)
+            .or_insert_with(|| variables.resolve(name))
+            .clone()
+    }
+
+    /// Returns the text of the first placeholder for the given tabstop, which is the
+    /// initial text of every other occurrence of the tabstop.
+    fn tabstop_text(&mut self, index: usize, visiting: &mut Vec<usize>) -> String {
+        if visiting.contains(&index) {
+            return String::new();
+        }
+        let Some(placeholder) = find_placeholder(self.root, index) else {
+            return String::new();
+        };
+        visiting.push(index);
+        let text = self.plain_text(placeholder, visiting);
+        visiting.pop();
+        text
+    }
+
+    /// Renders nodes without recording tabstops.
+    fn plain_text(&mut self, nodes: &'a [Node], visiting: &mut Vec<usize>) -> String {
+        let mut text = String::new();
+        for node in nodes {
+            match node {
+                Node::Text(chunk) => text.push_str(chunk),
+                Node::Tabstop {
+                    index,
+                    placeholder,
+                    transform,
+                    ..
+                } => {
+                    let value = match (placeholder, transform) {
+                        (Some(placeholder), None) => self.plain_text(placeholder, visiting),
+                        _ => self.tabstop_text(*index, visiting),
+                    };
+                    match transform {
+                        Some(transform) => text.push_str(&transform.apply(&value)),
+                        None => text.push_str(&value),
+                    }
+                }
+                Node::Variable {
+                    name,
+                    default,
+                    transform,
+                } => match self.variable_value(name) {
+                    Some(value) if !value.is_empty() => match transform {
+                        Some(transform) => text.push_str(&transform.apply(&value)),
+                        None => text.push_str(&value),
+                    },
+                    _ if default.is_some() => {
+                        let default =
+                            self.plain_text(default.as_deref().unwrap_or_default(), visiting);
+                        text.push_str(&default);
+                    }
+                    Some(_) => {}
+                    None => text.push_str(name),
+                },
+            }
+        }
+        text
     }
 }
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
+fn find_placeholder(nodes: &[Node], target: usize) -> Option<&[Node]> {
+    nodes.iter().find_map(|node| match node {
+        Node::Text(_) => None,
+        Node::Tabstop {
+            index,
+            placeholder: Some(placeholder),
+            transform: None,
+            ..
+        } => {
+            if *index == target {
+                Some(placeholder.as_slice())
+            } else {
+                find_placeholder(placeholder, target)
+            }
+        }
+        Node::Tabstop { .. } => None,
+        Node::Variable { default, .. } => find_placeholder(default.as_deref()?, target),
+    })
+}
+
+fn parse_nodes(mut source: &str, nested: bool) -> Result<(Vec<Node>, &str)> {
+    let mut nodes = Vec::new();
+    let mut text = String::new();
     loop {
         match source.chars().next() {
-            None => return Ok(""),
+            None => break,
             Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+                let (node, rest) = parse_dollar(&source[1..])?;
+                source = rest;
+                match node {
+                    Some(node) => {
+                        if !text.is_empty() {
+                            nodes.push(Node::Text(std::mem::take(&mut text)));
+                        }
+                        nodes.push(node);
+                    }
+                    // A `$` that doesn't start a tabstop or variable is literal text.
+                    None => text.push('$'),
+                }
             }
             Some('\\') => {
                 // As specified in the LSP spec (`Grammar` section),
@@ -73,7 +355,7 @@ fn parse_snippet<'a>(
             }
             Some('}') => {
                 if nested {
-                    return Ok(source);
+                    break;
                 } else {
                     text.push('}');
                     source = &source[1..];
@@ -87,50 +369,113 @@ fn parse_snippet<'a>(
             }
         }
     }
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+    Ok((nodes, source))
 }
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
-    let tabstop_index;
+/// Parses the tabstop or variable following a `$`, returning `None` if the `$` is
+/// followed by neither.
+fn parse_dollar(source: &str) -> Result<(Option<Node>, &str)> {
+    if let Some(source) = source.strip_prefix('{') {
+        let (node, source) = if source.starts_with(|c: char| c.is_ascii_digit()) {
+            parse_braced_tabstop(source)?
+        } else if let Some((name, source)) = parse_variable_name(source) {
+            parse_braced_variable(name, source)?
+        } else {
+            return Err(anyhow!("expected an integer or a variable name"));
+        };
+        let source = source
+            .strip_prefix('}')
+            .ok_or_else(|| anyhow!("expected a closing brace"))?;
+        Ok((Some(node), source))
+    } else if source.starts_with(|c: char| c.is_ascii_digit()) {
+        let (index, source) = parse_int(source)?;
+        let node = Node::Tabstop {
+            index,
+            placeholder: None,
+            choices: None,
+            transform: None,
+        };
+        Ok((Some(node), source))
+    } else if let Some((name, source)) = parse_variable_name(source) {
+        let node = Node::Variable {
+            name: name.to_string(),
+            default: None,
+            transform: None,
+        };
+        Ok((Some(node), source))
+    } else {
+        Ok((None, source))
+    }
+}
+
+fn parse_braced_tabstop(source: &str) -> Result<(Node, &str)> {
+    let (index, mut source) = parse_int(source)?;
+    let mut placeholder = None;
     let mut choices = None;
+    let mut transform = None;
 
-    if source.starts_with('{') {
-        let (index, rest) = parse_int(&source[1..])?;
-        tabstop_index = index;
+    if let Some(rest) = source.strip_prefix('|') {
+        let (rest, parsed_choices) = parse_choices(rest)?;
         source = rest;
+        placeholder = Some(
+            parsed_choices
+                .first()
+                .map(|choice| Node::Text(choice.clone()))
+                .into_iter()
+                .collect(),
+        );
+        choices = Some(parsed_choices);
+    } else if let Some(rest) = source.strip_prefix(':') {
+        let (nodes, rest) = parse_nodes(rest, true)?;
+        source = rest;
+        placeholder = Some(nodes);
+    } else if let Some(rest) = source.strip_prefix('/') {
+        let (parsed_transform, rest) = Transform::parse(rest)?;
+        source = rest;
+        transform = Some(parsed_transform);
+    }
 
-        if source.starts_with("|") {
-            (source, choices) = parse_choices(&source[1..], text)?;
-        }
-
-        if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
-        }
+    let node = Node::Tabstop {
+        index,
+        placeholder,
+        choices,
+        transform,
+    };
+    Ok((node, source))
+}
 
-        if source.starts_with('}') {
-            source = &source[1..];
-        } else {
-            return Err(anyhow!("expected a closing brace"));
-        }
-    } else {
-        let (index, rest) = parse_int(source)?;
-        tabstop_index = index;
+fn parse_braced_variable<'a>(name: &str, mut source: &'a str) -> Result<(Node, &'a str)> {
+    let mut default = None;
+    let mut transform = None;
+    if let Some(rest) = source.strip_prefix(':') {
+        let (nodes, rest) = parse_nodes(rest, true)?;
+        source = rest;
+        default = Some(nodes);
+    } else if let Some(rest) = source.strip_prefix('/') {
+        let (parsed_transform, rest) = Transform::parse(rest)?;
         source = rest;
+        transform = Some(parsed_transform);
     }
 
-    tabstops
-        .entry(tabstop_index)
-        .or_insert_with(|| TabStop {
-            ranges: Default::default(),
-            choices,
-        })
-        .ranges
-        .push(tabstop_start as isize..text.len() as isize);
-    Ok(source)
+    let node = Node::Variable {
+        name: name.to_string(),
+        default,
+        transform,
+    };
+    Ok((node, source))
+}
+
+fn parse_variable_name(source: &str) -> Option<(&str, &str)> {
+    if !source.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let len = source
+        .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+        .unwrap_or(source.len());
+    Some(source.split_at(len))
 }
 
 fn parse_int(source: &str) -> Result<(usize, &str)> {
@@ -144,30 +489,22 @@ fn parse_int(source: &str) -> Result<(usize, &str)> {
     Ok((prefix.parse()?, suffix))
 }
 
-fn parse_choices<'a>(
-    mut source: &'a str,
-    text: &mut String,
-) -> Result<(&'a str, Option<Vec<String>>)> {
-    let mut found_default_choice = false;
+fn parse_choices(mut source: &str) -> Result<(&str, Vec<String>)> {
     let mut current_choice = String::new();
     let mut choices = Vec::new();
 
     loop {
         match source.chars().next() {
-            None => return Ok(("", Some(choices))),
+            None => return Ok(("", choices)),
             Some('\\') => {
                 source = &source[1..];
 
                 if let Some(c) = source.chars().next() {
-                    if !found_default_choice {
-                        current_choice.push(c);
-                        text.push(c);
-                    }
+                    current_choice.push(c);
                     source = &source[c.len_utf8()..];
                 }
             }
             Some(',') => {
-                found_default_choice = true;
                 source = &source[1..];
                 choices.push(current_choice);
                 current_choice = String::new();
@@ -175,7 +512,7 @@ fn parse_choices<'a>(
             Some('|') => {
                 source = &source[1..];
                 choices.push(current_choice);
-                return Ok((source, Some(choices)));
+                return Ok((source, choices));
             }
             Some(_) => {
                 let chunk_end = source.find([',', '|', '\\']);
@@ -187,11 +524,6 @@ fn parse_choices<'a>(
                 }
 
                 let (chunk, rest) = source.split_at(chunk_end.unwrap());
-
-                if !found_default_choice {
-                    text.push_str(chunk);
-                }
-
                 current_choice.push_str(chunk);
                 source = rest;
             }
@@ -327,6 +659,59 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![4..4], vec![7..7]]);
     }
 
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "TM_SELECTED_TEXT" => Some(String::new()),
+            _ => None,
+        };
+
+        let snippet =
+            Snippet::parse_with_variables("// $TM_FILENAME: ${1:todo}", &variables).unwrap();
+        assert_eq!(snippet.text, "// main.rs: todo");
+        assert_eq!(tabstops(&snippet), &[vec![12..16], vec![16..16]]);
+
+        // Empty variables use their default, which can contain tabstops.
+        let snippet =
+            Snippet::parse_with_variables("(${TM_SELECTED_TEXT:${1:value}})", &variables).unwrap();
+        assert_eq!(snippet.text, "(value)");
+        assert_eq!(tabstops(&snippet), &[vec![1..6], vec![7..7]]);
+
+        // Unknown variables are inserted as placeholders after the other tabstops.
+        let snippet =
+            Snippet::parse_with_variables("$UNKNOWN = ${1:a}; $UNKNOWN", &variables).unwrap();
+        assert_eq!(snippet.text, "UNKNOWN = a; UNKNOWN");
+        assert_eq!(
+            tabstops(&snippet),
+            &[vec![10..11], vec![0..7, 13..20], vec![20..20]]
+        );
+
+        let snippet =
+            Snippet::parse_with_variables("${TM_FILENAME/(.*)\\.rs/$1/}", &variables).unwrap();
+        assert_eq!(snippet.text, "main");
+    }
+
+    #[test]
+    fn test_snippet_with_transforms() {
+        let snippet = Snippet::parse("${1:name} ${1/(.*)/${1:/upcase}/} $1$0").unwrap();
+        assert_eq!(snippet.text, "name NAME name");
+        assert_eq!(tabstops(&snippet), &[vec![0..4, 10..14], vec![14..14]]);
+        assert_eq!(snippet.mirrors.len(), 1);
+        assert_eq!(snippet.mirrors[0].tabstop, 0);
+        assert_eq!(snippet.mirrors[0].range, 5..9);
+        assert_eq!(snippet.mirrors[0].transform.apply("other"), "OTHER");
+
+        assert!(Snippet::parse("${1/(.*)/$1").is_err());
+    }
+
+    #[test]
+    fn test_snippet_with_literal_dollar_signs() {
+        let snippet = Snippet::parse("cost: $ 5, ${1:x}$").unwrap();
+        assert_eq!(snippet.text, "cost: $ 5, x$");
+        assert_eq!(tabstops(&snippet), &[vec![11..12], vec![13..13]]);
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.ranges.to_vec()).collect()
     }
@@ -0,0 +1,388 @@
+use anyhow::{anyhow, Context as _, Result};
+use regex::{Captures, Regex, RegexBuilder};
+
+/// A regular expression replacement applied to the text of a tabstop or variable,
+/// written as `/regex/format/options`.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum FormatItem {
+    Text(String),
+    Group(usize),
+    CaseChange(usize, CaseChange),
+    /// Inserts `if_matched`, or the group itself when it's `None`, if the group
+    /// matched, and `otherwise` if it didn't.
+    Conditional {
+        group: usize,
+        if_matched: Option<String>,
+        otherwise: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CaseChange {
+    Upcase,
+    Downcase,
+    Capitalize,
+    CamelCase,
+    PascalCase,
+}
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.format == other.format
+            && self.global == other.global
+    }
+}
+
+impl Transform {
+    /// Parses a transform following its opening `/`, returning the rest of the source
+    /// after the options.
+    pub(crate) fn parse(source: &str) -> Result<(Self, &str)> {
+        let (pattern, source) = parse_regex(source)?;
+        let (format, source) = parse_format(source)?;
+        let options_len = source.find('}').unwrap_or(source.len());
+        let (options, source) = source.split_at(options_len);
+
+        let mut builder = RegexBuilder::new(&pattern);
+        let mut global = false;
+        for option in options.chars() {
+            match option {
+                'g' => global = true,
+                'i' => {
+                    builder.case_insensitive(true);
+                }
+                'm' => {
+                    builder.multi_line(true);
+                }
+                's' => {
+                    builder.dot_matches_new_line(true);
+                }
+                // Options of JavaScript regular expressions that don't affect matching here.
+                'u' | 'y' | 'd' => {}
+                _ => return Err(anyhow!("invalid transform option {option:?}")),
+            }
+        }
+        let regex = builder
+            .build()
+            .with_context(|| format!("invalid transform regex {pattern:?}"))?;
+
+        Ok((
+            Self {
+                regex,
+                format,
+                global,
+            },
+            source,
+        ))
+    }
+
+    /// Replaces the first match of the regex in the input, or every match if the
+    /// transform has the `g` option, with the format.
+    pub fn apply(&self, input: &str) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut last_end = 0;
+        for captures in self.regex.captures_iter(input) {
+            let Some(matched) = captures.get(0) else {
+                continue;
+            };
+            output.push_str(&input[last_end..matched.start()]);
+            self.format_into(&captures, &mut output);
+            last_end = matched.end();
+            if !self.global {
+                break;
+            }
+        }
+        output.push_str(&input[last_end..]);
+        output
+    }
+
+    fn format_into(&self, captures: &Captures, output: &mut String) {
+        let group = |index: usize| captures.get(index).map(|group| group.as_str());
+        for item in &self.format {
+            match item {
+                FormatItem::Text(text) => output.push_str(text),
+                FormatItem::Group(index) => output.push_str(group(*index).unwrap_or_default()),
+                FormatItem::CaseChange(index, case_change) => {
+                    output.push_str(&case_change.apply(group(*index).unwrap_or_default()))
+                }
+                FormatItem::Conditional {
+                    group: index,
+                    if_matched,
+                    otherwise,
+                } => match group(*index).filter(|text| !text.is_empty()) {
+                    Some(text) => output.push_str(if_matched.as_deref().unwrap_or(text)),
+                    None => output.push_str(otherwise),
+                },
+            }
+        }
+    }
+}
+
+impl CaseChange {
+    fn apply(&self, text: &str) -> String {
+        match self {
+            CaseChange::Upcase => text.to_uppercase(),
+            CaseChange::Downcase => text.to_lowercase(),
+            CaseChange::Capitalize => capitalize(text),
+            CaseChange::CamelCase => words(text)
+                .enumerate()
+                .map(|(ix, word)| {
+                    if ix == 0 {
+                        let mut chars = word.chars();
+                        chars
+                            .next()
+                            .map(|first| first.to_lowercase().chain(chars).collect())
+                            .unwrap_or_default()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            CaseChange::PascalCase => words(text).map(capitalize).collect(),
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+/// Parses the regex up to the next unescaped `/`. Escaped slashes are unescaped and
+/// all other escapes are left for the regex.
+fn parse_regex(mut source: &str) -> Result<(String, &str)> {
+    let mut pattern = String::new();
+    loop {
+        let end = source
+            .find(['/', '\\'])
+            .ok_or_else(|| anyhow!("unterminated transform regex"))?;
+        pattern.push_str(&source[..end]);
+        source = &source[end..];
+        if let Some(rest) = source.strip_prefix('/') {
+            return Ok((pattern, rest));
+        }
+
+        let mut chars = source[1..].chars();
+        match chars.next() {
+            Some('/') => pattern.push('/'),
+            Some(c) => {
+                pattern.push('\\');
+                pattern.push(c);
+            }
+            None => return Err(anyhow!("unterminated transform regex")),
+        }
+        source = chars.as_str();
+    }
+}
+
+/// Parses the format up to the next unescaped `/`.
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut items = Vec::new();
+    let mut text = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("unterminated transform format")),
+            Some('/') => {
+                source = chars.as_str();
+                break;
+            }
+            Some('\\') => {
+                match chars.next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(c @ ('/' | '\\' | '$')) => text.push(c),
+                    Some(c) => {
+                        text.push('\\');
+                        text.push(c);
+                    }
+                    None => return Err(anyhow!("unterminated transform format")),
+                }
+                source = chars.as_str();
+            }
+            Some('$') => {
+                let (item, rest) = parse_format_item(chars.as_str())?;
+                source = rest;
+                match item {
+                    Some(item) => {
+                        if !text.is_empty() {
+                            items.push(FormatItem::Text(std::mem::take(&mut text)));
+                        }
+                        items.push(item);
+                    }
+                    None => text.push('$'),
+                }
+            }
+            Some(_) => {
+                let end = source.find(['/', '\\', '$']).unwrap_or(source.len());
+                text.push_str(&source[..end]);
+                source = &source[end..];
+            }
+        }
+    }
+    if !text.is_empty() {
+        items.push(FormatItem::Text(text));
+    }
+    Ok((items, source))
+}
+
+/// Parses the format item following a `$`, returning `None` if the `$` is literal.
+fn parse_format_item(source: &str) -> Result<(Option<FormatItem>, &str)> {
+    if source.starts_with(|c: char| c.is_ascii_digit()) {
+        let (group, source) = parse_group(source)?;
+        return Ok((Some(FormatItem::Group(group)), source));
+    }
+    let Some(source) = source.strip_prefix('{') else {
+        return Ok((None, source));
+    };
+
+    let (group, source) = parse_group(source)?;
+    if let Some(source) = source.strip_prefix('}') {
+        return Ok((Some(FormatItem::Group(group)), source));
+    }
+    let source = source
+        .strip_prefix(':')
+        .ok_or_else(|| anyhow!("expected a closing brace in transform format"))?;
+
+    let (item, source) = if let Some(source) = source.strip_prefix('/') {
+        let end = source.find('}').unwrap_or(source.len());
+        let case_change = match &source[..end] {
+            "upcase" => CaseChange::Upcase,
+            "downcase" => CaseChange::Downcase,
+            "capitalize" => CaseChange::Capitalize,
+            "camelcase" => CaseChange::CamelCase,
+            "pascalcase" => CaseChange::PascalCase,
+            other => return Err(anyhow!("unknown case change {other:?}")),
+        };
+        (FormatItem::CaseChange(group, case_change), &source[end..])
+    } else if let Some(source) = source.strip_prefix('+') {
+        let (if_matched, source) = parse_conditional_text(source, &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_matched: Some(if_matched),
+            otherwise: String::new(),
+        };
+        (item, source)
+    } else if let Some(source) = source.strip_prefix('?') {
+        let (if_matched, source) = parse_conditional_text(source, &[':'])?;
+        let (otherwise, source) = parse_conditional_text(&source[1..], &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_matched: Some(if_matched),
+            otherwise,
+        };
+        (item, source)
+    } else {
+        let source = source.strip_prefix('-').unwrap_or(source);
+        let (otherwise, source) = parse_conditional_text(source, &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_matched: None,
+            otherwise,
+        };
+        (item, source)
+    };
+
+    let source = source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace in transform format"))?;
+    Ok((Some(item), source))
+}
+
+fn parse_group(source: &str) -> Result<(usize, &str)> {
+    let len = source
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(source.len());
+    let (digits, rest) = source.split_at(len);
+    Ok((digits.parse().context("expected a group number")?, rest))
+}
+
+/// Parses the text of a conditional format item up to one of the terminators, which
+/// can be escaped with a backslash.
+fn parse_conditional_text<'a>(
+    mut source: &'a str,
+    terminators: &[char],
+) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    loop {
+        let mut chars = source.chars();
+        match chars.next() {
+            None => return Err(anyhow!("unterminated transform format")),
+            Some(c) if terminators.contains(&c) => return Ok((text, source)),
+            Some('\\') => {
+                if let Some(c) = chars.next() {
+                    text.push(c);
+                }
+                source = chars.as_str();
+            }
+            Some(c) => {
+                text.push(c);
+                source = chars.as_str();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(source: &str) -> Transform {
+        let (transform, rest) = Transform::parse(source).unwrap();
+        assert_eq!(rest, "", "unparsed transform source");
+        transform
+    }
+
+    #[test]
+    fn test_transforms() {
+        assert_eq!(transform("(.*)/${1:/upcase}/").apply("hello"), "HELLO");
+        assert_eq!(
+            transform("(.*)\\..+$/$1/").apply("component.test.tsx"),
+            "component.test"
+        );
+        assert_eq!(transform("a/b/").apply("banana"), "bbnana");
+        assert_eq!(transform("a/b/g").apply("banana"), "bbnbnb");
+        assert_eq!(transform("A/b/gi").apply("banana"), "bbnbnb");
+        assert_eq!(transform("x\\/y/z/").apply("ax/yb"), "azb");
+        assert_eq!(
+            transform("(.*)/${1:/pascalcase}/").apply("my-component_name"),
+            "MyComponentName"
+        );
+        assert_eq!(
+            transform("(.*)/${1:/camelcase}/").apply("My component name"),
+            "myComponentName"
+        );
+        assert_eq!(transform("^(.)/${1:/capitalize}/").apply("word"), "Word");
+    }
+
+    #[test]
+    fn test_conditional_formats() {
+        let transform = transform("(foo)?(bar)?/${1:+FOO}${2:?BAR:NO-BAR}${1:-none}/");
+        assert_eq!(transform.apply("foo"), "FOONO-BARfoo");
+        assert_eq!(transform.apply("bar"), "BARnone");
+        assert_eq!(transform.apply("foobar"), "FOOBARfoo");
+    }
+
+    #[test]
+    fn test_invalid_transforms() {
+        assert!(Transform::parse("(.*)/$1").is_err());
+        assert!(Transform::parse("(/$1/").is_err());
+        assert!(Transform::parse("(.*)/${1:/shout}/").is_err());
+        assert!(Transform::parse("(.*)/$1/x").is_err());
+    }
+}
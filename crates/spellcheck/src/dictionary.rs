@@ -0,0 +1,587 @@
+use anyhow::{anyhow, Context as _, Result};
+use collections::{HashMap, HashSet};
+
+/// The maximum number of suggestions offered for a misspelled word.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A set of correctly-spelled words, built from one or more Hunspell-style
+/// `.dic` files and their accompanying `.aff` affix files.
+///
+/// Only the parts of the affix format that are needed to expand the word list
+/// are supported: `PFX` and `SFX` rules (including cross products), the `FLAG`
+/// encodings and the `TRY` alphabet used when generating suggestions.
+#[derive(Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    alphabet: Vec<char>,
+}
+
+impl Dictionary {
+    /// Builds a dictionary from the contents of a `.dic` file and, optionally,
+    /// the contents of its `.aff` file.
+    pub fn from_hunspell(dic: &str, aff: Option<&str>) -> Result<Self> {
+        let affixes = match aff {
+            Some(aff) => Affixes::parse(aff).context("parsing affix file")?,
+            None => Affixes::default(),
+        };
+        let mut dictionary = Self::default();
+        dictionary.extend_hunspell(dic, &affixes);
+        dictionary.alphabet = affixes.alphabet;
+        Ok(dictionary)
+    }
+
+    /// Builds a dictionary from a plain list of words, one per line.
+    pub fn from_word_list(text: &str) -> Self {
+        let mut dictionary = Self::default();
+        dictionary.extend_word_list(text);
+        dictionary
+    }
+
+    /// Adds the words of a plain word list, one per line, to this dictionary.
+    pub fn extend_word_list(&mut self, text: &str) {
+        self.words.extend(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+
+    /// Merges the words of another dictionary into this one.
+    pub fn merge(&mut self, other: Dictionary) {
+        self.words.extend(other.words);
+        for char in other.alphabet {
+            if !self.alphabet.contains(&char) {
+                self.alphabet.push(char);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Whether the given word is spelled correctly.
+    ///
+    /// Lowercase dictionary entries also accept capitalized and all-uppercase
+    /// spellings, while capitalized entries (such as proper nouns) must be capitalized.
+    pub fn contains(&self, word: &str) -> bool {
+        if self.words.contains(word) {
+            return true;
+        }
+
+        match Case::of(word) {
+            Case::Lower | Case::Mixed => false,
+            Case::Title => self.words.contains(&word.to_lowercase()),
+            Case::Upper => {
+                self.words.contains(&word.to_lowercase())
+                    || self
+                        .words
+                        .contains(&Case::Title.apply(&word.to_lowercase()))
+            }
+        }
+    }
+
+    /// Returns correctly-spelled words that are a small number of edits away from
+    /// the given word, closest first, in the same case as the given word.
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let case = Case::of(word);
+        let word = word.to_lowercase();
+        let alphabet = if self.alphabet.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            self.alphabet.clone()
+        };
+
+        let mut suggestions = Vec::new();
+        let mut seen = HashSet::default();
+        let first_edits = edits(&word, &alphabet);
+        for candidate in &first_edits {
+            if self.contains(candidate) && seen.insert(candidate.clone()) {
+                suggestions.push(case.apply(candidate));
+            }
+        }
+
+        // Only fall back to words two edits away when there are no closer ones.
+        if suggestions.is_empty() {
+            'outer: for first_edit in &first_edits {
+                for candidate in edits(first_edit, &alphabet) {
+                    if candidate != word
+                        && self.contains(&candidate)
+                        && seen.insert(candidate.clone())
+                    {
+                        suggestions.push(case.apply(&candidate));
+                        if suggestions.len() >= MAX_SUGGESTIONS {
+                            break 'outer;
+                        }
+                    }
+                }
+            }
+        }
+
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+
+    fn extend_hunspell(&mut self, dic: &str, affixes: &Affixes) {
+        let mut lines = dic.lines();
+        // The first line of a `.dic` file holds an approximate word count.
+        if let Some(first_line) = lines.clone().next() {
+            if first_line.trim().parse::<usize>().is_ok() {
+                lines.next();
+            }
+        }
+
+        for line in lines {
+            // Morphological fields follow the word after whitespace.
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => (word, affixes.flag_format.parse(flags)),
+                None => (entry, Vec::new()),
+            };
+            if word.is_empty() {
+                continue;
+            }
+
+            self.words.insert(word.to_string());
+            let prefixes = flags
+                .iter()
+                .filter_map(|flag| affixes.prefixes.get(flag))
+                .collect::<Vec<_>>();
+            let suffixes = flags
+                .iter()
+                .filter_map(|flag| affixes.suffixes.get(flag))
+                .collect::<Vec<_>>();
+
+            for suffix in &suffixes {
+                for rule in &suffix.rules {
+                    let Some(suffixed) = rule.apply_suffix(word) else {
+                        continue;
+                    };
+                    if suffix.cross_product {
+                        for prefix in prefixes.iter().filter(|prefix| prefix.cross_product) {
+                            for prefix_rule in &prefix.rules {
+                                if let Some(both) = prefix_rule.apply_prefix(&suffixed) {
+                                    self.words.insert(both);
+                                }
+                            }
+                        }
+                    }
+                    self.words.insert(suffixed);
+                }
+            }
+
+            for prefix in &prefixes {
+                for rule in &prefix.rules {
+                    if let Some(prefixed) = rule.apply_prefix(word) {
+                        self.words.insert(prefixed);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// All strings one deletion, transposition, replacement or insertion away from `word`.
+fn edits(word: &str, alphabet: &[char]) -> Vec<String> {
+    let chars = word.chars().collect::<Vec<_>>();
+    let mut edits = Vec::new();
+    for ix in 0..chars.len() {
+        let mut deleted = chars.clone();
+        deleted.remove(ix);
+        edits.push(deleted.into_iter().collect());
+    }
+    for ix in 0..chars.len().saturating_sub(1) {
+        let mut transposed = chars.clone();
+        transposed.swap(ix, ix + 1);
+        edits.push(transposed.into_iter().collect());
+    }
+    for ix in 0..chars.len() {
+        for &char in alphabet {
+            if chars[ix] != char {
+                let mut replaced = chars.clone();
+                replaced[ix] = char;
+                edits.push(replaced.into_iter().collect());
+            }
+        }
+    }
+    for ix in 0..=chars.len() {
+        for &char in alphabet {
+            let mut inserted = chars.clone();
+            inserted.insert(ix, char);
+            edits.push(inserted.into_iter().collect());
+        }
+    }
+    edits
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Case {
+    Lower,
+    Title,
+    Upper,
+    Mixed,
+}
+
+impl Case {
+    fn of(word: &str) -> Self {
+        let mut chars = word.chars().filter(|char| char.is_alphabetic());
+        let Some(first) = chars.next() else {
+            return Case::Lower;
+        };
+        let rest = chars.collect::<Vec<_>>();
+        if first.is_lowercase() {
+            if rest.iter().all(|char| char.is_lowercase()) {
+                Case::Lower
+            } else {
+                Case::Mixed
+            }
+        } else if rest.iter().all(|char| char.is_lowercase()) {
+            Case::Title
+        } else if rest.iter().all(|char| char.is_uppercase()) {
+            Case::Upper
+        } else {
+            Case::Mixed
+        }
+    }
+
+    fn apply(self, word: &str) -> String {
+        match self {
+            Case::Lower | Case::Mixed => word.to_string(),
+            Case::Upper => word.to_uppercase(),
+            Case::Title => {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+enum FlagFormat {
+    /// Each flag is a single character.
+    #[default]
+    Short,
+    /// Each flag is two characters (`FLAG long`).
+    Long,
+    /// Flags are comma-separated decimal numbers (`FLAG num`).
+    Numeric,
+    /// Each flag is a single UTF-8 character (`FLAG UTF-8`).
+    Utf8,
+}
+
+impl FlagFormat {
+    fn parse(self, flags: &str) -> Vec<String> {
+        match self {
+            FlagFormat::Short | FlagFormat::Utf8 => {
+                flags.chars().map(|char| char.to_string()).collect()
+            }
+            FlagFormat::Long => flags
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|chunk| chunk.iter().collect())
+                .collect(),
+            FlagFormat::Numeric => flags
+                .split(',')
+                .map(|flag| flag.trim().to_string())
+                .filter(|flag| !flag.is_empty())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Affixes {
+    flag_format: FlagFormat,
+    alphabet: Vec<char>,
+    prefixes: HashMap<String, AffixClass>,
+    suffixes: HashMap<String, AffixClass>,
+}
+
+struct AffixClass {
+    cross_product: bool,
+    rules: Vec<AffixRule>,
+}
+
+struct AffixRule {
+    strip: String,
+    add: String,
+    condition: Condition,
+}
+
+impl Affixes {
+    fn parse(aff: &str) -> Result<Self> {
+        let mut affixes = Affixes::default();
+        for (row, line) in aff.lines().enumerate() {
+            let mut fields = line.split_whitespace();
+            let Some(keyword) = fields.next() else {
+                continue;
+            };
+            match keyword {
+                "FLAG" => {
+                    affixes.flag_format = match fields.next() {
+                        Some("long") => FlagFormat::Long,
+                        Some("num") => FlagFormat::Numeric,
+                        Some("UTF-8") => FlagFormat::Utf8,
+                        _ => FlagFormat::Short,
+                    };
+                }
+                "TRY" => {
+                    affixes.alphabet = fields
+                        .next()
+                        .unwrap_or_default()
+                        .chars()
+                        .flat_map(char::to_lowercase)
+                        .fold(Vec::new(), |mut alphabet, char| {
+                            if !alphabet.contains(&char) {
+                                alphabet.push(char);
+                            }
+                            alphabet
+                        });
+                }
+                "PFX" | "SFX" => {
+                    let fields = fields.collect::<Vec<_>>();
+                    let classes = if keyword == "PFX" {
+                        &mut affixes.prefixes
+                    } else {
+                        &mut affixes.suffixes
+                    };
+                    let [flag, second, third, rest @ ..] = fields.as_slice() else {
+                        return Err(anyhow!("invalid {keyword} entry on line {}", row + 1));
+                    };
+
+                    // A header line (`SFX flag Y count`) precedes the class's rules.
+                    if !classes.contains_key(*flag) && third.parse::<usize>().is_ok() {
+                        classes.insert(
+                            flag.to_string(),
+                            AffixClass {
+                                cross_product: *second == "Y",
+                                rules: Vec::new(),
+                            },
+                        );
+                        continue;
+                    }
+
+                    let Some(class) = classes.get_mut(*flag) else {
+                        return Err(anyhow!(
+                            "{keyword} rule for undeclared flag {flag:?} on line {}",
+                            row + 1
+                        ));
+                    };
+                    let strip = if *second == "0" { "" } else { second };
+                    // Continuation flags (`add/flags`) are not supported, so they are dropped.
+                    let add = third.split('/').next().unwrap_or_default();
+                    let add = if add == "0" { "" } else { add };
+                    let condition = Condition::parse(rest.first().copied().unwrap_or("."));
+                    class.rules.push(AffixRule {
+                        strip: strip.to_string(),
+                        add: add.to_string(),
+                        condition,
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(affixes)
+    }
+}
+
+impl AffixRule {
+    fn apply_suffix(&self, word: &str) -> Option<String> {
+        if !self.condition.matches_end(word) {
+            return None;
+        }
+        let stem = word.strip_suffix(self.strip.as_str())?;
+        Some(format!("{stem}{}", self.add))
+    }
+
+    fn apply_prefix(&self, word: &str) -> Option<String> {
+        if !self.condition.matches_start(word) {
+            return None;
+        }
+        let stem = word.strip_prefix(self.strip.as_str())?;
+        Some(format!("{}{stem}", self.add))
+    }
+}
+
+/// An affix condition, such as `[^aeiou]y`, which is a sequence of characters,
+/// character classes and `.` wildcards.
+struct Condition(Vec<CharClass>);
+
+enum CharClass {
+    Any,
+    Char(char),
+    OneOf(Vec<char>),
+    NoneOf(Vec<char>),
+}
+
+impl Condition {
+    fn parse(source: &str) -> Self {
+        let mut classes = Vec::new();
+        let mut chars = source.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                '.' => classes.push(CharClass::Any),
+                '[' => {
+                    let mut members = Vec::new();
+                    let mut negated = false;
+                    for member in chars.by_ref() {
+                        match member {
+                            ']' => break,
+                            '^' if members.is_empty() && !negated => negated = true,
+                            _ => members.push(member),
+                        }
+                    }
+                    classes.push(if negated {
+                        CharClass::NoneOf(members)
+                    } else {
+                        CharClass::OneOf(members)
+                    });
+                }
+                _ => classes.push(CharClass::Char(char)),
+            }
+        }
+        Self(classes)
+    }
+
+    fn matches_start(&self, word: &str) -> bool {
+        let chars = word.chars().collect::<Vec<_>>();
+        chars.len() >= self.0.len()
+            && self
+                .0
+                .iter()
+                .zip(&chars)
+                .all(|(class, char)| class.matches(*char))
+    }
+
+    fn matches_end(&self, word: &str) -> bool {
+        let chars = word.chars().collect::<Vec<_>>();
+        chars.len() >= self.0.len()
+            && self
+                .0
+                .iter()
+                .rev()
+                .zip(chars.iter().rev())
+                .all(|(class, char)| class.matches(*char))
+    }
+}
+
+impl CharClass {
+    fn matches(&self, char: char) -> bool {
+        match self {
+            CharClass::Any => true,
+            CharClass::Char(expected) => *expected == char,
+            CharClass::OneOf(members) => members.contains(&char),
+            CharClass::NoneOf(members) => !members.contains(&char),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "
+SET UTF-8
+TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+
+PFX A Y 1
+PFX A   0     re         .
+
+SFX D Y 4
+SFX D   0     d          e
+SFX D   y     ied        [^aeiou]y
+SFX D   0     ed         [^ey]
+SFX D   0     ed         [aeiou]y
+
+SFX S Y 3
+SFX S   y     ies        [^aeiou]y
+SFX S   0     s          [aeiou]y
+SFX S   0     s          [^y]
+";
+
+    const DIC: &str = "
+5
+create/ADS
+try/DS
+play/DS
+English
+HTTP
+";
+
+    #[test]
+    fn test_affix_expansion() {
+        let dictionary = Dictionary::from_hunspell(DIC, Some(AFF)).unwrap();
+        for word in [
+            "create",
+            "created",
+            "creates",
+            "recreate",
+            "recreated",
+            "recreates",
+            "try",
+            "tried",
+            "tries",
+            "play",
+            "played",
+            "plays",
+        ] {
+            assert!(
+                dictionary.contains(word),
+                "{word:?} should be in the dictionary"
+            );
+        }
+        for word in ["tryed", "plaies", "reply", "retry", "createed"] {
+            assert!(
+                !dictionary.contains(word),
+                "{word:?} should not be in the dictionary"
+            );
+        }
+    }
+
+    #[test]
+    fn test_case_sensitivity() {
+        let dictionary = Dictionary::from_hunspell(DIC, Some(AFF)).unwrap();
+        assert!(dictionary.contains("Created"));
+        assert!(dictionary.contains("CREATED"));
+        assert!(!dictionary.contains("cReated"));
+
+        assert!(dictionary.contains("English"));
+        assert!(dictionary.contains("ENGLISH"));
+        assert!(!dictionary.contains("english"));
+
+        assert!(dictionary.contains("HTTP"));
+        assert!(!dictionary.contains("http"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let dictionary = Dictionary::from_hunspell(DIC, Some(AFF)).unwrap();
+        assert_eq!(dictionary.suggestions("craeted"), ["created"]);
+        assert_eq!(dictionary.suggestions("Plyed"), ["Played"]);
+        assert_eq!(dictionary.suggestions("TRYS"), ["TRY"]);
+        assert_eq!(dictionary.suggestions("tryies"), ["tries"]);
+        assert!(dictionary.suggestions("zzzzzzzz").is_empty());
+    }
+
+    #[test]
+    fn test_word_lists() {
+        let mut dictionary = Dictionary::from_word_list("# comment\nfoo\n\n  bar  \n");
+        assert_eq!(dictionary.len(), 2);
+        dictionary.merge(Dictionary::from_word_list("baz"));
+        assert!(dictionary.contains("foo"));
+        assert!(dictionary.contains("bar"));
+        assert!(dictionary.contains("baz"));
+        assert!(!dictionary.contains("# comment"));
+    }
+}
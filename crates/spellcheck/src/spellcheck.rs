@@ -0,0 +1,141 @@
+mod dictionary;
+mod spellcheck_addon;
+mod spellcheck_settings;
+mod spellcheck_store;
+mod words;
+
+use std::{ops::Range, sync::Arc};
+
+use editor::Editor;
+use fs::Fs;
+use gpui::{AppContext, SharedString, ViewContext, VisualContext as _};
+use language::BufferSnapshot;
+use settings::Settings;
+use workspace::{
+    notifications::{simple_message_notification::MessageNotification, NotificationId},
+    Workspace,
+};
+
+pub use crate::dictionary::Dictionary;
+pub use crate::spellcheck_settings::SpellcheckSettings;
+pub use crate::spellcheck_store::{DictionaryStatus, SpellcheckStore};
+
+/// Languages whose entire contents are prose, rather than code with comments and strings.
+const PROSE_LANGUAGES: &[&str] = &["Plain Text", "Markdown", "Git Commit"];
+
+/// The syntax overrides whose contents are spell checked in code.
+const CHECKED_SCOPES: &[&str] = &["comment", "string"];
+
+pub fn init(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+    SpellcheckSettings::register(cx);
+    SpellcheckStore::init_global(fs, cx);
+
+    cx.observe_new_views(|editor: &mut Editor, cx| {
+        spellcheck_addon::register(editor, cx);
+    })
+    .detach();
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        let Some(store) = SpellcheckStore::try_global(cx) else {
+            return;
+        };
+        let status = store.read(cx).status().clone();
+        notify_if_dictionary_missing(workspace, &status, cx);
+        cx.observe(&store, |workspace, store, cx| {
+            let status = store.read(cx).status().clone();
+            notify_if_dictionary_missing(workspace, &status, cx);
+        })
+        .detach();
+    })
+    .detach();
+}
+
+/// Tells the user once that spell checking does nothing until a dictionary for
+/// their language is installed.
+fn notify_if_dictionary_missing(
+    workspace: &mut Workspace,
+    status: &DictionaryStatus,
+    cx: &mut ViewContext<Workspace>,
+) {
+    struct MissingDictionaryNotification;
+
+    let DictionaryStatus::NotFound { language } = status else {
+        return;
+    };
+    let message = format!(
+        "No {language} dictionary is configured, so spelling isn't checked. \
+        Add {language}.dic and {language}.aff to {}, or install a Hunspell dictionary for your system.",
+        paths::dictionaries_dir().display()
+    );
+    workspace.show_notification_once(
+        NotificationId::composite::<MissingDictionaryNotification>(SharedString::from(
+            language.clone(),
+        )),
+        cx,
+        |cx| {
+            cx.new_view(|_| {
+                MessageNotification::new(message)
+                    .with_click_message("Open Dictionaries Folder")
+                    .on_click(|cx| {
+                        let dir = paths::dictionaries_dir();
+                        std::fs::create_dir_all(dir).ok();
+                        cx.open_with_system(dir);
+                    })
+            })
+        },
+    );
+}
+
+/// Checks words against the configured dictionary and the words of a project's
+/// `.zed/dictionary.txt`.
+#[derive(Clone)]
+pub struct SpellChecker {
+    dictionary: Arc<Dictionary>,
+    project_words: Arc<Dictionary>,
+}
+
+impl SpellChecker {
+    pub fn new(dictionary: Arc<Dictionary>, project_words: Arc<Dictionary>) -> Self {
+        Self {
+            dictionary,
+            project_words,
+        }
+    }
+
+    pub fn is_correct(&self, word: &str) -> bool {
+        self.dictionary.contains(word) || self.project_words.contains(word)
+    }
+
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        self.dictionary.suggestions(word)
+    }
+
+    /// Returns the ranges of the misspelled words within the given range of the buffer.
+    ///
+    /// In code, only comments and strings are checked. Prose languages and buffers
+    /// without a language are checked in full.
+    pub fn misspellings(
+        &self,
+        snapshot: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Vec<Range<usize>> {
+        let mut misspellings = Vec::new();
+        for range in checked_ranges(snapshot, range) {
+            let text = snapshot.text_for_range(range.clone()).collect::<String>();
+            for word in words::words(&text) {
+                if !self.is_correct(&text[word.clone()]) {
+                    misspellings.push(range.start + word.start..range.start + word.end);
+                }
+            }
+        }
+        misspellings
+    }
+}
+
+fn checked_ranges(snapshot: &BufferSnapshot, range: Range<usize>) -> Vec<Range<usize>> {
+    match snapshot.language() {
+        Some(language) if !PROSE_LANGUAGES.contains(&language.name().0.as_ref()) => {
+            snapshot.override_ranges_in_range(range, CHECKED_SCOPES)
+        }
+        _ => vec![range],
+    }
+}
@@ -0,0 +1,410 @@
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    ops::Range,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use editor::{Addon, CodeActionProvider, Editor, EditorMode, ExcerptId};
+use gpui::{
+    px, HighlightStyle, Model, Subscription, Task, UnderlineStyle, ViewContext, WindowContext,
+};
+use language::{Buffer, LanguageServerId, Point, ToOffset as _};
+use multi_buffer::MultiBufferSnapshot;
+use project::{CodeAction, File, ProjectTransaction};
+use serde::{Deserialize, Serialize};
+use text::BufferId;
+use theme::ActiveTheme as _;
+
+use crate::{SpellChecker, SpellcheckStore};
+
+/// How long to wait after the last edit before re-checking an editor's contents.
+const CHECK_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// The highlight key for misspelled words.
+enum Misspelling {}
+
+struct SpellcheckAddon {
+    check_task: RefCell<Task<()>>,
+    /// Whether the next check must cover every excerpt, rather than only what was
+    /// edited since the last one.
+    needs_full_check: Cell<bool>,
+    /// The version of each buffer when its misspellings were last highlighted.
+    checked_versions: RefCell<HashMap<BufferId, clock::Global>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl Addon for SpellcheckAddon {
+    fn to_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode() != EditorMode::Full {
+        return;
+    }
+    let Some(store) = SpellcheckStore::try_global(cx) else {
+        return;
+    };
+
+    let multi_buffer = editor.buffer().clone();
+    let subscriptions = vec![
+        cx.subscribe(
+            &multi_buffer,
+            |editor, _, event: &multi_buffer::Event, cx| match event {
+                multi_buffer::Event::Edited { .. } | multi_buffer::Event::Reparsed(_) => {
+                    schedule_check(editor, false, cx)
+                }
+                multi_buffer::Event::ExcerptsAdded { .. }
+                | multi_buffer::Event::ExcerptsExpanded { .. }
+                | multi_buffer::Event::ExcerptsEdited { .. }
+                | multi_buffer::Event::Reloaded
+                | multi_buffer::Event::LanguageChanged(_) => schedule_check(editor, true, cx),
+                _ => {}
+            },
+        ),
+        cx.observe(&store, |editor, _, cx| schedule_check(editor, true, cx)),
+    ];
+    editor.register_addon(SpellcheckAddon {
+        check_task: RefCell::new(Task::ready(())),
+        needs_full_check: Cell::new(true),
+        checked_versions: RefCell::default(),
+        _subscriptions: subscriptions,
+    });
+    editor.push_code_action_provider(Arc::new(SpellcheckCodeActionProvider { store }), cx);
+    schedule_check(editor, true, cx);
+}
+
+/// Re-checks the editor's contents after a short delay. Unless `full` is set, only
+/// the lines edited since the last check are re-checked.
+fn schedule_check(editor: &mut Editor, full: bool, cx: &mut ViewContext<Editor>) {
+    let Some(addon) = editor.addon::<SpellcheckAddon>() else {
+        return;
+    };
+    if full {
+        addon.needs_full_check.set(true);
+    }
+
+    let task = cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(CHECK_DEBOUNCE).await;
+
+        let Some((snapshot, checks, full)) = editor
+            .update(&mut cx, |editor, cx| {
+                let addon = editor.addon::<SpellcheckAddon>()?;
+                let full = addon.needs_full_check.get();
+                let (snapshot, checks) = excerpts_to_check(editor, full, cx);
+                Some((snapshot, checks, full))
+            })
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+        let versions = checks
+            .iter()
+            .map(|check| (check.buffer_id, check.version.clone()))
+            .collect::<HashMap<_, _>>();
+        let (misspellings, checked_ranges) = cx
+            .background_executor()
+            .spawn(async move { find_misspellings(&snapshot, checks) })
+            .await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let Some(addon) = editor.addon::<SpellcheckAddon>() else {
+                    return;
+                };
+                if full {
+                    addon.needs_full_check.set(false);
+                    *addon.checked_versions.borrow_mut() = versions;
+                } else {
+                    addon.checked_versions.borrow_mut().extend(versions);
+                }
+
+                let misspellings = if full {
+                    misspellings
+                } else {
+                    // Keep the previous misspellings outside of the re-checked lines.
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let mut kept = editor
+                        .text_highlights::<Misspelling>(cx)
+                        .map(|(_, ranges)| ranges)
+                        .unwrap_or_default()
+                        .iter()
+                        .filter(|range| {
+                            !checked_ranges.iter().any(|checked| {
+                                range.start.cmp(&checked.end, &snapshot).is_le()
+                                    && checked.start.cmp(&range.end, &snapshot).is_le()
+                            })
+                        })
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    kept.extend(misspellings);
+                    kept.sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+                    kept
+                };
+
+                if misspellings.is_empty() {
+                    editor.clear_highlights::<Misspelling>(cx);
+                } else {
+                    let style = HighlightStyle {
+                        underline: Some(UnderlineStyle {
+                            thickness: px(1.),
+                            color: Some(cx.theme().status().info),
+                            wavy: true,
+                        }),
+                        ..Default::default()
+                    };
+                    editor.highlight_text::<Misspelling>(misspellings, style, cx);
+                }
+            })
+            .ok();
+    });
+    *addon.check_task.borrow_mut() = task;
+}
+
+struct ExcerptCheck {
+    excerpt_id: ExcerptId,
+    buffer_id: BufferId,
+    version: clock::Global,
+    ranges: Vec<Range<usize>>,
+    checker: SpellChecker,
+}
+
+fn excerpts_to_check(
+    editor: &Editor,
+    full: bool,
+    cx: &mut ViewContext<Editor>,
+) -> (MultiBufferSnapshot, Vec<ExcerptCheck>) {
+    let checked_versions = editor
+        .addon::<SpellcheckAddon>()
+        .map(|addon| addon.checked_versions.borrow().clone())
+        .unwrap_or_default();
+    let multi_buffer = editor.buffer().read(cx);
+    let snapshot = multi_buffer.snapshot(cx);
+    let excerpts = snapshot
+        .excerpts()
+        .filter_map(|(excerpt_id, buffer_snapshot, excerpt_range)| {
+            let buffer = multi_buffer.buffer(buffer_snapshot.remote_id())?;
+            let excerpt_range = excerpt_range.context.to_offset(buffer_snapshot);
+            let version = buffer_snapshot.version().clone();
+            let ranges = match checked_versions.get(&buffer_snapshot.remote_id()) {
+                Some(checked_version) if !full => {
+                    edited_lines(buffer_snapshot, checked_version, &excerpt_range)
+                }
+                _ => vec![excerpt_range],
+            };
+            (!ranges.is_empty()).then_some((excerpt_id, buffer, version, ranges))
+        })
+        .collect::<Vec<_>>();
+
+    let store = SpellcheckStore::global(cx);
+    let checks = excerpts
+        .into_iter()
+        .filter_map(|(excerpt_id, buffer, version, ranges)| {
+            if buffer.read(cx).is_large_file() {
+                return None;
+            }
+            let checker = store.update(cx, |store, cx| store.checker_for_buffer(&buffer, cx))?;
+            Some(ExcerptCheck {
+                excerpt_id,
+                buffer_id: buffer.read(cx).remote_id(),
+                version,
+                ranges,
+                checker,
+            })
+        })
+        .collect();
+    (snapshot, checks)
+}
+
+/// Returns the whole lines of the excerpt that were edited since the given version.
+fn edited_lines(
+    buffer_snapshot: &language::BufferSnapshot,
+    since: &clock::Global,
+    excerpt_range: &Range<usize>,
+) -> Vec<Range<usize>> {
+    let mut ranges = Vec::<Range<usize>>::new();
+    for edit in buffer_snapshot.edits_since::<Point>(since) {
+        let start = Point::new(edit.new.start.row, 0).to_offset(buffer_snapshot);
+        let end = Point::new(edit.new.end.row, buffer_snapshot.line_len(edit.new.end.row))
+            .to_offset(buffer_snapshot);
+        let start = start.max(excerpt_range.start);
+        let end = end.min(excerpt_range.end);
+        if start > end {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last) if last.end >= start => last.end = last.end.max(end),
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+/// Returns the misspellings found in the checked ranges, along with those ranges.
+fn find_misspellings(
+    snapshot: &MultiBufferSnapshot,
+    checks: Vec<ExcerptCheck>,
+) -> (
+    Vec<Range<multi_buffer::Anchor>>,
+    Vec<Range<multi_buffer::Anchor>>,
+) {
+    let mut misspellings = Vec::new();
+    let mut checked_ranges = Vec::new();
+    for check in checks {
+        let Some(buffer_snapshot) = snapshot.buffer_for_excerpt(check.excerpt_id) else {
+            continue;
+        };
+        let to_anchor_range = |range: Range<usize>| {
+            let start = snapshot
+                .anchor_in_excerpt(check.excerpt_id, buffer_snapshot.anchor_after(range.start));
+            let end = snapshot
+                .anchor_in_excerpt(check.excerpt_id, buffer_snapshot.anchor_before(range.end));
+            start.zip(end).map(|(start, end)| start..end)
+        };
+        for range in check.ranges {
+            checked_ranges.extend(to_anchor_range(range.clone()));
+            misspellings.extend(
+                check
+                    .checker
+                    .misspellings(buffer_snapshot, range)
+                    .into_iter()
+                    .filter_map(to_anchor_range),
+            );
+        }
+    }
+    (misspellings, checked_ranges)
+}
+
+/// The spelling fix stored in a [`CodeAction`]'s data.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum SpellcheckAction {
+    Replace { text: String },
+    AddToProjectDictionary { word: String },
+}
+
+struct SpellcheckCodeActionProvider {
+    store: Model<SpellcheckStore>,
+}
+
+impl CodeActionProvider for SpellcheckCodeActionProvider {
+    fn code_actions(
+        &self,
+        buffer: &Model<Buffer>,
+        range: Range<text::Anchor>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let Some(checker) = self
+            .store
+            .update(cx, |store, cx| store.checker_for_buffer(buffer, cx))
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        let snapshot = buffer.read(cx).snapshot();
+        let offset = range.start.to_offset(&snapshot);
+        let row = snapshot.offset_to_point(offset).row;
+        let line_range = Point::new(row, 0).to_offset(&snapshot)
+            ..Point::new(row, snapshot.line_len(row)).to_offset(&snapshot);
+        let Some(word_range) = checker
+            .misspellings(&snapshot, line_range)
+            .into_iter()
+            .find(|range| range.start <= offset && offset <= range.end)
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        let word = snapshot
+            .text_for_range(word_range.clone())
+            .collect::<String>();
+        let anchor_range =
+            snapshot.anchor_before(word_range.start)..snapshot.anchor_after(word_range.end);
+        let can_add_to_project = File::from_dyn(buffer.read(cx).file()).is_some();
+
+        // Generating the candidates that are two edits away from a long word is
+        // expensive, so it happens off the main thread.
+        cx.background_executor().spawn(async move {
+            let mut actions = checker
+                .suggestions(&word)
+                .into_iter()
+                .map(|suggestion| {
+                    (
+                        format!("Change to \"{suggestion}\""),
+                        SpellcheckAction::Replace { text: suggestion },
+                    )
+                })
+                .collect::<Vec<_>>();
+            if can_add_to_project {
+                actions.push((
+                    format!("Add \"{word}\" to project dictionary"),
+                    SpellcheckAction::AddToProjectDictionary { word },
+                ));
+            }
+
+            Ok(actions
+                .into_iter()
+                .map(|(title, action)| CodeAction {
+                    server_id: LanguageServerId(0),
+                    range: anchor_range.clone(),
+                    lsp_action: lsp::CodeAction {
+                        title,
+                        kind: Some(lsp::CodeActionKind::QUICKFIX),
+                        data: serde_json::to_value(action).ok(),
+                        ..Default::default()
+                    },
+                })
+                .collect())
+        })
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Model<Buffer>,
+        action: CodeAction,
+        _excerpt_id: ExcerptId,
+        push_to_history: bool,
+        cx: &mut WindowContext,
+    ) -> Task<Result<ProjectTransaction>> {
+        let spellcheck_action = action
+            .lsp_action
+            .data
+            .context("missing spelling fix")
+            .and_then(|data| {
+                serde_json::from_value::<SpellcheckAction>(data).context("invalid spelling fix")
+            });
+        match spellcheck_action {
+            Ok(SpellcheckAction::Replace { text }) => {
+                let mut project_transaction = ProjectTransaction::default();
+                buffer.update(cx, |buffer, cx| {
+                    buffer.finalize_last_transaction();
+                    buffer.start_transaction();
+                    buffer.edit([(action.range, text)], None, cx);
+                    if buffer.end_transaction(cx).is_some() {
+                        let transaction = buffer.finalize_last_transaction().unwrap().clone();
+                        if !push_to_history {
+                            buffer.forget_transaction(transaction.id);
+                        }
+                        project_transaction.0.insert(cx.handle(), transaction);
+                    }
+                });
+                Task::ready(Ok(project_transaction))
+            }
+            Ok(SpellcheckAction::AddToProjectDictionary { word }) => {
+                let add_word = self
+                    .store
+                    .update(cx, |store, cx| store.add_project_word(&buffer, word, cx));
+                cx.background_executor().spawn(async move {
+                    add_word.await?;
+                    Ok(ProjectTransaction::default())
+                })
+            }
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+}
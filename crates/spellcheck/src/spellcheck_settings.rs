@@ -0,0 +1,42 @@
+use anyhow::Result;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct SpellcheckSettings {
+    pub enabled: bool,
+    pub language: String,
+    pub dictionaries: Vec<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct SpellcheckSettingsContent {
+    /// Whether to check the spelling of comments, strings and prose.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The language of the Hunspell dictionary to check against, such as `en_US`.
+    /// Zed looks for `<language>.dic` and `<language>.aff` in its `dictionaries`
+    /// config directory and then in the system's Hunspell directories.
+    ///
+    /// Default: "en_US"
+    pub language: Option<String>,
+    /// Paths to additional dictionaries. Files ending in `.dic` are read as Hunspell
+    /// dictionaries, with a sibling `.aff` file if one exists; any other file is read
+    /// as a list of words, one per line.
+    ///
+    /// Default: []
+    pub dictionaries: Option<Vec<String>>,
+}
+
+impl Settings for SpellcheckSettings {
+    const KEY: Option<&'static str> = Some("spellcheck");
+
+    type FileContent = SpellcheckSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -0,0 +1,349 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use fs::Fs;
+use gpui::{AppContext, Context as _, Global, Model, ModelContext, Subscription, Task};
+use language::Buffer;
+use project::{File, Worktree};
+use settings::{Settings, SettingsStore};
+use util::{paths::home_dir, ResultExt as _};
+
+use crate::{Dictionary, SpellChecker, SpellcheckSettings};
+
+/// Word lists bundled with Zed that are merged into every dictionary.
+const BUNDLED_WORD_LISTS: &[&str] = &["dictionaries/programming.txt"];
+
+struct GlobalSpellcheckStore(Model<SpellcheckStore>);
+
+impl Global for GlobalSpellcheckStore {}
+
+/// Whether a dictionary is available to check spelling against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DictionaryStatus {
+    Disabled,
+    Loading,
+    Loaded,
+    /// No dictionary for the configured language was found in any of the searched
+    /// directories, so nothing is checked.
+    NotFound {
+        language: String,
+    },
+}
+
+/// Owns the loaded dictionary and the per-project word lists, and reloads them
+/// when the settings or a project's `.zed/dictionary.txt` change.
+pub struct SpellcheckStore {
+    fs: Arc<dyn Fs>,
+    settings: SpellcheckSettings,
+    dictionary: Option<Arc<Dictionary>>,
+    status: DictionaryStatus,
+    project_words: HashMap<Arc<Path>, ProjectWords>,
+    load_dictionary: Task<()>,
+    _settings_subscription: Subscription,
+}
+
+struct ProjectWords {
+    words: Arc<Dictionary>,
+    load: Task<()>,
+    _subscriptions: [Subscription; 2],
+}
+
+impl SpellcheckStore {
+    pub(crate) fn init_global(fs: Arc<dyn Fs>, cx: &mut AppContext) {
+        let store = cx.new_model(|cx| Self::new(fs, cx));
+        cx.set_global(GlobalSpellcheckStore(store));
+    }
+
+    pub fn global(cx: &AppContext) -> Model<Self> {
+        cx.global::<GlobalSpellcheckStore>().0.clone()
+    }
+
+    pub fn try_global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalSpellcheckStore>()
+            .map(|store| store.0.clone())
+    }
+
+    fn new(fs: Arc<dyn Fs>, cx: &mut ModelContext<Self>) -> Self {
+        let settings_subscription = cx.observe_global::<SettingsStore>(|this, cx| {
+            let settings = SpellcheckSettings::get_global(cx);
+            if *settings != this.settings {
+                this.settings = settings.clone();
+                this.reload_dictionary(cx);
+            }
+        });
+
+        let mut this = Self {
+            fs,
+            settings: SpellcheckSettings::get_global(cx).clone(),
+            dictionary: None,
+            status: DictionaryStatus::Loading,
+            project_words: HashMap::default(),
+            load_dictionary: Task::ready(()),
+            _settings_subscription: settings_subscription,
+        };
+        this.reload_dictionary(cx);
+        this
+    }
+
+    pub fn status(&self) -> &DictionaryStatus {
+        &self.status
+    }
+
+    /// Returns a checker for the given buffer, or `None` if spell checking is disabled
+    /// or no dictionary has been loaded.
+    pub fn checker_for_buffer(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<SpellChecker> {
+        if !self.settings.enabled {
+            return None;
+        }
+
+        let dictionary = self.dictionary.clone()?;
+        let project_words = File::from_dyn(buffer.read(cx).file())
+            .map(|file| self.project_words_for_worktree(&file.worktree, cx))
+            .unwrap_or_default();
+        Some(SpellChecker::new(dictionary, project_words))
+    }
+
+    /// Adds a word to the `.zed/dictionary.txt` of the worktree containing the given buffer.
+    pub fn add_project_word(
+        &mut self,
+        buffer: &Model<Buffer>,
+        word: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
+            return Task::ready(Err(anyhow!("buffer is not part of a project")));
+        };
+        let worktree = file.worktree.clone();
+        let path = worktree
+            .read(cx)
+            .abs_path()
+            .join(paths::local_dictionary_file_relative_path());
+        let fs = self.fs.clone();
+        cx.spawn(|this, mut cx| async move {
+            let mut contents = if fs.is_file(&path).await {
+                fs.load(&path).await?
+            } else {
+                String::new()
+            };
+            if contents.lines().any(|line| line.trim() == word) {
+                return Ok(());
+            }
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(&word);
+            contents.push('\n');
+
+            if let Some(parent) = path.parent() {
+                fs.create_dir(parent).await?;
+            }
+            fs.atomic_write(path, contents).await?;
+            this.update(&mut cx, |this, cx| this.reload_project_words(&worktree, cx))
+        })
+    }
+
+    fn project_words_for_worktree(
+        &mut self,
+        worktree: &Model<Worktree>,
+        cx: &mut ModelContext<Self>,
+    ) -> Arc<Dictionary> {
+        let root = worktree.read(cx).abs_path();
+        if let Some(project_words) = self.project_words.get(&root) {
+            return project_words.words.clone();
+        }
+
+        let subscriptions = [
+            cx.subscribe(worktree, |this, worktree, event, cx| {
+                if let worktree::Event::UpdatedEntries(changes) = event {
+                    let dictionary_path = paths::local_dictionary_file_relative_path();
+                    if changes
+                        .iter()
+                        .any(|(path, _, _)| path.as_ref() == dictionary_path)
+                    {
+                        this.reload_project_words(&worktree, cx);
+                    }
+                }
+            }),
+            cx.observe_release(worktree, {
+                let root = root.clone();
+                move |this, _, _| {
+                    this.project_words.remove(&root);
+                }
+            }),
+        ];
+        self.project_words.insert(
+            root,
+            ProjectWords {
+                words: Arc::default(),
+                load: Task::ready(()),
+                _subscriptions: subscriptions,
+            },
+        );
+        self.reload_project_words(worktree, cx);
+        Arc::default()
+    }
+
+    fn reload_project_words(&mut self, worktree: &Model<Worktree>, cx: &mut ModelContext<Self>) {
+        let root = worktree.read(cx).abs_path();
+        let Some(project_words) = self.project_words.get_mut(&root) else {
+            return;
+        };
+
+        let path = root.join(paths::local_dictionary_file_relative_path());
+        let fs = self.fs.clone();
+        project_words.load = cx.spawn(|this, mut cx| async move {
+            let words = if fs.is_file(&path).await {
+                fs.load(&path)
+                    .await
+                    .log_err()
+                    .map(|text| Dictionary::from_word_list(&text))
+                    .unwrap_or_default()
+            } else {
+                Dictionary::default()
+            };
+            this.update(&mut cx, |this, cx| {
+                if let Some(project_words) = this.project_words.get_mut(&root) {
+                    project_words.words = Arc::new(words);
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+    }
+
+    fn reload_dictionary(&mut self, cx: &mut ModelContext<Self>) {
+        if !self.settings.enabled {
+            self.dictionary = None;
+            self.status = DictionaryStatus::Disabled;
+            self.load_dictionary = Task::ready(());
+            cx.notify();
+            return;
+        }
+
+        let word_lists = BUNDLED_WORD_LISTS
+            .iter()
+            .filter_map(|path| {
+                let contents = cx.asset_source().load(path).log_err()??;
+                String::from_utf8(contents.into_owned()).log_err()
+            })
+            .collect::<Vec<_>>();
+        let settings = self.settings.clone();
+        let fs = self.fs.clone();
+        self.status = DictionaryStatus::Loading;
+        self.load_dictionary = cx.spawn(|this, mut cx| async move {
+            let dictionary = load_dictionary(fs, settings.clone(), word_lists)
+                .await
+                .log_err()
+                .flatten();
+            this.update(&mut cx, |this, cx| {
+                this.status = if dictionary.is_some() {
+                    DictionaryStatus::Loaded
+                } else {
+                    DictionaryStatus::NotFound {
+                        language: settings.language,
+                    }
+                };
+                this.dictionary = dictionary.map(Arc::new);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+}
+
+/// Loads the base dictionary for the configured language and merges the bundled
+/// and user-configured word lists into it. Returns `None` if no dictionary exists
+/// for the language.
+async fn load_dictionary(
+    fs: Arc<dyn Fs>,
+    settings: SpellcheckSettings,
+    word_lists: Vec<String>,
+) -> Result<Option<Dictionary>> {
+    let mut dictionary = None;
+    let file_name = format!("{}.dic", settings.language);
+    for dir in dictionary_search_paths() {
+        let path = dir.join(&file_name);
+        if fs.is_file(&path).await {
+            dictionary = Some(load_hunspell(fs.as_ref(), &path).await?);
+            break;
+        }
+    }
+    let Some(mut dictionary) = dictionary else {
+        log::info!(
+            "no {} dictionary found, spell checking is disabled",
+            settings.language
+        );
+        return Ok(None);
+    };
+
+    for word_list in word_lists {
+        dictionary.extend_word_list(&word_list);
+    }
+
+    for path in &settings.dictionaries {
+        let path = match path.strip_prefix("~/") {
+            Some(path) => home_dir().join(path),
+            None => PathBuf::from(path),
+        };
+        let result = if path
+            .extension()
+            .map_or(false, |extension| extension == "dic")
+        {
+            load_hunspell(fs.as_ref(), &path)
+                .await
+                .map(|extra| dictionary.merge(extra))
+        } else {
+            fs.load(&path)
+                .await
+                .map(|text| dictionary.extend_word_list(&text))
+        };
+        result
+            .with_context(|| format!("loading dictionary {path:?}"))
+            .log_err();
+    }
+
+    Ok(Some(dictionary))
+}
+
+async fn load_hunspell(fs: &dyn Fs, dic_path: &Path) -> Result<Dictionary> {
+    let dic = decode(fs.load_bytes(dic_path).await?);
+    let aff_path = dic_path.with_extension("aff");
+    let aff = if fs.is_file(&aff_path).await {
+        Some(decode(fs.load_bytes(&aff_path).await?))
+    } else {
+        None
+    };
+    Dictionary::from_hunspell(&dic, aff.as_deref())
+        .with_context(|| format!("parsing dictionary {dic_path:?}"))
+}
+
+/// Hunspell files are often encoded as ISO-8859-1 rather than UTF-8. Every byte of
+/// ISO-8859-1 maps directly to the Unicode code point of the same value.
+fn decode(bytes: Vec<u8>) -> String {
+    match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(error) => error.into_bytes().into_iter().map(char::from).collect(),
+    }
+}
+
+fn dictionary_search_paths() -> Vec<PathBuf> {
+    let mut dirs = vec![paths::dictionaries_dir().clone()];
+    if cfg!(target_os = "macos") {
+        dirs.push(home_dir().join("Library/Spelling"));
+        dirs.push(PathBuf::from("/Library/Spelling"));
+    } else if cfg!(any(target_os = "linux", target_os = "freebsd")) {
+        dirs.push(PathBuf::from("/usr/share/hunspell"));
+        dirs.push(PathBuf::from("/usr/share/myspell"));
+        dirs.push(PathBuf::from("/usr/share/myspell/dicts"));
+        dirs.push(PathBuf::from("/usr/local/share/hunspell"));
+    }
+    dirs
+}
@@ -0,0 +1,113 @@
+use std::ops::Range;
+
+/// Words shorter than this are never checked.
+const MIN_WORD_LEN: usize = 3;
+
+/// Returns the byte ranges of the words in `text` that should be spell checked.
+///
+/// Identifiers are split into their parts, so `parseHttpRequest` and
+/// `parse_http_request` both yield `parse`, `http` and `request`. Tokens that
+/// contain digits, all-uppercase words (which are usually acronyms or constants)
+/// and very short words are skipped.
+pub fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut token_start = None;
+    for (ix, char) in text.char_indices().chain([(text.len(), ' ')]) {
+        if is_token_char(char) || (char == '\'' && token_start.is_some()) {
+            token_start.get_or_insert(ix);
+        } else if let Some(start) = token_start.take() {
+            split_token(text, start..ix, &mut words);
+        }
+    }
+    words
+}
+
+fn is_token_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+fn split_token(text: &str, range: Range<usize>, words: &mut Vec<Range<usize>>) {
+    let token = &text[range.clone()];
+    if token.chars().any(|char| char.is_ascii_digit()) {
+        return;
+    }
+
+    for part in token.split('_') {
+        let part_start = range.start + (part.as_ptr() as usize - token.as_ptr() as usize);
+        // Apostrophes only belong to a word when they're surrounded by letters, as in "don't".
+        let part = part.trim_matches('\'');
+        let part_start = part_start
+            + text[part_start..]
+                .find(|char: char| char != '\'')
+                .unwrap_or(0);
+        for subword in split_camel_case(part) {
+            let subword_range = part_start + subword.start..part_start + subword.end;
+            let subword = &text[subword_range.clone()];
+            let char_count = subword.chars().count();
+            let is_uppercase = subword
+                .chars()
+                .filter(|char| char.is_alphabetic())
+                .all(char::is_uppercase);
+            if char_count >= MIN_WORD_LEN && !is_uppercase {
+                words.push(subword_range);
+            }
+        }
+    }
+}
+
+/// Splits an identifier at its case boundaries: `HTTPServer` becomes `HTTP` and
+/// `Server`, and `parseJSON` becomes `parse` and `JSON`.
+fn split_camel_case(word: &str) -> Vec<Range<usize>> {
+    let chars = word.char_indices().collect::<Vec<_>>();
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for window in 1..chars.len() {
+        let (ix, char) = chars[window];
+        let previous = chars[window - 1].1;
+        let next = chars.get(window + 1).map(|(_, char)| *char);
+        let is_boundary = char.is_uppercase()
+            && (previous.is_lowercase()
+                || (previous.is_uppercase() && next.map_or(false, char::is_lowercase)));
+        if is_boundary {
+            ranges.push(start..ix);
+            start = ix;
+        }
+    }
+    if start < word.len() {
+        ranges.push(start..word.len());
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str, expected: &[&str]) {
+        let actual = words(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect::<Vec<_>>();
+        assert_eq!(actual, expected, "words in {text:?}");
+    }
+
+    #[test]
+    fn test_words() {
+        check("Hello, world!", &["Hello", "world"]);
+        check("// Don't panic.", &["Don't", "panic"]);
+        check("'quoted' words", &["quoted", "words"]);
+        check("an HTML page", &["page"]);
+        check("sha256 and utf8", &["and"]);
+        check("naïve café", &["naïve", "café"]);
+    }
+
+    #[test]
+    fn test_identifiers() {
+        check("parseHttpRequest", &["parse", "Http", "Request"]);
+        check("parse_http_request", &["parse", "http", "request"]);
+        check("HTTPServer", &["Server"]);
+        check("parseJSON", &["parse"]);
+        check("MAX_BUFFER_SIZE", &[]);
+        check("__init__ fn", &["init"]);
+    }
+}
@@ -0,0 +1,631 @@
+use std::path::Path;
+
+use anyhow::Context as _;
+use collections::HashSet;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{ProblemMatcher, TaskTemplate, TaskTemplates};
+
+/// A file where projects declare their tasks already, which Zed can list tasks from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DetectedTaskSource {
+    /// `scripts` of a `package.json`, run with the package manager the project uses.
+    PackageJson,
+    /// Targets of a `Makefile`.
+    Makefile,
+    /// Recipes of a `justfile`.
+    Justfile,
+    /// Tasks of a `Taskfile.yml`.
+    Taskfile,
+    /// Binaries and examples of a `Cargo.toml` package.
+    Cargo,
+    /// Aliases from a `.cargo/config.toml`.
+    CargoConfig,
+    /// Scripts of a `pyproject.toml`.
+    Pyproject,
+}
+
+const SOURCE_FILES: &[(&str, DetectedTaskSource)] = &[
+    ("package.json", DetectedTaskSource::PackageJson),
+    ("Makefile", DetectedTaskSource::Makefile),
+    ("makefile", DetectedTaskSource::Makefile),
+    ("GNUmakefile", DetectedTaskSource::Makefile),
+    ("justfile", DetectedTaskSource::Justfile),
+    ("Justfile", DetectedTaskSource::Justfile),
+    (".justfile", DetectedTaskSource::Justfile),
+    ("Taskfile.yml", DetectedTaskSource::Taskfile),
+    ("Taskfile.yaml", DetectedTaskSource::Taskfile),
+    ("taskfile.yml", DetectedTaskSource::Taskfile),
+    ("taskfile.yaml", DetectedTaskSource::Taskfile),
+    ("Cargo.toml", DetectedTaskSource::Cargo),
+    (".cargo/config.toml", DetectedTaskSource::CargoConfig),
+    (".cargo/config", DetectedTaskSource::CargoConfig),
+    ("pyproject.toml", DetectedTaskSource::Pyproject),
+];
+
+impl DetectedTaskSource {
+    /// Paths of all files that can declare tasks, relative to the directory they run their tasks in.
+    pub fn file_paths() -> impl Iterator<Item = &'static Path> {
+        SOURCE_FILES
+            .iter()
+            .map(|(file_name, _)| Path::new(*file_name))
+    }
+
+    /// Checks whether the file at the path given declares tasks,
+    /// returns the kind of the source and the directory to run its tasks in.
+    pub fn for_path(path: &Path) -> Option<(Self, &Path)> {
+        SOURCE_FILES.iter().find_map(|(file_name, source)| {
+            let file_name = Path::new(file_name);
+            if !path.ends_with(file_name) {
+                return None;
+            }
+            let directory = path.ancestors().nth(file_name.components().count())?;
+            Some((*source, directory))
+        })
+    }
+
+    /// Lists the tasks declared in the file contents given.
+    ///
+    /// `directory` is the directory of the source, relative to the worktree root, and tasks run in it.
+    /// Tasks from a source outside of the worktree root have its directory in their labels,
+    /// so that the ones of different sources can be told apart.
+    /// `file_names` lists the names of the files in a directory relative to the source one,
+    /// used to detect things like the package manager from the lock files present.
+    pub fn task_templates(
+        &self,
+        contents: &str,
+        directory: &Path,
+        file_names: &dyn Fn(&Path) -> Vec<String>,
+    ) -> anyhow::Result<TaskTemplates> {
+        let tasks = match self {
+            Self::PackageJson => package_json_tasks(contents, &file_names(Path::new("")))?,
+            Self::Makefile => makefile_tasks(contents),
+            Self::Justfile => justfile_tasks(contents),
+            Self::Taskfile => taskfile_tasks(contents),
+            Self::Cargo => cargo_tasks(contents, file_names)?,
+            Self::CargoConfig => cargo_alias_tasks(contents)?,
+            Self::Pyproject => pyproject_tasks(contents, &file_names(Path::new("")))?,
+        };
+        let directory =
+            (directory != Path::new("")).then(|| directory.to_string_lossy().replace('\\', "/"));
+        let cwd = directory
+            .as_ref()
+            .map(|directory| format!("${{ZED_WORKTREE_ROOT}}/{directory}"));
+        Ok(TaskTemplates(
+            tasks
+                .into_iter()
+                .map(|(command, args)| {
+                    let mut label = std::iter::once(command.as_str())
+                        .chain(args.iter().map(String::as_str))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    if let Some(directory) = &directory {
+                        label = format!("{label} ({directory})");
+                    }
+                    TaskTemplate {
+                        label,
+                        problem_matcher: if command == "cargo" {
+                            vec![ProblemMatcher::BuiltIn("$rustc".to_string())]
+                        } else {
+                            Vec::new()
+                        },
+                        command,
+                        args,
+                        cwd: cwd.clone(),
+                        ..TaskTemplate::default()
+                    }
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// A command and its arguments.
+type DetectedTask = (String, Vec<String>);
+
+fn task(command: &str, args: &[&str]) -> DetectedTask {
+    (
+        command.to_string(),
+        args.iter().map(|arg| arg.to_string()).collect(),
+    )
+}
+
+fn package_json_tasks(contents: &str, file_names: &[String]) -> anyhow::Result<Vec<DetectedTask>> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PackageJson {
+        #[serde(default)]
+        scripts: serde_json_lenient::Map<String, serde_json_lenient::Value>,
+        package_manager: Option<String>,
+    }
+
+    let package_json =
+        serde_json_lenient::from_str::<PackageJson>(contents).context("parsing package.json")?;
+    let has_file = |name: &str| file_names.iter().any(|file_name| file_name == name);
+    // The `packageManager` field looks like `pnpm@9.1.0`.
+    let package_manager = match package_json
+        .package_manager
+        .as_deref()
+        .and_then(|package_manager| package_manager.split('@').next())
+    {
+        Some(package_manager @ ("npm" | "yarn" | "pnpm" | "bun")) => package_manager,
+        _ if has_file("bun.lockb") || has_file("bun.lock") => "bun",
+        _ if has_file("pnpm-lock.yaml") => "pnpm",
+        _ if has_file("yarn.lock") => "yarn",
+        _ => "npm",
+    };
+    Ok(package_json
+        .scripts
+        .keys()
+        .map(|script| task(package_manager, &["run", script]))
+        .collect())
+}
+
+fn makefile_tasks(contents: &str) -> Vec<DetectedTask> {
+    let mut seen = HashSet::default();
+    let mut tasks = Vec::new();
+    for line in contents.lines() {
+        // Recipe lines start with a tab, and the ones with other whitespace are not rules either.
+        if line.starts_with(char::is_whitespace) || line.starts_with('#') {
+            continue;
+        }
+        let Some((targets, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // Skip variable assignments like `A := b` or `A = b:c`.
+        if rest.starts_with('=') || targets.contains(['=', '$', '%']) {
+            continue;
+        }
+        for target in targets.split_whitespace() {
+            // Special targets like `.PHONY` are not meant to be run.
+            if !target.starts_with('.') && seen.insert(target.to_string()) {
+                tasks.push(task("make", &[target]));
+            }
+        }
+    }
+    tasks
+}
+
+fn justfile_tasks(contents: &str) -> Vec<DetectedTask> {
+    let recipe_regex = Regex::new(r"^@?([A-Za-z_][A-Za-z0-9_-]*)([^:]*):(.*)$").unwrap();
+    let mut tasks = Vec::new();
+    let mut private = false;
+    for line in contents.lines() {
+        if line.starts_with(char::is_whitespace) || line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            private |= line.contains("private");
+            continue;
+        }
+        let is_private = std::mem::take(&mut private);
+        let Some(captures) = recipe_regex.captures(line) else {
+            continue;
+        };
+        let name = &captures[1];
+        let parameters = &captures[2];
+        // Skip assignments like `version := "1.0"` and settings like `set shell := ["bash", "-c"]`.
+        if captures[3].starts_with('=')
+            || ["alias", "export", "import", "mod", "set"].contains(&name)
+            || is_private
+            || name.starts_with('_')
+        {
+            continue;
+        }
+        // Recipes with parameters that have no default values cannot be run without arguments.
+        // Exported parameters start with `$`, variadic ones with `+` (one or more) or `*` (zero or more).
+        let has_required_parameters = parameters.split_whitespace().any(|parameter| {
+            let parameter = parameter.trim_start_matches('$');
+            !parameter.contains('=') && !parameter.starts_with('*')
+        });
+        if !has_required_parameters {
+            tasks.push(task("just", &[name]));
+        }
+    }
+    tasks
+}
+
+fn taskfile_tasks(contents: &str) -> Vec<DetectedTask> {
+    let task_regex = Regex::new(r#"^(\s+)("[^"]+"|'[^']+'|[A-Za-z0-9_:.-]+):"#).unwrap();
+    let mut tasks = Vec::new();
+    let mut in_tasks = false;
+    let mut task_indent = None;
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            in_tasks = line.trim_end() == "tasks:";
+            task_indent = None;
+            continue;
+        }
+        if !in_tasks {
+            continue;
+        }
+        let Some(captures) = task_regex.captures(line) else {
+            continue;
+        };
+        let indent = captures[1].len();
+        // Task names are the keys with the smallest indentation, deeper ones are their fields.
+        if *task_indent.get_or_insert(indent) == indent {
+            let name = captures[2].trim_matches(['"', '\'']);
+            tasks.push(task("task", &[name]));
+        }
+    }
+    tasks
+}
+
+fn cargo_tasks(
+    contents: &str,
+    file_names: &dyn Fn(&Path) -> Vec<String>,
+) -> anyhow::Result<Vec<DetectedTask>> {
+    #[derive(Deserialize)]
+    struct CargoToml {
+        package: Option<Package>,
+        #[serde(default)]
+        bin: Vec<Target>,
+        #[serde(default)]
+        example: Vec<Target>,
+    }
+
+    #[derive(Deserialize)]
+    struct Package {
+        name: String,
+        autobins: Option<bool>,
+        autoexamples: Option<bool>,
+    }
+
+    #[derive(Deserialize)]
+    struct Target {
+        name: String,
+    }
+
+    let cargo_toml = toml::from_str::<CargoToml>(contents).context("parsing Cargo.toml")?;
+    // Workspace manifests have no targets of their own.
+    let Some(package) = cargo_toml.package else {
+        return Ok(Vec::new());
+    };
+    let rust_files = |directory: &str| {
+        file_names(Path::new(directory))
+            .into_iter()
+            .filter_map(|file_name| Some(file_name.strip_suffix(".rs")?.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    let mut bins = cargo_toml
+        .bin
+        .into_iter()
+        .map(|bin| bin.name)
+        .collect::<Vec<_>>();
+    if package.autobins != Some(false) {
+        if rust_files("src").iter().any(|file| file == "main") {
+            bins.push(package.name.clone());
+        }
+        bins.extend(rust_files("src/bin"));
+    }
+    let mut examples = cargo_toml
+        .example
+        .into_iter()
+        .map(|example| example.name)
+        .collect::<Vec<_>>();
+    if package.autoexamples != Some(false) {
+        examples.extend(rust_files("examples"));
+    }
+
+    let mut seen = HashSet::default();
+    Ok(bins
+        .iter()
+        .filter(|bin| seen.insert(("bin", bin.as_str())))
+        .map(|bin| task("cargo", &["run", "--bin", bin]))
+        .chain(
+            examples
+                .iter()
+                .filter(|example| seen.insert(("example", example.as_str())))
+                .map(|example| task("cargo", &["run", "--example", example])),
+        )
+        .collect())
+}
+
+fn cargo_alias_tasks(contents: &str) -> anyhow::Result<Vec<DetectedTask>> {
+    #[derive(Deserialize)]
+    struct CargoConfig {
+        #[serde(default)]
+        alias: toml::Table,
+    }
+
+    let cargo_config =
+        toml::from_str::<CargoConfig>(contents).context("parsing Cargo configuration")?;
+    Ok(cargo_config
+        .alias
+        .keys()
+        .map(|alias| task("cargo", &[alias]))
+        .collect())
+}
+
+fn pyproject_tasks(contents: &str, file_names: &[String]) -> anyhow::Result<Vec<DetectedTask>> {
+    let pyproject = toml::from_str::<toml::Table>(contents).context("parsing pyproject.toml")?;
+    let table = |path: &[&str]| {
+        path.iter()
+            .try_fold(&pyproject, |table, key| table.get(*key)?.as_table())
+    };
+    let has_file = |name: &str| file_names.iter().any(|file_name| file_name == name);
+
+    // Scripts are installed into the project's environment, which the project's tool activates.
+    let runner: &[&str] = if has_file("uv.lock") {
+        &["uv", "run"]
+    } else if has_file("poetry.lock") || table(&["tool", "poetry"]).is_some() {
+        &["poetry", "run"]
+    } else if has_file("pdm.lock") {
+        &["pdm", "run"]
+    } else {
+        &[]
+    };
+    let mut seen = HashSet::default();
+    let scripts = table(&["project", "scripts"])
+        .into_iter()
+        .chain(table(&["tool", "poetry", "scripts"]))
+        .flat_map(|scripts| scripts.keys())
+        .filter(|script| seen.insert(script.as_str()))
+        .map(|script| match runner.split_first() {
+            Some((command, args)) => {
+                let mut args = args.to_vec();
+                args.push(script.as_str());
+                task(command, &args)
+            }
+            None => task(script, &[]),
+        });
+    let pdm_scripts = table(&["tool", "pdm", "scripts"])
+        .into_iter()
+        .flat_map(|scripts| scripts.keys())
+        // `_` holds the settings shared by all scripts.
+        .filter(|script| *script != "_")
+        .map(|script| task("pdm", &["run", script]));
+    Ok(scripts.chain(pdm_scripts).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(
+        source: DetectedTaskSource,
+        contents: &str,
+        file_names: &[(&str, &[&str])],
+    ) -> Vec<String> {
+        source
+            .task_templates(contents, Path::new(""), &|directory| {
+                file_names
+                    .iter()
+                    .find(|(name, _)| Path::new(name) == directory)
+                    .map(|(_, files)| files.iter().map(|file| file.to_string()).collect())
+                    .unwrap_or_default()
+            })
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|template| template.label)
+            .collect()
+    }
+
+    #[test]
+    fn test_source_for_path() {
+        assert_eq!(
+            DetectedTaskSource::for_path(Path::new("web/package.json")),
+            Some((DetectedTaskSource::PackageJson, Path::new("web")))
+        );
+        assert_eq!(
+            DetectedTaskSource::for_path(Path::new(".cargo/config.toml")),
+            Some((DetectedTaskSource::CargoConfig, Path::new("")))
+        );
+        assert_eq!(
+            DetectedTaskSource::for_path(Path::new("src/Makefile.am")),
+            None
+        );
+        assert_eq!(
+            DetectedTaskSource::for_path(Path::new("crates/foo/Cargo.toml")),
+            Some((DetectedTaskSource::Cargo, Path::new("crates/foo")))
+        );
+    }
+
+    #[test]
+    fn test_package_json_tasks() {
+        let package_json = r#"{ "name": "app", "scripts": { "build": "tsc", "test": "jest" } }"#;
+        assert_eq!(
+            labels(DetectedTaskSource::PackageJson, package_json, &[]),
+            vec!["npm run build", "npm run test"]
+        );
+        assert_eq!(
+            labels(
+                DetectedTaskSource::PackageJson,
+                package_json,
+                &[("", &["package.json", "pnpm-lock.yaml"])]
+            ),
+            vec!["pnpm run build", "pnpm run test"]
+        );
+        let package_json = r#"{ "packageManager": "yarn@4.1.0", "scripts": { "dev": "vite" } }"#;
+        assert_eq!(
+            labels(
+                DetectedTaskSource::PackageJson,
+                package_json,
+                &[("", &["package-lock.json"])]
+            ),
+            vec!["yarn run dev"]
+        );
+
+        let templates = DetectedTaskSource::PackageJson
+            .task_templates(package_json, Path::new("web/app"), &|_| Vec::new())
+            .unwrap();
+        assert_eq!(
+            templates.0,
+            vec![TaskTemplate {
+                label: "yarn run dev (web/app)".to_string(),
+                command: "yarn".to_string(),
+                args: vec!["run".to_string(), "dev".to_string()],
+                cwd: Some("${ZED_WORKTREE_ROOT}/web/app".to_string()),
+                ..TaskTemplate::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_makefile_tasks() {
+        let makefile = "\
+CC := gcc
+FLAGS = -O2
+.PHONY: all clean
+
+all: build test
+build test: main.c
+\t$(CC) $(FLAGS) main.c
+%.o: %.c
+\t$(CC) -c $<
+clean:
+\trm -f *.o
+";
+        assert_eq!(
+            labels(DetectedTaskSource::Makefile, makefile, &[]),
+            vec!["make all", "make build", "make test", "make clean"]
+        );
+    }
+
+    #[test]
+    fn test_justfile_tasks() {
+        let justfile = "\
+set shell := [\"bash\", \"-c\"]
+version := \"1.0\"
+alias b := build
+
+# Builds the project
+build:
+    cargo build
+
+@test *args:
+    cargo test {{args}}
+
+deploy target:
+    ./deploy.sh {{target}}
+
+serve port=\"8080\": build
+    ./serve.sh {{port}}
+
+[private]
+helper:
+    echo hidden
+
+_other:
+    echo hidden
+";
+        assert_eq!(
+            labels(DetectedTaskSource::Justfile, justfile, &[]),
+            vec!["just build", "just test", "just serve"]
+        );
+    }
+
+    #[test]
+    fn test_taskfile_tasks() {
+        let taskfile = "\
+version: '3'
+
+vars:
+  GREETING: Hello
+
+tasks:
+  build:
+    desc: Build the app
+    cmds:
+      - go build ./...
+  'docs:serve':
+    cmds:
+      - mkdocs serve
+
+includes:
+  other: ./other
+";
+        assert_eq!(
+            labels(DetectedTaskSource::Taskfile, taskfile, &[]),
+            vec!["task build", "task docs:serve"]
+        );
+    }
+
+    #[test]
+    fn test_cargo_tasks() {
+        let cargo_toml = r#"
+[package]
+name = "app"
+version = "0.1.0"
+
+[[bin]]
+name = "tool"
+path = "tools/tool.rs"
+
+[[example]]
+name = "custom"
+path = "demos/custom.rs"
+"#;
+        assert_eq!(
+            labels(
+                DetectedTaskSource::Cargo,
+                cargo_toml,
+                &[
+                    ("src", &["main.rs", "lib.rs"]),
+                    ("src/bin", &["helper.rs"]),
+                    ("examples", &["basic.rs", "README.md"]),
+                ]
+            ),
+            vec![
+                "cargo run --bin tool",
+                "cargo run --bin app",
+                "cargo run --bin helper",
+                "cargo run --example custom",
+                "cargo run --example basic",
+            ]
+        );
+        assert!(labels(
+            DetectedTaskSource::Cargo,
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+            &[]
+        )
+        .is_empty());
+
+        let cargo_config = "[alias]\nxtask = \"run --package xtask --\"\n\n[build]\njobs = 4\n";
+        assert_eq!(
+            labels(DetectedTaskSource::CargoConfig, cargo_config, &[]),
+            vec!["cargo xtask"]
+        );
+    }
+
+    #[test]
+    fn test_pyproject_tasks() {
+        let pyproject = r#"
+[project]
+name = "app"
+
+[project.scripts]
+serve = "app.server:main"
+
+[tool.pdm.scripts]
+_ = { env_file = ".env" }
+lint = "ruff check ."
+"#;
+        assert_eq!(
+            labels(DetectedTaskSource::Pyproject, pyproject, &[]),
+            vec!["serve", "pdm run lint"]
+        );
+        assert_eq!(
+            labels(
+                DetectedTaskSource::Pyproject,
+                pyproject,
+                &[("", &["pyproject.toml", "uv.lock"])]
+            ),
+            vec!["uv run serve", "pdm run lint"]
+        );
+        let pyproject =
+            "[tool.poetry]\nname = \"app\"\n\n[tool.poetry.scripts]\ncli = \"app.cli:run\"\n";
+        assert_eq!(
+            labels(DetectedTaskSource::Pyproject, pyproject, &[]),
+            vec!["poetry run cli"]
+        );
+    }
+}
@@ -1,6 +1,8 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod detected_source;
+mod problem_matcher;
 pub mod static_source;
 mod task_template;
 mod vscode_format;
@@ -13,7 +15,12 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use detected_source::DetectedTaskSource;
+pub use problem_matcher::{
+    CustomProblemMatcher, Problem, ProblemCollector, ProblemMatcher, ProblemPattern,
+    ProblemSeverity,
+};
+pub use task_template::{DependsOrder, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
 pub use vscode_format::VsCodeTaskFile;
 
 /// Task identifier, unique within the application.
@@ -55,6 +62,14 @@ pub struct SpawnInTerminal {
     pub show_summary: bool,
     /// Whether to show the command line in the task output.
     pub show_command: bool,
+    /// A regular expression that marks the task as ready once its output matches it.
+    pub ready_pattern: Option<String>,
+    /// How to run the `dependencies`.
+    pub depends_order: DependsOrder,
+    /// Tasks to run before this one, resolved with the same context.
+    pub dependencies: Vec<SpawnInTerminal>,
+    /// Matchers turning the task output into diagnostics.
+    pub problem_matchers: Vec<ProblemMatcher>,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particualar [`TaskContext`] and now is ready to spawn the actual task.
@@ -0,0 +1,621 @@
+//! Problem matchers turn the output of tasks into diagnostics, using regular expressions
+//! that capture the file, position, severity and message of each reported problem.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context as _};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt as _;
+
+/// A way to find problems in the task output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// Name of a built-in matcher: `$rustc`, `$gcc`, `$tsc`, `$eslint` or `$go`.
+    BuiltIn(String),
+    /// A matcher defined with regular expressions.
+    Custom(CustomProblemMatcher),
+}
+
+/// A problem matcher defined with regular expressions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CustomProblemMatcher {
+    /// Name of a built-in matcher to take the fields that are not set here from.
+    #[serde(default)]
+    pub base: Option<String>,
+    /// Source name of the diagnostics produced, defaults to the base matcher's one or to `task`.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Patterns matching consecutive lines of a single problem.
+    /// The last one may `loop`, to match several problems sharing the fields of the previous patterns.
+    #[serde(default)]
+    pub pattern: Vec<ProblemPattern>,
+    /// Severity of the problems whose pattern does not capture one, defaults to `error`.
+    #[serde(default)]
+    pub severity: Option<ProblemSeverity>,
+    /// Directory to resolve relative file paths against, defaults to the task's working directory.
+    #[serde(default)]
+    pub file_location: Option<String>,
+}
+
+/// A regular expression for a line of the task output, along with the indices of its capture groups
+/// containing the parts of a problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match the line with.
+    pub regexp: String,
+    /// Capture group with the path of the file.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// Capture group with the 1-based line number.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// Capture group with the 1-based column number.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// Capture group with the 1-based line number where the problem ends.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// Capture group with the 1-based column number where the problem ends.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// Capture group with the severity: `error`, `warning`, `info` or `hint`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// Capture group with the code of the problem.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// Capture group with the message.
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the pattern, being the last one, may match several lines in a row, producing a problem for each.
+    #[serde(default, rename = "loop")]
+    pub repeat: bool,
+}
+
+/// How severe a problem is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error.
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Info,
+    /// A hint.
+    Hint,
+}
+
+impl ProblemSeverity {
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().to_lowercase();
+        if text.starts_with("err") || text.starts_with("fatal") {
+            Some(Self::Error)
+        } else if text.starts_with("warn") {
+            Some(Self::Warning)
+        } else if text.starts_with("info") || text.starts_with("note") {
+            Some(Self::Info)
+        } else if text.starts_with("hint") || text.starts_with("help") {
+            Some(Self::Hint)
+        } else {
+            None
+        }
+    }
+}
+
+/// A problem found in the task output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// Source name of the matcher that found the problem.
+    pub source: String,
+    /// Path of the file with the problem, absolute if it could be resolved.
+    pub path: PathBuf,
+    /// 1-based line number.
+    pub line: u32,
+    /// 1-based column number.
+    pub column: Option<u32>,
+    /// 1-based line number where the problem ends.
+    pub end_line: Option<u32>,
+    /// 1-based column number where the problem ends.
+    pub end_column: Option<u32>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// Code of the problem.
+    pub code: Option<String>,
+    /// Description of the problem.
+    pub message: String,
+}
+
+const BUILT_IN_SOURCES: &[(&str, &str)] = &[
+    ("$rustc", "rustc"),
+    ("$cargo", "rustc"),
+    ("$gcc", "gcc"),
+    ("$clang", "gcc"),
+    ("$tsc", "tsc"),
+    ("$tsc-watch", "tsc"),
+    ("$eslint", "eslint"),
+    ("$eslint-stylish", "eslint"),
+    ("$go", "go"),
+];
+
+fn built_in_matcher(name: &str) -> Option<CustomProblemMatcher> {
+    let source = BUILT_IN_SOURCES
+        .iter()
+        .find(|(built_in_name, _)| *built_in_name == name)?
+        .1;
+    let pattern = match source {
+        "rustc" => vec![
+            ProblemPattern {
+                regexp: r"^(warning|warn|error)(?:\[(\S+?)\])?: (.*)$".to_string(),
+                severity: Some(1),
+                code: Some(2),
+                message: Some(3),
+                ..ProblemPattern::default()
+            },
+            ProblemPattern {
+                regexp: r"^\s*-->\s+(.*?):(\d+):(\d+)\s*$".to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                ..ProblemPattern::default()
+            },
+        ],
+        "gcc" => vec![ProblemPattern {
+            regexp: r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$"
+                .to_string(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            message: Some(5),
+            ..ProblemPattern::default()
+        }],
+        "tsc" => vec![ProblemPattern {
+            regexp: r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$"
+                .to_string(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            code: Some(5),
+            message: Some(6),
+            ..ProblemPattern::default()
+        }],
+        "eslint" => vec![
+            ProblemPattern {
+                regexp: r"^([^\s].*)$".to_string(),
+                file: Some(1),
+                ..ProblemPattern::default()
+            },
+            ProblemPattern {
+                regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$"
+                    .to_string(),
+                line: Some(1),
+                column: Some(2),
+                severity: Some(3),
+                message: Some(4),
+                code: Some(5),
+                repeat: true,
+                ..ProblemPattern::default()
+            },
+        ],
+        "go" => vec![ProblemPattern {
+            regexp: r"^\s*([^:\s][^:]*\.go):(\d+)(?::(\d+))?:\s+(.*)$".to_string(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            message: Some(4),
+            ..ProblemPattern::default()
+        }],
+        _ => return None,
+    };
+    Some(CustomProblemMatcher {
+        base: None,
+        source: Some(source.to_string()),
+        pattern,
+        severity: None,
+        file_location: None,
+    })
+}
+
+struct CompiledMatcher {
+    source: String,
+    patterns: Vec<(Regex, ProblemPattern)>,
+    severity: ProblemSeverity,
+    file_location: Option<PathBuf>,
+    /// Index of the pattern to match the next line with.
+    next_pattern: usize,
+    /// Fields captured by the previous patterns of the current problem.
+    captured: CapturedProblem,
+}
+
+#[derive(Clone, Default)]
+struct CapturedProblem {
+    file: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl CompiledMatcher {
+    fn new(matcher: &ProblemMatcher) -> anyhow::Result<Self> {
+        let matcher = match matcher {
+            ProblemMatcher::BuiltIn(name) => {
+                built_in_matcher(name).ok_or_else(|| anyhow!("Unknown problem matcher {name}"))?
+            }
+            ProblemMatcher::Custom(matcher) => match &matcher.base {
+                Some(base) => {
+                    let base = built_in_matcher(base)
+                        .ok_or_else(|| anyhow!("Unknown base problem matcher {base}"))?;
+                    CustomProblemMatcher {
+                        base: None,
+                        source: matcher.source.clone().or(base.source),
+                        pattern: if matcher.pattern.is_empty() {
+                            base.pattern
+                        } else {
+                            matcher.pattern.clone()
+                        },
+                        severity: matcher.severity.or(base.severity),
+                        file_location: matcher.file_location.clone(),
+                    }
+                }
+                None => matcher.clone(),
+            },
+        };
+        anyhow::ensure!(
+            !matcher.pattern.is_empty(),
+            "Problem matcher has no patterns"
+        );
+        let patterns = matcher
+            .pattern
+            .into_iter()
+            .map(|pattern| {
+                let regex = Regex::new(&pattern.regexp)
+                    .with_context(|| format!("Invalid problem pattern {}", pattern.regexp))?;
+                anyhow::Ok((regex, pattern))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            source: matcher.source.unwrap_or_else(|| "task".to_string()),
+            patterns,
+            severity: matcher.severity.unwrap_or_default(),
+            file_location: matcher.file_location.map(PathBuf::from),
+            next_pattern: 0,
+            captured: CapturedProblem::default(),
+        })
+    }
+
+    fn process_line(&mut self, line: &str, cwd: Option<&Path>) -> Option<Problem> {
+        if self.next_pattern > 0 {
+            if let Some(problem) = self.match_pattern(self.next_pattern, line, cwd) {
+                return problem;
+            }
+            // The problem ended (or never got complete), the line may start a new one.
+            self.next_pattern = 0;
+        }
+        self.captured = CapturedProblem::default();
+        self.match_pattern(0, line, cwd).flatten()
+    }
+
+    /// Returns `None` if the pattern did not match the line, and the completed problem otherwise, if any.
+    fn match_pattern(
+        &mut self,
+        index: usize,
+        line: &str,
+        cwd: Option<&Path>,
+    ) -> Option<Option<Problem>> {
+        let (regex, pattern) = &self.patterns[index];
+        let captures = regex.captures(line)?;
+        let group = |index: Option<usize>| {
+            index
+                .and_then(|index| captures.get(index))
+                .map(|capture| capture.as_str().trim())
+                .filter(|capture| !capture.is_empty())
+        };
+        let number = |index: Option<usize>| group(index).and_then(|group| group.parse().ok());
+
+        let is_last = index + 1 == self.patterns.len();
+        // Looping patterns capture their fields anew for every problem.
+        let mut captured = if is_last && pattern.repeat {
+            self.captured.clone()
+        } else {
+            std::mem::take(&mut self.captured)
+        };
+        captured.file = group(pattern.file).map(str::to_owned).or(captured.file);
+        captured.line = number(pattern.line).or(captured.line);
+        captured.column = number(pattern.column).or(captured.column);
+        captured.end_line = number(pattern.end_line).or(captured.end_line);
+        captured.end_column = number(pattern.end_column).or(captured.end_column);
+        captured.severity = group(pattern.severity)
+            .and_then(ProblemSeverity::parse)
+            .or(captured.severity);
+        captured.code = group(pattern.code).map(str::to_owned).or(captured.code);
+        captured.message = group(pattern.message)
+            .map(str::to_owned)
+            .or(captured.message);
+
+        if !is_last {
+            self.captured = captured;
+            self.next_pattern = index + 1;
+            return Some(None);
+        }
+        self.next_pattern = if pattern.repeat { index } else { 0 };
+        Some(self.problem(captured, cwd))
+    }
+
+    fn problem(&self, captured: CapturedProblem, cwd: Option<&Path>) -> Option<Problem> {
+        let path = PathBuf::from(captured.file?);
+        let path = match self.file_location.as_deref().or(cwd) {
+            Some(directory) if path.is_relative() => directory.join(path),
+            _ => path,
+        };
+        Some(Problem {
+            source: self.source.clone(),
+            path,
+            line: captured.line.unwrap_or(1),
+            column: captured.column,
+            end_line: captured.end_line,
+            end_column: captured.end_column,
+            severity: captured.severity.unwrap_or(self.severity),
+            code: captured.code,
+            message: captured.message?,
+        })
+    }
+}
+
+/// Finds problems in the task output, line by line.
+pub struct ProblemCollector {
+    matchers: Vec<CompiledMatcher>,
+    cwd: Option<PathBuf>,
+    problems: Vec<Problem>,
+}
+
+impl ProblemCollector {
+    /// Prepares the matchers given, skipping the unknown ones and the ones with invalid patterns.
+    /// Relative paths in the output are resolved against `cwd`, unless a matcher has its own file location.
+    pub fn new(matchers: &[ProblemMatcher], cwd: Option<PathBuf>) -> Self {
+        Self {
+            matchers: matchers
+                .iter()
+                .filter_map(|matcher| CompiledMatcher::new(matcher).log_err())
+                .collect(),
+            cwd,
+            problems: Vec::new(),
+        }
+    }
+
+    /// Matches the next line of the output, returns whether any new problems were found.
+    pub fn process_line(&mut self, line: &str) -> bool {
+        let mut found = false;
+        for matcher in &mut self.matchers {
+            if let Some(problem) = matcher.process_line(line, self.cwd.as_deref()) {
+                self.problems.push(problem);
+                found = true;
+            }
+        }
+        found
+    }
+
+    /// Source names of all the matchers, even those that found no problems.
+    pub fn sources(&self) -> impl Iterator<Item = &str> {
+        let mut sources = self
+            .matchers
+            .iter()
+            .map(|matcher| matcher.source.as_str())
+            .collect::<Vec<_>>();
+        sources.sort_unstable();
+        sources.dedup();
+        sources.into_iter()
+    }
+
+    /// All problems found so far.
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(matcher: ProblemMatcher, output: &str) -> Vec<Problem> {
+        let mut collector = ProblemCollector::new(&[matcher], Some(PathBuf::from("/project")));
+        for line in output.lines() {
+            collector.process_line(line);
+        }
+        collector.problems().to_vec()
+    }
+
+    #[test]
+    fn test_rustc_problems() {
+        let output = "\
+   Compiling zed v0.1.0
+error[E0425]: cannot find value `x` in this scope
+  --> src/main.rs:2:13
+   |
+2  |     let y = x;
+   |             ^ not found in this scope
+
+warning: unused variable: `y`
+ --> src/lib.rs:10:9
+warning: `zed` (bin \"zed\") generated 1 warning
+error: could not compile `zed`";
+        assert_eq!(
+            collect(ProblemMatcher::BuiltIn("$rustc".to_string()), output),
+            vec![
+                Problem {
+                    source: "rustc".to_string(),
+                    path: PathBuf::from("/project/src/main.rs"),
+                    line: 2,
+                    column: Some(13),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0425".to_string()),
+                    message: "cannot find value `x` in this scope".to_string(),
+                },
+                Problem {
+                    source: "rustc".to_string(),
+                    path: PathBuf::from("/project/src/lib.rs"),
+                    line: 10,
+                    column: Some(9),
+                    end_line: None,
+                    end_column: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `y`".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_line_problems() {
+        let problems = collect(
+            ProblemMatcher::BuiltIn("$gcc".to_string()),
+            "main.c:3:5: warning: implicit declaration of function 'foo'\n\
+             /abs/util.c:7:1: fatal error: 'bar.h' file not found",
+        );
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (
+                    problem.path.clone(),
+                    problem.line,
+                    problem.severity,
+                    problem.message.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    PathBuf::from("/project/main.c"),
+                    3,
+                    ProblemSeverity::Warning,
+                    "implicit declaration of function 'foo'"
+                ),
+                (
+                    PathBuf::from("/abs/util.c"),
+                    7,
+                    ProblemSeverity::Error,
+                    "'bar.h' file not found"
+                ),
+            ]
+        );
+
+        let problems = collect(
+            ProblemMatcher::BuiltIn("$tsc".to_string()),
+            "src/index.ts(4,7): error TS2322: Type 'string' is not assignable to type 'number'.",
+        );
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].code.as_deref(), Some("TS2322"));
+        assert_eq!((problems[0].line, problems[0].column), (4, Some(7)));
+
+        let problems = collect(
+            ProblemMatcher::BuiltIn("$go".to_string()),
+            "# example\n./main.go:12:2: undefined: foo",
+        );
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].path, PathBuf::from("/project/main.go"));
+        assert_eq!(problems[0].message, "undefined: foo");
+    }
+
+    #[test]
+    fn test_looping_problems() {
+        let output = "\
+/project/src/a.js
+  1:10  error    'x' is defined but never used  no-unused-vars
+  3:1   warning  Unexpected console statement   no-console
+
+/project/src/b.js
+  7:3  error  Missing semicolon  semi
+
+✖ 3 problems (2 errors, 1 warning)";
+        let problems = collect(ProblemMatcher::BuiltIn("$eslint".to_string()), output);
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (
+                    problem.path.to_string_lossy().into_owned(),
+                    problem.line,
+                    problem.severity,
+                    problem.code.clone().unwrap_or_default()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "/project/src/a.js".to_string(),
+                    1,
+                    ProblemSeverity::Error,
+                    "no-unused-vars".to_string()
+                ),
+                (
+                    "/project/src/a.js".to_string(),
+                    3,
+                    ProblemSeverity::Warning,
+                    "no-console".to_string()
+                ),
+                (
+                    "/project/src/b.js".to_string(),
+                    7,
+                    ProblemSeverity::Error,
+                    "semi".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_problem_matcher() {
+        let matcher: ProblemMatcher = serde_json_lenient::from_str(
+            r#"{
+                "source": "lint",
+                "severity": "warning",
+                "file_location": "/other",
+                "pattern": [
+                    { "regexp": "^In (.*):$", "file": 1 },
+                    { "regexp": "^line (\\d+): (.*)$", "line": 1, "message": 2 }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let problems = collect(matcher, "In src/x.py:\nline 4: bad\nline 5: worse\n");
+        assert_eq!(
+            problems,
+            vec![Problem {
+                source: "lint".to_string(),
+                path: PathBuf::from("/other/src/x.py"),
+                line: 4,
+                column: None,
+                end_line: None,
+                end_column: None,
+                severity: ProblemSeverity::Warning,
+                code: None,
+                message: "bad".to_string(),
+            }],
+            "Patterns without `loop` produce a single problem"
+        );
+
+        let collector = ProblemCollector::new(
+            &[
+                ProblemMatcher::BuiltIn("$nope".to_string()),
+                ProblemMatcher::BuiltIn("$go".to_string()),
+            ],
+            None,
+        );
+        assert_eq!(
+            collector.sources().collect::<Vec<_>>(),
+            vec!["go"],
+            "Unknown matchers should be skipped"
+        );
+    }
+}
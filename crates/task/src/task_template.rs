@@ -9,7 +9,7 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
+    ProblemMatcher, ResolvedTask, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
     ZED_VARIABLE_NAME_PREFIX,
 };
 
@@ -64,6 +64,36 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Labels of the tasks to run before this one.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks from `depends_on`:
+    /// * `parallel` — start all of them at once (default)
+    /// * `sequence` — start each of them after the previous one is ready
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// A regular expression that marks the task as ready for the tasks that depend on it,
+    /// once any line of its output matches it. Allows long running (background) tasks,
+    /// such as watchers or servers, to be dependencies.
+    /// Tasks without it are ready when they finish successfully.
+    #[serde(default)]
+    pub ready_pattern: Option<String>,
+    /// Matchers that turn the task output into diagnostics, replaced on every run of the task.
+    /// Either names of the built-in ones (`$rustc`, `$gcc`, `$tsc`, `$eslint`, `$go`),
+    /// or custom ones defined with regular expressions.
+    #[serde(default)]
+    pub problem_matcher: Vec<ProblemMatcher>,
+}
+
+/// How to run the tasks that a task depends on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Start all dependencies at once.
+    #[default]
+    Parallel,
+    /// Start each dependency after the previous one is ready.
+    Sequence,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -109,6 +139,15 @@ impl TaskTemplates {
 }
 
 impl TaskTemplate {
+    /// The start of the [`TaskId`] of every task resolved from this template with the `id_base` given,
+    /// whatever its [`TaskContext`].
+    pub fn task_id_prefix(&self, id_base: &str) -> Option<String> {
+        let task_hash = to_hex_hash(self)
+            .context("hashing task template")
+            .log_err()?;
+        Some(format!("{id_base}_{task_hash}_"))
+    }
+
     /// Replaces all `VariableName` task variables in the task template string fields.
     /// If any replacement fails or the new string substitutions still have [`ZED_VARIABLE_NAME_PREFIX`],
     /// `None` is returned.
@@ -116,7 +155,9 @@ impl TaskTemplate {
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty()
+            || (self.command.trim().is_empty() && self.depends_on.is_empty())
+        {
             return None;
         }
 
@@ -183,13 +224,31 @@ impl TaskTemplate {
             &mut substituted_variables,
         )?;
 
-        let task_hash = to_hex_hash(self)
-            .context("hashing task template")
-            .log_err()?;
+        let problem_matchers = self
+            .problem_matcher
+            .iter()
+            .map(|matcher| match matcher {
+                ProblemMatcher::Custom(custom) => {
+                    let mut custom = custom.clone();
+                    if let Some(file_location) = &custom.file_location {
+                        custom.file_location = Some(substitute_all_template_variables_in_str(
+                            file_location,
+                            &task_variables,
+                            &variable_names,
+                            &mut substituted_variables,
+                        )?);
+                    }
+                    Some(ProblemMatcher::Custom(custom))
+                }
+                built_in => Some(built_in.clone()),
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let id_prefix = self.task_id_prefix(id_base)?;
         let variables_hash = to_hex_hash(&task_variables)
             .context("hashing task variables")
             .log_err()?;
-        let id = TaskId(format!("{id_base}_{task_hash}_{variables_hash}"));
+        let id = TaskId(format!("{id_prefix}{variables_hash}"));
 
         let env = {
             // Start with the project environment as the base.
@@ -239,6 +298,10 @@ impl TaskTemplate {
                 shell: self.shell.clone(),
                 show_summary: self.show_summary,
                 show_command: self.show_command,
+                ready_pattern: self.ready_pattern.clone(),
+                depends_order: self.depends_order,
+                dependencies: Vec::new(),
+                problem_matchers,
             }),
         })
     }
@@ -387,6 +450,18 @@ mod tests {
                 "should not resolve task with blank label and/or command: {task_with_blank_property:?}"
             );
         }
+
+        let task_with_dependencies_only = TaskTemplate {
+            command: "".to_string(),
+            depends_on: vec!["other_task".to_string()],
+            ..task_with_all_properties.clone()
+        };
+        let resolved = task_with_dependencies_only
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .and_then(|task| task.resolved)
+            .expect("should resolve a task that only runs its dependencies");
+        assert_eq!(resolved.command, "");
+        assert!(resolved.dependencies.is_empty());
     }
 
     #[test]
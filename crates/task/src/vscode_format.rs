@@ -1,9 +1,12 @@
-use anyhow::bail;
+use anyhow::{bail, Context as _};
 use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    CustomProblemMatcher, ProblemMatcher, ProblemPattern, ProblemSeverity, TaskTemplate,
+    TaskTemplates, VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -41,6 +44,97 @@ enum Command {
     },
 }
 
+#[derive(Clone, Deserialize, PartialEq, Debug)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    Many(Vec<T>),
+    One(T),
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::Many(items) => items,
+            Self::One(item) => vec![item],
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, PartialEq, Debug)]
+#[serde(untagged)]
+enum VsCodeProblemMatcher {
+    Named(String),
+    Custom(VsCodeCustomProblemMatcher),
+}
+
+#[derive(Clone, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeCustomProblemMatcher {
+    base: Option<String>,
+    owner: Option<String>,
+    source: Option<String>,
+    severity: Option<ProblemSeverity>,
+    file_location: Option<OneOrMany<String>>,
+    pattern: Option<OneOrMany<VsCodeProblemPattern>>,
+}
+
+#[derive(Clone, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+    #[serde(default, rename = "loop")]
+    repeat: bool,
+}
+
+impl VsCodeProblemMatcher {
+    fn into_zed_format(self, replacer: &EnvVariableReplacer) -> ProblemMatcher {
+        let matcher = match self {
+            Self::Named(name) => return ProblemMatcher::BuiltIn(name),
+            Self::Custom(matcher) => matcher,
+        };
+        // Code resolves relative paths against the workspace folder by default.
+        let file_location = match matcher.file_location.map(OneOrMany::into_vec) {
+            Some(location) if location.first().map(String::as_str) == Some("absolute") => None,
+            Some(location) => {
+                Some(replacer.replace(location.get(1).map_or("${workspaceFolder}", String::as_str)))
+            }
+            None => None,
+        };
+        ProblemMatcher::Custom(CustomProblemMatcher {
+            base: matcher.base,
+            source: matcher.source.or(matcher.owner),
+            pattern: matcher
+                .pattern
+                .map(OneOrMany::into_vec)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|pattern| ProblemPattern {
+                    regexp: pattern.regexp,
+                    file: pattern.file,
+                    line: pattern.line,
+                    column: pattern.column,
+                    end_line: pattern.end_line,
+                    end_column: pattern.end_column,
+                    severity: pattern.severity,
+                    code: pattern.code,
+                    message: pattern.message,
+                    repeat: pattern.repeat,
+                })
+                .collect(),
+            severity: matcher.severity,
+            file_location,
+        })
+    }
+}
+
 type VsCodeEnvVariable = String;
 type ZedEnvVariable = String;
 
@@ -114,6 +208,19 @@ impl VsCodeTaskDefinition {
             ret.cwd = options.cwd.map(|cwd| replacer.replace(&cwd));
             ret.env = options.env;
         }
+        if let Some(problem_matcher) = self.other_attributes.get("problemMatcher") {
+            ret.problem_matcher =
+                serde_json_lenient::from_value::<OneOrMany<VsCodeProblemMatcher>>(
+                    problem_matcher.clone(),
+                )
+                .context("parsing `problemMatcher`")
+                .log_err()
+                .map(OneOrMany::into_vec)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|matcher| matcher.into_zed_format(replacer))
+                .collect();
+        }
         Ok(ret)
     }
 }
@@ -155,7 +262,7 @@ mod tests {
 
     use crate::{
         vscode_format::{Command, VsCodeTaskDefinition},
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        CustomProblemMatcher, ProblemMatcher, TaskTemplate, TaskTemplates, VsCodeTaskFile,
     };
 
     use super::EnvVariableReplacer;
@@ -257,6 +364,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -268,18 +376,21 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matcher: vec![ProblemMatcher::BuiltIn("$tsc-watch".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matcher: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matcher: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -355,33 +466,45 @@ mod tests {
             .iter()
             .zip(expected)
             .for_each(|(lhs, rhs)| compare_without_other_attributes(lhs.clone(), rhs));
+        let extension_matcher = |base: &str| {
+            vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                base: Some(base.to_string()),
+                file_location: Some("${ZED_WORKTREE_ROOT}/editors/code/".to_string()),
+                ..Default::default()
+            })]
+        };
         let expected = vec![
             TaskTemplate {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matcher: extension_matcher("$tsc-watch"),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matcher: extension_matcher("$tsc"),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matcher: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matcher: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matcher: extension_matcher("$tsc"),
                 ..Default::default()
             },
         ];
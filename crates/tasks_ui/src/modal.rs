@@ -8,7 +8,7 @@ use gpui::{
     View, ViewContext, VisualContext, WeakView,
 };
 use picker::{highlighted_match_with_paths::HighlightedText, Picker, PickerDelegate};
-use project::{task_store::TaskStore, TaskSourceKind};
+use project::{task_store::TaskStore, CombinedTaskStatus, TaskRunStatus, TaskSourceKind};
 use task::{ResolvedTask, TaskContext, TaskTemplate};
 use ui::{
     div, h_flex, v_flex, ActiveTheme, Button, ButtonCommon, ButtonSize, Clickable, Color,
@@ -93,7 +93,7 @@ impl TasksModalDelegate {
 
 pub(crate) struct TasksModal {
     picker: View<Picker<TasksModalDelegate>>,
-    _subscription: Subscription,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl TasksModal {
@@ -103,18 +103,25 @@ impl TasksModal {
         workspace: WeakView<Workspace>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
+        let task_inventory = task_store.read(cx).task_inventory().cloned();
         let picker = cx.new_view(|cx| {
             Picker::uniform_list(
                 TasksModalDelegate::new(task_store, task_context, workspace),
                 cx,
             )
         });
-        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+        let mut _subscriptions = vec![cx.subscribe(&picker, |_, _, _, cx| {
             cx.emit(DismissEvent);
-        });
+        })];
+        // Keep the statuses of the running tasks up to date.
+        if let Some(task_inventory) = task_inventory {
+            _subscriptions.push(cx.observe(&task_inventory, |modal, _, cx| {
+                modal.picker.update(cx, |_, cx| cx.notify());
+            }));
+        }
         Self {
             picker,
-            _subscription,
+            _subscriptions,
         }
     }
 }
@@ -300,6 +307,24 @@ impl PickerDelegate for TasksModalDelegate {
                 tooltip_label_text.push_str(&resolved.command_label);
             }
         }
+        if !template.depends_on.is_empty() {
+            if !tooltip_label_text.trim().is_empty() {
+                tooltip_label_text.push('\n');
+            }
+            tooltip_label_text.push_str("Depends on: ");
+            tooltip_label_text.push_str(&template.depends_on.join(", "));
+        }
+        let task_status = self
+            .task_store
+            .read(cx)
+            .task_inventory()
+            .and_then(|inventory| inventory.read(cx).combined_task_status(resolved_task));
+        if let Some(task_status) = task_status {
+            if !tooltip_label_text.trim().is_empty() {
+                tooltip_label_text.push('\n');
+            }
+            tooltip_label_text.push_str(&task_status_text(task_status));
+        }
         let tooltip_label = if tooltip_label_text.trim().is_empty() {
             None
         } else {
@@ -319,29 +344,37 @@ impl PickerDelegate for TasksModalDelegate {
             TaskSourceKind::Language { name } => file_icons::FileIcons::get(cx)
                 .get_type_icon(&name.to_lowercase())
                 .map(Icon::from_path),
+            TaskSourceKind::Detected {
+                path_in_worktree, ..
+            } => file_icons::FileIcons::get_icon(path_in_worktree, cx)
+                .map(Icon::from_path)
+                .or_else(|| Some(Icon::new(IconName::FileTree))),
         }
         .map(|icon| icon.color(Color::Muted).size(IconSize::Small));
         let history_run_icon = if Some(ix) <= self.divider_index {
-            Some(
-                Icon::new(IconName::HistoryRerun)
-                    .color(Color::Muted)
-                    .size(IconSize::Small)
-                    .into_any_element(),
-            )
+            Icon::new(IconName::HistoryRerun)
+                .color(Color::Muted)
+                .size(IconSize::Small)
+                .into_any_element()
         } else {
-            Some(
-                v_flex()
-                    .flex_none()
-                    .size(IconSize::Small.rems())
-                    .into_any_element(),
-            )
+            v_flex()
+                .flex_none()
+                .size(IconSize::Small.rems())
+                .into_any_element()
         };
+        let end_slot = h_flex()
+            .gap_1()
+            .when_some(task_status, |end_slot, task_status| {
+                end_slot.child(task_status_icon(task_status.status))
+            })
+            .child(history_run_icon)
+            .into_any_element();
 
         Some(
             ListItem::new(SharedString::from(format!("tasks-modal-{ix}")))
                 .inset(true)
                 .start_slot::<Icon>(icon)
-                .end_slot::<AnyElement>(history_run_icon)
+                .end_slot::<AnyElement>(end_slot)
                 .spacing(ListItemSpacing::Sparse)
                 .when_some(tooltip_label, |list_item, item_label| {
                     list_item.tooltip(move |_| item_label.clone())
@@ -508,6 +541,35 @@ impl PickerDelegate for TasksModalDelegate {
     }
 }
 
+fn task_status_icon(status: TaskRunStatus) -> Icon {
+    let (icon, color) = match status {
+        TaskRunStatus::Waiting => (IconName::CountdownTimer, Color::Muted),
+        TaskRunStatus::Running => (IconName::ArrowCircle, Color::Muted),
+        TaskRunStatus::Ready => (IconName::Check, Color::Accent),
+        TaskRunStatus::Succeeded => (IconName::Check, Color::Success),
+        TaskRunStatus::Failed => (IconName::XCircle, Color::Error),
+    };
+    Icon::new(icon).color(color).size(IconSize::Small)
+}
+
+fn task_status_text(task_status: CombinedTaskStatus) -> String {
+    let status = match task_status.status {
+        TaskRunStatus::Waiting => "Waiting for dependencies",
+        TaskRunStatus::Running => "Running",
+        TaskRunStatus::Ready => "Ready",
+        TaskRunStatus::Succeeded => "Succeeded",
+        TaskRunStatus::Failed => "Failed",
+    };
+    if task_status.total > 1 {
+        format!(
+            "{status} ({} of {} tasks ready)",
+            task_status.ready, task_status.total
+        )
+    } else {
+        status.to_string()
+    }
+}
+
 fn string_match_candidates<'a>(
     candidates: impl Iterator<Item = &'a (TaskSourceKind, ResolvedTask)> + 'a,
 ) -> Vec<StringMatchCandidate> {
@@ -0,0 +1,310 @@
+//! Inline images, printed with the iTerm2 image protocol (`OSC 1337 ; File=... : <base64>`), as
+//! tools like `imgcat`, `viu`, `chafa` and matplotlib backends do.
+//!
+//! The pty scanner takes image sequences out of the pty output, and writes blank cells in their
+//! place. Each row of those cells carries an OSC 8 hyperlink naming the image and the row, so the
+//! image keeps its place in the grid as the content scrolls, and goes away when it's cleared.
+//! Only the image's size is read on the pty reader thread; its pixels are decoded on the
+//! background executor once the terminal picks it up.
+
+use std::{
+    io::{self, Write as _},
+    mem,
+    sync::Arc,
+};
+
+use alacritty_terminal::{
+    event::WindowSize,
+    index::{Line, Point as AlacPoint},
+};
+use anyhow::{anyhow, Result};
+use base64::Engine as _;
+use collections::{HashSet, VecDeque};
+use gpui::RenderImage;
+
+use crate::IndexedCell;
+
+pub(crate) const IMAGE_SEQUENCE_PREFIX: &[u8] = b"1337;File=";
+/// Longest image sequence that is decoded, base64 included.
+pub(crate) const MAX_IMAGE_SEQUENCE_LEN: usize = 32 << 20;
+/// The most images kept per terminal. Older images are no longer drawn.
+const MAX_IMAGES: usize = 256;
+const MAX_IMAGE_ROWS: usize = 1000;
+const PLACEHOLDER_PREFIX: &str = "zed-image:";
+
+/// An image printed in the terminal.
+#[derive(Clone, Debug)]
+pub struct InlineImage {
+    pub id: usize,
+    pub image: Arc<RenderImage>,
+    /// The number of cells the image spans.
+    pub columns: usize,
+    pub rows: usize,
+    pub preserve_aspect_ratio: bool,
+}
+
+/// An image whose pixels haven't been decoded yet.
+#[derive(Clone, Debug)]
+pub(crate) struct EncodedImage {
+    pub id: usize,
+    pub data: Vec<u8>,
+    pub columns: usize,
+    pub rows: usize,
+    pub preserve_aspect_ratio: bool,
+}
+
+impl EncodedImage {
+    pub fn decode(self) -> Result<InlineImage> {
+        let mut data = image::load_from_memory(&self.data)?.into_rgba8();
+        // Convert from RGBA to BGRA.
+        for pixel in data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        Ok(InlineImage {
+            id: self.id,
+            image: Arc::new(RenderImage::new(vec![image::Frame::new(data)])),
+            columns: self.columns,
+            rows: self.rows,
+            preserve_aspect_ratio: self.preserve_aspect_ratio,
+        })
+    }
+}
+
+/// An image on the visible part of the grid.
+#[derive(Clone, Debug)]
+pub struct ImagePlacement {
+    pub image: InlineImage,
+    /// The top left cell of the image, which may be scrolled out of view.
+    pub origin: AlacPoint,
+}
+
+/// Parses the payload of an image sequence, after its `1337;File=` prefix.
+///
+/// Returns the image and the bytes that hold its place in the grid, or `None` for files that
+/// are sent to be downloaded rather than shown.
+pub(crate) fn parse(
+    payload: &[u8],
+    id: usize,
+    window_size: WindowSize,
+) -> Result<Option<(EncodedImage, Vec<u8>)>> {
+    let colon_ix = payload
+        .iter()
+        .position(|byte| *byte == b':')
+        .ok_or_else(|| anyhow!("image sequence has no data"))?;
+    let arguments = std::str::from_utf8(&payload[..colon_ix])?;
+    let mut inline = false;
+    let mut width = None;
+    let mut height = None;
+    let mut preserve_aspect_ratio = true;
+    for argument in arguments.split(';') {
+        match argument.split_once('=') {
+            Some(("inline", value)) => inline = value == "1",
+            Some(("width", value)) => width = Some(value),
+            Some(("height", value)) => height = Some(value),
+            Some(("preserveAspectRatio", value)) => preserve_aspect_ratio = value != "0",
+            _ => {}
+        }
+    }
+    if !inline {
+        return Ok(None);
+    }
+
+    let data = payload[colon_ix + 1..]
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    let data = base64::engine::general_purpose::STANDARD.decode(data)?;
+    let (image_width, image_height) = image::ImageReader::new(io::Cursor::new(&data))
+        .with_guessed_format()?
+        .into_dimensions()?;
+
+    let cell_width = window_size.cell_width.max(1) as f32;
+    let cell_height = window_size.cell_height.max(1) as f32;
+    let max_columns = window_size.num_cols.max(1) as usize;
+    let width = width.and_then(|width| {
+        cells_for_dimension(width, cell_width, window_size.num_cols)
+            .map(|columns| columns as f32 * cell_width)
+    });
+    let height = height.and_then(|height| {
+        cells_for_dimension(height, cell_height, window_size.num_lines)
+            .map(|rows| rows as f32 * cell_height)
+    });
+    let aspect_ratio = image_width as f32 / image_height.max(1) as f32;
+    let (mut width, mut height) = match (width, height) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) if preserve_aspect_ratio => (width, width / aspect_ratio),
+        (None, Some(height)) if preserve_aspect_ratio => (height * aspect_ratio, height),
+        (width, height) => (
+            width.unwrap_or(image_width as f32),
+            height.unwrap_or(image_height as f32),
+        ),
+    };
+    // Shrink the image to fit the width of the terminal.
+    let max_width = max_columns as f32 * cell_width;
+    if width > max_width {
+        if preserve_aspect_ratio {
+            height *= max_width / width;
+        }
+        width = max_width;
+    }
+    let columns = ((width / cell_width).ceil() as usize).clamp(1, max_columns);
+    let rows = ((height / cell_height).ceil() as usize).clamp(1, MAX_IMAGE_ROWS);
+
+    let image = EncodedImage {
+        id,
+        data,
+        columns,
+        rows,
+        preserve_aspect_ratio,
+    };
+    Ok(Some((image, placeholder(id, columns, rows))))
+}
+
+/// Parses an iTerm2 image dimension: `N` cells, `Npx`, `N%` of the terminal, or `auto`.
+fn cells_for_dimension(value: &str, cell_size: f32, terminal_cells: u16) -> Option<usize> {
+    if let Some(pixels) = value.strip_suffix("px") {
+        let pixels = pixels.parse::<f32>().ok()?;
+        Some((pixels / cell_size).ceil() as usize)
+    } else if let Some(percent) = value.strip_suffix('%') {
+        let percent = percent.parse::<f32>().ok()?;
+        Some((terminal_cells as f32 * percent / 100.).ceil() as usize)
+    } else {
+        value.parse().ok()
+    }
+}
+
+/// The blank cells that hold an image's place, with the cursor left after the last one.
+fn placeholder(id: usize, columns: usize, rows: usize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for row in 0..rows {
+        if row > 0 {
+            write!(bytes, "\n\x1b[{columns}D").unwrap();
+        }
+        write!(
+            bytes,
+            "\x1b]8;id=zed-image-{id}-{row};{PLACEHOLDER_PREFIX}{id}:{row}\x1b\\"
+        )
+        .unwrap();
+        bytes.extend(std::iter::repeat(b' ').take(columns));
+        bytes.extend_from_slice(b"\x1b]8;;\x1b\\");
+    }
+    bytes
+}
+
+/// Whether a hyperlink marks the place of an image, rather than being a link.
+pub fn is_placeholder(uri: &str) -> bool {
+    uri.starts_with(PLACEHOLDER_PREFIX)
+}
+
+fn parse_placeholder(uri: &str) -> Option<(usize, usize)> {
+    let (id, row) = uri.strip_prefix(PLACEHOLDER_PREFIX)?.split_once(':')?;
+    Some((id.parse().ok()?, row.parse().ok()?))
+}
+
+/// The images printed in a terminal.
+#[derive(Default)]
+pub(crate) struct InlineImages {
+    images: VecDeque<InlineImage>,
+    /// Images waiting to be decoded.
+    encoded: Vec<EncodedImage>,
+    /// Images that are no longer drawn, and should be removed from the windows that drew them.
+    evicted: Vec<Arc<RenderImage>>,
+}
+
+impl InlineImages {
+    pub fn queue(&mut self, images: impl IntoIterator<Item = EncodedImage>) {
+        self.encoded.extend(images);
+    }
+
+    pub fn take_queued(&mut self) -> Vec<EncodedImage> {
+        mem::take(&mut self.encoded)
+    }
+
+    pub fn insert(&mut self, image: InlineImage) {
+        if self.images.len() == MAX_IMAGES {
+            if let Some(evicted) = self.images.pop_front() {
+                self.evicted.push(evicted.image);
+            }
+        }
+        self.images.push_back(image);
+    }
+
+    pub fn take_evicted(&mut self) -> Vec<Arc<RenderImage>> {
+        mem::take(&mut self.evicted)
+    }
+
+    /// The images that may have been drawn, evicted ones included.
+    pub fn render_images(&self) -> impl Iterator<Item = &Arc<RenderImage>> {
+        self.images
+            .iter()
+            .map(|image| &image.image)
+            .chain(&self.evicted)
+    }
+
+    /// Finds the images whose placeholders are among the given cells.
+    pub fn placements(&self, cells: &[IndexedCell]) -> Vec<ImagePlacement> {
+        if self.images.is_empty() {
+            return Vec::new();
+        }
+
+        let mut placed = HashSet::new();
+        let mut placements = Vec::new();
+        for cell in cells {
+            let Some(link) = cell.hyperlink() else {
+                continue;
+            };
+            let Some((id, row)) = parse_placeholder(link.uri()) else {
+                continue;
+            };
+            if !placed.insert(id) {
+                continue;
+            }
+            if let Some(image) = self.images.iter().find(|image| image.id == id) {
+                placements.push(ImagePlacement {
+                    image: image.clone(),
+                    origin: AlacPoint::new(Line(cell.point.line.0 - row as i32), cell.point.column),
+                });
+            }
+        }
+        placements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(id: usize) -> InlineImage {
+        InlineImage {
+            id,
+            image: Arc::new(RenderImage::new(vec![image::Frame::new(
+                image::RgbaImage::new(1, 1),
+            )])),
+            columns: 1,
+            rows: 1,
+            preserve_aspect_ratio: true,
+        }
+    }
+
+    #[test]
+    fn test_evict_images() {
+        let mut images = InlineImages::default();
+        let first = image(0);
+        let first_image_id = first.image.id;
+        images.insert(first);
+        for id in 1..MAX_IMAGES {
+            images.insert(image(id));
+        }
+        assert!(images.take_evicted().is_empty());
+
+        images.insert(image(MAX_IMAGES));
+        assert_eq!(images.render_images().count(), MAX_IMAGES + 1);
+        let evicted = images.take_evicted();
+        assert_eq!(
+            evicted.iter().map(|image| image.id).collect::<Vec<_>>(),
+            vec![first_image_id]
+        );
+        assert_eq!(images.render_images().count(), MAX_IMAGES);
+    }
+}
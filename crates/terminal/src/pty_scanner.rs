@@ -0,0 +1,402 @@
+//! Alacritty drops the escape sequences it doesn't know, so the pty output is scanned for
+//! shell integration marks and inline images before it reaches Alacritty's parser.
+
+use std::{
+    io::{self, Read},
+    sync::Arc,
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    tty::{ChildEvent, EventedPty, EventedReadWrite, Pty},
+};
+use parking_lot::Mutex;
+use polling::{Event, PollMode, Poller};
+use util::ResultExt;
+
+use crate::{
+    inline_images::{self, EncodedImage, IMAGE_SEQUENCE_PREFIX, MAX_IMAGE_SEQUENCE_LEN},
+    shell_integration::{parse_mark, ShellMark},
+};
+
+/// Longest escape sequence payload that is kept, other than images.
+const MAX_SEQUENCE_LEN: usize = 4096;
+const READ_BUFFER_SIZE: usize = 0x10000;
+
+/// What the scanner found in the pty output that the terminal hasn't picked up yet.
+#[derive(Default)]
+pub(crate) struct ScannedOutput {
+    /// Shell integration marks, along with the number of line feeds written before each of them.
+    pub marks: Vec<(ShellMark, usize)>,
+    /// The number of line feeds handed to Alacritty so far.
+    pub line_feeds: usize,
+    pub images: Vec<EncodedImage>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// Scans the pty output, and writes what Alacritty should see of it.
+///
+/// OSC sequences are held back until it's clear that they aren't images. Images are replaced
+/// with the blank cells that hold their place in the grid.
+pub(crate) struct PtyScanner {
+    state: ScanState,
+    /// The payload of the current OSC sequence.
+    sequence: Vec<u8>,
+    overflowed: bool,
+    /// Whether the current OSC sequence was already written out as it came in.
+    passthrough: bool,
+    /// The number of line feeds written out so far.
+    line_feeds: usize,
+    next_image_id: usize,
+    window_size: WindowSize,
+}
+
+impl PtyScanner {
+    pub fn new(window_size: WindowSize) -> Self {
+        Self {
+            state: ScanState::Ground,
+            sequence: Vec::new(),
+            overflowed: false,
+            passthrough: false,
+            line_feeds: 0,
+            next_image_id: 0,
+            window_size,
+        }
+    }
+
+    pub fn advance(&mut self, bytes: &[u8], output: &mut Vec<u8>, scanned: &mut ScannedOutput) {
+        for &byte in bytes {
+            self.advance_byte(byte, output, scanned);
+        }
+    }
+
+    fn advance_byte(&mut self, byte: u8, output: &mut Vec<u8>, scanned: &mut ScannedOutput) {
+        match self.state {
+            ScanState::Ground => match byte {
+                0x1b => self.state = ScanState::Escape,
+                _ => self.write(&[byte], output),
+            },
+            ScanState::Escape => match byte {
+                b']' => {
+                    self.sequence.clear();
+                    self.overflowed = false;
+                    self.passthrough = false;
+                    self.state = ScanState::Osc;
+                }
+                0x1b => output.push(0x1b),
+                _ => {
+                    output.push(0x1b);
+                    self.state = ScanState::Ground;
+                    self.advance_byte(byte, output, scanned);
+                }
+            },
+            ScanState::Osc => match byte {
+                0x07 => {
+                    self.finish_sequence(&[0x07], output, scanned);
+                    self.state = ScanState::Ground;
+                }
+                0x1b => self.state = ScanState::OscEscape,
+                // CAN and SUB cancel the sequence.
+                0x18 | 0x1a => {
+                    if !self.is_image() {
+                        self.flush_sequence(output);
+                        output.push(byte);
+                    }
+                    self.state = ScanState::Ground;
+                }
+                _ => {
+                    let max_len = if self.is_image() {
+                        MAX_IMAGE_SEQUENCE_LEN
+                    } else {
+                        MAX_SEQUENCE_LEN
+                    };
+                    if self.sequence.len() < max_len {
+                        self.sequence.push(byte);
+                    } else {
+                        self.overflowed = true;
+                    }
+                    if self.passthrough {
+                        output.push(byte);
+                    } else if !self.is_image() {
+                        self.flush_sequence(output);
+                    }
+                }
+            },
+            // Like Alacritty, treat any escape as the end of the sequence, not just `ESC \`.
+            ScanState::OscEscape => {
+                if byte == b'\\' {
+                    self.finish_sequence(b"\x1b\\", output, scanned);
+                    self.state = ScanState::Ground;
+                } else {
+                    self.finish_sequence(&[], output, scanned);
+                    self.state = ScanState::Escape;
+                    self.advance_byte(byte, output, scanned);
+                }
+            }
+        }
+    }
+
+    /// Whether the current OSC sequence may still turn out to be an image.
+    fn is_image(&self) -> bool {
+        !self.passthrough
+            && IMAGE_SEQUENCE_PREFIX
+                .iter()
+                .zip(&self.sequence)
+                .all(|(a, b)| a == b)
+    }
+
+    /// Writes out the part of the current OSC sequence that was held back.
+    fn flush_sequence(&mut self, output: &mut Vec<u8>) {
+        if !self.passthrough {
+            output.extend_from_slice(b"\x1b]");
+            output.extend_from_slice(&self.sequence);
+            self.passthrough = true;
+        }
+    }
+
+    fn finish_sequence(
+        &mut self,
+        terminator: &[u8],
+        output: &mut Vec<u8>,
+        scanned: &mut ScannedOutput,
+    ) {
+        if self.is_image() && self.sequence.len() >= IMAGE_SEQUENCE_PREFIX.len() {
+            if self.overflowed {
+                return;
+            }
+            let payload = &self.sequence[IMAGE_SEQUENCE_PREFIX.len()..];
+            if let Some(Some((image, placeholder))) =
+                inline_images::parse(payload, self.next_image_id, self.window_size).log_err()
+            {
+                self.next_image_id += 1;
+                scanned.images.push(image);
+                self.write(&placeholder, output);
+            }
+            return;
+        }
+
+        self.flush_sequence(output);
+        output.extend_from_slice(terminator);
+        if self.overflowed {
+            return;
+        }
+        if let Some(mark) = parse_mark(&self.sequence) {
+            scanned.marks.push((mark, self.line_feeds));
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8], output: &mut Vec<u8>) {
+        self.line_feeds += bytes.iter().filter(|byte| **byte == b'\n').count();
+        output.extend_from_slice(bytes);
+    }
+}
+
+/// Reads the pty output through a [`PtyScanner`].
+pub(crate) struct ScannedReader {
+    pty: Pty,
+    scanner: PtyScanner,
+    scanned: Arc<Mutex<ScannedOutput>>,
+    buffer: Box<[u8]>,
+    /// Scanned output that didn't fit into the last read.
+    output: Vec<u8>,
+    output_ix: usize,
+}
+
+impl Read for ScannedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Images are held back until they are complete, so keep reading until there's output.
+        while self.output_ix == self.output.len() {
+            self.output.clear();
+            self.output_ix = 0;
+            let len = self.pty.reader().read(&mut self.buffer)?;
+            if len == 0 {
+                return Ok(0);
+            }
+            self.scanner.advance(
+                &self.buffer[..len],
+                &mut self.output,
+                &mut self.scanned.lock(),
+            );
+        }
+
+        let output = &self.output[self.output_ix..];
+        let len = output.len().min(buf.len());
+        buf[..len].copy_from_slice(&output[..len]);
+        self.output_ix += len;
+        self.scanned.lock().line_feeds += buf[..len].iter().filter(|byte| **byte == b'\n').count();
+        Ok(len)
+    }
+}
+
+/// A pty whose output is scanned before Alacritty parses it.
+pub(crate) struct ScanningPty {
+    reader: ScannedReader,
+}
+
+impl ScanningPty {
+    pub fn new(pty: Pty, window_size: WindowSize, scanned: Arc<Mutex<ScannedOutput>>) -> Self {
+        Self {
+            reader: ScannedReader {
+                pty,
+                scanner: PtyScanner::new(window_size),
+                scanned,
+                buffer: vec![0; READ_BUFFER_SIZE].into_boxed_slice(),
+                output: Vec::new(),
+                output_ix: 0,
+            },
+        }
+    }
+}
+
+impl EventedReadWrite for ScanningPty {
+    type Reader = ScannedReader;
+    type Writer = <Pty as EventedReadWrite>::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.reader.pty.register(poller, interest, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.reader.pty.reregister(poller, interest, mode)
+    }
+
+    fn deregister(&mut self, poller: &Arc<Poller>) -> io::Result<()> {
+        self.reader.pty.deregister(poller)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        &mut self.reader
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.reader.pty.writer()
+    }
+}
+
+impl EventedPty for ScanningPty {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.reader.pty.next_child_event()
+    }
+}
+
+impl OnResize for ScanningPty {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.reader.scanner.window_size = window_size;
+        self.reader.pty.on_resize(window_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn scan(chunks: &[&[u8]]) -> (Vec<u8>, ScannedOutput) {
+        let mut scanner = PtyScanner::new(WindowSize {
+            num_lines: 24,
+            num_cols: 80,
+            cell_width: 8,
+            cell_height: 16,
+        });
+        let mut output = Vec::new();
+        let mut scanned = ScannedOutput::default();
+        for chunk in chunks {
+            scanner.advance(chunk, &mut output, &mut scanned);
+        }
+        (output, scanned)
+    }
+
+    #[test]
+    fn test_scan_marks() {
+        let chunks: &[&[u8]] = &[
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n",
+            b"\x1b]633;E;ls\x07\x1b]133;C\x07",
+            b"a\r\nb\r\n\x1b]133;D;2\x1b\\",
+        ];
+        let (output, scanned) = scan(chunks);
+        assert_eq!(output, chunks.concat());
+        assert_eq!(
+            scanned.marks,
+            vec![
+                (ShellMark::PromptStart, 0),
+                (ShellMark::CommandStart, 0),
+                (ShellMark::CommandLine("ls".to_string()), 1),
+                (ShellMark::CommandExecuted, 1),
+                (ShellMark::CommandFinished { exit_code: Some(2) }, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_split_sequences() {
+        let chunks: &[&[u8]] = &[b"\x1b", b"]13", b"3;D", b"\x1b", b"\\\n"];
+        let (output, scanned) = scan(chunks);
+        assert_eq!(output, chunks.concat());
+        assert_eq!(
+            scanned.marks,
+            vec![(ShellMark::CommandFinished { exit_code: None }, 0)]
+        );
+
+        // Other sequences and control characters are passed through.
+        let chunks: &[&[u8]] = &[
+            b"\x1b]0;title\x07\x1b[31mred\x1b]133;X\x07\x1b]133;A\x18",
+            b"\x1b]7;file://host/tmp\x1b[0m",
+        ];
+        let (output, scanned) = scan(chunks);
+        assert_eq!(output, chunks.concat());
+        assert_eq!(
+            scanned.marks,
+            vec![(ShellMark::WorkingDirectory(PathBuf::from("/tmp")), 0)]
+        );
+    }
+
+    #[test]
+    fn test_scan_images() {
+        // A 16x32 pixel PNG takes up 2x2 cells.
+        let mut png = Vec::new();
+        image::RgbaImage::new(16, 32)
+            .write_to(&mut io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let data = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &png);
+        let sequence = format!("\x1b]1337;File=name=eC5wbmc=;inline=1:{data}\x07");
+        let (split_start, split_end) = sequence.as_bytes().split_at(20);
+        let (output, scanned) = scan(&[b"a\n", split_start, split_end, b"b"]);
+
+        assert_eq!(scanned.images.len(), 1);
+        assert_eq!(scanned.images[0].columns, 2);
+        assert_eq!(scanned.images[0].rows, 2);
+        let image = scanned.images[0].clone().decode().unwrap();
+        assert_eq!(image.image.as_bytes(0).unwrap().len(), 16 * 32 * 4);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "a\n\
+             \x1b]8;id=zed-image-0-0;zed-image:0:0\x1b\\  \x1b]8;;\x1b\\\n\x1b[2D\
+             \x1b]8;id=zed-image-0-1;zed-image:0:1\x1b\\  \x1b]8;;\x1b\\b"
+        );
+
+        // Downloads are dropped.
+        let sequence = format!("\x1b]1337;File=name=eC5wbmc=:{data}\x07");
+        let (output, scanned) = scan(&[sequence.as_bytes(), b"b"]);
+        assert!(scanned.images.is_empty());
+        assert_eq!(output, b"b");
+    }
+}
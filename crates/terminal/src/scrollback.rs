@@ -0,0 +1,253 @@
+//! Saving the terminal's content as text with escape sequences, so that it can be shown again
+//! when the workspace is reopened.
+
+use std::fmt::Write as _;
+
+use alacritty_terminal::{
+    grid::{Dimensions, GridCell},
+    index::{Column, Line},
+    term::cell::{Cell, Flags},
+    vte::ansi::{Color, NamedColor, Processor},
+    Term,
+};
+
+/// Writes out the last `max_lines` lines of the terminal, colors and text attributes included.
+pub(crate) fn serialize<T>(term: &Term<T>, max_lines: usize) -> String {
+    let Some(last_line) = last_non_empty_line(term) else {
+        return String::new();
+    };
+    let grid = term.grid();
+    let first_line = (last_line + 1)
+        .saturating_sub(max_lines.try_into().unwrap_or(i32::MAX))
+        .max(grid.topmost_line().0);
+
+    let mut output = String::new();
+    let mut style = Style::default();
+    for line in first_line..=last_line {
+        let row = &grid[Line(line)];
+        let wrapped = row[Column(grid.columns() - 1)]
+            .flags
+            .contains(Flags::WRAPLINE);
+        let len = if wrapped {
+            grid.columns()
+        } else {
+            row.into_iter()
+                .rposition(|cell| !cell.is_empty())
+                .map_or(0, |ix| ix + 1)
+        };
+        for cell in row.into_iter().take(len) {
+            if cell
+                .flags
+                .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+            {
+                continue;
+            }
+            let cell_style = Style::of(cell);
+            if cell_style != style {
+                cell_style.write(&mut output);
+                style = cell_style;
+            }
+            output.push(cell.c);
+            if let Some(zerowidth) = cell.zerowidth() {
+                output.extend(zerowidth);
+            }
+        }
+        if !wrapped {
+            if style != Style::default() {
+                Style::default().write(&mut output);
+                style = Style::default();
+            }
+            output.push_str("\r\n");
+        }
+    }
+    if style != Style::default() {
+        Style::default().write(&mut output);
+    }
+    output
+}
+
+/// Shows saved content in the terminal, above whatever it shows already.
+pub(crate) fn restore<T: alacritty_terminal::event::EventListener>(
+    term: &mut Term<T>,
+    contents: &str,
+) {
+    let history_size = term.history_size() as i32;
+    let cursor_row = (term.grid().cursor.point.line.0 + history_size).max(0) as usize;
+    let cursor_column = term.grid().cursor.point.column;
+    let existing = serialize(term, usize::MAX);
+    let existing_rows =
+        last_non_empty_line(term).map_or(0, |line| line + history_size + 1) as usize;
+
+    let mut processor: Processor = Processor::new();
+    let mut advance = |term: &mut Term<T>, text: &str| {
+        for byte in text.bytes() {
+            processor.advance(term, byte);
+        }
+    };
+    // Clear the screen and the scrollback, then write the saved content before the existing one.
+    advance(term, "\x1b[H\x1b[2J\x1b[3J");
+    advance(term, contents);
+    advance(term, existing.strip_suffix("\r\n").unwrap_or(&existing));
+    // Put the cursor back where it was in the existing content.
+    let rows_below = cursor_row.saturating_sub(existing_rows.max(1) - 1);
+    advance(term, &"\r\n".repeat(rows_below));
+    advance(term, &format!("\x1b[{}G", cursor_column.0 + 1));
+}
+
+fn last_non_empty_line<T>(term: &Term<T>) -> Option<i32> {
+    let grid = term.grid();
+    (grid.topmost_line().0..=grid.bottommost_line().0)
+        .rev()
+        .find(|line| grid[Line(*line)].into_iter().any(|cell| !cell.is_empty()))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Style {
+    fg: Color,
+    bg: Color,
+    flags: Flags,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fg: Color::Named(NamedColor::Foreground),
+            bg: Color::Named(NamedColor::Background),
+            flags: Flags::empty(),
+        }
+    }
+}
+
+impl Style {
+    const FLAGS: Flags = Flags::BOLD
+        .union(Flags::DIM)
+        .union(Flags::ITALIC)
+        .union(Flags::ALL_UNDERLINES)
+        .union(Flags::INVERSE)
+        .union(Flags::HIDDEN)
+        .union(Flags::STRIKEOUT);
+
+    fn of(cell: &Cell) -> Self {
+        Self {
+            fg: cell.fg,
+            bg: cell.bg,
+            flags: cell.flags & Self::FLAGS,
+        }
+    }
+
+    /// Writes the SGR sequence that switches to this style.
+    fn write(&self, output: &mut String) {
+        output.push_str("\x1b[0");
+        for (flag, parameter) in [
+            (Flags::BOLD, "1"),
+            (Flags::DIM, "2"),
+            (Flags::ITALIC, "3"),
+            (Flags::UNDERLINE, "4"),
+            (Flags::DOUBLE_UNDERLINE, "21"),
+            (Flags::UNDERCURL, "4:3"),
+            (Flags::DOTTED_UNDERLINE, "4:4"),
+            (Flags::DASHED_UNDERLINE, "4:5"),
+            (Flags::INVERSE, "7"),
+            (Flags::HIDDEN, "8"),
+            (Flags::STRIKEOUT, "9"),
+        ] {
+            if self.flags.contains(flag) {
+                output.push(';');
+                output.push_str(parameter);
+            }
+        }
+        write_color(output, self.fg, 30, 90, 38);
+        write_color(output, self.bg, 40, 100, 48);
+        output.push('m');
+    }
+}
+
+fn write_color(output: &mut String, color: Color, normal: u8, bright: u8, extended: u8) {
+    match color {
+        Color::Named(named) => {
+            let named = named as usize;
+            let index = if named < 16 {
+                named
+            } else if (NamedColor::DimBlack as usize..=NamedColor::DimWhite as usize)
+                .contains(&named)
+            {
+                named - NamedColor::DimBlack as usize
+            } else {
+                return;
+            };
+            if index < 8 {
+                write!(output, ";{}", normal as usize + index).unwrap();
+            } else {
+                write!(output, ";{}", bright as usize + index - 8).unwrap();
+            }
+        }
+        Color::Indexed(index) => write!(output, ";{extended};5;{index}").unwrap(),
+        Color::Spec(rgb) => write!(output, ";{extended};2;{};{};{}", rgb.r, rgb.g, rgb.b).unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{event::VoidListener, index::Point as AlacPoint, term::Config};
+
+    use super::*;
+    use crate::TerminalSize;
+
+    fn term(contents: &str) -> Term<VoidListener> {
+        let mut term = Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        let mut processor: Processor = Processor::new();
+        for byte in contents.bytes() {
+            processor.advance(&mut term, byte);
+        }
+        term
+    }
+
+    fn text(term: &Term<VoidListener>) -> String {
+        let start = term.grid().topmost_line();
+        let end = term.grid().bottommost_line();
+        term.bounds_to_string(
+            AlacPoint::new(start, Column(0)),
+            AlacPoint::new(end, Column(term.grid().columns() - 1)),
+        )
+        .trim_end()
+        .to_string()
+    }
+
+    #[test]
+    fn test_serialize_scrollback() {
+        let term = term("plain\r\n\x1b[1;31mbold red\x1b[0m and \x1b[48;5;42mindexed\x1b[0m\r\n$ ");
+        assert_eq!(
+            serialize(&term, usize::MAX),
+            "plain\r\n\
+             \x1b[0;1;31mbold red\x1b[0m and \x1b[0;48;5;42mindexed\x1b[0m\r\n\
+             $\r\n"
+        );
+        assert_eq!(serialize(&term, 1), "$\r\n");
+        assert_eq!(serialize(&self::term(""), usize::MAX), "");
+
+        // Wrapped lines are joined again.
+        let long_line = "x".repeat(TerminalSize::default().columns() + 10);
+        let term = self::term(&long_line);
+        assert_eq!(serialize(&term, usize::MAX), format!("{long_line}\r\n"));
+    }
+
+    #[test]
+    fn test_restore_scrollback() {
+        let saved = serialize(&term("one\r\n\x1b[32mtwo\x1b[0m\r\n"), usize::MAX);
+
+        // The new shell already printed its prompt, which stays below the saved content.
+        let mut term = term("$ ");
+        restore(&mut term, &saved);
+        assert_eq!(text(&term), "one\ntwo\n$");
+        assert_eq!(term.grid().cursor.point.column, Column(2));
+        assert_eq!(
+            term.grid()[Line(1)][Column(0)].fg,
+            Color::Named(NamedColor::Green)
+        );
+
+        let mut term = self::term("");
+        restore(&mut term, &saved);
+        assert_eq!(text(&term), "one\ntwo");
+        assert_eq!(term.grid().cursor.point, AlacPoint::new(Line(2), Column(0)));
+    }
+}
@@ -0,0 +1,635 @@
+//! Shell integration lets the shell tell the terminal where prompts, commands and their output
+//! start, through the OSC 133 (FinalTerm) and OSC 633 (VS Code) escape sequences.
+
+use std::{
+    mem,
+    path::{Path, PathBuf},
+};
+
+use alacritty_terminal::{
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    term::{cell::Flags, TermMode},
+    Term,
+};
+use anyhow::{Context as _, Result};
+use collections::{HashMap, VecDeque};
+use task::Shell;
+
+use crate::pty_scanner::ScannedOutput;
+
+const BASH_SCRIPT: &str = include_str!("shell_integration/zed.bash");
+const ZSH_SCRIPT: &str = include_str!("shell_integration/zed.zsh");
+const ZSH_ENV: &str = include_str!("shell_integration/zshenv");
+const ZSH_PROFILE: &str = include_str!("shell_integration/zprofile");
+const ZSH_RC: &str = include_str!("shell_integration/zshrc");
+const FISH_SCRIPT: &str = include_str!("shell_integration/zed.fish");
+
+/// The most commands remembered per terminal.
+const MAX_COMMANDS: usize = 1000;
+
+/// Something the shell reported about the prompt or the command it runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellMark {
+    /// The prompt is about to be printed (`133;A`).
+    PromptStart,
+    /// The prompt was printed and the user is typing a command (`133;B`).
+    CommandStart,
+    /// The command started running, and its output follows (`133;C`).
+    CommandExecuted,
+    /// The command finished (`133;D`).
+    CommandFinished { exit_code: Option<i32> },
+    /// The command line that is about to run (`633;E`).
+    CommandLine(String),
+    /// The shell's working directory (`633;P;Cwd=` or `OSC 7`).
+    WorkingDirectory(PathBuf),
+}
+
+pub(crate) fn parse_mark(sequence: &[u8]) -> Option<ShellMark> {
+    let sequence = std::str::from_utf8(sequence).ok()?;
+    let (code, params) = sequence.split_once(';').unwrap_or((sequence, ""));
+    match code {
+        "133" | "633" => {
+            let mut params = params.split(';');
+            match params.next()? {
+                "A" => Some(ShellMark::PromptStart),
+                "B" => Some(ShellMark::CommandStart),
+                "C" => Some(ShellMark::CommandExecuted),
+                "D" => Some(ShellMark::CommandFinished {
+                    exit_code: params.next().and_then(|code| code.parse().ok()),
+                }),
+                "E" if code == "633" => Some(ShellMark::CommandLine(unescape(
+                    params.next().unwrap_or_default(),
+                ))),
+                "P" if code == "633" => {
+                    let cwd = params.next()?.strip_prefix("Cwd=")?;
+                    Some(ShellMark::WorkingDirectory(PathBuf::from(unescape(cwd))))
+                }
+                _ => None,
+            }
+        }
+        // `file://hostname/path`, percent-encoded.
+        "7" => {
+            let url = params.strip_prefix("file://")?;
+            let path = &url[url.find('/')?..];
+            Some(ShellMark::WorkingDirectory(PathBuf::from(percent_decode(
+                path,
+            ))))
+        }
+        _ => None,
+    }
+}
+
+/// Undoes OSC 633's escaping, where `\\` is a backslash and `\xAB` is the byte `0xAB`.
+fn unescape(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut ix = 0;
+    while ix < bytes.len() {
+        if bytes[ix] == b'\\' {
+            if bytes.get(ix + 1) == Some(&b'\\') {
+                unescaped.push(b'\\');
+                ix += 2;
+                continue;
+            }
+            if bytes.get(ix + 1) == Some(&b'x') {
+                if let Some(byte) = text
+                    .get(ix + 2..ix + 4)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    unescaped.push(byte);
+                    ix += 4;
+                    continue;
+                }
+            }
+        }
+        unescaped.push(bytes[ix]);
+        ix += 1;
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+pub(crate) fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut ix = 0;
+    while ix < bytes.len() {
+        if bytes[ix] == b'%' {
+            if let Some(byte) = text
+                .get(ix + 1..ix + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                ix += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[ix]);
+        ix += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// The state of a command run in a terminal with shell integration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    /// The prompt is shown and the user hasn't run a command from it yet.
+    Prompt,
+    Running,
+    Finished {
+        exit_code: Option<i32>,
+    },
+}
+
+impl CommandStatus {
+    pub fn failed(&self) -> bool {
+        matches!(self, CommandStatus::Finished { exit_code: Some(code) } if *code != 0)
+    }
+}
+
+/// Counts the lines that have scrolled off the top of the screen, including the ones that were
+/// dropped from the scrollback once it was full, so that lines can be numbered in a way that
+/// doesn't change as the terminal scrolls.
+pub(crate) struct ScrolledLines {
+    count: usize,
+    max_history_size: usize,
+    history_size: usize,
+    cursor_line: Line,
+    line_feeds: usize,
+}
+
+impl ScrolledLines {
+    pub fn new(max_history_size: usize) -> Self {
+        Self {
+            count: 0,
+            max_history_size,
+            history_size: 0,
+            cursor_line: Line(0),
+            line_feeds: 0,
+        }
+    }
+
+    /// Catches up with the output Alacritty has been handed, which had `line_feeds` line feeds
+    /// in it so far.
+    pub fn update<T>(&mut self, term: &Term<T>, line_feeds: usize) {
+        let new_line_feeds = line_feeds - self.line_feeds;
+        self.line_feeds = line_feeds;
+        // The alternate screen has no scrollback of its own.
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+
+        let history_size = term.history_size();
+        let cursor_line = term.grid().cursor.point.line;
+        let mut scrolled = history_size.saturating_sub(self.history_size);
+        // Once the scrollback is full, each line that scrolls into it pushes out another, and
+        // its size stays the same. The line feeds that didn't move the cursor down scrolled the
+        // screen instead.
+        if history_size > 0 && history_size == self.max_history_size {
+            let moved_down = (cursor_line.0 - self.cursor_line.0).max(0) as usize;
+            scrolled = scrolled.max(new_line_feeds.saturating_sub(moved_down));
+        }
+        self.count += scrolled;
+        self.history_size = history_size;
+        self.cursor_line = cursor_line;
+    }
+
+    /// The number of a line on the grid, counted from the first line that was ever shown.
+    pub fn absolute_line(&self, line: Line) -> usize {
+        (self.count as i64 + line.0 as i64).max(0) as usize
+    }
+
+    /// The line on the grid for a number returned by [`Self::absolute_line`]. Lines that were
+    /// dropped from the scrollback end up above [`Term::topmost_line`].
+    pub fn grid_line(&self, line: usize) -> Line {
+        grid_line(line, self.count)
+    }
+}
+
+fn grid_line(line: usize, scrolled_lines: usize) -> Line {
+    Line((line as i64 - scrolled_lines as i64).max(i32::MIN as i64) as i32)
+}
+
+/// A prompt and the command that was run from it, as reported by the shell.
+#[derive(Clone, Debug)]
+pub struct ShellCommand {
+    /// Lines are numbered by [`ScrolledLines::absolute_line`].
+    prompt_line: usize,
+    output_line: Option<usize>,
+    end_line: Option<usize>,
+    pub command: Option<String>,
+    pub working_directory: Option<PathBuf>,
+    pub status: CommandStatus,
+}
+
+/// A command's status, shown next to its prompt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommandMark {
+    pub line: Line,
+    pub status: CommandStatus,
+}
+
+/// The commands run in a terminal, placed on its grid.
+///
+/// Marks are placed by counting the line feeds that followed them, so they can end up off by
+/// a few lines when programs move the cursor around.
+#[derive(Default)]
+pub(crate) struct ShellCommands {
+    commands: VecDeque<ShellCommand>,
+    working_directory: Option<PathBuf>,
+    history_size: usize,
+    /// [`ScrolledLines`]'s count when the marks were last placed.
+    scrolled_lines: usize,
+}
+
+impl ShellCommands {
+    pub fn commands(&self) -> impl Iterator<Item = &ShellCommand> {
+        self.commands.iter()
+    }
+
+    /// Places the marks that Alacritty has been handed since the last call on the grid.
+    /// `scrolled_lines` must be up to date with the same output.
+    pub fn resolve<T>(
+        &mut self,
+        term: &Term<T>,
+        scanned: &mut ScannedOutput,
+        scrolled_lines: &ScrolledLines,
+    ) {
+        let history_size = term.history_size();
+        // The scrollback was cleared, and the commands in it are gone.
+        if history_size < self.history_size {
+            self.commands.clear();
+        }
+        self.history_size = history_size;
+        self.scrolled_lines = scrolled_lines.count;
+
+        let line_feeds = scanned.line_feeds;
+        // Marks read after the output Alacritty has seen so far wait for the next call.
+        let (marks, pending) = mem::take(&mut scanned.marks)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, line_feeds_before)| *line_feeds_before <= line_feeds);
+        scanned.marks = pending;
+        for (mark, line_feeds_before) in marks {
+            let line = line_of_mark(term, line_feeds - line_feeds_before);
+            self.apply(mark, scrolled_lines.absolute_line(line));
+        }
+    }
+
+    fn apply(&mut self, mark: ShellMark, line: usize) {
+        match mark {
+            ShellMark::PromptStart => {
+                if self.commands.len() == MAX_COMMANDS {
+                    self.commands.pop_front();
+                }
+                self.commands.push_back(ShellCommand {
+                    prompt_line: line,
+                    output_line: None,
+                    end_line: None,
+                    command: None,
+                    working_directory: self.working_directory.clone(),
+                    status: CommandStatus::Prompt,
+                });
+            }
+            ShellMark::CommandStart => {}
+            ShellMark::CommandLine(command_line) => {
+                if let Some(command) = self.current(CommandStatus::Prompt) {
+                    command.command = Some(command_line);
+                }
+            }
+            ShellMark::CommandExecuted => {
+                if let Some(command) = self.current(CommandStatus::Prompt) {
+                    command.output_line = Some(line);
+                    command.status = CommandStatus::Running;
+                }
+            }
+            ShellMark::CommandFinished { exit_code } => {
+                if let Some(command) = self.current(CommandStatus::Running) {
+                    command.end_line = Some(line);
+                    command.status = CommandStatus::Finished { exit_code };
+                }
+            }
+            ShellMark::WorkingDirectory(path) => {
+                if let Some(command) = self.current(CommandStatus::Prompt) {
+                    command.working_directory = Some(path.clone());
+                }
+                self.working_directory = Some(path);
+            }
+        }
+    }
+
+    fn current(&mut self, status: CommandStatus) -> Option<&mut ShellCommand> {
+        self.commands
+            .back_mut()
+            .filter(|command| command.status == status)
+    }
+
+    /// The closest prompt above or below `line`.
+    pub fn prompt_line_near(&self, line: Line, previous: bool) -> Option<Line> {
+        let mut prompts = self
+            .commands
+            .iter()
+            .map(|command| self.grid_line(command.prompt_line));
+        if previous {
+            prompts.filter(|prompt| *prompt < line).last()
+        } else {
+            prompts.find(|prompt| *prompt > line)
+        }
+    }
+
+    /// The lines that the output of the last command that was run spans.
+    pub fn last_output_range<T>(&self, term: &Term<T>) -> Option<(AlacPoint, AlacPoint)> {
+        let command = self
+            .commands
+            .iter()
+            .rev()
+            .find(|command| command.output_line.is_some())?;
+        let start = self.grid_line(command.output_line?);
+        let end = match command.end_line {
+            Some(end_line) => Line(self.grid_line(end_line).0 - 1),
+            None => term.grid().cursor.point.line,
+        };
+        if end < start || start < term.topmost_line() {
+            return None;
+        }
+        Some((
+            AlacPoint::new(start, Column(0)),
+            AlacPoint::new(end, term.last_column()),
+        ))
+    }
+
+    /// The working directory of the command whose prompt or output is on `line`.
+    pub fn working_directory_at(&self, line: Line) -> Option<&Path> {
+        self.commands
+            .iter()
+            .rev()
+            .find(|command| self.grid_line(command.prompt_line) <= line)?
+            .working_directory
+            .as_deref()
+    }
+
+    /// The marks of the commands that start on the visible lines.
+    pub fn visible_marks<T>(&self, term: &Term<T>) -> Vec<CommandMark> {
+        let top = Line(-(term.grid().display_offset() as i32));
+        let bottom = Line(top.0 + term.screen_lines() as i32 - 1);
+        self.commands
+            .iter()
+            .filter(|command| command.status != CommandStatus::Prompt)
+            .map(|command| CommandMark {
+                line: self.grid_line(command.prompt_line),
+                status: command.status,
+            })
+            .filter(|mark| top <= mark.line && mark.line <= bottom)
+            .collect()
+    }
+
+    fn grid_line(&self, line: usize) -> Line {
+        grid_line(line, self.scrolled_lines)
+    }
+}
+
+/// Finds the line the cursor was on `line_feeds` line feeds ago, skipping wrapped lines.
+fn line_of_mark<T>(term: &Term<T>, mut line_feeds: usize) -> Line {
+    let grid = term.grid();
+    let last_column = term.last_column();
+    let mut line = grid.cursor.point.line;
+    while line > term.topmost_line() {
+        let previous = Line(line.0 - 1);
+        if !grid[previous][last_column].flags.contains(Flags::WRAPLINE) {
+            if line_feeds == 0 {
+                break;
+            }
+            line_feeds -= 1;
+        }
+        line = previous;
+    }
+    line
+}
+
+/// Makes bash, zsh and fish load Zed's shell integration script on startup.
+/// Other shells are returned unchanged.
+pub fn inject(shell: Shell, env: &mut HashMap<String, String>) -> Result<Shell> {
+    let program = match &shell {
+        Shell::System => match std::env::var("SHELL") {
+            Ok(program) if cfg!(unix) => program,
+            _ => return Ok(shell),
+        },
+        Shell::Program(program) | Shell::WithArguments { program, .. } => program.clone(),
+    };
+    let shell_name = Path::new(&program)
+        .file_name()
+        .map(|name| name.to_string_lossy().trim_start_matches('-').to_string())
+        .unwrap_or_default();
+
+    let dir = paths::shell_integration_dir();
+    match shell_name.as_str() {
+        // Bash only reads one startup file, so the script loads the user's `.bashrc` itself.
+        // Custom arguments may already pick another one, or make it a login shell.
+        "bash" => {
+            let title_override = match shell {
+                Shell::System | Shell::Program(_) => None,
+                Shell::WithArguments {
+                    args,
+                    title_override,
+                    ..
+                } if args.is_empty() => title_override,
+                shell => return Ok(shell),
+            };
+            let script = write_script(&dir.join("bash"), "zed.bash", BASH_SCRIPT)?;
+            Ok(Shell::WithArguments {
+                program,
+                args: vec![
+                    "--init-file".to_string(),
+                    script.to_string_lossy().into_owned(),
+                ],
+                title_override,
+            })
+        }
+        // Zsh reads its startup files from `ZDOTDIR`. Zed's files load the user's ones.
+        "zsh" => {
+            let zsh_dir = dir.join("zsh");
+            write_script(&zsh_dir, "zed.zsh", ZSH_SCRIPT)?;
+            write_script(&zsh_dir, ".zshenv", ZSH_ENV)?;
+            write_script(&zsh_dir, ".zprofile", ZSH_PROFILE)?;
+            write_script(&zsh_dir, ".zshrc", ZSH_RC)?;
+            let user_zdotdir = env
+                .get("ZDOTDIR")
+                .cloned()
+                .or_else(|| std::env::var("ZDOTDIR").ok());
+            if let Some(user_zdotdir) = user_zdotdir {
+                env.insert("ZED_USER_ZDOTDIR".to_string(), user_zdotdir);
+            }
+            env.insert(
+                "ZDOTDIR".to_string(),
+                zsh_dir.to_string_lossy().into_owned(),
+            );
+            Ok(shell)
+        }
+        // Fish loads scripts from the `vendor_conf.d` of every `XDG_DATA_DIRS` entry.
+        "fish" => {
+            let data_dir = dir.join("fish");
+            write_script(
+                &data_dir.join("fish").join("vendor_conf.d"),
+                "zed.fish",
+                FISH_SCRIPT,
+            )?;
+            let user_data_dirs = env
+                .get("XDG_DATA_DIRS")
+                .cloned()
+                .or_else(|| std::env::var("XDG_DATA_DIRS").ok());
+            let data_dirs = format!(
+                "{}:{}",
+                data_dir.to_string_lossy(),
+                user_data_dirs
+                    .as_deref()
+                    .unwrap_or("/usr/local/share:/usr/share")
+            );
+            env.insert(
+                "ZED_USER_XDG_DATA_DIRS".to_string(),
+                user_data_dirs.unwrap_or_default(),
+            );
+            env.insert("XDG_DATA_DIRS".to_string(), data_dirs);
+            Ok(shell)
+        }
+        _ => Ok(shell),
+    }
+}
+
+fn write_script(dir: &Path, name: &str, contents: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("creating shell integration directory {dir:?}"))?;
+    let path = dir.join(name);
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(contents) {
+        std::fs::write(&path, contents)
+            .with_context(|| format!("writing shell integration script {path:?}"))?;
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{
+        event::{VoidListener, WindowSize},
+        term::Config,
+        vte::ansi::Processor,
+    };
+
+    use super::*;
+    use crate::{pty_scanner::PtyScanner, TerminalSize};
+
+    /// Feeds output to a terminal the way [`crate::pty_scanner::ScannedReader`] does.
+    struct TestTerminal {
+        term: Term<VoidListener>,
+        processor: Processor,
+        scanner: PtyScanner,
+        scanned: ScannedOutput,
+        scrolled_lines: ScrolledLines,
+        commands: ShellCommands,
+    }
+
+    impl TestTerminal {
+        fn new(scrolling_history: usize) -> Self {
+            let size = TerminalSize::default();
+            Self {
+                term: Term::new(
+                    Config {
+                        scrolling_history,
+                        ..Config::default()
+                    },
+                    &size,
+                    VoidListener,
+                ),
+                processor: Processor::new(),
+                scanner: PtyScanner::new(WindowSize::from(size)),
+                scanned: ScannedOutput::default(),
+                scrolled_lines: ScrolledLines::new(scrolling_history),
+                commands: ShellCommands::default(),
+            }
+        }
+
+        fn write(&mut self, text: &str) {
+            let mut output = Vec::new();
+            self.scanner
+                .advance(text.as_bytes(), &mut output, &mut self.scanned);
+            for byte in &output {
+                self.processor.advance(&mut self.term, *byte);
+            }
+            self.scanned.line_feeds += output.iter().filter(|byte| **byte == b'\n').count();
+            self.scrolled_lines
+                .update(&self.term, self.scanned.line_feeds);
+            self.commands
+                .resolve(&self.term, &mut self.scanned, &self.scrolled_lines);
+        }
+
+        fn run(&mut self, command: &str, output_lines: usize) {
+            self.write(&format!(
+                "\x1b]133;A\x07$ \x1b]133;B\x07{command}\r\n\x1b]133;C\x07"
+            ));
+            for ix in 0..output_lines {
+                self.write(&format!("{command} {ix}\r\n"));
+            }
+            self.write("\x1b]133;D;0\x07");
+        }
+
+        fn line_text(&self, line: Line) -> String {
+            self.term
+                .bounds_to_string(
+                    AlacPoint::new(line, Column(0)),
+                    AlacPoint::new(line, self.term.last_column()),
+                )
+                .trim_end()
+                .to_string()
+        }
+    }
+
+    #[test]
+    fn test_marks_with_full_scrollback() {
+        let mut terminal = TestTerminal::new(10);
+        terminal.run("one", 5);
+        terminal.run("two", 100);
+        assert_eq!(terminal.term.history_size(), 10);
+        terminal.run("three", 12);
+        terminal.run("four", 3);
+        terminal.write("\x1b]133;A\x07$ ");
+
+        let cursor_line = terminal.term.grid().cursor.point.line;
+        let prompt = terminal
+            .commands
+            .prompt_line_near(cursor_line, true)
+            .unwrap();
+        assert_eq!(terminal.line_text(prompt), "$ four");
+        let (start, end) = terminal.commands.last_output_range(&terminal.term).unwrap();
+        assert_eq!(terminal.line_text(start.line), "four 0");
+        assert_eq!(terminal.line_text(end.line), "four 2");
+
+        // Commands that were dropped from the scrollback are above its top.
+        let previous = terminal.commands.prompt_line_near(prompt, true).unwrap();
+        assert_eq!(previous, Line(prompt.0 - 13));
+        assert!(previous < terminal.term.topmost_line());
+        let visible_marks = terminal.commands.visible_marks(&terminal.term);
+        assert_eq!(
+            visible_marks
+                .iter()
+                .map(|mark| terminal.line_text(mark.line))
+                .collect::<Vec<_>>(),
+            vec!["$ four"]
+        );
+    }
+
+    #[test]
+    fn test_parse_working_directory() {
+        assert_eq!(
+            parse_mark(b"633;P;Cwd=/tmp/a\\x3bb\\\\c"),
+            Some(ShellMark::WorkingDirectory(PathBuf::from("/tmp/a;b\\c")))
+        );
+        assert_eq!(
+            parse_mark(b"7;file://host/home/me/my%20dir"),
+            Some(ShellMark::WorkingDirectory(PathBuf::from(
+                "/home/me/my dir"
+            )))
+        );
+        assert_eq!(
+            parse_mark(b"633;E;echo \\x3b ok\\x0a"),
+            Some(ShellMark::CommandLine("echo ; ok\n".to_string()))
+        );
+    }
+}
@@ -0,0 +1,100 @@
+# Zed shell integration for bash.
+#
+# Reports prompts, commands and their exit status to Zed's terminal using the
+# OSC 133 and OSC 633 escape sequences. Loaded with `bash --init-file`.
+
+if [[ -f ~/.bashrc ]]; then
+    builtin source ~/.bashrc
+fi
+
+if [[ -n "${__zed_integration_loaded-}" || $- != *i* ]]; then
+    builtin return 0
+fi
+__zed_integration_loaded=1
+
+__zed_escape() {
+    local text="$1" out="" char i
+    for ((i = 0; i < ${#text}; i++)); do
+        char="${text:i:1}"
+        case "$char" in
+            '\') out+='\\' ;;
+            ';') out+='\x3b' ;;
+            $'\n') out+='\x0a' ;;
+            $'\a') out+='\x07' ;;
+            $'\e') out+='\x1b' ;;
+            *) out+="$char" ;;
+        esac
+    done
+    builtin printf '%s' "$out"
+}
+
+__zed_at_prompt=0
+__zed_command_running=0
+
+__zed_preexec() {
+    if [[ "$__zed_at_prompt" != 1 ]]; then
+        return
+    fi
+    __zed_at_prompt=0
+    __zed_command_running=1
+    local command="$1"
+    if [[ -z "$command" ]]; then
+        command="$(HISTTIMEFORMAT= builtin history 1)"
+        command="${command#*[0-9]  }"
+    fi
+    builtin printf '\e]633;E;%s\a\e]133;C\a' "$(__zed_escape "$command")"
+}
+
+__zed_precmd() {
+    if [[ "$__zed_command_running" == 1 ]]; then
+        builtin printf '\e]133;D;%s\a' "$__zed_status"
+        __zed_command_running=0
+    fi
+    builtin printf '\e]633;P;Cwd=%s\a' "$(__zed_escape "$PWD")"
+    if [[ "$PS1" != *'133;A'* ]]; then
+        PS1="\[\e]133;A\a\]$PS1\[\e]133;B\a\]"
+    fi
+}
+
+# Gives the user's prompt commands the exit status of the last command back.
+__zed_restore_status() {
+    return "$__zed_status"
+}
+
+if [[ -n "${bash_preexec_imported-}" ]]; then
+    # bash-preexec restores `$?` before calling each of its hooks.
+    __zed_bp_precmd() {
+        __zed_status=$?
+        __zed_at_prompt=0
+        __zed_precmd
+    }
+    __zed_bp_prompt_ready() {
+        __zed_at_prompt=1
+    }
+    precmd_functions=(__zed_bp_precmd "${precmd_functions[@]}" __zed_bp_prompt_ready)
+    preexec_functions+=(__zed_preexec)
+else
+    __zed_debug_trap() {
+        # Skip the commands that run as part of `PROMPT_COMMAND`. The user's own prompt
+        # commands run while `__zed_at_prompt` is still 0.
+        case "$BASH_COMMAND" in
+            __zed_*) return ;;
+        esac
+        __zed_preexec
+    }
+    trap '__zed_debug_trap' DEBUG
+
+    if (( BASH_VERSINFO[0] > 5 || (BASH_VERSINFO[0] == 5 && BASH_VERSINFO[1] >= 1) )) \
+        && [[ "$(builtin declare -p PROMPT_COMMAND 2>/dev/null)" == "declare -a"* ]]; then
+        PROMPT_COMMAND=(
+            '__zed_status=$?'
+            '__zed_at_prompt=0'
+            __zed_precmd
+            __zed_restore_status
+            "${PROMPT_COMMAND[@]}"
+            '__zed_at_prompt=1'
+        )
+    else
+        PROMPT_COMMAND="__zed_status=\$?; __zed_at_prompt=0; __zed_precmd; __zed_restore_status; ${PROMPT_COMMAND:+$PROMPT_COMMAND; }__zed_at_prompt=1"
+    fi
+fi
@@ -0,0 +1,55 @@
+# Zed shell integration for fish.
+#
+# Reports prompts, commands and their exit status to Zed's terminal using the
+# OSC 133 and OSC 633 escape sequences. Fish loads this from `vendor_conf.d`, because
+# Zed puts this directory first in `XDG_DATA_DIRS`.
+
+# Hide Zed's directory from the programs started in the terminal.
+if set -q ZED_USER_XDG_DATA_DIRS
+    if test -n "$ZED_USER_XDG_DATA_DIRS"
+        set -gx XDG_DATA_DIRS $ZED_USER_XDG_DATA_DIRS
+    else
+        set -e XDG_DATA_DIRS
+    end
+    set -e ZED_USER_XDG_DATA_DIRS
+end
+
+if not status is-interactive; or set -q __zed_integration_loaded
+    exit 0
+end
+set -g __zed_integration_loaded 1
+set -g __zed_command_running 0
+
+function __zed_escape
+    string replace -a '\\' '\\\\' -- $argv \
+        | string replace -a ';' '\\x3b' \
+        | string replace -a \a '\\x07' \
+        | string replace -a \e '\\x1b' \
+        | string join '\\x0a'
+end
+
+function __zed_prompt_start --on-event fish_prompt
+    set -l last_status $status
+    if test "$__zed_command_running" = 1
+        printf '\e]133;D;%s\a' $last_status
+        set -g __zed_command_running 0
+    end
+    printf '\e]633;P;Cwd=%s\a\e]133;A\a' (__zed_escape $PWD)
+
+    # The prompt is printed after this event, so the end of the prompt is marked by
+    # wrapping the user's `fish_prompt`. It may be redefined later, so check every time.
+    if not functions -q __zed_user_fish_prompt; or not functions -q fish_prompt; or not string match -q '*__zed_prompt_wrapped*' -- (functions fish_prompt)
+        functions -e __zed_user_fish_prompt
+        functions -q fish_prompt; and functions -c fish_prompt __zed_user_fish_prompt
+        function fish_prompt
+            # __zed_prompt_wrapped
+            functions -q __zed_user_fish_prompt; and __zed_user_fish_prompt
+            printf '\e]133;B\a'
+        end
+    end
+end
+
+function __zed_preexec --on-event fish_preexec
+    set -g __zed_command_running 1
+    printf '\e]633;E;%s\a\e]133;C\a' (__zed_escape $argv)
+end
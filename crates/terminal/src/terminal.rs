@@ -1,8 +1,12 @@
+pub mod inline_images;
 pub mod mappings;
 
 pub use alacritty_terminal;
 
 mod pty_info;
+mod pty_scanner;
+mod scrollback;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -13,7 +17,7 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Hyperlink},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
@@ -27,7 +31,11 @@ use alacritty_terminal::{
 use anyhow::{bail, Result};
 
 use futures::{
-    channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    channel::{
+        mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+    future::Shared,
     FutureExt,
 };
 
@@ -38,20 +46,25 @@ use mappings::mouse::{
 
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
+use inline_images::{ImagePlacement, InlineImages};
+use parking_lot::Mutex;
 use pty_info::PtyProcessInfo;
+use pty_scanner::{ScannedOutput, ScanningPty};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{percent_decode, CommandMark, ScrolledLines, ShellCommand, ShellCommands};
 use smol::channel::{Receiver, Sender};
-use task::{HideStrategy, Shell, TaskId};
+use task::{HideStrategy, ProblemCollector, ProblemMatcher, Shell, TaskId};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
-use util::{paths::home_dir, truncate_and_trailoff};
+use util::{paths::home_dir, truncate_and_trailoff, ResultExt};
 
 use std::{
     cmp::{self, min},
     fmt::Display,
     ops::{Deref, Index, RangeInclusive},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
 };
@@ -60,7 +73,7 @@ use thiserror::Error;
 use gpui::{
     actions, black, px, AnyWindowHandle, AppContext, Bounds, ClipboardItem, EventEmitter, Hsla,
     Keystroke, ModelContext, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
-    Pixels, Point, Rgba, ScrollWheelEvent, SharedString, Size, Task, TouchPhase,
+    Pixels, Point, RenderImage, Rgba, ScrollWheelEvent, SharedString, Size, Task, TouchPhase,
 };
 
 use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
@@ -80,6 +93,10 @@ actions!(
         ScrollToTop,
         ScrollToBottom,
         ToggleViMode,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        SelectLastCommandOutput,
+        CopyLastCommandOutput,
     ]
 );
 
@@ -108,6 +125,8 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The task's problem matchers found new problems in its output.
+    TaskProblemsChanged,
 }
 
 #[derive(Clone, Debug)]
@@ -136,6 +155,7 @@ enum InternalEvent {
     // FocusNextMatch,
     Scroll(AlacScroll),
     ScrollToAlacPoint(AlacPoint),
+    ScrollToPrompt { previous: bool },
     SetSelection(Option<(Selection, AlacPoint)>),
     UpdateSelection(Point<Pixels>),
     // Adjusted mouse position, should open
@@ -433,6 +453,17 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let task_ready_regex = task
+            .as_ref()
+            .and_then(|task| task.ready_pattern.as_deref())
+            .and_then(|pattern| Regex::new(pattern).log_err());
+        let (task_ready_tx, task_ready_rx) = oneshot::channel();
+        let task_problems = task
+            .as_ref()
+            .filter(|task| !task.problem_matchers.is_empty())
+            .map(|task| ProblemCollector::new(&task.problem_matchers, working_directory.clone()));
+        let scanned_output = Arc::new(Mutex::new(ScannedOutput::default()));
+        let pty = ScanningPty::new(pty, TerminalSize::default().into(), scanned_output.clone());
 
         //And connect them together
         let event_loop = EventLoop::new(
@@ -471,6 +502,16 @@ impl TerminalBuilder {
             word_regex: RegexSearch::new(WORD_REGEX).unwrap(),
             vi_mode_enabled: false,
             is_ssh_terminal,
+            scanned_output,
+            shell_commands: ShellCommands::default(),
+            scrolled_lines: ScrolledLines::new(scrolling_history),
+            images: InlineImages::default(),
+            hovered_hyperlink: false,
+            task_ready_regex,
+            task_problems,
+            task_scanned_lines: 0,
+            task_ready_tx: Some(task_ready_tx),
+            task_ready_rx: task_ready_rx.shared(),
         };
 
         Ok(TerminalBuilder {
@@ -569,6 +610,8 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub command_marks: Vec<CommandMark>,
+    pub images: Vec<ImagePlacement>,
 }
 
 #[derive(Clone)]
@@ -593,6 +636,8 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            command_marks: Vec::new(),
+            images: Vec::new(),
         }
     }
 }
@@ -629,6 +674,20 @@ pub struct Terminal {
     task: Option<TaskState>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    scanned_output: Arc<Mutex<ScannedOutput>>,
+    shell_commands: ShellCommands,
+    scrolled_lines: ScrolledLines,
+    images: InlineImages,
+    /// Whether the hovered word is an explicit OSC 8 hyperlink, which doesn't need the
+    /// secondary modifier to be held.
+    hovered_hyperlink: bool,
+    task_ready_regex: Option<Regex>,
+    task_problems: Option<ProblemCollector>,
+    /// How many lines of the task output were checked for the ready pattern and problems,
+    /// counting the lines that scrolled out of the history like [`ScrolledLines::absolute_line`].
+    task_scanned_lines: usize,
+    task_ready_tx: Option<oneshot::Sender<bool>>,
+    task_ready_rx: Shared<oneshot::Receiver<bool>>,
 }
 
 pub struct TaskState {
@@ -641,6 +700,12 @@ pub struct TaskState {
     pub hide: HideStrategy,
     pub show_summary: bool,
     pub show_command: bool,
+    /// A regular expression that marks the task as ready once a line of its output matches it.
+    pub ready_pattern: Option<String>,
+    /// Whether the task output matched its ready pattern.
+    pub ready: bool,
+    /// Problem matchers that turn the task output into diagnostics.
+    pub problem_matchers: Vec<ProblemMatcher>,
 }
 
 /// A status of the current terminal tab's task.
@@ -710,6 +775,7 @@ impl Terminal {
             }
             AlacTermEvent::Wakeup => {
                 cx.emit(Event::Wakeup);
+                self.scan_task_output(false, cx);
 
                 if self.pty_info.has_changed() {
                     cx.emit(Event::TitleChanged);
@@ -873,6 +939,13 @@ impl Terminal {
                 term.scroll_to_point(*point);
                 self.refresh_hovered_word();
             }
+            InternalEvent::ScrollToPrompt { previous } => {
+                let top = Line(-(term.grid().display_offset() as i32));
+                if let Some(prompt) = self.shell_commands.prompt_line_near(top, *previous) {
+                    term.scroll_display(AlacScroll::Delta(top.0 - prompt.0));
+                    self.refresh_hovered_word();
+                }
+            }
             InternalEvent::ToggleViMode => {
                 self.vi_mode_enabled = !self.vi_mode_enabled;
                 term.toggle_vi_mode();
@@ -890,7 +963,12 @@ impl Terminal {
                 )
                 .grid_clamp(term, Boundary::Grid);
 
-                let link = term.grid().index(point).hyperlink();
+                let link = term
+                    .grid()
+                    .index(point)
+                    .hyperlink()
+                    .filter(|link| !inline_images::is_placeholder(link.uri()));
+                self.hovered_hyperlink = link.is_some();
                 let found_word = if link.is_some() {
                     let mut min_index = point;
                     loop {
@@ -916,10 +994,12 @@ impl Terminal {
                         }
                     }
 
-                    let url = link.unwrap().uri().to_owned();
+                    let (target, is_url) = hyperlink_target(link.unwrap().uri());
                     let url_match = min_index..=max_index;
 
-                    Some((url, true, url_match))
+                    Some((target, is_url, url_match))
+                } else if !self.secondary_pressed {
+                    None
                 } else if let Some(url_match) = regex_match_at(term, point, &mut self.url_regex) {
                     let url = term.bounds_to_string(*url_match.start(), *url_match.end());
                     Some((url, true, url_match))
@@ -956,9 +1036,14 @@ impl Terminal {
                             } else {
                                 MaybeNavigationTarget::PathLike(PathLikeTarget {
                                     maybe_path: maybe_url_or_path,
-                                    terminal_dir: self.working_directory(),
+                                    terminal_dir: self.working_directory_at(*url_match.start()),
                                 })
                             };
+                            // Hyperlinks open without hovering them with the modifier first,
+                            // so check that their files exist now.
+                            if self.hovered_hyperlink && !is_url {
+                                cx.emit(Event::NewNavigationTarget(Some(target.clone())));
+                            }
                             cx.emit(Event::Open(target));
                         } else {
                             self.update_selected_word(
@@ -1001,6 +1086,7 @@ impl Terminal {
             }
         }
 
+        let word_match_start = *word_match.start();
         self.last_content.last_hovered_word = Some(HoveredWord {
             word: word.clone(),
             word_match,
@@ -1011,7 +1097,7 @@ impl Terminal {
         } else {
             MaybeNavigationTarget::PathLike(PathLikeTarget {
                 maybe_path: word,
-                terminal_dir: self.working_directory(),
+                terminal_dir: self.working_directory_at(word_match_start),
             })
         };
         cx.emit(Event::NewNavigationTarget(Some(navigation_target)));
@@ -1133,6 +1219,37 @@ impl Terminal {
             .push_back(InternalEvent::Scroll(AlacScroll::Bottom));
     }
 
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt { previous: true });
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt { previous: false });
+    }
+
+    pub fn select_last_command_output(&mut self) {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+        self.take_scanned_output(&terminal);
+        let Some((start, end)) = self.shell_commands.last_output_range(&terminal) else {
+            return;
+        };
+        drop(terminal);
+        let mut selection = Selection::new(SelectionType::Lines, start, AlacDirection::Left);
+        selection.update(end, AlacDirection::Right);
+        self.set_selection(Some((selection, end)));
+        self.events
+            .push_back(InternalEvent::ScrollToAlacPoint(start));
+    }
+
+    pub fn copy_last_command_output(&mut self, cx: &mut ModelContext<Self>) {
+        if let Some(output) = self.last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new_string(output));
+        }
+    }
+
     ///Resize the terminal and the PTY.
     pub fn set_size(&mut self, new_size: TerminalSize) {
         if self.last_content.size != new_size {
@@ -1300,12 +1417,56 @@ impl Terminal {
     pub fn sync(&mut self, cx: &mut ModelContext<Self>) {
         let term = self.term.clone();
         let mut terminal = term.lock_unfair();
+        self.take_scanned_output(&terminal);
         //Note that the ordering of events matters for event processing
         while let Some(e) = self.events.pop_front() {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
 
         self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content.command_marks = self.shell_commands.visible_marks(&terminal);
+        self.last_content.images = self.images.placements(&self.last_content.cells);
+        drop(terminal);
+        self.decode_images(cx);
+    }
+
+    /// Decodes the images picked up from the output in the background, and shows them once
+    /// they're ready.
+    fn decode_images(&mut self, cx: &mut ModelContext<Self>) {
+        for image in self.images.take_queued() {
+            let image = cx
+                .background_executor()
+                .spawn(async move { image.decode() });
+            cx.spawn(|this, mut cx| async move {
+                let image = image.await?;
+                this.update(&mut cx, |this, cx| {
+                    this.images.insert(image);
+                    cx.notify();
+                })
+            })
+            .detach_and_log_err(cx);
+        }
+    }
+
+    /// Takes the images that are no longer drawn, so that they can be removed from the windows
+    /// that drew them.
+    pub fn take_evicted_images(&mut self) -> Vec<Arc<RenderImage>> {
+        self.images.take_evicted()
+    }
+
+    /// All the images that may have been drawn, to be removed from the window when the view
+    /// that drew them goes away.
+    pub fn inline_images(&self) -> Vec<Arc<RenderImage>> {
+        self.images.render_images().cloned().collect()
+    }
+
+    /// Picks up the marks and images found in the output Alacritty has read so far.
+    fn take_scanned_output(&mut self, terminal: &Term<ZedListener>) {
+        let mut scanned = self.scanned_output.lock();
+        self.scrolled_lines.update(terminal, scanned.line_feeds);
+        self.shell_commands
+            .resolve(terminal, &mut scanned, &self.scrolled_lines);
+        self.images.queue(scanned.images.drain(..));
     }
 
     fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
@@ -1333,7 +1494,26 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            command_marks: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    /// The terminal's content, colors included, for [`Self::restore_scrollback`] to show again.
+    /// Returns `None` while a full screen program is using the alternate screen.
+    pub fn scrollback(&self) -> Option<String> {
+        let term = self.term.lock_unfair();
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return None;
         }
+        let max_lines = self.term_config.scrolling_history + term.screen_lines();
+        Some(scrollback::serialize(&term, max_lines))
+    }
+
+    /// Shows the content of a previous terminal above what this one shows already.
+    pub fn restore_scrollback(&mut self, contents: &str, cx: &mut ModelContext<Self>) {
+        scrollback::restore(&mut self.term.lock_unfair(), contents);
+        cx.notify();
     }
 
     pub fn last_n_non_empty_lines(&self, n: usize) -> Vec<String> {
@@ -1361,6 +1541,21 @@ impl Terminal {
         lines
     }
 
+    /// The output of the last command that was run, if the shell reports its commands.
+    pub fn last_command_output(&mut self) -> Option<String> {
+        let term = self.term.clone();
+        let terminal = term.lock_unfair();
+        self.take_scanned_output(&terminal);
+        let (start, end) = self.shell_commands.last_output_range(&terminal)?;
+        let output = terminal.bounds_to_string(start, end);
+        Some(output.trim_end().to_string())
+    }
+
+    /// The commands the shell reported, oldest first.
+    pub fn shell_commands(&self) -> impl Iterator<Item = &ShellCommand> {
+        self.shell_commands.commands()
+    }
+
     pub fn focus_in(&self) {
         if self.last_content.mode.contains(TermMode::FOCUS_IN_OUT) {
             self.write_to_pty("\x1b[I".to_string());
@@ -1410,11 +1605,23 @@ impl Terminal {
                     self.pty_tx.notify(bytes);
                 }
             }
-        } else if self.secondary_pressed {
+        } else if self.secondary_pressed
+            || self.hovered_hyperlink
+            || self.hyperlink_at(position).is_some()
+        {
             self.word_from_position(Some(position));
         }
     }
 
+    fn hyperlink_at(&self, position: Point<Pixels>) -> Option<Hyperlink> {
+        let mouse_cell_index = content_index_for_mouse(position, &self.last_content.size);
+        self.last_content
+            .cells
+            .get(mouse_cell_index)?
+            .hyperlink()
+            .filter(|link| !inline_images::is_placeholder(link.uri()))
+    }
+
     fn word_from_position(&mut self, position: Option<Point<Pixels>>) {
         if self.selection_phase == SelectionPhase::Selecting {
             self.last_content.last_hovered_word = None;
@@ -1548,14 +1755,11 @@ impl Terminal {
             }
 
             //Hyperlinks
-            if self.selection_phase == SelectionPhase::Ended {
-                let mouse_cell_index = content_index_for_mouse(position, &self.last_content.size);
-                if let Some(link) = self.last_content.cells[mouse_cell_index].hyperlink() {
-                    cx.open_url(link.uri());
-                } else if self.secondary_pressed {
-                    self.events
-                        .push_back(InternalEvent::FindHyperlink(position, true));
-                }
+            if self.selection_phase == SelectionPhase::Ended
+                && (self.secondary_pressed || self.hyperlink_at(position).is_some())
+            {
+                self.events
+                    .push_back(InternalEvent::FindHyperlink(position, true));
             }
         }
 
@@ -1651,6 +1855,15 @@ impl Terminal {
         }
     }
 
+    /// The working directory that paths printed at `point` are relative to: the one of the
+    /// command that printed them, when the shell reports it.
+    fn working_directory_at(&self, point: AlacPoint) -> Option<PathBuf> {
+        self.shell_commands
+            .working_directory_at(point.line)
+            .map(Path::to_path_buf)
+            .or_else(|| self.working_directory())
+    }
+
     /// Returns the working directory of the process that's connected to the PTY.
     /// That means it returns the working directory of the local shell or program
     /// that's running inside the terminal.
@@ -1715,7 +1928,7 @@ impl Terminal {
     }
 
     pub fn can_navigate_to_selected_word(&self) -> bool {
-        self.secondary_pressed && self.hovered_word
+        self.hovered_word && (self.secondary_pressed || self.hovered_hyperlink)
     }
 
     pub fn task(&self) -> Option<&TaskState> {
@@ -1734,12 +1947,82 @@ impl Terminal {
         Task::ready(())
     }
 
+    /// Waits until the task can be depended on: until its output matches its ready pattern,
+    /// or until it finishes, if there's no pattern. Resolves to `false` if the task failed first.
+    pub fn wait_for_task_ready(&self, cx: &AppContext) -> Task<bool> {
+        let task_ready = self.task_ready_rx.clone();
+        cx.spawn(|_| async move { task_ready.await.unwrap_or(false) })
+    }
+
+    /// Problems that the task's problem matchers found in its output so far.
+    pub fn task_problems(&self) -> Option<&ProblemCollector> {
+        self.task_problems.as_ref()
+    }
+
+    /// Checks the new task output for the ready pattern and problems.
+    fn scan_task_output(&mut self, include_cursor_line: bool, cx: &mut ModelContext<Self>) {
+        let Some(task) = &mut self.task else {
+            return;
+        };
+        let check_ready = self.task_ready_regex.is_some() && !task.ready;
+        if task.status != TaskStatus::Running || (!check_ready && self.task_problems.is_none()) {
+            return;
+        }
+        // Unless the task has finished, only check the lines that are finished,
+        // the one with the cursor may still be written to.
+        let term = self.term.lock();
+        self.scrolled_lines
+            .update(&term, self.scanned_output.lock().line_feeds);
+        let first_line = self
+            .scrolled_lines
+            .grid_line(self.task_scanned_lines)
+            .max(term.topmost_line());
+        let cursor_line = term.grid().cursor.point.line;
+        let last_line = if include_cursor_line {
+            cursor_line
+        } else {
+            Line(cursor_line.0 - 1)
+        };
+        if first_line > last_line {
+            return;
+        }
+        let output = term.bounds_to_string(
+            AlacPoint::new(first_line, Column(0)),
+            AlacPoint::new(last_line, term.last_column()),
+        );
+        drop(term);
+        self.task_scanned_lines = self.scrolled_lines.absolute_line(last_line) + 1;
+
+        if let Some(ready_regex) = self.task_ready_regex.as_ref().filter(|_| check_ready) {
+            if output.lines().any(|line| ready_regex.is_match(line)) {
+                task.ready = true;
+                if let Some(task_ready_tx) = self.task_ready_tx.take() {
+                    task_ready_tx.send(true).ok();
+                }
+            }
+        }
+        if let Some(task_problems) = &mut self.task_problems {
+            let mut found_problems = false;
+            for line in output.lines() {
+                found_problems |= task_problems.process_line(line);
+            }
+            if found_problems {
+                cx.emit(Event::TaskProblemsChanged);
+            }
+        }
+    }
+
     fn register_task_finished(
         &mut self,
         error_code: Option<i32>,
         cx: &mut ModelContext<'_, Terminal>,
     ) {
         self.completion_tx.try_send(()).ok();
+        // Wake up everyone waiting for the task, not only the first receiver.
+        self.completion_tx.close();
+        if let Some(task_ready_tx) = self.task_ready_tx.take() {
+            task_ready_tx.send(error_code == Some(0)).ok();
+        }
         let task = match &mut self.task {
             Some(task) => task,
             None => {
@@ -1752,6 +2035,11 @@ impl Terminal {
         if task.status != TaskStatus::Running {
             return;
         }
+        // The task won't write anything else, so the last line can be checked too.
+        self.scan_task_output(true, cx);
+        let Some(task) = &mut self.task else {
+            return;
+        };
         match error_code {
             Some(error_code) => {
                 task.status.register_task_exit(error_code);
@@ -1856,6 +2144,35 @@ impl Drop for Terminal {
 
 impl EventEmitter<Event> for Terminal {}
 
+/// Turns the URI of an OSC 8 hyperlink into a navigation target, and whether it's a URL.
+/// `file://` URIs, like the ones `ls --hyperlink` and compilers print, open in Zed.
+/// A `#123` or `#L123` fragment picks the line.
+fn hyperlink_target(uri: &str) -> (String, bool) {
+    let Some(path_start) = uri
+        .strip_prefix("file://")
+        .and_then(|rest| Some(rest.find('/')? + "file://".len()))
+    else {
+        return (uri.to_owned(), true);
+    };
+    let (path, fragment) = match uri[path_start..].split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (&uri[path_start..], None),
+    };
+    let mut path = percent_decode(path);
+    // `file:///C:/dir` names `C:/dir` on Windows.
+    if cfg!(windows) && path.get(2..3) == Some(":") {
+        path.remove(0);
+    }
+    if let Some(line) = fragment
+        .map(|fragment| fragment.trim_start_matches('L'))
+        .filter(|line| !line.is_empty() && line.chars().all(|c| c.is_ascii_digit()))
+    {
+        path.push(':');
+        path.push_str(line);
+    }
+    (path, false)
+}
+
 /// Based on alacritty/src/display/hint.rs > regex_match_at
 /// Retrieve the match, if the specified point is inside the content matching the regex.
 fn regex_match_at<T>(term: &Term<T>, point: AlacPoint, regex: &mut RegexSearch) -> Option<Match> {
@@ -1997,7 +2314,8 @@ mod tests {
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, hyperlink_target, rgb_for_index, IndexedCell, TerminalContent,
+        TerminalSize,
     };
 
     #[test]
@@ -2175,4 +2493,28 @@ mod tests {
             vec!["Main.cs:20:5:Error", "desc"],
         );
     }
+
+    #[test]
+    fn test_hyperlink_target() {
+        assert_eq!(
+            hyperlink_target("https://zed.dev/docs"),
+            ("https://zed.dev/docs".to_string(), true)
+        );
+        assert_eq!(
+            hyperlink_target("file://my-host/home/me/my%20file.rs"),
+            ("/home/me/my file.rs".to_string(), false)
+        );
+        assert_eq!(
+            hyperlink_target("file:///home/me/main.rs#L20"),
+            ("/home/me/main.rs:20".to_string(), false)
+        );
+        assert_eq!(
+            hyperlink_target("file:///home/me/main.rs:20:5"),
+            ("/home/me/main.rs:20:5".to_string(), false)
+        );
+        assert_eq!(
+            hyperlink_target("file://no-path"),
+            ("file://no-path".to_string(), true)
+        );
+    }
 }
@@ -46,6 +46,8 @@ pub struct TerminalSettings {
     pub default_height: Pixels,
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
+    pub shell_integration: bool,
+    pub restore_scrollback: bool,
     pub toolbar: Toolbar,
 }
 
@@ -185,6 +187,17 @@ pub struct TerminalSettingsContent {
     ///
     /// Default: 10_000
     pub max_scroll_history_lines: Option<usize>,
+    /// Whether to load Zed's shell integration into bash, zsh and fish, so that the terminal
+    /// knows where prompts and commands start and whether the commands succeeded.
+    /// Existing terminals will not pick up this change until they are recreated.
+    ///
+    /// Default: true
+    pub shell_integration: Option<bool>,
+    /// Whether to save the scrollback of terminals along with the workspace, and show it
+    /// again when the workspace is reopened.
+    ///
+    /// Default: true
+    pub restore_scrollback: Option<bool>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
 }
@@ -1,6 +1,6 @@
 use editor::{CursorLayout, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    div, fill, point, px, relative, size, AnyElement, AvailableSpace, Bounds, ContentMask,
+    div, fill, point, px, relative, size, AnyElement, AvailableSpace, Bounds, ContentMask, Corners,
     DispatchPhase, Element, ElementId, FocusHandle, Font, FontStyle, FontWeight, GlobalElementId,
     HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity, IntoElement,
     LayoutId, Model, ModelContext, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels,
@@ -20,11 +20,14 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    inline_images::ImagePlacement,
+    shell_integration::CommandStatus,
     terminal_settings::TerminalSettings,
     HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
 use ui::{ParentElement, Tooltip};
+use util::ResultExt;
 use workspace::Workspace;
 
 use std::mem;
@@ -45,6 +48,9 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    /// The display lines of the commands reported by the shell, and the color of their marks.
+    command_marks: Vec<(i32, Hsla)>,
+    images: Vec<ImagePlacement>,
     last_hovered_word: Option<HoveredWord>,
     block_below_cursor_element: Option<AnyElement>,
 }
@@ -715,6 +721,12 @@ impl Element for TerminalElement {
                         None
                     }
                 });
+                for image in self
+                    .terminal
+                    .update(cx, |terminal, _| terminal.take_evicted_images())
+                {
+                    cx.drop_image(image).log_err();
+                }
 
                 let scroll_top = self.terminal_view.read(cx).scroll_top;
                 let hyperlink_tooltip = last_hovered_word.clone().map(|hovered_word| {
@@ -735,11 +747,30 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    command_marks,
+                    images,
                     ..
                 } = &self.terminal.read(cx).last_content;
+                let images = images.clone();
                 let mode = *mode;
                 let display_offset = *display_offset;
 
+                let command_marks = command_marks
+                    .iter()
+                    .map(|mark| {
+                        let color = match mark.status {
+                            CommandStatus::Finished { .. } if mark.status.failed() => {
+                                theme.status().error
+                            }
+                            CommandStatus::Finished { exit_code: Some(_) } => {
+                                theme.status().success
+                            }
+                            _ => theme.colors().text_muted,
+                        };
+                        (mark.line.0 + display_offset as i32, color)
+                    })
+                    .collect();
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
@@ -855,6 +886,8 @@ impl Element for TerminalElement {
                     display_offset,
                     hyperlink_tooltip,
                     gutter,
+                    command_marks,
+                    images,
                     last_hovered_word,
                     block_below_cursor_element,
                 }
@@ -919,6 +952,16 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, cx);
                     }
 
+                    let mark_width = layout.gutter / 4.;
+                    for (line, color) in &layout.command_marks {
+                        let mark_origin = point(
+                            bounds.origin.x + (layout.gutter - mark_width) / 2.,
+                            origin.y + *line as f32 * layout.dimensions.line_height,
+                        );
+                        let mark_size = size(mark_width, layout.dimensions.line_height);
+                        cx.paint_quad(fill(Bounds::new(mark_origin, mark_size), *color));
+                    }
+
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
                     {
@@ -940,6 +983,10 @@ impl Element for TerminalElement {
                         cell.paint(origin, &layout.dimensions, bounds, cx);
                     }
 
+                    for placement in &layout.images {
+                        paint_image(placement, origin, layout, cx);
+                    }
+
                     if self.cursor_visible {
                         if let Some(mut cursor) = cursor {
                             cursor.paint(origin, cx);
@@ -958,6 +1005,40 @@ impl Element for TerminalElement {
     }
 }
 
+/// Paints an inline image over the blank cells that hold its place.
+fn paint_image(
+    placement: &ImagePlacement,
+    origin: Point<Pixels>,
+    layout: &LayoutState,
+    cx: &mut WindowContext,
+) {
+    let cell_width = layout.dimensions.cell_width;
+    let line_height = layout.dimensions.line_height;
+    let line = placement.origin.line.0 + layout.display_offset as i32;
+    let image = &placement.image;
+    let mut bounds = Bounds::new(
+        point(
+            origin.x + placement.origin.column.0 as f32 * cell_width,
+            origin.y + line as f32 * line_height,
+        ),
+        size(
+            image.columns as f32 * cell_width,
+            image.rows as f32 * line_height,
+        ),
+    );
+    if image.preserve_aspect_ratio {
+        let image_size = image.image.size(0);
+        let scale = (bounds.size.width.0 / image_size.width.0.max(1) as f32)
+            .min(bounds.size.height.0 / image_size.height.0.max(1) as f32);
+        bounds.size = size(
+            px(image_size.width.0 as f32 * scale),
+            px(image_size.height.0 as f32 * scale),
+        );
+    }
+    cx.paint_image(bounds, Corners::default(), image.image.clone(), 0, false)
+        .log_err();
+}
+
 impl IntoElement for TerminalElement {
     type Element = Self;
 
@@ -1,4 +1,6 @@
-use std::{cmp, ops::ControlFlow, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    cell::RefCell, cmp, mem, ops::ControlFlow, path::PathBuf, rc::Rc, sync::Arc, time::Duration,
+};
 
 use crate::{
     default_working_directory,
@@ -10,20 +12,24 @@ use crate::{
 use breadcrumbs::Breadcrumbs;
 use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
-use futures::future::join_all;
+use futures::{
+    channel::oneshot,
+    future::{join_all, Shared},
+    FutureExt as _,
+};
 use gpui::{
     actions, Action, AnchorCorner, AnyView, AppContext, AsyncWindowContext, EventEmitter,
     ExternalPaths, FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render,
     Styled, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
-use project::{terminals::TerminalKind, Fs, Project, ProjectEntryId};
+use project::{terminals::TerminalKind, Fs, Inventory, Project, ProjectEntryId, TaskRunStatus};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use settings::Settings;
-use task::{RevealStrategy, Shell, SpawnInTerminal, TaskId};
+use task::{DependsOrder, RevealStrategy, Shell, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{TerminalDockPosition, TerminalSettings},
-    Terminal,
+    TaskStatus, Terminal,
 };
 use ui::{
     prelude::*, ButtonCommon, Clickable, ContextMenu, FluentBuilder, PopoverMenu, Selectable,
@@ -40,7 +46,7 @@ use workspace::{
     SplitUp, SwapPaneInDirection, ToggleZoom, Workspace,
 };
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use zed_actions::InlineAssist;
 
 const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
@@ -255,7 +261,7 @@ impl TerminalPanel {
                 .update(&mut cx, |_, cx| {
                     cx.subscribe(&workspace, |terminal_panel, _, e, cx| {
                         if let workspace::Event::SpawnTask(spawn_in_terminal) = e {
-                            terminal_panel.spawn_task(spawn_in_terminal, cx);
+                            terminal_panel.run_task(spawn_in_terminal.as_ref().clone(), cx);
                         };
                     })
                     .detach();
@@ -418,7 +424,135 @@ impl TerminalPanel {
             .detach_and_log_err(cx);
     }
 
-    fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
+    /// Runs the task once all the tasks it depends on are ready.
+    fn run_task(&mut self, spawn_in_terminal: SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        let started_tasks = Rc::default();
+        let run = self.run_task_with_dependencies(
+            spawn_in_terminal,
+            false,
+            Rc::clone(&started_tasks),
+            cx,
+        );
+        cx.spawn(move |_, _| async move {
+            run.await;
+            // Started tasks refer to the map themselves, break the cycle once they are done.
+            started_tasks.borrow_mut().clear();
+        })
+        .detach();
+    }
+
+    /// Starts the task after its dependencies and returns whether it got ready, recording
+    /// the statuses of all of them in the task inventory.
+    /// Dependencies shared by several tasks of the same run are started once.
+    fn run_task_with_dependencies(
+        &mut self,
+        mut spawn_in_terminal: SpawnInTerminal,
+        is_dependency: bool,
+        started_tasks: StartedTasks,
+        cx: &mut ViewContext<Self>,
+    ) -> Shared<Task<bool>> {
+        if let Some(run) = started_tasks.borrow().get(&spawn_in_terminal.id) {
+            return run.clone();
+        }
+
+        let id = spawn_in_terminal.id.clone();
+        let dependencies = mem::take(&mut spawn_in_terminal.dependencies);
+        let run = cx
+            .spawn({
+                let id = id.clone();
+                let started_tasks = Rc::clone(&started_tasks);
+                move |terminal_panel, mut cx| async move {
+                    let ready = run_task_after_dependencies(
+                        terminal_panel.clone(),
+                        spawn_in_terminal,
+                        dependencies,
+                        is_dependency,
+                        started_tasks,
+                        &mut cx,
+                    )
+                    .await
+                    .log_err();
+                    if ready.is_none() {
+                        terminal_panel
+                            .update(&mut cx, |terminal_panel, cx| {
+                                terminal_panel.set_task_status(id, TaskRunStatus::Failed, cx)
+                            })
+                            .ok();
+                    }
+                    ready.unwrap_or(false)
+                }
+            })
+            .shared();
+        started_tasks.borrow_mut().insert(id, run.clone());
+        run
+    }
+
+    fn set_task_status(&self, id: TaskId, status: TaskRunStatus, cx: &mut AppContext) {
+        self.update_task_inventory(cx, |task_inventory| {
+            task_inventory.set_task_status(id, status)
+        });
+    }
+
+    /// Forgets the state of the task that ran in a terminal that was closed, unless it runs
+    /// in another terminal already.
+    fn forget_task_status(&self, id: TaskId, cx: &mut AppContext) {
+        let runs_elsewhere = self.center.panes().into_iter().any(|pane| {
+            pane.read(cx).items().any(|item| {
+                item.act_as::<TerminalView>(cx)
+                    .is_some_and(|terminal_view| {
+                        terminal_view
+                            .read(cx)
+                            .terminal()
+                            .read(cx)
+                            .task()
+                            .is_some_and(|task| task.id == id)
+                    })
+            })
+        });
+        if !runs_elsewhere {
+            self.update_task_inventory(cx, |task_inventory| task_inventory.forget_task_status(&id));
+        }
+    }
+
+    fn update_task_inventory(&self, cx: &mut AppContext, update: impl FnOnce(&mut Inventory)) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let task_inventory = workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .task_store()
+            .read(cx)
+            .task_inventory()
+            .cloned();
+        if let Some(task_inventory) = task_inventory {
+            task_inventory.update(cx, |task_inventory, cx| {
+                update(task_inventory);
+                cx.notify();
+            });
+        }
+    }
+
+    /// Returns the terminal where the task with the label given still runs, if any.
+    fn running_task_terminal(&self, label: &str, cx: &mut AppContext) -> Option<Model<Terminal>> {
+        self.terminals_for_task(label, cx)
+            .into_iter()
+            .map(|(_, _, terminal_view)| terminal_view.read(cx).terminal().clone())
+            .find(|terminal| {
+                terminal
+                    .read(cx)
+                    .task()
+                    .is_some_and(|task| task.status == TaskStatus::Running)
+            })
+    }
+
+    /// Spawns the task in a terminal right away, without running the tasks it depends on.
+    pub fn spawn_task(
+        &mut self,
+        spawn_in_terminal: &SpawnInTerminal,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<Model<Terminal>>> {
         let mut spawn_task = spawn_in_terminal.clone();
         // Set up shell args unconditionally, as tasks are always spawned inside of a shell.
         let Some((shell, mut user_args)) = (match spawn_in_terminal.shell.clone() {
@@ -434,13 +568,13 @@ impl TerminalPanel {
                             Some(("\"${SHELL:-sh}\"".to_string(), Vec::new()))
                         }
                     }
-                    Err(_no_window_e) => return,
+                    Err(no_window_e) => return Task::ready(Err(no_window_e)),
                 }
             }
             Shell::Program(shell) => Some((shell, Vec::new())),
             Shell::WithArguments { program, args, .. } => Some((program, args)),
         }) else {
-            return;
+            return Task::ready(Err(anyhow::anyhow!("No shell to run the task in")));
         };
         #[cfg(target_os = "windows")]
         let windows_shell_type = to_windows_shell_type(&shell);
@@ -503,16 +637,12 @@ impl TerminalPanel {
         let use_new_terminal = spawn_in_terminal.use_new_terminal;
 
         if allow_concurrent_runs && use_new_terminal {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, cx);
         }
 
         let terminals_for_task = self.terminals_for_task(&spawn_in_terminal.full_label, cx);
         if terminals_for_task.is_empty() {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, cx);
         }
         let (existing_item_index, task_pane, existing_terminal) = terminals_for_task
             .last()
@@ -534,42 +664,46 @@ impl TerminalPanel {
                         cx,
                     )
                 })?
-                .await;
+                .await
+                .context("replacing the task terminal")
             } else {
+                let (new_terminal_tx, new_terminal_rx) = oneshot::channel();
                 this.update(&mut cx, |this, cx| {
                     this.deferred_tasks.insert(
                         id,
                         cx.spawn(|terminal_panel, mut cx| async move {
                             wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                            let Ok(Some(new_terminal_task)) =
+                            let Ok(new_terminal_task) =
                                 terminal_panel.update(&mut cx, |terminal_panel, cx| {
                                     if use_new_terminal {
-                                        terminal_panel
-                                            .spawn_in_new_terminal(spawn_task, cx)
-                                            .detach_and_log_err(cx);
-                                        None
+                                        terminal_panel.spawn_in_new_terminal(spawn_task, cx)
                                     } else {
-                                        Some(terminal_panel.replace_terminal(
+                                        let replace_terminal = terminal_panel.replace_terminal(
                                             spawn_task,
                                             task_pane,
                                             existing_item_index,
                                             existing_terminal,
                                             cx,
-                                        ))
+                                        );
+                                        cx.spawn(|_, _| async move {
+                                            replace_terminal
+                                                .await
+                                                .context("replacing the task terminal")
+                                        })
                                     }
                                 })
                             else {
                                 return;
                             };
-                            new_terminal_task.await;
+                            new_terminal_tx.send(new_terminal_task.await).ok();
                         }),
                     );
-                })
-                .ok();
+                })?;
+                new_terminal_rx
+                    .await
+                    .context("the task was rescheduled before it started")?
             }
-            anyhow::Result::<_, anyhow::Error>::Ok(())
         })
-        .detach()
     }
 
     pub fn spawn_in_new_terminal(
@@ -742,7 +876,7 @@ impl TerminalPanel {
         terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
-    ) -> Task<Option<()>> {
+    ) -> Task<Option<Model<Terminal>>> {
         let reveal = spawn_task.reveal;
         let window = cx.window_handle();
         let task_workspace = self.workspace.clone();
@@ -764,7 +898,7 @@ impl TerminalPanel {
                 .log_err()?;
             terminal_to_replace
                 .update(&mut cx, |terminal_to_replace, cx| {
-                    terminal_to_replace.set_terminal(new_terminal, cx);
+                    terminal_to_replace.set_terminal(new_terminal.clone(), cx);
                 })
                 .ok()?;
 
@@ -798,7 +932,7 @@ impl TerminalPanel {
                 RevealStrategy::Never => {}
             }
 
-            Some(())
+            Some(new_terminal)
         })
     }
 
@@ -949,6 +1083,133 @@ pub fn new_terminal_pane(
     pane
 }
 
+type StartedTasks = Rc<RefCell<HashMap<TaskId, Shared<Task<bool>>>>>;
+
+async fn run_task_after_dependencies(
+    terminal_panel: WeakView<TerminalPanel>,
+    spawn_in_terminal: SpawnInTerminal,
+    dependencies: Vec<SpawnInTerminal>,
+    is_dependency: bool,
+    started_tasks: StartedTasks,
+    cx: &mut AsyncWindowContext,
+) -> Result<bool> {
+    let id = spawn_in_terminal.id.clone();
+    let set_status = |status, cx: &mut AsyncWindowContext| {
+        terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.set_task_status(id.clone(), status, cx)
+        })
+    };
+
+    // Background tasks that are already running are not restarted for the tasks depending on them.
+    if is_dependency && spawn_in_terminal.ready_pattern.is_some() {
+        let running_terminal = terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.running_task_terminal(&spawn_in_terminal.full_label, cx)
+        })?;
+        if let Some(terminal) = running_terminal {
+            return wait_for_task_ready(terminal, terminal_panel, id, cx).await;
+        }
+    }
+
+    if !dependencies.is_empty() {
+        set_status(TaskRunStatus::Waiting, cx)?;
+        let run_dependency = |dependency, cx: &mut AsyncWindowContext| {
+            terminal_panel.update(cx, |terminal_panel, cx| {
+                terminal_panel.run_task_with_dependencies(
+                    dependency,
+                    true,
+                    Rc::clone(&started_tasks),
+                    cx,
+                )
+            })
+        };
+        let dependencies_ready = match spawn_in_terminal.depends_order {
+            DependsOrder::Parallel => {
+                let runs = dependencies
+                    .into_iter()
+                    .map(|dependency| run_dependency(dependency, cx))
+                    .collect::<Result<Vec<_>>>()?;
+                join_all(runs).await.into_iter().all(|ready| ready)
+            }
+            DependsOrder::Sequence => {
+                let mut dependencies_ready = true;
+                for dependency in dependencies {
+                    if !run_dependency(dependency, cx)?.await {
+                        dependencies_ready = false;
+                        break;
+                    }
+                }
+                dependencies_ready
+            }
+        };
+        if !dependencies_ready {
+            set_status(TaskRunStatus::Failed, cx)?;
+            return Ok(false);
+        }
+    }
+
+    // Tasks without a command only group the tasks they depend on.
+    if spawn_in_terminal.command.is_empty() {
+        set_status(TaskRunStatus::Succeeded, cx)?;
+        return Ok(true);
+    }
+    let terminal = terminal_panel
+        .update(cx, |terminal_panel, cx| {
+            terminal_panel.spawn_task(&spawn_in_terminal, cx)
+        })?
+        .await?;
+    wait_for_task_ready(terminal, terminal_panel, id, cx).await
+}
+
+async fn wait_for_task_ready(
+    terminal: Model<Terminal>,
+    terminal_panel: WeakView<TerminalPanel>,
+    id: TaskId,
+    cx: &mut AsyncWindowContext,
+) -> Result<bool> {
+    terminal_panel.update(cx, |_, cx| {
+        let id = id.clone();
+        cx.observe_release(&terminal, move |terminal_panel, _, cx| {
+            terminal_panel.forget_task_status(id.clone(), cx)
+        })
+        .detach();
+    })?;
+    update_task_status(&terminal, &terminal_panel, &id, cx)?;
+    let ready = terminal
+        .update(cx, |terminal, cx| terminal.wait_for_task_ready(cx))?
+        .await;
+    if update_task_status(&terminal, &terminal_panel, &id, cx)? == TaskRunStatus::Ready {
+        // Background tasks keep running after they got ready, track them until they finish.
+        let completed = terminal.update(cx, |terminal, cx| terminal.wait_for_completed_task(cx))?;
+        cx.spawn(|mut cx| async move {
+            completed.await;
+            update_task_status(&terminal, &terminal_panel, &id, &mut cx).log_err();
+        })
+        .detach();
+    }
+    Ok(ready)
+}
+
+fn update_task_status(
+    terminal: &Model<Terminal>,
+    terminal_panel: &WeakView<TerminalPanel>,
+    id: &TaskId,
+    cx: &mut AsyncWindowContext,
+) -> Result<TaskRunStatus> {
+    let status = terminal.read_with(cx, |terminal, _| match terminal.task() {
+        Some(task) => match task.status {
+            TaskStatus::Running if task.ready => TaskRunStatus::Ready,
+            TaskStatus::Running => TaskRunStatus::Running,
+            TaskStatus::Completed { success: true } => TaskRunStatus::Succeeded,
+            TaskStatus::Completed { success: false } | TaskStatus::Unknown => TaskRunStatus::Failed,
+        },
+        None => TaskRunStatus::Failed,
+    })?;
+    terminal_panel.update(cx, |terminal_panel, cx| {
+        terminal_panel.set_task_status(id.clone(), status, cx)
+    })?;
+    Ok(status)
+}
+
 async fn wait_for_terminals_tasks(
     terminals_for_task: Vec<(usize, View<Pane>, View<TerminalView>)>,
     cx: &mut AsyncWindowContext,
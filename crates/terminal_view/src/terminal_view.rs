@@ -6,10 +6,10 @@ use collections::HashSet;
 use editor::{actions::SelectAll, scroll::Autoscroll, Editor};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
-    anchored, deferred, div, impl_actions, AnyElement, AppContext, DismissEvent, EventEmitter,
-    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
-    MouseDownEvent, Pixels, Render, ScrollWheelEvent, Styled, Subscription, Task, View,
-    VisualContext, WeakView,
+    anchored, deferred, div, impl_actions, AnyElement, AnyWindowHandle, AppContext, DismissEvent,
+    EventEmitter, FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model,
+    MouseButton, MouseDownEvent, Pixels, Render, ScrollWheelEvent, Styled, Subscription, Task,
+    View, VisualContext, WeakView,
 };
 use language::Bias;
 use persistence::TERMINAL_DB;
@@ -20,9 +20,10 @@ use terminal::{
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{CursorShape, TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp, ScrollPageDown,
-    ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal,
-    TerminalSize, ToggleViMode,
+    Clear, Copy, CopyLastCommandOutput, Event, MaybeNavigationTarget, Paste, ScrollLineDown,
+    ScrollLineUp, ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ScrollToTop, SelectLastCommandOutput, ShowCharacterPalette, TaskStatus,
+    Terminal, TerminalSize, ToggleViMode,
 };
 use terminal_element::{is_blank, TerminalElement};
 use terminal_panel::TerminalPanel;
@@ -33,8 +34,8 @@ use workspace::{
     notifications::NotifyResultExt,
     register_serializable_item,
     searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
-    CloseActiveItem, NewCenterTerminal, NewTerminal, OpenVisible, ToolbarItemLocation, Workspace,
-    WorkspaceId,
+    CloseActiveItem, ItemId, NewCenterTerminal, NewTerminal, OpenVisible, ToolbarItemLocation,
+    Workspace, WorkspaceId,
 };
 
 use anyhow::Context;
@@ -44,12 +45,12 @@ use smol::Timer;
 use zed_actions::InlineAssist;
 
 use std::{
-    cmp,
+    cmp, mem,
     ops::RangeInclusive,
     path::{Path, PathBuf},
     rc::Rc,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 const REGEX_SPECIAL_CHARS: &[char] = &[
@@ -57,6 +58,8 @@ const REGEX_SPECIAL_CHARS: &[char] = &[
 ];
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
+/// How often the scrollback is saved while the terminal is in use. It is also saved on quit.
+const SCROLLBACK_SAVE_INTERVAL: Duration = Duration::from_secs(10);
 
 const GIT_DIFF_PATH_PREFIXES: &[char] = &['a', 'b'];
 
@@ -112,6 +115,7 @@ pub struct TerminalView {
     show_breadcrumbs: bool,
     block_below_cursor: Option<Rc<BlockProperties>>,
     scroll_top: Pixels,
+    scrollback_saved_at: Option<Instant>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -187,6 +191,24 @@ impl TerminalView {
         let cursor_shape = TerminalSettings::get_global(cx)
             .cursor_shape
             .unwrap_or_default();
+        let save_on_quit = {
+            let view = cx.view().downgrade();
+            let item_id = cx.entity_id().as_u64();
+            cx.on_app_quit(move |cx| {
+                let save = view
+                    .upgrade()
+                    .and_then(|view| view.read(cx).save_scrollback(item_id, cx));
+                async move {
+                    if let Some(save) = save {
+                        save.await.log_err();
+                    }
+                }
+            })
+        };
+
+        let drop_images = cx.on_release(|this, window, cx| {
+            drop_terminal_images(&this.terminal, window, cx);
+        });
 
         Self {
             terminal,
@@ -204,9 +226,12 @@ impl TerminalView {
             show_breadcrumbs: TerminalSettings::get_global(cx).toolbar.breadcrumbs,
             block_below_cursor: None,
             scroll_top: Pixels::ZERO,
+            scrollback_saved_at: None,
             _subscriptions: vec![
                 focus_in,
                 focus_out,
+                save_on_quit,
+                drop_images,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
             ],
             _terminal_subscriptions: terminal_subscriptions,
@@ -217,6 +242,27 @@ impl TerminalView {
         &self.terminal
     }
 
+    fn save_scrollback(
+        &self,
+        item_id: ItemId,
+        cx: &AppContext,
+    ) -> Option<Task<anyhow::Result<()>>> {
+        let workspace_id = self.workspace_id?;
+        if !TerminalSettings::get_global(cx).restore_scrollback {
+            return None;
+        }
+        let terminal = self.terminal.read(cx);
+        if terminal.task().is_some() {
+            return None;
+        }
+        let scrollback = terminal.scrollback()?;
+        Some(cx.background_executor().spawn(async move {
+            TERMINAL_DB
+                .save_scrollback(item_id, workspace_id, scrollback)
+                .await
+        }))
+    }
+
     pub fn has_bell(&self) -> bool {
         self.has_bell
     }
@@ -245,6 +291,7 @@ impl TerminalView {
                 .action("Copy", Box::new(Copy))
                 .action("Paste", Box::new(Paste))
                 .action("Select All", Box::new(SelectAll))
+                .action("Copy Last Command Output", Box::new(CopyLastCommandOutput))
                 .action("Clear", Box::new(Clear))
                 .when(assistant_enabled, |menu| {
                     menu.separator()
@@ -443,6 +490,38 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn select_last_command_output(
+        &mut self,
+        _: &SelectLastCommandOutput,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.select_last_command_output());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, cx| term.copy_last_command_output(cx));
+        cx.notify();
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, cx: &mut ViewContext<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -637,10 +716,28 @@ impl TerminalView {
     fn set_terminal(&mut self, terminal: Model<Terminal>, cx: &mut ViewContext<'_, TerminalView>) {
         self._terminal_subscriptions =
             subscribe_for_terminal_events(&terminal, self.workspace.clone(), cx);
-        self.terminal = terminal;
+        let previous_terminal = mem::replace(&mut self.terminal, terminal);
+        for image in previous_terminal.read(cx).inline_images() {
+            cx.drop_image(image).log_err();
+        }
     }
 }
 
+/// Removes the terminal's inline images from the window, once the view that drew them is gone.
+fn drop_terminal_images(terminal: &Model<Terminal>, window: AnyWindowHandle, cx: &mut AppContext) {
+    let images = terminal.read(cx).inline_images();
+    if images.is_empty() {
+        return;
+    }
+    window
+        .update(cx, |_, cx| {
+            for image in images {
+                cx.drop_image(image).log_err();
+            }
+        })
+        .ok();
+}
+
 fn subscribe_for_terminal_events(
     terminal: &Model<Terminal>,
     workspace: WeakView<Workspace>,
@@ -985,6 +1082,10 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::select_last_command_output))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
@@ -1199,15 +1300,39 @@ impl SerializableItem for TerminalView {
             return None;
         }
 
-        if let Some((cwd, workspace_id)) = terminal.working_directory().zip(self.workspace_id) {
-            Some(cx.background_executor().spawn(async move {
-                TERMINAL_DB
-                    .save_working_directory(item_id, workspace_id, cwd)
-                    .await
-            }))
+        let save_working_directory =
+            terminal
+                .working_directory()
+                .zip(self.workspace_id)
+                .map(|(cwd, workspace_id)| {
+                    cx.background_executor().spawn(async move {
+                        TERMINAL_DB
+                            .save_working_directory(item_id, workspace_id, cwd)
+                            .await
+                    })
+                });
+        // Serializing the whole scrollback is too slow to do on every update of the terminal.
+        let save_scrollback = if self.scrollback_saved_at.map_or(true, |saved_at| {
+            saved_at.elapsed() >= SCROLLBACK_SAVE_INTERVAL
+        }) {
+            self.scrollback_saved_at = Some(Instant::now());
+            self.save_scrollback(item_id, cx)
         } else {
             None
+        };
+        if save_working_directory.is_none() && save_scrollback.is_none() {
+            return None;
         }
+
+        Some(cx.background_executor().spawn(async move {
+            if let Some(save_working_directory) = save_working_directory {
+                save_working_directory.await?;
+            }
+            if let Some(save_scrollback) = save_scrollback {
+                save_scrollback.await?;
+            }
+            Ok(())
+        }))
     }
 
     fn should_serialize(&self, event: &Self::Event) -> bool {
@@ -1223,6 +1348,19 @@ impl SerializableItem for TerminalView {
     ) -> Task<anyhow::Result<View<Self>>> {
         let window = cx.window_handle();
         cx.spawn(|mut cx| async move {
+            let scrollback = cx
+                .update(|cx| {
+                    if TerminalSettings::get_global(cx).restore_scrollback {
+                        TERMINAL_DB
+                            .get_scrollback(item_id, workspace_id)
+                            .log_err()
+                            .flatten()
+                    } else {
+                        None
+                    }
+                })
+                .ok()
+                .flatten();
             let cwd = cx
                 .update(|cx| {
                     let from_db = TERMINAL_DB
@@ -1248,6 +1386,11 @@ impl SerializableItem for TerminalView {
                     project.create_terminal(TerminalKind::Shell(cwd), window, cx)
                 })?
                 .await?;
+            if let Some(scrollback) = scrollback {
+                terminal.update(&mut cx, |terminal, cx| {
+                    terminal.restore_scrollback(&scrollback, cx)
+                })?;
+            }
             cx.update(|cx| {
                 cx.new_view(|cx| TerminalView::new(terminal, workspace, Some(workspace_id), cx))
             })
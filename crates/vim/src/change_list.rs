@@ -1,7 +1,10 @@
 use editor::{display_map::ToDisplayPoint, movement, scroll::Autoscroll, Bias, Direction, Editor};
 use gpui::{actions, ViewContext};
 
-use crate::{state::Mode, Vim};
+use crate::{
+    state::{editor_file, FilePosition, Mode},
+    Vim,
+};
 
 actions!(vim, [ChangeListOlder, ChangeListNewer]);
 
@@ -77,6 +80,47 @@ impl Vim {
             self.change_list.pop();
         }
         self.change_list.push(new_positions);
+        self.remember_change_list(cx);
+    }
+
+    /// Copies the change list into the globals, so that it survives the editor
+    /// being closed and can be persisted with the workspace.
+    fn remember_change_list(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((path, positions)) = self
+            .update_editor(cx, |vim, editor, cx| {
+                let (buffer, path) = editor_file(editor, cx)?;
+                let positions = vim
+                    .change_list
+                    .iter()
+                    .filter_map(|anchors| anchors.first())
+                    .map(|anchor| FilePosition::new(path.clone(), &buffer, anchor.text_anchor, cx))
+                    .collect::<Vec<_>>();
+                Some((path, positions))
+            })
+            .flatten()
+        else {
+            return;
+        };
+        Vim::globals(cx).change_lists.insert(path, positions);
+    }
+
+    /// Seeds the change list of a newly opened editor from the last time its
+    /// file was edited.
+    pub(crate) fn restore_change_list(&mut self, editor: &Editor, cx: &mut ViewContext<Self>) {
+        let Some((_, path)) = editor_file(editor, cx) else {
+            return;
+        };
+        let Some(positions) = Vim::globals(cx).change_lists.get(&path).cloned() else {
+            return;
+        };
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        self.change_list = positions
+            .into_iter()
+            .map(|position| {
+                let point = snapshot.clip_point(position.point(cx), Bias::Left);
+                vec![snapshot.anchor_before(point)]
+            })
+            .collect();
     }
 }
 
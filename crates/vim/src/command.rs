@@ -1,6 +1,8 @@
 use std::{
     iter::Peekable,
     ops::{Deref, Range},
+    path::{Path, PathBuf},
+    process::Output,
     str::Chars,
     sync::OnceLock,
 };
@@ -9,23 +11,30 @@ use anyhow::{anyhow, Result};
 use command_palette_hooks::CommandInterceptResult;
 use editor::{
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
-    Editor, ToPoint,
+    display_map::DisplaySnapshot,
+    Anchor, DisplayPoint, Editor, ToPoint,
 };
-use gpui::{actions, impl_actions, Action, AppContext, Global, ViewContext};
-use language::Point;
+use gpui::{actions, impl_actions, Action, AppContext, Global, Keystroke, Modifiers, ViewContext};
+use language::{Point, Selection};
 use multi_buffer::MultiBufferRow;
+use project::{ProjectPath, TaskSourceKind};
+use regex::Regex;
 use serde::Deserialize;
+use task::{TaskContext, TaskTemplate};
 use ui::WindowContext;
-use util::ResultExt;
-use workspace::{notifications::NotifyResultExt, SaveIntent};
+use util::{paths::home_dir, ResultExt};
+use workspace::{notifications::NotifyResultExt, tasks::schedule_task, SaveIntent};
 
 use crate::{
+    mappings::user_command,
     motion::{EndOfDocument, Motion, StartOfDocument},
     normal::{
+        repeat::Replayer,
         search::{FindCommand, ReplaceCommand, Replacement},
         JoinLines,
     },
-    state::Mode,
+    object::Object,
+    state::{Mode, ReplayableAction, VimGlobals},
     visual::VisualDeleteLine,
     Vim,
 };
@@ -53,11 +62,74 @@ pub struct WithCount {
     action: WrappedAction,
 }
 
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WriteCommand {
+    save_intent: Option<SaveIntent>,
+    filename: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SaveAsCommand {
+    filename: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EditCommand {
+    filename: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ReadCommand {
+    range: Option<CommandRange>,
+    filename: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NormalCommand {
+    range: Option<CommandRange>,
+    keys: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OnMatchingLines {
+    range: CommandRange,
+    search: String,
+    invert: bool,
+    action: Option<WrappedAction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ShellExec {
+    range: Option<CommandRange>,
+    command: String,
+    is_read: bool,
+}
+
 #[derive(Debug)]
 struct WrappedAction(Box<dyn Action>);
 
-actions!(vim, [VisualCommand, CountCommand]);
-impl_actions!(vim, [GoToLine, YankCommand, WithRange, WithCount]);
+struct ReadPosition {
+    anchor: Anchor,
+    above_first_line: bool,
+}
+
+actions!(vim, [VisualCommand, CountCommand, ShellCommand]);
+impl_actions!(
+    vim,
+    [
+        GoToLine,
+        YankCommand,
+        WithRange,
+        WithCount,
+        WriteCommand,
+        SaveAsCommand,
+        EditCommand,
+        ReadCommand,
+        NormalCommand,
+        OnMatchingLines,
+        ShellExec
+    ]
+);
 
 impl<'de> Deserialize<'de> for WrappedAction {
     fn deserialize<D>(_: D) -> Result<Self, D::Error>
@@ -204,6 +276,592 @@ pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
             });
         });
     });
+
+    Vim::action(editor, cx, Vim::write_command);
+    Vim::action(editor, cx, Vim::save_as_command);
+    Vim::action(editor, cx, Vim::edit_command);
+    Vim::action(editor, cx, Vim::read_command);
+    Vim::action(editor, cx, Vim::normal_command);
+    Vim::action(editor, cx, Vim::on_matching_lines);
+    Vim::action(editor, cx, Vim::shell_exec);
+
+    Vim::action(editor, cx, |vim, _: &ShellCommand, cx| {
+        let Some(workspace) = vim.workspace(cx) else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| {
+            command_palette::CommandPalette::toggle(workspace, "'<,'>!", cx);
+        })
+    });
+}
+
+impl Vim {
+    fn write_command(&mut self, action: &WriteCommand, cx: &mut ViewContext<Self>) {
+        let Some(path) = self.resolve_command_path(&action.filename, cx) else {
+            return;
+        };
+        let Some(fs) = self
+            .workspace(cx)
+            .map(|workspace| workspace.read(cx).project().read(cx).fs().clone())
+        else {
+            return;
+        };
+        let Some(buffer) = self
+            .editor()
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        else {
+            return;
+        };
+
+        let buffer = buffer.read(cx);
+        let current_path = buffer
+            .file()
+            .and_then(|file| file.as_local())
+            .map(|file| file.abs_path(cx));
+        if current_path.as_ref() == Some(&path) {
+            cx.dispatch_action(
+                workspace::Save {
+                    save_intent: action.save_intent,
+                }
+                .boxed_clone(),
+            );
+            return;
+        }
+
+        // Like vim, writing to another file leaves the buffer associated with its own file.
+        let text = buffer.as_rope().clone();
+        let line_ending = buffer.line_ending();
+        let overwrite = action.save_intent == Some(SaveIntent::Overwrite);
+        cx.spawn(|_, mut cx| async move {
+            if !overwrite && fs.is_file(&path).await {
+                Err(anyhow!(
+                    "{} already exists, add ! to overwrite it",
+                    path.display()
+                ))
+                .notify_async_err(&mut cx);
+                return;
+            }
+            fs.save(&path, &text, line_ending)
+                .await
+                .notify_async_err(&mut cx);
+        })
+        .detach();
+    }
+
+    fn save_as_command(&mut self, action: &SaveAsCommand, cx: &mut ViewContext<Self>) {
+        let Some(path) = self.resolve_command_path(&action.filename, cx) else {
+            return;
+        };
+        let Some(project) = self
+            .workspace(cx)
+            .map(|workspace| workspace.read(cx).project().clone())
+        else {
+            return;
+        };
+        let Some(buffer) = self
+            .editor()
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        else {
+            return;
+        };
+
+        let worktree = project.update(cx, |project, cx| {
+            project.find_or_create_worktree(&path, true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let result = async {
+                let (worktree, path) = worktree.await?;
+                let project_path = ProjectPath {
+                    worktree_id: worktree.update(&mut cx, |worktree, _| worktree.id())?,
+                    path: path.into(),
+                };
+                project
+                    .update(&mut cx, |project, cx| {
+                        project.save_buffer_as(buffer, project_path, cx)
+                    })?
+                    .await
+            }
+            .await;
+            result.notify_async_err(&mut cx);
+        })
+        .detach();
+    }
+
+    fn edit_command(&mut self, action: &EditCommand, cx: &mut ViewContext<Self>) {
+        let Some(path) = self.resolve_command_path(&action.filename, cx) else {
+            return;
+        };
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let open = workspace.update(cx, |workspace, cx| workspace.open_abs_path(path, false, cx));
+        cx.spawn(|_, mut cx| async move {
+            open.await.notify_async_err(&mut cx);
+        })
+        .detach();
+    }
+
+    fn read_command(&mut self, action: &ReadCommand, cx: &mut ViewContext<Self>) {
+        let Some(path) = self.resolve_command_path(&action.filename, cx) else {
+            return;
+        };
+        let Some(fs) = self
+            .workspace(cx)
+            .map(|workspace| workspace.read(cx).project().read(cx).fs().clone())
+        else {
+            return;
+        };
+        let Some(position) = self.read_position(&action.range, cx) else {
+            return;
+        };
+
+        cx.spawn(|vim, mut cx| async move {
+            let Some(contents) = fs.load(&path).await.notify_async_err(&mut cx) else {
+                return;
+            };
+            vim.update(&mut cx, |vim, cx| {
+                vim.insert_read_lines(&position, &contents, cx)
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Returns where `:read` inserts lines, which is below the last line of the range or
+    /// the cursor's line, or above the first line for `:0read`.
+    fn read_position(
+        &mut self,
+        range: &Option<CommandRange>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<ReadPosition> {
+        self.switch_mode(Mode::Normal, false, cx);
+        let above_first_line = range
+            .as_ref()
+            .is_some_and(|range| range.as_count() == Some(0));
+        let result = self.update_editor(cx, |vim, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let row = match range {
+                Some(range) => range.buffer_range(vim, editor, cx)?.end,
+                None => MultiBufferRow(editor.selections.newest::<Point>(cx).head().row),
+            };
+            let anchor = if above_first_line {
+                snapshot.anchor_before(Point::zero())
+            } else {
+                snapshot.anchor_after(Point::new(row.0, snapshot.line_len(row)))
+            };
+            anyhow::Ok(ReadPosition {
+                anchor,
+                above_first_line,
+            })
+        })?;
+        match result {
+            Ok(position) => Some(position),
+            Err(err) => {
+                self.notify_command_error(err, cx);
+                None
+            }
+        }
+    }
+
+    fn insert_read_lines(
+        &mut self,
+        position: &ReadPosition,
+        contents: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let contents = contents.strip_suffix('\n').unwrap_or(contents);
+        self.update_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let point = position.anchor.to_point(&snapshot);
+            let (text, row) = if position.above_first_line {
+                (format!("{contents}\n"), 0)
+            } else {
+                (format!("\n{contents}"), point.row + 1)
+            };
+            editor.transact(cx, |editor, cx| {
+                editor.edit([(point..point, text)], cx);
+                editor.change_selections(None, cx, |s| {
+                    s.select_ranges([Point::new(row, 0)..Point::new(row, 0)])
+                });
+            });
+        });
+    }
+
+    fn normal_command(&mut self, action: &NormalCommand, cx: &mut ViewContext<Self>) {
+        let keystrokes = parse_keystrokes(&action.keys)
+            .into_iter()
+            .map(ReplayableAction::Keystroke)
+            .collect::<Vec<_>>();
+        let rows = match &action.range {
+            None => None,
+            Some(range) => {
+                let result = self.update_editor(cx, |vim, editor, cx| {
+                    let range = range.buffer_range(vim, editor, cx)?;
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    anyhow::Ok(
+                        (range.start.0..=range.end.0)
+                            .map(|row| snapshot.anchor_before(Point::new(row, 0)))
+                            .collect::<Vec<_>>(),
+                    )
+                });
+                match result {
+                    None => return,
+                    Some(Err(err)) => {
+                        self.notify_command_error(err, cx);
+                        return;
+                    }
+                    Some(Ok(rows)) => Some(rows),
+                }
+            }
+        };
+        self.switch_mode(Mode::Normal, false, cx);
+
+        // Any command left incomplete by the keys, such as an insertion, is ended with
+        // an escape as it is in vim.
+        let escape = ReplayableAction::Keystroke(Keystroke::parse("escape").unwrap());
+        let mut actions = Vec::new();
+        if let Some(rows) = rows {
+            for row in rows {
+                actions.push(ReplayableAction::StartOfLine(row));
+                actions.extend(keystrokes.iter().cloned());
+                actions.push(escape.clone());
+            }
+        } else {
+            actions.extend(keystrokes);
+            actions.push(escape);
+        }
+        Self::replay(actions, cx);
+    }
+
+    fn on_matching_lines(&mut self, action: &OnMatchingLines, cx: &mut ViewContext<Self>) {
+        let regex = match Regex::new(&action.search) {
+            Ok(regex) => regex,
+            Err(err) => {
+                self.notify_command_error(err.into(), cx);
+                return;
+            }
+        };
+        let result = self.update_editor(cx, |vim, editor, cx| {
+            let range = action.range.buffer_range(vim, editor, cx)?;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let rows = (range.start.0..=range.end.0)
+                .filter(|row| {
+                    let row = MultiBufferRow(*row);
+                    let line = snapshot
+                        .text_for_range(
+                            Point::new(row.0, 0)..Point::new(row.0, snapshot.line_len(row)),
+                        )
+                        .collect::<String>();
+                    regex.is_match(&line) != action.invert
+                })
+                .collect::<Vec<_>>();
+            anyhow::Ok(rows)
+        });
+        let rows = match result {
+            None => return,
+            Some(Err(err)) => {
+                self.notify_command_error(err, cx);
+                return;
+            }
+            Some(Ok(rows)) if rows.is_empty() => {
+                self.notify_command_error(anyhow!("Pattern not found: {}", action.search), cx);
+                return;
+            }
+            Some(Ok(rows)) => rows,
+        };
+        self.switch_mode(Mode::Normal, false, cx);
+
+        let Some(sub_action) = action.action.as_ref() else {
+            // Without a command, leave a cursor on each of the lines.
+            self.update_editor(cx, |_, editor, cx| {
+                editor.change_selections(None, cx, |s| {
+                    s.select_ranges(
+                        rows.iter()
+                            .map(|row| Point::new(*row, 0)..Point::new(*row, 0)),
+                    )
+                });
+            });
+            return;
+        };
+
+        // Substitutions are made directly rather than through the buffer search, which
+        // can only search one set of ranges at a time.
+        if let Some(replace) = sub_action.as_any().downcast_ref::<ReplaceCommand>() {
+            let regex = match replace.replacement.regex(&action.search) {
+                Ok(regex) => regex,
+                Err(err) => {
+                    self.notify_command_error(err, cx);
+                    return;
+                }
+            };
+            self.update_editor(cx, |_, editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let edits = rows
+                    .iter()
+                    .filter_map(|row| {
+                        let row = MultiBufferRow(*row);
+                        let range = Point::new(row.0, 0)..Point::new(row.0, snapshot.line_len(row));
+                        let line = snapshot.text_for_range(range.clone()).collect::<String>();
+                        let replaced = replace.replacement.replace_line(&regex, &line)?;
+                        Some((range, replaced))
+                    })
+                    .collect::<Vec<_>>();
+                let Some(last_row) = edits.last().map(|(range, _)| range.start.row) else {
+                    return;
+                };
+                editor.transact(cx, |editor, cx| {
+                    editor.edit(edits, cx);
+                    editor.change_selections(None, cx, |s| {
+                        s.select_ranges([Point::new(last_row, 0)..Point::new(last_row, 0)])
+                    });
+                });
+            });
+            return;
+        }
+
+        let anchors = self
+            .update_editor(cx, |_, editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                rows.iter()
+                    .map(|row| snapshot.anchor_before(Point::new(*row, 0)))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let mut actions = Vec::new();
+        for anchor in anchors {
+            actions.push(ReplayableAction::StartOfLine(anchor));
+            actions.push(ReplayableAction::Action(sub_action.boxed_clone()));
+        }
+        Self::replay(actions, cx);
+    }
+
+    fn shell_exec(&mut self, action: &ShellExec, cx: &mut ViewContext<Self>) {
+        let command = match self.expand_shell_command(&action.command, cx) {
+            Ok(command) => command,
+            Err(err) => {
+                self.notify_command_error(err, cx);
+                return;
+            }
+        };
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let project = workspace.read(cx).project().clone();
+
+        if action.is_read {
+            let Some(position) = self.read_position(&action.range, cx) else {
+                return;
+            };
+            let output = project.update(cx, |project, cx| {
+                project.run_shell_command(command.clone(), None, cx)
+            });
+            cx.spawn(|vim, mut cx| async move {
+                let Some(output) = command_output(&command, output.await).notify_async_err(&mut cx)
+                else {
+                    return;
+                };
+                vim.update(&mut cx, |vim, cx| {
+                    vim.insert_read_lines(&position, &output, cx)
+                })
+                .ok();
+            })
+            .detach();
+            return;
+        }
+
+        let Some(range) = action.range.as_ref() else {
+            // Vim shows the output of `:!cmd` below the command line, here it's shown in a
+            // terminal.
+            let cwd = project.read(cx).first_project_directory(cx);
+            workspace.update(cx, |workspace, cx| {
+                let task = TaskTemplate {
+                    label: command.clone(),
+                    command,
+                    ..Default::default()
+                };
+                let task_cx = TaskContext {
+                    cwd,
+                    ..Default::default()
+                };
+                schedule_task(
+                    workspace,
+                    TaskSourceKind::UserInput,
+                    &task,
+                    &task_cx,
+                    true,
+                    cx,
+                );
+            });
+            return;
+        };
+
+        let result = self.update_editor(cx, |vim, editor, cx| {
+            let range = range.buffer_range(vim, editor, cx)?;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let range =
+                Point::new(range.start.0, 0)..Point::new(range.end.0, snapshot.line_len(range.end));
+            let input = snapshot.text_for_range(range.clone()).collect::<String>() + "\n";
+            let range = snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end);
+            anyhow::Ok((range, input))
+        });
+        let (range, input) = match result {
+            None => return,
+            Some(Err(err)) => {
+                self.notify_command_error(err, cx);
+                return;
+            }
+            Some(Ok(result)) => result,
+        };
+        self.switch_mode(Mode::Normal, false, cx);
+
+        let output = project.update(cx, |project, cx| {
+            project.run_shell_command(command.clone(), Some(input), cx)
+        });
+        cx.spawn(|vim, mut cx| async move {
+            let Some(output) = command_output(&command, output.await).notify_async_err(&mut cx)
+            else {
+                return;
+            };
+            let output = output.strip_suffix('\n').unwrap_or(&output);
+            vim.update(&mut cx, |vim, cx| {
+                vim.update_editor(cx, |_, editor, cx| {
+                    editor.transact(cx, |editor, cx| {
+                        editor.edit([(range.clone(), output)], cx);
+                        editor.change_selections(None, cx, |s| {
+                            s.select_anchor_ranges([range.start..range.start])
+                        });
+                    });
+                });
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Expands `%` in a shell command to the path of the current file, as vim does. An
+    /// escaped `\%` is a literal `%`.
+    fn expand_shell_command(&self, command: &str, cx: &AppContext) -> Result<String> {
+        let path = self
+            .editor()
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+            .and_then(|buffer| {
+                let file = buffer.read(cx).file()?.as_local()?;
+                Some(file.abs_path(cx))
+            });
+
+        let mut expanded = String::with_capacity(command.len());
+        let mut chars = command.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if chars.peek() == Some(&'%') => {
+                    chars.next();
+                    expanded.push('%');
+                }
+                '%' => {
+                    let path = path
+                        .as_ref()
+                        .ok_or_else(|| anyhow!("no file name to substitute for '%'"))?;
+                    expanded.push_str(&shlex::try_quote(&path.to_string_lossy())?);
+                }
+                c => expanded.push(c),
+            }
+        }
+        Ok(expanded)
+    }
+
+    pub(crate) fn shell_command_motion(
+        &mut self,
+        motion: Motion,
+        times: Option<usize>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.stop_recording(cx);
+        let rows = self.update_editor(cx, |_, editor, cx| {
+            let text_layout_details = editor.text_layout_details(cx);
+            let map = editor.snapshot(cx).display_snapshot;
+            let mut selection = editor.selections.newest_display(cx);
+            motion.expand_selection(&map, &mut selection, times, false, &text_layout_details);
+            selection_rows(&map, &selection)
+        });
+        if let Some(rows) = rows {
+            self.open_shell_command_palette(rows, cx);
+        }
+    }
+
+    pub(crate) fn shell_command_object(
+        &mut self,
+        object: Object,
+        around: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.stop_recording(cx);
+        let rows = self.update_editor(cx, |_, editor, cx| {
+            let map = editor.snapshot(cx).display_snapshot;
+            let mut selection = editor.selections.newest_display(cx);
+            object.expand_selection(&map, &mut selection, around);
+            selection_rows(&map, &selection)
+        });
+        if let Some(rows) = rows {
+            self.open_shell_command_palette(rows, cx);
+        }
+    }
+
+    /// Opens the command palette with the lines that `!{motion}` covers, for the user to
+    /// type the command to filter them through.
+    fn open_shell_command_palette(&mut self, rows: Range<u32>, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let range = if rows.start == rows.end {
+            format!("{}!", rows.start + 1)
+        } else {
+            format!("{},{}!", rows.start + 1, rows.end + 1)
+        };
+        workspace.update(cx, |workspace, cx| {
+            command_palette::CommandPalette::toggle(workspace, &range, cx);
+        })
+    }
+
+    fn replay(actions: Vec<ReplayableAction>, cx: &mut ViewContext<Self>) {
+        let mut replayer = Vim::globals(cx)
+            .replayer
+            .get_or_insert_with(Replayer::new)
+            .clone();
+        replayer.replay(actions, cx);
+    }
+
+    /// Resolves a filename given to an ex command. Relative paths are resolved against
+    /// the first visible worktree, which plays the part of vim's working directory.
+    fn resolve_command_path(&self, filename: &str, cx: &mut ViewContext<Self>) -> Option<PathBuf> {
+        if let Some(path) = filename.strip_prefix("~/") {
+            return Some(home_dir().join(path));
+        }
+        let path = Path::new(filename);
+        if path.is_absolute() {
+            return Some(path.to_path_buf());
+        }
+        let workspace = self.workspace(cx)?;
+        let worktree = workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .visible_worktrees(cx)
+            .next();
+        match worktree {
+            Some(worktree) => Some(worktree.read(cx).abs_path().join(path)),
+            None => {
+                self.notify_command_error(anyhow!("no folder is open to resolve {filename}"), cx);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn notify_command_error(&self, err: anyhow::Error, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        workspace.update(cx, |workspace, cx| workspace.show_error(&err, cx));
+    }
 }
 
 #[derive(Default)]
@@ -221,6 +879,10 @@ struct VimCommand {
                 + 'static,
         >,
     >,
+    args: Option<
+        Box<dyn Fn(Box<dyn Action>, String) -> Option<Box<dyn Action>> + Send + Sync + 'static>,
+    >,
+    complete_filenames: bool,
     has_count: bool,
 }
 
@@ -262,12 +924,42 @@ impl VimCommand {
         self
     }
 
+    /// Accepts arguments after the command, which `f` turns into the action to run.
+    fn args(
+        mut self,
+        f: impl Fn(Box<dyn Action>, String) -> Option<Box<dyn Action>> + Send + Sync + 'static,
+    ) -> Self {
+        self.args = Some(Box::new(f));
+        self
+    }
+
+    /// Accepts a filename argument, which is completed from the project's files.
+    fn filename(
+        mut self,
+        f: impl Fn(Box<dyn Action>, String) -> Option<Box<dyn Action>> + Send + Sync + 'static,
+    ) -> Self {
+        self.complete_filenames = true;
+        self.args(f)
+    }
+
+    /// Splits the query into the command and its arguments, if the command takes any.
+    fn split_args<'a>(&self, query: &'a str) -> (&'a str, &'a str) {
+        if self.args.is_none() {
+            return (query, "");
+        }
+        match query.split_once(char::is_whitespace) {
+            Some((command, args)) => (command, args.trim_start()),
+            None => (query, ""),
+        }
+    }
+
     fn parse(
         &self,
-        mut query: &str,
+        query: &str,
         range: &Option<CommandRange>,
         cx: &AppContext,
     ) -> Option<Box<dyn Action>> {
+        let (mut query, args) = self.split_args(query);
         let has_bang = query.ends_with('!');
         if has_bang {
             query = &query[..query.len() - 1];
@@ -288,6 +980,12 @@ impl VimCommand {
             return None;
         };
 
+        let action = if args.is_empty() {
+            action
+        } else {
+            self.args.as_ref()?(action, args.to_string())?
+        };
+
         if let Some(range) = range {
             self.range.as_ref().and_then(|f| f(action, range))
         } else {
@@ -507,7 +1205,21 @@ fn generate_commands(_: &AppContext) -> Vec<VimCommand> {
         )
         .bang(workspace::Save {
             save_intent: Some(SaveIntent::Overwrite),
+        })
+        .filename(|action, filename| {
+            Some(
+                WriteCommand {
+                    save_intent: action
+                        .as_any()
+                        .downcast_ref::<workspace::Save>()?
+                        .save_intent,
+                    filename,
+                }
+                .boxed_clone(),
+            )
         }),
+        VimCommand::new(("sav", "eas"), workspace::SaveAs)
+            .filename(|_, filename| Some(SaveAsCommand { filename }.boxed_clone())),
         VimCommand::new(
             ("q", "uit"),
             workspace::CloseActiveItem {
@@ -691,7 +1403,59 @@ fn generate_commands(_: &AppContext) -> Vec<VimCommand> {
         VimCommand::new(("%", ""), EndOfDocument),
         VimCommand::new(("0", ""), StartOfDocument),
         VimCommand::new(("e", "dit"), editor::actions::ReloadFile)
-            .bang(editor::actions::ReloadFile),
+            .bang(editor::actions::ReloadFile)
+            .filename(|_, filename| Some(EditCommand { filename }.boxed_clone())),
+        VimCommand::new(("r", "ead"), gpui::NoAction)
+            .filename(|_, filename| {
+                if let Some(command) = filename.strip_prefix('!') {
+                    return Some(
+                        ShellExec {
+                            range: None,
+                            command: command.trim().to_string(),
+                            is_read: true,
+                        }
+                        .boxed_clone(),
+                    );
+                }
+                Some(
+                    ReadCommand {
+                        range: None,
+                        filename,
+                    }
+                    .boxed_clone(),
+                )
+            })
+            .range(|action, range| {
+                if let Some(action) = action.as_any().downcast_ref::<ShellExec>() {
+                    return Some(
+                        ShellExec {
+                            range: Some(range.clone()),
+                            ..action.clone()
+                        }
+                        .boxed_clone(),
+                    );
+                }
+                let action = action.as_any().downcast_ref::<ReadCommand>()?;
+                Some(
+                    ReadCommand {
+                        range: Some(range.clone()),
+                        filename: action.filename.clone(),
+                    }
+                    .boxed_clone(),
+                )
+            }),
+        VimCommand::new(("norm", "al"), gpui::NoAction)
+            .args(|_, keys| Some(NormalCommand { range: None, keys }.boxed_clone()))
+            .range(|action, range| {
+                let action = action.as_any().downcast_ref::<NormalCommand>()?;
+                Some(
+                    NormalCommand {
+                        range: Some(range.clone()),
+                        keys: action.keys.clone(),
+                    }
+                    .boxed_clone(),
+                )
+            }),
         VimCommand::new(("cpp", "link"), editor::actions::CopyPermalinkToLine).range(act_on_range),
     ]
 }
@@ -709,6 +1473,32 @@ fn commands(cx: &AppContext) -> &Vec<VimCommand> {
         .0
 }
 
+/// Returns the buffer rows a selection made by a motion or object covers.
+fn selection_rows(map: &DisplaySnapshot, selection: &Selection<DisplayPoint>) -> Range<u32> {
+    let start = selection.start.to_point(map);
+    let mut end = selection.end.to_point(map);
+    // Linewise motions select up to the start of the following line.
+    if end.column == 0 && end.row > start.row {
+        end.row -= 1;
+    }
+    start.row..end.row
+}
+
+/// Returns the standard output of a shell command, or an error with its standard error
+/// if it failed.
+fn command_output(command: &str, output: Result<Output>) -> Result<String> {
+    let output = output?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!(
+            "{command:?} failed with {}: {}",
+            output.status,
+            stderr.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 fn act_on_range(action: Box<dyn Action>, range: &CommandRange) -> Option<Box<dyn Action>> {
     Some(
         WithRange {
@@ -741,15 +1531,14 @@ fn wrap_count(action: Box<dyn Action>, range: &CommandRange) -> Option<Box<dyn A
     })
 }
 
-pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
-    // NOTE: We also need to support passing arguments to commands like :w
-    // (ideally with filename autocompletion).
+pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Vec<CommandInterceptResult> {
     while input.starts_with(':') {
         input = &input[1..];
     }
 
     let (range, query) = VimCommand::parse_range(input);
     let range_prefix = input[0..(input.len() - query.len())].to_string();
+    let completing_args = query.ends_with(char::is_whitespace);
     let query = query.as_str().trim();
 
     let action = if range.is_some() && query.is_empty() {
@@ -759,6 +1548,16 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
             }
             .boxed_clone(),
         )
+    } else if let Some(command) = query.strip_prefix('!') {
+        let command = command.trim();
+        (!command.is_empty()).then(|| {
+            ShellExec {
+                range: range.clone(),
+                command: command.to_string(),
+                is_read: false,
+            }
+            .boxed_clone()
+        })
     } else if query.starts_with('/') || query.starts_with('?') {
         Some(
             FindCommand {
@@ -767,6 +1566,27 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
             }
             .boxed_clone(),
         )
+    } else if let Some((invert, search, command)) = parse_global(query) {
+        let action = if command.is_empty() {
+            Some(None)
+        } else {
+            command_interceptor(command, cx)
+                .into_iter()
+                .next()
+                .map(|result| Some(WrappedAction(result.action)))
+        };
+        action.map(|action| {
+            OnMatchingLines {
+                range: range.clone().unwrap_or(CommandRange {
+                    start: Position::Line { row: 1, offset: 0 },
+                    end: Some(Position::LastLine { offset: 0 }),
+                }),
+                search,
+                invert,
+                action,
+            }
+            .boxed_clone()
+        })
     } else if query.starts_with('s') {
         let mut substitute = "substitute".chars().peekable();
         let mut query = query.chars().peekable();
@@ -787,34 +1607,242 @@ pub fn command_interceptor(mut input: &str, cx: &AppContext) -> Option<CommandIn
             None
         }
     } else {
-        None
+        user_command(query, cx)
     };
     if let Some(action) = action {
         let string = input.to_string();
         let positions = generate_positions(&string, &(range_prefix + query));
-        return Some(CommandInterceptResult {
+        return vec![CommandInterceptResult {
             action,
             string,
             positions,
-        });
+        }];
     }
 
     for command in commands(cx).iter() {
-        if let Some(action) = command.parse(query, &range, cx) {
-            let mut string = ":".to_owned() + &range_prefix + command.prefix + command.suffix;
-            if query.ends_with('!') {
-                string.push('!');
+        let Some(action) = command.parse(query, &range, cx) else {
+            continue;
+        };
+        let (name, args) = command.split_args(query);
+        let mut string = ":".to_owned() + &range_prefix + command.prefix + command.suffix;
+        if name.ends_with('!') {
+            string.push('!');
+        }
+        let typed = if args.is_empty() {
+            string.clone()
+        } else {
+            format!("{string} {args}")
+        };
+        let mut results = vec![CommandInterceptResult {
+            action,
+            positions: generate_positions(&typed, &(range_prefix.clone() + query)),
+            string: typed,
+        }];
+
+        // The typed command comes first so that enter runs it, followed by completions
+        // of its filename argument that tab accepts.
+        if command.complete_filenames && (completing_args || !args.is_empty()) {
+            for filename in filename_completions(args, cx) {
+                if filename == args {
+                    continue;
+                }
+                let query = format!("{name} {filename}");
+                let Some(action) = command.parse(&query, &range, cx) else {
+                    continue;
+                };
+                let string = format!("{string} {filename}");
+                let positions = generate_positions(&string, &(range_prefix.clone() + name));
+                results.push(CommandInterceptResult {
+                    action,
+                    string,
+                    positions,
+                });
             }
-            let positions = generate_positions(&string, &(range_prefix + query));
+        }
+        return results;
+    }
+    Vec::new()
+}
 
-            return Some(CommandInterceptResult {
-                action,
-                string,
-                positions,
-            });
+/// Parses the `g[lobal][!]/{pattern}/[cmd]` and `v[global]/{pattern}/[cmd]` commands,
+/// returning whether non-matching lines are selected, the pattern and the command.
+/// The pattern's parens are converted in the same way as `:s`.
+fn parse_global(query: &str) -> Option<(bool, String, &str)> {
+    let name_len = query
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(query.len());
+    let (name, rest) = query.split_at(name_len);
+    let mut invert = if name.starts_with('g') && "global".starts_with(name) {
+        false
+    } else if name.starts_with('v') && "vglobal".starts_with(name) {
+        true
+    } else {
+        return None;
+    };
+    let rest = match rest.strip_prefix('!') {
+        Some(rest) if !invert => {
+            invert = true;
+            rest
+        }
+        _ => rest,
+    };
+
+    let mut chars = rest.chars();
+    let delimiter = chars
+        .next()
+        .filter(|c| !c.is_alphanumeric() && !c.is_whitespace() && *c != '"' && *c != '|')?;
+    let rest = chars.as_str();
+    let mut search = String::new();
+    let mut command = "";
+    let mut escaped = false;
+    for (ix, c) in rest.char_indices() {
+        if escaped {
+            escaped = false;
+            if c != '(' && c != ')' && c != delimiter {
+                search.push('\\');
+            }
+            search.push(c);
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            command = &rest[ix + c.len_utf8()..];
+            break;
+        } else {
+            if c == '(' || c == ')' {
+                search.push('\\');
+            }
+            search.push(c);
+        }
+    }
+    if search.is_empty() {
+        return None;
+    }
+    Some((invert, search, command.trim()))
+}
+
+const MAX_FILENAME_COMPLETIONS: usize = 20;
+
+/// Completes a filename relative to the root of the focused workspace's first visible
+/// worktree.
+fn filename_completions(partial: &str, cx: &AppContext) -> Vec<String> {
+    if partial.starts_with('/') || partial.starts_with('~') {
+        return Vec::new();
+    }
+    let Some(worktree) = cx
+        .try_global::<VimGlobals>()
+        .and_then(|globals| globals.focused_vim())
+        .and_then(|vim| vim.read(cx).editor())
+        .and_then(|editor| editor.read(cx).workspace())
+        .and_then(|workspace| {
+            workspace
+                .read(cx)
+                .project()
+                .read(cx)
+                .visible_worktrees(cx)
+                .next()
+        })
+    else {
+        return Vec::new();
+    };
+
+    let (dir, prefix) = match partial.rfind('/') {
+        Some(ix) => partial.split_at(ix + 1),
+        None => ("", partial),
+    };
+    let mut completions = worktree
+        .read(cx)
+        .child_entries(Path::new(dir.trim_end_matches('/')))
+        .filter_map(|entry| {
+            let name = entry.path.file_name()?.to_str()?;
+            if !name.starts_with(prefix) {
+                return None;
+            }
+            let mut completion = format!("{dir}{name}");
+            if entry.is_dir() {
+                completion.push('/');
+            }
+            Some(completion)
+        })
+        .collect::<Vec<_>>();
+    completions.sort();
+    completions.truncate(MAX_FILENAME_COMPLETIONS);
+    completions
+}
+
+/// Parses the keys given to `:normal`, which can use vim's notation for special keys
+/// such as `<esc>`, `<cr>` and `<c-w>`.
+pub(crate) fn parse_keystrokes(keys: &str) -> Vec<Keystroke> {
+    let mut keystrokes = Vec::new();
+    let mut rest = keys;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(keystroke) = rest[1..]
+                .split_once('>')
+                .and_then(|(notation, _)| parse_key_notation(notation))
+            {
+                rest = &rest[rest.find('>').unwrap() + 1..];
+                keystrokes.push(keystroke);
+                continue;
+            }
         }
+        let (key, shift) = match c {
+            ' ' => ("space".to_string(), false),
+            c if c.is_ascii_uppercase() => (c.to_ascii_lowercase().to_string(), true),
+            c => (c.to_string(), false),
+        };
+        keystrokes.push(Keystroke {
+            modifiers: Modifiers {
+                shift,
+                ..Default::default()
+            },
+            key,
+            key_char: None,
+        });
+        rest = &rest[c.len_utf8()..];
     }
-    None
+    keystrokes
+}
+
+fn parse_key_notation(notation: &str) -> Option<Keystroke> {
+    let notation = notation.to_ascii_lowercase();
+    let mut modifiers = Modifiers::default();
+    let mut key = notation.as_str();
+    loop {
+        if let Some(rest) = key.strip_prefix("c-") {
+            modifiers.control = true;
+            key = rest;
+        } else if let Some(rest) = key.strip_prefix("s-") {
+            modifiers.shift = true;
+            key = rest;
+        } else if let Some(rest) = key.strip_prefix("a-").or_else(|| key.strip_prefix("m-")) {
+            modifiers.alt = true;
+            key = rest;
+        } else if let Some(rest) = key.strip_prefix("d-") {
+            modifiers.platform = true;
+            key = rest;
+        } else {
+            break;
+        }
+    }
+    let key = match key {
+        "esc" => "escape",
+        "cr" | "enter" | "return" => "enter",
+        "bs" => "backspace",
+        "del" => "delete",
+        "lt" => "<",
+        "bar" => "|",
+        "bslash" => "\\",
+        "tab" | "space" | "up" | "down" | "left" | "right" | "home" | "end" | "pageup"
+        | "pagedown" | "insert" => key,
+        _ if key.len() > 1 && key.starts_with('f') && key[1..].parse::<u8>().is_ok() => key,
+        _ if key.chars().count() == 1 => key,
+        _ => return None,
+    };
+    Some(Keystroke {
+        modifiers,
+        key: key.to_string(),
+        key_char: None,
+    })
 }
 
 fn generate_positions(string: &str, query: &str) -> Vec<usize> {
@@ -843,10 +1871,12 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
 mod test {
     use std::path::Path;
 
+    use super::{command_interceptor, parse_keystrokes, CommandRange, Position, ShellExec};
     use crate::{
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
     };
+    use command_palette::CommandPalette;
     use editor::Editor;
     use gpui::TestAppContext;
     use indoc::indoc;
@@ -1040,6 +2070,174 @@ mod test {
         cx.shared_state().await.assert_eq("k\nk\nˇk\n4\n4\n3\n2\n1");
     }
 
+    #[gpui::test]
+    async fn test_command_global(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\na\nc", Mode::Normal);
+        cx.simulate_keystrokes(": v / a / s / $ / ! enter");
+        cx.assert_state("a\nb!\na\nˇc!", Mode::Normal);
+
+        cx.simulate_keystrokes(": g / a / d enter");
+        cx.run_until_parked();
+        cx.assert_state("b!\nˇc!", Mode::Normal);
+
+        cx.set_state("ˇone\ntwo\nthree", Mode::Normal);
+        cx.simulate_keystrokes(": g / t / n o r m a l space shift-a ; enter");
+        cx.run_until_parked();
+        cx.assert_state("one\ntwo;\nthreeˇ;", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_normal(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes(": % n o r m a l space shift-a ; enter");
+        cx.run_until_parked();
+        cx.assert_state("a;\nb;\ncˇ;", Mode::Normal);
+
+        cx.simulate_keystrokes(": n o r m space 0 r < l t > enter");
+        cx.run_until_parked();
+        cx.assert_state("a;\nb;\nˇ<;", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_command_file_arguments(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let fs = cx.workspace(|workspace, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file("/root/dir/other.rs", b"one\ntwo\n".to_vec())
+            .await;
+
+        cx.set_state("ˇa\nb", Mode::Normal);
+        cx.simulate_keystrokes(": r space d i r / o t h e r . r s enter");
+        cx.run_until_parked();
+        cx.assert_state("a\nˇone\ntwo\nb", Mode::Normal);
+
+        // Writing to an existing file needs a bang.
+        cx.simulate_keystrokes(": w space d i r / o t h e r . r s enter");
+        cx.run_until_parked();
+        assert_eq!(
+            fs.load(Path::new("/root/dir/other.rs")).await.unwrap(),
+            "one\ntwo\n"
+        );
+        cx.simulate_keystrokes(": w ! space d i r / o t h e r . r s enter");
+        cx.run_until_parked();
+        assert_eq!(
+            fs.load(Path::new("/root/dir/other.rs")).await.unwrap(),
+            "a\none\ntwo\nb"
+        );
+
+        cx.simulate_keystrokes(": e space d i r / o t h e r . r s enter");
+        cx.run_until_parked();
+        cx.workspace(|workspace, cx| {
+            assert_active_item(workspace, "/root/dir/other.rs", "a\none\ntwo\nb", cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_command_filename_completions(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        let completions = |query: &str, cx: &mut VimTestContext| {
+            cx.update(|cx| {
+                command_interceptor(query, cx)
+                    .into_iter()
+                    .map(|result| result.string)
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(completions("e d", &mut cx), [":edit d", ":edit dir/"]);
+        assert_eq!(
+            completions("w! dir/", &mut cx),
+            [":write! dir/", ":write! dir/file.rs"]
+        );
+        assert_eq!(completions("w", &mut cx), [":write"]);
+    }
+
+    #[gpui::test]
+    async fn test_command_shell(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        let action = |query: &str, cx: &mut VimTestContext| {
+            cx.update(|cx| {
+                command_interceptor(query, cx)
+                    .into_iter()
+                    .next()
+                    .and_then(|result| result.action.as_any().downcast_ref::<ShellExec>().cloned())
+            })
+        };
+        assert_eq!(
+            action("%!sort -u", &mut cx),
+            Some(ShellExec {
+                range: Some(CommandRange {
+                    start: Position::Line { row: 1, offset: 0 },
+                    end: Some(Position::LastLine { offset: 0 }),
+                }),
+                command: "sort -u".to_string(),
+                is_read: false,
+            })
+        );
+        assert_eq!(
+            action("r !date", &mut cx),
+            Some(ShellExec {
+                range: None,
+                command: "date".to_string(),
+                is_read: true,
+            })
+        );
+        assert_eq!(action("!", &mut cx), None);
+
+        cx.set_state("ˇa\nb\nc", Mode::Normal);
+        cx.simulate_keystrokes("! j");
+        assert!(
+            cx.workspace(|workspace, cx| workspace.active_modal::<CommandPalette>(cx).is_some())
+        );
+        cx.simulate_keystrokes("escape");
+        cx.run_until_parked();
+        cx.assert_state("ˇa\nb\nc", Mode::Normal);
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[gpui::test]
+    async fn test_command_shell_filter_with_unread_input(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.executor().allow_parking();
+
+        // `head` exits before reading all of its input, as there is more than fits in a
+        // pipe's buffer.
+        let lines = (0..20_000)
+            .map(|ix| format!("line {ix}"))
+            .collect::<Vec<_>>();
+        cx.set_state(&format!("ˇ{}", lines.join("\n")), Mode::Normal);
+        cx.simulate_keystrokes(": % ! h e a d space - 2 enter");
+
+        // The command runs as a real process.
+        for _ in 0..500 {
+            cx.run_until_parked();
+            if cx.buffer_text() == "line 0\nline 1" {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        cx.assert_state("ˇline 0\nline 1", Mode::Normal);
+    }
+
+    #[test]
+    fn test_parse_keystrokes() {
+        let keys = |keys: &str| {
+            parse_keystrokes(keys)
+                .into_iter()
+                .map(|keystroke| keystroke.unparse())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(keys("dw"), ["d", "w"]);
+        assert_eq!(keys("A x"), ["shift-a", "space", "x"]);
+        assert_eq!(keys("<Esc><c-w>l<lt>"), ["escape", "ctrl-w", "l", "<"]);
+        assert_eq!(keys("<nope>"), ["<", "n", "o", "p", "e", ">"]);
+    }
+
     fn assert_active_item(
         workspace: &mut Workspace,
         expected_path: &str,
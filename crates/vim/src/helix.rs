@@ -1,18 +1,84 @@
-use editor::{movement, scroll::Autoscroll, DisplayPoint, Editor};
-use gpui::{actions, Action};
-use language::{CharClassifier, CharKind};
+mod edit;
+mod select;
+
+use editor::{
+    display_map::DisplaySnapshot, movement, scroll::Autoscroll, DisplayPoint, Editor, SelectionGoal,
+};
+use gpui::{actions, Action, AppContext};
+use language::{CharClassifier, CharKind, Selection};
+use settings::Settings;
 use ui::ViewContext;
 
-use crate::{motion::Motion, state::Mode, Vim};
+use crate::{
+    motion::Motion,
+    object::Object,
+    state::{Mode, Operator},
+    Vim, VimSettings,
+};
+
+pub(crate) use select::RegexSelection;
 
 actions!(vim, [HelixNormalAfter, HelixDelete]);
 
 pub fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
     Vim::action(editor, cx, Vim::helix_normal_after);
     Vim::action(editor, cx, Vim::helix_delete);
+    edit::register(editor, cx);
+    select::register(editor, cx);
+}
+
+/// Returns the positions of the characters at the anchor and the cursor of a helix selection.
+/// An empty selection is a cursor on the character after it.
+pub(crate) fn helix_anchor_and_cursor(
+    map: &DisplaySnapshot,
+    selection: &Selection<DisplayPoint>,
+) -> (DisplayPoint, DisplayPoint) {
+    if selection.is_empty() {
+        (selection.start, selection.start)
+    } else if selection.reversed {
+        (movement::left(map, selection.end), selection.start)
+    } else {
+        (selection.start, movement::left(map, selection.end))
+    }
+}
+
+/// Selects the characters from `anchor` to `cursor`, both included.
+pub(crate) fn set_helix_selection(
+    map: &DisplaySnapshot,
+    selection: &mut Selection<DisplayPoint>,
+    anchor: DisplayPoint,
+    cursor: DisplayPoint,
+    goal: SelectionGoal,
+) {
+    if cursor < anchor {
+        selection.start = cursor;
+        selection.end = movement::right(map, anchor);
+        selection.reversed = true;
+    } else {
+        selection.start = anchor;
+        selection.end = movement::right(map, cursor);
+        selection.reversed = false;
+    }
+    selection.goal = goal;
+}
+
+/// Turns cursors into selections of the character under them, so that they can be
+/// edited like any other helix selection.
+pub(crate) fn helix_expand_cursors(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    editor.change_selections(None, cx, |s| {
+        s.move_with(|map, selection| {
+            if selection.is_empty() && !selection.reversed {
+                selection.end = movement::right(map, selection.end);
+            }
+        });
+    });
 }
 
 impl Vim {
+    pub(crate) fn helix_enabled(cx: &AppContext) -> bool {
+        VimSettings::get_global(cx).helix_mode
+    }
+
     pub fn helix_normal_after(&mut self, action: &HelixNormalAfter, cx: &mut ViewContext<Self>) {
         if self.active_operator().is_some() {
             self.operator_stack.clear();
@@ -30,7 +96,29 @@ impl Vim {
         times: Option<usize>,
         cx: &mut ViewContext<Self>,
     ) {
-        self.helix_move_cursor(motion, times, cx);
+        if let Motion::ZedSearchResult {
+            prior_selections,
+            new_selections,
+        } = motion
+        {
+            let extend = self.mode == Mode::HelixSelect;
+            self.update_editor(cx, |_, editor, cx| {
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    if extend {
+                        s.select_anchor_ranges(prior_selections.into_iter().chain(new_selections))
+                    } else {
+                        s.select_anchor_ranges(new_selections)
+                    }
+                });
+            });
+            return;
+        }
+
+        if self.mode == Mode::HelixSelect {
+            self.helix_extend(motion, times, cx)
+        } else {
+            self.helix_move_cursor(motion, times, cx);
+        }
     }
 
     fn helix_find_range_forward(
@@ -224,28 +312,99 @@ impl Vim {
                     found
                 })
             }
+            Motion::FindForward { .. } | Motion::FindBackward { .. } => {
+                self.helix_select_to(motion, times, cx)
+            }
             _ => self.helix_move_and_collapse(motion, times, cx),
         }
     }
 
+    /// Selects from the cursor to wherever the motion goes, like `f` and `t` do in helix.
+    fn helix_select_to(
+        &mut self,
+        motion: Motion,
+        times: Option<usize>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            let text_layout_details = editor.text_layout_details(cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let (_, cursor) = helix_anchor_and_cursor(map, selection);
+                    if let Some((target, goal)) =
+                        motion.move_point(map, cursor, selection.goal, times, &text_layout_details)
+                    {
+                        set_helix_selection(map, selection, cursor, target, goal);
+                    }
+                });
+            });
+        });
+    }
+
+    /// Moves the cursor end of each selection, keeping its anchor. Used by select mode.
+    fn helix_extend(&mut self, motion: Motion, times: Option<usize>, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            let text_layout_details = editor.text_layout_details(cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let (anchor, cursor) = helix_anchor_and_cursor(map, selection);
+                    if let Some((cursor, goal)) =
+                        motion.move_point(map, cursor, selection.goal, times, &text_layout_details)
+                    {
+                        set_helix_selection(map, selection, anchor, cursor, goal);
+                    }
+                });
+            });
+        });
+    }
+
+    pub(crate) fn helix_object(&mut self, object: Object, cx: &mut ViewContext<Self>) {
+        let mut waiting_operator = None;
+        match self.maybe_pop_operator() {
+            Some(Operator::Object { around }) => self.helix_select_object(object, around, cx),
+            Some(Operator::ChangeSurrounds { target: None }) => {
+                if self.check_and_move_to_valid_bracket_pair(object, cx) {
+                    waiting_operator = Some(Operator::ChangeSurrounds {
+                        target: Some(object),
+                    });
+                }
+            }
+            _ => {}
+        }
+        self.clear_operator(cx);
+        if let Some(operator) = waiting_operator {
+            self.push_operator(operator, cx);
+        }
+    }
+
+    fn helix_select_object(&mut self, object: Object, around: bool, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let (_, cursor) = helix_anchor_and_cursor(map, selection);
+                    let mut at_cursor = selection.clone();
+                    at_cursor.collapse_to(cursor, selection.goal);
+                    if let Some(range) = object.range(map, at_cursor, around) {
+                        if !range.is_empty() {
+                            selection.start = range.start;
+                            selection.end = range.end;
+                            selection.reversed = false;
+                            selection.goal = SelectionGoal::None;
+                        }
+                    }
+                });
+            });
+        });
+    }
+
     pub fn helix_delete(&mut self, _: &HelixDelete, cx: &mut ViewContext<Self>) {
         self.store_visual_marks(cx);
         self.update_editor(cx, |vim, editor, cx| {
-            // Fixup selections so they have helix's semantics.
-            // Specifically:
-            //  - Make sure that each cursor acts as a 1 character wide selection
             editor.transact(cx, |editor, cx| {
-                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
-                    s.move_with(|map, selection| {
-                        if selection.is_empty() && !selection.reversed {
-                            selection.end = movement::right(map, selection.end);
-                        }
-                    });
-                });
+                helix_expand_cursors(editor, cx);
+                vim.copy_selections_content(editor, false, cx);
+                editor.insert("", cx);
             });
-
-            vim.copy_selections_content(editor, false, cx);
-            editor.insert("", cx);
         });
     }
 }
@@ -370,4 +529,65 @@ mod test {
             Mode::HelixNormal,
         );
     }
+
+    #[gpui::test]
+    async fn test_select_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("aˇbcd efg", Mode::HelixNormal);
+        cx.simulate_keystrokes("v l l");
+        cx.assert_state("a«bcdˇ» efg", Mode::HelixSelect);
+        cx.simulate_keystrokes("h h h");
+        cx.assert_state("«ˇab»cd efg", Mode::HelixSelect);
+        cx.simulate_keystrokes("v");
+        assert_eq!(cx.mode(), Mode::HelixNormal);
+
+        // Leaving insert mode returns to helix normal mode.
+        cx.set_state("aˇbc", Mode::HelixNormal);
+        cx.simulate_keystrokes("i x escape");
+        cx.assert_state("axˇbc", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_find_selects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("ˇone two three", Mode::HelixNormal);
+        cx.simulate_keystrokes("f w");
+        cx.assert_state("«one twˇ»o three", Mode::HelixNormal);
+
+        cx.set_state("one two threˇe", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-f o");
+        cx.assert_state("one tw«ˇo three»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_match_mode(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("fooˇ(bar baz)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m m");
+        cx.assert_state("foo(bar bazˇ)", Mode::HelixNormal);
+
+        cx.set_state("foo(baˇr baz)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m i (");
+        cx.assert_state("foo(«bar bazˇ»)", Mode::HelixNormal);
+
+        cx.set_state("foo(baˇr baz)", Mode::HelixNormal);
+        cx.simulate_keystrokes("m a (");
+        cx.assert_state("foo«(bar baz)ˇ»", Mode::HelixNormal);
+
+        cx.set_state("foo «barˇ» baz", Mode::HelixNormal);
+        cx.simulate_keystrokes("m s (");
+        cx.assert_state("foo «(bar)ˇ» baz", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_search_selects_match(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("oˇne two one", Mode::HelixNormal);
+        cx.simulate_keystrokes("/ o n e enter");
+        cx.assert_state("one two «oneˇ»", Mode::HelixNormal);
+    }
 }
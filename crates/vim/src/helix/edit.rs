@@ -0,0 +1,326 @@
+use std::ops::Range;
+
+use editor::{movement, scroll::Autoscroll, ClipboardSelection, Editor, ToPoint};
+use gpui::{actions, impl_actions, ViewContext};
+use language::SelectionGoal;
+use serde::Deserialize;
+
+use crate::{
+    helix::helix_expand_cursors,
+    state::{Mode, Register},
+    Vim,
+};
+
+#[derive(Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HelixPaste {
+    #[serde(default)]
+    before: bool,
+}
+
+actions!(
+    vim,
+    [
+        HelixInsert,
+        HelixAppend,
+        HelixChange,
+        HelixYank,
+        HelixReplaceWithYanked
+    ]
+);
+impl_actions!(vim, [HelixPaste]);
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
+    Vim::action(editor, cx, Vim::helix_insert);
+    Vim::action(editor, cx, Vim::helix_append);
+    Vim::action(editor, cx, Vim::helix_change);
+    Vim::action(editor, cx, Vim::helix_yank);
+    Vim::action(editor, cx, |vim, action: &HelixPaste, cx| {
+        let position = if action.before {
+            PastePosition::Before
+        } else {
+            PastePosition::After
+        };
+        vim.helix_paste(position, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixReplaceWithYanked, cx| {
+        vim.helix_paste(PastePosition::Replace, cx)
+    });
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PastePosition {
+    Before,
+    After,
+    Replace,
+}
+
+/// The ranges covered by each helix selection, counting cursors as the character under them.
+fn helix_ranges(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> Vec<(Range<usize>, bool)> {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    editor
+        .selections
+        .all::<usize>(cx)
+        .into_iter()
+        .map(|selection| {
+            let mut range = selection.range();
+            if range.is_empty() {
+                if let Some(ch) = snapshot.chars_at(range.start).next() {
+                    range.end += ch.len_utf8();
+                }
+            }
+            (range, selection.reversed)
+        })
+        .collect()
+}
+
+/// Replaces each range with the matching text and selects what was inserted.
+fn replace_and_select(
+    editor: &mut Editor,
+    edits: Vec<(Range<usize>, String, bool)>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let mut delta = 0isize;
+    let mut new_ranges = Vec::with_capacity(edits.len());
+    for (range, text, reversed) in &edits {
+        let start = (range.start as isize + delta) as usize;
+        let end = start + text.len();
+        new_ranges.push(if *reversed { end..start } else { start..end });
+        delta += text.len() as isize - range.len() as isize;
+    }
+    editor.transact(cx, |editor, cx| {
+        editor.edit(edits.into_iter().map(|(range, text, _)| (range, text)), cx);
+        editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(new_ranges));
+    });
+}
+
+/// Splits a register into one piece per selection when it was yanked from as many
+/// selections, otherwise every selection gets the whole text.
+fn register_pieces(
+    text: &str,
+    clipboard_selections: Option<&[ClipboardSelection]>,
+    count: usize,
+) -> Vec<String> {
+    match clipboard_selections {
+        Some(clipboard_selections) if count > 1 && clipboard_selections.len() == count => {
+            let mut start = 0;
+            clipboard_selections
+                .iter()
+                .map(|selection| {
+                    let end = (start + selection.len).min(text.len());
+                    let piece = text.get(start..end).unwrap_or_default().to_string();
+                    start = end + 1;
+                    piece
+                })
+                .collect()
+        }
+        _ => vec![text.to_string(); count],
+    }
+}
+
+impl Vim {
+    fn helix_insert(&mut self, _: &HelixInsert, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.switch_mode(Mode::Insert, false, cx);
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|_, selection| {
+                    selection.collapse_to(selection.start, SelectionGoal::None)
+                });
+            });
+        });
+    }
+
+    fn helix_append(&mut self, _: &HelixAppend, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.switch_mode(Mode::Insert, false, cx);
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let end = if selection.is_empty() {
+                        movement::right(map, selection.end)
+                    } else {
+                        selection.end
+                    };
+                    selection.collapse_to(end, SelectionGoal::None)
+                });
+            });
+        });
+    }
+
+    fn helix_change(&mut self, _: &HelixChange, cx: &mut ViewContext<Self>) {
+        self.start_recording(cx);
+        self.update_editor(cx, |vim, editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                helix_expand_cursors(editor, cx);
+                vim.copy_selections_content(editor, false, cx);
+                editor.insert("", cx);
+            });
+        });
+        self.switch_mode(Mode::Insert, true, cx);
+    }
+
+    fn helix_yank(&mut self, _: &HelixYank, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |vim, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let ranges = helix_ranges(editor, cx)
+                .into_iter()
+                .map(|(range, _)| range.start.to_point(&snapshot)..range.end.to_point(&snapshot))
+                .collect();
+            vim.copy_ranges(editor, false, true, ranges, cx);
+        });
+    }
+
+    fn helix_paste(&mut self, position: PastePosition, cx: &mut ViewContext<Self>) {
+        let count = Vim::take_count(cx).unwrap_or(1);
+        self.update_editor(cx, |vim, editor, cx| {
+            let selected_register = vim.selected_register.take();
+            let Some(Register {
+                text,
+                clipboard_selections,
+            }) = Vim::update_globals(cx, |globals, cx| {
+                globals.read_register(selected_register, Some(editor), cx)
+            })
+            .filter(|register| !register.text.is_empty())
+            else {
+                return;
+            };
+
+            let ranges = helix_ranges(editor, cx);
+            let pieces = register_pieces(&text, clipboard_selections.as_deref(), ranges.len());
+            let edits = ranges
+                .into_iter()
+                .zip(pieces)
+                .map(|((range, reversed), piece)| {
+                    let range = match position {
+                        PastePosition::Before => range.start..range.start,
+                        PastePosition::After => range.end..range.end,
+                        PastePosition::Replace => range,
+                    };
+                    (range, piece.repeat(count), reversed)
+                })
+                .collect();
+            replace_and_select(editor, edits, cx);
+        });
+    }
+
+    /// Replaces every character in the selections, except line breaks, with `text`.
+    pub(crate) fn helix_replace(&mut self, text: &str, cx: &mut ViewContext<Self>) {
+        self.stop_recording(cx);
+        self.update_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let edits = helix_ranges(editor, cx)
+                .into_iter()
+                .map(|(range, reversed)| {
+                    let replacement = snapshot
+                        .text_for_range(range.clone())
+                        .flat_map(|chunk| chunk.chars())
+                        .map(|ch| if ch == '\n' { "\n" } else { text })
+                        .collect::<String>();
+                    (range, replacement, reversed)
+                })
+                .collect();
+            replace_and_select(editor, edits, cx);
+        });
+        self.clear_operator(cx);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_insert_and_append(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("i - escape");
+        cx.assert_state("one -ˇtwo three", Mode::HelixNormal);
+
+        cx.set_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("a - escape");
+        cx.assert_state("one two-ˇ three", Mode::HelixNormal);
+
+        // A cursor appends after the character under it.
+        cx.set_state("oˇne", Mode::HelixNormal);
+        cx.simulate_keystrokes("a x escape");
+        cx.assert_state("onxˇe", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_change(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("c 2");
+        cx.assert_state("one 2ˇ three", Mode::Insert);
+        cx.simulate_keystrokes("escape");
+        assert_eq!(cx.mode(), Mode::HelixNormal);
+
+        // The changed text is yanked.
+        cx.simulate_keystrokes("p");
+        cx.assert_state("one 2 «twoˇ»three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_yank_and_paste(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("«oneˇ» two", Mode::HelixNormal);
+        cx.simulate_keystrokes("y");
+        cx.assert_state("«oneˇ» two", Mode::HelixNormal);
+
+        cx.set_state("one «twoˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("p");
+        cx.assert_state("one two«oneˇ»", Mode::HelixNormal);
+
+        cx.set_state("one «twoˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("2 shift-p");
+        cx.assert_state("one «oneoneˇ»two", Mode::HelixNormal);
+
+        // Each selection pastes its own piece of a multi-selection yank.
+        cx.set_state("«aˇ» «bˇ»\nx y", Mode::HelixNormal);
+        cx.simulate_keystrokes("y j p");
+        cx.assert_state("a b\nx«aˇ» y«bˇ»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_replace_with_yanked(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("«oneˇ» two", Mode::HelixNormal);
+        cx.simulate_keystrokes("y");
+        cx.set_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-r");
+        cx.assert_state("one «oneˇ» three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_replace_characters(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("a«bc\ndˇ»e", Mode::HelixNormal);
+        cx.simulate_keystrokes("r x");
+        cx.assert_state("a«xx\nxˇ»e", Mode::HelixNormal);
+
+        cx.set_state("aˇbc", Mode::HelixNormal);
+        cx.simulate_keystrokes("r x");
+        cx.assert_state("a«xˇ»c", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_change_case(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("«helloˇ» world", Mode::HelixNormal);
+        cx.simulate_keystrokes("~");
+        cx.assert_state("«HELLOˇ» world", Mode::HelixNormal);
+        cx.simulate_keystrokes("`");
+        cx.assert_state("«helloˇ» world", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-`");
+        cx.assert_state("«HELLOˇ» world", Mode::HelixNormal);
+    }
+}
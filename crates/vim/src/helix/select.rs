@@ -0,0 +1,599 @@
+use std::ops::Range;
+
+use anyhow::anyhow;
+use editor::{display_map::ToDisplayPoint, scroll::Autoscroll, Editor};
+use gpui::{actions, ViewContext};
+use language::{Point, SelectionGoal};
+use regex::{Regex, RegexBuilder};
+use search::{buffer_search, BufferSearchBar, SearchOptions};
+use workspace::searchable::Direction;
+
+use crate::{
+    helix::{helix_anchor_and_cursor, helix_expand_cursors},
+    state::SearchState,
+    Vim,
+};
+
+actions!(
+    vim,
+    [
+        HelixCollapseSelection,
+        HelixFlipSelections,
+        HelixEnsureSelectionsForward,
+        HelixKeepPrimarySelection,
+        HelixRemovePrimarySelection,
+        HelixSelectLine,
+        HelixExtendToLineBounds,
+        HelixShrinkToLineBounds,
+        HelixTrimSelections,
+        HelixSplitSelectionOnNewline,
+        HelixSelectRegex,
+        HelixSplitSelection,
+        HelixKeepSelections,
+        HelixRemoveSelections
+    ]
+);
+
+/// What to do with the selections once the user has typed a regex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegexSelection {
+    /// Select every match inside the selections (`s`).
+    Select,
+    /// Split the selections on every match (`S`).
+    Split,
+    /// Keep the selections that contain a match (`K`).
+    Keep,
+    /// Remove the selections that contain a match (`alt-K`).
+    Remove,
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
+    Vim::action(editor, cx, Vim::helix_collapse_selection);
+    Vim::action(editor, cx, Vim::helix_flip_selections);
+    Vim::action(editor, cx, Vim::helix_ensure_selections_forward);
+    Vim::action(editor, cx, Vim::helix_keep_primary_selection);
+    Vim::action(editor, cx, Vim::helix_remove_primary_selection);
+    Vim::action(editor, cx, Vim::helix_select_line);
+    Vim::action(editor, cx, Vim::helix_extend_to_line_bounds);
+    Vim::action(editor, cx, Vim::helix_shrink_to_line_bounds);
+    Vim::action(editor, cx, Vim::helix_trim_selections);
+    Vim::action(editor, cx, Vim::helix_split_selection_on_newline);
+    Vim::action(editor, cx, |vim, _: &HelixSelectRegex, cx| {
+        vim.prompt_for_regex_selection(RegexSelection::Select, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixSplitSelection, cx| {
+        vim.prompt_for_regex_selection(RegexSelection::Split, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixKeepSelections, cx| {
+        vim.prompt_for_regex_selection(RegexSelection::Keep, cx)
+    });
+    Vim::action(editor, cx, |vim, _: &HelixRemoveSelections, cx| {
+        vim.prompt_for_regex_selection(RegexSelection::Remove, cx)
+    });
+}
+
+/// Replaces the selections with sub-ranges of them, computed from their text. Selections
+/// are left alone if that would remove all of them.
+fn map_selection_ranges(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+    mut f: impl FnMut(&str) -> Vec<Range<usize>>,
+) -> bool {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let mut ranges = Vec::new();
+    for selection in editor.selections.all::<usize>(cx) {
+        let text = snapshot
+            .text_for_range(selection.range())
+            .collect::<String>();
+        ranges.extend(f(&text).into_iter().map(|range| {
+            let range = selection.start + range.start..selection.start + range.end;
+            if selection.reversed {
+                range.end..range.start
+            } else {
+                range
+            }
+        }));
+    }
+    if ranges.is_empty() {
+        return false;
+    }
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| s.select_ranges(ranges));
+    true
+}
+
+fn regex_selection_ranges(
+    operation: RegexSelection,
+    regex: &Regex,
+    text: &str,
+) -> Vec<Range<usize>> {
+    match operation {
+        RegexSelection::Select => regex
+            .find_iter(text)
+            .map(|found| found.range())
+            .filter(|range| !range.is_empty())
+            .collect(),
+        RegexSelection::Split => {
+            let mut ranges = Vec::new();
+            let mut start = 0;
+            for found in regex.find_iter(text) {
+                if found.start() > start {
+                    ranges.push(start..found.start());
+                }
+                start = found.end();
+            }
+            if start < text.len() {
+                ranges.push(start..text.len());
+            }
+            ranges
+        }
+        RegexSelection::Keep if regex.is_match(text) => vec![0..text.len()],
+        RegexSelection::Remove if !regex.is_match(text) => vec![0..text.len()],
+        RegexSelection::Keep | RegexSelection::Remove => Vec::new(),
+    }
+}
+
+impl Vim {
+    fn helix_collapse_selection(&mut self, _: &HelixCollapseSelection, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let (_, cursor) = helix_anchor_and_cursor(map, selection);
+                    selection.collapse_to(cursor, SelectionGoal::None);
+                });
+            });
+        });
+    }
+
+    fn helix_flip_selections(&mut self, _: &HelixFlipSelections, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|_, selection| {
+                    if !selection.is_empty() {
+                        selection.reversed = !selection.reversed;
+                    }
+                });
+            });
+        });
+    }
+
+    fn helix_ensure_selections_forward(
+        &mut self,
+        _: &HelixEnsureSelectionsForward,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|_, selection| selection.reversed = false);
+            });
+        });
+    }
+
+    fn helix_keep_primary_selection(
+        &mut self,
+        _: &HelixKeepPrimarySelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                let primary = s.newest_anchor().clone();
+                s.select_anchors(vec![primary]);
+            });
+        });
+    }
+
+    fn helix_remove_primary_selection(
+        &mut self,
+        _: &HelixRemovePrimarySelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                let primary_id = s.newest_anchor().id;
+                let rest = s
+                    .disjoint_anchors()
+                    .iter()
+                    .filter(|selection| selection.id != primary_id)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !rest.is_empty() {
+                    s.select_anchors(rest);
+                }
+            });
+        });
+    }
+
+    /// Selects whole lines, extending a selection that already is one by `count` lines.
+    fn helix_select_line(&mut self, _: &HelixSelectLine, cx: &mut ViewContext<Self>) {
+        let count = Vim::take_count(cx).unwrap_or(1) as u32;
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let max_point = map.buffer_snapshot.max_point();
+                    let start = selection.start.to_point(map);
+                    let end = selection.end.to_point(map);
+                    let ends_at_line_start = end.column == 0 && end.row > start.row;
+                    let is_whole_lines = start.column == 0 && ends_at_line_start;
+
+                    let mut last_row = if ends_at_line_start {
+                        end.row - 1
+                    } else {
+                        end.row
+                    };
+                    last_row += if is_whole_lines { count } else { count - 1 };
+                    let end = if last_row < max_point.row {
+                        Point::new(last_row + 1, 0)
+                    } else {
+                        max_point
+                    };
+
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = end.to_display_point(map);
+                    selection.reversed = false;
+                    selection.goal = SelectionGoal::None;
+                });
+            });
+        });
+    }
+
+    fn helix_extend_to_line_bounds(
+        &mut self,
+        _: &HelixExtendToLineBounds,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let max_point = map.buffer_snapshot.max_point();
+                    let start = selection.start.to_point(map);
+                    let end = selection.end.to_point(map);
+                    let end = if end.column == 0 && end.row > start.row {
+                        end
+                    } else if end.row < max_point.row {
+                        Point::new(end.row + 1, 0)
+                    } else {
+                        max_point
+                    };
+                    selection.start = Point::new(start.row, 0).to_display_point(map);
+                    selection.end = end.to_display_point(map);
+                    selection.goal = SelectionGoal::None;
+                });
+            });
+        });
+    }
+
+    fn helix_shrink_to_line_bounds(
+        &mut self,
+        _: &HelixShrinkToLineBounds,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.move_with(|map, selection| {
+                    let mut start = selection.start.to_point(map);
+                    let mut end = selection.end.to_point(map);
+                    if start.column != 0 {
+                        start = Point::new(start.row + 1, 0);
+                    }
+                    if end.column != 0 {
+                        end = Point::new(end.row, 0);
+                    }
+                    // Selections within a single line stay as they are.
+                    if start < end {
+                        selection.start = start.to_display_point(map);
+                        selection.end = end.to_display_point(map);
+                        selection.goal = SelectionGoal::None;
+                    }
+                });
+            });
+        });
+    }
+
+    fn helix_trim_selections(&mut self, _: &HelixTrimSelections, cx: &mut ViewContext<Self>) {
+        self.update_editor(cx, |_, editor, cx| {
+            map_selection_ranges(editor, cx, |text| {
+                let start = text.len() - text.trim_start().len();
+                let end = text.trim_end().len();
+                if start < end {
+                    vec![start..end]
+                } else {
+                    Vec::new()
+                }
+            });
+        });
+    }
+
+    fn helix_split_selection_on_newline(
+        &mut self,
+        _: &HelixSplitSelectionOnNewline,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.update_editor(cx, |_, editor, cx| {
+            map_selection_ranges(editor, cx, |text| {
+                let mut ranges = Vec::new();
+                let mut start = 0;
+                for line in text.split('\n') {
+                    if !line.is_empty() {
+                        ranges.push(start..start + line.len());
+                    }
+                    start += line.len() + 1;
+                }
+                ranges
+            });
+        });
+    }
+
+    /// Opens the buffer search bar to read the regex for `operation`, which is applied to
+    /// the current selections when the search is submitted.
+    fn prompt_for_regex_selection(
+        &mut self,
+        operation: RegexSelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(pane) = self.pane(cx) else {
+            return;
+        };
+        self.update_editor(cx, |_, editor, cx| helix_expand_cursors(editor, cx));
+        let prior_selections = self.editor_selections(cx);
+        let prior_mode = self.mode;
+        pane.update(cx, |pane, cx| {
+            if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
+                search_bar.update(cx, |search_bar, cx| {
+                    if !search_bar.show(cx) {
+                        return;
+                    }
+                    search_bar.select_query(cx);
+                    cx.focus_self();
+                    search_bar.set_replacement(None, cx);
+                    search_bar.set_search_options(SearchOptions::REGEX, cx);
+
+                    self.search = SearchState {
+                        direction: Direction::Next,
+                        count: 1,
+                        initial_query: search_bar.query(cx),
+                        prior_selections,
+                        prior_operator: None,
+                        prior_mode,
+                        regex_selection: Some(operation),
+                    };
+                });
+            }
+        })
+    }
+
+    pub(crate) fn submit_regex_selection(
+        &mut self,
+        operation: RegexSelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(pane) = self.pane(cx) else {
+            return;
+        };
+        let query = pane.update(cx, |pane, cx| {
+            let search_bar = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>()?;
+            search_bar.update(cx, |search_bar, cx| {
+                let case_sensitive = search_bar.has_search_option(SearchOptions::CASE_SENSITIVE);
+                let query = search_bar.query(cx);
+                search_bar.dismiss(&buffer_search::Dismiss, cx);
+                Some((query, case_sensitive))
+            })
+        });
+        let Some((query, case_sensitive)) = query else {
+            return;
+        };
+
+        // The search bar moves the selections to each match as the regex is typed.
+        let prior_selections = std::mem::take(&mut self.search.prior_selections);
+        self.update_editor(cx, |_, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            if prior_selections
+                .iter()
+                .all(|range| range.start.is_valid(&snapshot))
+            {
+                editor.change_selections(None, cx, |s| s.select_anchor_ranges(prior_selections));
+            }
+        });
+        if query.is_empty() {
+            return;
+        }
+
+        let regex = match RegexBuilder::new(&query)
+            .case_insensitive(!case_sensitive)
+            .build()
+        {
+            Ok(regex) => regex,
+            Err(err) => {
+                self.notify_command_error(anyhow!("Invalid regex {query:?}: {err}"), cx);
+                return;
+            }
+        };
+        self.update_editor(cx, |_, editor, cx| {
+            map_selection_ranges(editor, cx, |text| {
+                regex_selection_ranges(operation, &regex, text)
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_collapse_and_flip(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-;");
+        cx.assert_state("one «ˇtwo» three", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-:");
+        cx.assert_state("one «twoˇ» three", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("one twˇo three", Mode::HelixNormal);
+
+        cx.set_state("one «ˇtwo» three", Mode::HelixNormal);
+        cx.simulate_keystrokes(";");
+        cx.assert_state("one ˇtwo three", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_primary_selection(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("«aˇ» b «cˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-,");
+        cx.assert_state("«aˇ» b c", Mode::HelixNormal);
+
+        cx.set_state("«aˇ» b «cˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes(",");
+        cx.assert_state("a b «cˇ»", Mode::HelixNormal);
+    }
+
+    #[gpui::test]
+    async fn test_select_line(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state(
+            indoc! {"
+            one
+            twˇo
+            three
+            four"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            one
+            «two
+            ˇ»three
+            four"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            one
+            «two
+            three
+            ˇ»four"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("x");
+        cx.assert_state(
+            indoc! {"
+            one
+            «two
+            three
+            fourˇ»"},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            oˇne
+            two
+            three"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("2 x");
+        cx.assert_state(
+            indoc! {"
+            «one
+            two
+            ˇ»three"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_line_bounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state(
+            indoc! {"
+            one
+            t«wo
+            thˇ»ree
+            four"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("shift-x");
+        cx.assert_state(
+            indoc! {"
+            one
+            «two
+            three
+            ˇ»four"},
+            Mode::HelixNormal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            o«ne
+            two
+            thˇ»ree"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("alt-x");
+        cx.assert_state(
+            indoc! {"
+            one
+            «two
+            ˇ»three"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_trim_and_split_lines(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("a«  b c  ˇ»d", Mode::HelixNormal);
+        cx.simulate_keystrokes("_");
+        cx.assert_state("a  «b cˇ»  d", Mode::HelixNormal);
+
+        cx.set_state(
+            indoc! {"
+            «one
+            two
+            thˇ»ree"},
+            Mode::HelixNormal,
+        );
+        cx.simulate_keystrokes("alt-s");
+        cx.assert_state(
+            indoc! {"
+            «oneˇ»
+            «twoˇ»
+            «thˇ»ree"},
+            Mode::HelixNormal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_regex_selections(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new_helix(cx).await;
+
+        cx.set_state("«foo bar baz fooˇ» bar", Mode::HelixNormal);
+        cx.simulate_keystrokes("s b a . enter");
+        cx.assert_state("foo «barˇ» «bazˇ» foo bar", Mode::HelixNormal);
+
+        cx.set_state("«a, b,c ˇ»d", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-s , space * enter");
+        cx.assert_state("«aˇ», «bˇ»,«c ˇ»d", Mode::HelixNormal);
+
+        cx.set_state("«oneˇ» «twoˇ» «threeˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("shift-k t enter");
+        cx.assert_state("one «twoˇ» «threeˇ»", Mode::HelixNormal);
+
+        cx.set_state("«oneˇ» «twoˇ» «threeˇ»", Mode::HelixNormal);
+        cx.simulate_keystrokes("alt-shift-k t enter");
+        cx.assert_state("«oneˇ» two three", Mode::HelixNormal);
+
+        // Without a match the selections stay where they were.
+        cx.set_state("«oneˇ» two", Mode::HelixNormal);
+        cx.simulate_keystrokes("s x enter");
+        cx.assert_state("«oneˇ» two", Mode::HelixNormal);
+    }
+}
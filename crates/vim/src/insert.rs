@@ -21,8 +21,13 @@ impl Vim {
         self.stop_recording_immediately(action.boxed_clone(), cx);
         if count <= 1 || Vim::globals(cx).dot_replaying {
             self.create_mark("^".into(), false, cx);
+            // Helix leaves the cursor where insert mode ended.
+            let helix = Vim::helix_enabled(cx);
             self.update_editor(cx, |_, editor, cx| {
                 editor.dismiss_menus_and_popups(false, cx);
+                if helix {
+                    return;
+                }
                 editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.move_cursors_with(|map, mut cursor, _| {
                         *cursor.column_mut() = cursor.column().saturating_sub(1);
@@ -30,7 +35,12 @@ impl Vim {
                     });
                 });
             });
-            self.switch_mode(Mode::Normal, false, cx);
+            let mode = if helix {
+                Mode::HelixNormal
+            } else {
+                Mode::Normal
+            };
+            self.switch_mode(mode, false, cx);
             return;
         }
 
@@ -0,0 +1,763 @@
+//! Mappings and ex commands defined with vimrc-style lines in the `vim.vimrc` setting.
+//!
+//! Mappings are turned into key bindings that replay their keys (or dispatch an action)
+//! in the modes they were defined for. Non-recursive mappings replay their keys with the
+//! `VimNoremap` context set, which all user mappings exclude.
+
+use std::ops::Deref;
+
+use anyhow::{anyhow, bail, Result};
+use collections::HashMap;
+use editor::Editor;
+use gpui::{
+    impl_actions, Action, AppContext, DismissEvent, Global, KeyBinding, Keystroke, NoAction,
+    ViewContext, VisualContext as _, WindowContext,
+};
+use serde::Deserialize;
+use settings::{Settings, SettingsStore};
+use util::ResultExt;
+use workspace::notifications::{simple_message_notification::MessageNotification, NotificationId};
+
+use crate::{
+    command::{command_interceptor, parse_keystrokes},
+    state::VimGlobals,
+    Vim, VimSettings,
+};
+
+/// Replays the keys of a user mapping.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct MappedKeys {
+    keys: String,
+    remap: bool,
+}
+
+/// Runs an ex command defined with `:command`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct UserCommand {
+    name: String,
+    args: String,
+}
+
+impl_actions!(vim, [MappedKeys, UserCommand]);
+
+/// Stops runaway recursive mappings.
+const MAX_MAPPED_KEYSTROKES: usize = 1000;
+/// Stops user commands that (indirectly) run themselves.
+const MAX_COMMAND_DEPTH: usize = 20;
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
+    Vim::action(editor, cx, |_, action: &MappedKeys, cx| {
+        send_mapped_keys(action, cx)
+    });
+    Vim::action(
+        editor,
+        cx,
+        |vim, action: &UserCommand, cx| match expand_user_command(&action.name, &action.args, 0, cx)
+        {
+            Ok(actions) => {
+                for action in actions {
+                    cx.dispatch_action(action);
+                }
+            }
+            Err(err) => vim.notify_command_error(err, cx),
+        },
+    );
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MapMode {
+    Normal,
+    Visual,
+    OperatorPending,
+    Insert,
+}
+
+impl MapMode {
+    fn context(&self) -> &'static str {
+        match self {
+            MapMode::Normal => "vim_mode == normal && !VimNoremap",
+            MapMode::Visual => "vim_mode == visual && !VimNoremap",
+            MapMode::OperatorPending => "vim_mode == operator && !VimNoremap",
+            MapMode::Insert => "vim_mode == insert && !VimNoremap",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum MapTarget {
+    Keys(String),
+    Action { name: String, args: Option<String> },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Definition {
+    Map {
+        modes: &'static [MapMode],
+        lhs: String,
+        rhs: MapTarget,
+        remap: bool,
+    },
+    Unmap {
+        modes: &'static [MapMode],
+        lhs: String,
+    },
+    Command {
+        name: String,
+        replacement: String,
+    },
+}
+
+/// The `vim.vimrc` setting as it was last parsed.
+#[derive(Default)]
+struct ParsedVimrc {
+    lines: Vec<String>,
+    definitions: Vec<Definition>,
+    errors: Vec<String>,
+    commands: HashMap<String, String>,
+}
+
+impl Global for ParsedVimrc {}
+
+pub(crate) fn init(cx: &mut AppContext) {
+    parse_user_mappings(cx);
+    cx.observe_global::<SettingsStore>(parse_user_mappings)
+        .detach();
+}
+
+/// Parses the `vim.vimrc` setting again if it has changed since it was last parsed.
+fn parse_user_mappings(cx: &mut AppContext) {
+    let lines = user_mappings(cx);
+    if cx
+        .try_global::<ParsedVimrc>()
+        .is_some_and(|parsed| parsed.lines == lines)
+    {
+        return;
+    }
+    let (definitions, errors) = parse_vimrc(&lines);
+    let commands = definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Command { name, replacement } => Some((name.clone(), replacement.clone())),
+            _ => None,
+        })
+        .collect();
+    cx.set_global(ParsedVimrc {
+        lines,
+        definitions,
+        errors,
+        commands,
+    });
+}
+
+/// The vimrc lines from the user's settings, or nothing if vim mode is off.
+pub fn user_mappings(cx: &AppContext) -> Vec<String> {
+    if !cx.has_global::<VimGlobals>() || !vim_mode_setting::VimModeSetting::get_global(cx).0 {
+        return Vec::new();
+    }
+    VimSettings::get_global(cx).vimrc.clone()
+}
+
+/// Binds the mappings from the `vim.vimrc` setting. Lines that can't be parsed are
+/// skipped, shown in a notification, and reported in the returned error.
+pub fn load_user_mappings(cx: &mut AppContext) -> Result<()> {
+    parse_user_mappings(cx);
+    let parsed = cx.global::<ParsedVimrc>();
+    let definitions = parsed.definitions.clone();
+    let mut errors = parsed.errors.clone();
+    let mut bindings = Vec::new();
+    for definition in definitions {
+        let (modes, lhs, action) = match definition {
+            Definition::Map {
+                modes,
+                lhs,
+                rhs,
+                remap,
+            } => {
+                let action = match rhs {
+                    MapTarget::Keys(keys) => MappedKeys { keys, remap }.boxed_clone(),
+                    MapTarget::Action { name, args } => match build_action(&name, args, cx) {
+                        Ok(action) => action,
+                        Err(err) => {
+                            errors.push(err.to_string());
+                            continue;
+                        }
+                    },
+                };
+                (modes, lhs, action)
+            }
+            Definition::Unmap { modes, lhs } => (modes, lhs, NoAction.boxed_clone()),
+            Definition::Command { .. } => continue,
+        };
+        for mode in modes {
+            match KeyBinding::load(&lhs, action.boxed_clone(), Some(mode.context()), None) {
+                Ok(binding) => bindings.push(binding),
+                Err(err) => errors.push(format!("{lhs}: {err}")),
+            }
+        }
+    }
+    cx.bind_keys(bindings);
+
+    let error =
+        (!errors.is_empty()).then(|| format!("Invalid vim mappings:\n{}", errors.join("\n")));
+    notify_mapping_errors(error.clone(), cx);
+    match error {
+        Some(error) => Err(anyhow!(error)),
+        None => Ok(()),
+    }
+}
+
+fn notify_mapping_errors(error: Option<String>, cx: &mut AppContext) {
+    struct VimrcErrorNotification;
+    let id = NotificationId::unique::<VimrcErrorNotification>();
+
+    for workspace in workspace::local_workspace_windows(cx) {
+        workspace
+            .update(cx, |workspace, cx| match &error {
+                Some(error) => {
+                    workspace.show_notification(id.clone(), cx, |cx| {
+                        cx.new_view(|_| {
+                            MessageNotification::new(error.clone())
+                                .with_click_message("Open settings")
+                                .on_click(|cx| {
+                                    cx.dispatch_action(zed_actions::OpenSettings.boxed_clone());
+                                    cx.emit(DismissEvent);
+                                })
+                        })
+                    });
+                }
+                None => workspace.dismiss_notification(&id, cx),
+            })
+            .log_err();
+    }
+}
+
+fn build_action(name: &str, args: Option<String>, cx: &AppContext) -> Result<Box<dyn Action>> {
+    let args = args
+        .map(|args| serde_json::from_str(&args))
+        .transpose()
+        .map_err(|err| anyhow!("invalid arguments for {name}: {err}"))?;
+    cx.build_action(name, args)
+}
+
+fn send_mapped_keys(action: &MappedKeys, cx: &mut WindowContext) {
+    let globals = Vim::globals(cx);
+    let mut keystrokes = parse_keystrokes(&action.keys)
+        .into_iter()
+        .map(|keystroke| (keystroke, action.remap))
+        .collect::<Vec<_>>();
+    keystrokes.reverse();
+    // Keys from mappings that run while another mapping is being replayed go to the
+    // front of the queue, so that they're handled before the rest of the outer mapping.
+    globals.mapped_keystrokes.append(&mut keystrokes);
+    if globals.dispatching_mapped_keys {
+        return;
+    }
+    globals.dispatching_mapped_keys = true;
+
+    cx.spawn(|mut cx| async move {
+        let mut result = Ok(());
+        let mut count = 0;
+        loop {
+            if count == MAX_MAPPED_KEYSTROKES {
+                result = Err(anyhow!(
+                    "mappings expanded to over {MAX_MAPPED_KEYSTROKES} keystrokes"
+                ));
+                break;
+            }
+            count += 1;
+            let dispatched = cx.update(|cx| {
+                let Some((keystroke, remap)) = Vim::globals(cx).mapped_keystrokes.pop() else {
+                    return false;
+                };
+                if Vim::globals(cx).noremap == remap {
+                    Vim::globals(cx).noremap = !remap;
+                    cx.refresh();
+                }
+                let focused = cx.focused();
+                cx.dispatch_keystroke(keystroke);
+                if cx.focused() != focused {
+                    // Let focus changes (e.g. opening the command palette) take effect
+                    // before the next keystroke is dispatched.
+                    cx.draw();
+                }
+                true
+            });
+            if !dispatched.unwrap_or(false) {
+                break;
+            }
+        }
+
+        cx.deref()
+            .update(|cx| {
+                let globals = Vim::globals(cx);
+                globals.mapped_keystrokes.clear();
+                globals.dispatching_mapped_keys = false;
+                let was_noremap = std::mem::take(&mut globals.noremap);
+                if was_noremap {
+                    cx.refresh();
+                }
+            })
+            .log_err();
+        result
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Returns an action for `query` if it names a command defined with `:command`.
+pub(crate) fn user_command(query: &str, cx: &AppContext) -> Option<Box<dyn Action>> {
+    let (name, args) = split_command(query);
+    if user_command_replacement(name, cx).is_none() {
+        return None;
+    }
+    Some(
+        UserCommand {
+            name: name.to_string(),
+            args: args.to_string(),
+        }
+        .boxed_clone(),
+    )
+}
+
+/// What the command defined with `:command` as `name` expands to.
+fn user_command_replacement<'a>(name: &str, cx: &'a AppContext) -> Option<&'a String> {
+    cx.try_global::<ParsedVimrc>()?.commands.get(name)
+}
+
+fn split_command(query: &str) -> (&str, &str) {
+    let (name, args) = query
+        .trim()
+        .split_once(char::is_whitespace)
+        .unwrap_or((query.trim(), ""));
+    (name.trim_end_matches('!'), args.trim())
+}
+
+/// Resolves a user command into the actions it runs, expanding `<args>` and any user
+/// commands it calls.
+fn expand_user_command(
+    name: &str,
+    args: &str,
+    depth: usize,
+    cx: &AppContext,
+) -> Result<Vec<Box<dyn Action>>> {
+    if depth == MAX_COMMAND_DEPTH {
+        bail!("{name} calls itself too many times");
+    }
+    let replacement = user_command_replacement(name, cx)
+        .ok_or_else(|| anyhow!("Not an editor command: {name}"))?
+        .replace("<args>", args);
+
+    let mut actions = Vec::new();
+    for command in split_bar(&replacement) {
+        if let Some((name, args)) = parse_action(command) {
+            actions.push(build_action(name, args.map(ToString::to_string), cx)?);
+            continue;
+        }
+        let (name, args) = split_command(command);
+        if user_command_replacement(name, cx).is_some() {
+            actions.extend(expand_user_command(name, args, depth + 1, cx)?);
+            continue;
+        }
+        let result = command_interceptor(command, cx)
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Not an editor command: {command}"))?;
+        actions.push(result.action);
+    }
+    Ok(actions)
+}
+
+/// Splits a command line on `|`, except in commands like `:normal` that take the
+/// rest of the line as their argument.
+fn split_bar(line: &str) -> Vec<&str> {
+    let mut commands = Vec::new();
+    let mut rest = line;
+    loop {
+        let command = rest.trim_start().trim_start_matches(':');
+        if command.starts_with("norm") || command.starts_with('!') {
+            commands.push(command.trim_end());
+            break;
+        }
+        match command.split_once('|') {
+            Some((command, remainder)) => {
+                commands.push(command.trim_end());
+                rest = remainder;
+            }
+            None => {
+                commands.push(command.trim_end());
+                break;
+            }
+        }
+    }
+    commands.retain(|command| !command.is_empty());
+    commands
+}
+
+/// Parses `namespace::Action` optionally followed by JSON arguments.
+fn parse_action(text: &str) -> Option<(&str, Option<&str>)> {
+    let (name, args) = match text.split_once(char::is_whitespace) {
+        Some((name, args)) => (name, Some(args.trim()).filter(|args| !args.is_empty())),
+        None => (text, None),
+    };
+    let is_action_name = name.split("::").count() > 1
+        && name.split("::").all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+    is_action_name.then_some((name, args))
+}
+
+/// Parses vimrc lines, returning the definitions and an error message for each line
+/// that isn't supported.
+fn parse_vimrc(lines: &[String]) -> (Vec<Definition>, Vec<String>) {
+    let mut leader = "\\".to_string();
+    let mut definitions = Vec::new();
+    let mut errors = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('"') {
+            continue;
+        }
+        match parse_line(line, &mut leader) {
+            Ok(Some(definition)) => definitions.push(definition),
+            Ok(None) => {}
+            Err(err) => errors.push(format!("{line}: {err}")),
+        }
+    }
+    (definitions, errors)
+}
+
+fn parse_line(line: &str, leader: &mut String) -> Result<Option<Definition>> {
+    let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let command = command.trim_end_matches('!');
+    let mut rest = rest.trim_start();
+
+    if command == "let" {
+        let Some(value) = rest
+            .strip_prefix("mapleader")
+            .and_then(|rest| rest.trim_start().strip_prefix('='))
+        else {
+            bail!("only mapleader can be set");
+        };
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        *leader = match value.to_ascii_lowercase().as_str() {
+            "\\<space>" | "<space>" | " " => "<space>".to_string(),
+            _ => value.to_string(),
+        };
+        return Ok(None);
+    }
+
+    if command == "command" || command == "com" {
+        while let Some(attribute) = rest.strip_prefix('-') {
+            rest = attribute
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, rest)| rest.trim_start());
+        }
+        let (name, replacement) = rest
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| anyhow!("expected a command name and replacement"))?;
+        if !name.starts_with(|c: char| c.is_ascii_uppercase())
+            || !name.chars().all(|c| c.is_ascii_alphanumeric())
+        {
+            bail!("user command names must start with an uppercase letter");
+        }
+        return Ok(Some(Definition::Command {
+            name: name.to_string(),
+            replacement: replacement.trim().to_string(),
+        }));
+    }
+
+    let (mode, kind) = ["noremap", "unmap", "map"]
+        .into_iter()
+        .find_map(|kind| Some((command.strip_suffix(kind)?, kind)))
+        .ok_or_else(|| anyhow!("unsupported command {command:?}"))?;
+    let modes: &'static [MapMode] = match mode {
+        "" => &[MapMode::Normal, MapMode::Visual, MapMode::OperatorPending],
+        "n" => &[MapMode::Normal],
+        "v" | "x" => &[MapMode::Visual],
+        "o" => &[MapMode::OperatorPending],
+        "i" => &[MapMode::Insert],
+        _ => bail!("unsupported command {command:?}"),
+    };
+
+    while let Some(special) = ["<silent>", "<nowait>", "<buffer>", "<unique>"]
+        .into_iter()
+        .find(|special| rest.starts_with(special))
+    {
+        rest = rest[special.len()..].trim_start();
+    }
+    let (lhs, rhs) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let lhs = expand_leader(lhs, leader);
+    let keystrokes = parse_keystrokes(&lhs);
+    if keystrokes.is_empty() {
+        bail!("expected keys to map");
+    }
+    let lhs = keystrokes
+        .iter()
+        .map(Keystroke::unparse)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if kind == "unmap" {
+        return Ok(Some(Definition::Unmap { modes, lhs }));
+    }
+
+    let rhs = rhs.trim();
+    if rhs.is_empty() {
+        bail!("expected keys or an action to map {lhs} to");
+    }
+    let rhs = match parse_action(rhs) {
+        Some((name, args)) => MapTarget::Action {
+            name: name.to_string(),
+            args: args.map(ToString::to_string),
+        },
+        None => MapTarget::Keys(expand_leader(rhs, leader)),
+    };
+    Ok(Some(Definition::Map {
+        modes,
+        lhs,
+        rhs,
+        remap: kind == "map",
+    }))
+}
+
+fn expand_leader(keys: &str, leader: &str) -> String {
+    let mut expanded = String::new();
+    let mut rest = keys;
+    while let Some(ix) = rest.to_ascii_lowercase().find("<leader>") {
+        expanded.push_str(&rest[..ix]);
+        expanded.push_str(leader);
+        rest = &rest[ix + "<leader>".len()..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use settings::SettingsStore;
+
+    use super::*;
+    use crate::{state::Mode, test::VimTestContext};
+
+    fn parse(lines: &[&str]) -> Vec<Definition> {
+        let lines = lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        let (definitions, errors) = parse_vimrc(&lines);
+        assert_eq!(errors, Vec::<String>::new());
+        definitions
+    }
+
+    #[test]
+    fn test_parse_vimrc() {
+        assert_eq!(
+            parse(&[
+                "\" comment",
+                "nnoremap Y y$",
+                "let mapleader = \" \"",
+                "nmap <silent> <leader>w :w<CR>",
+                "xnoremap <C-f> editor::Format",
+                "omap ic iw",
+                "iunmap <c-w>",
+                "command! -nargs=1 Grep normal /<args><CR>",
+            ]),
+            vec![
+                Definition::Map {
+                    modes: &[MapMode::Normal],
+                    lhs: "shift-y".to_string(),
+                    rhs: MapTarget::Keys("y$".to_string()),
+                    remap: false,
+                },
+                Definition::Map {
+                    modes: &[MapMode::Normal],
+                    lhs: "space w".to_string(),
+                    rhs: MapTarget::Keys(":w<CR>".to_string()),
+                    remap: true,
+                },
+                Definition::Map {
+                    modes: &[MapMode::Visual],
+                    lhs: "ctrl-f".to_string(),
+                    rhs: MapTarget::Action {
+                        name: "editor::Format".to_string(),
+                        args: None,
+                    },
+                    remap: false,
+                },
+                Definition::Map {
+                    modes: &[MapMode::OperatorPending],
+                    lhs: "i c".to_string(),
+                    rhs: MapTarget::Keys("iw".to_string()),
+                    remap: true,
+                },
+                Definition::Unmap {
+                    modes: &[MapMode::Insert],
+                    lhs: "ctrl-w".to_string(),
+                },
+                Definition::Command {
+                    name: "Grep".to_string(),
+                    replacement: "normal /<args><CR>".to_string(),
+                },
+            ]
+        );
+
+        let lines = ["set number".to_string(), "command lower x".to_string()];
+        let (definitions, errors) = parse_vimrc(&lines);
+        assert!(definitions.is_empty());
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_split_bar() {
+        assert_eq!(split_bar("w | e foo"), vec!["w", "e foo"]);
+        assert_eq!(split_bar(":w|normal a|b"), vec!["w", "normal a|b"]);
+        assert_eq!(
+            parse_action("editor::Format"),
+            Some(("editor::Format", None))
+        );
+        assert_eq!(
+            parse_action("pane::CloseActiveItem {\"save_intent\": \"skip\"}"),
+            Some(("pane::CloseActiveItem", Some("{\"save_intent\": \"skip\"}")))
+        );
+        assert_eq!(parse_action("normal dd"), None);
+    }
+
+    fn set_vimrc(cx: &mut VimTestContext, lines: &[&str]) {
+        let lines = lines
+            .iter()
+            .map(|line| line.to_string())
+            .collect::<Vec<_>>();
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| s.vimrc = Some(lines));
+        });
+        cx.update(|cx| load_user_mappings(cx).unwrap());
+    }
+
+    #[gpui::test]
+    async fn test_user_mappings(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        set_vimrc(
+            &mut cx,
+            &[
+                "nnoremap Q dd",
+                "nmap X Qp",
+                "nnoremap k j",
+                "nnoremap Z kk",
+                "onoremap il $",
+            ],
+        );
+
+        cx.set_state(
+            indoc! {"
+                ˇone
+                two
+                three
+                four"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("shift-x");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+                two
+                ˇone
+                three
+                four"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("k");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+                two
+                one
+                ˇthree
+                four"},
+            Mode::Normal,
+        );
+
+        // The keys of a non-recursive mapping ignore other mappings, so this moves
+        // up even though k is mapped to j.
+        cx.simulate_keystrokes("shift-z");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+                ˇtwo
+                one
+                three
+                four"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes("l d i l");
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+                ˇt
+                one
+                three
+                four"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_user_commands(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        set_vimrc(
+            &mut cx,
+            &[
+                "command! Once normal yyp",
+                "command! Twice Once | Once",
+                "command! Prefix normal I<args><Esc>",
+            ],
+        );
+
+        cx.set_state("ˇone", Mode::Normal);
+        cx.simulate_keystrokes(": shift-t w i c e enter");
+        cx.run_until_parked();
+        cx.assert_state("one\none\nˇone", Mode::Normal);
+
+        cx.simulate_keystrokes(": shift-p r e f i x space - enter");
+        cx.run_until_parked();
+        cx.assert_state("one\none\nˇ-one", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_vimrc_changes(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        set_vimrc(&mut cx, &["command! Dup normal yyp"]);
+        cx.set_state("ˇone", Mode::Normal);
+        cx.simulate_keystrokes(": shift-d u p enter");
+        cx.run_until_parked();
+        cx.assert_state("one\nˇone", Mode::Normal);
+
+        // Commands are parsed again when the setting changes.
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| {
+                s.vimrc = Some(vec!["command! Dup normal dd".to_string()])
+            });
+        });
+        cx.simulate_keystrokes(": shift-d u p enter");
+        cx.run_until_parked();
+        cx.assert_state("ˇone", Mode::Normal);
+
+        // Lines that can't be parsed are shown in a notification until they're fixed.
+        cx.update_global(|store: &mut SettingsStore, cx| {
+            store.update_user_settings::<VimSettings>(cx, |s| {
+                s.vimrc = Some(vec!["set number".to_string()])
+            });
+        });
+        assert!(cx.update(|cx| load_user_mappings(cx)).is_err());
+        cx.run_until_parked();
+        assert_eq!(
+            cx.workspace(|workspace, _| workspace.notification_ids().len()),
+            1
+        );
+
+        set_vimrc(&mut cx, &[]);
+        cx.run_until_parked();
+        assert!(cx.workspace(|workspace, _| workspace.notification_ids().is_empty()));
+    }
+}
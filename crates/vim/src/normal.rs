@@ -183,6 +183,7 @@ impl Vim {
                 self.change_case_motion(motion, times, CaseTarget::OppositeCase, cx)
             }
             Some(Operator::ToggleComments) => self.toggle_comments_motion(motion, times, cx),
+            Some(Operator::ShellCommand) => self.shell_command_motion(motion, times, cx),
             Some(operator) => {
                 // Can't do anything for text objects, Ignoring
                 error!("Unexpected normal mode motion operator: {:?}", operator)
@@ -224,6 +225,7 @@ impl Vim {
                     });
                 }
                 Some(Operator::ToggleComments) => self.toggle_comments_object(object, around, cx),
+                Some(Operator::ShellCommand) => self.shell_command_object(object, around, cx),
                 _ => {
                     // Can't do anything for namespace operators. Ignoring
                 }
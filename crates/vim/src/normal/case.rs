@@ -146,7 +146,18 @@ impl Vim {
                         }
                     }
 
-                    Mode::HelixNormal => {}
+                    Mode::HelixNormal | Mode::HelixSelect => {
+                        let mut end = selection.end;
+                        if selection.is_empty() {
+                            end = snapshot.clip_point(end + Point::new(0, 1), Bias::Right);
+                        }
+                        ranges.push(selection.start..end);
+                        cursor_positions.push(if selection.reversed {
+                            end..selection.start
+                        } else {
+                            selection.start..end
+                        });
+                    }
                     Mode::Insert | Mode::Normal | Mode::Replace => {
                         let start = selection.start;
                         let mut end = start;
@@ -177,7 +188,9 @@ impl Vim {
                 })
             });
         });
-        self.switch_mode(Mode::Normal, true, cx)
+        if !self.mode.is_helix() {
+            self.switch_mode(Mode::Normal, true, cx)
+        }
     }
 }
 
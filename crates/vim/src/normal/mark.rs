@@ -1,20 +1,26 @@
-use std::{ops::Range, sync::Arc};
+use std::{ops::Range, path::Path, sync::Arc};
 
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint,
+    Anchor, Bias, DisplayPoint, Editor,
 };
 use gpui::ViewContext;
-use language::SelectionGoal;
+use language::{Point, SelectionGoal};
 
 use crate::{
     motion::{self, Motion},
-    state::Mode,
+    state::{editor_file, FilePosition, Mode},
     Vim,
 };
 
+/// Uppercase marks remember a file as well as a position, and are shared between editors.
+fn is_global_mark(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_ascii_uppercase()) && chars.next().is_none()
+}
+
 impl Vim {
     pub fn create_mark(&mut self, text: Arc<str>, tail: bool, cx: &mut ViewContext<Self>) {
         let Some(anchors) = self.update_editor(cx, |_, editor, _| {
@@ -27,6 +33,20 @@ impl Vim {
         }) else {
             return;
         };
+        if is_global_mark(&text) {
+            let position = self.update_editor(cx, |_, editor, cx| {
+                let (buffer, path) = editor_file(editor, cx)?;
+                let anchor = anchors.last()?;
+                Some(FilePosition::new(path, &buffer, anchor.text_anchor, cx))
+            });
+            if let Some(position) = position.flatten() {
+                Vim::globals(cx)
+                    .global_marks
+                    .insert(text.to_string(), position);
+                self.clear_operator(cx);
+                return;
+            }
+        }
         self.marks.insert(text.to_string(), anchors);
         self.clear_operator(cx);
     }
@@ -85,6 +105,9 @@ impl Vim {
                     .collect::<Vec<Anchor>>()
             }),
             "." => self.change_list.last().cloned(),
+            _ if is_global_mark(&text) && Vim::globals(cx).global_marks.contains_key(&*text) => {
+                self.global_mark(&text, line, cx)
+            }
             _ => self.marks.get(&*text).cloned(),
         };
 
@@ -124,6 +147,68 @@ impl Vim {
             });
         }
     }
+
+    /// Resolves a global mark in the current editor, opening its file instead
+    /// when the mark belongs to a different one.
+    fn global_mark(
+        &mut self,
+        text: &str,
+        line: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Vec<Anchor>> {
+        let mark = Vim::globals(cx).global_marks.get(text).cloned()?;
+        let point = mark.point(cx);
+        let anchor = self
+            .update_editor(cx, |_, editor, cx| {
+                let (_, path) = editor_file(editor, cx)?;
+                if path != mark.path {
+                    return None;
+                }
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                Some(snapshot.anchor_before(snapshot.clip_point(point, Bias::Left)))
+            })
+            .flatten();
+
+        if anchor.is_none() && self.active_operator().is_none() {
+            self.open_global_mark(mark.path, point, line, cx);
+        }
+        anchor.map(|anchor| vec![anchor])
+    }
+
+    fn open_global_mark(
+        &mut self,
+        path: Arc<Path>,
+        point: Point,
+        line: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let task = workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(path.to_path_buf(), true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let item = task.await?;
+            let Some(editor) = cx.update(|cx| item.act_as::<Editor>(cx))? else {
+                return Ok(());
+            };
+            editor.update(&mut cx, |editor, cx| {
+                let map = editor.snapshot(cx).display_snapshot;
+                let mut point = map
+                    .buffer_snapshot
+                    .clip_point(point, Bias::Left)
+                    .to_display_point(&map);
+                if line {
+                    point = motion::first_non_whitespace(&map, false, point);
+                }
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_display_ranges([point..point])
+                })
+            })
+        })
+        .detach_and_log_err(cx);
+    }
 }
 
 pub fn jump_motion(
@@ -7,8 +7,9 @@ use crate::{
     state::{Mode, Operator, RecordedSelection, ReplayableAction, VimGlobals},
     Vim,
 };
-use editor::Editor;
+use editor::{Editor, ToPoint};
 use gpui::{actions, Action, ViewContext, WindowContext};
+use language::Point;
 use util::ResultExt;
 use workspace::Workspace;
 
@@ -41,7 +42,9 @@ fn repeatable_insert(action: &ReplayableAction) -> Option<Box<dyn Action>> {
                 None
             }
         }
-        ReplayableAction::Insertion { .. } => None,
+        ReplayableAction::Insertion { .. }
+        | ReplayableAction::Keystroke(_)
+        | ReplayableAction::StartOfLine(_) => None,
     }
 }
 
@@ -143,6 +146,26 @@ impl Replayer {
                     })
                     .log_err();
             }
+            ReplayableAction::Keystroke(keystroke) => {
+                cx.dispatch_keystroke(keystroke);
+            }
+            ReplayableAction::StartOfLine(anchor) => {
+                cx.window_handle()
+                    .update(cx, |handle, cx| {
+                        let Ok(workspace) = handle.downcast::<Workspace>() else {
+                            return;
+                        };
+                        let Some(editor) = workspace.read(cx).active_item_as::<Editor>(cx) else {
+                            return;
+                        };
+                        editor.update(cx, |editor, cx| {
+                            let snapshot = editor.buffer().read(cx).snapshot(cx);
+                            let start = Point::new(anchor.to_point(&snapshot).row, 0);
+                            editor.change_selections(None, cx, |s| s.select_ranges([start..start]))
+                        })
+                    })
+                    .log_err();
+            }
         }
         cx.defer(move |cx| self.next(cx));
     }
@@ -3,6 +3,7 @@ use std::{iter::Peekable, str::Chars, time::Duration};
 use editor::Editor;
 use gpui::{actions, impl_actions, ViewContext};
 use language::Point;
+use regex::{Regex, RegexBuilder};
 use search::{buffer_search, BufferSearchBar, SearchOptions};
 use serde_derive::Deserialize;
 use util::serde::default_true;
@@ -152,6 +153,7 @@ impl Vim {
                         prior_selections,
                         prior_operator: self.operator_stack.last().cloned(),
                         prior_mode,
+                        regex_selection: None,
                     }
                 });
             }
@@ -165,6 +167,10 @@ impl Vim {
     }
 
     pub fn search_submit(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(operation) = self.search.regex_selection.take() {
+            self.submit_regex_selection(operation, cx);
+            return;
+        }
         self.store_visual_marks(cx);
         let Some(pane) = self.pane(cx) else { return };
         let result = pane.update(cx, |pane, cx| {
@@ -525,6 +531,32 @@ impl Replacement {
 
         Some(replacement)
     }
+
+    /// Builds the regex to substitute on lines matched by `:g`, which uses the `:g`
+    /// pattern when the substitution doesn't have its own.
+    pub(crate) fn regex(&self, default_search: &str) -> anyhow::Result<Regex> {
+        let search = if self.search.is_empty() {
+            default_search
+        } else {
+            &self.search
+        };
+        Ok(RegexBuilder::new(search)
+            .case_insensitive(!self.is_case_sensitive)
+            .build()?)
+    }
+
+    /// Returns the line with the substitution made, or `None` if the regex doesn't match.
+    pub(crate) fn replace_line(&self, regex: &Regex, line: &str) -> Option<String> {
+        if !regex.is_match(line) {
+            return None;
+        }
+        let replaced = if self.should_replace_all {
+            regex.replace_all(line, self.replacement.as_str())
+        } else {
+            regex.replace(line, self.replacement.as_str())
+        };
+        Some(replaced.into_owned())
+    }
 }
 
 #[cfg(test)]
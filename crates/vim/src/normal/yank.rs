@@ -197,7 +197,8 @@ impl Vim {
         });
 
         let highlight_duration = VimSettings::get_global(cx).highlight_on_yank_duration;
-        if !is_yank || self.mode == Mode::Visual || highlight_duration == 0 {
+        if !is_yank || self.mode == Mode::Visual || self.mode.is_helix() || highlight_duration == 0
+        {
             return;
         }
 
@@ -0,0 +1,584 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use db::sqlez_macros::sql;
+use db::{define_connection, query};
+use editor::NavigationData;
+use gpui::{AppContext, Task};
+use language::Point;
+use util::ResultExt;
+use workspace::{RestoredNavigationEntry, Workspace, WorkspaceDb, WorkspaceId};
+
+use crate::{
+    state::{FilePosition, Register, VimGlobals},
+    Vim,
+};
+
+/// The most recent changes in each file that are remembered across restarts.
+const MAX_PERSISTED_CHANGES: usize = 100;
+
+/// The most recent jumps in each workspace that are remembered across restarts.
+const MAX_PERSISTED_JUMPS: usize = 100;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct SerializedVimState {
+    pub registers: Vec<SerializedRegister>,
+    pub marks: Vec<SerializedMark>,
+    pub change_lists: Vec<SerializedChangeList>,
+    /// The jump list, oldest jump first.
+    pub jumps: Vec<SerializedJump>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedRegister {
+    pub name: char,
+    pub text: String,
+    /// The register's clipboard selections, encoded as JSON.
+    pub clipboard_selections: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedMark {
+    pub name: String,
+    pub path: PathBuf,
+    pub point: Point,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedChangeList {
+    pub path: PathBuf,
+    pub points: Vec<Point>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedJump {
+    pub path: PathBuf,
+    pub point: Point,
+}
+
+define_connection! {
+    // Current schema shape using pseudo-rust syntax:
+    // vim_registers(
+    //   workspace_id: usize,
+    //   name: String,
+    //   contents: String,
+    //   clipboard_selections: Option<String>,
+    // )
+    // vim_marks(
+    //   workspace_id: usize,
+    //   name: String,
+    //   path: PathBuf,
+    //   row: u32,
+    //   column: u32,
+    // )
+    // vim_changes(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   ix: usize,
+    //   row: u32,
+    //   column: u32,
+    // )
+    // vim_jumps(
+    //   workspace_id: usize,
+    //   ix: usize,
+    //   path: PathBuf,
+    //   row: u32,
+    //   column: u32,
+    // )
+    pub static ref DB: VimDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE vim_registers (
+                workspace_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                contents TEXT NOT NULL,
+                clipboard_selections TEXT,
+                PRIMARY KEY(workspace_id, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE vim_marks (
+                workspace_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE vim_changes (
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                ix INTEGER NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, path, ix),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE vim_jumps (
+                workspace_id INTEGER NOT NULL,
+                ix INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, ix),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl VimDb {
+    query! {
+        fn registers(workspace_id: WorkspaceId) -> Result<Vec<(String, String, Option<String>)>> {
+            SELECT name, contents, clipboard_selections
+            FROM vim_registers
+            WHERE workspace_id = ?
+        }
+    }
+
+    query! {
+        fn marks(workspace_id: WorkspaceId) -> Result<Vec<(String, PathBuf, u32, u32)>> {
+            SELECT name, path, row, column
+            FROM vim_marks
+            WHERE workspace_id = ?
+        }
+    }
+
+    query! {
+        fn changes(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, u32)>> {
+            SELECT path, row, column
+            FROM vim_changes
+            WHERE workspace_id = ?
+            ORDER BY path, ix
+        }
+    }
+
+    query! {
+        fn jumps(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, u32)>> {
+            SELECT path, row, column
+            FROM vim_jumps
+            WHERE workspace_id = ?
+            ORDER BY ix
+        }
+    }
+
+    pub(crate) fn load_vim_state(&self, workspace_id: WorkspaceId) -> Result<SerializedVimState> {
+        let registers = self
+            .registers(workspace_id)?
+            .into_iter()
+            .filter_map(|(name, text, clipboard_selections)| {
+                Some(SerializedRegister {
+                    name: name.chars().next()?,
+                    text,
+                    clipboard_selections,
+                })
+            })
+            .collect();
+        let marks = self
+            .marks(workspace_id)?
+            .into_iter()
+            .map(|(name, path, row, column)| SerializedMark {
+                name,
+                path,
+                point: Point::new(row, column),
+            })
+            .collect();
+
+        let mut change_lists: Vec<SerializedChangeList> = Vec::new();
+        for (path, row, column) in self.changes(workspace_id)? {
+            let point = Point::new(row, column);
+            match change_lists.last_mut() {
+                Some(change_list) if change_list.path == path => change_list.points.push(point),
+                _ => change_lists.push(SerializedChangeList {
+                    path,
+                    points: vec![point],
+                }),
+            }
+        }
+
+        let jumps = self
+            .jumps(workspace_id)?
+            .into_iter()
+            .map(|(path, row, column)| SerializedJump {
+                path,
+                point: Point::new(row, column),
+            })
+            .collect();
+
+        Ok(SerializedVimState {
+            registers,
+            marks,
+            change_lists,
+            jumps,
+        })
+    }
+
+    pub(crate) async fn save_vim_state(
+        &self,
+        workspace_id: WorkspaceId,
+        state: SerializedVimState,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_vim_state", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM vim_registers WHERE workspace_id = ?1;
+                    DELETE FROM vim_marks WHERE workspace_id = ?1;
+                    DELETE FROM vim_changes WHERE workspace_id = ?1;
+                    DELETE FROM vim_jumps WHERE workspace_id = ?1;
+                ))?(workspace_id)
+                .context("Clearing old vim state")?;
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO vim_registers(workspace_id, name, contents, clipboard_selections)
+                        VALUES (?, ?, ?, ?)
+                    ))
+                    .context("Preparing register insertion")?;
+                for register in state.registers {
+                    insert((
+                        workspace_id,
+                        register.name.to_string(),
+                        register.text,
+                        register.clipboard_selections,
+                    ))?;
+                }
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO vim_marks(workspace_id, name, path, row, column)
+                        VALUES (?, ?, ?, ?, ?)
+                    ))
+                    .context("Preparing mark insertion")?;
+                for mark in state.marks {
+                    insert((
+                        workspace_id,
+                        mark.name,
+                        mark.path,
+                        mark.point.row,
+                        mark.point.column,
+                    ))?;
+                }
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO vim_changes(workspace_id, path, ix, row, column)
+                        VALUES (?, ?, ?, ?, ?)
+                    ))
+                    .context("Preparing change insertion")?;
+                for change_list in state.change_lists {
+                    for (ix, point) in change_list.points.into_iter().enumerate() {
+                        insert((
+                            workspace_id,
+                            change_list.path.clone(),
+                            ix,
+                            point.row,
+                            point.column,
+                        ))?;
+                    }
+                }
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO vim_jumps(workspace_id, ix, path, row, column)
+                        VALUES (?, ?, ?, ?, ?)
+                    ))
+                    .context("Preparing jump insertion")?;
+                for (ix, jump) in state.jumps.into_iter().enumerate() {
+                    insert((
+                        workspace_id,
+                        ix,
+                        jump.path,
+                        jump.point.row,
+                        jump.point.column,
+                    ))?;
+                }
+
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+pub(crate) fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        // Workspaces that aren't persisted, such as those in tests, leave the saved
+        // state alone.
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        if let Some(state) = DB.load_vim_state(workspace_id).log_err() {
+            restore_vim_state(workspace, state, cx);
+        }
+        cx.on_release(move |workspace, _, cx| save_vim_state(workspace_id, workspace, cx).detach())
+            .detach();
+    })
+    .detach();
+
+    cx.on_app_quit(|cx| {
+        let saves = cx
+            .windows()
+            .into_iter()
+            .filter_map(|window| window.downcast::<Workspace>())
+            .filter_map(|window| {
+                let workspace = window.read(cx).ok()?;
+                Some(save_vim_state(workspace.database_id()?, workspace, cx))
+            })
+            .collect::<Vec<_>>();
+        async move {
+            for save in saves {
+                save.await;
+            }
+        }
+    })
+    .detach();
+}
+
+fn restore_vim_state(workspace: &mut Workspace, state: SerializedVimState, cx: &mut AppContext) {
+    workspace.set_restored_navigation_history(
+        state
+            .jumps
+            .iter()
+            .map(|jump| RestoredNavigationEntry {
+                abs_path: jump.path.clone(),
+                data: Some(Box::new(jump.point)),
+            })
+            .collect(),
+    );
+    Vim::globals(cx).restore(state);
+}
+
+fn save_vim_state(workspace_id: WorkspaceId, workspace: &Workspace, cx: &AppContext) -> Task<()> {
+    if !Vim::enabled(cx) {
+        return Task::ready(());
+    }
+    let state = serialize_vim_state(workspace, cx);
+    cx.background_executor().spawn(async move {
+        DB.save_vim_state(workspace_id, state).await.log_err();
+    })
+}
+
+/// Returns the vim state to save for a workspace: the registers, the marks and
+/// change lists of its files, and the jump list of its active pane.
+fn serialize_vim_state(workspace: &Workspace, cx: &AppContext) -> SerializedVimState {
+    let project = workspace.project().read(cx);
+    let worktree_paths = project
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).abs_path())
+        .collect::<Vec<_>>();
+    let mut state = cx.global::<VimGlobals>().serialize(
+        |path| worktree_paths.iter().any(|root| path.starts_with(root)),
+        cx,
+    );
+
+    // Jumps restored from the previous session that haven't been visited yet come
+    // before the ones made in this session.
+    let mut jumps = workspace
+        .restored_navigation_history()
+        .iter()
+        .filter_map(|entry| {
+            Some(SerializedJump {
+                path: entry.abs_path.clone(),
+                point: *entry.data.as_ref()?.downcast_ref::<Point>()?,
+            })
+        })
+        .collect::<Vec<_>>();
+    workspace
+        .active_pane()
+        .read(cx)
+        .nav_history()
+        .for_each_backward_entry(cx, |entry, (project_path, abs_path)| {
+            let Some(data) = entry
+                .data
+                .as_ref()
+                .and_then(|data| data.downcast_ref::<NavigationData>())
+            else {
+                return;
+            };
+            if let Some(path) = abs_path.or_else(|| project.absolute_path(&project_path, cx)) {
+                jumps.push(SerializedJump {
+                    path,
+                    point: data.cursor_position(),
+                });
+            }
+        });
+    jumps.drain(..jumps.len().saturating_sub(MAX_PERSISTED_JUMPS));
+    state.jumps = jumps;
+    state
+}
+
+fn is_persisted_register(name: char) -> bool {
+    name.is_ascii_lowercase() || name.is_ascii_digit() || matches!(name, '"' | '-')
+}
+
+impl VimGlobals {
+    /// Serializes the registers, along with the marks and change lists of the files
+    /// for which `includes_path` returns true.
+    fn serialize(
+        &self,
+        includes_path: impl Fn(&Path) -> bool,
+        cx: &AppContext,
+    ) -> SerializedVimState {
+        let registers = self
+            .registers
+            .iter()
+            .filter(|(name, _)| is_persisted_register(**name))
+            .map(|(name, register)| SerializedRegister {
+                name: *name,
+                text: register.text.to_string(),
+                clipboard_selections: register
+                    .clipboard_selections
+                    .as_ref()
+                    .and_then(|selections| serde_json::to_string(selections).log_err()),
+            })
+            .collect();
+        let marks = self
+            .global_marks
+            .iter()
+            .filter(|(_, position)| includes_path(&position.path))
+            .map(|(name, position)| SerializedMark {
+                name: name.clone(),
+                path: position.path.to_path_buf(),
+                point: position.point(cx),
+            })
+            .collect();
+        let change_lists = self
+            .change_lists
+            .iter()
+            .filter(|(path, _)| includes_path(path))
+            .map(|(path, positions)| SerializedChangeList {
+                path: path.to_path_buf(),
+                points: positions
+                    .iter()
+                    .rev()
+                    .take(MAX_PERSISTED_CHANGES)
+                    .rev()
+                    .map(|position| position.point(cx))
+                    .collect(),
+            })
+            .collect();
+
+        SerializedVimState {
+            registers,
+            marks,
+            change_lists,
+            jumps: Vec::new(),
+        }
+    }
+
+    /// Restores state saved by a previous session, keeping anything that was
+    /// already recorded in this one.
+    fn restore(&mut self, state: SerializedVimState) {
+        for register in state.registers {
+            self.registers
+                .entry(register.name)
+                .or_insert_with(|| Register {
+                    text: register.text.into(),
+                    clipboard_selections: register
+                        .clipboard_selections
+                        .and_then(|selections| serde_json::from_str(&selections).log_err()),
+                });
+        }
+        for mark in state.marks {
+            self.global_marks
+                .entry(mark.name)
+                .or_insert_with(|| FilePosition::from_point(mark.path.into(), mark.point));
+        }
+        for change_list in state.change_lists {
+            let path: Arc<std::path::Path> = change_list.path.into();
+            self.change_lists.entry(path.clone()).or_insert_with(|| {
+                change_list
+                    .points
+                    .into_iter()
+                    .map(|point| FilePosition::from_point(path.clone(), point))
+                    .collect()
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_save_and_load_vim_state() {
+        let workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+        let other_workspace_id = workspace::WORKSPACE_DB.next_id().await.unwrap();
+
+        let state = SerializedVimState {
+            registers: vec![SerializedRegister {
+                name: 'a',
+                text: "hello\n".to_string(),
+                clipboard_selections: Some(
+                    r#"[{"len":6,"is_entire_line":true,"first_line_indent":0}]"#.to_string(),
+                ),
+            }],
+            marks: vec![SerializedMark {
+                name: "A".to_string(),
+                path: PathBuf::from("/root/dir/file.rs"),
+                point: Point::new(3, 4),
+            }],
+            change_lists: vec![
+                SerializedChangeList {
+                    path: PathBuf::from("/root/a.rs"),
+                    points: vec![Point::new(1, 0), Point::new(5, 2)],
+                },
+                SerializedChangeList {
+                    path: PathBuf::from("/root/b.rs"),
+                    points: vec![Point::new(0, 7)],
+                },
+            ],
+            jumps: vec![
+                SerializedJump {
+                    path: PathBuf::from("/root/b.rs"),
+                    point: Point::new(9, 0),
+                },
+                SerializedJump {
+                    path: PathBuf::from("/root/a.rs"),
+                    point: Point::new(2, 3),
+                },
+            ],
+        };
+
+        DB.save_vim_state(workspace_id, state.clone())
+            .await
+            .unwrap();
+        assert_eq!(DB.load_vim_state(workspace_id).unwrap(), state);
+        // Each workspace has its own state.
+        assert_eq!(
+            DB.load_vim_state(other_workspace_id).unwrap(),
+            SerializedVimState::default()
+        );
+
+        // Saving again replaces the previous state rather than appending to it.
+        let state = SerializedVimState {
+            registers: Vec::new(),
+            marks: Vec::new(),
+            change_lists: vec![SerializedChangeList {
+                path: PathBuf::from("/root/a.rs"),
+                points: vec![Point::new(2, 0)],
+            }],
+            jumps: vec![SerializedJump {
+                path: PathBuf::from("/root/a.rs"),
+                point: Point::new(4, 0),
+            }],
+        };
+        DB.save_vim_state(workspace_id, state.clone())
+            .await
+            .unwrap();
+        assert_eq!(DB.load_vim_state(workspace_id).unwrap(), state);
+    }
+}
@@ -1,7 +1,10 @@
 use std::borrow::BorrowMut;
+use std::path::Path;
 use std::{fmt::Display, ops::Range, sync::Arc};
 
 use crate::command::command_interceptor;
+use crate::helix::RegexSelection;
+use crate::mappings::MappedKeys;
 use crate::normal::repeat::Replayer;
 use crate::surrounds::SurroundsType;
 use crate::{motion::Motion, object::Object};
@@ -10,9 +13,10 @@ use collections::HashMap;
 use command_palette_hooks::{CommandPaletteFilter, CommandPaletteInterceptor};
 use editor::{Anchor, ClipboardSelection, Editor};
 use gpui::{
-    Action, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, Global, View, WeakView,
+    Action, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, Global, Keystroke, Model,
+    View, WeakModel, WeakView,
 };
-use language::Point;
+use language::{Buffer, Point, ToPoint};
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
 use ui::{SharedString, ViewContext};
@@ -27,6 +31,7 @@ pub enum Mode {
     VisualLine,
     VisualBlock,
     HelixNormal,
+    HelixSelect,
 }
 
 impl Display for Mode {
@@ -39,6 +44,7 @@ impl Display for Mode {
             Mode::VisualLine => write!(f, "VISUAL LINE"),
             Mode::VisualBlock => write!(f, "VISUAL BLOCK"),
             Mode::HelixNormal => write!(f, "HELIX NORMAL"),
+            Mode::HelixSelect => write!(f, "HELIX SELECT"),
         }
     }
 }
@@ -48,9 +54,13 @@ impl Mode {
         match self {
             Mode::Normal | Mode::Insert | Mode::Replace => false,
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => true,
-            Mode::HelixNormal => false,
+            Mode::HelixNormal | Mode::HelixSelect => false,
         }
     }
+
+    pub fn is_helix(&self) -> bool {
+        matches!(self, Mode::HelixNormal | Mode::HelixSelect)
+    }
 }
 
 impl Default for Mode {
@@ -86,6 +96,7 @@ pub enum Operator {
     RecordRegister,
     ReplayRegister,
     ToggleComments,
+    ShellCommand,
 }
 
 #[derive(Default, Clone, Debug)]
@@ -147,6 +158,54 @@ impl From<String> for Register {
     }
 }
 
+/// A position in a file that outlives the editors showing it.
+///
+/// While the file's buffer is open the position is tracked with an anchor,
+/// otherwise the last known point is used.
+#[derive(Clone, Debug)]
+pub struct FilePosition {
+    pub path: Arc<Path>,
+    point: Point,
+    anchor: Option<(WeakModel<Buffer>, language::Anchor)>,
+}
+
+impl FilePosition {
+    pub fn new(
+        path: Arc<Path>,
+        buffer: &Model<Buffer>,
+        anchor: language::Anchor,
+        cx: &AppContext,
+    ) -> Self {
+        Self {
+            path,
+            point: anchor.to_point(buffer.read(cx)),
+            anchor: Some((buffer.downgrade(), anchor)),
+        }
+    }
+
+    pub fn from_point(path: Arc<Path>, point: Point) -> Self {
+        Self {
+            path,
+            point,
+            anchor: None,
+        }
+    }
+
+    pub fn point(&self, cx: &AppContext) -> Point {
+        self.anchor
+            .as_ref()
+            .and_then(|(buffer, anchor)| Some(anchor.to_point(buffer.upgrade()?.read(cx))))
+            .unwrap_or(self.point)
+    }
+}
+
+/// Returns the buffer and absolute path of the file shown in a singleton editor.
+pub(crate) fn editor_file(editor: &Editor, cx: &AppContext) -> Option<(Model<Buffer>, Arc<Path>)> {
+    let buffer = editor.buffer().read(cx).as_singleton()?;
+    let path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+    Some((buffer, path.into()))
+}
+
 #[derive(Default, Clone)]
 pub struct VimGlobals {
     pub last_find: Option<Motion>,
@@ -175,7 +234,20 @@ pub struct VimGlobals {
     pub registers: HashMap<char, Register>,
     pub recordings: HashMap<char, Vec<ReplayableAction>>,
 
+    /// Uppercase marks, which unlike other marks are shared between files.
+    pub global_marks: HashMap<String, FilePosition>,
+    /// The change list of each file, kept after its editors close so that
+    /// it can be restored when the file is reopened.
+    pub change_lists: HashMap<Arc<Path>, Vec<FilePosition>>,
+
     pub focused_vim: Option<WeakView<Vim>>,
+
+    /// Keystrokes from user mappings waiting to be dispatched, in reverse order, and
+    /// whether other user mappings apply to them.
+    pub mapped_keystrokes: Vec<(Keystroke, bool)>,
+    pub dispatching_mapped_keys: bool,
+    /// Set while the keys of a non-recursive mapping are dispatched.
+    pub noremap: bool,
 }
 impl Global for VimGlobals {}
 
@@ -348,6 +420,10 @@ impl VimGlobals {
     }
 
     pub fn observe_action(&mut self, action: Box<dyn Action>) {
+        // The keys a mapping replays are recorded as they're dispatched.
+        if action.as_any().is::<MappedKeys>() {
+            return;
+        }
         if self.dot_recording {
             self.recording_actions
                 .push(ReplayableAction::Action(action.boxed_clone()));
@@ -419,6 +495,12 @@ pub enum ReplayableAction {
         text: Arc<str>,
         utf16_range_to_replace: Option<Range<isize>>,
     },
+    /// A keystroke dispatched as if it was typed, used by `:normal`.
+    Keystroke(Keystroke),
+    /// Moves the cursor to the start of the line containing the anchor, used by
+    /// `:normal` and `:global` to visit lines that may have moved since they were
+    /// collected.
+    StartOfLine(Anchor),
 }
 
 impl Clone for ReplayableAction {
@@ -432,6 +514,8 @@ impl Clone for ReplayableAction {
                 text: text.clone(),
                 utf16_range_to_replace: utf16_range_to_replace.clone(),
             },
+            Self::Keystroke(keystroke) => Self::Keystroke(keystroke.clone()),
+            Self::StartOfLine(anchor) => Self::StartOfLine(*anchor),
         }
     }
 }
@@ -445,6 +529,8 @@ pub struct SearchState {
     pub prior_selections: Vec<Range<Anchor>>,
     pub prior_operator: Option<Operator>,
     pub prior_mode: Mode,
+    /// Set when the search bar is prompting for a regex to filter helix selections with.
+    pub regex_selection: Option<RegexSelection>,
 }
 
 impl Operator {
@@ -479,6 +565,7 @@ impl Operator {
             Operator::RecordRegister => "q",
             Operator::ReplayRegister => "@",
             Operator::ToggleComments => "gc",
+            Operator::ShellCommand => "sh",
         }
     }
 
@@ -491,13 +578,16 @@ impl Operator {
                 prefix: Some(prefix),
             } => format!("^V{prefix}"),
             Operator::AutoIndent => "=".to_string(),
+            Operator::ShellCommand => "!".to_string(),
             _ => self.id().to_string(),
         }
     }
 
     pub fn is_waiting(&self, mode: Mode) -> bool {
         match self {
-            Operator::AddSurrounds { target } => target.is_some() || mode.is_visual(),
+            Operator::AddSurrounds { target } => {
+                target.is_some() || mode.is_visual() || mode.is_helix()
+            }
             Operator::FindForward { .. }
             | Operator::Mark
             | Operator::Jump { .. }
@@ -522,7 +612,8 @@ impl Operator {
             | Operator::Object { .. }
             | Operator::ChangeSurrounds { target: None }
             | Operator::OppositeCase
-            | Operator::ToggleComments => false,
+            | Operator::ToggleComments
+            | Operator::ShellCommand => false,
         }
     }
 }
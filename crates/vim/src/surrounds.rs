@@ -52,7 +52,8 @@ impl Vim {
                         newline: false,
                     },
                 };
-                let surround = pair.end != surround_alias((*text).as_ref());
+                // Helix never pads the pair with spaces.
+                let surround = pair.end != surround_alias((*text).as_ref()) && !mode.is_helix();
                 let (display_map, display_selections) = editor.selections.all_adjusted_display(cx);
                 let mut edits = Vec::new();
                 let mut anchors = Vec::new();
@@ -91,6 +92,9 @@ impl Vim {
                                     range
                                 })
                         }
+                        SurroundsType::Selection if mode.is_helix() && selection.is_empty() => {
+                            Some(selection.start..movement::right(&display_map, selection.start))
+                        }
                         SurroundsType::Selection => Some(selection.range()),
                     };
 
@@ -110,7 +114,13 @@ impl Vim {
 
                         edits.push((start..start, start_cursor_str));
                         edits.push((end..end, end_cursor_str));
-                        anchors.push(start_anchor..start_anchor);
+                        if mode.is_helix() {
+                            // Helix keeps the surrounded text selected, pairs included.
+                            let end_anchor = display_map.buffer_snapshot.anchor_after(end);
+                            anchors.push(start_anchor..end_anchor);
+                        } else {
+                            anchors.push(start_anchor..start_anchor);
+                        }
                     } else {
                         let start_anchor = display_map
                             .buffer_snapshot
@@ -130,7 +140,9 @@ impl Vim {
                 });
             });
         });
-        self.switch_mode(Mode::Normal, false, cx);
+        if !mode.is_helix() {
+            self.switch_mode(Mode::Normal, false, cx);
+        }
     }
 
     pub fn delete_surrounds(&mut self, text: Arc<str>, cx: &mut ViewContext<Self>) {
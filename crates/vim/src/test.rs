@@ -9,15 +9,20 @@ use command_palette::CommandPalette;
 use editor::{actions::DeleteLine, display_map::DisplayRow, DisplayPoint};
 use futures::StreamExt;
 use gpui::{KeyBinding, Modifiers, MouseButton, TestAppContext};
+use language::Point;
 pub use neovim_backed_test_context::*;
 use settings::SettingsStore;
 pub use vim_test_context::*;
 
 use indoc::indoc;
 use search::BufferSearchBar;
-use workspace::WorkspaceSettings;
+use workspace::{RestoredNavigationEntry, WorkspaceSettings};
 
-use crate::{insert::NormalBefore, motion, state::Mode};
+use crate::{
+    insert::NormalBefore,
+    motion,
+    state::{Mode, VimGlobals},
+};
 
 #[gpui::test]
 async fn test_initially_disabled(cx: &mut gpui::TestAppContext) {
@@ -1062,6 +1067,52 @@ async fn test_lowercase_marks(cx: &mut TestAppContext) {
         .assert_eq("line one\nˇtwo\nline three");
 }
 
+#[gpui::test]
+async fn test_global_marks(cx: &mut TestAppContext) {
+    let mut cx = VimTestContext::new(cx, true).await;
+
+    cx.set_state("line one\nline ˇtwo\nline three", Mode::Normal);
+    cx.simulate_keystrokes("m shift-a j ' shift-a");
+    cx.assert_state("line one\nˇline two\nline three", Mode::Normal);
+    cx.simulate_keystrokes("j ` shift-a");
+    cx.assert_state("line one\nline ˇtwo\nline three", Mode::Normal);
+
+    // Uppercase marks are kept with their file, and follow edits to it.
+    cx.simulate_keystrokes("g g shift-o escape");
+    cx.update(|cx| {
+        let mark = cx.global::<VimGlobals>().global_marks.get("A").cloned();
+        let mark = mark.expect("mark A was not created");
+        assert!(mark.path.ends_with("dir/file.rs"));
+        assert_eq!(mark.point(cx), Point::new(2, 5));
+    });
+}
+
+#[gpui::test]
+async fn test_go_back_to_restored_jumps(cx: &mut TestAppContext) {
+    let mut cx = VimTestContext::new(cx, true).await;
+
+    cx.set_state("line one\nline two\nline ˇthree", Mode::Normal);
+    cx.workspace(|workspace, cx| {
+        let project_path = workspace.active_item(cx).unwrap().project_path(cx).unwrap();
+        let abs_path = workspace
+            .project()
+            .read(cx)
+            .absolute_path(&project_path, cx)
+            .unwrap();
+        workspace.set_restored_navigation_history(vec![RestoredNavigationEntry {
+            abs_path,
+            data: Some(Box::new(Point::new(1, 5))),
+        }]);
+    });
+
+    // Once the pane's own history is exhausted, going back visits the jumps
+    // restored from the previous session.
+    cx.simulate_keystrokes("ctrl-o");
+    cx.run_until_parked();
+    cx.assert_state("line one\nline ˇtwo\nline three", Mode::Normal);
+    cx.workspace(|workspace, _| assert!(workspace.restored_navigation_history().is_empty()));
+}
+
 #[gpui::test]
 async fn test_lt_gt_marks(cx: &mut TestAppContext) {
     let mut cx = NeovimBackedTestContext::new(cx).await;
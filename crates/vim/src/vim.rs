@@ -9,10 +9,12 @@ mod digraph;
 mod helix;
 mod indent;
 mod insert;
+mod mappings;
 mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
 mod replace;
 mod rewrap;
 mod state;
@@ -31,6 +33,7 @@ use gpui::{
 };
 use insert::{NormalBefore, TemporaryNormal};
 use language::{CursorShape, Point, Selection, SelectionGoal, TransactionId};
+pub use mappings::{load_user_mappings, user_mappings};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
 use normal::search::SearchSubmit;
@@ -99,6 +102,8 @@ pub fn init(cx: &mut AppContext) {
     vim_mode_setting::init(cx);
     VimSettings::register(cx);
     VimGlobals::register(cx);
+    persistence::init(cx);
+    mappings::init(cx);
 
     cx.observe_new_views(|editor: &mut Editor, cx| Vim::register(editor, cx))
         .detach();
@@ -242,10 +247,15 @@ impl Vim {
 
     pub fn new(cx: &mut ViewContext<Editor>) -> View<Self> {
         let editor = cx.view().clone();
+        let mode = if Vim::helix_enabled(cx) {
+            Mode::HelixNormal
+        } else {
+            Mode::Normal
+        };
 
         cx.new_view(|cx| Vim {
-            mode: Mode::Normal,
-            last_mode: Mode::Normal,
+            mode,
+            last_mode: mode,
             temp_mode: false,
             exit_temporary_mode: false,
             operator_stack: Vec::new(),
@@ -314,7 +324,9 @@ impl Vim {
 
         editor.register_addon(VimAddon { view: vim.clone() });
 
-        vim.update(cx, |_, cx| {
+        vim.update(cx, |vim, cx| {
+            vim.restore_change_list(editor, cx);
+
             Vim::action(editor, cx, |vim, action: &SwitchMode, cx| {
                 vim.switch_mode(action.0, false, cx)
             });
@@ -348,6 +360,7 @@ impl Vim {
             visual::register(editor, cx);
             change_list::register(editor, cx);
             digraph::register(editor, cx);
+            mappings::register(editor, cx);
 
             cx.defer(|vim, cx| {
                 vim.focused(false, cx);
@@ -633,9 +646,11 @@ impl Vim {
                 }
             }
             Mode::Replace => CursorShape::Underline,
-            Mode::HelixNormal | Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
-                CursorShape::Block
-            }
+            Mode::HelixNormal
+            | Mode::HelixSelect
+            | Mode::Visual
+            | Mode::VisualLine
+            | Mode::VisualBlock => CursorShape::Block,
             Mode::Insert => CursorShape::Bar,
         }
     }
@@ -651,6 +666,7 @@ impl Vim {
             }
             Mode::Normal
             | Mode::HelixNormal
+            | Mode::HelixSelect
             | Mode::Replace
             | Mode::Visual
             | Mode::VisualLine
@@ -669,7 +685,8 @@ impl Vim {
             | Mode::VisualLine
             | Mode::VisualBlock
             | Mode::Replace
-            | Mode::HelixNormal => false,
+            | Mode::HelixNormal
+            | Mode::HelixSelect => false,
             Mode::Normal => true,
         }
     }
@@ -681,6 +698,7 @@ impl Vim {
             Mode::Insert => "insert",
             Mode::Replace => "replace",
             Mode::HelixNormal => "helix_normal",
+            Mode::HelixSelect => "helix_select",
         }
         .to_string();
 
@@ -709,6 +727,12 @@ impl Vim {
         if mode == "normal" || mode == "visual" || mode == "operator" {
             context.add("VimControl");
         }
+        if self.mode.is_helix() {
+            context.add("VimHelix");
+        }
+        if cx.global::<VimGlobals>().noremap {
+            context.add("VimNoremap");
+        }
         context.set("vim_mode", mode);
         context.set("vim_operator", operator_id);
     }
@@ -1009,7 +1033,7 @@ impl Vim {
                     })
                 });
             }
-            Mode::Insert | Mode::Replace | Mode::HelixNormal => {}
+            Mode::Insert | Mode::Replace | Mode::HelixNormal | Mode::HelixSelect => {}
         }
     }
 
@@ -1085,6 +1109,7 @@ impl Vim {
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
                     self.visual_replace(text, cx)
                 }
+                Mode::HelixNormal | Mode::HelixSelect => self.helix_replace(&text, cx),
                 _ => self.clear_operator(cx),
             },
             Some(Operator::Digraph { first_char }) => {
@@ -1108,14 +1133,18 @@ impl Vim {
                         self.clear_operator(cx);
                     }
                 }
-                Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
+                Mode::Visual
+                | Mode::VisualLine
+                | Mode::VisualBlock
+                | Mode::HelixNormal
+                | Mode::HelixSelect => {
                     self.add_surrounds(text, SurroundsType::Selection, cx);
                     self.clear_operator(cx);
                 }
                 _ => self.clear_operator(cx),
             },
             Some(Operator::ChangeSurrounds { target }) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal | Mode::HelixSelect => {
                     if let Some(target) = target {
                         self.change_surrounds(text, target, cx);
                         self.clear_operator(cx);
@@ -1124,7 +1153,7 @@ impl Vim {
                 _ => self.clear_operator(cx),
             },
             Some(Operator::DeleteSurrounds) => match self.mode {
-                Mode::Normal => {
+                Mode::Normal | Mode::HelixNormal | Mode::HelixSelect => {
                     self.delete_surrounds(text, cx);
                     self.clear_operator(cx);
                 }
@@ -1170,7 +1199,7 @@ impl Vim {
         self.update_editor(cx, |vim, editor, cx| {
             editor.set_cursor_shape(vim.cursor_shape(), cx);
             editor.set_clip_at_line_ends(vim.clip_at_line_ends(), cx);
-            editor.set_collapse_matches(true);
+            editor.set_collapse_matches(!vim.mode.is_helix());
             editor.set_input_enabled(vim.editor_input_enabled());
             editor.set_autoindent(vim.should_autoindent());
             editor.selections.line_mode = matches!(vim.mode, Mode::VisualLine);
@@ -1200,6 +1229,8 @@ struct VimSettings {
     pub use_smartcase_find: bool,
     pub custom_digraphs: HashMap<String, Arc<str>>,
     pub highlight_on_yank_duration: u64,
+    pub vimrc: Vec<String>,
+    pub helix_mode: bool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -1210,6 +1241,8 @@ struct VimSettingsContent {
     pub use_smartcase_find: Option<bool>,
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
     pub highlight_on_yank_duration: Option<u64>,
+    pub vimrc: Option<Vec<String>>,
+    pub helix_mode: Option<bool>,
 }
 
 impl Settings for VimSettings {
@@ -3056,18 +3056,26 @@ impl NavHistory {
             .chain(borrowed_history.backward_stack.iter())
             .chain(borrowed_history.closed_stack.iter())
             .for_each(|entry| {
-                if let Some(project_and_abs_path) =
-                    borrowed_history.paths_by_item.get(&entry.item.id())
-                {
-                    f(entry, project_and_abs_path.clone());
-                } else if let Some(item) = entry.item.upgrade() {
-                    if let Some(path) = item.project_path(cx) {
-                        f(entry, (path, None));
-                    }
+                if let Some(path) = borrowed_history.path_for_entry(entry, cx) {
+                    f(entry, path);
                 }
             })
     }
 
+    /// Calls `f` with each entry that going back visits, oldest first.
+    pub fn for_each_backward_entry(
+        &self,
+        cx: &AppContext,
+        mut f: impl FnMut(&NavigationEntry, (ProjectPath, Option<PathBuf>)),
+    ) {
+        let borrowed_history = self.0.lock();
+        for entry in &borrowed_history.backward_stack {
+            if let Some(path) = borrowed_history.path_for_entry(entry, cx) {
+                f(entry, path);
+            }
+        }
+    }
+
     pub fn set_mode(&mut self, mode: NavigationMode) {
         self.0.lock().mode = mode;
     }
@@ -3180,6 +3188,18 @@ impl NavHistory {
 }
 
 impl NavHistoryState {
+    fn path_for_entry(
+        &self,
+        entry: &NavigationEntry,
+        cx: &AppContext,
+    ) -> Option<(ProjectPath, Option<PathBuf>)> {
+        if let Some(project_and_abs_path) = self.paths_by_item.get(&entry.item.id()) {
+            Some(project_and_abs_path.clone())
+        } else {
+            Some((entry.item.upgrade()?.project_path(cx)?, None))
+        }
+    }
+
     pub fn did_update(&self, cx: &mut WindowContext) {
         if let Some(pane) = self.pane.upgrade() {
             cx.defer(move |cx| {
@@ -2,6 +2,7 @@ use project::TaskSourceKind;
 use remote::ConnectionState;
 use task::{ResolvedTask, TaskContext, TaskTemplate};
 use ui::ViewContext;
+use util::ResultExt;
 
 use crate::Workspace;
 
@@ -26,9 +27,29 @@ pub fn schedule_task(
         }
     }
 
-    if let Some(spawn_in_terminal) =
+    if let Some(mut spawn_in_terminal) =
         task_to_resolve.resolve_task(&task_source_kind.to_id_base(), task_cx)
     {
+        let worktree = match &task_source_kind {
+            TaskSourceKind::Worktree { id, .. } | TaskSourceKind::Detected { id, .. } => Some(*id),
+            _ => None,
+        };
+        if let Some(task_inventory) = workspace
+            .project
+            .read(cx)
+            .task_store()
+            .read(cx)
+            .task_inventory()
+        {
+            if task_inventory
+                .read(cx)
+                .resolve_dependencies(&mut spawn_in_terminal, worktree, task_cx)
+                .log_err()
+                .is_none()
+            {
+                return;
+            }
+        }
         schedule_resolved_task(
             workspace,
             task_source_kind,
@@ -76,7 +76,7 @@ use sqlez::{
 use status_bar::StatusBar;
 pub use status_bar::StatusItemView;
 use std::{
-    any::TypeId,
+    any::{Any, TypeId},
     borrow::Cow,
     cell::RefCell,
     cmp,
@@ -767,6 +767,15 @@ pub struct Workspace {
     serialized_ssh_project: Option<SerializedSshProject>,
     _items_serializer: Task<Result<()>>,
     session_id: Option<String>,
+    restored_navigation_history: Vec<RestoredNavigationEntry>,
+}
+
+/// An entry of the navigation history restored from a previous session, whose item
+/// isn't open. Going back past the start of a pane's history visits these entries,
+/// most recent first, reopening their files.
+pub struct RestoredNavigationEntry {
+    pub abs_path: PathBuf,
+    pub data: Option<Box<dyn Any + Send>>,
 }
 
 impl EventEmitter<Event> for Workspace {}
@@ -1072,6 +1081,7 @@ impl Workspace {
             _items_serializer,
             session_id: Some(session_id),
             serialized_ssh_project: None,
+            restored_navigation_history: Vec::new(),
         }
     }
 
@@ -1359,7 +1369,20 @@ impl Workspace {
                 pane.focus(cx);
                 loop {
                     // Retrieve the weak item handle from the history.
-                    let entry = pane.nav_history_mut().pop(mode, cx)?;
+                    let Some(entry) = pane.nav_history_mut().pop(mode, cx) else {
+                        if !matches!(mode, NavigationMode::GoingBack) {
+                            break None;
+                        }
+                        // Continue through the history restored from the previous session,
+                        // skipping files that are no longer in the project.
+                        let entry = self.restored_navigation_history.pop()?;
+                        if let Some(project_path) =
+                            self.project.read(cx).find_project_path(&entry.abs_path, cx)
+                        {
+                            break Some((project_path, Some(entry.abs_path), entry.data, false));
+                        }
+                        continue;
+                    };
 
                     // If the item is still present in this pane, then activate it.
                     if let Some(index) = entry
@@ -1383,10 +1406,11 @@ impl Workspace {
                     } else {
                         // If the item is no longer present in this pane, then retrieve its
                         // path info in order to reopen it.
-                        break pane
-                            .nav_history()
-                            .path_for_item(entry.item.id())
-                            .map(|(project_path, abs_path)| (project_path, abs_path, entry));
+                        break pane.nav_history().path_for_item(entry.item.id()).map(
+                            |(project_path, abs_path)| {
+                                (project_path, abs_path, entry.data, entry.is_preview)
+                            },
+                        );
                     }
                 }
             })
@@ -1394,7 +1418,7 @@ impl Workspace {
             None
         };
 
-        if let Some((project_path, abs_path, entry)) = to_load {
+        if let Some((project_path, abs_path, data, is_preview)) = to_load {
             // If the item was no longer present, then load it again from its previous path, first try the local path
             let open_by_project_path = self.load_path(project_path.clone(), cx);
 
@@ -1414,14 +1438,14 @@ impl Workspace {
                             let item = pane.open_item(
                                 project_entry_id,
                                 true,
-                                entry.is_preview,
+                                is_preview,
                                 None,
                                 cx,
                                 build_item,
                             );
                             navigated |= Some(item.item_id()) != prev_active_item_id;
                             pane.nav_history_mut().set_mode(NavigationMode::Normal);
-                            if let Some(data) = entry.data {
+                            if let Some(data) = data {
                                 navigated |= item.navigate(data, cx);
                             }
                         })?;
@@ -1445,7 +1469,7 @@ impl Workspace {
                                     pane.update(&mut cx, |pane, cx| {
                                         navigated |= Some(item.item_id()) != prev_active_item_id;
                                         pane.nav_history_mut().set_mode(NavigationMode::Normal);
-                                        if let Some(data) = entry.data {
+                                        if let Some(data) = data {
                                             navigated |= item.navigate(data, cx);
                                         }
                                     })?;
@@ -1473,6 +1497,18 @@ impl Workspace {
         }
     }
 
+    /// The navigation history restored from a previous session that hasn't been
+    /// visited yet, oldest entry first.
+    pub fn restored_navigation_history(&self) -> &[RestoredNavigationEntry] {
+        &self.restored_navigation_history
+    }
+
+    /// Sets the navigation history restored from a previous session, oldest entry
+    /// first, which going back visits once a pane's own history is exhausted.
+    pub fn set_restored_navigation_history(&mut self, entries: Vec<RestoredNavigationEntry>) {
+        self.restored_navigation_history = entries;
+    }
+
     pub fn go_back(
         &mut self,
         pane: WeakView<Pane>,
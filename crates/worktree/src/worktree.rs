@@ -106,6 +106,29 @@ pub struct LoadedFile {
     pub text: String,
 }
 
+/// A file whose size exceeded [`WorktreeSettings::large_file_threshold`], streamed
+/// from disk into an already-normalized rope.
+pub struct LoadedLargeFile {
+    pub file: Arc<File>,
+    pub text: Rope,
+    pub line_ending: LineEnding,
+}
+
+/// Returned by [`Worktree::load_file`] when a file exceeds the large-file threshold.
+/// Such files must be opened with [`Worktree::load_large_file`] instead.
+#[derive(Debug)]
+pub struct FileTooLarge {
+    pub size: u64,
+}
+
+impl fmt::Display for FileTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "file is too large to load in full ({} bytes)", self.size)
+    }
+}
+
+impl std::error::Error for FileTooLarge {}
+
 pub struct LoadedBinaryFile {
     pub file: Arc<File>,
     pub content: Vec<u8>,
@@ -706,6 +729,22 @@ impl Worktree {
         }
     }
 
+    /// Loads a file in large-file mode, reading it in chunks on a background thread and
+    /// reporting the number of bytes read so far through `progress`.
+    pub fn load_large_file(
+        &self,
+        path: &Path,
+        progress: watch::Sender<u64>,
+        cx: &ModelContext<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        match self {
+            Worktree::Local(this) => this.load_large_file(path, progress, cx),
+            Worktree::Remote(_) => {
+                Task::ready(Err(anyhow!("remote worktrees can't yet load files")))
+            }
+        }
+    }
+
     pub fn load_staged_file(&self, path: &Path, cx: &AppContext) -> Task<Result<Option<String>>> {
         match self {
             Worktree::Local(this) => {
@@ -1384,43 +1423,74 @@ impl LocalWorktree {
         let fs = self.fs.clone();
         let entry = self.refresh_entry(path.clone(), None, cx);
         let is_private = self.is_path_private(path.as_ref());
+        let settings = self.settings.clone();
 
         cx.spawn(|this, _cx| async move {
             let abs_path = abs_path?;
+            // The refreshed entry's size saves a separate metadata call. Excluded
+            // files have no entry, and are checked when their metadata is loaded.
+            let entry = entry.await?;
+            let size = match &entry {
+                Some(entry) => Some(entry.size),
+                None => fs.metadata(&abs_path).await?.map(|metadata| metadata.len),
+            };
+            if let Some(size) = size.filter(|size| settings.is_large_file(*size)) {
+                return Err(FileTooLarge { size }.into());
+            }
             let text = fs.load(&abs_path).await?;
 
             let worktree = this
                 .upgrade()
                 .ok_or_else(|| anyhow!("worktree was dropped"))?;
-            let file = match entry.await? {
-                Some(entry) => File::for_entry(entry, worktree),
-                None => {
-                    let metadata = fs
-                        .metadata(&abs_path)
-                        .await
-                        .with_context(|| {
-                            format!("Loading metadata for excluded file {abs_path:?}")
-                        })?
-                        .with_context(|| {
-                            format!("Excluded file {abs_path:?} got removed during loading")
-                        })?;
-                    Arc::new(File {
-                        entry_id: None,
-                        worktree,
-                        path,
-                        disk_state: DiskState::Present {
-                            mtime: metadata.mtime,
-                        },
-                        is_local: true,
-                        is_private,
-                    })
-                }
-            };
+            let file =
+                file_for_loaded_entry(entry, worktree, &fs, &abs_path, path, is_private).await?;
 
             Ok(LoadedFile { file, text })
         })
     }
 
+    fn load_large_file(
+        &self,
+        path: &Path,
+        mut progress: watch::Sender<u64>,
+        cx: &ModelContext<Worktree>,
+    ) -> Task<Result<LoadedLargeFile>> {
+        let path = Arc::from(path);
+        let abs_path = self.absolutize(&path);
+        let fs = self.fs.clone();
+        let entry = self.refresh_entry(path.clone(), None, cx);
+        let is_private = self.is_path_private(path.as_ref());
+        let background = cx.background_executor().clone();
+
+        cx.spawn(|this, _cx| async move {
+            let abs_path = abs_path?;
+            let reader_fs = fs.clone();
+            let reader_path = abs_path.clone();
+            let (text, line_ending) = background
+                .spawn(async move {
+                    let reader = reader_fs.open_sync(&reader_path).await?;
+                    read_normalized_rope(reader, |bytes_read| {
+                        *progress.borrow_mut() = bytes_read;
+                    })
+                })
+                .await
+                .with_context(|| format!("Loading large file {abs_path:?}"))?;
+
+            let worktree = this
+                .upgrade()
+                .ok_or_else(|| anyhow!("worktree was dropped"))?;
+            let file =
+                file_for_loaded_entry(entry.await?, worktree, &fs, &abs_path, path, is_private)
+                    .await?;
+
+            Ok(LoadedLargeFile {
+                file,
+                text,
+                line_ending,
+            })
+        })
+    }
+
     /// Find the lowest path in the worktree's datastructures that is an ancestor
     fn lowest_ancestor(&self, path: &Path) -> PathBuf {
         let mut lowest_ancestor = None;
@@ -1507,7 +1577,6 @@ impl LocalWorktree {
     ) -> Task<Result<Arc<File>>> {
         let path = path.into();
         let fs = self.fs.clone();
-        let is_private = self.is_path_private(&path);
         let Ok(abs_path) = self.absolutize(&path) else {
             return Task::ready(Err(anyhow!("invalid path {path:?}")));
         };
@@ -1520,13 +1589,29 @@ impl LocalWorktree {
 
         cx.spawn(move |this, mut cx| async move {
             write.await?;
-            let entry = this
-                .update(&mut cx, |this, cx| {
-                    this.as_local_mut()
-                        .unwrap()
-                        .refresh_entry(path.clone(), None, cx)
-                })?
-                .await?;
+            this.update(&mut cx, |this, cx| {
+                this.as_local().unwrap().refresh_file(path, cx)
+            })?
+            .await
+        })
+    }
+
+    /// Returns the file at the given path after it has been written, without
+    /// waiting for the file system to report the change.
+    pub fn refresh_file(
+        &self,
+        path: Arc<Path>,
+        cx: &ModelContext<Worktree>,
+    ) -> Task<Result<Arc<File>>> {
+        let fs = self.fs.clone();
+        let is_private = self.is_path_private(&path);
+        let Ok(abs_path) = self.absolutize(&path) else {
+            return Task::ready(Err(anyhow!("invalid path {path:?}")));
+        };
+        let refresh = self.refresh_entry(path.clone(), None, cx);
+
+        cx.spawn(move |this, _| async move {
+            let entry = refresh.await?;
             let worktree = this.upgrade().ok_or_else(|| anyhow!("worktree dropped"))?;
             if let Some(entry) = entry {
                 Ok(File::for_entry(entry, worktree))
@@ -1902,6 +1987,88 @@ impl LocalWorktree {
     }
 }
 
+async fn file_for_loaded_entry(
+    entry: Option<Entry>,
+    worktree: Model<Worktree>,
+    fs: &Arc<dyn Fs>,
+    abs_path: &Path,
+    path: Arc<Path>,
+    is_private: bool,
+) -> Result<Arc<File>> {
+    Ok(match entry {
+        Some(entry) => File::for_entry(entry, worktree),
+        None => {
+            let metadata = fs
+                .metadata(abs_path)
+                .await
+                .with_context(|| format!("Loading metadata for excluded file {abs_path:?}"))?
+                .with_context(|| {
+                    format!("Excluded file {abs_path:?} got removed during loading")
+                })?;
+            Arc::new(File {
+                entry_id: None,
+                worktree,
+                path,
+                disk_state: DiskState::Present {
+                    mtime: metadata.mtime,
+                },
+                is_local: true,
+                is_private,
+            })
+        }
+    })
+}
+
+const LARGE_FILE_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Reads UTF-8 text into a rope one chunk at a time, normalizing line endings as it goes,
+/// so that the file's contents are never held in memory twice.
+fn read_normalized_rope(
+    mut reader: impl std::io::Read,
+    mut on_progress: impl FnMut(u64),
+) -> Result<(Rope, LineEnding)> {
+    let mut rope = Rope::new();
+    let mut line_ending = None;
+    let mut chunk = vec![0; LARGE_FILE_CHUNK_SIZE];
+    let mut pending = Vec::new();
+    let mut bytes_read = 0;
+    loop {
+        let len = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        };
+        bytes_read += len as u64;
+        pending.extend_from_slice(&chunk[..len]);
+
+        // Keep incomplete UTF-8 sequences and a trailing `\r` for the next chunk, so
+        // that characters and `\r\n` pairs split across chunk boundaries survive.
+        let mut valid_len = match std::str::from_utf8(&pending) {
+            Ok(_) => pending.len(),
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(error) => return Err(error.into()),
+        };
+        if pending[..valid_len].ends_with(b"\r") {
+            valid_len -= 1;
+        }
+
+        let mut text = String::from_utf8(pending.drain(..valid_len).collect())?;
+        if line_ending.is_none() && text.contains('\n') {
+            line_ending = Some(LineEnding::detect(&text));
+        }
+        LineEnding::normalize(&mut text);
+        rope.push(&text);
+        on_progress(bytes_read);
+    }
+
+    let mut text = String::from_utf8(pending)?;
+    LineEnding::normalize(&mut text);
+    rope.push(&text);
+
+    Ok((rope, line_ending.unwrap_or_default()))
+}
+
 impl RemoteWorktree {
     pub fn project_id(&self) -> u64 {
         self.project_id
@@ -3360,6 +3527,16 @@ impl language::LocalFile for File {
         cx.background_executor()
             .spawn(async move { fs.load_bytes(&abs_path?).await })
     }
+
+    fn load_rope(&self, cx: &AppContext) -> Task<Result<(Rope, LineEnding)>> {
+        let worktree = self.worktree.read(cx).as_local().unwrap();
+        let abs_path = worktree.absolutize(&self.path);
+        let fs = worktree.fs.clone();
+        cx.background_executor().spawn(async move {
+            let reader = fs.open_sync(&abs_path?).await?;
+            read_normalized_rope(reader, |_| {})
+        })
+    }
 }
 
 impl File {
@@ -7,11 +7,14 @@ use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
 use util::paths::PathMatcher;
 
+const DEFAULT_LARGE_FILE_THRESHOLD: u64 = 64 * 1024 * 1024;
+
 #[derive(Clone, PartialEq, Eq)]
 pub struct WorktreeSettings {
     pub file_scan_inclusions: PathMatcher,
     pub file_scan_exclusions: PathMatcher,
     pub private_files: PathMatcher,
+    pub large_file_threshold: u64,
 }
 
 impl WorktreeSettings {
@@ -29,6 +32,10 @@ impl WorktreeSettings {
         path.ancestors()
             .any(|ancestor| self.file_scan_inclusions.is_match(&ancestor))
     }
+
+    pub fn is_large_file(&self, size: u64) -> bool {
+        size > self.large_file_threshold
+    }
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -61,6 +68,13 @@ pub struct WorktreeSettingsContent {
     /// Treat the files matching these globs as `.env` files.
     /// Default: [ "**/.env*" ]
     pub private_files: Option<Vec<String>>,
+
+    /// Files larger than this many bytes are opened in large-file mode: they are
+    /// loaded progressively and opened read-only, without syntax parsing,
+    /// language servers or git diffs.
+    ///
+    /// Default: 67108864 (64 MiB)
+    pub large_file_threshold: Option<u64>,
 }
 
 impl Settings for WorktreeSettings {
@@ -90,6 +104,9 @@ impl Settings for WorktreeSettings {
         private_files.sort();
         parsed_file_scan_inclusions.sort();
         Ok(Self {
+            large_file_threshold: result
+                .large_file_threshold
+                .unwrap_or(DEFAULT_LARGE_FILE_THRESHOLD),
             file_scan_exclusions: path_matchers(&file_scan_exclusions, "file_scan_exclusions")?,
             private_files: path_matchers(&private_files, "private_files")?,
             file_scan_inclusions: path_matchers(
@@ -1,6 +1,6 @@
 use crate::{
-    worktree_settings::WorktreeSettings, Entry, EntryKind, Event, PathChange, Snapshot, Worktree,
-    WorktreeModelHandle,
+    read_normalized_rope, worktree_settings::WorktreeSettings, Entry, EntryKind, Event, PathChange,
+    Snapshot, Worktree, WorktreeModelHandle,
 };
 use anyhow::Result;
 use fs::{FakeFs, Fs, RealFs, RemoveOptions};
@@ -19,6 +19,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
+use text::LineEnding;
 use util::{test::temp_tree, ResultExt};
 
 #[gpui::test]
@@ -2712,6 +2713,32 @@ async fn test_propagate_git_statuses(cx: &mut TestAppContext) {
     );
 }
 
+#[test]
+fn test_reading_large_files_in_chunks() {
+    // Splits its input into tiny reads, so that multi-byte characters and
+    // `\r\n` pairs straddle chunk boundaries.
+    struct TinyReads<'a>(&'a [u8]);
+
+    impl std::io::Read for TinyReads<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(3);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    let text = "αβγ\r\ndéjà vu\r\n\r\n🦀 end";
+    let mut progress = Vec::new();
+    let (rope, line_ending) =
+        read_normalized_rope(TinyReads(text.as_bytes()), |bytes| progress.push(bytes)).unwrap();
+    assert_eq!(rope.to_string(), "αβγ\ndéjà vu\n\n🦀 end");
+    assert_eq!(line_ending, LineEnding::Windows);
+    assert_eq!(progress.last().copied(), Some(text.len() as u64));
+
+    assert!(read_normalized_rope(TinyReads(b"ok\xff"), |_| {}).is_err());
+}
+
 #[track_caller]
 fn check_propagated_statuses(
     snapshot: &Snapshot,
@@ -413,6 +413,7 @@ fn main() {
             app_state.client.telemetry().clone(),
             cx,
         );
+        spellcheck::init(app_state.fs.clone(), cx);
         extension_host::init(
             extension_host_proxy,
             app_state.fs.clone(),
@@ -430,6 +431,7 @@ fn main() {
         app_state.languages.set_theme(cx.theme().clone());
         editor::init(cx);
         image_viewer::init(cx);
+        hex_editor::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
 
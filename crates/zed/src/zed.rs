@@ -835,13 +835,19 @@ pub fn handle_keymap_file_changes(
     let (keyboard_layout_tx, mut keyboard_layout_rx) = mpsc::unbounded();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
     let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
+    let mut old_vim_mappings = vim::user_mappings(cx);
     cx.observe_global::<SettingsStore>(move |cx| {
         let new_base_keymap = *BaseKeymap::get_global(cx);
         let new_vim_enabled = VimModeSetting::get_global(cx).0;
+        let new_vim_mappings = vim::user_mappings(cx);
 
-        if new_base_keymap != old_base_keymap || new_vim_enabled != old_vim_enabled {
+        if new_base_keymap != old_base_keymap
+            || new_vim_enabled != old_vim_enabled
+            || new_vim_mappings != old_vim_mappings
+        {
             old_base_keymap = new_base_keymap;
             old_vim_enabled = new_vim_enabled;
+            old_vim_mappings = new_vim_mappings;
             base_keymap_tx.unbounded_send(()).unwrap();
         }
     })
@@ -888,6 +894,7 @@ pub fn handle_keymap_file_changes(
 fn reload_keymaps(cx: &mut AppContext, keymap_content: &KeymapFile) {
     cx.clear_key_bindings();
     load_default_keymap(cx);
+    vim::load_user_mappings(cx).log_err();
     keymap_content.clone().add_to_cx(cx).log_err();
     cx.set_menus(app_menus());
     cx.set_dock_menu(vec![MenuItem::action("New Window", workspace::NewWindow)]);
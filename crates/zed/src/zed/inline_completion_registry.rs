@@ -6,6 +6,7 @@ use copilot::{Copilot, CopilotCompletionProvider};
 use editor::{Editor, EditorMode};
 use gpui::{AnyWindowHandle, AppContext, Context, ViewContext, WeakView};
 use language::language_settings::all_language_settings;
+use language_model_inline_completion::LanguageModelCompletionProvider;
 use settings::SettingsStore;
 use supermaven::{Supermaven, SupermavenCompletionProvider};
 
@@ -131,5 +132,12 @@ fn assign_inline_completion_provider(
                 editor.set_inline_completion_provider(Some(provider), cx);
             }
         }
+        language::language_settings::InlineCompletionProvider::LanguageModel => {
+            let project = editor.project().map(|project| project.downgrade());
+            let provider = cx.new_model(|_| {
+                LanguageModelCompletionProvider::new(project).with_telemetry(telemetry.clone())
+            });
+            editor.set_inline_completion_provider(Some(provider), cx);
+        }
     }
 }